@@ -0,0 +1,550 @@
+//! Hourly history of a long range of days as CSV (`weather export`): fetched from the archive a
+//! chunk of days at a time, with the rows written as the chunks arrive, so that a failure halfway
+//! leaves the rows before it and the export continues from there
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufReader},
+    path::Path,
+};
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, SecondsFormat, Timelike};
+
+use crate::{
+    data::{Degradation, UnitSeries, WeatherData},
+    date::DateRange,
+    error::{Result, WeatherError},
+    locale::machine_number,
+};
+
+/// Days fetched by a single request, so that every request stays small and a failed one is
+/// repeated quickly
+pub const CHUNK_DAYS: i64 = 7;
+
+/// Hourly series that can be exported, each of them is a column (two for the wind)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Metric {
+    Temperature,
+    /// Apparent ("feels like") temperature
+    FeelsLike,
+    Humidity,
+    /// Speed and direction
+    Wind,
+    Gusts,
+    Precipitation,
+    Pressure,
+    Clouds,
+}
+
+impl Metric {
+    pub const AVAILABLE_METRICS: [&'static str; 8] = [
+        "temperature",
+        "feels-like",
+        "humidity",
+        "wind",
+        "gusts",
+        "precipitation",
+        "pressure",
+        "clouds",
+    ];
+
+    /// Parse a string into a metric
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: impl AsRef<str>) -> Result<Self> {
+        match s.as_ref() {
+            "temperature" => Ok(Self::Temperature),
+            "feels-like" => Ok(Self::FeelsLike),
+            "humidity" => Ok(Self::Humidity),
+            "wind" => Ok(Self::Wind),
+            "gusts" => Ok(Self::Gusts),
+            "precipitation" => Ok(Self::Precipitation),
+            "pressure" => Ok(Self::Pressure),
+            "clouds" => Ok(Self::Clouds),
+            s => Err(WeatherError::InvalidInput(format!(
+                "Invalid metric {s}, available metrics: [{}]",
+                Self::AVAILABLE_METRICS.join(", ")
+            ))),
+        }
+    }
+
+    /// Names of its columns in the header
+    fn columns(&self) -> &'static [&'static str] {
+        match self {
+            Self::Temperature => &["temperature"],
+            Self::FeelsLike => &["apparent_temperature"],
+            Self::Humidity => &["humidity"],
+            Self::Wind => &["wind_speed", "wind_direction"],
+            Self::Gusts => &["wind_gusts"],
+            Self::Precipitation => &["precipitation"],
+            Self::Pressure => &["pressure"],
+            Self::Clouds => &["cloud_cover"],
+        }
+    }
+
+    /// Its values of the hour, empty where the provider has none
+    fn values(&self, data: &WeatherData, i: usize) -> Vec<String> {
+        let value = |values: &[f64]| {
+            values
+                .get(i)
+                .filter(|value| !value.is_nan())
+                .map(|value| machine_number(*value))
+                .unwrap_or_default()
+        };
+
+        match self {
+            Self::Temperature => vec![value(&data.temperatures)],
+            Self::FeelsLike => vec![value(&data.apparent_temperatures)],
+            Self::Humidity => vec![value(&data.humidity)],
+            Self::Wind => vec![
+                value(&data.wind_speeds),
+                data.wind_directions
+                    .get(i)
+                    .map(|direction| direction.to_string())
+                    .unwrap_or_default(),
+            ],
+            Self::Gusts => vec![value(&data.wind_gusts)],
+            Self::Precipitation => vec![value(&data.precipitation)],
+            Self::Pressure => vec![value(&data.pressures)],
+            Self::Clouds => vec![value(&data.cloud_cover)],
+        }
+    }
+}
+
+/// Header of the CSV with the metrics
+fn header(metrics: &[Metric]) -> Vec<&'static str> {
+    std::iter::once("timestamp")
+        .chain(
+            metrics
+                .iter()
+                .flat_map(|metric| metric.columns().iter().copied()),
+        )
+        .collect()
+}
+
+/// Split the days into the chunks of `days` days (the last one is shorter if they don't divide)
+pub fn chunks(range: DateRange, days: i64) -> Vec<DateRange> {
+    let mut chunks = vec![];
+    let mut start = range.start;
+
+    while start <= range.end {
+        let end = (start + Duration::days(days - 1)).min(range.end);
+        chunks.push(DateRange { start, end });
+        start = end + Duration::days(1);
+    }
+
+    chunks
+}
+
+/// Where an export that was stopped continues
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Resume {
+    /// After the last hour in the file
+    AfterLast,
+    /// From the day (the hours already in the file are still skipped)
+    From(NaiveDate),
+}
+
+/// A chunk that was written
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChunkProgress {
+    /// Its number from 1, of how many
+    pub chunk: usize,
+    pub chunks: usize,
+    pub range: DateRange,
+    pub rows: usize,
+    /// Its series that came in other units than in the chunks before, converted to those
+    pub converted: Vec<Degradation>,
+}
+
+/// Export the hours of the days with the metrics as CSV to the file, fetching the days a chunk at
+/// a time with `fetch` and reporting every written chunk to `progress`. Continuing an export, the
+/// file must have the same columns, and the hours it already has aren't written again. Returns the
+/// number of the rows written, a failed chunk fails with where to continue from
+pub fn export_archive(
+    (range, metrics): (DateRange, &[Metric]),
+    path: &Path,
+    resume: Option<Resume>,
+    mut fetch: impl FnMut(DateRange) -> Result<WeatherData>,
+    mut progress: impl FnMut(ChunkProgress),
+) -> Result<usize> {
+    if range.start > range.end {
+        return Err(WeatherError::InvalidDate(format!(
+            "The export starts on {} after it ends on {}",
+            range.start, range.end
+        )));
+    }
+
+    let header = header(metrics);
+    // A missing file is a new export
+    let existing = match resume.is_some() && path.exists() {
+        true => Some(last_timestamp(path, &header)?),
+        false => None,
+    };
+    let last = existing.flatten();
+
+    let start = match (resume, last) {
+        (Some(Resume::From(date)), _) => date,
+        // The day of the last hour is fetched again if it isn't over (the hours before are
+        // skipped)
+        (Some(Resume::AfterLast), Some(last)) => match last.hour() {
+            23 => last.date_naive() + Duration::days(1),
+            _ => last.date_naive(),
+        },
+        _ => range.start,
+    }
+    .max(range.start);
+
+    let mut writer = match existing {
+        Some(_) => csv::Writer::from_writer(OpenOptions::new().append(true).open(path)?),
+        None => {
+            let mut writer = csv::Writer::from_writer(File::create(path)?);
+            writer.write_record(&header).map_err(csv_error)?;
+            writer.flush()?;
+
+            writer
+        }
+    };
+
+    if start > range.end {
+        return Ok(0);
+    }
+
+    let chunks = chunks(
+        DateRange {
+            start,
+            end: range.end,
+        },
+        CHUNK_DAYS,
+    );
+    let mut written = 0;
+    // The columns have no units, so all the rows are in the units the series first came in
+    let mut units: Vec<(UnitSeries, String)> = vec![];
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let interrupted = |e| WeatherError::ExportInterrupted {
+            resume_from: chunk.start,
+            source: Box::new(e),
+        };
+        let mut data = fetch(*chunk).map_err(interrupted)?;
+        let converted = data.convert_units(&units).map_err(interrupted)?;
+        for (series, unit) in data.units() {
+            if units.iter().all(|(known, _)| *known != series) {
+                units.push((series, unit));
+            }
+        }
+
+        let mut rows = 0;
+        for (hour, timestamp) in data.timestamps.iter().enumerate() {
+            // The chunk before might have ended with the same hours
+            if last.is_some_and(|last| timestamp.time <= last)
+                || !chunk.contains(timestamp.time.date_naive())
+            {
+                continue;
+            }
+
+            let record = std::iter::once(timestamp.time.to_rfc3339_opts(SecondsFormat::Secs, true))
+                .chain(metrics.iter().flat_map(|metric| metric.values(&data, hour)));
+            writer.write_record(record).map_err(csv_error)?;
+            rows += 1;
+        }
+        // Whatever was written stays written if the next chunk fails
+        writer.flush()?;
+
+        written += rows;
+        progress(ChunkProgress {
+            chunk: i + 1,
+            chunks: chunks.len(),
+            range: *chunk,
+            rows,
+            converted,
+        });
+    }
+
+    Ok(written)
+}
+
+/// Check that the file has the columns of the header, and get the time of its last row (`None` if
+/// it has none)
+fn last_timestamp(path: &Path, header: &[&str]) -> Result<Option<DateTime<FixedOffset>>> {
+    let mut reader = csv::Reader::from_reader(BufReader::new(File::open(path)?));
+
+    let columns = reader.headers().map_err(csv_error)?.clone();
+    if columns.iter().ne(header.iter().copied()) {
+        return Err(WeatherError::InvalidInput(format!(
+            "{} has the columns [{}], not the ones of this export [{}]; ask for the same --metrics \
+            or export to another file",
+            path.display(),
+            columns.iter().collect::<Vec<_>>().join(", "),
+            header.join(", ")
+        )));
+    }
+
+    // Only the last row is kept
+    let mut last = None;
+    for record in reader.records() {
+        last = record.map_err(csv_error)?.get(0).map(str::to_string);
+    }
+
+    last.map(|last| {
+        DateTime::parse_from_rfc3339(&last).map_err(|_| {
+            WeatherError::InvalidInput(format!(
+                "The last row of {} has no time to continue after ({last})",
+                path.display()
+            ))
+        })
+    })
+    .transpose()
+}
+
+/// The CSV errors are the ones of the file
+fn csv_error(e: csv::Error) -> WeatherError {
+    WeatherError::Io(io::Error::other(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::Datelike;
+
+    use super::*;
+    use crate::{
+        geocode::ResolvedLocation,
+        providers::{Provider, ProviderRequestType},
+        test_server::TestServer,
+    };
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: application/json";
+    const FAILED: &str = "HTTP/1.1 500 Internal Server Error";
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn january(start: u32, end: u32) -> DateRange {
+        DateRange {
+            start: day(start),
+            end: day(end),
+        }
+    }
+
+    /// open_meteo archive response of the days, the temperature of every hour is its day and hour
+    /// (1.05 is 01:00 on January 5)
+    fn archive_response(range: DateRange) -> &'static str {
+        let days = (range.end - range.start).num_days() + 1;
+        let hours = (0..days * 24)
+            .map(|hour| range.start.and_hms_opt(0, 0, 0).unwrap() + Duration::hours(hour))
+            .collect::<Vec<_>>();
+        let json = serde_json::json!({
+            "utc_offset_seconds": 0,
+            "timezone": "GMT",
+            "hourly_units": { "temperature_2m": "°C", "precipitation": "mm" },
+            "hourly": {
+                "time": hours.iter().map(|hour| hour.format("%Y-%m-%dT%H:%M").to_string()).collect::<Vec<_>>(),
+                "temperature_2m": hours.iter().map(|hour| f64::from(hour.hour()) + f64::from(hour.day()) / 100.0).collect::<Vec<_>>(),
+                "precipitation": hours.iter().map(|_| 0.1).collect::<Vec<_>>(),
+            },
+        });
+
+        // The server answers with the static ones
+        Box::leak(json.to_string().into_boxed_str())
+    }
+
+    /// Fetch the chunk from the server the way the client gets it from the archive, with its days
+    /// in the request
+    fn fetch(server: &TestServer) -> impl FnMut(DateRange) -> Result<WeatherData> + '_ {
+        |range| {
+            let url = server
+                .url
+                .join(&format!(
+                    "archive?start_date={}&end_date={}",
+                    range.start, range.end
+                ))
+                .unwrap();
+            let response = reqwest::blocking::get(url)?;
+            if !response.status().is_success() {
+                return Err(WeatherError::Provider(format!(
+                    "open_meteo answered with {}",
+                    response.status()
+                )));
+            }
+            let json = response.json::<serde_json::Map<String, serde_json::Value>>()?;
+
+            WeatherData::from_json(
+                &json,
+                Provider::OpenMeteo,
+                ProviderRequestType::History,
+                range.start.to_string(),
+                Some(range),
+                ResolvedLocation::default(),
+                (false, Default::default()),
+            )
+        }
+    }
+
+    fn path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("weather-archive-{name}-{}.csv", std::process::id()))
+    }
+
+    fn rows(path: &Path) -> Vec<String> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn chunk_boundaries() {
+        // (range, chunk length) and the chunks
+        type Case = (DateRange, i64, Vec<(u32, u32)>);
+        let cases: [Case; 4] = [
+            (
+                january(1, 31),
+                7,
+                vec![(1, 7), (8, 14), (15, 21), (22, 28), (29, 31)],
+            ),
+            (january(1, 14), 7, vec![(1, 7), (8, 14)]),
+            (january(5, 5), 7, vec![(5, 5)]),
+            (january(1, 3), 1, vec![(1, 1), (2, 2), (3, 3)]),
+        ];
+
+        for (range, days, expected) in cases {
+            let expected = expected
+                .into_iter()
+                .map(|(start, end)| january(start, end))
+                .collect::<Vec<_>>();
+            assert_eq!(chunks(range, days), expected, "{range:?} {days}");
+        }
+    }
+
+    #[test]
+    fn export_in_chunks() {
+        let range = january(1, 10);
+        let server = TestServer::start(vec![
+            (OK, archive_response(january(1, 7))),
+            (OK, archive_response(january(8, 10))),
+        ]);
+        let path = path("chunks");
+        let metrics = [Metric::Temperature, Metric::Wind, Metric::Precipitation];
+
+        let mut progress = vec![];
+        let written = export_archive((range, &metrics), &path, None, fetch(&server), |chunk| {
+            progress.push((chunk.chunk, chunk.chunks, chunk.rows))
+        })
+        .unwrap();
+
+        assert_eq!(written, 240);
+        assert_eq!(progress, [(1, 2, 168), (2, 2, 72)]);
+        // A request per chunk, with its days
+        let requests = server.requests();
+        assert!(requests[0].contains("start_date=2024-01-01&end_date=2024-01-07"));
+        assert!(requests[1].contains("start_date=2024-01-08&end_date=2024-01-10"));
+
+        // The same columns in every row, the missing wind is left empty
+        let rows = rows(&path);
+        assert_eq!(
+            rows[0],
+            "timestamp,temperature,wind_speed,wind_direction,precipitation"
+        );
+        assert_eq!(rows.len(), 241);
+        assert!(rows.iter().all(|row| row.split(',').count() == 5));
+        assert_eq!(rows[1], "2024-01-01T00:00:00Z,0.01,,,0.1");
+        // Right after the boundary
+        assert_eq!(rows[169], "2024-01-08T00:00:00Z,0.08,,,0.1");
+        assert_eq!(rows[240], "2024-01-10T23:00:00Z,23.1,,,0.1");
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn failed_export_resumed() {
+        let range = january(1, 20);
+        let path = path("resume");
+        let metrics = [Metric::Temperature];
+
+        // The second chunk fails, the first one stays written
+        let server = TestServer::start(vec![(OK, archive_response(january(1, 7))), (FAILED, "{}")]);
+        let e = export_archive((range, &metrics), &path, None, fetch(&server), |_| {}).unwrap_err();
+        match &e {
+            WeatherError::ExportInterrupted { resume_from, .. } => {
+                assert_eq!(*resume_from, day(8))
+            }
+            e => panic!("{e}"),
+        }
+        assert!(e.to_string().contains("--resume-from 2024-01-08"), "{e}");
+        assert_eq!(rows(&path).len(), 1 + 7 * 24);
+
+        // Continued after the last hour of the file, the days before aren't fetched again
+        let server = TestServer::start(vec![
+            (OK, archive_response(january(8, 14))),
+            (OK, archive_response(january(15, 20))),
+        ]);
+        let written = export_archive(
+            (range, &metrics),
+            &path,
+            Some(Resume::AfterLast),
+            fetch(&server),
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!(written, 13 * 24);
+        assert!(server.requests()[0].contains("start_date=2024-01-08"));
+
+        // Every hour once, in order, under a single header
+        let rows = rows(&path);
+        assert_eq!(rows.len(), 1 + 20 * 24);
+        assert_eq!(
+            rows.iter()
+                .filter(|row| *row == "timestamp,temperature")
+                .count(),
+            1
+        );
+        assert_eq!(rows[168], "2024-01-07T23:00:00Z,23.07");
+        assert_eq!(rows[169], "2024-01-08T00:00:00Z,0.08");
+
+        // Resuming from a day already in the file doesn't write its hours twice
+        let server = TestServer::start(vec![(OK, archive_response(january(19, 20)))]);
+        let written = export_archive(
+            (range, &metrics),
+            &path,
+            Some(Resume::From(day(19))),
+            fetch(&server),
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!(written, 0);
+        assert_eq!(server.request_count(), 1);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn resume_with_other_columns() {
+        let path = path("columns");
+        std::fs::write(&path, "timestamp,temperature\n2024-01-01T00:00:00Z,0.01\n").unwrap();
+        let server = TestServer::start(vec![(OK, archive_response(january(1, 2)))]);
+
+        let e = export_archive(
+            (january(1, 2), &[Metric::Temperature, Metric::Humidity]),
+            &path,
+            Some(Resume::AfterLast),
+            fetch(&server),
+            |_| {},
+        )
+        .unwrap_err();
+
+        assert!(matches!(e, WeatherError::InvalidInput(_)), "{e}");
+        assert!(
+            e.to_string().contains("timestamp, temperature, humidity"),
+            "{e}"
+        );
+        // Nothing is fetched or written then
+        assert_eq!(server.request_count(), 0);
+        assert_eq!(rows(&path).len(), 2);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,222 @@
+//! Sunrise and sunset calculated from the position of the sun, for the providers that don't return
+//! them (the NOAA approximation, which is within a couple of minutes outside of the polar regions),
+//! the length of the day and the phase of the moon
+
+use std::f64::consts::PI;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+
+/// Angle of the sun below the horizon at the sunrise and the sunset (the refraction and the size of
+/// the sun make it visible a bit before it's geometrically above the horizon)
+const SUNRISE_ZENITH_DEG: f64 = 90.833;
+
+/// Equation of time (in minutes) and declination of the sun (in radians) at noon of the date
+fn solar_position(date: NaiveDate) -> (f64, f64) {
+    // Fractional year (in radians) at noon
+    let gamma = 2.0 * PI / 365.0 * (date.ordinal0() as f64 + 0.5);
+
+    let equation_of_time = 229.18
+        * (0.000075 + 0.001868 * gamma.cos()
+            - 0.032077 * gamma.sin()
+            - 0.014615 * (2.0 * gamma).cos()
+            - 0.040849 * (2.0 * gamma).sin());
+    let declination = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+        - 0.006758 * (2.0 * gamma).cos()
+        + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos()
+        + 0.00148 * (3.0 * gamma).sin();
+
+    (equation_of_time, declination)
+}
+
+/// Cosine of the hour angle of the sunrise. Out of the [-1, 1] range the sun stays below (> 1) or
+/// above (< -1) the horizon all day
+fn cos_sunrise_hour_angle(lat: f64, declination: f64) -> f64 {
+    let lat = lat.to_radians();
+
+    SUNRISE_ZENITH_DEG.to_radians().cos() / (lat.cos() * declination.cos())
+        - lat.tan() * declination.tan()
+}
+
+/// Get the sunrise and the sunset in UTC on the date, or `None` if the sun doesn't rise or doesn't
+/// set that day (polar night or polar day)
+pub(crate) fn sunrise_sunset(
+    lat: f64,
+    lon: f64,
+    date: NaiveDate,
+) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    let (equation_of_time, declination) = solar_position(date);
+    let cos_hour_angle = cos_sunrise_hour_angle(lat, declination);
+
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None;
+    }
+
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+
+    // Minutes since the UTC midnight (4 minutes per degree of the earth's rotation)
+    let minutes = |hour_angle: f64| 720.0 - 4.0 * (lon + hour_angle) - equation_of_time;
+    let time = |minutes: f64| {
+        date.and_hms_opt(0, 0, 0)
+            .map(|midnight| midnight + Duration::seconds((minutes * 60.0).round() as i64))
+    };
+
+    Some((time(minutes(hour_angle))?, time(minutes(-hour_angle))?))
+}
+
+/// Time between the sunrise and the sunset on the date: none during the polar night, and the whole
+/// day during the polar day
+pub(crate) fn day_length(lat: f64, date: NaiveDate) -> Duration {
+    let (_, declination) = solar_position(date);
+    let cos_hour_angle = cos_sunrise_hour_angle(lat, declination);
+
+    match cos_hour_angle {
+        cos if cos > 1.0 => Duration::zero(),
+        cos if cos < -1.0 => Duration::hours(24),
+        // The sun is up for the hour angle before and after the noon, at 4 minutes per degree
+        cos => Duration::seconds((cos.acos().to_degrees() * 8.0 * 60.0).round() as i64),
+    }
+}
+
+/// Angle between the moon and the sun along the ecliptic at the time in UTC, in degrees: 0 at the
+/// new moon, 90 at the first quarter, 180 at the full moon and 270 at the last quarter.
+///
+/// The longitudes are the low precision ones (the mean motion with the largest correction of the
+/// orbit of each), the phases are within a few hours of the exact ones
+pub(crate) fn moon_elongation(time: NaiveDateTime) -> f64 {
+    // Days since the J2000 epoch (2000-01-01 12:00 UTC)
+    let epoch = NaiveDate::from_ymd_opt(2000, 1, 1)
+        .and_then(|date| date.and_hms_opt(12, 0, 0))
+        .unwrap_or_default();
+    let days = (time - epoch).num_seconds() as f64 / 86_400.0;
+
+    // The sun: mean anomaly and the longitude with the equation of the center
+    let sun_anomaly = (357.529 + 0.985_600_28 * days).to_radians();
+    let sun_longitude = 280.459
+        + 0.985_647_36 * days
+        + 1.915 * sun_anomaly.sin()
+        + 0.020 * (2.0 * sun_anomaly).sin();
+
+    // The moon: mean anomaly, and the longitude with the equation of the center and the evection
+    // (the sun pulling the orbit out of shape)
+    let moon_anomaly = (134.963 + 13.064_993 * days).to_radians();
+    let mean_elongation = (297.850 + 12.190_749 * days).to_radians();
+    let moon_longitude = 218.316
+        + 13.176_396 * days
+        + 6.289 * moon_anomaly.sin()
+        + 1.274 * (2.0 * mean_elongation - moon_anomaly).sin()
+        + 0.658 * (2.0 * mean_elongation).sin();
+
+    (moon_longitude - sun_longitude).rem_euclid(360.0)
+}
+
+/// Illuminated part of the moon's disk at the elongation, from 0 (new moon) to 1 (full moon)
+pub(crate) fn moon_illumination(elongation: f64) -> f64 {
+    (1.0 - elongation.to_radians().cos()) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::MoonPhase;
+
+    fn time(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    /// Degrees between the two elongations, whichever way around
+    fn degrees_apart(a: f64, b: f64) -> f64 {
+        let apart = (a - b).rem_euclid(360.0);
+
+        apart.min(360.0 - apart)
+    }
+
+    #[test]
+    fn moon_phases() {
+        // The exact times of the phases in UTC
+        let cases = [
+            ("2023-08-31 01:35", 180.0, MoonPhase::Full),
+            ("2024-01-25 17:54", 180.0, MoonPhase::Full),
+            ("2024-04-23 23:49", 180.0, MoonPhase::Full),
+            ("2024-09-18 02:34", 180.0, MoonPhase::Full),
+            ("2025-03-14 06:55", 180.0, MoonPhase::Full),
+            ("2024-01-11 11:57", 0.0, MoonPhase::New),
+            ("2024-04-08 18:21", 0.0, MoonPhase::New),
+            ("2024-06-14 05:18", 90.0, MoonPhase::FirstQuarter),
+            ("2024-06-28 21:53", 270.0, MoonPhase::LastQuarter),
+        ];
+
+        for (at, expected, phase) in cases {
+            let elongation = moon_elongation(time(at));
+
+            // Within a few hours of the exact time, the moon moves half a degree an hour
+            assert!(
+                degrees_apart(elongation, expected) < 3.0,
+                "{at}: {elongation}"
+            );
+            assert_eq!(MoonPhase::from_elongation(elongation), phase, "{at}");
+        }
+
+        assert!(moon_illumination(moon_elongation(time("2024-01-25 17:54"))) > 0.99);
+        assert!(moon_illumination(moon_elongation(time("2024-04-08 18:21"))) < 0.01);
+        let quarter = moon_illumination(moon_elongation(time("2024-06-14 05:18")));
+        assert!((0.45..0.55).contains(&quarter), "{quarter}");
+    }
+
+    #[test]
+    fn equinox_day_length() {
+        // A few minutes over 12 hours, the refraction lifts the sun over the horizon early
+        for (lat, on) in [
+            (0.0, "2024-03-20"),
+            (0.0, "2024-09-22"),
+            (-0.5, "2024-03-20"),
+        ] {
+            let length = day_length(lat, date(on));
+
+            assert!(
+                (Duration::hours(12)..Duration::minutes(12 * 60 + 10)).contains(&length),
+                "{lat} on {on}: {length}"
+            );
+        }
+
+        // The same at the mid latitudes, where the days are the longest in June
+        let length = day_length(50.45, date("2024-03-20"));
+        assert!(
+            (Duration::hours(12)..Duration::minutes(12 * 60 + 15)).contains(&length),
+            "{length}"
+        );
+        assert!(day_length(50.45, date("2024-06-21")) > Duration::hours(16));
+    }
+
+    #[test]
+    fn polar_days_and_nights() {
+        // Longyearbyen and McMurdo Station
+        let (svalbard, antarctica) = ((78.22, 15.65), (-77.85, 166.67));
+        let (december, june) = (date("2024-12-21"), date("2024-06-21"));
+
+        assert_eq!(day_length(svalbard.0, december), Duration::zero());
+        assert_eq!(sunrise_sunset(svalbard.0, svalbard.1, december), None);
+        assert_eq!(day_length(svalbard.0, june), Duration::hours(24));
+        assert_eq!(sunrise_sunset(svalbard.0, svalbard.1, june), None);
+
+        assert_eq!(day_length(antarctica.0, december), Duration::hours(24));
+        assert_eq!(day_length(antarctica.0, june), Duration::zero());
+    }
+
+    #[test]
+    fn sunrise_and_sunset() {
+        // Kyiv at the summer solstice: 04:47 and 21:13 in the local time (UTC+3)
+        let (sunrise, sunset) = sunrise_sunset(50.45, 30.52, date("2024-06-21")).unwrap();
+
+        for (calculated, expected) in [(sunrise, "2024-06-21 01:47"), (sunset, "2024-06-21 18:13")]
+        {
+            let off = (calculated - time(expected)).num_minutes().abs();
+
+            assert!(off <= 3, "{calculated} instead of {expected}");
+        }
+    }
+}
@@ -0,0 +1,330 @@
+//! Recorded responses for the development without the real APIs: `WEATHER_CASSETTE=record:path.json`
+//! appends every provider and geocoding response to the cassette, `WEATHER_CASSETTE=replay:path.json`
+//! answers the requests from it without opening any sockets (and makes the fixtures of the tests)
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
+
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use reqwest::{
+    blocking::{RequestBuilder, Response},
+    header::{CONTENT_TYPE, EXPIRES, LAST_MODIFIED, RETRY_AFTER},
+    Url,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::NetworkSettings,
+    error::{Result, WeatherError},
+    retry,
+};
+
+/// Variable the cassette is set with, as `record:<path>` or `replay:<path>`
+pub(crate) const CASSETTE_ENV: &str = "WEATHER_CASSETTE";
+
+/// Query parameters with the API keys and the contacts, never written into the cassette
+const SECRET_PARAMS: [&str; 5] = ["appid", "apikey", "api_key", "key", "token"];
+
+/// Headers of the responses the requests look at, the rest of them aren't recorded
+const RECORDED_HEADERS: [reqwest::header::HeaderName; 4] =
+    [CONTENT_TYPE, LAST_MODIFIED, EXPIRES, RETRY_AFTER];
+
+/// How many of the closest recorded URLs a missing request lists
+const NEAR_MISSES: usize = 3;
+
+/// Recordings are appended by the threads of `--compare` at once
+static RECORDING: Mutex<()> = Mutex::new(());
+
+/// A request and the response to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Interaction {
+    /// The URL with the query parameters sorted and the secrets redacted
+    url: String,
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+/// Where the responses come from and go to
+#[derive(Debug, Clone)]
+pub(crate) enum Cassette {
+    /// Send the requests and append them to the file
+    Record(PathBuf),
+    /// Answer the requests from the file
+    Replay(PathBuf),
+}
+
+impl Cassette {
+    /// The cassette set with [`CASSETTE_ENV`], read once per run
+    pub(crate) fn from_env() -> Result<Option<&'static Cassette>> {
+        static CASSETTE: OnceLock<Option<Cassette>> = OnceLock::new();
+
+        if let Some(cassette) = CASSETTE.get() {
+            return Ok(cassette.as_ref());
+        }
+
+        let cassette = std::env::var(CASSETTE_ENV)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| Self::from_str(&value))
+            .transpose()?;
+
+        Ok(CASSETTE.get_or_init(|| cassette).as_ref())
+    }
+
+    /// Parse a string into a cassette
+    #[allow(clippy::should_implement_trait)]
+    pub(crate) fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            Some(("record", path)) if !path.is_empty() => Ok(Self::Record(path.into())),
+            Some(("replay", path)) if !path.is_empty() => Ok(Self::Replay(path.into())),
+            _ => Err(WeatherError::Config(format!(
+                "Invalid {CASSETTE_ENV} {s}, expected record:<path> or replay:<path>"
+            ))),
+        }
+    }
+
+    /// Send the request and record the response, or answer it from the recorded ones
+    pub(crate) fn send(&self, request: RequestBuilder) -> Result<Response> {
+        match self {
+            Self::Record(path) => {
+                let url = request
+                    .try_clone()
+                    .and_then(|request| request.build().ok())
+                    .map(|request| normalized_url(request.url()))
+                    .unwrap_or_default();
+                let response = request.send().map_err(|e| e.without_url())?;
+
+                let interaction = Interaction {
+                    url,
+                    status: response.status().as_u16(),
+                    headers: RECORDED_HEADERS
+                        .iter()
+                        .filter_map(|name| {
+                            let value = response.headers().get(name)?.to_str().ok()?;
+
+                            Some((name.to_string(), value.to_string()))
+                        })
+                        .collect(),
+                    body: response.text()?,
+                };
+                record(path, &interaction)?;
+
+                interaction.response()
+            }
+            Self::Replay(path) => {
+                let url = normalized_url(request.build()?.url());
+                let interactions = interactions(path)?;
+
+                match interactions
+                    .iter()
+                    .find(|interaction| interaction.url == url)
+                {
+                    Some(interaction) => interaction.response(),
+                    None => Err(WeatherError::NotCached(format!(
+                        "{url} isn't in the cassette {}, the closest recorded ones are: [{}]",
+                        path.display(),
+                        near_misses(&url, &interactions).join(", ")
+                    ))),
+                }
+            }
+        }
+    }
+}
+
+/// Send the provider request, through the cassette if there is one and with the retries otherwise
+pub(crate) fn send(
+    settings: &NetworkSettings,
+    now: DateTime<Utc>,
+    request: impl Fn() -> RequestBuilder,
+) -> Result<Response> {
+    match Cassette::from_env()? {
+        Some(cassette) => cassette.send(request()),
+        // The open_weather_map API key is a part of the URL, so make sure it doesn't end up in the
+        // error messages
+        None => Ok(retry::send(settings, now, request).map_err(|e| e.without_url())?),
+    }
+}
+
+impl Interaction {
+    /// The recorded response, as if it came from the network
+    fn response(&self) -> Result<Response> {
+        let response = self
+            .headers
+            .iter()
+            .fold(
+                http::Response::builder().status(self.status),
+                |response, (name, value)| response.header(name, value),
+            )
+            .body(self.body.clone())
+            .map_err(|e| {
+                WeatherError::InvalidResponse(format!("Invalid recorded response: {e}"))
+            })?;
+
+        Ok(response.into())
+    }
+}
+
+/// The URL with its query parameters sorted, so that their order doesn't matter, and the secrets
+/// redacted
+fn normalized_url(url: &Url) -> String {
+    let query = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = match SECRET_PARAMS.contains(&name.as_ref()) {
+                true => "<redacted>".into(),
+                false => value,
+            };
+
+            (name.into_owned(), value.into_owned())
+        })
+        .sorted()
+        .collect_vec();
+
+    let mut url = url.clone();
+    url.set_query(None);
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(query);
+    }
+
+    url.to_string()
+}
+
+/// The recorded interactions, none if nothing is recorded yet
+fn interactions(path: &Path) -> Result<Vec<Interaction>> {
+    match std::fs::read_to_string(path) {
+        Ok(json) => Ok(serde_json::from_str(&json)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Append the interaction to the cassette, a request recorded again replaces the earlier response
+fn record(path: &Path, interaction: &Interaction) -> Result<()> {
+    let _recording = RECORDING.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut interactions = interactions(path)?;
+    interactions.retain(|recorded| recorded.url != interaction.url);
+    interactions.push(interaction.clone());
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&interactions)?)?;
+
+    Ok(())
+}
+
+/// The recorded URLs most like the missing one
+fn near_misses<'a>(url: &str, interactions: &'a [Interaction]) -> Vec<&'a str> {
+    interactions
+        .iter()
+        .map(|interaction| interaction.url.as_str())
+        .sorted_by(|a, b| {
+            strsim::normalized_levenshtein(url, b)
+                .total_cmp(&strsim::normalized_levenshtein(url, a))
+        })
+        .take(NEAR_MISSES)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+
+    use super::*;
+    use crate::{
+        data::WeatherData,
+        geocode::ResolvedLocation,
+        providers::{Provider, ProviderRequestType},
+        test_server::TestServer,
+    };
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nLast-Modified: Fri, 14 Jun 2024 06:00:00 GMT";
+    const RESPONSE: &str = r#"{"utc_offset_seconds":0,"timezone":"GMT","hourly_units":{"temperature_2m":"°C"},"hourly":{"time":["2024-06-14T12:00","2024-06-14T13:00"],"temperature_2m":[18.5,19.0]}}"#;
+
+    fn path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "weather-cassette-{name}-{}.json",
+            std::process::id()
+        ))
+    }
+
+    fn parsed(response: Response) -> WeatherData {
+        WeatherData::from_json(
+            &response.json::<Map<String, Value>>().unwrap(),
+            Provider::OpenMeteo,
+            ProviderRequestType::Forecast,
+            "2024-06-14".to_string(),
+            None,
+            ResolvedLocation::default(),
+            (false, Default::default()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn record_then_replay() {
+        let server = TestServer::start(vec![(OK, RESPONSE)]);
+        let path = path("round-trip");
+        let _ = std::fs::remove_file(&path);
+        let client = reqwest::blocking::Client::new();
+        let url = |query: &str| server.url.join(&format!("forecast?{query}")).unwrap();
+
+        let recorded = Cassette::Record(path.clone())
+            .send(client.get(url("longitude=6.89&latitude=52.22&appid=secret")))
+            .unwrap();
+        assert_eq!(
+            recorded.headers()[LAST_MODIFIED],
+            "Fri, 14 Jun 2024 06:00:00 GMT"
+        );
+        let recorded = parsed(recorded);
+
+        // The secret never makes it to the file
+        let cassette = std::fs::read_to_string(&path).unwrap();
+        assert!(!cassette.contains("secret"), "{cassette}");
+        assert!(cassette.contains("appid=%3Credacted%3E&latitude=52.22&longitude=6.89"));
+
+        // The parameters in another order are the same request, and it's not sent again
+        let replay = Cassette::Replay(path.clone());
+        let replayed = replay
+            .send(client.get(url("latitude=52.22&appid=other&longitude=6.89")))
+            .unwrap();
+        assert_eq!(replayed.status(), 200);
+        assert_eq!(format!("{:?}", parsed(replayed)), format!("{recorded:?}"));
+        assert_eq!(server.request_count(), 1);
+
+        // The missing requests list the recorded ones they are the closest to
+        let e = replay
+            .send(client.get(url("latitude=52.23&longitude=6.89")))
+            .unwrap_err();
+        assert!(matches!(e, WeatherError::NotCached(_)), "{e}");
+        assert!(
+            e.to_string().contains("[http://127.0.0.1:")
+                && e.to_string().contains("latitude=52.22&longitude=6.89]"),
+            "{e}"
+        );
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn cassettes_from_the_env() {
+        // (value, cassette)
+        let cases = [
+            ("record:a.json", Some("Record(\"a.json\")")),
+            ("replay:/tmp/b.json", Some("Replay(\"/tmp/b.json\")")),
+            ("replay:", None),
+            ("a.json", None),
+        ];
+
+        for (value, cassette) in cases {
+            let parsed = Cassette::from_str(value).map(|cassette| format!("{cassette:?}"));
+            assert_eq!(parsed.ok().as_deref(), cassette, "{value}");
+        }
+    }
+}
@@ -0,0 +1,763 @@
+//! Command line interface definition, shared by the argument parsing and the man page generation
+
+use std::path::PathBuf;
+
+use clap::builder::{NonEmptyStringValueParser, PossibleValuesParser};
+use clap::{arg, command};
+
+use chrono::NaiveDate;
+
+use crate::{
+    archive::{Metric, Resume},
+    config,
+    config::{TemperatureUnit, TimeFormat},
+    error::ErrorCategory,
+    last_run::DEFAULT_PRECISION,
+    locale::Language,
+    logging, paths,
+    providers::{Provider, ELEVATION_RANGE},
+    queries::{Condition, NoMatchOutput, NO_MATCH_EXIT_CODE},
+    ui::{parse_export_path, parse_export_size, ChartSeries, OutputMode, Theme},
+    update,
+};
+
+/// Usage examples for the `get` subcommand
+pub(crate) const GET_EXAMPLES: [(&str, &str); 18] = [
+    (
+        "weather get \"Kyiv, Ukraine\"",
+        "Forecast for today with the current conditions",
+    ),
+    (
+        "weather get \"Groningen, Netherlands\" 2023-02-24",
+        "Historical data for a specific date",
+    ),
+    (
+        "weather get Kyiv \"next 3 days\"",
+        "Forecast for today and the next two days",
+    ),
+    (
+        "weather get \"50.45, 30.52\" \"June 5\"",
+        "Forecast for coordinates on the next June 5th",
+    ),
+    (
+        "weather get @home tomorrow",
+        "Forecast for a saved location (see `weather locations add`)",
+    ),
+    (
+        "weather get Kyiv --provider met_no",
+        "Try another provider once, without changing the config",
+    ),
+    (
+        "weather get Kyiv week",
+        "The next 7 days, from the lowest to the highest temperature of each",
+    ),
+    (
+        "weather get Kyiv --compact --hours 8",
+        "One line for a status bar: \"Kyiv 21°C ☀ ▃▄▅▆▇▇▆▅\"",
+    ),
+    (
+        "weather get Kyiv --best-window 3",
+        "The best 3 hours in a row to be outside: \"Best 3h window: 17:00–20:00 — 21°C, 10% rain, UV 3\"",
+    ),
+    (
+        "weather get @garden --next rain --no-match-exit 0 --no-match-output empty",
+        "When it rains next in the next 2 days: \"rain at 15:00 (2 h)\", or nothing and success if it doesn't",
+    ),
+    (
+        "weather get @garden --output ndjson --watch 300 --ndjson-delta",
+        "A line of JSON every 5 minutes for a collector, only with what changed after the first one",
+    ),
+    (
+        "weather get Kyiv --export kyiv.svg",
+        "Save the forecast chart as an SVG image instead of showing it",
+    ),
+    (
+        "weather get Kyiv Warsaw Berlin \"next 3 days\"",
+        "Forecasts for several places, Tab switches between them",
+    ),
+    (
+        "weather get Kyiv --csv kyiv.csv --plain",
+        "Save the hourly data for a spreadsheet and print it as text",
+    ),
+    (
+        "weather get Kyiv --output speech",
+        "Sentences for a screen reader: \"Temperatures rise from 5 degrees Celsius at 6 in the morning to a high of 14 at 4 in the afternoon, then fall to 8 by midnight\"",
+    ),
+    (
+        "weather get @home --days 5 --export-ics weather.ics",
+        "The rain, the alerts, the sunrise and the sunset of the next 5 days as calendar events",
+    ),
+    (
+        "weather get Kyiv --compare",
+        "Forecast from all the providers, one under another",
+    ),
+    (
+        "weather --config-dir /tmp/weather get Kyiv",
+        "Use a different config directory for one run",
+    ),
+];
+
+/// Environment variables the cli reads, with their descriptions
+pub(crate) const ENVIRONMENT_VARIABLES: [(&str, &str); 10] = [
+    (
+        paths::CONFIG_DIR_ENV,
+        "Directory with the config file (the --config-dir flag takes precedence)",
+    ),
+    (
+        paths::CACHE_DIR_ENV,
+        "Directory for cached data and state (the --cache-dir flag takes precedence)",
+    ),
+    (
+        config::PROVIDER_ENV,
+        "Provider to use instead of the one from the config file",
+    ),
+    (
+        update::NO_UPDATE_CHECK_ENV,
+        "Set to anything to turn the daily check for a newer version off (check_updates in the config turns it on)",
+    ),
+    (
+        logging::LOG_ENV,
+        "Log filter (\"weather=debug\", the tracing EnvFilter syntax), takes precedence over -v",
+    ),
+    (
+        "HTTPS_PROXY, HTTP_PROXY, NO_PROXY",
+        "Proxy for the https and the http requests, and the hosts reached without it (network.proxy takes precedence)",
+    ),
+    (
+        "LANG, LC_ALL, LC_MESSAGES, LC_TIME",
+        "Language of the resolved addresses and of the titles and the labels (the language config key takes precedence), and the day/month order of numeric dates",
+    ),
+    (
+        "LC_ALL, LC_CTYPE, LANG",
+        "Charmap of the terminal, the output is ASCII-only if it isn't UTF-8 (the charset config key takes precedence)",
+    ),
+    (
+        "SCREEN_READER, ACCESSIBILITY_ENABLED",
+        "Set to anything but 0 or false to get the data as sentences for the screen readers (--output and --plain take precedence)",
+    ),
+    (
+        "WEATHER_CASSETTE",
+        "record:<path> to save the provider and geocoding responses into the file, replay:<path> to answer the requests from it without the network (for the development)",
+    ),
+];
+
+/// Process exit codes, with their meaning
+pub(crate) const EXIT_CODES: [(u8, &str); 8] = [
+    (0, "Success"),
+    (1, "Any other error"),
+    (
+        2,
+        "Invalid input (arguments, coordinates, dates the provider doesn't have)",
+    ),
+    (
+        3,
+        "Network error (the provider or Nominatim couldn't be reached)",
+    ),
+    (
+        4,
+        "The provider refused the request, is rate-limiting or returned an invalid response",
+    ),
+    (5, "The address couldn't be found"),
+    (
+        6,
+        "Invalid config file or value (including a missing API key)",
+    ),
+    (
+        NO_MATCH_EXIT_CODE,
+        "The --next condition doesn't come (another code can be set with --no-match-exit)",
+    ),
+];
+
+/// Exit code for the category of the error (see `EXIT_CODES`), the errors that are not a
+/// `WeatherError` don't have one
+pub(crate) fn exit_code(category: Option<ErrorCategory>) -> u8 {
+    match category {
+        Some(ErrorCategory::InvalidInput) => 2,
+        Some(ErrorCategory::Network) => 3,
+        Some(ErrorCategory::ProviderResponse) => 4,
+        Some(ErrorCategory::Geocoding) => 5,
+        Some(ErrorCategory::Config) => 6,
+        _ => 1,
+    }
+}
+
+pub(crate) fn build_cli() -> clap::Command {
+    command!()
+        .about("Weather forecasts, historical data and current conditions in your terminal")
+        .long_about(
+            "Weather forecasts, historical data and current conditions in your terminal.\n\n\
+            The data is fetched from one of the free weather APIs (open_meteo, met_no or open_weather_map) for an \
+            address or a \"lat, lon\" pair, and drawn as an hourly temperature chart next to the \
+            current conditions.",
+        )
+        .arg(
+            arg!(-q --quiet)
+                .global(true)
+                .help("Print errors as a single \"error: <category>: <message>\" line, without the backtrace")
+        )
+        .arg(
+            arg!(-v --verbose ...)
+                .global(true)
+                .help("Log the resolved locations, the requests and how long they took to stderr (-vv for the responses too)")
+        )
+        .arg(
+            arg!(--"config-dir" <DIR>)
+                .global(true)
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Directory with the config file (overrides WEATHER_CLI_CONFIG_DIR)")
+        )
+        .arg(
+            arg!(--"cache-dir" <DIR>)
+                .global(true)
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Directory for cached data and state (overrides WEATHER_CLI_CACHE_DIR)")
+        )
+        .arg(
+            arg!(--insecure)
+                .global(true)
+                .help("Don't verify the TLS certificates (DANGEROUS, anyone on the way can read and change the data; set network.ca_bundle instead if you can)")
+        )
+        .subcommand(
+            clap::Command::new("configure")
+                .about("Configure the weather cli (provider, its API key, the temperature unit, the clock and the default location)")
+                .arg(
+                    arg!([provider])
+                        .help("Weather API Provider")
+                        .value_parser(Provider::AVAILABLE_PROVIDERS)
+                )
+                .arg(
+                    arg!(--"api-key" <KEY>)
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .help("API key for the provider (required by open_weather_map)")
+                )
+                .arg(
+                    arg!(--unit <UNIT>)
+                        .help("Unit to show the temperatures in")
+                        .value_parser(TemperatureUnit::AVAILABLE_UNITS)
+                )
+                .arg(
+                    arg!(--"time-format" <FORMAT>)
+                        .help("Clock to show the hours in (the one of the system locale by default)")
+                        .value_parser(TimeFormat::AVAILABLE_FORMATS)
+                )
+                .arg(
+                    arg!(--language <LANGUAGE>)
+                        .help("Language of the titles, the labels and the conditions (the one of the system locale by default)")
+                        .value_parser(Language::AVAILABLE_LANGUAGES)
+                )
+                .arg(
+                    arg!(--"default-location" <ADDRESS>)
+                        .allow_hyphen_values(true)
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .help("Location to use when `weather get` has no address (resolved once and saved, \"lat, lon\" pairs and saved @locations are supported)")
+                )
+                .arg(
+                    arg!(-i --interactive)
+                        .help("Ask for the provider, the unit, the clock and the default location one after another (like on the first run)")
+                        .conflicts_with_all(["provider", "api-key", "unit", "time-format", "language", "default-location"])
+                )
+        )
+        .subcommand(
+            clap::Command::new("get")
+                .about("Get the weather for an address and a date")
+                .long_about(
+                    "Get the weather for an address and a date.\n\n\
+                    Dates in the past are fetched as historical data, the last few days, today and \
+                    future dates as a forecast (with the current conditions when the provider has \
+                    them). The forecast reaches 15 days ahead with open_meteo, 9 with met_no and 2 \
+                    with open_weather_map.",
+                )
+                .after_help(examples_help())
+                .arg(
+                    arg!([address])
+                        .allow_hyphen_values(true)
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .help("Address you want to get weather information from (\"lat, lon\" format and saved @locations are supported, the default location if it's omitted)")
+                )
+                .arg(
+                    arg!([more] ...)
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .help("More addresses, and the date for which you want to get weather information (Check README for more info)")
+                        .long_help(
+                            "More addresses (each of them gets its own page), and the date for \
+                            which you want to get weather information. The last value is the \
+                            date if it is one: \"now\" (the default), or any date understood by \
+                            dateparser. Dates without a year (\"June 5\", \"05.06\") are \
+                            resolved to their next occurrence, and the days can be relative \
+                            to today: \"tomorrow\", \"yesterday\", \"in 3 days\", \"2 days ago\", \
+                            \"friday\" (the next one) or \"last friday\". Ranges of whole days are written \
+                            as \"2024-01-01..2024-01-07\" or \"next 3 days\" (\"last 3 days\", \
+                            \"past week\").\n\n\
+                            Unlike the first address, these can't start with a hyphen (put them \
+                            after -- if they do).",
+                        )
+                )
+                .arg(
+                    arg!(--"from-file" <FILE>)
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Also get the weather for the addresses in the file, one per line (the empty lines and the lines starting with # are skipped)")
+                )
+                .arg(
+                    arg!(--location <NAME>)
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .help("Saved location to use instead of the address (the only positional argument is the date then)")
+                        .conflicts_with("from-file")
+                )
+                .arg(
+                    arg!(--"normalize-lon")
+                        .help("Wrap longitudes outside of [-180, 180] around the globe instead of rejecting them")
+                )
+                .arg(
+                    arg!(--elevation <METERS>)
+                        .allow_negative_numbers(true)
+                        .value_parser(parse_elevation)
+                        .help("Elevation of the location, instead of the one the provider guesses (or the one saved with the location)")
+                )
+                .arg(
+                    arg!(--unit <UNIT>)
+                        .help("Unit to show the temperatures in (overrides the configured one)")
+                        .value_parser(TemperatureUnit::AVAILABLE_UNITS)
+                )
+                .arg(
+                    arg!(--"24h")
+                        .help("Show the hours in the 24-hour clock (\"15:00\" instead of \"03 PM\"), whatever the configured one is")
+                )
+                .arg(
+                    arg!(--precision <DIGITS>)
+                        .help("Decimal places of the values on the chart bars, 0 or 1 (as they are, up to one decimal, by default)")
+                        .value_parser(clap::value_parser!(u8).range(0..=1))
+                )
+                .arg(
+                    arg!(--ascii)
+                        .help("Draw only with the ASCII characters (bars, borders, units and conditions), for the terminals that can't show the rest (the default if the locale isn't UTF-8)")
+                )
+                .arg(
+                    arg!(--lang <LANGUAGE>)
+                        .help("Language of the titles, the labels, the conditions and the addresses of the coordinates (overrides the configured one; the ASCII output is always in English)")
+                        .value_parser(Language::AVAILABLE_LANGUAGES)
+                )
+                .arg(
+                    arg!(--theme <THEME>)
+                        .help("Colors and borders of the TUI and the exports: default, light, monochrome or solarized (overrides the configured preset, the elements the config overrides stay overridden)")
+                        .value_parser(Theme::PRESETS)
+                )
+                .arg(
+                    arg!(--plain)
+                        .visible_alias("no-tui")
+                        .help("Print the data as plain text instead of drawing it (the default when stdout is not a terminal)")
+                )
+                .arg(
+                    arg!(--output <MODE>)
+                        .help("How to show the data: tui, plain (the same as --plain), speech (sentences for the screen readers, without the drawing and with the units and the hours in words; the default when SCREEN_READER or ACCESSIBILITY_ENABLED is set), ndjson (a line of compact JSON with the place, the age of the data, the current conditions, the hourly series with their summary, the alerts and the warnings, one per refresh with --watch) or json (the same JSON indented, with the --best-window, an array with the error of each failed address for several of them)")
+                        .value_parser(OutputMode::AVAILABLE_MODES)
+                        .conflicts_with_all(["plain", "compact"])
+                )
+                .arg(
+                    arg!(--show <SERIES>)
+                        .visible_alias("metric")
+                        .help("Hourly data to show on the chart: temperature, feels-like, wind, pressure, clouds or precipitation (the plain text output has all of it)")
+                        .value_parser(ChartSeries::AVAILABLE_SERIES)
+                        .default_value("temperature")
+                )
+                .arg(
+                    arg!(--daily)
+                        .help("Show the daily minimum and maximum temperatures instead of the hourly ones (best with a range of days)")
+                )
+                .arg(
+                    arg!(--week)
+                        .help("Show the next 7 days, a bar from the lowest to the highest temperature of each with its conditions (the same as \"week\" for the date)")
+                        .conflicts_with_all(["compare", "compact", "show", "from-dump", "compare-yesterday"])
+                )
+                .arg(
+                    arg!(--days <N>)
+                        .help("Show the next N days (the same as \"next N days\" for the date)")
+                        .value_parser(clap::value_parser!(u16).range(1..))
+                        .conflicts_with_all(["week", "from-dump"])
+                )
+                .arg(
+                    arg!(--provider <PROVIDER>)
+                        .help("Provider to use for this run instead of the configured one")
+                        .value_parser(Provider::AVAILABLE_PROVIDERS)
+                )
+                .arg(
+                    arg!(--fallback <PROVIDER>)
+                        .help("Provider to get the weather from when the chosen one can't serve the request (history, dates out of its range, extras it doesn't have or a missing API key), or auto for the first one that can (overrides the configured one)")
+                        .value_parser(PossibleValuesParser::new(
+                            Provider::AVAILABLE_PROVIDERS.into_iter().chain(["auto"]),
+                        ))
+                        .conflicts_with_all(["compare", "from-dump"])
+                )
+                .arg(
+                    arg!(--compare)
+                        .help("Get the weather from all the providers at once and show them one under another")
+                        .conflicts_with_all(["daily", "provider", "from-file"])
+                )
+                .arg(
+                    arg!(--first)
+                        .help("Take the best match if the address matches several places, instead of asking which one (always done when stdin is not a terminal)")
+                )
+                .arg(
+                    arg!(--"no-geocode")
+                        .help("Only accept \"lat, lon\" pairs and don't look up their address (nothing is sent to Nominatim)")
+                        .conflicts_with("first")
+                )
+                .arg(
+                    arg!(--compact)
+                        .help("Print a single line with the current temperature and a sparkline of the next hours (for status bars)")
+                        .conflicts_with_all(["compare", "daily", "plain", "show"])
+                )
+                .arg(
+                    arg!(--hours <N>)
+                        .help("Number of hours in the --compact sparkline")
+                        .value_parser(clap::value_parser!(u16).range(1..))
+                        .default_value("12")
+                )
+                .arg(
+                    arg!(--"display-tz" <ZONE>)
+                        .help("Timezone to show the times in: location (the default), local (the one of this machine) or an IANA name (\"Europe/Kyiv\"). The CSV keeps the time of the location")
+                        .conflicts_with("compact")
+                )
+                .arg(
+                    arg!(--"with-table")
+                        .help("Mark the bars of the chart with a, b, c... and list them under it with their full time and all their values (the plain text and the .txt/.ansi snapshots, the TUI has no room for the list)")
+                        .conflicts_with_all(["compact", "daily", "week"])
+                )
+                .arg(
+                    arg!(--"best-window" <HOURS>)
+                        .help("Find the best hours in a row to be outside (the least rain, 15-25°C, UV up to 7, gusts up to 40 km/h, penalized as the outdoor section of the config says) and show them under the summary")
+                        .value_parser(clap::value_parser!(u16).range(1..))
+                        .conflicts_with_all(["compact", "daily", "week"])
+                )
+                .arg(
+                    arg!(--next <CONDITION>)
+                        .help("Only print when the condition comes next: rain, snow, thunderstorm or frost (in the next 2 days unless a date is given)")
+                        .value_parser(Condition::AVAILABLE_CONDITIONS)
+                        .conflicts_with_all(["compare", "compact", "csv", "export", "export-ics", "daily", "week"])
+                )
+                .arg(
+                    arg!(--"no-match-exit" <CODE>)
+                        .help(format!("Exit code when the --next condition doesn't come ({NO_MATCH_EXIT_CODE} by default, 0 for the scripts that only go by the output)"))
+                        .value_parser(clap::value_parser!(u8))
+                        .requires("next")
+                )
+                .arg(
+                    arg!(--"no-match-output" <OUTPUT>)
+                        .help("What to print when the --next condition doesn't come: empty (nothing), message or json-null ({\"match\": null}, and the match as JSON when it comes)")
+                        .value_parser(NoMatchOutput::AVAILABLE_OUTPUTS)
+                        .default_value("message")
+                        .requires("next")
+                )
+                .arg(
+                    arg!(--watch <SECONDS>)
+                        .help("Get the data again every N seconds until interrupted (only with --output ndjson for now, the errors are lines of the stream and it goes on)")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .conflicts_with_all(["compare", "compact", "csv", "export", "export-ics", "next", "changed-only"])
+                )
+                .arg(
+                    arg!(--"ndjson-delta")
+                        .help("With --watch, only write the fields that changed since the last refresh, with the fingerprint of it and a summary of the changes")
+                        .requires("watch")
+                )
+                .arg(
+                    arg!(--publish <DEST>)
+                        .help("Also send the JSON of the data (the line of --output ndjson) after showing it: file:<dir> writes a file into the directory (keeping the last publish.keep of them), an http(s):// URL gets it POSTed with the publish.authorization header of the config. A failure is only reported")
+                        .conflicts_with_all(["compare", "compact", "csv", "export", "next", "watch"])
+                )
+                .arg(
+                    arg!(--"publish-required")
+                        .help("Fail the run when the data couldn't be published")
+                        .requires("publish")
+                )
+                .arg(
+                    arg!(--"changed-only")
+                        .help("Print nothing (and succeed) when the forecast is the same as on the last run for the location, provider and date, and the changes before the data when it isn't (\"max temp 19→22, rain window added 15:00–16:00\")")
+                        .conflicts_with_all(["compare", "compact"])
+                )
+                .arg(
+                    arg!(--"change-precision" <STEP>)
+                        .help(format!("Step the temperatures are rounded to before they are compared with --changed-only ({DEFAULT_PRECISION} by default), the smaller changes don't count"))
+                        .value_parser(parse_precision)
+                        .requires("changed-only")
+                )
+                .arg(
+                    arg!(--export <PATH>)
+                        .help("Write a snapshot of the data to the file instead of showing it: the TUI layout for .txt (.ansi keeps the colors), a bar chart for .svg")
+                        .value_parser(parse_export_path)
+                        .conflicts_with_all(["compare", "compact", "from-file"])
+                )
+                .arg(
+                    arg!(--csv [PATH])
+                        .help("Write the hourly data as CSV to the file (printed if there is no path) for the spreadsheets, the data is still shown after writing the file (--plain for the text instead of the TUI)")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .conflicts_with_all(["compare", "compact", "from-file"])
+                )
+                .arg(
+                    arg!(--"export-ics" <PATH>)
+                        .help("Write the rain (the hours in a row it rains), the alerts, the sunrise and the sunset as the events of an iCalendar file, importing it again updates the events instead of adding them twice (the data is still shown after writing the file)")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .conflicts_with_all(["compare", "compact", "from-file"])
+                )
+                .arg(
+                    arg!(--"ics-days")
+                        .help("Also add an all-day event per day with its lowest and highest temperature to the --export-ics calendar")
+                        .requires("export-ics")
+                )
+                .arg(
+                    arg!(--"export-size" <SIZE>)
+                        .help("Size of the .txt/.ansi snapshot in characters (<width>x<height>)")
+                        .value_parser(parse_export_size)
+                        .default_value("120x40")
+                )
+                .arg(
+                    arg!(--interactive)
+                        .help("Still show the data after writing the --export snapshot (the TUI, or the plain text)")
+                        .requires("export")
+                )
+                .arg(
+                    arg!(--"dump-response" <FILE>)
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Write the raw provider response to the file (with the provider, the request URL and the time) before it's parsed, to report the responses that break the parsing")
+                        .conflicts_with_all(["compare", "from-file", "more"])
+                )
+                .arg(
+                    arg!(--"from-dump" <FILE>)
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Show the response written with --dump-response instead of fetching anything (the provider, the location and the date are the ones in the file)")
+                        .conflicts_with_all(["address", "location", "from-file", "provider", "daily", "elevation", "compare", "cached", "dump-response"])
+                )
+                .arg(
+                    arg!(--"air-quality")
+                        .help("Also show the air quality of the day (the European Air Quality Index, only open_meteo has it)")
+                        .conflicts_with("from-dump")
+                )
+                .arg(
+                    arg!(--nowcast)
+                        .help("Also show the precipitation of the next 90 minutes (met_no's radar nowcast, only in the Nordic countries, or open_meteo's 15 minute forecast; only for now)")
+                        .conflicts_with("from-dump")
+                )
+                .arg(
+                    arg!(--astro)
+                        .help("Also show the moon phase and the length of the day (calculated for the location, with any provider)")
+                )
+                .arg(
+                    arg!(--"compare-yesterday")
+                        .help("Show the day before the requested one on the chart too, as a tick on every bar (only open_meteo can)")
+                        .conflicts_with_all(["compare", "daily", "from-dump"])
+                )
+                .arg(
+                    arg!(--cached)
+                        .help("Show the data fetched the last time instead of fetching it (used automatically when the provider can't be reached)")
+                )
+                .arg(
+                    arg!(--past)
+                        .help("Resolve dates without a year (\"June 5\") to their last occurrence instead of the next one")
+                )
+        )
+        .subcommand(
+            clap::Command::new("export")
+                .about("Export the hourly history of a range of days as CSV")
+                .long_about(
+                    "Export the hourly history of a range of days as CSV.\n\n\
+                    The days are fetched a week at a time, and the rows are written as they \
+                    arrive with a progress line per week. If a week fails, the rows before it stay \
+                    in the file and the error says where to continue from with --resume-from.",
+                )
+                .arg(
+                    arg!([address])
+                        .allow_hyphen_values(true)
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .help("Address to export the weather of (\"lat, lon\" format and saved @locations are supported, the default location if it's omitted)")
+                )
+                .arg(
+                    arg!(--from <DATE>)
+                        .required(true)
+                        .value_parser(parse_day)
+                        .help("First day of the export (YYYY-MM-DD)")
+                )
+                .arg(
+                    arg!(--to <DATE>)
+                        .required(true)
+                        .value_parser(parse_day)
+                        .help("Last day of the export, included (YYYY-MM-DD)")
+                )
+                .arg(
+                    arg!(--out <PATH>)
+                        .required(true)
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("CSV file to write the rows to (replaced unless the export is resumed)")
+                )
+                .arg(
+                    arg!(--metrics <METRICS>)
+                        .value_delimiter(',')
+                        .value_parser(Metric::AVAILABLE_METRICS)
+                        .default_value("temperature")
+                        .help("Columns to export after the timestamp, separated by commas")
+                )
+                .arg(
+                    arg!(--"resume-from" [DATE])
+                        .value_parser(parse_resume)
+                        .default_missing_value("last")
+                        .help("Continue an export into the existing file from the day (after its last row without a day), with the same --metrics")
+                )
+                .arg(
+                    arg!(--provider <PROVIDER>)
+                        .help("Provider to use for this run instead of the configured one")
+                        .value_parser(Provider::AVAILABLE_PROVIDERS)
+                )
+                .arg(
+                    arg!(--unit <UNIT>)
+                        .help("Unit to export the temperatures in (overrides the configured one)")
+                        .value_parser(TemperatureUnit::AVAILABLE_UNITS)
+                )
+        )
+        .subcommand(
+            clap::Command::new("locations")
+                .about("Manage the saved locations (used as `weather get @name`)")
+                .subcommand_required(true)
+                .subcommand(
+                    clap::Command::new("add")
+                        .about("Resolve the address and save it under the name")
+                        .arg(
+                            arg!(<name>)
+                                .value_parser(NonEmptyStringValueParser::new())
+                                .help("Name of the location")
+                        )
+                        .arg(
+                            arg!(<address>)
+                                .allow_hyphen_values(true)
+                                .value_parser(NonEmptyStringValueParser::new())
+                                .help("Address or \"lat, lon\" pair of the location")
+                        )
+                        .arg(
+                            arg!(--elevation <METERS>)
+                                .allow_negative_numbers(true)
+                                .value_parser(parse_elevation)
+                                .help("Elevation of the location, sent to the providers that take it (open_meteo and met_no)")
+                        )
+                        .arg(
+                            arg!(--force)
+                                .help("Replace the location if the name is already taken")
+                        )
+                        .arg(
+                            arg!(--first)
+                                .help("Take the best match if the address matches several places, instead of asking which one")
+                        )
+                        .arg(
+                            arg!(--"no-geocode")
+                                .help("Only accept \"lat, lon\" pairs and don't look up their address (nothing is sent to Nominatim)")
+                                .conflicts_with("first")
+                        )
+                )
+                .subcommand(
+                    clap::Command::new("list")
+                        .about("List the saved locations")
+                )
+                .subcommand(
+                    clap::Command::new("remove")
+                        .about("Remove the saved location")
+                        .arg(
+                            arg!(<name>)
+                                .value_parser(NonEmptyStringValueParser::new())
+                                .help("Name of the location")
+                        )
+                )
+        )
+        .subcommand(
+            clap::Command::new("config")
+                .about("Inspect the configuration")
+                .subcommand_required(true)
+                .subcommand(
+                    clap::Command::new("show")
+                        .about("Show the configuration in effect (with the environment overrides) and where it's read from")
+                )
+        )
+        .subcommand(
+            clap::Command::new("providers")
+                .about("List the providers and what each of them can do")
+                .arg(
+                    arg!(--json)
+                        .help("Print the list as JSON")
+                )
+        )
+        .subcommand(
+            clap::Command::new("doctor")
+                .about("Show where the weather cli keeps its files")
+        )
+        .subcommand(
+            clap::Command::new("cache")
+                .about("Manage the cached data")
+                .subcommand_required(true)
+                .subcommand(
+                    clap::Command::new("clear")
+                        .about("Remove the cached geocoding results")
+                        .arg(
+                            arg!(--all)
+                                .help("Remove the cached provider responses as well (the data shown offline)")
+                        )
+                )
+        )
+        .subcommand(
+            clap::Command::new("update")
+                .about("Check GitHub for a newer version of the weather cli")
+                .arg(
+                    arg!(--install)
+                        .help("Download the newer version for this platform, verify its checksum and replace this executable with it")
+                )
+        )
+        .subcommand(
+            clap::Command::new("man")
+                .about("Print the man page (roff format) to stdout")
+        )
+}
+
+/// Parse the elevation in meters, in the range the providers accept
+fn parse_elevation(s: &str) -> Result<f64, String> {
+    let elevation = s
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| format!("Invalid elevation '{s}', expected a number of meters like 1450"))?;
+
+    match ELEVATION_RANGE.contains(&elevation) {
+        true => Ok(elevation),
+        false => Err(format!(
+            "The elevation {elevation} m is out of range, it should be between {} and {} m",
+            ELEVATION_RANGE.start(),
+            ELEVATION_RANGE.end()
+        )),
+    }
+}
+
+/// Parse the step of --change-precision, a positive number of degrees
+fn parse_precision(s: &str) -> Result<f64, String> {
+    match s.trim().parse::<f64>() {
+        Ok(step) if step > 0.0 && step.is_finite() => Ok(step),
+        _ => Err(format!(
+            "Invalid precision '{s}', expected a positive number of degrees like 0.5"
+        )),
+    }
+}
+
+/// Parse a day of an export, only the exact ones to not guess the range
+fn parse_day(s: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid day '{s}', expected YYYY-MM-DD like 2024-01-31"))
+}
+
+/// Parse where to resume an export from, "last" (no day) is after the last row of the file
+fn parse_resume(s: &str) -> Result<Resume, String> {
+    match s {
+        "last" => Ok(Resume::AfterLast),
+        s => parse_day(s).map(Resume::From),
+    }
+}
+
+/// The examples formatted for the `--help` output
+fn examples_help() -> String {
+    let examples = GET_EXAMPLES
+        .iter()
+        .map(|(command, description)| format!("  # {description}\n  {command}"))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    format!("Examples:\n{examples}")
+}
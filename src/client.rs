@@ -0,0 +1,364 @@
+//! Entry point of the library: the provider and the request options are chosen once, without
+//! touching the config file, and then used for every request
+
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+
+use crate::{
+    config::{GeocoderBackend, NetworkSettings, TemperatureUnit, TimeFormat},
+    data::{CurrentSnapshot, WeatherData},
+    error::Result,
+    geocode::{Geocoder, ResolvedLocation},
+    http,
+    locale::Language,
+    paths::Paths,
+    providers::{resolve_location, Fallback, Location, Provider, RequestOptions},
+    response_dump::ResponseDump,
+};
+
+/// Client for one of the weather providers, created with [`WeatherClient::builder`]
+#[derive(Debug, Clone)]
+pub struct WeatherClient {
+    provider: Provider,
+    options: RequestOptions,
+    /// The time of the run, if it's fixed
+    now: Option<DateTime<Utc>>,
+}
+
+impl WeatherClient {
+    pub fn builder() -> WeatherClientBuilder {
+        WeatherClientBuilder::default()
+    }
+
+    pub fn provider(&self) -> Provider {
+        self.provider
+    }
+
+    /// The time the requests are relative to: the one the replayed response was received at, the
+    /// time of the run, or the current one
+    pub fn now(&self) -> DateTime<Utc> {
+        self.request_options().now
+    }
+
+    /// Get the weather for the address (or a "lat, lon" pair) and the date ("now", a date or a
+    /// range of days like "next 3 days")
+    pub fn get(&self, address: &str, date: &str) -> Result<WeatherData> {
+        self.get_location(Location::Address(address.to_string()), date)
+    }
+
+    /// Get the weather for the location, which is only geocoded if it's an address
+    pub fn get_location(&self, location: Location, date: &str) -> Result<WeatherData> {
+        self.provider
+            .get(location, date.to_string(), &self.request_options())
+    }
+
+    /// Get the current conditions and the temperatures of the next `hours` hours at the location,
+    /// for the one line outputs. The coordinates (and the resolved locations) skip the geocoding,
+    /// and the providers that have a lighter request for them skip the full response too
+    pub fn get_current(&self, location: Location, hours: usize) -> Result<CurrentSnapshot> {
+        self.provider
+            .get_current(location, hours, &self.request_options())
+    }
+
+    /// Get the weather for the location from all the clients at once (each of them on its own
+    /// thread). The results are in the order of the clients, and a failure of one of them doesn't
+    /// affect the others.
+    ///
+    /// The location is better resolved beforehand, otherwise every client geocodes it separately
+    pub fn get_all(
+        clients: &[WeatherClient],
+        location: &Location,
+        date: &str,
+    ) -> Vec<Result<WeatherData>> {
+        std::thread::scope(|scope| {
+            let handles = clients
+                .iter()
+                .map(|client| scope.spawn(|| client.get_location(location.clone(), date)))
+                .collect_vec();
+
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        })
+    }
+
+    /// Check that the date ("now", a date or a range of days) parses, before any of the addresses
+    /// it's for are geocoded
+    pub fn check_date(&self, date: &str) -> Result<()> {
+        self.provider.check_date(date, &self.request_options())
+    }
+
+    /// Resolve the address (or a "lat, lon" pair) into the coordinates and the name to show
+    pub fn resolve(&self, address: &str) -> Result<ResolvedLocation> {
+        resolve_location(address, &self.request_options())
+    }
+
+    /// Every request is relative to the time of the run if it's fixed (to when the replayed response
+    /// was received, if there is one), and to the moment it's made otherwise
+    fn request_options(&self) -> RequestOptions {
+        RequestOptions {
+            now: self
+                .options
+                .replay
+                .as_ref()
+                .map(|replay| replay.time)
+                .or(self.now)
+                .unwrap_or_else(Utc::now),
+            ..self.options.clone()
+        }
+    }
+}
+
+/// Options of the [`WeatherClient`], everything is optional (open_meteo in °C by default)
+#[derive(Debug, Default, Clone)]
+pub struct WeatherClientBuilder {
+    provider: Provider,
+    api_key: Option<String>,
+    unit: TemperatureUnit,
+    time_format: TimeFormat,
+    paths: Option<Paths>,
+    network: NetworkSettings,
+    normalize_lon: bool,
+    past: bool,
+    daily: bool,
+    cached: bool,
+    choose_place: bool,
+    geocoder: Option<Arc<dyn Geocoder>>,
+    geocoder_backend: GeocoderBackend,
+    no_geocode: bool,
+    language: Option<Language>,
+    elevation: Option<f64>,
+    dump_response: Option<PathBuf>,
+    replay: Option<Arc<ResponseDump>>,
+    now: Option<DateTime<Utc>>,
+    air_quality: bool,
+    astro: bool,
+    nowcast: bool,
+    compare_yesterday: bool,
+    fallback: Option<Fallback>,
+    fallback_api_keys: HashMap<Provider, String>,
+}
+
+impl WeatherClientBuilder {
+    pub fn provider(mut self, provider: Provider) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    /// API key for the providers that need one (open_weather_map)
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Unit to get the temperatures in
+    pub fn unit(mut self, unit: TemperatureUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Clock the hours of the data are labeled in
+    pub fn time_format(mut self, time_format: TimeFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+
+    /// Where to keep the geocoding cache and the rate limiting state (the system cache directory
+    /// otherwise)
+    pub fn paths(mut self, paths: Paths) -> Self {
+        self.paths = Some(paths);
+        self
+    }
+
+    /// Timeouts, retries, proxy and certificates of the requests
+    pub fn network(mut self, network: NetworkSettings) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Wrap the longitudes outside of [-180, 180] around the globe instead of rejecting them
+    pub fn normalize_lon(mut self, normalize_lon: bool) -> Self {
+        self.normalize_lon = normalize_lon;
+        self
+    }
+
+    /// Resolve the dates without a year to their last occurrence instead of the next one
+    pub fn past(mut self, past: bool) -> Self {
+        self.past = past;
+        self
+    }
+
+    /// Summarize the temperatures per day
+    pub fn daily(mut self, daily: bool) -> Self {
+        self.daily = daily;
+        self
+    }
+
+    /// Only show the data fetched before (the location still has to be geocoded, unless it's
+    /// resolved already or the geocoding result is cached)
+    pub fn cached(mut self, cached: bool) -> Self {
+        self.cached = cached;
+        self
+    }
+
+    /// Ask on stdin which place was meant if the address matches several of them (only if stdin
+    /// is a terminal, the best match is taken otherwise, which is also the default)
+    pub fn choose_place(mut self, choose_place: bool) -> Self {
+        self.choose_place = choose_place;
+        self
+    }
+
+    /// Resolve the addresses with another backend instead of Nominatim
+    pub fn geocoder(mut self, geocoder: impl Geocoder + 'static) -> Self {
+        self.geocoder = Some(Arc::new(geocoder));
+        self
+    }
+
+    /// Which of the built-in geocoders to use (Nominatim with Open-Meteo to fall back to by
+    /// default), ignored if [`WeatherClientBuilder::geocoder`] is set
+    pub fn geocoder_backend(mut self, geocoder_backend: GeocoderBackend) -> Self {
+        self.geocoder_backend = geocoder_backend;
+        self
+    }
+
+    /// Only accept "lat, lon" pairs, and name the locations after them instead of looking up their
+    /// addresses (nothing is sent to the geocoder)
+    pub fn no_geocode(mut self, no_geocode: bool) -> Self {
+        self.no_geocode = no_geocode;
+        self
+    }
+
+    /// Language of the addresses found by the coordinates (the one of the locale otherwise, and
+    /// the local one of the place if the geocoder doesn't have it)
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    /// Elevation of the location in m, for the providers that take it (open_meteo and met_no guess
+    /// it from their grids otherwise, which is off in the mountains)
+    pub fn elevation(mut self, elevation: f64) -> Self {
+        self.elevation = Some(elevation);
+        self
+    }
+
+    /// Write every provider response to the file (with the request it answered) before it's
+    /// parsed, to reproduce the parsing later with [`WeatherClientBuilder::replay`]
+    pub fn dump_response(mut self, path: impl Into<PathBuf>) -> Self {
+        self.dump_response = Some(path.into());
+        self
+    }
+
+    /// Parse the dumped response instead of fetching anything, as if it was just received (the
+    /// request should be the one in the dump: its provider, location, date and daily summary)
+    pub fn replay(mut self, dump: ResponseDump) -> Self {
+        self.replay = Some(Arc::new(dump));
+        self
+    }
+
+    /// Time of the run, for all of the requests to agree on what "now" and "today" are (the moment
+    /// of each request otherwise, as the long running callers need)
+    pub fn now(mut self, now: DateTime<Utc>) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    /// Fetch the air quality of the day along with the weather (only open_meteo has it, the rest of
+    /// the providers fail with [`WeatherError::Unsupported`](crate::WeatherError::Unsupported))
+    pub fn air_quality(mut self, air_quality: bool) -> Self {
+        self.air_quality = air_quality;
+        self
+    }
+
+    /// Calculate the moon phase and the length of the requested day at the location, into
+    /// [`WeatherData::astro`](crate::WeatherData::astro) (nothing more is fetched for it, so every
+    /// provider has it)
+    pub fn astro(mut self, astro: bool) -> Self {
+        self.astro = astro;
+        self
+    }
+
+    /// Fetch the precipitation of the next 90 minutes along with the weather, into
+    /// [`WeatherData::nowcast`](crate::WeatherData::nowcast): met_no's radar nowcast (empty outside
+    /// of the radar coverage) or open_meteo's 15 minute forecast. Only for "now", the rest of the
+    /// dates and providers fail with [`WeatherError::Unsupported`](crate::WeatherError::Unsupported)
+    /// or [`WeatherError::InvalidInput`](crate::WeatherError::InvalidInput)
+    pub fn nowcast(mut self, nowcast: bool) -> Self {
+        self.nowcast = nowcast;
+        self
+    }
+
+    /// Fetch the day before the requested one as well, into [`WeatherData::comparison`]
+    /// (only the providers that take the custom dates can, the rest of them fail with
+    /// [`WeatherError::Unsupported`](crate::WeatherError::Unsupported))
+    ///
+    /// [`WeatherData::comparison`]: crate::WeatherData::comparison
+    pub fn compare_yesterday(mut self, compare_yesterday: bool) -> Self {
+        self.compare_yesterday = compare_yesterday;
+        self
+    }
+
+    /// Get the weather from another provider when this one can't serve the request at all: the
+    /// dates it doesn't have, the extras it can't fetch or its missing API key (a warning says
+    /// so, and [`WeatherData::provider`](crate::WeatherData::provider) is the one it came from).
+    /// The rest of the failures, like the network ones, are returned as they are
+    pub fn fallback(mut self, fallback: Fallback) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    /// API key of a provider to fall back to, for the ones that need it
+    pub fn fallback_api_key(mut self, provider: Provider, api_key: impl Into<String>) -> Self {
+        self.fallback_api_keys.insert(provider, api_key.into());
+        self
+    }
+
+    pub fn build(self) -> Result<WeatherClient> {
+        let paths = match self.paths {
+            Some(paths) => paths,
+            None => Paths::resolve(None, None)?,
+        };
+
+        // The provider and the geocoding requests go through the same client
+        let http = http::client(&self.network)?;
+
+        Ok(WeatherClient {
+            provider: self.provider,
+            options: RequestOptions {
+                normalize_lon: self.normalize_lon,
+                past: self.past,
+                paths,
+                now: Utc::now(),
+                api_key: self.api_key,
+                unit: self.unit,
+                time_format: self.time_format,
+                daily: self.daily,
+                network: self.network,
+                http,
+                cached: self.cached,
+                choose_place: self.choose_place,
+                geocoder: self.geocoder,
+                geocoder_backend: self.geocoder_backend,
+                no_geocode: self.no_geocode,
+                language: self.language,
+                elevation: self.elevation,
+                dump_response: self.dump_response,
+                replay: self.replay,
+                air_quality: self.air_quality,
+                astro: self.astro,
+                nowcast: self.nowcast,
+                compare_yesterday: self.compare_yesterday,
+                fallback: self.fallback,
+                fallback_api_keys: self.fallback_api_keys,
+            },
+            now: self.now,
+        })
+    }
+}
@@ -0,0 +1,332 @@
+//! The commands of the cli apart from `weather get`, each of them in a function of its own
+
+pub(crate) mod get;
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre;
+use itertools::Itertools;
+
+use crate::{
+    archive,
+    config::{self, TemperatureUnit, TimeFormat},
+    date::DateRange,
+    error::WeatherError,
+    geocode::GeocodeCache,
+    locale::Language,
+    paths::Paths,
+    providers::{Location, Provider, ProviderCapabilities},
+    response_cache, update, WeatherClient,
+};
+
+/// `weather configure`: change the settings given on the command line and save them
+pub(crate) fn configure(
+    matches: &clap::ArgMatches,
+    config: &mut config::Config,
+    paths: &Paths,
+) -> eyre::Result<()> {
+    let provider = matches.get_one::<String>("provider");
+    let api_key = matches.get_one::<String>("api-key");
+    let unit = matches.get_one::<String>("unit");
+    let time_format = matches.get_one::<String>("time-format");
+    let language = matches.get_one::<String>("language");
+    let default_location = matches.get_one::<String>("default-location");
+
+    if provider.is_none()
+        && api_key.is_none()
+        && unit.is_none()
+        && time_format.is_none()
+        && language.is_none()
+        && default_location.is_none()
+    {
+        return Err(WeatherError::InvalidInput(
+            "Nothing to configure, specify a provider, --api-key, --unit, --time-format, \
+            --language or --default-location (or go through all of them with --interactive)"
+                .to_string(),
+        )
+        .into());
+    }
+
+    if let Some(provider) = provider {
+        // Check if the input provider is valid, and if yes, set it in the config
+        config.provider = Provider::from_str(provider)?;
+    }
+
+    // The API key is for the provider that was just set (or the one in effect)
+    if let Some(api_key) = api_key {
+        let provider = match provider {
+            Some(_) => config.provider,
+            None => config.provider(),
+        };
+        config.api_keys.insert(provider, api_key.clone());
+    }
+
+    if let Some(unit) = unit {
+        config.unit = TemperatureUnit::from_str(unit)?;
+    }
+
+    if let Some(time_format) = time_format {
+        config.time_format = Some(TimeFormat::from_str(time_format)?);
+    }
+
+    if let Some(language) = language {
+        config.language = Some(Language::from_str(language)?);
+    }
+
+    // Resolve the location once, so that using it doesn't need geocoding anymore
+    if let Some(address) = default_location {
+        let location = match address.starts_with('@') {
+            true => config.location(address)?,
+            false => WeatherClient::builder()
+                .paths(paths.clone())
+                .network(config.network.clone())
+                .geocoder_backend(config.geocoder)
+                .choose_place(true)
+                .build()?
+                .resolve(address)?
+                .into(),
+        };
+
+        println!("Default location: {}", location.describe());
+
+        config.default_location = Some(location);
+    }
+
+    // And save the config
+    Ok(config.save()?)
+}
+
+/// `weather export`: write the archive of the days to a file, a chunk of days at a time
+pub(crate) fn export(
+    matches: &clap::ArgMatches,
+    config: &config::Config,
+    paths: Paths,
+    now: DateTime<Utc>,
+) -> eyre::Result<()> {
+    let range = DateRange {
+        start: *matches
+            .get_one::<chrono::NaiveDate>("from")
+            .ok_or(eyre::eyre!("No start day specified"))?,
+        end: *matches
+            .get_one::<chrono::NaiveDate>("to")
+            .ok_or(eyre::eyre!("No end day specified"))?,
+    };
+    let path = matches
+        .get_one::<PathBuf>("out")
+        .ok_or(eyre::eyre!("No output file specified"))?;
+    let metrics = matches
+        .get_many::<String>("metrics")
+        .into_iter()
+        .flatten()
+        .map(archive::Metric::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+    let provider = matches
+        .get_one::<String>("provider")
+        .map(Provider::from_str)
+        .transpose()?
+        .unwrap_or(config.provider());
+
+    let builder = WeatherClient::builder()
+        .provider(provider)
+        .unit(
+            matches
+                .get_one::<String>("unit")
+                .map(TemperatureUnit::from_str)
+                .transpose()?
+                .unwrap_or(config.unit),
+        )
+        .paths(paths)
+        .network(config.network.clone())
+        .geocoder_backend(config.geocoder)
+        .now(now);
+    let builder = match config.api_keys.get(&provider) {
+        Some(api_key) => builder.api_key(api_key),
+        None => builder,
+    };
+
+    // Resolved once, not for every chunk (the saved locations with their elevation)
+    let saved = |location: config::SavedLocation| (location.elevation, location.into());
+    let (elevation, location) = match matches.get_one::<String>("address") {
+        Some(address) if address.starts_with('@') => saved(config.location(address)?),
+        Some(address) => (None, builder.clone().build()?.resolve(address)?),
+        None => saved(config.default_location()?),
+    };
+    let client = match elevation {
+        Some(elevation) => builder.elevation(elevation),
+        None => builder,
+    }
+    .build()?;
+
+    let rows = archive::export_archive(
+        (range, &metrics),
+        path,
+        matches.get_one::<archive::Resume>("resume-from").copied(),
+        // Through the client, with its retries and the cooldown of the provider
+        |chunk| {
+            client.get_location(
+                Location::Resolved(location.clone()),
+                &format!("{}..{}", chunk.start, chunk.end),
+            )
+        },
+        |progress| {
+            eprintln!(
+                "Chunk {}/{}: {}..{}, {} rows",
+                progress.chunk,
+                progress.chunks,
+                progress.range.start,
+                progress.range.end,
+                progress.rows
+            );
+            for converted in &progress.converted {
+                eprintln!("  {}", converted.message(Language::En));
+            }
+        },
+    )?;
+
+    eprintln!("Exported {rows} rows to {}", path.display());
+
+    Ok(())
+}
+
+/// `weather locations`: save the locations under a name, list and remove them
+pub(crate) fn locations(
+    matches: &clap::ArgMatches,
+    config: &mut config::Config,
+    paths: &Paths,
+) -> eyre::Result<()> {
+    match matches.subcommand() {
+        Some(("add", matches)) => {
+            let name = matches
+                .get_one::<String>("name")
+                .ok_or(eyre::eyre!("No name specified"))?;
+            let name = name.strip_prefix('@').unwrap_or(name);
+            let address = matches
+                .get_one::<String>("address")
+                .ok_or(eyre::eyre!("No address specified"))?;
+
+            if let (Some(existing), false) = (config.locations.get(name), matches.get_flag("force"))
+            {
+                return Err(WeatherError::InvalidInput(format!(
+                    "Location {name} is already saved ({}), pass --force to replace it",
+                    existing.address
+                ))
+                .into());
+            }
+
+            // Resolve the location once, so that using it doesn't need geocoding anymore
+            let location = WeatherClient::builder()
+                .paths(paths.clone())
+                .network(config.network.clone())
+                .geocoder_backend(config.geocoder)
+                .choose_place(!matches.get_flag("first"))
+                .no_geocode(matches.get_flag("no-geocode"))
+                .build()?
+                .resolve(address)?;
+
+            let location = config::SavedLocation {
+                elevation: matches.get_one::<f64>("elevation").copied(),
+                ..location.into()
+            };
+
+            println!("Saved {name}: {}", location.describe());
+
+            config.locations.insert(name.to_string(), location);
+            Ok(config.save()?)
+        }
+        Some(("list", _)) => {
+            if config.locations.is_empty() {
+                println!(
+                    "No saved locations, add one with `weather locations add <name> <address>`"
+                );
+            }
+
+            for (name, location) in config.locations.iter().sorted_by_key(|(name, _)| *name) {
+                println!("@{name}: {}", location.describe());
+            }
+
+            Ok(())
+        }
+        Some(("remove", matches)) => {
+            let name = matches
+                .get_one::<String>("name")
+                .ok_or(eyre::eyre!("No name specified"))?;
+            let name = name.strip_prefix('@').unwrap_or(name);
+
+            config
+                .locations
+                .remove(name)
+                .ok_or(WeatherError::InvalidInput(format!(
+                    "No saved location named {name}"
+                )))?;
+
+            println!("Removed {name}");
+
+            Ok(config.save()?)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// `weather config show`: the settings in effect
+pub(crate) fn show_config(matches: &clap::ArgMatches, config: &config::Config) -> eyre::Result<()> {
+    if let Some(("show", _)) = matches.subcommand() {
+        print!("{}", config.summary());
+    }
+
+    Ok(())
+}
+
+/// `weather providers`: what every provider can do, as a table or as JSON
+pub(crate) fn providers(matches: &clap::ArgMatches) -> eyre::Result<()> {
+    let capabilities = Provider::ALL.map(|provider| provider.capabilities());
+
+    match matches.get_flag("json") {
+        true => println!("{}", serde_json::to_string_pretty(&capabilities)?),
+        false => print!("{}", ProviderCapabilities::table(&capabilities)),
+    }
+
+    Ok(())
+}
+
+/// `weather cache clear`: forget the geocoding results (and the responses, with `--all`)
+pub(crate) fn clear_cache(matches: &clap::ArgMatches, paths: &Paths) -> eyre::Result<()> {
+    let Some(("clear", matches)) = matches.subcommand() else {
+        return Ok(());
+    };
+
+    GeocodeCache::clear(paths)?;
+
+    match matches.get_flag("all") {
+        true => {
+            response_cache::clear(paths)?;
+
+            println!("Geocoding cache and cached responses cleared");
+        }
+        false => println!("Geocoding cache cleared"),
+    }
+
+    Ok(())
+}
+
+/// `weather update`: check for a newer version, and install it with `--install`
+pub(crate) fn update(matches: &clap::ArgMatches, config: &config::Config) -> eyre::Result<()> {
+    let release = update::latest_release(&config.network)?;
+
+    match (release.newer_version(), matches.get_flag("install")) {
+        (None, _) => println!(
+            "weather-cli {} is the latest version",
+            update::CURRENT_VERSION
+        ),
+        (Some(version), false) => {
+            println!("{}", update::update_message(&release, &version));
+            println!("Run `weather update --install` to install it");
+        }
+        (Some(version), true) => {
+            update::install(&release, &config.network)?;
+            println!("Updated to weather-cli {version}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,684 @@
+//! `weather get`: the weather of the address (or of several of them) for the date, in the output
+//! that was asked for
+
+use std::{collections::HashSet, io, path::PathBuf, time::Duration};
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre;
+use itertools::Itertools;
+
+use crate::{
+    config::{self, Charset, TemperatureUnit, TimeFormat},
+    data::{CurrentSnapshot, WeatherData},
+    date::{is_date, is_week, DateOptions},
+    error::{self, WeatherError},
+    geocode::read_addresses,
+    last_run::{Change, LastRuns, DEFAULT_PRECISION},
+    locale::Language,
+    paths::Paths,
+    providers::{Fallback, Location, Provider},
+    publish::{self, Publisher},
+    queries::{Condition, NoMatchOutput, Query, NO_MATCH_EXIT_CODE},
+    response_dump::ResponseDump,
+    timezone::DisplayZone,
+    ui::{
+        draw_batch, draw_batch_compact, draw_compact, draw_comparison, draw_data, export_csv,
+        export_data, export_ics, ChartSeries, OutputMode, RenderOptions, Theme,
+    },
+    watch::{self, NdjsonMode, NdjsonStream},
+    NoMatch, WeatherClient, WeatherClientBuilder,
+};
+
+/// Get the weather and show it the way the options ask for
+pub(crate) fn run(
+    matches: &clap::ArgMatches,
+    config: &config::Config,
+    paths: Paths,
+    now: DateTime<Utc>,
+) -> eyre::Result<()> {
+    let mut values = matches
+        .get_one::<String>("address")
+        .into_iter()
+        .chain(matches.get_many::<String>("more").into_iter().flatten())
+        .cloned()
+        .collect_vec();
+    let from_file_path = matches.get_one::<PathBuf>("from-file");
+    let from_file = from_file_path
+        .map(|path| read_addresses(path))
+        .transpose()?;
+    // A dumped response is shown for the request it answered, without the network
+    let replay = matches
+        .get_one::<PathBuf>("from-dump")
+        .map(|path| ResponseDump::read(path))
+        .transpose()?;
+
+    // The dates of the replayed response are relative to when it was received
+    let today = replay
+        .as_ref()
+        .map_or(now, |replay| replay.time)
+        .with_timezone(&chrono::Local)
+        .date_naive();
+    let date = DateOptions {
+        date: trailing_date(matches, &mut values, from_file.is_some(), today)?
+            .or(replay.as_ref().map(|replay| replay.date.clone())),
+        days: matches.get_one::<u16>("days").copied(),
+        week: matches.get_flag("week"),
+        next: matches.contains_id("next"),
+    }
+    .resolve()?;
+
+    // The addresses from the file come after the ones on the command line
+    let addresses = values
+        .into_iter()
+        .chain(from_file.into_iter().flatten())
+        .collect_vec();
+
+    // The provider from the command line is only used for this run
+    let provider = matches
+        .get_one::<String>("provider")
+        .map(Provider::from_str)
+        .transpose()?
+        .or(replay.as_ref().map(|replay| replay.provider))
+        .unwrap_or(config.provider());
+    let clients = Clients {
+        // The client takes the paths, the last runs are kept next to its state
+        builder: client_builder(matches, config, paths.clone(), replay.as_ref(), &date, now)?,
+        elevation: matches.get_one::<f64>("elevation").copied(),
+        config,
+    };
+
+    // The screen readers get the sentences unless another output is asked for
+    let output = OutputMode::resolve(
+        matches.get_one::<String>("output").map(String::as_str),
+        matches.get_flag("plain"),
+    )?;
+    let options = render_options(matches, config, is_week(&date), now)?;
+
+    // Saved locations are used as they are, without any geocoding (and with their elevation)
+    let saved =
+        |location: config::SavedLocation| (location.elevation, Location::Resolved(location.into()));
+    let (elevation, location) = match (matches.get_one::<String>("location"), addresses.as_slice())
+    {
+        (Some(name), _) => saved(config.location(name)?),
+        (None, []) => match (&replay, from_file_path) {
+            (Some(replay), _) => (
+                replay.elevation,
+                Location::Resolved(replay.location.clone()),
+            ),
+            (None, Some(path)) => {
+                return Err(WeatherError::InvalidInput(format!(
+                    "There are no addresses in {}",
+                    path.display()
+                ))
+                .into());
+            }
+            (None, None) => saved(config.default_location()?),
+        },
+        (None, [address]) if address.starts_with('@') => saved(config.location(address)?),
+        (None, [address]) => (None, Location::Address(address.clone())),
+        // Several locations at once, each of them on its own page
+        (None, _) => {
+            return batch(
+                matches,
+                (&clients, provider),
+                addresses,
+                &date,
+                (output, options),
+            );
+        }
+    };
+
+    match matches.get_flag("compare") {
+        // A single object per line, not one per provider
+        true if matches!(output, OutputMode::Ndjson | OutputMode::Json) => {
+            Err(WeatherError::InvalidInput(
+                "--compare can't be written as --output ndjson or json".to_string(),
+            )
+            .into())
+        }
+        true => {
+            // Geocode the address once, instead of once per provider
+            let location = match location {
+                Location::Address(address) => {
+                    Location::Resolved(clients.client(provider, elevation)?.resolve(&address)?)
+                }
+                location => location,
+            };
+            let clients = Provider::ALL
+                .into_iter()
+                .map(|provider| clients.client(provider, elevation))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // Get the weather data from all the providers at once
+            let results = WeatherClient::get_all(&clients, &location, &date);
+
+            // Draw whatever the providers returned, noting the ones that failed
+            draw_comparison(
+                Provider::ALL.into_iter().zip(results).collect(),
+                &config.extreme_temperatures,
+                output,
+                options,
+            )
+        }
+        false => single(
+            matches,
+            (clients.client(provider, elevation)?, location),
+            &date,
+            (config, &paths),
+            (output, options),
+        ),
+    }
+}
+
+/// A single value is the address, but after more of them (or when only the date is left to give)
+/// the last one is the date if it looks like one on `today`
+fn trailing_date(
+    matches: &clap::ArgMatches,
+    values: &mut Vec<String>,
+    from_file: bool,
+    today: chrono::NaiveDate,
+) -> eyre::Result<Option<String>> {
+    match matches.get_one::<String>("location") {
+        Some(_) if values.len() > 1 => Err(WeatherError::InvalidInput(
+            "--location replaces the address, only the date can be specified".to_string(),
+        )
+        .into()),
+        Some(_) => Ok(values.pop()),
+        None => match (values.len() > 1 || from_file)
+            && values.last().is_some_and(|value| is_date(value, today))
+        {
+            true => Ok(values.pop()),
+            false => Ok(None),
+        },
+    }
+}
+
+/// The client options of the command line, with the ones of the config in place of the options
+/// that aren't given
+fn client_builder(
+    matches: &clap::ArgMatches,
+    config: &config::Config,
+    paths: Paths,
+    replay: Option<&ResponseDump>,
+    date: &str,
+    now: DateTime<Utc>,
+) -> eyre::Result<WeatherClientBuilder> {
+    let builder = WeatherClient::builder()
+        .unit(
+            matches
+                .get_one::<String>("unit")
+                .map(TemperatureUnit::from_str)
+                .transpose()?
+                .unwrap_or(config.unit),
+        )
+        .time_format(match matches.get_flag("24h") {
+            true => TimeFormat::Hours24,
+            false => config.time_format.unwrap_or_else(TimeFormat::from_locale),
+        })
+        .paths(paths)
+        .network(config.network.clone())
+        .geocoder_backend(config.geocoder)
+        .normalize_lon(matches.get_flag("normalize-lon"))
+        .past(matches.get_flag("past"))
+        .daily(
+            matches.get_flag("daily") || is_week(date) || replay.is_some_and(|replay| replay.daily),
+        )
+        .cached(matches.get_flag("cached"))
+        .air_quality(matches.get_flag("air-quality"))
+        .astro(matches.get_flag("astro"))
+        .nowcast(matches.get_flag("nowcast"))
+        .compare_yesterday(matches.get_flag("compare-yesterday"))
+        .choose_place(!matches.get_flag("first"))
+        .no_geocode(matches.get_flag("no-geocode"));
+    // The addresses in the chosen language, the geocoder follows the locale otherwise
+    let builder = match matches
+        .get_one::<String>("lang")
+        .map(Language::from_str)
+        .transpose()?
+        .or(config.language)
+    {
+        Some(language) => builder.language(language),
+        None => builder,
+    };
+    let builder = match matches.get_one::<PathBuf>("dump-response") {
+        Some(path) => builder.dump_response(path),
+        None => builder,
+    };
+    let builder = match replay {
+        Some(replay) => builder.replay(replay.clone()),
+        None => builder,
+    };
+    // Each refresh of --watch is a moment of its own
+    let builder = match matches.contains_id("watch") {
+        true => builder,
+        false => builder.now(now),
+    };
+
+    Ok(match fallback(matches, config)? {
+        Some(fallback) => config.api_keys.iter().fold(
+            builder.fallback(fallback),
+            |builder, (provider, api_key)| builder.fallback_api_key(*provider, api_key),
+        ),
+        None => builder,
+    })
+}
+
+/// Provider to fall back to: the one from the command line (only used for this run), the one from
+/// the config otherwise. None with `--compare`, which gets the weather from all the providers anyway
+fn fallback(matches: &clap::ArgMatches, config: &config::Config) -> eyre::Result<Option<Fallback>> {
+    Ok(matches
+        .get_one::<String>("fallback")
+        .map(Fallback::from_str)
+        .transpose()?
+        .or(config.fallback_provider.map(Fallback::Provider))
+        .filter(|_| !matches.get_flag("compare")))
+}
+
+/// How the data is shown, from the command line and the config
+fn render_options(
+    matches: &clap::ArgMatches,
+    config: &config::Config,
+    week: bool,
+    now: DateTime<Utc>,
+) -> eyre::Result<RenderOptions> {
+    // Only the ASCII characters if asked for, or if the terminal's locale can't show the rest
+    let ascii = matches.get_flag("ascii")
+        || config.charset.unwrap_or_else(Charset::from_locale) == Charset::Ascii;
+
+    Ok(RenderOptions {
+        series: matches
+            .get_one::<String>("show")
+            .map(ChartSeries::from_str)
+            .transpose()?
+            .unwrap_or(ChartSeries::Temperature),
+        ascii_glyphs: config.ascii_glyphs || ascii,
+        ascii,
+        precision: matches
+            .get_one::<u8>("precision")
+            .map(|precision| *precision as usize),
+        data_age: config.data_age,
+        week,
+        // The translations are not ASCII
+        language: match (ascii, matches.get_one::<String>("lang")) {
+            (true, _) => Language::En,
+            (false, Some(language)) => Language::from_str(language)?,
+            (false, None) => config.language.unwrap_or_else(Language::from_locale),
+        },
+        theme: Theme::resolve(
+            matches.get_one::<String>("theme").map(String::as_str),
+            &config.theme,
+        ),
+        best_window: matches
+            .get_one::<u16>("best-window")
+            .map(|hours| (*hours as usize, config.outdoor)),
+        display_zone: matches
+            .get_one::<String>("display-tz")
+            .map(DisplayZone::from_str)
+            .transpose()?
+            .unwrap_or_default(),
+        with_table: matches.get_flag("with-table"),
+        now,
+    })
+}
+
+/// The clients of the providers, every one of them with its own API key
+struct Clients<'a> {
+    builder: WeatherClientBuilder,
+    /// Elevation from the command line, it overrides the one of the saved location
+    elevation: Option<f64>,
+    config: &'a config::Config,
+}
+
+impl Clients<'_> {
+    fn client(&self, provider: Provider, elevation: Option<f64>) -> error::Result<WeatherClient> {
+        let builder = self.builder.clone().provider(provider);
+        let builder = match self.elevation.or(elevation) {
+            Some(elevation) => builder.elevation(elevation),
+            None => builder,
+        };
+
+        match self.config.api_keys.get(&provider) {
+            Some(api_key) => builder.api_key(api_key),
+            None => builder,
+        }
+        .build()
+    }
+}
+
+/// The status bars run it every few minutes, so the current conditions come from the lighter
+/// request where the provider has one
+fn compact_snapshot(
+    matches: &clap::ArgMatches,
+    client: WeatherClient,
+    location: Location,
+    date: &str,
+) -> error::Result<CurrentSnapshot> {
+    let hours = matches.get_one::<u16>("hours").copied().unwrap_or(12) as usize;
+
+    match date {
+        "now" => client.get_current(location, hours),
+        _ => client
+            .get_location(location, date)
+            .map(|data| CurrentSnapshot::from_data(&data, hours)),
+    }
+}
+
+/// Several addresses at once, each of them on its own page (or line)
+fn batch(
+    matches: &clap::ArgMatches,
+    (clients, provider): (&Clients, Provider),
+    addresses: Vec<String>,
+    date: &str,
+    (output, options): (OutputMode, RenderOptions),
+) -> eyre::Result<()> {
+    if matches.get_flag("compare")
+        || matches.contains_id("export")
+        || matches.contains_id("csv")
+        || matches.contains_id("next")
+        || matches.get_flag("changed-only")
+        || output == OutputMode::Ndjson
+        || matches.contains_id("publish")
+    {
+        return Err(WeatherError::InvalidInput(
+            "--compare, --export, --csv, --next, --changed-only, --output ndjson and --publish \
+            take a single address"
+                .to_string(),
+        )
+        .into());
+    }
+
+    // A wrong date is wrong for all of them, not a failure of each address
+    clients.client(provider, None)?.check_date(date)?;
+
+    // The same place written differently is only shown once
+    let mut seen = HashSet::new();
+    let mut locations = vec![];
+
+    for address in addresses {
+        let resolved = match address.starts_with('@') {
+            true => clients
+                .config
+                .location(&address)
+                .map(|location| (location.elevation, location.into())),
+            false => clients
+                .client(provider, None)
+                .and_then(|client| client.resolve(&address))
+                .map(|location| (None, location)),
+        };
+
+        match &resolved {
+            Ok((_, location))
+                if !seen.insert(format!("{:.2},{:.2}", location.lat, location.lon)) => {}
+            _ => locations.push((address, resolved)),
+        }
+    }
+
+    let extreme_temperatures = &clients.config.extreme_temperatures;
+
+    if matches.get_flag("compact") {
+        let results = locations
+            .into_iter()
+            .map(|(address, resolved)| {
+                let snapshot = resolved.and_then(|(elevation, location)| {
+                    compact_snapshot(
+                        matches,
+                        clients.client(provider, elevation)?,
+                        Location::Resolved(location),
+                        date,
+                    )
+                });
+
+                (address, snapshot)
+            })
+            .collect();
+
+        return draw_batch_compact(results, extreme_temperatures, options);
+    }
+
+    let results = locations
+        .into_iter()
+        .map(|(address, resolved)| {
+            let data = resolved.and_then(|(elevation, location)| {
+                clients
+                    .client(provider, elevation)?
+                    .get_location(Location::Resolved(location), date)
+            });
+
+            (address, data)
+        })
+        .collect();
+
+    draw_batch(results, extreme_temperatures, output, options)
+}
+
+/// A single address from a single provider, with everything that can be done with its data
+fn single(
+    matches: &clap::ArgMatches,
+    (client, location): (WeatherClient, Location),
+    date: &str,
+    (config, paths): (&config::Config, &Paths),
+    (output, options): (OutputMode, RenderOptions),
+) -> eyre::Result<()> {
+    // A single line for the status bars
+    if matches.get_flag("compact") {
+        return draw_compact(
+            compact_snapshot(matches, client, location, date)?,
+            &config.extreme_temperatures,
+            options,
+        );
+    }
+
+    // A line of JSON per refresh for the collectors, until interrupted
+    if let Some(seconds) = matches.get_one::<u64>("watch") {
+        if output != OutputMode::Ndjson {
+            return Err(WeatherError::InvalidInput(
+                "--watch only streams --output ndjson for now".to_string(),
+            )
+            .into());
+        }
+
+        let mut stream = NdjsonStream::new(match matches.get_flag("ndjson-delta") {
+            true => NdjsonMode::Delta,
+            false => NdjsonMode::Full,
+        });
+
+        return Ok(watch::watch(
+            || client.get_location(location.clone(), date),
+            (Duration::from_secs(*seconds), None),
+            &mut stream,
+            &mut io::stdout(),
+        )?);
+    }
+
+    // The destination is checked before the data is fetched, so that a mistyped one fails right
+    // away
+    let publisher = matches
+        .get_one::<String>("publish")
+        .map(|destination| publish::publisher(destination, &config.publish, &config.network))
+        .transpose()?;
+
+    // Get the weather data
+    let data = client.get_location(location, date)?;
+
+    // Only when the forecast changed since the last run, with what changed first
+    if matches.get_flag("changed-only") {
+        let precision = matches
+            .get_one::<f64>("change-precision")
+            .copied()
+            .unwrap_or(DEFAULT_PRECISION);
+
+        match LastRuns::load(paths).record(&data, precision)? {
+            Change::Unchanged => return Ok(()),
+            Change::Changed(changes) => println!("{}", changes.join(", ")),
+            _ => {}
+        }
+    }
+
+    // Only when the condition comes next, for the scripts
+    if let Some(condition) = matches.get_one::<String>("next") {
+        return print_next(
+            &data,
+            Condition::from_str(condition)?,
+            matches,
+            options.language,
+        );
+    }
+
+    // The numbers for the spreadsheets, only them if they are printed
+    if matches.contains_id("csv") {
+        let path = matches.get_one::<PathBuf>("csv");
+        export_csv(&data, path.map(PathBuf::as_path))?;
+
+        match path {
+            Some(path) => eprintln!("CSV saved to {}", path.display()),
+            None => return Ok(()),
+        }
+    }
+
+    // The events for the calendars, the data is still shown after writing them
+    if let Some(path) = matches.get_one::<PathBuf>("export-ics") {
+        export_ics(&data, path, matches.get_flag("ics-days"), options.language)?;
+        eprintln!("Calendar saved to {}", path.display());
+    }
+
+    // A snapshot to share, the data is only shown as well if asked for
+    if let Some(path) = matches.get_one::<PathBuf>("export") {
+        let size = matches
+            .get_one::<(u16, u16)>("export-size")
+            .copied()
+            .unwrap_or((120, 40));
+
+        export_data(&data, path, size, &config.extreme_temperatures, options)?;
+
+        if !matches.get_flag("interactive") {
+            println!("Snapshot saved to {}", path.display());
+
+            return Ok(());
+        }
+    }
+
+    // The JSON to publish, before the drawing takes the data
+    let published = publisher.map(|publisher| (publisher, NdjsonStream::default().line(Ok(&data))));
+
+    // Draw the weather data
+    draw_data(data, &config.extreme_temperatures, output, options)?;
+
+    match published {
+        Some((publisher, json)) => publish_data(
+            publisher.as_ref(),
+            &json,
+            matches.get_flag("publish-required"),
+            options.now,
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Publish the JSON of the data after it's shown, a failure only fails the run if the publishing
+/// is required
+fn publish_data(
+    publisher: &dyn Publisher,
+    json: &str,
+    required: bool,
+    now: DateTime<Utc>,
+) -> eyre::Result<()> {
+    match (publisher.publish(json, now), required) {
+        (Err(e), true) => Err(e.into()),
+        (Err(e), false) => {
+            eprintln!("Warning: {e}");
+
+            Ok(())
+        }
+        (Ok(()), _) => Ok(()),
+    }
+}
+
+/// Print when the condition comes next, failing with [`NoMatch`] if it doesn't
+fn print_next(
+    data: &WeatherData,
+    condition: Condition,
+    matches: &clap::ArgMatches,
+    language: Language,
+) -> eyre::Result<()> {
+    let output = matches
+        .get_one::<String>("no-match-output")
+        .map(NoMatchOutput::from_str)
+        .transpose()?
+        .unwrap_or_default();
+    let query = data.next(condition)?;
+
+    if let Some(line) = query.format(data, output, language) {
+        println!("{line}");
+    }
+
+    match query {
+        Query::NotFound { .. } => Err(NoMatch(
+            matches
+                .get_one::<u8>("no-match-exit")
+                .copied()
+                .unwrap_or(NO_MATCH_EXIT_CODE),
+        )
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Arguments of `weather get` with the options
+    fn get_matches(args: &[&str]) -> clap::ArgMatches {
+        crate::cli::build_cli()
+            .try_get_matches_from(["weather", "get", "Kyiv"].iter().chain(args))
+            .unwrap()
+            .subcommand_matches("get")
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn fallback_from_the_command_line_first() {
+        let mut config = config::Config::default();
+        let cases = [
+            (&[][..], None, None),
+            (
+                &[][..],
+                Some(Provider::OpenMeteo),
+                Some(Fallback::Provider(Provider::OpenMeteo)),
+            ),
+            (
+                &["--fallback", "met_no"][..],
+                None,
+                Some(Fallback::Provider(Provider::MetNo)),
+            ),
+            (
+                &["--fallback", "met_no"][..],
+                Some(Provider::OpenMeteo),
+                Some(Fallback::Provider(Provider::MetNo)),
+            ),
+            (
+                &["--fallback", "auto"][..],
+                Some(Provider::OpenMeteo),
+                Some(Fallback::Auto),
+            ),
+            // Not with --compare, even from the config
+            (&["--compare"][..], Some(Provider::OpenMeteo), None),
+        ];
+
+        for (args, configured, expected) in cases {
+            config.fallback_provider = configured;
+
+            assert_eq!(
+                fallback(&get_matches(args), &config).unwrap(),
+                expected,
+                "{args:?} with {configured:?} in the config"
+            );
+        }
+
+        // --fallback and --compare don't go together
+        assert!(crate::cli::build_cli()
+            .try_get_matches_from(["weather", "get", "Kyiv", "--compare", "--fallback", "auto"])
+            .is_err());
+    }
+}
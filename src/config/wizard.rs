@@ -0,0 +1,569 @@
+//! Setup that asks for the provider, the unit, the clock and the default location one after
+//! another, on the first run and with `weather configure --interactive`
+
+use std::io::Write;
+
+use crossterm::{
+    cursor::MoveUp,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
+};
+
+use crate::{
+    config::{Config, SavedLocation, TemperatureUnit, TimeFormat},
+    error::Result,
+    providers::Provider,
+};
+
+/// Keys that move the selection of the options
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Enter,
+    /// Esc or Ctrl-C, ends the setup without saving anything
+    Cancel,
+}
+
+/// Where the answers come from: the terminal, or a script of them
+pub trait WizardInput {
+    /// Read the next key of a selection
+    fn read_key(&mut self) -> Result<Key>;
+
+    /// Read a line of the answer (without the line break), `None` at the end of the input
+    fn read_line(&mut self) -> Result<Option<String>>;
+}
+
+/// Keys and lines typed in the terminal. The stdin is only locked for each line, as the
+/// geocoding asks which of the places was meant on it too
+pub struct TerminalInput;
+
+impl WizardInput for TerminalInput {
+    fn read_key(&mut self) -> Result<Key> {
+        // Only the keys need the raw mode, the lines are read with the usual editing
+        enable_raw_mode()?;
+        let key = read_terminal_key();
+        disable_raw_mode()?;
+
+        key
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>> {
+        let mut line = String::new();
+
+        match std::io::stdin().read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some(line.trim_end_matches(['\r', '\n']).to_string())),
+        }
+    }
+}
+
+/// Wait for one of the keys of a selection, the others are ignored
+fn read_terminal_key() -> Result<Key> {
+    loop {
+        // Only the presses, as some terminals report the releases too
+        if let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        {
+            match code {
+                KeyCode::Up | KeyCode::Char('k') => return Ok(Key::Up),
+                KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => return Ok(Key::Down),
+                KeyCode::Enter => return Ok(Key::Enter),
+                // The raw mode turns Ctrl-C into a regular key press instead of a signal
+                KeyCode::Esc => return Ok(Key::Cancel),
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(Key::Cancel)
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Ask for the settings, write the answers into the config and save it. `resolve` geocodes the
+/// default location (the saved @locations are looked up without it).
+///
+/// Nothing is saved if the setup is cancelled (Esc, Ctrl-C or the end of the input), the config
+/// keeps the values it had
+pub fn run(
+    config: &mut Config,
+    input: &mut impl WizardInput,
+    output: &mut impl Write,
+    resolve: impl Fn(&str) -> Result<SavedLocation>,
+) -> Result<()> {
+    writeln!(
+        output,
+        "Let's set up the weather cli (Esc to skip it, `weather configure --interactive` starts it \
+        again)"
+    )?;
+
+    // The answers are only written into the config at the end, so that a cancelled setup leaves
+    // nothing behind
+    let Some(answers) = ask(config, input, output, resolve)? else {
+        writeln!(output, "Setup cancelled, the answers weren't saved")?;
+        return Ok(());
+    };
+
+    config.provider = answers.provider;
+    if let Some(api_key) = answers.api_key {
+        config.api_keys.insert(config.provider, api_key);
+    }
+    config.unit = answers.unit;
+    config.time_format = answers.time_format;
+    config.default_location = answers.default_location;
+
+    config.save()?;
+    writeln!(output, "Config saved to {}", config.file_path().display())?;
+
+    Ok(())
+}
+
+/// Values the setup changes
+struct Answers {
+    provider: Provider,
+    /// `None` keeps the saved one
+    api_key: Option<String>,
+    unit: TemperatureUnit,
+    time_format: Option<TimeFormat>,
+    default_location: Option<SavedLocation>,
+}
+
+/// Ask every question in order, starting at the configured values. `None` if the setup was
+/// cancelled
+fn ask(
+    config: &Config,
+    input: &mut impl WizardInput,
+    output: &mut impl Write,
+    resolve: impl Fn(&str) -> Result<SavedLocation>,
+) -> Result<Option<Answers>> {
+    // Provider, and its API key if it needs one
+    let providers = Provider::ALL
+        .iter()
+        .map(|provider| match provider.capabilities().requires_api_key {
+            true => format!("{provider} (needs an API key)"),
+            false => provider.to_string(),
+        })
+        .collect::<Vec<_>>();
+    let default = Provider::ALL
+        .iter()
+        .position(|provider| *provider == config.provider)
+        .unwrap_or_default();
+    let Some(provider) = select(input, output, "Weather provider", &providers, default)? else {
+        return Ok(None);
+    };
+    let provider = Provider::ALL[provider];
+
+    let api_key = match provider.capabilities().requires_api_key {
+        true => {
+            let saved = config.api_keys.contains_key(&provider);
+            let Some(api_key) = api_key(input, output, provider, saved)? else {
+                return Ok(None);
+            };
+            api_key
+        }
+        false => None,
+    };
+
+    // Unit
+    let units = TemperatureUnit::AVAILABLE_UNITS.map(String::from);
+    let default = TemperatureUnit::AVAILABLE_UNITS
+        .iter()
+        .position(|unit| TemperatureUnit::from_str(unit).ok() == Some(config.unit))
+        .unwrap_or_default();
+    let Some(unit) = select(input, output, "Temperature unit", &units, default)? else {
+        return Ok(None);
+    };
+    let unit = TemperatureUnit::from_str(TemperatureUnit::AVAILABLE_UNITS[unit])?;
+
+    // Clock, the last option leaves it to the locale
+    let formats = TimeFormat::AVAILABLE_FORMATS
+        .iter()
+        .map(|format| format.to_string())
+        .chain([format!(
+            "the one of the system locale ({} now)",
+            TimeFormat::from_locale().name()
+        )])
+        .collect::<Vec<_>>();
+    let default = config
+        .time_format
+        .and_then(|time_format| {
+            TimeFormat::AVAILABLE_FORMATS
+                .iter()
+                .position(|format| *format == time_format.name())
+        })
+        .unwrap_or(formats.len() - 1);
+    let Some(time_format) = select(input, output, "Clock", &formats, default)? else {
+        return Ok(None);
+    };
+    let time_format = TimeFormat::AVAILABLE_FORMATS
+        .get(time_format)
+        .map(TimeFormat::from_str)
+        .transpose()?;
+
+    // Default location
+    let Some(default_location) = default_location(input, output, config, resolve)? else {
+        return Ok(None);
+    };
+
+    Ok(Some(Answers {
+        provider,
+        api_key,
+        unit,
+        time_format,
+        default_location,
+    }))
+}
+
+/// Let the user pick one of the options with the arrow keys. The index of the picked one, `None`
+/// if the setup was cancelled
+fn select(
+    input: &mut impl WizardInput,
+    output: &mut impl Write,
+    question: &str,
+    options: &[String],
+    default: usize,
+) -> Result<Option<usize>> {
+    let mut selected = default.min(options.len() - 1);
+
+    writeln!(output, "{question} (Up/Down, Enter to choose):")?;
+    draw_options(output, options, selected)?;
+
+    loop {
+        selected = match input.read_key()? {
+            Key::Up => selected.checked_sub(1).unwrap_or(options.len() - 1),
+            Key::Down => (selected + 1) % options.len(),
+            Key::Enter => return Ok(Some(selected)),
+            Key::Cancel => return Ok(None),
+        };
+
+        // Draw the options again over the previous ones
+        queue!(output, MoveUp(options.len() as u16))?;
+        draw_options(output, options, selected)?;
+    }
+}
+
+/// Options of a selection, one per line with the selected one marked
+fn draw_options(output: &mut impl Write, options: &[String], selected: usize) -> Result<()> {
+    for (i, option) in options.iter().enumerate() {
+        queue!(output, Clear(ClearType::CurrentLine))?;
+        match i == selected {
+            true => writeln!(output, "\r  > {option}")?,
+            false => writeln!(output, "\r    {option}")?,
+        }
+    }
+
+    output.flush()?;
+
+    Ok(())
+}
+
+/// Ask for a line of the answer (trimmed), `None` at the end of the input
+fn prompt(
+    input: &mut impl WizardInput,
+    output: &mut impl Write,
+    question: &str,
+) -> Result<Option<String>> {
+    write!(output, "{question}: ")?;
+    output.flush()?;
+
+    let answer = input.read_line()?;
+    if answer.is_none() {
+        writeln!(output)?;
+    }
+
+    Ok(answer.map(|answer| answer.trim().to_string()))
+}
+
+/// Ask for the API key of the provider until there is one. `Some(None)` keeps the saved one,
+/// `None` if the setup was cancelled
+fn api_key(
+    input: &mut impl WizardInput,
+    output: &mut impl Write,
+    provider: Provider,
+    saved: bool,
+) -> Result<Option<Option<String>>> {
+    let question = match saved {
+        true => format!("API key for {provider} (empty to keep the saved one)"),
+        false => format!("API key for {provider}"),
+    };
+
+    loop {
+        let Some(api_key) = prompt(input, output, &question)? else {
+            return Ok(None);
+        };
+
+        match (api_key.is_empty(), saved) {
+            (false, _) => return Ok(Some(Some(api_key))),
+            (true, true) => return Ok(Some(None)),
+            (true, false) => writeln!(output, "{provider} can't be used without an API key")?,
+        }
+    }
+}
+
+/// Ask for the default location until one is found and confirmed, or skipped with an empty answer
+/// (which keeps the configured one). `None` if the setup was cancelled
+fn default_location(
+    input: &mut impl WizardInput,
+    output: &mut impl Write,
+    config: &Config,
+    resolve: impl Fn(&str) -> Result<SavedLocation>,
+) -> Result<Option<Option<SavedLocation>>> {
+    let question = match &config.default_location {
+        Some(location) => format!(
+            "Default location (address, \"lat, lon\" or @name, empty to keep {})",
+            location.address
+        ),
+        None => "Default location (address, \"lat, lon\" or @name, empty to skip)".to_string(),
+    };
+
+    loop {
+        let Some(address) = prompt(input, output, &question)? else {
+            return Ok(None);
+        };
+
+        if address.is_empty() {
+            return Ok(Some(config.default_location.clone()));
+        }
+
+        // Not found (or not understood), the address can be typed again
+        let location = match address.starts_with('@') {
+            true => config.location(&address),
+            false => resolve(&address),
+        };
+        let location = match location {
+            Ok(location) => location,
+            Err(e) => {
+                writeln!(output, "{e}")?;
+                continue;
+            }
+        };
+
+        let question = format!(
+            "Found {} ({}, {}), use it? [Y/n]",
+            location.address, location.lat, location.lon
+        );
+        match confirm(input, output, &question)? {
+            Some(true) => return Ok(Some(Some(location))),
+            Some(false) => continue,
+            None => return Ok(None),
+        }
+    }
+}
+
+/// Ask a yes or no question until it's answered (yes by default), `None` if the setup was
+/// cancelled
+fn confirm(
+    input: &mut impl WizardInput,
+    output: &mut impl Write,
+    question: &str,
+) -> Result<Option<bool>> {
+    loop {
+        let Some(answer) = prompt(input, output, question)? else {
+            return Ok(None);
+        };
+
+        match answer.to_lowercase().as_str() {
+            "" | "y" | "yes" => return Ok(Some(true)),
+            "n" | "no" => return Ok(Some(false)),
+            _ => writeln!(output, "Answer y or n")?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+    use crate::{error::WeatherError, paths::Paths};
+
+    /// Keys and lines of the answers, in the order they're asked for (the end of the input once
+    /// they run out)
+    #[derive(Default)]
+    struct Script {
+        keys: VecDeque<Key>,
+        lines: VecDeque<&'static str>,
+    }
+
+    impl WizardInput for Script {
+        fn read_key(&mut self) -> Result<Key> {
+            Ok(self.keys.pop_front().unwrap_or(Key::Cancel))
+        }
+
+        fn read_line(&mut self) -> Result<Option<String>> {
+            Ok(self.lines.pop_front().map(String::from))
+        }
+    }
+
+    /// The default config, saved in a directory of the test
+    fn config(name: &str) -> Config {
+        let dir =
+            std::env::temp_dir().join(format!("weather-wizard-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        Config::new(&Paths {
+            config_dir: dir.clone(),
+            cache_dir: dir.join("cache"),
+            state_dir: dir.join("state"),
+        })
+        .unwrap()
+    }
+
+    /// Only Kyiv can be found
+    fn resolve(address: &str) -> Result<SavedLocation> {
+        match address {
+            "Kyiv" => Ok(SavedLocation {
+                address: "Kyiv, Ukraine".to_string(),
+                lat: 50.45,
+                lon: 30.52,
+                elevation: None,
+                timezone: Some("Europe/Kyiv".to_string()),
+                country_code: Some("UA".to_string()),
+            }),
+            _ => Err(WeatherError::Geocoding(format!(
+                "Could not find location '{address}'"
+            ))),
+        }
+    }
+
+    /// Run the setup with the script, and what it wrote
+    fn run_script(config: &mut Config, mut script: Script) -> String {
+        let mut output = Vec::new();
+        run(config, &mut script, &mut output, resolve).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn every_answer_saved() {
+        let mut config = config("answers");
+        let script = Script {
+            // met_no, fahrenheit and the 24 hour clock (up from the locale one past the end)
+            keys: [
+                Key::Down,
+                Key::Enter,
+                Key::Down,
+                Key::Enter,
+                Key::Up,
+                Key::Enter,
+            ]
+            .into(),
+            // Not found, then found but not confirmed until the second time
+            lines: ["Nowhere", "Kyiv", "maybe", "n", "  Kyiv ", "y"].into(),
+        };
+
+        let output = run_script(&mut config, script);
+
+        assert_eq!(config.provider, Provider::MetNo);
+        assert_eq!(config.unit, TemperatureUnit::Fahrenheit);
+        assert_eq!(
+            config.time_format,
+            Some(TimeFormat::from_str("24h").unwrap())
+        );
+        let location = config.default_location.as_ref().unwrap();
+        assert_eq!(
+            (location.address.as_str(), location.lat, location.lon),
+            ("Kyiv, Ukraine", 50.45, 30.52)
+        );
+
+        // Asked again after every answer that wasn't one
+        assert!(
+            output.contains("Could not find location 'Nowhere'\n"),
+            "{output}"
+        );
+        assert_eq!(output.matches("Answer y or n").count(), 1, "{output}");
+        assert_eq!(output.matches("Found Kyiv, Ukraine").count(), 3, "{output}");
+
+        // And all of them end up in the file
+        let saved = std::fs::read_to_string(config.file_path()).unwrap();
+        assert!(output.ends_with(&format!(
+            "Config saved to {}\n",
+            config.file_path().display()
+        )));
+        for value in [
+            "\"met_no\"",
+            "\"fahrenheit\"",
+            "\"24h\"",
+            "\"Kyiv, Ukraine\"",
+        ] {
+            assert!(saved.contains(value), "{value}:\n{saved}");
+        }
+    }
+
+    #[test]
+    fn empty_answers_keep_the_config() {
+        let mut config = config("keep");
+        config.default_location = resolve("Kyiv").ok();
+        let script = Script {
+            keys: [Key::Enter, Key::Enter, Key::Enter].into(),
+            lines: [""].into(),
+        };
+
+        run_script(&mut config, script);
+
+        // The defaults are selected at first, the clock of the locale is the last option
+        assert_eq!(config.provider, Provider::OpenMeteo);
+        assert_eq!(config.unit, TemperatureUnit::Celsius);
+        assert_eq!(config.time_format, None);
+        assert!(config.default_location.is_some());
+    }
+
+    #[test]
+    fn api_key_asked_until_given() {
+        let mut config = config("api-key");
+        let script = Script {
+            keys: [Key::Up, Key::Enter, Key::Enter, Key::Enter].into(),
+            lines: ["", " secret ", ""].into(),
+        };
+
+        let output = run_script(&mut config, script);
+
+        assert_eq!(config.provider, Provider::OpenWeatherMap);
+        assert_eq!(
+            config
+                .api_keys
+                .get(&Provider::OpenWeatherMap)
+                .map(String::as_str),
+            Some("secret")
+        );
+        assert_eq!(
+            output
+                .matches("open_weather_map can't be used without an API key")
+                .count(),
+            1,
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn cancelled_setup_saves_nothing() {
+        let cases = [
+            // Esc at the unit
+            Script {
+                keys: [Key::Down, Key::Enter, Key::Cancel].into(),
+                ..Script::default()
+            },
+            // The input ends at the location
+            Script {
+                keys: [Key::Down, Key::Enter, Key::Enter, Key::Enter].into(),
+                lines: ["Nowhere"].into(),
+            },
+        ];
+
+        for (i, script) in cases.into_iter().enumerate() {
+            let mut config = config(&format!("cancelled-{i}"));
+            let before = std::fs::read_to_string(config.file_path()).unwrap();
+
+            let output = run_script(&mut config, script);
+
+            assert!(output.ends_with("Setup cancelled, the answers weren't saved\n"));
+            assert_eq!(config.provider, Provider::OpenMeteo, "{output}");
+            assert_eq!(std::fs::read_to_string(config.file_path()).unwrap(), before);
+        }
+    }
+}
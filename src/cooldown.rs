@@ -0,0 +1,229 @@
+//! Rate limiting cooldowns, persisted across runs so that a throttled API is not hit again by
+//! every following invocation (e.g. from cron) until it's ready to talk to us again
+
+use std::{collections::HashMap, path::PathBuf};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    error::{Result, WeatherError},
+    paths::Paths,
+};
+
+/// How long to back off if the API didn't tell us itself
+pub(crate) const DEFAULT_COOLDOWN_HOURS: i64 = 1;
+
+/// Longest cooldown taken from a `Retry-After`, so that a broken one doesn't lock the API out for
+/// good
+const MAX_COOLDOWN_HOURS: i64 = 24;
+
+#[derive(Debug, Default)]
+pub(crate) struct Cooldowns {
+    /// Host -> time until which no requests should be sent to it
+    hosts: HashMap<String, DateTime<Utc>>,
+    file_path: PathBuf,
+}
+
+impl Cooldowns {
+    pub(crate) fn load(paths: &Paths) -> Self {
+        let file_path = paths.state_dir.join("cooldowns.json");
+
+        // A missing or broken file just means there are no cooldowns we know about
+        let hosts = std::fs::read_to_string(&file_path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Self { hosts, file_path }
+    }
+
+    /// Get the time until which the host shouldn't be contacted, if that time is still ahead
+    pub(crate) fn active(&self, host: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.hosts.get(host).copied().filter(|until| *until > now)
+    }
+
+    /// Remember that the host shouldn't be contacted until the given time
+    pub(crate) fn start(&mut self, host: &str, until: DateTime<Utc>) {
+        self.hosts.insert(host.to_string(), until);
+        self.save();
+    }
+
+    /// Forget about the host cooldown (only touches the file if there was one)
+    pub(crate) fn clear(&mut self, host: &str) {
+        if self.hosts.remove(host).is_some() {
+            self.save();
+        }
+    }
+
+    /// Save the cooldowns, if the state location is writable (the worst that can happen otherwise
+    /// is an extra request on the next run, so it's not worth failing over)
+    fn save(&self) {
+        let _ = self.try_save();
+    }
+
+    fn try_save(&self) -> Result<()> {
+        if let Some(dir) = self.file_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        std::fs::write(&self.file_path, serde_json::to_string_pretty(&self.hosts)?)?;
+
+        Ok(())
+    }
+}
+
+/// Check if the response means that we are being rate limited, and if so, until when
+pub(crate) fn throttled_until(
+    response: &reqwest::blocking::Response,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    cooldown_until(
+        response.status(),
+        response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|retry_after| retry_after.to_str().ok()),
+        now,
+    )
+}
+
+/// Time until which the API wants us to back off, if the status says that it does
+fn cooldown_until(
+    status: reqwest::StatusCode,
+    retry_after: Option<&str>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+
+    retry_after
+        .and_then(|retry_after| parse_retry_after(retry_after, now))
+        .or_else(|| now.checked_add_signed(Duration::hours(DEFAULT_COOLDOWN_HOURS)))
+}
+
+/// Parse the `Retry-After` header value, which is either a number of seconds or an HTTP date. It
+/// comes from the server, so it's kept between now and [`MAX_COOLDOWN_HOURS`] from now
+fn parse_retry_after(retry_after: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let max_cooldown = Duration::hours(MAX_COOLDOWN_HOURS);
+    let latest = now.checked_add_signed(max_cooldown)?;
+
+    let until = match retry_after.trim().parse::<i64>() {
+        Ok(seconds) => now.checked_add_signed(Duration::try_seconds(
+            seconds.clamp(0, max_cooldown.num_seconds()),
+        )?)?,
+        Err(_) => DateTime::parse_from_rfc2822(retry_after.trim())
+            .ok()?
+            .with_timezone(&Utc),
+    };
+
+    Some(until.clamp(now, latest))
+}
+
+/// Error for the requests that were not sent because of the cooldown
+pub(crate) fn cooldown_error(who: impl std::fmt::Display, until: DateTime<Utc>) -> WeatherError {
+    WeatherError::RateLimited {
+        who: who.to_string(),
+        until,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-06-14T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn retry_after_seconds() {
+        let until = cooldown_until(StatusCode::TOO_MANY_REQUESTS, Some("120"), now());
+
+        assert_eq!(until, Some(now() + Duration::seconds(120)));
+    }
+
+    #[test]
+    fn retry_after_http_date() {
+        let until = cooldown_until(
+            StatusCode::TOO_MANY_REQUESTS,
+            Some("Fri, 14 Jun 2024 12:30:00 GMT"),
+            now(),
+        );
+
+        assert_eq!(until, Some(now() + Duration::minutes(30)));
+    }
+
+    #[test]
+    fn without_retry_after_the_default_cooldown() {
+        let default = Some(now() + Duration::hours(DEFAULT_COOLDOWN_HOURS));
+
+        assert_eq!(
+            cooldown_until(StatusCode::TOO_MANY_REQUESTS, None, now()),
+            default
+        );
+        assert_eq!(
+            cooldown_until(StatusCode::TOO_MANY_REQUESTS, Some("soon"), now()),
+            default
+        );
+    }
+
+    #[test]
+    fn retry_after_out_of_range() {
+        let latest = now() + Duration::hours(MAX_COOLDOWN_HOURS);
+
+        for (retry_after, expected) in [
+            ("9223372036854775807", latest),
+            ("9223372036854775", latest),
+            ("-60", now()),
+            ("-9223372036854775808", now()),
+            ("Fri, 14 Jun 2024 11:00:00 GMT", now()),
+            ("14 Jun 2124 12:00:00 GMT", latest),
+        ] {
+            assert_eq!(
+                parse_retry_after(retry_after, now()),
+                Some(expected),
+                "{retry_after}"
+            );
+        }
+    }
+
+    #[test]
+    fn only_429_is_a_cooldown() {
+        for status in [
+            StatusCode::OK,
+            StatusCode::FORBIDDEN,
+            StatusCode::SERVICE_UNAVAILABLE,
+        ] {
+            assert_eq!(cooldown_until(status, Some("120"), now()), None);
+        }
+    }
+
+    #[test]
+    fn persisted_across_runs() {
+        let dir = std::env::temp_dir().join(format!("weather-cooldown-{}", std::process::id()));
+        let paths = Paths {
+            state_dir: dir.clone(),
+            ..Paths::default()
+        };
+        let until = now() + Duration::minutes(10);
+
+        Cooldowns::load(&paths).start("api.met.no", until);
+
+        // The next run sees it until it's over, and only for that host
+        let cooldowns = Cooldowns::load(&paths);
+        assert_eq!(cooldowns.active("api.met.no", now()), Some(until));
+        assert_eq!(cooldowns.active("api.open-meteo.com", now()), None);
+        assert_eq!(cooldowns.active("api.met.no", until), None);
+
+        // A successful request clears it for the runs after it
+        Cooldowns::load(&paths).clear("api.met.no");
+        assert_eq!(Cooldowns::load(&paths).active("api.met.no", now()), None);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,318 @@
+//! Coordinates typed in place of an address: "50.45, 30.52", "50.45 30.52", "50,45; 30,52",
+//! "50.45N, 30.52E", "30.52E 50.45N" and "50°27'13"N 30°31'25"E"
+
+use itertools::Itertools;
+
+/// Characters before and after the pair that are not a part of it ("(50.45, 30.52).")
+const LEADING_JUNK: &[char] = &['(', '[', '{'];
+const TRAILING_JUNK: &[char] = &[')', ']', '}', '.', ',', ';', ':', '!', '?'];
+
+/// Which coordinate a cardinal direction is for
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Axis {
+    Latitude,
+    Longitude,
+}
+
+/// Part of a degrees-minutes-seconds coordinate
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Unit {
+    Degrees,
+    Minutes,
+    Seconds,
+}
+
+/// Coordinate with the direction it was suffixed (or prefixed) with, if any
+#[derive(Debug, Copy, Clone)]
+struct Coordinate {
+    value: f64,
+    axis: Option<Axis>,
+}
+
+/// Parse the latitude and the longitude (in this order) out of the input, if it's a pair of
+/// coordinates and nothing else. The directions decide which one is which ("30.52E 50.45N"), and
+/// turn the southern and western ones negative. The ranges are not checked here, so that the
+/// error can say what was wrong with the values
+pub(crate) fn parse_coordinates(input: &str) -> Option<(f64, f64)> {
+    let input = input
+        .trim_start_matches(|c: char| c.is_whitespace() || LEADING_JUNK.contains(&c))
+        .trim_end_matches(|c: char| c.is_whitespace() || TRAILING_JUNK.contains(&c));
+
+    pair_candidates(input)
+        .into_iter()
+        .filter_map(|(first, second)| {
+            order(
+                parse_coordinate(&decimal_commas(&first))?,
+                parse_coordinate(&decimal_commas(&second))?,
+            )
+        })
+        // The splits that can be read in more than one way are left to the geocoder
+        .exactly_one()
+        .ok()
+}
+
+/// The ways to split the input into two coordinates: on the semicolon if there is one, then on
+/// the comma (the one followed by a space if the commas are decimal separators too, as in
+/// "50,45, 30,52"), then on every space in between (for "50.45 30.52", where a coordinate can have
+/// spaces of its own)
+fn pair_candidates(input: &str) -> Vec<(String, String)> {
+    let owned = |(first, second): (&str, &str)| vec![(first.to_string(), second.to_string())];
+
+    if let Some(pair) = input.split_once(';') {
+        return owned(pair);
+    }
+
+    let separators = input
+        .match_indices(',')
+        .filter(|(i, _)| input[i + 1..].starts_with(char::is_whitespace))
+        .map(|(i, _)| i)
+        .collect_vec();
+
+    match (input.matches(',').count(), separators.as_slice()) {
+        (0, _) => whitespace_candidates(input),
+        (1, _) => input.split_once(',').map(owned).unwrap_or_default(),
+        (_, [i]) => owned((&input[..*i], &input[i + 1..])),
+        // "50,45 30,52"
+        (_, []) => whitespace_candidates(input),
+        _ => Vec::new(),
+    }
+}
+
+/// Every split of the input on a space
+fn whitespace_candidates(input: &str) -> Vec<(String, String)> {
+    let words = input.split_whitespace().collect_vec();
+
+    (1..words.len())
+        .map(|i| (words[..i].join(" "), words[i..].join(" ")))
+        .collect()
+}
+
+/// Take the commas in a coordinate as decimal separators ("50,45")
+fn decimal_commas(coordinate: &str) -> String {
+    coordinate.replace(',', ".")
+}
+
+/// Figure out which one is the latitude from the directions (they go first and second otherwise)
+fn order(first: Coordinate, second: Coordinate) -> Option<(f64, f64)> {
+    use Axis::*;
+
+    match (first.axis, second.axis) {
+        (Some(Longitude), None | Some(Latitude)) | (None, Some(Latitude)) => {
+            Some((second.value, first.value))
+        }
+        (Some(Latitude), None | Some(Longitude)) | (None, Some(Longitude)) | (None, None) => {
+            Some((first.value, second.value))
+        }
+        // "50N 30N"
+        (Some(Latitude), Some(Latitude)) | (Some(Longitude), Some(Longitude)) => None,
+    }
+}
+
+/// Parse a single coordinate: decimal degrees ("-50.45"), or degrees, minutes and seconds
+/// ("50°27'13.5"", "50° 27.2'"), with an optional direction before or after it ("50.45N",
+/// "N 50.45")
+fn parse_coordinate(coordinate: &str) -> Option<Coordinate> {
+    let coordinate = coordinate.trim();
+
+    // The direction is either the first or the last character, never both
+    let direction = |c: Option<char>| c.and_then(|c| direction(c.to_ascii_uppercase()));
+    let (direction, rest) = match (
+        direction(coordinate.chars().next()),
+        direction(coordinate.chars().last()),
+    ) {
+        (Some(_), Some(_)) => return None,
+        (Some(direction), None) => (Some(direction), &coordinate[1..]),
+        (None, Some(direction)) => (Some(direction), &coordinate[..coordinate.len() - 1]),
+        (None, None) => (None, coordinate),
+    };
+
+    let parts = dms_parts(rest.trim())?;
+    let value = match parts.as_slice() {
+        // Plain decimal degrees
+        [(value, None | Some(Unit::Degrees))] => *value,
+        // The minutes and the seconds always have the marks, in this order and after the degrees
+        [(degrees, Some(Unit::Degrees)), rest @ ..]
+            if rest
+                .iter()
+                .map(|(_, unit)| *unit)
+                .tuple_windows()
+                .all(|(a, b)| a < b)
+                && rest
+                    .iter()
+                    .all(|(value, unit)| unit.is_some() && (0.0..60.0).contains(value)) =>
+        {
+            let fraction = rest
+                .iter()
+                .map(|(value, unit)| match unit {
+                    Some(Unit::Minutes) => value / 60.0,
+                    _ => value / 3600.0,
+                })
+                .sum::<f64>();
+
+            match degrees.is_sign_negative() {
+                true => degrees - fraction,
+                false => degrees + fraction,
+            }
+        }
+        _ => return None,
+    };
+
+    match direction {
+        // The direction already says which side of the equator (or the meridian) it's on
+        Some(_) if value.is_sign_negative() => None,
+        Some((axis, sign)) => Some(Coordinate {
+            value: sign * value,
+            axis: Some(axis),
+        }),
+        None => Some(Coordinate { value, axis: None }),
+    }
+}
+
+/// Axis and the sign of a cardinal direction
+fn direction(c: char) -> Option<(Axis, f64)> {
+    match c {
+        'N' => Some((Axis::Latitude, 1.0)),
+        'S' => Some((Axis::Latitude, -1.0)),
+        'E' => Some((Axis::Longitude, 1.0)),
+        'W' => Some((Axis::Longitude, -1.0)),
+        _ => None,
+    }
+}
+
+/// Split "50°27'13"" into the numbers with their marks, `None` if anything else is in there
+fn dms_parts(mut rest: &str) -> Option<Vec<(f64, Option<Unit>)>> {
+    let mut parts = Vec::new();
+
+    while !rest.is_empty() {
+        // The sign only makes sense for the degrees, the minutes and the seconds are checked to be
+        // positive later
+        let end = rest
+            .char_indices()
+            .find(|(i, c)| !(c.is_ascii_digit() || *c == '.' || (*i == 0 && "+-".contains(*c))))
+            .map_or(rest.len(), |(i, _)| i);
+        let value = rest[..end]
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())?;
+        rest = rest[end..].trim_start();
+
+        let (unit, len) = match rest.chars().next() {
+            Some(c @ ('°' | 'º')) => (Some(Unit::Degrees), c.len_utf8()),
+            // Two apostrophes are often typed instead of the double quote
+            _ if rest.starts_with("''") => (Some(Unit::Seconds), 2),
+            Some(c @ ('\'' | '′' | '’')) => (Some(Unit::Minutes), c.len_utf8()),
+            Some(c @ ('"' | '″' | '”')) => (Some(Unit::Seconds), c.len_utf8()),
+            // Only the last number can go without a mark
+            None => (None, 0),
+            Some(_) => return None,
+        };
+        rest = rest[len..].trim_start();
+
+        if parts.iter().any(|(_, other)| *other == unit) || (unit.is_none() && !parts.is_empty()) {
+            return None;
+        }
+
+        parts.push((value, unit));
+    }
+
+    Some(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Close enough for the seconds of DMS
+    fn assert_parsed(input: &str, expected: Option<(f64, f64)>) {
+        let parsed = parse_coordinates(input);
+
+        match (parsed, expected) {
+            (Some((lat, lon)), Some((expected_lat, expected_lon))) => assert!(
+                (lat - expected_lat).abs() < 1e-6 && (lon - expected_lon).abs() < 1e-6,
+                "{input}: got ({lat}, {lon}), expected ({expected_lat}, {expected_lon})"
+            ),
+            _ => assert_eq!(parsed, expected, "{input}"),
+        }
+    }
+
+    #[test]
+    fn coordinate_pairs() {
+        let kyiv = Some((50.45, 30.52));
+        let kyiv_dms = Some((
+            50.0 + 27.0 / 60.0 + 13.0 / 3600.0,
+            30.0 + 31.0 / 60.0 + 25.0 / 3600.0,
+        ));
+
+        for (input, expected) in [
+            // Separators
+            ("50.45, 30.52", kyiv),
+            ("50.45,30.52", kyiv),
+            ("50.45 30.52", kyiv),
+            ("50.45   30.52", kyiv),
+            ("50.45; 30.52", kyiv),
+            ("50.45;30.52", kyiv),
+            // Decimal commas
+            ("50,45, 30,52", kyiv),
+            ("50,45; 30,52", kyiv),
+            ("50,45 30,52", kyiv),
+            // Signs
+            ("-33.87, 151.21", Some((-33.87, 151.21))),
+            ("+50.45, -30.52", Some((50.45, -30.52))),
+            ("0, 0", Some((0.0, 0.0))),
+            // Hemisphere letters, before or after, in any case
+            ("50.45N, 30.52E", kyiv),
+            ("50.45n 30.52e", kyiv),
+            ("N 50.45, E 30.52", kyiv),
+            ("33.87S, 151.21E", Some((-33.87, 151.21))),
+            ("40.71N 74.01W", Some((40.71, -74.01))),
+            // Swapped order, told apart by the letters
+            ("30.52E 50.45N", kyiv),
+            ("30.52E, 50.45N", kyiv),
+            ("74.01W; 40.71N", Some((40.71, -74.01))),
+            ("30.52E 50.45", kyiv),
+            // Degrees, minutes and seconds
+            ("50°27'13\"N 30°31'25\"E", kyiv_dms),
+            ("50° 27' 13\" N, 30° 31' 25\" E", kyiv_dms),
+            ("50°27′13″N 30°31′25″E", kyiv_dms),
+            ("50°27'13''N 30°31'25''E", kyiv_dms),
+            (
+                "50°27.5'N 30°31.5'E",
+                Some((50.0 + 27.5 / 60.0, 30.0 + 31.5 / 60.0)),
+            ),
+            (
+                "33°52'S 151°12'E",
+                Some((-(33.0 + 52.0 / 60.0), 151.0 + 12.0 / 60.0)),
+            ),
+            ("50°, 30°", Some((50.0, 30.0))),
+            // Junk around the pair
+            ("(50.45, 30.52)", kyiv),
+            ("[50.45, 30.52].", kyiv),
+            ("  50.45, 30.52  ", kyiv),
+            // Out of range values are still coordinates, the range is checked by the caller
+            ("95, 30", Some((95.0, 30.0))),
+            ("50, 200", Some((50.0, 200.0))),
+            ("-91.5, -181", Some((-91.5, -181.0))),
+            // Addresses
+            ("Paris, France", None),
+            ("Kyiv", None),
+            ("221B Baker Street, London", None),
+            ("10 Downing Street", None),
+            ("Route 66, 12", None),
+            ("", None),
+            // Not quite coordinates
+            ("50.45", None),
+            ("50.45, 30.52, 10", None),
+            ("50.45N 30.52N", None),
+            ("30.52E 50.45E", None),
+            ("N50.45S, 30.52", None),
+            ("-50.45N, 30.52E", None),
+            ("50°61'N 30°E", None),
+            ("50'27°N 30°E", None),
+            ("50.45x, 30.52", None),
+            ("NaN, 30", None),
+            ("inf, 30", None),
+        ] {
+            assert_parsed(input, expected);
+        }
+    }
+}
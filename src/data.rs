@@ -1,331 +1,1681 @@
-use std::fmt::{Display, Formatter};
-
-use color_eyre::eyre;
-use itertools::{
-    FoldWhile::{Continue, Done},
-    Itertools,
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Display, Formatter},
 };
+
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, Utc};
+use itertools::Itertools;
+use serde::de::DeserializeOwned;
 use serde_json::{Map, Value};
 
-use crate::providers::{Provider, ProviderRequestType};
+use crate::{
+    astro,
+    config::{TemperatureUnit, TimeFormat},
+    date::DateRange,
+    error::{Result, WeatherError},
+    geocode::ResolvedLocation,
+    locale::{Language, Text},
+    providers::{Provider, ProviderRequestType},
+};
+
+#[derive(Default, Debug, Clone)]
+#[non_exhaustive]
+pub struct WeatherData {
+    pub provider: Provider,
+    pub request_type: ProviderRequestType,
+
+    pub requested_date: String,
+    /// Whole days requested (if the date was a range)
+    pub requested_range: Option<DateRange>,
+    pub location: ResolvedLocation,
+    /// Elevation of the location (in m) the provider used, the requested one or its own guess
+    /// (`None` if the provider doesn't say)
+    pub elevation: Option<f64>,
+
+    /// Hours of the data, in the order the hourly series are in
+    pub timestamps: Vec<Timestamp>,
+    /// Hourly temperatures, lined up with the timestamps (NaN for the hours the provider didn't
+    /// return them for)
+    pub temperatures: Vec<f64>,
+    pub unit: String,
+    /// Clock the timestamps (and the time of the current conditions) are shown in
+    pub time_format: TimeFormat,
+
+    /// Timezone the timestamps, the current conditions and the sunrise/sunset are in ("Asia/Tokyo",
+    /// "UTC+09:00" if the provider only has the offset)
+    pub timezone: String,
+
+    /// Hourly apparent ("feels like") temperatures in the same unit as the temperatures, and the
+    /// relative humidity in %, lined up with the timestamps (empty if the provider didn't return
+    /// them, NaN for the hours it didn't return them for)
+    pub apparent_temperatures: Vec<f64>,
+    pub humidity: Vec<f64>,
 
-#[derive(Default, Debug)]
-pub(crate) struct WeatherData {
-    pub(crate) provider: Provider,
-    pub(crate) request_type: ProviderRequestType,
+    /// Hourly air pressure and cloud cover in %, lined up with the timestamps (empty if the
+    /// provider didn't return them, NaN for the hours it didn't return them for)
+    pub pressures: Vec<f64>,
+    pub pressure_unit: String,
+    pub cloud_cover: Vec<f64>,
 
-    pub(crate) requested_date: String,
-    pub(crate) address: String,
+    /// Hourly wind, lined up with the timestamps (empty if the provider didn't return it, NaN
+    /// speeds for the hours it didn't return it for)
+    pub wind_speeds: Vec<f64>,
+    pub wind_directions: Vec<WindDirection>,
+    pub wind_speed_unit: String,
+    /// Hourly wind gusts in the unit of the wind speeds, lined up with the timestamps (empty if the
+    /// provider didn't return them, NaN for the hours it didn't return them for)
+    pub wind_gusts: Vec<f64>,
 
-    pub(crate) latitude: f64,
-    pub(crate) longitude: f64,
+    /// Hourly precipitation, lined up with the timestamps (empty if the provider didn't return it,
+    /// NaN for the hours it didn't return it for)
+    pub precipitation: Vec<f64>,
+    pub precipitation_unit: String,
+    /// Hourly chance of precipitation in % (empty if the provider doesn't have it)
+    pub precipitation_probabilities: Vec<f64>,
+    /// Hourly UV index, lined up with the timestamps (empty if the provider doesn't have it, NaN
+    /// for the hours it didn't return it for)
+    pub uv_indices: Vec<f64>,
 
-    pub(crate) timestamps: Vec<String>,
-    pub(crate) temperatures: Vec<f64>,
-    pub(crate) unit: String,
+    /// Hourly conditions, lined up with the timestamps (empty if the provider didn't return them)
+    pub weather_codes: Vec<WeatherCode>,
 
-    pub(crate) current: Option<CurrentWeatherData>,
+    /// Sunrise and sunset on the (first) requested day, in the same time as the timestamps (`None`
+    /// if the sun doesn't rise or set that day, or if the provider doesn't have them)
+    pub sunrise: Option<String>,
+    pub sunset: Option<String>,
+
+    /// Per day summary of the temperatures (only if it was requested)
+    pub daily: Option<DailyWeatherData>,
+
+    pub current: Option<CurrentWeatherData>,
+    /// Weather alerts for the location that haven't expired yet (only met_no has them)
+    pub alerts: Vec<WeatherAlert>,
+    /// Air quality of the day, if it was asked for (only open_meteo has it)
+    pub air_quality: Option<AirQualityData>,
+    /// Moon and the length of the day, if they were asked for (calculated, whatever the provider)
+    pub astro: Option<AstroData>,
+    /// Precipitation of the next minutes, if it was asked for (met_no and open_meteo have it)
+    pub nowcast: Option<NowcastData>,
+    /// Index of the current hour in the hourly series (`None` if the data doesn't include it)
+    pub current_hour: Option<usize>,
+    /// Index of the hour emphasized on the chart: the requested one if the date had the time of the
+    /// day in it, the current one otherwise (`None` if the data doesn't include it)
+    pub highlight_hour: Option<usize>,
+
+    /// Warning about the quality of the data
+    pub caveat: Option<String>,
+    /// What went wrong while getting the data, apart from what the data itself shows (see
+    /// [`WeatherData::degradations`] for all of them)
+    pub degradations: Vec<Degradation>,
+
+    /// Where every hour comes from, lined up with the timestamps (empty if all of them were just
+    /// fetched)
+    pub sources: Vec<DataSource>,
+    /// When the data was fetched, if it's from the cache instead of the provider
+    pub cached_at: Option<DateTime<Utc>>,
+    /// When the response was received (from the provider, even if it's from the cache now)
+    pub fetched_at: DateTime<Utc>,
+    /// When the provider computed the forecast (only met_no says)
+    pub data_updated_at: Option<DateTime<Utc>>,
+
+    /// The same location a day earlier, to compare the day with (only if it was asked for)
+    pub comparison: Option<Box<WeatherData>>,
 }
 
 impl WeatherData {
-    pub(crate) fn from_json(
+    #[tracing::instrument(skip_all, fields(provider = %provider))]
+    pub fn from_json(
         json: &Map<String, Value>,
         provider: Provider,
         request_type: ProviderRequestType,
         requested_date: String,
-        address: String,
-    ) -> eyre::Result<Self> {
+        requested_range: Option<DateRange>,
+        location: ResolvedLocation,
+        (daily, time_format): (bool, TimeFormat),
+    ) -> Result<Self> {
         let res = Self {
             provider,
             request_type,
             requested_date,
-            address,
+            requested_range,
+            location,
+            time_format,
+            // Filled in by the parsers
+            daily: daily.then(DailyWeatherData::default),
             ..Default::default()
         };
 
-        // Parse the json based on the provider
-        match &res.provider {
-            Provider::OpenMeteo => res.parse_open_meteo_json(json),
-            Provider::MetNo => res.parse_met_no_json(json),
-        }
-    }
-
-    fn parse_open_meteo_json(mut self, json: &Map<String, Value>) -> eyre::Result<Self> {
-        if let (Some(Value::Bool(true)), Some(Value::String(reason))) =
-            (json.get("error"), json.get("reason"))
-        {
-            return Err(eyre::eyre!("Error response from open_meteo: {}", reason));
-        }
-
-        self.latitude = json
-            .get("latitude")
-            .and_then(|l| l.as_f64())
-            .ok_or(eyre::eyre!("Latitude not found"))?;
-        self.longitude = json
-            .get("longitude")
-            .and_then(|l| l.as_f64())
-            .ok_or(eyre::eyre!("Longitude not found"))?;
-
-        (self.timestamps, self.temperatures) = {
-            let hourly = json
-                .get("hourly")
-                .ok_or(eyre::eyre!("Hourly data not found"))?;
-
-            match hourly {
-                Value::Object(hourly) => {
-                    let time = hourly.get("time").ok_or(eyre::eyre!("Time not found"))?;
-
-                    let timestamps = match time {
-                        Value::Array(time) => {
-                            let timestamps = time
-                                .clone()
-                                .into_iter()
-                                .map(|t| t.as_str().map(|t| t.replace('T', " ")))
-                                .collect_vec();
-
-                            // If any of the timestamps couldn't be parsed, return an error
-                            match timestamps.iter().any(|t| t.is_none()) {
-                                true => Err(eyre::eyre!("Couldn't parse timestamps")),
-                                false => {
-                                    let mapped_timestamps = timestamps
-                                        .into_iter()
-                                        .flatten() // We can fearlessly flatten here since we already checked for nulls in the match
-                                        .map_while(|t| {
-                                            let date = match dateparser::parse(&t) {
-                                                Ok(date) => date,
-                                                Err(err) => {
-                                                    panic!(
-                                                        "Couldn't parse timestamp ({t}): {}",
-                                                        err
-                                                    )
-                                                }
-                                            };
-
-                                            Some(date.format("%I %p").to_string())
-                                        })
-                                        .collect_vec();
-
-                                    match mapped_timestamps.len() == time.len() {
-                                        true => Ok(mapped_timestamps),
-                                        false => {
-                                            Err(eyre::eyre!("Couldn't reformat all the timestamps"))
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        _ => Err(eyre::eyre!("Couldn't parse timestamps")),
-                    }?;
-
-                    let temperatures = {
-                        let temperature = hourly
-                            .get("temperature_2m")
-                            .ok_or(eyre::eyre!("Temperature not found"))?;
-
-                        match temperature {
-                            Value::Array(temperature) => {
-                                let temperatures = temperature
-                                    .clone()
-                                    .into_iter()
-                                    .map(|t| t.as_f64())
-                                    .collect_vec();
-
-                                match temperatures.iter().any(|t| t.is_none()) {
-                                    true => Err(eyre::eyre!("Couldn't parse temperatures")),
-                                    false => Ok(temperatures.into_iter().flatten().collect_vec()),
-                                }
-                            }
-                            _ => Err(eyre::eyre!("Couldn't parse temperatures")),
-                        }
-                    }?;
-
-                    match timestamps.len() == temperatures.len() {
-                        true => Ok((timestamps, temperatures)),
-                        false => Err(eyre::eyre!("Mismatch in timestamps and temperatures data, please try a different provider/location/date")),
+        // Every provider has a format of its own
+        let mut data = provider.implementation().parse(json, res)?;
+        data.set_wind_chill();
+        data.dedup_hours();
+        tracing::debug!(
+            hours = data.timestamps.len(),
+            current = data.current.is_some(),
+            "Parsed the response"
+        );
+
+        Ok(data)
+    }
+
+    /// Days to pick out of the responses that always have the next few days (the requested day
+    /// itself is enough for the hourly data, but the daily summary needs the whole of it)
+    pub(crate) fn requested_days(&self) -> Option<DateRange> {
+        match (self.requested_range, &self.daily) {
+            (Some(range), _) => Some(range),
+            (None, Some(_)) => NaiveDate::parse_from_str(&self.requested_date, "%Y-%m-%d")
+                .ok()
+                .map(|date| DateRange {
+                    start: date,
+                    end: date,
+                }),
+            (None, None) => None,
+        }
+    }
+
+    /// Calculate the wind chill of the current conditions (none of the providers return it), if it's
+    /// cold and windy enough for it
+    fn set_wind_chill(&mut self) {
+        let unit = TemperatureUnit::from_provider_unit(&self.unit);
+
+        if let Some(current) = &mut self.current {
+            let temperature = TemperatureUnit::Celsius.convert(current.temperature, unit);
+
+            current.wind_chill = wind_speed_in_kmh(current.wind_speed, &current.wind_speed_unit)
+                .and_then(|wind_speed| wind_chill(temperature, wind_speed))
+                .map(|wind_chill| unit.convert(wind_chill, TemperatureUnit::Celsius));
+        }
+    }
+
+    /// Format of the chart labels, with the day of the week if there are multiple days
+    pub(crate) fn timestamp_format(&self) -> &'static str {
+        self.time_format.hour_format(self.requested_range.is_some())
+    }
+
+    /// Chart labels that fit the narrow bars, whatever the clock of the data is ("15", "Fri 15")
+    pub fn narrow_labels(&self) -> Vec<String> {
+        let format = TimeFormat::narrow_hour_format(self.requested_range.is_some());
+
+        self.timestamps
+            .iter()
+            .map(|timestamp| timestamp.time.format(format).to_string())
+            .collect()
+    }
+
+    /// Time of the current conditions in the clock of the data ("2024-01-01 03:15 PM")
+    pub fn current_time(&self) -> Option<String> {
+        let current = self.current.as_ref()?;
+
+        // The providers' times are parsed into the same format, so the time is left as is if it
+        // can't be read
+        Some(
+            NaiveDateTime::parse_from_str(&current.time, "%Y-%m-%d %H:%M")
+                .map(|time| time.format(self.time_format.time_format()).to_string())
+                .unwrap_or(current.time.clone()),
+        )
+    }
+
+    /// Lowest, highest and average hourly temperatures, and where they are heading
+    pub fn summary(&self) -> SeriesSummary {
+        SeriesSummary::new(&self.temperatures)
+    }
+
+    /// Index of the hour the time is in, found by its label (`None` if the data doesn't have it)
+    pub(crate) fn hour_index(&self, time: NaiveDateTime) -> Option<usize> {
+        let label = time.format(self.timestamp_format()).to_string();
+
+        self.timestamps.iter().position(|ts| ts.label == label)
+    }
+
+    /// Where the hour with the index comes from
+    pub fn source(&self, i: usize) -> DataSource {
+        self.sources.get(i).copied().unwrap_or_default()
+    }
+
+    /// Add the hours of an earlier response to the same request that this one doesn't have
+    /// anymore, marked as cached `age` ago. The forecasts start at the current hour, so the earlier
+    /// hours of the requested days drop out of them, but the earlier response still has them.
+    ///
+    /// Only the hours before the first one of this data are added, and only to the series this
+    /// data has (NaN where the earlier response doesn't have them). Nothing is added without the
+    /// requested days (the next hours don't include the earlier ones). The earlier series in other
+    /// units are converted to the ones of this data, and it fails if they can't be
+    pub(crate) fn merge_cached(&mut self, cached: WeatherData, age: Duration) -> Result<()> {
+        let (Some(range), Some(first)) = (self.requested_days(), self.timestamps.first()) else {
+            return Ok(());
+        };
+        let first = first.time;
+
+        let earlier = cached
+            .timestamps
+            .iter()
+            .enumerate()
+            .filter(|(_, ts)| ts.time < first && range.contains(ts.time.date_naive()))
+            .map(|(i, _)| i)
+            .collect_vec();
+        if earlier.is_empty() {
+            return Ok(());
+        }
+
+        // The earlier response might be in other units
+        let mut cached = cached;
+        let converted = cached.convert_units(&self.units())?;
+        self.degradations.extend(converted);
+        let hours = self.timestamps.len();
+
+        self.sources = earlier
+            .iter()
+            .map(|_| DataSource::Cached { age })
+            .chain((0..hours).map(|i| self.source(i)))
+            .collect();
+        prepend_by(&mut self.timestamps, &cached.timestamps, &earlier, None);
+        prepend_by(
+            &mut self.temperatures,
+            &cached.temperatures,
+            &earlier,
+            Some(f64::NAN),
+        );
+        prepend_by(
+            &mut self.apparent_temperatures,
+            &cached.apparent_temperatures,
+            &earlier,
+            Some(f64::NAN),
+        );
+        prepend_by(
+            &mut self.humidity,
+            &cached.humidity,
+            &earlier,
+            Some(f64::NAN),
+        );
+        prepend_by(
+            &mut self.pressures,
+            &cached.pressures,
+            &earlier,
+            Some(f64::NAN),
+        );
+        prepend_by(
+            &mut self.cloud_cover,
+            &cached.cloud_cover,
+            &earlier,
+            Some(f64::NAN),
+        );
+        prepend_by(
+            &mut self.wind_speeds,
+            &cached.wind_speeds,
+            &earlier,
+            Some(f64::NAN),
+        );
+        prepend_by(
+            &mut self.wind_directions,
+            &cached.wind_directions,
+            &earlier,
+            Some(WindDirection::Unknown),
+        );
+        prepend_by(
+            &mut self.wind_gusts,
+            &cached.wind_gusts,
+            &earlier,
+            Some(f64::NAN),
+        );
+        prepend_by(
+            &mut self.precipitation,
+            &cached.precipitation,
+            &earlier,
+            Some(f64::NAN),
+        );
+        prepend_by(
+            &mut self.precipitation_probabilities,
+            &cached.precipitation_probabilities,
+            &earlier,
+            Some(f64::NAN),
+        );
+        prepend_by(
+            &mut self.uv_indices,
+            &cached.uv_indices,
+            &earlier,
+            Some(f64::NAN),
+        );
+        prepend_by(
+            &mut self.weather_codes,
+            &cached.weather_codes,
+            &earlier,
+            Some(WeatherCode::Unknown),
+        );
+
+        // The current and the highlighted hours move forward by the hours added before them
+        self.current_hour = self.current_hour.map(|i| i + earlier.len());
+        self.highlight_hour = self.highlight_hour.map(|i| i + earlier.len());
+
+        Ok(())
+    }
+
+    /// Units of the hourly series this data has
+    pub fn units(&self) -> Vec<(UnitSeries, String)> {
+        UnitSeries::ALL
+            .into_iter()
+            .filter(|series| !self.series_values(*series).is_empty())
+            .map(|series| (series, self.series_unit(series).to_string()))
+            .collect()
+    }
+
+    /// Convert the hourly series to the units (the series that aren't listed, or are in the same
+    /// unit already, are left as they are), with a degradation for every converted one. Fails if
+    /// one of the units has no conversion to the other
+    pub fn convert_units(&mut self, units: &[(UnitSeries, String)]) -> Result<Vec<Degradation>> {
+        let mut converted = vec![];
+
+        for (series, to) in units {
+            let from = self.series_unit(*series).to_string();
+            if self.series_values(*series).is_empty() || series.same_units(&from, to) {
+                continue;
+            }
+
+            let incompatible = || WeatherError::IncompatibleUnits {
+                series: *series,
+                from: from.clone(),
+                to: to.clone(),
+            };
+            match series {
+                // The current conditions and the days are in the unit of the temperatures too
+                UnitSeries::Temperature => {
+                    let unit = temperature_unit(to).ok_or_else(incompatible)?;
+                    temperature_unit(&from).ok_or_else(incompatible)?;
+
+                    *self = std::mem::take(self).convert_temperatures(unit);
+                }
+                _ => {
+                    series.convert(1.0, (&from, to)).ok_or_else(incompatible)?;
+
+                    for values in self.series_values_mut(*series) {
+                        values.iter_mut().for_each(|value| {
+                            *value = series.convert(*value, (&from, to)).unwrap_or(f64::NAN)
+                        });
                     }
+                    *self.series_unit_mut(*series) = to.clone();
                 }
-                _ => Err(eyre::eyre!("Couldn't parse hourly data")),
-            }?
-        };
+            }
 
-        self.unit = {
-            let unit = json
-                .get("hourly_units")
-                .ok_or(eyre::eyre!("Unit not found"))?;
+            converted.push(Degradation::ConvertedUnits {
+                series: *series,
+                from,
+                to: to.clone(),
+            });
+        }
 
-            unit.get("temperature_2m")
-                .and_then(|u| u.as_str())
-                .ok_or(eyre::eyre!("Unit not found"))?
-                .to_string()
-        };
+        Ok(converted)
+    }
+
+    /// Unit of the series, as the provider wrote it
+    fn series_unit(&self, series: UnitSeries) -> &str {
+        match series {
+            UnitSeries::Temperature => &self.unit,
+            UnitSeries::WindSpeed => &self.wind_speed_unit,
+            UnitSeries::Precipitation => &self.precipitation_unit,
+            UnitSeries::Pressure => &self.pressure_unit,
+        }
+    }
+
+    fn series_unit_mut(&mut self, series: UnitSeries) -> &mut String {
+        match series {
+            UnitSeries::Temperature => &mut self.unit,
+            UnitSeries::WindSpeed => &mut self.wind_speed_unit,
+            UnitSeries::Precipitation => &mut self.precipitation_unit,
+            UnitSeries::Pressure => &mut self.pressure_unit,
+        }
+    }
+
+    /// The main series in the unit (the gusts are in the unit of the wind speeds, the apparent
+    /// temperatures in the one of the temperatures)
+    fn series_values(&self, series: UnitSeries) -> &[f64] {
+        match series {
+            UnitSeries::Temperature => &self.temperatures,
+            UnitSeries::WindSpeed => &self.wind_speeds,
+            UnitSeries::Precipitation => &self.precipitation,
+            UnitSeries::Pressure => &self.pressures,
+        }
+    }
+
+    /// All the hourly series in the unit of the series
+    fn series_values_mut(&mut self, series: UnitSeries) -> Vec<&mut Vec<f64>> {
+        match series {
+            UnitSeries::Temperature => {
+                vec![&mut self.temperatures, &mut self.apparent_temperatures]
+            }
+            UnitSeries::WindSpeed => vec![&mut self.wind_speeds, &mut self.wind_gusts],
+            UnitSeries::Precipitation => vec![&mut self.precipitation],
+            UnitSeries::Pressure => vec![&mut self.pressures],
+        }
+    }
+
+    /// Hours that are not from this response, as the runs of the same source in a row with the
+    /// first and the last index (the live hours are left out)
+    pub fn stale_runs(&self) -> Vec<(usize, usize, DataSource)> {
+        let mut runs: Vec<(usize, usize, DataSource)> = vec![];
+
+        for (i, source) in self.sources.iter().enumerate() {
+            match runs.last_mut() {
+                Some((_, last, run)) if *last + 1 == i && run == source => *last = i,
+                _ if *source != DataSource::Live => runs.push((i, i, *source)),
+                _ => {}
+            }
+        }
+
+        runs
+    }
+
+    /// Everything that makes the data less than what was asked for: what went wrong while getting
+    /// it, and what the data shows itself (the caveat, the stale and the cached hours, the hours
+    /// further apart than one), in no particular order
+    pub fn degradations(&self) -> Vec<Degradation> {
+        let stale = self
+            .cached_at
+            .map(|cached_at| Degradation::Stale { cached_at });
+        // All of the hours are cached for the stale data, which says it already
+        let cached_hours = self
+            .stale_runs()
+            .into_iter()
+            .filter(|_| self.cached_at.is_none())
+            .filter_map(|(first, last, source)| match source {
+                DataSource::Cached { age } => Some(Degradation::CachedHours {
+                    first: self.timestamps[first].label.clone(),
+                    last: self.timestamps[last].label.clone(),
+                    age,
+                }),
+                _ => None,
+            });
+        let sparse = self
+            .timestamps
+            .iter()
+            .tuple_windows()
+            .find(|(hour, next)| next.time - hour.time > Duration::hours(1))
+            .map(|(_, next)| Degradation::SparseHours {
+                from: next.label.clone(),
+            });
+
+        self.degradations
+            .iter()
+            .cloned()
+            .chain(self.caveat.clone().map(Degradation::Caveat))
+            .chain(stale)
+            .chain(cached_hours)
+            .chain(sparse)
+            .collect()
+    }
+
+    /// Convert all the temperatures to the unit (if the provider returned them in a different one)
+    pub fn convert_temperatures(mut self, unit: TemperatureUnit) -> Self {
+        let from = TemperatureUnit::from_provider_unit(&self.unit);
 
-        self.current = {
-            let current_weather = json
-                .get("current_weather")
-                .ok_or(eyre::eyre!("Current weather not found"))?;
+        if from != unit {
+            for temperatures in [&mut self.temperatures, &mut self.apparent_temperatures] {
+                temperatures
+                    .iter_mut()
+                    .for_each(|temperature| *temperature = unit.convert(*temperature, from));
+            }
 
-            match current_weather {
-                Value::Object(current_weather) => {
-                    let current_weather = CurrentWeatherData::from_json(current_weather)?;
-                    Ok(Some(current_weather))
+            if let Some(current) = &mut self.current {
+                current.temperature = unit.convert(current.temperature, from);
+                current.apparent_temperature = current
+                    .apparent_temperature
+                    .map(|temperature| unit.convert(temperature, from));
+                current.wind_chill = current
+                    .wind_chill
+                    .map(|temperature| unit.convert(temperature, from));
+            }
+
+            if let Some(daily) = &mut self.daily {
+                for temperatures in [
+                    &mut daily.min_temps,
+                    &mut daily.max_temps,
+                    &mut daily.mean_temps,
+                ] {
+                    temperatures
+                        .iter_mut()
+                        .for_each(|temperature| *temperature = unit.convert(*temperature, from));
                 }
-                _ => Err(eyre::eyre!("Couldn't parse current weather data")),
-            }?
+            }
+
+            self.unit = unit.symbol().to_string();
+        }
+
+        self
+    }
+
+    /// Keep only the hours that pass the check, along with their wind
+    fn retain_hours(&mut self, keep: impl Fn(&str) -> bool) {
+        let keep = self
+            .timestamps
+            .iter()
+            .map(|ts| keep(&ts.label))
+            .collect_vec();
+
+        self.retain_flagged(&keep);
+    }
+
+    /// Keep only the first of the hours the provider returned more than once
+    fn dedup_hours(&mut self) {
+        let mut seen = HashSet::new();
+        let keep = self
+            .timestamps
+            .iter()
+            .map(|ts| seen.insert(ts.time))
+            .collect_vec();
+
+        let duplicates = keep.iter().filter(|keep| !**keep).count();
+        if duplicates > 0 {
+            self.retain_flagged(&keep);
+            self.degradations
+                .push(Degradation::DuplicateHours(duplicates));
+        }
+    }
+
+    /// Keep only the hours whose flags are set, along with their wind
+    fn retain_flagged(&mut self, keep: &[bool]) {
+        // The current and the highlighted hours move back by the number of the hours removed
+        // before them
+        let shift = |hour: Option<usize>| {
+            hour.filter(|&i| keep.get(i).copied().unwrap_or_default())
+                .map(|i| keep[..i].iter().filter(|k| **k).count())
         };
+        self.current_hour = shift(self.current_hour);
+        self.highlight_hour = shift(self.highlight_hour);
+
+        retain_by(&mut self.timestamps, keep);
+        retain_by(&mut self.temperatures, keep);
+        retain_by(&mut self.apparent_temperatures, keep);
+        retain_by(&mut self.humidity, keep);
+        retain_by(&mut self.pressures, keep);
+        retain_by(&mut self.cloud_cover, keep);
+        retain_by(&mut self.wind_speeds, keep);
+        retain_by(&mut self.wind_directions, keep);
+        retain_by(&mut self.wind_gusts, keep);
+        retain_by(&mut self.precipitation, keep);
+        retain_by(&mut self.precipitation_probabilities, keep);
+        retain_by(&mut self.uv_indices, keep);
+        retain_by(&mut self.weather_codes, keep);
+        retain_by(&mut self.sources, keep);
+    }
+
+    /// Make sure the hourly series line up with the timestamps (the ones the provider didn't return
+    /// are empty)
+    pub(crate) fn check_hourly_lengths(&self) -> Result<()> {
+        let mismatch = [
+            ("wind speeds", self.wind_speeds.len()),
+            ("wind directions", self.wind_directions.len()),
+            ("precipitation", self.precipitation.len()),
+            (
+                "precipitation probabilities",
+                self.precipitation_probabilities.len(),
+            ),
+            ("weather codes", self.weather_codes.len()),
+        ]
+        .into_iter()
+        .find(|(_, len)| *len != 0 && *len != self.timestamps.len());
+
+        match mismatch {
+            None => Ok(()),
+            Some((name, len)) => Err(WeatherError::InvalidResponse(format!(
+                "Mismatch in timestamps ({}) and {name} ({len}), please try a different \
+                provider/location/date",
+                self.timestamps.len()
+            ))),
+        }
+    }
+}
+
+/// The most common of the known conditions, the more severe ones if several are as common
+/// (`Unknown` if none of them is known)
+fn dominant_weather_code(codes: impl Iterator<Item = WeatherCode>) -> WeatherCode {
+    codes
+        .filter(|code| *code != WeatherCode::Unknown)
+        .counts()
+        .into_iter()
+        .max_by_key(|(code, count)| (*count, *code))
+        .map(|(code, _)| code)
+        .unwrap_or_default()
+}
+
+/// Format of the sunrise and the sunset
+pub const DAYLIGHT_FORMAT: &str = "%H:%M";
 
-        Ok(self)
+/// Name of the timezone with the offset from UTC (in seconds), "UTC+05:30"
+pub(crate) fn utc_offset_name(offset: i64) -> String {
+    let sign = match offset < 0 {
+        true => '-',
+        false => '+',
+    };
+    let minutes = offset.abs() / 60;
+
+    format!("UTC{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Keep only the hours all the data has, so that the data of the different providers lines up.
+///
+/// The data without any hours (only the current conditions) is left out of it, and nothing is
+/// removed if there are no hours in common at all
+pub fn align_timestamps(data: &mut [WeatherData]) {
+    let common = data
+        .iter()
+        .filter(|data| !data.timestamps.is_empty())
+        .map(|data| {
+            data.timestamps
+                .iter()
+                .map(|ts| ts.label.clone())
+                .collect::<HashSet<_>>()
+        })
+        .reduce(|common, timestamps| &common & &timestamps)
+        .unwrap_or_default();
+
+    if common.is_empty() {
+        return;
+    }
+
+    for data in data {
+        data.retain_hours(|ts| common.contains(ts));
+    }
+}
+
+/// Keep the values whose flags are set (the series that are not lined up with the flags, like the
+/// missing wind, are left as is)
+fn retain_by<T>(values: &mut Vec<T>, keep: &[bool]) {
+    if values.len() != keep.len() {
+        return;
+    }
+
+    let mut keep = keep.iter();
+    values.retain(|_| *keep.next().unwrap_or(&true));
+}
+
+/// Put the values with the indices in front of the series, if it's lined up with the hours (the
+/// missing ones are `missing`, or the series is left as is without it)
+fn prepend_by<T: Clone>(values: &mut Vec<T>, earlier: &[T], indices: &[usize], missing: Option<T>) {
+    // The series the data doesn't have stay empty
+    if values.is_empty() {
+        return;
     }
 
-    fn parse_met_no_json(mut self, json: &Map<String, Value>) -> eyre::Result<Self> {
-        let Value::Array(coords_arr) = json
-            .get("geometry")
-            .ok_or(eyre::eyre!("Geometry not found"))?
-            .get("coordinates")
-            .ok_or(eyre::eyre!("Coordinates not found"))? else {
-            return Err(eyre::eyre!("Couldn't parse coordinates"));
+    let Some(earlier) = indices
+        .iter()
+        .map(|i| earlier.get(*i).cloned().or(missing.clone()))
+        .collect::<Option<Vec<_>>>()
+    else {
+        return;
+    };
+
+    values.splice(0..0, earlier);
+}
+
+/// Deserialize the response of the provider, naming the path of the field that doesn't match in
+/// the error
+pub(crate) fn parse_response<T: DeserializeOwned>(
+    json: &Map<String, Value>,
+    provider: Provider,
+) -> Result<T> {
+    serde_path_to_error::deserialize(Value::Object(json.clone())).map_err(|e| {
+        WeatherError::InvalidResponse(format!(
+            "Couldn't parse the {provider} response at {}: {}",
+            e.path(),
+            e.inner()
+        ))
+    })
+}
+
+/// Deserialize the body of the response straight into the type, without the JSON map in between
+/// (the errors are the same as the ones of [`parse_response`])
+pub(crate) fn parse_response_body<T: DeserializeOwned>(
+    body: &str,
+    provider: Provider,
+) -> Result<T> {
+    serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(body)).map_err(|e| {
+        WeatherError::InvalidResponse(format!(
+            "Couldn't parse the {provider} response at {}: {}",
+            e.path(),
+            e.inner()
+        ))
+    })
+}
+
+/// Error for a field of the response that is missing (`path` is "hourly.temperature_2m")
+pub(crate) fn missing_field(provider: Provider, path: &str) -> WeatherError {
+    WeatherError::InvalidResponse(format!("{path} not found in the {provider} response"))
+}
+
+/// Get the value, if it's there and is not NaN
+pub(crate) fn valid_value(values: &[f64], i: usize) -> Option<f64> {
+    values.get(i).copied().filter(|value| !value.is_nan())
+}
+
+/// Apparent temperature (°C) the way the Australian Bureau of Meteorology calculates it, from the
+/// temperature (°C), the relative humidity (%) and the wind speed (m/s)
+pub(crate) fn apparent_temperature(temperature: f64, humidity: f64, wind_speed: f64) -> f64 {
+    // Water vapour pressure in hPa
+    let vapour_pressure =
+        humidity / 100.0 * 6.105 * (17.27 * temperature / (237.7 + temperature)).exp();
+    let apparent_temperature = temperature + 0.33 * vapour_pressure - 0.70 * wind_speed - 4.0;
+
+    // Rounded to one decimal, like the providers return the temperatures
+    (apparent_temperature * 10.0).round() / 10.0
+}
+
+/// Wind chill (°C) the way the North American and UK weather services calculate it, from the
+/// temperature (°C) and the wind speed (km/h). `None` outside of where the formula is defined: above
+/// 10 °C or below 4.8 km/h the wind doesn't make it feel any colder
+pub(crate) fn wind_chill(temperature: f64, wind_speed: f64) -> Option<f64> {
+    if temperature > 10.0 || wind_speed < 4.8 {
+        return None;
+    }
+
+    let wind_factor = wind_speed.powf(0.16);
+    let wind_chill =
+        13.12 + 0.6215 * temperature - 11.37 * wind_factor + 0.3965 * temperature * wind_factor;
+
+    // Rounded to one decimal, like the providers return the temperatures
+    Some((wind_chill * 10.0).round() / 10.0)
+}
+
+/// Hourly series that have a unit, which can differ between the responses that are merged
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnitSeries {
+    Temperature,
+    WindSpeed,
+    Precipitation,
+    Pressure,
+}
+
+impl Display for UnitSeries {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Temperature => write!(f, "temperature"),
+            Self::WindSpeed => write!(f, "wind speed"),
+            Self::Precipitation => write!(f, "precipitation"),
+            Self::Pressure => write!(f, "pressure"),
+        }
+    }
+}
+
+impl UnitSeries {
+    pub const ALL: [Self; 4] = [
+        Self::Temperature,
+        Self::WindSpeed,
+        Self::Precipitation,
+        Self::Pressure,
+    ];
+
+    /// Convert the value of the series in the first unit to the second one (`None` if one of the
+    /// units is unknown)
+    pub fn convert(&self, value: f64, (from, to): (&str, &str)) -> Option<f64> {
+        match self {
+            Self::Temperature => {
+                Some(temperature_unit(to)?.convert(value, temperature_unit(from)?))
+            }
+            // The rest are only scaled, through the unit they are all known in
+            Self::WindSpeed => Some(wind_speed_in_kmh(value, from)? / wind_speed_in_kmh(1.0, to)?),
+            Self::Precipitation => {
+                Some(value * precipitation_in_mm(from)? / precipitation_in_mm(to)?)
+            }
+            Self::Pressure => Some(value * pressure_in_hpa(from)? / pressure_in_hpa(to)?),
+        }
+    }
+
+    /// Whether the units are the same one, written the same way or not ("celsius" and "°C")
+    fn same_units(&self, first: &str, second: &str) -> bool {
+        first == second || self.convert(1.0, (first, second)) == Some(1.0)
+    }
+
+    fn text(&self) -> Text {
+        match self {
+            Self::Temperature => Text::Temperature,
+            Self::WindSpeed => Text::WindSpeed,
+            Self::Precipitation => Text::Precipitation,
+            Self::Pressure => Text::Pressure,
+        }
+    }
+}
+
+/// Temperature unit the provider wrote (`None` for the units we don't know)
+fn temperature_unit(unit: &str) -> Option<TemperatureUnit> {
+    match unit.to_lowercase().as_str() {
+        "°c" | "c" | "celsius" => Some(TemperatureUnit::Celsius),
+        "°f" | "f" | "fahrenheit" => Some(TemperatureUnit::Fahrenheit),
+        _ => None,
+    }
+}
+
+/// Millimeters in the precipitation unit (`None` for the units we don't know)
+fn precipitation_in_mm(unit: &str) -> Option<f64> {
+    match unit {
+        "mm" => Some(1.0),
+        "cm" => Some(10.0),
+        "inch" | "in" => Some(25.4),
+        _ => None,
+    }
+}
+
+/// Hectopascals in the pressure unit (`None` for the units we don't know)
+fn pressure_in_hpa(unit: &str) -> Option<f64> {
+    match unit {
+        "hPa" | "mbar" | "mb" => Some(1.0),
+        "kPa" => Some(10.0),
+        "inHg" => Some(33.863_886),
+        "mmHg" => Some(1.333_224),
+        _ => None,
+    }
+}
+
+/// Convert the wind speed in the unit the provider returned it in to km/h (`None` for the units we
+/// don't know)
+pub fn wind_speed_in_kmh(wind_speed: f64, unit: &str) -> Option<f64> {
+    match unit {
+        "km/h" => Some(wind_speed),
+        "m/s" => Some(wind_speed * 3.6),
+        "mph" | "mp/h" => Some(wind_speed * 1.609_344),
+        "kn" => Some(wind_speed * 1.852),
+        _ => None,
+    }
+}
+
+/// How many of the timestamps that failed to parse are listed in the error
+const MAX_LISTED_TIMESTAMPS: usize = 5;
+
+/// Parse the timestamps, listing all the ones that couldn't be parsed in the error (`None` is for
+/// the values that are not even strings)
+pub(crate) fn parse_timestamps(
+    timestamps: impl Iterator<Item = Option<String>>,
+) -> Result<Vec<NaiveDateTime>> {
+    let (parsed, failed): (Vec<_>, Vec<_>) = timestamps
+        .map(|t| match t {
+            // The times are already in the timezone of the location, so they are taken as they are
+            // (dateparser would read them in the timezone of this machine otherwise)
+            Some(t) => dateparser::parse_with_timezone(&t, &Utc)
+                .map(|date| date.naive_utc())
+                .map_err(|_| format!("\"{t}\"")),
+            None => Err("(not a string)".to_string()),
+        })
+        .partition_result();
+
+    match failed.is_empty() {
+        true => Ok(parsed),
+        false => Err(WeatherError::InvalidResponse(format!(
+            "Couldn't parse {} timestamp(s): {}{}",
+            failed.len(),
+            failed.iter().take(MAX_LISTED_TIMESTAMPS).join(", "),
+            match failed.len() > MAX_LISTED_TIMESTAMPS {
+                true => ", ...",
+                false => "",
+            }
+        ))),
+    }
+}
+
+/// Parse the timestamps (in the time of the location, which is `utc_offset` ahead of UTC) and
+/// format them for the chart labels
+pub(crate) fn format_timestamps(
+    timestamps: impl Iterator<Item = Option<String>>,
+    utc_offset: FixedOffset,
+    format: &str,
+) -> Result<Vec<Timestamp>> {
+    Ok(parse_timestamps(timestamps)?
+        .into_iter()
+        .map(|time| Timestamp::new(with_utc_offset(time, utc_offset), format))
+        .collect())
+}
+
+/// The time of the location with its offset from UTC
+pub(crate) fn with_utc_offset(
+    time: NaiveDateTime,
+    utc_offset: FixedOffset,
+) -> DateTime<FixedOffset> {
+    DateTime::from_naive_utc_and_offset(time - utc_offset, utc_offset)
+}
+
+/// How much a degradation takes away from the data, from the least
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Severity {
+    Notice,
+    Warning,
+    Severe,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Notice => write!(f, "notice"),
+            Self::Warning => write!(f, "warning"),
+            Self::Severe => write!(f, "severe"),
+        }
+    }
+}
+
+/// What makes the data less than what was asked for
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Degradation {
+    /// Another provider got the data, the reason the requested one couldn't
+    Failover { provider: Provider, reason: String },
+    /// All of the data is an earlier response, the provider couldn't be reached
+    Stale { cached_at: DateTime<Utc> },
+    /// The hours from an earlier response (the first and the last label), received `age` before
+    /// the rest
+    CachedHours {
+        first: String,
+        last: String,
+        age: Duration,
+    },
+    /// An extra that was asked for couldn't be fetched, and why (`None` if it's only fetched with
+    /// the fresh data)
+    MissingExtra {
+        extra: Extra,
+        reason: Option<String>,
+    },
+    /// The coordinates have no address, the location is named after them
+    UnknownAddress,
+    /// The hours are further apart than one from the label on (met_no has 6 hour steps further
+    /// ahead)
+    SparseHours { from: String },
+    /// The hours the provider returned more than once, only the first of each is kept
+    DuplicateHours(usize),
+    /// Warning about the quality of the data from the provider or the request
+    Caveat(String),
+    /// The series of a merged response was converted to the unit of the rest
+    ConvertedUnits {
+        series: UnitSeries,
+        from: String,
+        to: String,
+    },
+}
+
+impl Degradation {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::Stale { .. } => Severity::Severe,
+            Self::Failover { .. }
+            | Self::CachedHours { .. }
+            | Self::DuplicateHours(_)
+            | Self::Caveat(_) => Severity::Warning,
+            Self::MissingExtra { .. }
+            | Self::UnknownAddress
+            | Self::SparseHours { .. }
+            | Self::ConvertedUnits { .. } => Severity::Notice,
+        }
+    }
+
+    /// What happened, in the language
+    pub fn message(&self, language: Language) -> String {
+        match self {
+            Self::Failover { provider, reason } => {
+                language.format(Text::FellBackTo, &[reason, provider])
+            }
+            Self::Stale { cached_at } => {
+                let time = cached_at.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+
+                language.format(Text::StaleData, &[&time])
+            }
+            Self::CachedHours { first, last, age } => {
+                language.format(Text::CachedHours, &[first, last, &language.ago(*age)])
+            }
+            Self::MissingExtra {
+                extra,
+                reason: Some(reason),
+            } => language.format(Text::MissingExtra, &[&language.text(extra.text()), reason]),
+            Self::MissingExtra {
+                extra,
+                reason: None,
+            } => language.format(Text::ExtraNotFetched, &[&language.text(extra.text())]),
+            Self::UnknownAddress => language.text(Text::UnknownAddress).to_string(),
+            Self::SparseHours { from } => language.format(Text::SparseHours, &[from]),
+            Self::DuplicateHours(count) => language.format(Text::DuplicateHours, &[count]),
+            Self::Caveat(caveat) => caveat.clone(),
+            Self::ConvertedUnits { series, from, to } => language.format(
+                Text::ConvertedUnits,
+                &[&language.text(series.text()), from, to],
+            ),
+        }
+    }
+}
+
+/// What is fetched along with the weather if it was asked for
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Extra {
+    Alerts,
+    AirQuality,
+    Nowcast,
+    /// The day before, to compare with
+    Yesterday,
+}
+
+impl Extra {
+    fn text(&self) -> Text {
+        match self {
+            Self::Alerts => Text::ExtraAlerts,
+            Self::AirQuality => Text::ExtraAirQuality,
+            Self::Nowcast => Text::ExtraNowcast,
+            Self::Yesterday => Text::ExtraYesterday,
+        }
+    }
+}
+
+/// Where an hour of the data comes from
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DataSource {
+    /// The response to this request
+    #[default]
+    Live,
+    /// An earlier response, received `age` before this one
+    Cached { age: Duration },
+    /// Filled in between the hours the provider returned
+    Interpolated,
+}
+
+impl Display for DataSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Live => write!(f, "live"),
+            Self::Cached { .. } => write!(f, "cached"),
+            Self::Interpolated => write!(f, "interpolated"),
+        }
+    }
+}
+
+/// Hour of the data: the time of the location with its offset from UTC, and how it's shown on the
+/// chart ("03 PM", "Mon 15:00")
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Timestamp {
+    pub time: DateTime<FixedOffset>,
+    pub label: String,
+}
+
+impl Timestamp {
+    pub(crate) fn new(time: DateTime<FixedOffset>, format: &str) -> Self {
+        Self {
+            label: time.format(format).to_string(),
+            time,
+        }
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Padded like the label would be
+        f.pad(&self.label)
+    }
+}
+
+/// How much the last third of a series has to differ from the first one (relative to the range of
+/// the whole series) to count as rising or falling
+const TREND_THRESHOLD: f64 = 0.25;
+
+/// Overall picture of an hourly series: the lowest and the highest values with their hours (the
+/// first one on a tie), the average and the trend. The hours without a value (NaN) are skipped, and
+/// everything is `None` for an empty series
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct SeriesSummary {
+    /// Lowest value and its index in the series
+    pub min: Option<(f64, usize)>,
+    /// Highest value and its index in the series
+    pub max: Option<(f64, usize)>,
+    pub mean: Option<f64>,
+    /// `None` for less than two values
+    pub trend: Option<Trend>,
+}
+
+impl SeriesSummary {
+    pub fn new(values: &[f64]) -> Self {
+        let values = values
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, value)| !value.is_nan())
+            .collect_vec();
+
+        // `max_by` keeps the last one of the equal values, so the first one is searched for on the
+        // reversed series (`min_by` keeps the first one already)
+        let min = values
+            .iter()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, value)| (*value, *i));
+        let max = values
+            .iter()
+            .rev()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, value)| (*value, *i));
+        let mean = |values: &[(usize, f64)]| {
+            (!values.is_empty())
+                .then(|| values.iter().map(|(_, value)| value).sum::<f64>() / values.len() as f64)
         };
 
-        if coords_arr.len() < 2 {
-            return Err(eyre::eyre!("Couldn't parse coordinates"));
-        }
-
-        self.latitude = coords_arr[1]
-            .as_f64()
-            .ok_or(eyre::eyre!("Couldn't parse latitude"))?;
-        self.longitude = coords_arr[0]
-            .as_f64()
-            .ok_or(eyre::eyre!("Couldn't parse longitude"))?;
-
-        let properties = json
-            .get("properties")
-            .ok_or(eyre::eyre!("Properties not found"))?;
-
-        self.unit = properties
-            .get("meta")
-            .and_then(|m| m.get("units"))
-            .and_then(|u| u.get("air_temperature"))
-            .and_then(|t| t.as_str())
-            .ok_or(eyre::eyre!("Couldn't parse unit"))?
-            .to_string();
-
-        let Value::Array(time_series) = properties
-            .get("timeseries")
-            .ok_or(eyre::eyre!("Timeseries not found"))? else {
-            return Err(eyre::eyre!("Couldn't parse timeseries"));
+        // The averages of the first and the last thirds are compared, so that a single odd hour
+        // doesn't decide it
+        let third = (values.len() / 3).max(1);
+        let trend = match (min, max, values.len() >= 2) {
+            (Some((min, _)), Some((max, _)), true) => {
+                let change = mean(&values[values.len() - third..]).unwrap_or_default()
+                    - mean(&values[..third]).unwrap_or_default();
+
+                Some(
+                    match (change.abs() > (max - min) * TREND_THRESHOLD, change > 0.0) {
+                        (false, _) => Trend::Steady,
+                        (true, true) => Trend::Rising,
+                        (true, false) => Trend::Falling,
+                    },
+                )
+            }
+            _ => None,
         };
 
-        let time_series = time_series.iter().take(24).collect_vec();
+        Self {
+            min,
+            max,
+            mean: mean(&values),
+            trend,
+        }
+    }
+}
 
-        let (timestamps, temperatures, err) = time_series
+/// Where a series is heading, see [`SeriesSummary`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Rising,
+    Falling,
+    Steady,
+}
+
+impl Trend {
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            Trend::Rising => "↗",
+            Trend::Falling => "↘",
+            Trend::Steady => "→",
+        }
+    }
+}
+
+impl Display for Trend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(Language::En.text(Text::Trend(*self)))
+    }
+}
+
+/// Temperatures summarized per calendar day
+#[derive(Default, Debug, Clone)]
+pub struct DailyWeatherData {
+    pub dates: Vec<NaiveDate>,
+    /// NaN for the days the provider didn't return any temperatures for
+    pub min_temps: Vec<f64>,
+    pub max_temps: Vec<f64>,
+    pub mean_temps: Vec<f64>,
+    /// Days the data only covers a part of (the first and the last ones usually), their minimums
+    /// and maximums are likely not the real ones
+    pub partial_days: Vec<bool>,
+    /// Conditions of every day (empty if the provider didn't return any)
+    pub weather_codes: Vec<WeatherCode>,
+}
+
+/// Shortest span between the first and the last data point of a day to consider it whole (met_no
+/// switches to 6 hour steps further ahead, so 00:00 to 18:00 is the whole day there)
+const WHOLE_DAY_MIN_HOURS: i64 = 18;
+
+impl DailyWeatherData {
+    /// Group the (time-ordered) hourly temperatures by calendar day. The hours without a
+    /// temperature (NaN) are skipped, the days without any get NaN
+    pub fn from_hourly(hourly: impl Iterator<Item = (NaiveDateTime, f64)>) -> Self {
+        let mut daily = Self::default();
+
+        for (date, hours) in &hourly.group_by(|(time, _)| time.date()) {
+            let hours = hours.collect_vec();
+            let temperatures = hours
+                .iter()
+                .map(|(_, temperature)| *temperature)
+                .filter(|temperature| !temperature.is_nan())
+                .collect_vec();
+
+            let (min, max, mean) = match temperatures.is_empty() {
+                true => (f64::NAN, f64::NAN, f64::NAN),
+                false => (
+                    temperatures.iter().copied().fold(f64::INFINITY, f64::min),
+                    temperatures
+                        .iter()
+                        .copied()
+                        .fold(f64::NEG_INFINITY, f64::max),
+                    temperatures.iter().sum::<f64>() / temperatures.len() as f64,
+                ),
+            };
+
+            // Groups are never empty
+            let span = hours[hours.len() - 1].0 - hours[0].0;
+
+            daily.dates.push(date);
+            daily.min_temps.push(min);
+            daily.max_temps.push(max);
+            // Rounded to one decimal, like the providers return the temperatures
+            daily.mean_temps.push((mean * 10.0).round() / 10.0);
+            daily
+                .partial_days
+                .push(span < Duration::hours(WHOLE_DAY_MIN_HOURS));
+        }
+
+        daily
+    }
+
+    /// Set the conditions of every day to the most common ones of its (time-ordered) hours, the
+    /// more severe ones if several are as common
+    pub(crate) fn with_hourly_weather_codes(
+        mut self,
+        hourly: impl Iterator<Item = (NaiveDateTime, WeatherCode)>,
+    ) -> Self {
+        let days = hourly
+            .group_by(|(time, _)| time.date())
             .into_iter()
-            .fold_while(
-                (Vec::new(), Vec::new(), None),
-                |(mut ts, mut temps, _), map| {
-                    let timestep = match map
-                        .get("time")
-                        .ok_or("Couldn't find time field".to_string())
-                        .and_then(|t| {
-                            t.as_str()
-                                .map(|t| t.replace('T', " ").replace('Z', ""))
-                                .ok_or("time field is not a string".to_string())
-                        })
-                        .and_then(|t| {
-                            let date = match dateparser::parse(&t) {
-                                Ok(date) => date,
-                                Err(err) => {
-                                    return Err(format!("Couldn't parse timestamp ({t}): {err}"));
-                                }
-                            };
-
-                            Ok(date.format("%I %p").to_string())
-                        }) {
-                        Ok(timestep) => timestep,
-                        Err(err) => return Done((ts, temps, Some(err))),
-                    };
-
-                    ts.push(timestep);
-
-                    let temperature = match map
-                        .get("data")
-                        .ok_or("Couldn't find data field")
-                        .and_then(|d| d.get("instant").ok_or("Couldn't find instant field"))
-                        .and_then(|i| i.get("details").ok_or("Couldn't find details field"))
-                        .and_then(|d| {
-                            d.get("air_temperature")
-                                .ok_or("Couldn't find air_temperature_field")
-                        })
-                        .and_then(|a| a.as_f64().ok_or("Couldn't parse air_temperature"))
-                    {
-                        Ok(temperature) => temperature,
-                        Err(err) => return Done((ts, temps, Some(err.to_string()))),
-                    };
-
-                    temps.push(temperature);
-
-                    Continue((ts, temps, None))
-                },
-            )
-            .into_inner();
+            .map(|(date, hours)| (date, dominant_weather_code(hours.map(|(_, code)| code))))
+            .collect::<HashMap<_, _>>();
 
-        (self.timestamps, self.temperatures) = match err {
-            Some(err) => return Err(eyre::eyre!(err)),
-            None => (timestamps, temperatures),
+        self.weather_codes = self
+            .dates
+            .iter()
+            .map(|date| days.get(date).copied().unwrap_or_default())
+            .collect();
+
+        self
+    }
+
+    /// Label of the day on the chart, marked with `*` if the data only covers a part of it
+    pub fn label(&self, i: usize) -> String {
+        format!(
+            "{}{}",
+            self.dates[i].format("%a %d"),
+            match self.partial_days[i] {
+                true => "*",
+                false => "",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CurrentWeatherData {
+    pub time: String,
+    pub temperature: f64,
+    pub weather_code: WeatherCode,
+    pub wind_speed: f64,
+    pub wind_speed_unit: String,
+    pub wind_direction: WindDirection,
+    /// Chance of precipitation in % (if the provider has it)
+    pub precipitation_probability: Option<f64>,
+    /// Apparent ("feels like") temperature and relative humidity in % (if the provider has them)
+    pub apparent_temperature: Option<f64>,
+    pub humidity: Option<f64>,
+    /// UV index (if the provider has it) and the wind chill (only when it's cold and windy enough)
+    pub uv_index: Option<f64>,
+    pub wind_chill: Option<f64>,
+}
+
+/// Only what the one line outputs show: the current temperature and conditions, and the
+/// temperatures of the next hours
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct CurrentSnapshot {
+    pub location: ResolvedLocation,
+    /// Unit of the temperatures, as the provider named it
+    pub unit: String,
+    /// Temperature of the current conditions, or of the first hour if the provider has no current
+    /// conditions
+    pub temperature: Option<f64>,
+    pub weather_code: Option<WeatherCode>,
+    /// Temperatures from the current hour on (from the first one if it's not known), NaN for the
+    /// hours the provider didn't return them for
+    pub temperatures: Vec<f64>,
+}
+
+impl CurrentSnapshot {
+    /// The snapshot of the `hours` hours from the current hour of the data
+    pub fn from_data(data: &WeatherData, hours: usize) -> Self {
+        Self::new(
+            (data.location.clone(), data.unit.clone()),
+            data.current
+                .as_ref()
+                .map(|current| (current.temperature, current.weather_code)),
+            data.temperatures
+                .get(data.current_hour.unwrap_or_default()..)
+                .unwrap_or_default(),
+            hours,
+        )
+    }
+
+    /// The snapshot of the current conditions and the temperatures from the current hour on
+    pub(crate) fn new(
+        (location, unit): (ResolvedLocation, String),
+        current: Option<(f64, WeatherCode)>,
+        temperatures: &[f64],
+        hours: usize,
+    ) -> Self {
+        let temperatures = temperatures.iter().take(hours).copied().collect::<Vec<_>>();
+
+        Self {
+            location,
+            unit,
+            temperature: current
+                .map(|(temperature, _)| temperature)
+                .or(temperatures.first().copied().filter(|t| !t.is_nan())),
+            weather_code: current.map(|(_, weather_code)| weather_code),
+            temperatures,
+        }
+    }
+
+    /// Convert the temperatures to the unit, if the provider sent them in the other one
+    pub fn convert_temperatures(mut self, unit: TemperatureUnit) -> Self {
+        let from = TemperatureUnit::from_provider_unit(&self.unit);
+
+        if from != unit {
+            self.temperature = self
+                .temperature
+                .map(|temperature| unit.convert(temperature, from));
+            self.temperatures
+                .iter_mut()
+                .for_each(|temperature| *temperature = unit.convert(*temperature, from));
+            self.unit = unit.symbol().to_string();
+        }
+
+        self
+    }
+}
+
+/// Warning about extreme weather issued for the location
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct WeatherAlert {
+    /// What the alert is about ("gale", "ice", ...)
+    pub event: String,
+    /// "Moderate", "Severe" or "Extreme"
+    pub severity: String,
+    pub headline: String,
+    /// When the alert is in effect (`None` if the provider didn't say)
+    pub onset: Option<DateTime<Utc>>,
+    pub expires: Option<DateTime<Utc>>,
+}
+
+impl WeatherAlert {
+    /// Alert of the event (with its severity and headline), in effect from the onset until it
+    /// expires
+    pub fn new(
+        (event, severity, headline): (String, String, String),
+        onset: Option<DateTime<Utc>>,
+        expires: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            event,
+            severity,
+            headline,
+            onset,
+            expires,
+        }
+    }
+}
+
+/// European Air Quality Index (0 is the cleanest air, above 100 the worst) of the day, with the
+/// particulate matter of the current hour
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct AirQualityData {
+    pub date: NaiveDate,
+    /// Index of the current hour (`None` if the data doesn't include it)
+    pub current_aqi: Option<f64>,
+    /// Lowest and highest hourly index of the day
+    pub min_aqi: Option<f64>,
+    pub max_aqi: Option<f64>,
+    /// Particulate matter in μg/m³ of the current hour
+    pub pm2_5: Option<f64>,
+    pub pm10: Option<f64>,
+}
+
+impl AirQualityData {
+    /// Summarize the hourly series of the day (the hours of the other days are left out) and pick
+    /// out the current hour
+    pub(crate) fn from_hourly(
+        date: NaiveDate,
+        current_hour: Option<NaiveDateTime>,
+        hours: impl Iterator<Item = (NaiveDateTime, Option<f64>, Option<f64>, Option<f64>)>,
+    ) -> Self {
+        let mut air_quality = Self {
+            date,
+            current_aqi: None,
+            min_aqi: None,
+            max_aqi: None,
+            pm2_5: None,
+            pm10: None,
         };
 
-        Ok(self)
+        for (time, aqi, pm2_5, pm10) in hours.filter(|(time, ..)| time.date() == date) {
+            if Some(time) == current_hour {
+                air_quality.current_aqi = aqi;
+                air_quality.pm2_5 = pm2_5;
+                air_quality.pm10 = pm10;
+            }
+
+            if let Some(aqi) = aqi.filter(|aqi| !aqi.is_nan()) {
+                air_quality.min_aqi = Some(air_quality.min_aqi.map_or(aqi, |min| min.min(aqi)));
+                air_quality.max_aqi = Some(air_quality.max_aqi.map_or(aqi, |max| max.max(aqi)));
+            }
+        }
+
+        air_quality
+    }
+
+    /// Category of the current index, if there is one
+    pub fn category(&self) -> Option<&'static str> {
+        self.current_aqi.map(aqi_category)
+    }
+}
+
+/// How far ahead the nowcast goes, in minutes
+pub const NOWCAST_MINUTES: i64 = 90;
+
+/// Precipitation of the next [`NOWCAST_MINUTES`]: met_no's radar nowcast (5 minute steps, only
+/// where its radars reach) or open_meteo's 15 minute forecast
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct NowcastData {
+    /// Start of every interval in the clock of the data ("14:05"), in the same time as the
+    /// timestamps
+    pub labels: Vec<String>,
+    /// Precipitation rate in mm/h, lined up with the labels (NaN for the intervals the provider
+    /// didn't return it for)
+    pub precipitation_rates: Vec<f64>,
+    /// Whether the radars cover the location, the intervals are empty if they don't
+    pub radar_coverage: RadarCoverage,
+}
+
+impl NowcastData {
+    /// Keep the intervals of the next [`NOWCAST_MINUTES`] from the first one
+    pub(crate) fn new(
+        intervals: impl Iterator<Item = (NaiveDateTime, Option<f64>)>,
+        time_format: TimeFormat,
+        radar_coverage: RadarCoverage,
+    ) -> Self {
+        let mut intervals = intervals.peekable();
+        let end = intervals
+            .peek()
+            .map(|(first, _)| *first + Duration::minutes(NOWCAST_MINUTES));
+
+        let (labels, precipitation_rates) = intervals
+            .take_while(|(time, _)| Some(*time) < end)
+            .map(|(time, rate)| {
+                (
+                    time.format(time_format.minute_format()).to_string(),
+                    rate.unwrap_or(f64::NAN),
+                )
+            })
+            .unzip();
+
+        Self {
+            labels,
+            precipitation_rates,
+            radar_coverage,
+        }
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct CurrentWeatherData {
-    pub(crate) time: String,
-    pub(crate) temperature: f64,
-    pub(crate) weather_code: WeatherCode,
-    pub(crate) wind_speed: f64,
-    pub(crate) wind_speed_unit: String,
-    pub(crate) wind_direction: WindDirection,
+/// Whether the nowcast has the data for the location
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RadarCoverage {
+    Ok,
+    /// The radars reach the location, but not all of them are working at the moment
+    TemporarilyUnavailable,
+    /// The location is outside of the radars' reach
+    NoCoverage,
 }
 
-impl CurrentWeatherData {
-    fn from_json(json: &Map<String, Value>) -> eyre::Result<Self> {
-        let time = json
-            .get("time")
-            .and_then(|t| t.as_str().map(|t| t.replace('T', " ")))
-            .ok_or(eyre::eyre!("Time not found"))?;
+impl RadarCoverage {
+    /// The value of met_no's `radar_coverage` ("ok", "temporarily unavailable", "no coverage"),
+    /// anything it might add later is taken as no coverage
+    pub(crate) fn from_met_no(coverage: &str) -> Self {
+        match coverage {
+            "ok" => Self::Ok,
+            "temporarily unavailable" => Self::TemporarilyUnavailable,
+            _ => Self::NoCoverage,
+        }
+    }
+}
 
-        let temperature = json
-            .get("temperature")
-            .and_then(|t| t.as_f64())
-            .ok_or(eyre::eyre!("Temperature not found"))?;
+/// Moon phase and the daylight of the (first) requested day at the location
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct AstroData {
+    pub date: NaiveDate,
+    /// Phase of the moon at noon of the day (local time)
+    pub moon_phase: MoonPhase,
+    /// Illuminated part of the moon's disk in %
+    pub illumination: f64,
+    /// Time between the sunrise and the sunset: zero during the polar night, 24 hours during the
+    /// polar day
+    pub day_length: Duration,
+    /// Sunrise and sunset in the time of the timestamps (`None` if the sun doesn't rise or set)
+    pub sunrise: Option<String>,
+    pub sunset: Option<String>,
+}
 
-        let weather_code = json
-            .get("weathercode")
-            .and_then(|t| t.as_u64().map(WeatherCode::from_open_meteo))
-            .ok_or(eyre::eyre!("Weather code not found"))?;
+impl AstroData {
+    /// Calculate them for the requested day at the location, in the timezone of the data (its
+    /// first timestamp says what the offset is, UTC if there are none). `None` if the requested
+    /// date isn't a date
+    pub(crate) fn new(data: &WeatherData) -> Option<Self> {
+        let date = NaiveDate::parse_from_str(&data.requested_date, "%Y-%m-%d").ok()?;
+        let offset = data
+            .timestamps
+            .first()
+            .map_or(Duration::zero(), |timestamp| {
+                Duration::seconds(timestamp.time.offset().local_minus_utc() as i64)
+            });
 
-        let wind_speed = json
-            .get("windspeed")
-            .and_then(|t| t.as_f64())
-            .ok_or(eyre::eyre!("Wind speed not found"))?;
+        // Noon in the local time, in UTC
+        let noon = date.and_hms_opt(12, 0, 0)? - offset;
+        let elongation = astro::moon_elongation(noon);
 
-        let wind_direction = json
-            .get("winddirection")
-            .and_then(|t| t.as_f64().map(WindDirection::from_degrees))
-            .ok_or(eyre::eyre!("Wind direction not found"))?;
+        // The provider's sunrise and sunset are the ones shown everywhere else, they are only
+        // calculated for the data that doesn't have them (the history)
+        let (sunrise, sunset) = match (&data.sunrise, &data.sunset) {
+            (Some(sunrise), Some(sunset)) => (Some(sunrise.clone()), Some(sunset.clone())),
+            _ => astro::sunrise_sunset(data.location.lat, data.location.lon, date)
+                .map(|(sunrise, sunset)| {
+                    (
+                        Some((sunrise + offset).format(DAYLIGHT_FORMAT).to_string()),
+                        Some((sunset + offset).format(DAYLIGHT_FORMAT).to_string()),
+                    )
+                })
+                .unwrap_or_default(),
+        };
 
-        Ok(Self {
-            time,
-            temperature,
-            weather_code,
-            wind_speed,
-            wind_speed_unit: "km/h".to_string(),
-            wind_direction,
+        Some(Self {
+            date,
+            moon_phase: MoonPhase::from_elongation(elongation),
+            illumination: astro::moon_illumination(elongation) * 100.0,
+            day_length: astro::day_length(data.location.lat, date),
+            sunrise,
+            sunset,
         })
     }
 }
 
-#[derive(Default, Debug)]
-pub(crate) enum WeatherCode {
+/// The eight phases of the moon, from the new moon on
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MoonPhase {
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl MoonPhase {
+    const ALL: [Self; 8] = [
+        Self::New,
+        Self::WaxingCrescent,
+        Self::FirstQuarter,
+        Self::WaxingGibbous,
+        Self::Full,
+        Self::WaningGibbous,
+        Self::LastQuarter,
+        Self::WaningCrescent,
+    ];
+
+    /// Phase at the elongation of the moon from the sun (in degrees), each of them is the 45° around
+    /// its own elongation (the full moon is 157.5° to 202.5°)
+    pub(crate) fn from_elongation(elongation: f64) -> Self {
+        Self::ALL[((elongation + 22.5).rem_euclid(360.0) / 45.0) as usize % Self::ALL.len()]
+    }
+
+    /// Name of the phase in the language
+    pub fn localized_name(&self, language: Language) -> &'static str {
+        language.text(Text::MoonPhase(*self))
+    }
+
+    /// Symbol of the phase, as it's seen from the northern hemisphere
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            MoonPhase::New => "🌑",
+            MoonPhase::WaxingCrescent => "🌒",
+            MoonPhase::FirstQuarter => "🌓",
+            MoonPhase::WaxingGibbous => "🌔",
+            MoonPhase::Full => "🌕",
+            MoonPhase::WaningGibbous => "🌖",
+            MoonPhase::LastQuarter => "🌗",
+            MoonPhase::WaningCrescent => "🌘",
+        }
+    }
+}
+
+impl Display for MoonPhase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.localized_name(Language::En))
+    }
+}
+
+/// Category of the European Air Quality Index: "Good" up to 20, then "Fair", "Moderate", "Poor" and
+/// "Very poor" every 20 more, and "Extremely poor" above 100
+pub fn aqi_category(aqi: f64) -> &'static str {
+    match aqi {
+        aqi if aqi <= 20.0 => "Good",
+        aqi if aqi <= 40.0 => "Fair",
+        aqi if aqi <= 60.0 => "Moderate",
+        aqi if aqi <= 80.0 => "Poor",
+        aqi if aqi <= 100.0 => "Very poor",
+        _ => "Extremely poor",
+    }
+}
+
+/// Conditions, in the order from the clearest to the most severe (which is what they are compared
+/// by)
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum WeatherCode {
     #[default]
     Unknown,
     ClearSky,
@@ -344,30 +1694,52 @@ pub(crate) enum WeatherCode {
     Thunderstorm,
 }
 
+/// The English name, which is what the plain text output has whatever the language is
 impl Display for WeatherCode {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            WeatherCode::Unknown => write!(f, "Unknown"),
-            WeatherCode::ClearSky => write!(f, "Clear sky"),
-            WeatherCode::MainlyClear => write!(f, "Mainly Clear Sky"),
-            WeatherCode::PartlyCloudy => write!(f, "Partly Cloudy"),
-            WeatherCode::Overcast => write!(f, "Overcast"),
-            WeatherCode::Fog => write!(f, "Fog"),
-            WeatherCode::Drizzle => write!(f, "Drizzle"),
-            WeatherCode::FreezingDrizzle => write!(f, "Freezing Drizzle"),
-            WeatherCode::Rain => write!(f, "Rain"),
-            WeatherCode::FreezingRain => write!(f, "Freezing Rain"),
-            WeatherCode::SnowFall => write!(f, "Snow Fall"),
-            WeatherCode::SnowGrains => write!(f, "Snow Grains"),
-            WeatherCode::RainShowers => write!(f, "Rain Showers"),
-            WeatherCode::SnowShowers => write!(f, "Snow Showers"),
-            WeatherCode::Thunderstorm => write!(f, "Thunderstorm"),
-        }
+        f.write_str(self.localized_name(Language::En))
     }
 }
 
 impl WeatherCode {
-    fn from_open_meteo(code: u64) -> Self {
+    /// Name of the conditions in the language
+    pub fn localized_name(&self, language: Language) -> &'static str {
+        language.text(Text::Conditions(*self))
+    }
+
+    /// Single symbol for the conditions, for the places the name doesn't fit in
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            WeatherCode::Unknown => "?",
+            WeatherCode::ClearSky => "☀",
+            WeatherCode::MainlyClear => "🌤",
+            WeatherCode::PartlyCloudy => "⛅",
+            WeatherCode::Overcast => "☁",
+            WeatherCode::Fog => "🌫",
+            WeatherCode::Drizzle | WeatherCode::RainShowers => "🌦",
+            WeatherCode::FreezingDrizzle | WeatherCode::Rain | WeatherCode::FreezingRain => "🌧",
+            WeatherCode::SnowFall | WeatherCode::SnowGrains | WeatherCode::SnowShowers => "❄",
+            WeatherCode::Thunderstorm => "⛈",
+        }
+    }
+
+    /// Single character for the conditions, for the terminals without the emoji fonts
+    pub fn ascii_glyph(&self) -> &'static str {
+        match self {
+            WeatherCode::Unknown => "?",
+            WeatherCode::ClearSky => "O",
+            WeatherCode::MainlyClear => "o",
+            WeatherCode::PartlyCloudy => "c",
+            WeatherCode::Overcast => "C",
+            WeatherCode::Fog => "=",
+            WeatherCode::Drizzle | WeatherCode::RainShowers => ",",
+            WeatherCode::FreezingDrizzle | WeatherCode::Rain | WeatherCode::FreezingRain => "/",
+            WeatherCode::SnowFall | WeatherCode::SnowGrains | WeatherCode::SnowShowers => "*",
+            WeatherCode::Thunderstorm => "!",
+        }
+    }
+
+    pub(crate) fn from_open_meteo(code: u64) -> Self {
         match code {
             0 => WeatherCode::ClearSky,
             1 => WeatherCode::MainlyClear,
@@ -380,12 +1752,55 @@ impl WeatherCode {
             66 | 67 => WeatherCode::FreezingRain,
             71 | 73 | 75 => WeatherCode::SnowFall,
             77 => WeatherCode::SnowGrains,
-            80 | 81 | 82 => WeatherCode::RainShowers,
+            80..=82 => WeatherCode::RainShowers,
             85 | 86 => WeatherCode::SnowShowers,
             95 | 96 | 99 => WeatherCode::Thunderstorm,
             _ => WeatherCode::Unknown,
         }
     }
+
+    /// Map the symbol codes ("lightrainshowers_day", https://api.met.no/weatherapi/weathericon/2.0)
+    pub(crate) fn from_met_no(symbol: &str) -> Self {
+        // The time of the day doesn't matter
+        let symbol = symbol.split('_').next().unwrap_or(symbol);
+
+        match symbol {
+            "clearsky" => WeatherCode::ClearSky,
+            "fair" => WeatherCode::MainlyClear,
+            "partlycloudy" => WeatherCode::PartlyCloudy,
+            "cloudy" => WeatherCode::Overcast,
+            "fog" => WeatherCode::Fog,
+            // "heavyrainshowersandthunder", "lightssleetandthunder", ...
+            s if s.contains("thunder") => WeatherCode::Thunderstorm,
+            s if s.contains("sleet") => WeatherCode::FreezingRain,
+            s if s.ends_with("rainshowers") => WeatherCode::RainShowers,
+            s if s.ends_with("rain") => WeatherCode::Rain,
+            s if s.ends_with("snowshowers") => WeatherCode::SnowShowers,
+            s if s.ends_with("snow") => WeatherCode::SnowFall,
+            _ => WeatherCode::Unknown,
+        }
+    }
+
+    /// Map the condition codes (https://openweathermap.org/weather-conditions)
+    pub(crate) fn from_open_weather_map(code: u64) -> Self {
+        match code {
+            200..=232 => WeatherCode::Thunderstorm,
+            300..=321 => WeatherCode::Drizzle,
+            500..=504 => WeatherCode::Rain,
+            511 => WeatherCode::FreezingRain,
+            520..=531 => WeatherCode::RainShowers,
+            600..=602 => WeatherCode::SnowFall,
+            // Sleet and rain with snow
+            611..=616 => WeatherCode::FreezingRain,
+            620..=622 => WeatherCode::SnowShowers,
+            701 | 741 => WeatherCode::Fog,
+            800 => WeatherCode::ClearSky,
+            801 => WeatherCode::MainlyClear,
+            802 => WeatherCode::PartlyCloudy,
+            803 | 804 => WeatherCode::Overcast,
+            _ => WeatherCode::Unknown,
+        }
+    }
 }
 
 type DegreeRanges = (Option<(f64, f64)>, Option<(f64, f64)>);
@@ -413,8 +1828,9 @@ macro_rules! wind_direction_decl {
         )
     ),*]) => {
         #[allow(clippy::upper_case_acronyms)]
-        #[derive(Default, Debug)]
-        pub(crate) enum WindDirection {
+        #[derive(Default, Debug, Clone)]
+        #[non_exhaustive]
+        pub enum WindDirection {
             #[default]
             $($variant),*
         }
@@ -461,7 +1877,7 @@ wind_direction_decl!(17: [
 ]);
 
 impl WindDirection {
-    fn from_degrees(degrees: f64) -> Self {
+    pub(crate) fn from_degrees(degrees: f64) -> Self {
         let deg = (degrees % 360.0).round();
 
         Self::WIND_DIRECTIONS
@@ -480,3 +1896,480 @@ impl WindDirection {
             .unwrap() // We definitely know that the list of enum variants is not empty, so we can unwrap here
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// open_meteo response with the hourly temperatures at the times, and the current conditions at
+    /// 15:15
+    fn open_meteo(times: &[&str]) -> Map<String, Value> {
+        json!({
+            "utc_offset_seconds": 0,
+            "timezone": "GMT",
+            "current_weather": {
+                "time": "2024-06-14T15:15",
+                "temperature": 21.5,
+                "weathercode": 1,
+                "windspeed": 10.0,
+                "winddirection": 180.0,
+            },
+            "hourly_units": { "temperature_2m": "°C" },
+            "hourly": {
+                "time": times,
+                "temperature_2m": times.iter().map(|_| 20.0).collect::<Vec<_>>(),
+            },
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    /// met_no response with the temperatures at the times
+    fn met_no(times: &[&str]) -> Map<String, Value> {
+        json!({
+            "properties": {
+                "meta": { "units": { "air_temperature": "celsius" } },
+                "timeseries": times
+                    .iter()
+                    .map(|time| json!({
+                        "time": time,
+                        "data": { "instant": { "details": { "air_temperature": 20.0 } } },
+                    }))
+                    .collect::<Vec<_>>(),
+            },
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    fn parse(
+        json: Map<String, Value>,
+        provider: Provider,
+        range: Option<DateRange>,
+        time_format: TimeFormat,
+    ) -> Result<WeatherData> {
+        WeatherData::from_json(
+            &json,
+            provider,
+            ProviderRequestType::Forecast,
+            "2024-06-14".to_string(),
+            range,
+            ResolvedLocation::default(),
+            (false, time_format),
+        )
+    }
+
+    fn labels(data: &WeatherData) -> Vec<&str> {
+        data.timestamps
+            .iter()
+            .map(|timestamp| timestamp.label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn labels_in_the_clock() {
+        let hours = ["2024-06-14T00:00", "2024-06-14T09:00", "2024-06-14T15:00"];
+        let met_no_hours = [
+            "2024-06-14T00:00:00Z",
+            "2024-06-14T09:00:00Z",
+            "2024-06-14T15:00:00Z",
+        ];
+        let day = NaiveDate::from_ymd_opt(2024, 6, 14).unwrap();
+        let range = Some(DateRange {
+            start: day,
+            end: day,
+        });
+
+        for (json, provider) in [
+            (open_meteo(&hours), Provider::OpenMeteo),
+            (met_no(&met_no_hours), Provider::MetNo),
+        ] {
+            let cases: [(_, _, &[&str]); 4] = [
+                (TimeFormat::Hours12, None, &["12 AM", "09 AM", "03 PM"]),
+                (TimeFormat::Hours24, None, &["00:00", "09:00", "15:00"]),
+                (
+                    TimeFormat::Hours12,
+                    range,
+                    &["Fri 12 AM", "Fri 09 AM", "Fri 03 PM"],
+                ),
+                (
+                    TimeFormat::Hours24,
+                    range,
+                    &["Fri 00:00", "Fri 09:00", "Fri 15:00"],
+                ),
+            ];
+
+            for (time_format, range, expected) in cases {
+                let data = parse(json.clone(), provider, range, time_format).unwrap();
+
+                assert_eq!(labels(&data), expected, "{provider} {time_format:?}");
+                assert_eq!(data.time_format, time_format, "{provider}");
+            }
+        }
+    }
+
+    #[test]
+    fn current_time_in_the_clock() {
+        let json = open_meteo(&["2024-06-14T15:00"]);
+
+        let data = parse(json.clone(), Provider::OpenMeteo, None, TimeFormat::Hours12).unwrap();
+        assert_eq!(data.current_time().as_deref(), Some("2024-06-14 03:15 PM"));
+
+        let data = parse(json, Provider::OpenMeteo, None, TimeFormat::Hours24).unwrap();
+        assert_eq!(data.current_time().as_deref(), Some("2024-06-14 15:15"));
+    }
+
+    #[test]
+    fn unparsable_timestamps() {
+        let cases: &[(&[&str], &str)] = &[
+            (&["", ""], "Couldn't parse 2 timestamp(s): \"\", \"\""),
+            (&["garbage"], "Couldn't parse 1 timestamp(s): \"garbage\""),
+            (
+                &[
+                    "2024-06-14T00:00",
+                    "14:00 yesterday-ish",
+                    "2024-06-14T02:00",
+                ],
+                "Couldn't parse 1 timestamp(s): \"14:00 yesterday-ish\"",
+            ),
+            (
+                &["a", "b", "c", "d", "e", "f", "2024-06-14T00:00"],
+                "Couldn't parse 6 timestamp(s): \"a\", \"b\", \"c\", \"d\", \"e\", ...",
+            ),
+        ];
+
+        for (times, expected) in cases {
+            let mut json = open_meteo(times);
+            json.remove("current_weather");
+
+            let err = parse(json, Provider::OpenMeteo, None, TimeFormat::Hours24).unwrap_err();
+            assert!(
+                matches!(&err, WeatherError::InvalidResponse(message) if message == expected),
+                "{times:?}: {err}"
+            );
+
+            // With the current conditions to show instead, the chart is left out
+            let data = parse(
+                open_meteo(times),
+                Provider::OpenMeteo,
+                None,
+                TimeFormat::Hours24,
+            )
+            .unwrap();
+            assert!(data.timestamps.is_empty(), "{times:?}");
+            assert!(
+                data.caveat
+                    .as_ref()
+                    .is_some_and(|caveat| caveat.contains(expected)),
+                "{times:?}: {:?}",
+                data.caveat
+            );
+
+            // met_no has the times in a format of its own, anything else is an invalid response
+            let err = parse(met_no(times), Provider::MetNo, None, TimeFormat::Hours24).unwrap_err();
+            assert!(
+                matches!(err, WeatherError::InvalidResponse(_)),
+                "{times:?}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn astro_in_the_local_time() {
+        // The archive has no sunrise and sunset, the times are 2 hours ahead of UTC in Kyiv in January
+        let mut json = open_meteo(&["2024-01-25T00:00", "2024-01-25T01:00"]);
+        json.insert("utc_offset_seconds".to_string(), json!(7200));
+        json.remove("current_weather");
+        let mut data = parse(json, Provider::OpenMeteo, None, TimeFormat::Hours24).unwrap();
+        data.requested_date = "2024-01-25".to_string();
+        data.location.lat = 50.45;
+        data.location.lon = 30.52;
+
+        let astro = AstroData::new(&data).unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 25).unwrap();
+        let (sunrise, sunset) = astro::sunrise_sunset(50.45, 30.52, date).unwrap();
+
+        assert_eq!(astro.moon_phase, MoonPhase::Full);
+        assert!(astro.illumination > 99.0, "{}", astro.illumination);
+        assert_eq!(
+            astro.sunrise,
+            Some(
+                (sunrise + Duration::hours(2))
+                    .format(DAYLIGHT_FORMAT)
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            astro.sunset,
+            Some(
+                (sunset + Duration::hours(2))
+                    .format(DAYLIGHT_FORMAT)
+                    .to_string()
+            )
+        );
+        assert_eq!(astro.day_length, astro::day_length(50.45, date));
+    }
+
+    #[test]
+    fn earlier_hours_from_the_cache() {
+        let hours = (0..6)
+            .map(|hour| format!("2024-06-14T{hour:02}:00:00Z"))
+            .collect::<Vec<_>>();
+        let hours = hours.iter().map(String::as_str).collect::<Vec<_>>();
+        let day = NaiveDate::from_ymd_opt(2024, 6, 14).unwrap();
+        let range = Some(DateRange {
+            start: day,
+            end: day,
+        });
+        let age = Duration::hours(2);
+
+        // The earlier response has all the hours, the new one starts at 03:00
+        let earlier = || {
+            let mut data =
+                parse(met_no(&hours), Provider::MetNo, range, TimeFormat::Hours24).unwrap();
+            data.temperatures = vec![10.0, 11.0, 12.0, 13.0, 14.0, 15.0];
+            data.uv_indices = vec![1.0; 6];
+
+            data
+        };
+        let live = |range| {
+            let mut data = parse(
+                met_no(&hours[3..]),
+                Provider::MetNo,
+                range,
+                TimeFormat::Hours24,
+            )
+            .unwrap();
+            data.humidity = vec![50.0; 3];
+
+            data
+        };
+
+        let mut data = live(range);
+        data.merge_cached(earlier(), age).unwrap();
+
+        assert_eq!(
+            labels(&data),
+            [
+                "Fri 00:00",
+                "Fri 01:00",
+                "Fri 02:00",
+                "Fri 03:00",
+                "Fri 04:00",
+                "Fri 05:00"
+            ]
+        );
+        assert_eq!(data.temperatures, [10.0, 11.0, 12.0, 20.0, 20.0, 20.0]);
+        assert_eq!(
+            data.sources,
+            [
+                DataSource::Cached { age },
+                DataSource::Cached { age },
+                DataSource::Cached { age },
+                DataSource::Live,
+                DataSource::Live,
+                DataSource::Live,
+            ]
+        );
+        assert_eq!(data.stale_runs(), [(0, 2, DataSource::Cached { age })]);
+        // Still the same hour, now after the earlier ones
+        assert_eq!(data.current_hour, Some(3));
+        // Only the new response has the humidity, the earlier hours don't
+        assert_eq!(data.humidity.len(), 6);
+        assert!(data.humidity[..3].iter().all(|humidity| humidity.is_nan()));
+        // The new response has no UV index, so it's not made up from the earlier one
+        assert!(data.uv_indices.is_empty());
+        data.check_hourly_lengths().unwrap();
+
+        // The sources stay lined up with the hours left
+        let mut aligned = [data, live(range)];
+        align_timestamps(&mut aligned);
+        assert_eq!(aligned[0].sources, [DataSource::Live; 3]);
+
+        // The next hours don't start with the earlier ones
+        let mut data = live(None);
+        data.merge_cached(earlier(), age).unwrap();
+        assert_eq!(labels(&data), ["03:00", "04:00", "05:00"]);
+        assert!(data.sources.is_empty());
+
+        // Nothing to add if the new response has all the hours
+        let mut data = earlier();
+        data.merge_cached(live(range), age).unwrap();
+        assert_eq!(data.timestamps.len(), 6);
+        assert!(data.stale_runs().is_empty());
+    }
+
+    #[test]
+    fn merged_units() {
+        let hours = (0..6)
+            .map(|hour| format!("2024-06-14T{hour:02}:00:00Z"))
+            .collect::<Vec<_>>();
+        let hours = hours.iter().map(String::as_str).collect::<Vec<_>>();
+        let day = NaiveDate::from_ymd_opt(2024, 6, 14).unwrap();
+        let range = Some(DateRange {
+            start: day,
+            end: day,
+        });
+        let age = Duration::hours(2);
+
+        // The earlier response in °F and m/s, the new one from 03:00 in celsius and km/h
+        let earlier = || {
+            let mut data =
+                parse(met_no(&hours), Provider::MetNo, range, TimeFormat::Hours24).unwrap();
+            data.unit = "°F".to_string();
+            data.temperatures = vec![50.0, 59.0, 68.0, 77.0, 86.0, 95.0];
+            data.wind_speeds = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+            data.wind_speed_unit = "m/s".to_string();
+            data.precipitation = vec![0.0; 6];
+            data.precipitation_unit = "mm".to_string();
+
+            data
+        };
+        let live = || {
+            let mut data = parse(
+                met_no(&hours[3..]),
+                Provider::MetNo,
+                range,
+                TimeFormat::Hours24,
+            )
+            .unwrap();
+            data.wind_speeds = vec![10.0; 3];
+            data.wind_directions = vec![WindDirection::N; 3];
+            data.wind_speed_unit = "km/h".to_string();
+
+            data
+        };
+
+        let mut data = live();
+        data.merge_cached(earlier(), age).unwrap();
+        assert_eq!(data.temperatures, [10.0, 15.0, 20.0, 20.0, 20.0, 20.0]);
+        assert_eq!(data.unit, "celsius");
+        let wind = data
+            .wind_speeds
+            .iter()
+            .map(|speed| (speed * 10.0).round() / 10.0)
+            .collect_vec();
+        assert_eq!(wind, [3.6, 7.2, 10.8, 10.0, 10.0, 10.0]);
+        assert_eq!(data.wind_speed_unit, "km/h");
+        assert_eq!(
+            data.degradations(),
+            [
+                Degradation::ConvertedUnits {
+                    series: UnitSeries::Temperature,
+                    from: "°F".to_string(),
+                    to: "celsius".to_string(),
+                },
+                Degradation::ConvertedUnits {
+                    series: UnitSeries::WindSpeed,
+                    from: "m/s".to_string(),
+                    to: "km/h".to_string(),
+                },
+                Degradation::CachedHours {
+                    first: "Fri 00:00".to_string(),
+                    last: "Fri 02:00".to_string(),
+                    age,
+                },
+            ]
+        );
+
+        // The same unit written differently is not converted
+        let mut data = live();
+        let mut same = earlier();
+        same.unit = "°C".to_string();
+        same.wind_speed_unit = "km/h".to_string();
+        data.merge_cached(same, age).unwrap();
+        assert_eq!(data.temperatures[..3], [50.0, 59.0, 68.0]);
+        assert!(data
+            .degradations
+            .iter()
+            .all(|degradation| !matches!(degradation, Degradation::ConvertedUnits { .. })));
+
+        // A unit without a conversion fails, whichever side it's on
+        let mut data = live();
+        data.precipitation = vec![0.0; 3];
+        data.precipitation_unit = "l/m²".to_string();
+        let e = data.merge_cached(earlier(), age).unwrap_err();
+        assert!(matches!(e, WeatherError::IncompatibleUnits { .. }), "{e}");
+        assert_eq!(
+            e.to_string(),
+            "Can't merge the precipitation in mm with the precipitation in l/m², there is no \
+            conversion between them"
+        );
+    }
+
+    #[test]
+    fn repeated_and_sparse_hours() {
+        let hours = [
+            "2024-06-14T00:00:00Z",
+            "2024-06-14T01:00:00Z",
+            "2024-06-14T01:00:00Z",
+            "2024-06-14T02:00:00Z",
+            "2024-06-14T08:00:00Z",
+        ];
+        let data = parse(met_no(&hours), Provider::MetNo, None, TimeFormat::Hours24).unwrap();
+
+        // Only the first of the repeated hours is kept
+        assert_eq!(labels(&data), ["00:00", "01:00", "02:00", "08:00"]);
+        data.check_hourly_lengths().unwrap();
+        assert_eq!(
+            data.degradations(),
+            [
+                Degradation::DuplicateHours(1),
+                Degradation::SparseHours {
+                    from: "08:00".to_string()
+                },
+            ]
+        );
+
+        // Nothing to say about the hours one after another
+        let data = parse(
+            met_no(&hours[..2]),
+            Provider::MetNo,
+            None,
+            TimeFormat::Hours24,
+        )
+        .unwrap();
+        assert!(data.degradations().is_empty());
+    }
+
+    #[test]
+    fn met_no_symbols() {
+        for (symbol, code, glyph) in [
+            ("clearsky_day", WeatherCode::ClearSky, "☀"),
+            ("clearsky_polartwilight", WeatherCode::ClearSky, "☀"),
+            ("fair_night", WeatherCode::MainlyClear, "🌤"),
+            ("partlycloudy_day", WeatherCode::PartlyCloudy, "⛅"),
+            ("cloudy", WeatherCode::Overcast, "☁"),
+            ("fog", WeatherCode::Fog, "🌫"),
+            ("lightrain", WeatherCode::Rain, "🌧"),
+            ("heavyrain", WeatherCode::Rain, "🌧"),
+            ("lightrainshowers_night", WeatherCode::RainShowers, "🌦"),
+            ("rainshowers_day", WeatherCode::RainShowers, "🌦"),
+            ("sleet", WeatherCode::FreezingRain, "🌧"),
+            ("lightsleetshowers_day", WeatherCode::FreezingRain, "🌧"),
+            ("snow", WeatherCode::SnowFall, "❄"),
+            ("heavysnowshowers_night", WeatherCode::SnowShowers, "❄"),
+            ("rainandthunder", WeatherCode::Thunderstorm, "⛈"),
+            (
+                "lightssleetshowersandthunder_day",
+                WeatherCode::Thunderstorm,
+                "⛈",
+            ),
+            ("heavysnowandthunder", WeatherCode::Thunderstorm, "⛈"),
+            ("tornado", WeatherCode::Unknown, "?"),
+            ("", WeatherCode::Unknown, "?"),
+        ] {
+            let parsed = WeatherCode::from_met_no(symbol);
+
+            assert_eq!(parsed, code, "{symbol}");
+            assert_eq!(parsed.glyph(), glyph, "{symbol}");
+            // A single character without the emoji
+            assert_eq!(parsed.ascii_glyph().chars().count(), 1, "{symbol}");
+            assert!(parsed.ascii_glyph().is_ascii(), "{symbol}");
+        }
+    }
+}
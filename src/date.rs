@@ -0,0 +1,575 @@
+//! Helpers for making sense of the user provided dates that `dateparser` can't handle on its own
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use crate::error::{Result, WeatherError};
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// How far away (in years) we look for the nearest occurrence of a month/day pair, enough to reach
+/// the next leap year from anywhere
+const MAX_YEARS_AWAY: i32 = 8;
+
+/// Split the input into its date parts (separators don't matter for the checks we do)
+fn date_tokens(input: &str) -> impl Iterator<Item = &str> {
+    input
+        .split([' ', ',', '/', '.', '-'])
+        .filter(|token| !token.is_empty())
+}
+
+/// Check if the date input contains a year ("2024-06-05", "5/6/23", "June 5 2024"), as opposed to
+/// partial inputs like "June 5", "05.06" or "12:00"
+pub(crate) fn input_has_year(input: &str) -> bool {
+    let numeric_tokens = date_tokens(input)
+        .filter(|token| token.chars().all(|c| c.is_ascii_digit()))
+        .collect::<Vec<_>>();
+
+    numeric_tokens.iter().any(|token| token.len() == 4) || numeric_tokens.len() >= 3
+}
+
+/// Check if the date input contains the time of the day ("2024-06-01 18:00", "June 5 6pm"), as
+/// opposed to the dates alone that `dateparser` fills in with the current time
+pub(crate) fn input_has_time(input: &str) -> bool {
+    let input = input.to_lowercase();
+
+    input.contains(':')
+        || date_tokens(&input).any(|token| {
+            ["am", "pm"].into_iter().any(|suffix| {
+                token.strip_suffix(suffix).is_some_and(|hour| {
+                    !hour.is_empty() && hour.chars().all(|c| c.is_ascii_digit())
+                })
+            })
+        })
+}
+
+/// Parse a month name or its abbreviation ("Jun", "june", "Sept") into the month number
+fn parse_month(token: &str) -> Option<u32> {
+    let token = token.to_lowercase();
+
+    if token.len() < 3 {
+        return None;
+    }
+
+    MONTHS
+        .iter()
+        .position(|month| month.starts_with(&token))
+        .map(|index| index as u32 + 1)
+}
+
+/// Parse a date without a year ("June 5", "5 Jun", "05.06", "06/05") into a (month, day) pair.
+///
+/// Dotted dates are always day first, while for slashes and dashes `month_first` decides the order
+/// (it should come from the locale, as "06/05" means different things in the US and in Europe)
+pub(crate) fn parse_partial_date(input: &str, month_first: bool) -> Option<(u32, u32)> {
+    let input = input.trim();
+
+    if input.contains(':') || input_has_year(input) {
+        return None;
+    }
+
+    let tokens = date_tokens(input).collect::<Vec<_>>();
+    let [first, second] = tokens.as_slice() else {
+        return None;
+    };
+
+    let (month, day) = match (parse_month(first), parse_month(second)) {
+        // "June 5"
+        (Some(month), None) => (month, second.parse::<u32>().ok()?),
+        // "5 June"
+        (None, Some(month)) => (month, first.parse::<u32>().ok()?),
+        (None, None) => {
+            let first = first.parse::<u32>().ok()?;
+            let second = second.parse::<u32>().ok()?;
+
+            match month_first && !input.contains('.') {
+                true => (first, second),
+                false => (second, first),
+            }
+        }
+        (Some(_), Some(_)) => return None,
+    };
+
+    match (1..=12).contains(&month) && (1..=31).contains(&day) {
+        true => Some((month, day)),
+        false => None,
+    }
+}
+
+/// Find the nearest date with the given month and day: the next one (including today) or, if
+/// `past` is set, the previous one (including today).
+///
+/// Returns `None` if the month/day pair doesn't exist in any year ("Feb 30")
+pub(crate) fn nearest_occurrence(
+    month: u32,
+    day: u32,
+    today: NaiveDate,
+    past: bool,
+) -> Option<NaiveDate> {
+    (0..=MAX_YEARS_AWAY)
+        .map(|years_away| match past {
+            true => today.year() - years_away,
+            false => today.year() + years_away,
+        })
+        .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+        .find(|date| match past {
+            true => *date <= today,
+            false => *date >= today,
+        })
+}
+
+/// Check if the user's locale writes numeric dates month first (US style "06/05" for June 5th)
+pub(crate) fn locale_is_month_first() -> bool {
+    ["LC_ALL", "LC_TIME", "LANG"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|locale| !locale.is_empty())
+        .map(|locale| locale.starts_with("en_US"))
+        .unwrap_or_default()
+}
+
+/// Check if the input is something the requests take as the date ("now", a date, a range of days)
+/// on `today`, to tell it apart from the addresses when they are given together. The dates that
+/// don't exist ("Feb 30") still count, so that the error is about the date and not about the address
+pub fn is_date(input: &str, today: NaiveDate) -> bool {
+    let input = input.trim();
+
+    input == "now"
+        || split_range(input).is_some()
+        || parse_relative_range(input, today).is_some()
+        || parse_relative_date(input, today).is_some()
+        || parse_partial_date(input, locale_is_month_first()).is_some()
+        || dateparser::parse(input).is_ok()
+        || has_numeric_date_shape(input)
+}
+
+/// Check if the input is written like a numeric date ("2024-13-01", "99/99", "31.02.2024"),
+/// whether or not the date exists
+fn has_numeric_date_shape(input: &str) -> bool {
+    let Some(separator) = input.chars().find(|c| ['-', '/', '.'].contains(c)) else {
+        return false;
+    };
+    let parts = input.split(separator).collect::<Vec<_>>();
+
+    (2..=3).contains(&parts.len())
+        && parts
+            .iter()
+            .all(|part| (1..=4).contains(&part.len()) && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Whole days the weather is requested for (both ends included)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateRange {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        (self.start..=self.end).contains(&date)
+    }
+}
+
+/// Split a range input ("2024-01-01..2024-01-07") into its start and end parts
+pub(crate) fn split_range(input: &str) -> Option<(&str, &str)> {
+    input
+        .split_once("..")
+        .map(|(start, end)| (start.trim(), end.trim()))
+}
+
+/// Parse a day relative to today ("tomorrow", "yesterday", "in 3 days", "2 days ago") or a
+/// weekday ("friday" is the next one, today included, and "last friday" the previous one)
+pub(crate) fn parse_relative_date(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let input = input.trim().to_lowercase();
+    let tokens = input.split_whitespace().collect::<Vec<_>>();
+
+    let days = match tokens.as_slice() {
+        ["today"] => 0,
+        ["tomorrow"] => 1,
+        ["yesterday"] => -1,
+        ["in", count, "day" | "days"] => count.parse::<u32>().ok()?.into(),
+        [count, "day" | "days", "ago"] => -i64::from(count.parse::<u32>().ok()?),
+        [weekday] => {
+            let weekday = weekday.parse::<Weekday>().ok()?;
+
+            // Days until the weekday, 0 if it's today
+            i64::from(
+                (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7,
+            )
+        }
+        ["last", weekday] => {
+            let weekday = weekday.parse::<Weekday>().ok()?;
+
+            // Days since the weekday, a whole week if it's today
+            -i64::from(
+                (today.weekday().num_days_from_monday() + 6 - weekday.num_days_from_monday()) % 7
+                    + 1,
+            )
+        }
+        _ => return None,
+    };
+
+    today.checked_add_signed(chrono::Duration::days(days))
+}
+
+/// Parse a relative range ("next 3 days", "last 2 days", "past week") into a range of whole days.
+/// "week" alone is the next 7 days. `None` if it's not a relative range, and an error if it is one
+/// but it reaches too far for a date.
+///
+/// Today is included in the upcoming days, but not in the past ones, as its data is not in the
+/// archive yet
+pub(crate) fn parse_relative_range(input: &str, today: NaiveDate) -> Option<Result<DateRange>> {
+    let input = input.trim().to_lowercase();
+    let tokens = input.split_whitespace().collect::<Vec<_>>();
+
+    let (direction, count, unit_days) = match tokens.as_slice() {
+        ["week"] => ("next", "1", 7),
+        [direction, "week"] => (*direction, "1", 7),
+        [direction, count, "day" | "days"] => (*direction, *count, 1),
+        [direction, count, "week" | "weeks"] => (*direction, *count, 7),
+        _ => return None,
+    };
+
+    if !["next", "last", "past"].contains(&direction)
+        || count.is_empty()
+        || !count.chars().all(|c| c.is_ascii_digit())
+        || count.chars().all(|c| c == '0')
+    {
+        return None;
+    }
+
+    // The counts too big for a date are still ranges, just not ones that can be requested
+    let range = || {
+        let days = count.parse::<u32>().ok()?.checked_mul(unit_days)?;
+        let duration = |days: u32| chrono::Duration::try_days(days.into());
+
+        match direction {
+            "next" => Some(DateRange {
+                start: today,
+                end: today.checked_add_signed(duration(days - 1)?)?,
+            }),
+            _ => Some(DateRange {
+                start: today.checked_sub_signed(duration(days)?)?,
+                end: today.checked_sub_signed(duration(1)?)?,
+            }),
+        }
+    };
+
+    Some(range().ok_or(WeatherError::InvalidDate(format!(
+        "Invalid date range: {input} reaches too far from today"
+    ))))
+}
+
+/// The ways `weather get` can be given its date: the date itself, `--days` ("next <N> days") or
+/// `--week` ("week"), only one of them at a time
+#[derive(Debug, Default, Clone)]
+pub struct DateOptions {
+    pub date: Option<String>,
+    pub days: Option<u16>,
+    pub week: bool,
+    /// The next condition is looked for (`--next`), in the hours ahead and not only in the ones of
+    /// today
+    pub next: bool,
+}
+
+impl DateOptions {
+    /// The date to request, "now" if none was given
+    pub fn resolve(self) -> Result<String> {
+        let date = match (self.days, self.date) {
+            (Some(days), Some(date)) => {
+                return Err(WeatherError::InvalidInput(format!(
+                    "--days shows the next {days} days, it can't be used with a date ({date})"
+                )));
+            }
+            (Some(days), None) => Some(format!("next {days} days")),
+            (None, date) => date,
+        };
+
+        match (self.week, date) {
+            (true, Some(date)) if !is_week(&date) => Err(WeatherError::InvalidInput(format!(
+                "--week shows the next 7 days, it can't be used with a date ({date})"
+            ))),
+            (true, _) => Ok("week".to_string()),
+            (false, date) => Ok(date.unwrap_or_else(|| match self.next {
+                true => "next 2 days".to_string(),
+                false => "now".to_string(),
+            })),
+        }
+    }
+}
+
+/// Whether the date is the week, the one view that shows whole days
+pub fn is_week(date: &str) -> bool {
+    date.trim().eq_ignore_ascii_case("week")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn relative_dates() {
+        // A Friday
+        let friday = date(2024, 6, 14);
+        // The end of the week, and of the month
+        let sunday = date(2024, 6, 30);
+
+        for (input, today, expected) in [
+            ("today", friday, Some(friday)),
+            ("tomorrow", friday, Some(date(2024, 6, 15))),
+            ("yesterday", friday, Some(date(2024, 6, 13))),
+            ("Tomorrow", friday, Some(date(2024, 6, 15))),
+            ("  yesterday ", friday, Some(date(2024, 6, 13))),
+            ("in 3 days", friday, Some(date(2024, 6, 17))),
+            ("in 1 day", friday, Some(date(2024, 6, 15))),
+            ("in 0 days", friday, Some(friday)),
+            ("2 days ago", friday, Some(date(2024, 6, 12))),
+            ("20 days ago", friday, Some(date(2024, 5, 25))),
+            // The weekdays are the next ones, today included
+            ("friday", friday, Some(friday)),
+            ("saturday", friday, Some(date(2024, 6, 15))),
+            ("thursday", friday, Some(date(2024, 6, 20))),
+            ("mon", friday, Some(date(2024, 6, 17))),
+            // ... and the last ones a whole week back if it's today
+            ("last friday", friday, Some(date(2024, 6, 7))),
+            ("last thursday", friday, Some(date(2024, 6, 13))),
+            ("last saturday", friday, Some(date(2024, 6, 8))),
+            // Around the end of the week (and of the month)
+            ("monday", sunday, Some(date(2024, 7, 1))),
+            ("sunday", sunday, Some(sunday)),
+            ("saturday", sunday, Some(date(2024, 7, 6))),
+            ("last sunday", sunday, Some(date(2024, 6, 23))),
+            ("last monday", sunday, Some(date(2024, 6, 24))),
+            ("tomorrow", sunday, Some(date(2024, 7, 1))),
+            // Left to dateparser
+            ("in three days", friday, None),
+            ("in -3 days", friday, None),
+            ("next friday", friday, None),
+            ("fridays", friday, None),
+            ("2024-06-14", friday, None),
+            ("", friday, None),
+        ] {
+            assert_eq!(
+                parse_relative_date(input, today),
+                expected,
+                "{input} on {today}"
+            );
+        }
+    }
+
+    #[test]
+    fn partial_dates() {
+        for (input, has_year) in [
+            ("2024-06-05", true),
+            ("5/6/23", true),
+            ("June 5 2024", true),
+            ("05.06.2024", true),
+            ("June 5", false),
+            ("5 Jun", false),
+            ("05.06", false),
+            ("06/05", false),
+            ("12:00", false),
+        ] {
+            assert_eq!(input_has_year(input), has_year, "{input}");
+        }
+
+        // Dots are day first, slashes and dashes follow the locale
+        for (input, month_first, expected) in [
+            ("June 5", false, Some((6, 5))),
+            ("5 Jun", true, Some((6, 5))),
+            ("sept 30", false, Some((9, 30))),
+            ("05.06", false, Some((6, 5))),
+            ("05.06", true, Some((6, 5))),
+            ("06/05", true, Some((6, 5))),
+            ("06/05", false, Some((5, 6))),
+            ("06-05", true, Some((6, 5))),
+            ("06-05", false, Some((5, 6))),
+            // Only one of the orders is a date
+            ("13/05", true, None),
+            ("13/05", false, Some((5, 13))),
+            ("05/13", true, Some((5, 13))),
+            ("05/13", false, None),
+            ("31/31", false, None),
+            ("Ju 5", false, None),
+            ("June July", false, None),
+            ("June 5 2024", false, None),
+            ("June 5 12:00", false, None),
+        ] {
+            assert_eq!(
+                parse_partial_date(input, month_first),
+                expected,
+                "{input}, month first: {month_first}"
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_occurrences() {
+        let new_year = date(2025, 1, 1);
+        let new_years_eve = date(2024, 12, 31);
+
+        for ((month, day), today, past, expected) in [
+            // Around the year boundary, today included
+            ((12, 31), new_year, false, Some(date(2025, 12, 31))),
+            ((12, 31), new_year, true, Some(new_years_eve)),
+            ((1, 1), new_years_eve, false, Some(new_year)),
+            ((1, 1), new_years_eve, true, Some(date(2024, 1, 1))),
+            ((12, 31), new_years_eve, false, Some(new_years_eve)),
+            ((12, 31), new_years_eve, true, Some(new_years_eve)),
+            // The leap day is in the next leap year, or the last one
+            ((2, 29), date(2025, 3, 1), false, Some(date(2028, 2, 29))),
+            ((2, 29), date(2025, 3, 1), true, Some(date(2024, 2, 29))),
+            ((2, 29), date(2024, 2, 28), false, Some(date(2024, 2, 29))),
+            ((2, 29), date(2024, 3, 1), false, Some(date(2028, 2, 29))),
+            // Skipping 2100, which isn't a leap year
+            ((2, 29), date(2097, 1, 1), false, Some(date(2104, 2, 29))),
+            // Never
+            ((2, 30), new_year, false, None),
+            ((4, 31), new_year, true, None),
+        ] {
+            assert_eq!(
+                nearest_occurrence(month, day, today, past),
+                expected,
+                "{month}/{day} on {today}, past: {past}"
+            );
+        }
+    }
+
+    #[test]
+    fn relative_dates_too_far() {
+        assert_eq!(
+            parse_relative_date("in 4294967295 days", date(2024, 6, 14)),
+            None
+        );
+        assert_eq!(
+            parse_relative_date("in 4294967296 days", date(2024, 6, 14)),
+            None
+        );
+    }
+
+    #[test]
+    fn relative_ranges() {
+        let today = date(2024, 6, 14);
+        let range = |start, end| Some(DateRange { start, end });
+
+        for (input, expected) in [
+            ("week", range(today, date(2024, 6, 20))),
+            ("next week", range(today, date(2024, 6, 20))),
+            ("next 3 days", range(today, date(2024, 6, 16))),
+            ("next 1 day", range(today, today)),
+            ("Next 2 Weeks", range(today, date(2024, 6, 27))),
+            ("last 2 days", range(date(2024, 6, 12), date(2024, 6, 13))),
+            ("past week", range(date(2024, 6, 7), date(2024, 6, 13))),
+            ("next 0 days", None),
+            ("next -3 days", None),
+            ("next +3 days", None),
+            ("soon 3 days", None),
+            ("3 days", None),
+        ] {
+            assert_eq!(
+                parse_relative_range(input, today).map(|range| range.unwrap()),
+                expected,
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn relative_ranges_too_far_are_errors() {
+        let today = date(2024, 6, 14);
+
+        for input in [
+            "next 999999999 days",
+            "next 99999999999999999 weeks",
+            "next 4294967295 weeks",
+            "past 4294967295 days",
+        ] {
+            assert!(
+                matches!(
+                    parse_relative_range(input, today),
+                    Some(Err(WeatherError::InvalidDate(_)))
+                ),
+                "{input}"
+            );
+            // Still a date, so it's not taken for an address
+            assert!(is_date(input, today), "{input}");
+        }
+    }
+
+    #[test]
+    fn invalid_dates_are_still_dates() {
+        let today = date(2024, 6, 14);
+
+        for input in [
+            "9999-99-99",
+            "2024-13-01",
+            "2024-02-30",
+            "31/31",
+            "99.99.2024",
+        ] {
+            assert!(is_date(input, today), "{input}");
+        }
+        // The coordinates, the numbers and the addresses are not
+        for input in [
+            "50,30",
+            "50.45,30.52",
+            "221",
+            "Baker Street 221b",
+            "12345-6789-1-2",
+        ] {
+            assert!(!is_date(input, today), "{input}");
+        }
+    }
+
+    #[test]
+    fn date_options() {
+        let options = |date: Option<&str>, days, week, next| DateOptions {
+            date: date.map(String::from),
+            days,
+            week,
+            next,
+        };
+
+        for (options, expected) in [
+            (options(None, None, false, false), "now"),
+            (options(None, None, false, true), "next 2 days"),
+            (options(Some("tomorrow"), None, false, true), "tomorrow"),
+            (options(None, Some(3), false, false), "next 3 days"),
+            (options(None, None, true, false), "week"),
+            (options(Some("Week"), None, true, false), "week"),
+        ] {
+            assert_eq!(options.clone().resolve().unwrap(), expected, "{options:?}");
+        }
+
+        // Only one of them at a time
+        for options in [
+            options(Some("tomorrow"), Some(3), false, false),
+            options(Some("tomorrow"), None, true, false),
+            options(None, Some(3), true, false),
+        ] {
+            assert!(
+                matches!(
+                    options.clone().resolve(),
+                    Err(WeatherError::InvalidInput(_))
+                ),
+                "{options:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Errors of the weather library, split by what went wrong so that the callers can react to them
+
+use std::fmt::{Display, Formatter};
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+
+use crate::{data::UnitSeries, providers::Provider};
+
+/// Everything that can go wrong while getting the weather
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum WeatherError {
+    /// The date couldn't be parsed, or the provider doesn't have the data for it
+    #[error("{0}")]
+    InvalidDate(String),
+    /// An argument or a value given by the user is invalid
+    #[error("{0}")]
+    InvalidInput(String),
+    /// The coordinates are out of range
+    #[error("{0}")]
+    InvalidLocation(String),
+    /// The address couldn't be resolved into coordinates (or the coordinates into an address)
+    #[error("{0}")]
+    Geocoding(String),
+    /// The provider doesn't support this kind of request
+    #[error("{0}")]
+    Unsupported(String),
+    /// The provider can't be used without an API key
+    #[error(
+        "{provider} requires an API key, set it with `weather configure {provider} --api-key <key>`"
+    )]
+    MissingApiKey { provider: Provider },
+    /// The API told us to back off, no requests are sent to it until then
+    #[error(
+        "{who} is rate-limiting this client until {}, please try again later",
+        until.with_timezone(&Local).format("%H:%M")
+    )]
+    RateLimited { who: String, until: DateTime<Utc> },
+    /// The provider answered with an error
+    #[error("{0}")]
+    Provider(String),
+    /// The provider answered with something we don't understand
+    #[error("{0}")]
+    InvalidResponse(String),
+    /// Only the cached data was asked for, but nothing is cached for the request
+    #[error("{0}")]
+    NotCached(String),
+    /// The config file or one of its values is invalid
+    #[error("{0}")]
+    Config(String),
+    /// The same series came in units that can't be converted into each other, so they can't be
+    /// merged
+    #[error("Can't merge the {series} in {from} with the {series} in {to}, there is no conversion between them")]
+    IncompatibleUnits {
+        series: UnitSeries,
+        from: String,
+        to: String,
+    },
+    /// The data couldn't be published to the destination of `--publish`
+    #[error("{0}")]
+    Publish(String),
+    /// A chunk of an export failed, the rows before it are in the file
+    #[error("{source}; the rows before {resume_from} are saved, continue with `--resume-from {resume_from}`")]
+    ExportInterrupted {
+        resume_from: NaiveDate,
+        source: Box<WeatherError>,
+    },
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+impl WeatherError {
+    /// Who is at fault, for the callers that don't care about the details (like the exit codes)
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::InvalidDate(_)
+            | Self::InvalidInput(_)
+            | Self::InvalidLocation(_)
+            | Self::Unsupported(_) => ErrorCategory::InvalidInput,
+            Self::Geocoding(_) => ErrorCategory::Geocoding,
+            // A response that is not what we expected is the provider's fault, not the network's
+            Self::Http(e) if e.is_decode() => ErrorCategory::ProviderResponse,
+            Self::Http(_) | Self::Publish(_) => ErrorCategory::Network,
+            Self::RateLimited { .. }
+            | Self::Provider(_)
+            | Self::InvalidResponse(_)
+            | Self::IncompatibleUnits { .. } => ErrorCategory::ProviderResponse,
+            Self::Config(_) | Self::MissingApiKey { .. } | Self::Json(_) => ErrorCategory::Config,
+            Self::NotCached(_) | Self::Io(_) => ErrorCategory::Other,
+            // The export failed the way its chunk did
+            Self::ExportInterrupted { source, .. } => source.category(),
+        }
+    }
+}
+
+/// Broad kind of a [`WeatherError`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorCategory {
+    InvalidInput,
+    Geocoding,
+    Network,
+    ProviderResponse,
+    Config,
+    Other,
+}
+
+impl Display for ErrorCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidInput => write!(f, "input"),
+            Self::Geocoding => write!(f, "geocoding"),
+            Self::Network => write!(f, "network"),
+            Self::ProviderResponse => write!(f, "provider"),
+            Self::Config => write!(f, "config"),
+            Self::Other => write!(f, "other"),
+        }
+    }
+}
+
+pub type Result<T, E = WeatherError> = std::result::Result<T, E>;
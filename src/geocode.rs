@@ -0,0 +1,1136 @@
+//! Address <-> coordinates resolution through the OpenStreetMap Nominatim API, with the Open-Meteo
+//! geocoding API to fall back to when Nominatim is down or rate-limits us
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    cassette::Cassette,
+    error::{Result, WeatherError},
+    locale::Language,
+    paths::Paths,
+    providers::logged_body,
+};
+
+const NOMINATIM_BASE_URL: &str = "https://nominatim.openstreetmap.org";
+
+const OPEN_METEO_GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+
+/// How many places the Open-Meteo search returns (Nominatim returns up to 10 by default too)
+const OPEN_METEO_GEOCODING_COUNT: &str = "10";
+
+/// Nominatim requires an identifying User-Agent for every request
+const USER_AGENT: &str = "tukweathercli/0.1.0";
+
+/// Parameters shared by every geocoding query
+#[derive(Debug, Clone)]
+pub(crate) struct GeocodeRequest {
+    /// Value for the `Accept-Language` header, so that the resolved place names come back in the
+    /// user's language (if `None`, the header is not sent and Nominatim picks the local names)
+    pub(crate) accept_language: Option<String>,
+}
+
+impl Default for GeocodeRequest {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl GeocodeRequest {
+    /// The names in the language if it was chosen, in the one of the locale otherwise
+    pub(crate) fn new(language: Option<Language>) -> Self {
+        Self {
+            accept_language: language
+                .map(|language| language.name().to_string())
+                .or_else(accept_language_from_env),
+        }
+    }
+}
+
+/// Read the addresses from the file, one per line (the empty lines and the # comments are skipped)
+pub fn read_addresses(path: &Path) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        WeatherError::InvalidInput(format!("Couldn't read {}: {e}", path.display()))
+    })?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// A location resolved from the user input once per invocation, and then shared by every request
+/// and parser that needs to know where the weather is requested for
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ResolvedLocation {
+    pub lat: f64,
+    pub lon: f64,
+    /// Whole name of the place, as the geocoder has it
+    pub display_name: String,
+    /// Settlement and country of the coordinates ("Kyiv, Ukraine") for the titles, where the whole
+    /// address of a street doesn't fit (`None` for the searched addresses, they are named the way
+    /// they were found)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub short_name: Option<String>,
+    /// IANA timezone of the place ("Europe/Kyiv"), if the geocoder knows it, for the providers that
+    /// only have the times in UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// ISO 3166-1 code of the country ("UA"), if the geocoder knows it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+}
+
+impl Default for ResolvedLocation {
+    fn default() -> Self {
+        Self {
+            lat: 0.0,
+            lon: 0.0,
+            display_name: "Unknown".to_string(),
+            short_name: None,
+            timezone: None,
+            country_code: None,
+        }
+    }
+}
+
+impl ResolvedLocation {
+    /// Name to show: the short one if there is one, the whole one otherwise
+    pub fn name(&self) -> &str {
+        self.short_name.as_deref().unwrap_or(&self.display_name)
+    }
+}
+
+/// Address of the place at the coordinates
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Address {
+    /// Whole address ("вулиця Хрещатик, Печерський район, Київ, 01001, Україна")
+    pub full: String,
+    /// Settlement (or the area around it) and the country ("Київ, Україна"), `None` if the address
+    /// has neither
+    pub short: Option<String>,
+    /// ISO 3166-1 code of the country ("UA"), `None` outside of the countries (and in the addresses
+    /// cached before it was kept)
+    #[serde(default)]
+    pub country_code: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct NominatimPlace {
+    lat: String,
+    lon: String,
+    display_name: Option<String>,
+    address: Option<NominatimAddress>,
+}
+
+/// Reverse geocoding result, nothing of it is there for the places without an address (the
+/// oceans, where Nominatim answers with an error message instead)
+#[derive(serde::Deserialize)]
+struct NominatimReverse {
+    display_name: Option<String>,
+    address: Option<NominatimAddress>,
+}
+
+/// Parts of the address, only the ones the short name is made of
+#[derive(Default, serde::Deserialize)]
+struct NominatimAddress {
+    city: Option<String>,
+    town: Option<String>,
+    village: Option<String>,
+    hamlet: Option<String>,
+    municipality: Option<String>,
+    county: Option<String>,
+    state: Option<String>,
+    country: Option<String>,
+    /// Lowercase ("ua")
+    country_code: Option<String>,
+}
+
+impl NominatimAddress {
+    /// The settlement, or the area it's in if the point is outside of the settlements, with the
+    /// country ("Kyiv, Ukraine", "Sumy Oblast, Ukraine")
+    fn short_name(&self) -> Option<String> {
+        let place = [
+            &self.city,
+            &self.town,
+            &self.village,
+            &self.hamlet,
+            &self.municipality,
+            &self.county,
+            &self.state,
+        ]
+        .into_iter()
+        .flatten()
+        .next();
+
+        match (place, &self.country) {
+            (Some(place), Some(country)) => Some(format!("{place}, {country}")),
+            (Some(place), None) => Some(place.clone()),
+            (None, country) => country.clone(),
+        }
+    }
+}
+
+/// Address out of the reverse geocoding response, `None` if there is nothing at the coordinates
+fn parse_reverse(body: &str) -> Result<Option<Address>> {
+    let place: NominatimReverse = serde_json::from_str(body).map_err(|e| {
+        WeatherError::InvalidResponse(format!("Nominatim sent an invalid response: {e}"))
+    })?;
+
+    let address = place.address.unwrap_or_default();
+
+    Ok(place.display_name.map(|full| Address {
+        short: address.short_name(),
+        full,
+        country_code: address.country_code.map(|code| code.to_uppercase()),
+    }))
+}
+
+/// Backend resolving the addresses to coordinates and back ([`Nominatim`] unless another one is
+/// passed to [`WeatherClientBuilder::geocoder`](crate::WeatherClientBuilder::geocoder))
+pub trait Geocoder: std::fmt::Debug + Send + Sync {
+    /// Get all the places matching the address, the best match first
+    fn forward(&self, address: &str) -> Result<Vec<ResolvedLocation>>;
+
+    /// Get the address of the place at the coordinates (`None` if there is nothing there)
+    fn reverse(&self, lat: f64, lon: f64) -> Result<Option<Address>>;
+
+    /// Name of the backend in the logs, to tell which one answered
+    fn name(&self) -> &'static str {
+        "custom"
+    }
+}
+
+/// Send the geocoding request and get the body of the successful response
+fn send(request: reqwest::blocking::RequestBuilder, backend: &str) -> Result<String> {
+    let started = Instant::now();
+    let response = match Cassette::from_env()? {
+        Some(cassette) => cassette.send(request)?,
+        None => request.send()?,
+    };
+    tracing::debug!(
+        status = %response.status(),
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "Got the {backend} response"
+    );
+
+    let body = response.error_for_status()?.text()?;
+    tracing::trace!(body = %logged_body(&body), "{backend} response body");
+
+    Ok(body)
+}
+
+/// The OpenStreetMap Nominatim API
+#[derive(Debug)]
+pub struct Nominatim {
+    client: reqwest::blocking::Client,
+    request: GeocodeRequest,
+    base_url: String,
+}
+
+impl Default for Nominatim {
+    fn default() -> Self {
+        Self::new(GeocodeRequest::default(), reqwest::blocking::Client::new())
+    }
+}
+
+impl Nominatim {
+    pub(crate) fn new(request: GeocodeRequest, client: reqwest::blocking::Client) -> Self {
+        Self {
+            client,
+            request,
+            base_url: NOMINATIM_BASE_URL.to_string(),
+        }
+    }
+
+    /// Send the requests to the server instead of the public Nominatim instance
+    #[cfg(test)]
+    fn with_base_url(self, base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            ..self
+        }
+    }
+
+    /// Send the query to the endpoint and parse the JSON response
+    fn get<T: DeserializeOwned>(&self, endpoint: &str, query: &[(&str, &str)]) -> Result<T> {
+        let body = self.get_body(endpoint, query)?;
+
+        serde_json::from_str(&body).map_err(|e| {
+            WeatherError::InvalidResponse(format!("Nominatim sent an invalid response: {e}"))
+        })
+    }
+
+    /// Send the query to the endpoint
+    #[tracing::instrument(skip(self))]
+    fn get_body(&self, endpoint: &str, query: &[(&str, &str)]) -> Result<String> {
+        let mut request = self
+            .client
+            .get(format!("{}/{endpoint}", self.base_url))
+            .query(query)
+            .header("User-Agent", USER_AGENT);
+
+        // Only send the language preference if we actually have one
+        if let Some(accept_language) = &self.request.accept_language {
+            request = request.header("Accept-Language", accept_language);
+        }
+
+        send(request, "Nominatim")
+    }
+}
+
+impl Geocoder for Nominatim {
+    /// The places without a name are named after the address itself
+    fn forward(&self, address: &str) -> Result<Vec<ResolvedLocation>> {
+        let places: Vec<NominatimPlace> = self.get(
+            "search",
+            &[
+                ("q", address),
+                ("format", "jsonv2"),
+                ("addressdetails", "1"),
+            ],
+        )?;
+
+        places
+            .into_iter()
+            .map(
+                |place| match (place.lat.parse::<f64>(), place.lon.parse::<f64>()) {
+                    (Ok(lat), Ok(lon)) => Ok(ResolvedLocation {
+                        lat,
+                        lon,
+                        display_name: place.display_name.unwrap_or(address.to_string()),
+                        short_name: None,
+                        timezone: None,
+                        country_code: place
+                            .address
+                            .and_then(|address| address.country_code)
+                            .map(|code| code.to_uppercase()),
+                    }),
+                    _ => Err(WeatherError::InvalidResponse(format!(
+                        "Nominatim returned invalid coordinates ({}, {})",
+                        place.lat, place.lon
+                    ))),
+                },
+            )
+            .collect()
+    }
+
+    /// The parts of the address are asked for too, the short name is made of them
+    fn reverse(&self, lat: f64, lon: f64) -> Result<Option<Address>> {
+        let body = self.get_body(
+            "reverse",
+            &[
+                ("lat", lat.to_string().as_str()),
+                ("lon", lon.to_string().as_str()),
+                ("format", "jsonv2"),
+                ("addressdetails", "1"),
+            ],
+        )?;
+
+        parse_reverse(&body)
+    }
+
+    fn name(&self) -> &'static str {
+        "nominatim"
+    }
+}
+
+/// Places found by the Open-Meteo geocoding API (there is no `results` at all if nothing was)
+#[derive(serde::Deserialize)]
+struct OpenMeteoSearch {
+    #[serde(default)]
+    results: Vec<OpenMeteoPlace>,
+}
+
+#[derive(serde::Deserialize)]
+struct OpenMeteoPlace {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    /// Region ("Kyiv City")
+    admin1: Option<String>,
+    /// District, for the smaller places
+    admin2: Option<String>,
+    country: Option<String>,
+    /// Uppercase already ("UA")
+    country_code: Option<String>,
+    /// IANA timezone ("Europe/Kyiv")
+    timezone: Option<String>,
+}
+
+impl OpenMeteoPlace {
+    /// Name with the district, the region and the country, the way Nominatim names the places
+    /// ("Kyiv, Kyiv City, Ukraine"), without the parts that repeat the name
+    fn display_name(&self) -> String {
+        let mut parts: Vec<&str> = vec![&self.name];
+
+        for part in [&self.admin2, &self.admin1, &self.country]
+            .into_iter()
+            .flatten()
+        {
+            if !part.is_empty() && !parts.contains(&part.as_str()) {
+                parts.push(part);
+            }
+        }
+
+        parts.join(", ")
+    }
+}
+
+/// The Open-Meteo geocoding API (from the GeoNames data). It only searches for the place names,
+/// not the street addresses, and can't find the addresses of the coordinates
+#[derive(Debug)]
+pub struct OpenMeteoGeocoder {
+    client: reqwest::blocking::Client,
+    request: GeocodeRequest,
+    url: String,
+}
+
+impl Default for OpenMeteoGeocoder {
+    fn default() -> Self {
+        Self::new(GeocodeRequest::default(), reqwest::blocking::Client::new())
+    }
+}
+
+impl OpenMeteoGeocoder {
+    pub(crate) fn new(request: GeocodeRequest, client: reqwest::blocking::Client) -> Self {
+        Self {
+            client,
+            request,
+            url: OPEN_METEO_GEOCODING_URL.to_string(),
+        }
+    }
+
+    /// Send the searches to the server instead of the Open-Meteo API
+    #[cfg(test)]
+    fn with_url(self, url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            ..self
+        }
+    }
+}
+
+impl Geocoder for OpenMeteoGeocoder {
+    fn forward(&self, address: &str) -> Result<Vec<ResolvedLocation>> {
+        let mut query = vec![
+            ("name", address),
+            ("count", OPEN_METEO_GEOCODING_COUNT),
+            ("format", "json"),
+        ];
+
+        // It takes a single language code ("uk" out of "uk-UA, uk")
+        let language = self
+            .request
+            .accept_language
+            .as_deref()
+            .and_then(|language| {
+                language
+                    .split([',', ';', '-'])
+                    .next()
+                    .map(|language| language.trim().to_lowercase())
+            });
+        if let Some(language) = &language {
+            query.push(("language", language));
+        }
+
+        let request = self
+            .client
+            .get(&self.url)
+            .query(&query)
+            .header("User-Agent", USER_AGENT);
+        let body = send(request, "Open-Meteo geocoding")?;
+
+        let search: OpenMeteoSearch = serde_json::from_str(&body).map_err(|e| {
+            WeatherError::InvalidResponse(format!(
+                "Open-Meteo geocoding sent an invalid response: {e}"
+            ))
+        })?;
+
+        Ok(search
+            .results
+            .into_iter()
+            .map(|place| ResolvedLocation {
+                lat: place.latitude,
+                lon: place.longitude,
+                display_name: place.display_name(),
+                short_name: None,
+                timezone: place.timezone,
+                country_code: place.country_code,
+            })
+            .collect())
+    }
+
+    fn reverse(&self, _lat: f64, _lon: f64) -> Result<Option<Address>> {
+        Err(WeatherError::Unsupported(
+            "Open-Meteo geocoding can't find the addresses of the coordinates".to_string(),
+        ))
+    }
+
+    fn name(&self) -> &'static str {
+        "open_meteo"
+    }
+}
+
+/// Asks the primary geocoder first, and the secondary one if the primary fails or finds nothing
+#[derive(Debug, Clone)]
+pub struct FallbackGeocoder {
+    primary: Arc<dyn Geocoder>,
+    secondary: Arc<dyn Geocoder>,
+}
+
+impl FallbackGeocoder {
+    pub fn new(primary: impl Geocoder + 'static, secondary: impl Geocoder + 'static) -> Self {
+        Self {
+            primary: Arc::new(primary),
+            secondary: Arc::new(secondary),
+        }
+    }
+
+    /// Ask the primary geocoder, then the secondary one if there is no answer. The answer of the
+    /// primary one (or its error) is returned if the secondary one has nothing better, it's the one
+    /// that's normally used
+    fn ask<T>(
+        &self,
+        query: impl Fn(&dyn Geocoder) -> Result<T>,
+        found: impl Fn(&T) -> bool,
+    ) -> Result<T> {
+        let primary = match query(self.primary.as_ref()) {
+            Ok(answer) if found(&answer) => {
+                tracing::debug!(geocoder = self.primary.name(), "Geocoded");
+                return Ok(answer);
+            }
+            answer => answer,
+        };
+
+        match &primary {
+            Ok(_) => tracing::debug!(
+                geocoder = self.primary.name(),
+                "Found nothing, asking {}",
+                self.secondary.name()
+            ),
+            Err(e) => tracing::debug!(
+                geocoder = self.primary.name(),
+                error = %e,
+                "Failed, asking {}",
+                self.secondary.name()
+            ),
+        }
+
+        match query(self.secondary.as_ref()) {
+            Ok(answer) if found(&answer) => {
+                tracing::debug!(geocoder = self.secondary.name(), "Geocoded");
+                Ok(answer)
+            }
+            Ok(_) => {
+                tracing::debug!(geocoder = self.secondary.name(), "Found nothing either");
+                primary
+            }
+            Err(e) => {
+                tracing::debug!(geocoder = self.secondary.name(), error = %e, "Failed too");
+                primary
+            }
+        }
+    }
+}
+
+impl Geocoder for FallbackGeocoder {
+    fn forward(&self, address: &str) -> Result<Vec<ResolvedLocation>> {
+        self.ask(
+            |geocoder| geocoder.forward(address),
+            |places| !places.is_empty(),
+        )
+    }
+
+    fn reverse(&self, lat: f64, lon: f64) -> Result<Option<Address>> {
+        self.ask(|geocoder| geocoder.reverse(lat, lon), Option::is_some)
+    }
+
+    fn name(&self) -> &'static str {
+        "fallback"
+    }
+}
+
+/// Name of the geocoding cache file in the cache directory
+const GEOCODE_CACHE_FILE: &str = "geocache.json";
+
+/// How long the cached geocoding results are trusted (places don't move, but names do change)
+const GEOCODE_CACHE_TTL_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct CachedEntry<T> {
+    value: T,
+    cached_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct GeocodeCacheEntries {
+    /// Language and normalized address -> the place chosen for it
+    #[serde(default)]
+    places: HashMap<String, CachedEntry<ResolvedLocation>>,
+    /// Language and rounded (lat, lon) -> address (under a new key since it has the short name
+    /// too, the entries with only the display name are left to expire)
+    #[serde(default, rename = "reverse_addresses")]
+    reverse: HashMap<String, CachedEntry<Address>>,
+}
+
+/// On-disk cache of the geocoding results, so that running the cli in a loop doesn't hit
+/// Nominatim every time (which is against its usage policy)
+#[derive(Debug, Default)]
+pub struct GeocodeCache {
+    entries: GeocodeCacheEntries,
+    file_path: PathBuf,
+    /// Language of the reverse geocoding results, as the display names depend on it
+    language: String,
+    now: DateTime<Utc>,
+}
+
+impl GeocodeCache {
+    pub(crate) fn load(paths: &Paths, request: &GeocodeRequest, now: DateTime<Utc>) -> Self {
+        let file_path = paths.cache_dir.join(GEOCODE_CACHE_FILE);
+
+        // A missing or broken file just means there is nothing cached, it gets rebuilt on the
+        // next save
+        let entries = std::fs::read_to_string(&file_path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Self {
+            entries,
+            file_path,
+            language: request.accept_language.clone().unwrap_or_default(),
+            now,
+        }
+    }
+
+    /// Get the cached place of the address
+    pub(crate) fn forward(&self, address: &str) -> Option<ResolvedLocation> {
+        self.entries
+            .places
+            .get(&self.forward_key(address))
+            .filter(|entry| self.is_fresh(entry))
+            .map(|entry| entry.value.clone())
+    }
+
+    /// Get the cached address of the place at the coordinates
+    pub(crate) fn reverse(&self, lat: f64, lon: f64) -> Option<Address> {
+        self.entries
+            .reverse
+            .get(&self.reverse_key(lat, lon))
+            .filter(|entry| self.is_fresh(entry))
+            .map(|entry| entry.value.clone())
+    }
+
+    /// Remember the place chosen for the address
+    pub(crate) fn insert_forward(&mut self, address: &str, place: &ResolvedLocation) {
+        let entry = CachedEntry {
+            value: place.clone(),
+            cached_at: self.now,
+        };
+
+        self.entries.places.insert(self.forward_key(address), entry);
+        self.save();
+    }
+
+    /// Remember the address of the place at the coordinates
+    pub(crate) fn insert_reverse(&mut self, lat: f64, lon: f64, address: &Address) {
+        let entry = CachedEntry {
+            value: address.clone(),
+            cached_at: self.now,
+        };
+
+        self.entries
+            .reverse
+            .insert(self.reverse_key(lat, lon), entry);
+        self.save();
+    }
+
+    /// Remove the cache file
+    pub fn clear(paths: &Paths) -> Result<()> {
+        let file_path = paths.cache_dir.join(GEOCODE_CACHE_FILE);
+
+        match std::fs::remove_file(&file_path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(WeatherError::Io(std::io::Error::new(
+                    e.kind(),
+                    format!(
+                        "Couldn't remove the geocoding cache at {} ({e})",
+                        file_path.display()
+                    ),
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn is_fresh<T>(&self, entry: &CachedEntry<T>) -> bool {
+        self.now - entry.cached_at < Duration::days(GEOCODE_CACHE_TTL_DAYS)
+    }
+
+    /// The display names are in the requested language, so it's a part of the key
+    fn forward_key(&self, address: &str) -> String {
+        format!("{}|{}", self.language, normalize_address(address))
+    }
+
+    /// Coordinates are rounded to ~10 meters, so that the tiny differences in the input still hit
+    /// the cache
+    fn reverse_key(&self, lat: f64, lon: f64) -> String {
+        format!("{}|{lat:.4},{lon:.4}", self.language)
+    }
+
+    /// Save the cache without the expired entries, if the cache location is writable (the worst
+    /// that can happen otherwise is an extra request on the next run)
+    fn save(&mut self) {
+        let now = self.now;
+        let ttl = Duration::days(GEOCODE_CACHE_TTL_DAYS);
+
+        self.entries
+            .places
+            .retain(|_, entry| now - entry.cached_at < ttl);
+        self.entries
+            .reverse
+            .retain(|_, entry| now - entry.cached_at < ttl);
+
+        let _ = self.try_save();
+    }
+
+    fn try_save(&self) -> Result<()> {
+        if let Some(dir) = self.file_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        std::fs::write(&self.file_path, serde_json::to_string(&self.entries)?)?;
+
+        Ok(())
+    }
+}
+
+/// Ask which of the places matching the address was meant: list them on stderr and read the number
+/// from stdin (the best match is taken without asking if there is only one, if stdin is not a
+/// terminal, or if nothing or EOF is entered)
+pub(crate) fn choose_place(
+    address: &str,
+    mut places: Vec<ResolvedLocation>,
+) -> Result<Option<ResolvedLocation>> {
+    let stdin = std::io::stdin();
+
+    if places.len() < 2 || !stdin.is_terminal() {
+        return Ok(places.into_iter().next());
+    }
+
+    let mut stderr = std::io::stderr();
+    writeln!(stderr, "{} places match \"{address}\":", places.len())?;
+    for (i, place) in places.iter().enumerate() {
+        writeln!(
+            stderr,
+            "  {}) {} ({}, {})",
+            i + 1,
+            place.display_name,
+            place.lat,
+            place.lon
+        )?;
+    }
+
+    loop {
+        write!(stderr, "Which one? [1-{}, default 1]: ", places.len())?;
+        stderr.flush()?;
+
+        let mut input = String::new();
+        if stdin.lock().read_line(&mut input)? == 0 {
+            return Ok(Some(places.swap_remove(0)));
+        }
+
+        match input.trim() {
+            "" => return Ok(Some(places.swap_remove(0))),
+            input => match input.parse::<usize>() {
+                Ok(n) if (1..=places.len()).contains(&n) => {
+                    return Ok(Some(places.swap_remove(n - 1)))
+                }
+                _ => writeln!(stderr, "Enter a number between 1 and {}", places.len())?,
+            },
+        }
+    }
+}
+
+/// Make the addresses that only differ in case and whitespace share the cache entry
+fn normalize_address(address: &str) -> String {
+    address
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Get the preferred language from the usual locale environment variables
+fn accept_language_from_env() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|locale| !locale.is_empty())
+        .and_then(|locale| accept_language_from_locale(&locale))
+}
+
+/// Convert a POSIX locale string (e.g. "uk_UA.UTF-8") to an `Accept-Language` value ("uk-UA, uk")
+fn accept_language_from_locale(locale: &str) -> Option<String> {
+    // Strip the encoding and modifier parts
+    let locale = locale.split(['.', '@']).next().unwrap_or_default().trim();
+
+    // "C" and "POSIX" locales don't say anything about the preferred language
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return None;
+    }
+
+    let tag = locale.replace('_', "-");
+
+    match tag.split_once('-') {
+        // Fall back to the bare language if the region specific names are not available
+        Some((language, _)) => Some(format!("{tag}, {language}")),
+        None => Some(tag),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::TestServer;
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: application/json";
+
+    const SERVER_ERROR: &str = "HTTP/1.1 500 Internal Server Error";
+
+    const NOMINATIM_KYIV: &str = r#"[{"lat": "50.4500336", "lon": "30.5241361",
+        "display_name": "Київ, Україна",
+        "address": {"city": "Київ", "country": "Україна", "country_code": "ua"}}]"#;
+
+    const OPEN_METEO_KYIV: &str = r#"{"results": [{"name": "Kyiv", "latitude": 50.45466,
+        "longitude": 30.5238, "admin1": "Kyiv City", "country": "Ukraine", "country_code": "UA",
+        "timezone": "Europe/Kyiv"}]}"#;
+
+    /// Nominatim and the Open-Meteo geocoding behind it, each with a server of its own
+    fn fallback(
+        nominatim: (&'static str, &'static str),
+        open_meteo: (&'static str, &'static str),
+    ) -> (FallbackGeocoder, TestServer, TestServer) {
+        let (nominatim, open_meteo) = (
+            TestServer::start(vec![nominatim]),
+            TestServer::start(vec![open_meteo]),
+        );
+        let client = reqwest::blocking::Client::new();
+        let geocoder = FallbackGeocoder::new(
+            Nominatim::new(GeocodeRequest::default(), client.clone())
+                .with_base_url(nominatim.url.as_str()),
+            OpenMeteoGeocoder::new(GeocodeRequest::default(), client)
+                .with_url(open_meteo.url.as_str()),
+        );
+
+        (geocoder, nominatim, open_meteo)
+    }
+
+    fn names(places: &[ResolvedLocation]) -> Vec<&str> {
+        places
+            .iter()
+            .map(|place| place.display_name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn primary_answers() {
+        let (geocoder, nominatim, open_meteo) =
+            fallback((OK, NOMINATIM_KYIV), (OK, OPEN_METEO_KYIV));
+
+        let places = geocoder.forward("Kyiv").unwrap();
+        assert_eq!(names(&places), ["Київ, Україна"]);
+        assert_eq!((places[0].lat, places[0].lon), (50.4500336, 30.5241361));
+
+        assert_eq!(nominatim.request_count(), 1);
+        assert_eq!(open_meteo.request_count(), 0);
+    }
+
+    #[test]
+    fn secondary_answers_when_primary_fails() {
+        let (geocoder, nominatim, open_meteo) = fallback((SERVER_ERROR, ""), (OK, OPEN_METEO_KYIV));
+
+        let places = geocoder.forward("Kyiv").unwrap();
+        assert_eq!(names(&places), ["Kyiv, Kyiv City, Ukraine"]);
+        assert_eq!((places[0].lat, places[0].lon), (50.45466, 30.5238));
+
+        assert_eq!(nominatim.request_count(), 1);
+        assert_eq!(open_meteo.request_count(), 1);
+        assert!(open_meteo.requests()[0].starts_with("GET /?name=Kyiv&count=10&format=json "));
+    }
+
+    #[test]
+    fn secondary_answers_when_primary_finds_nothing() {
+        let (geocoder, nominatim, open_meteo) = fallback((OK, "[]"), (OK, OPEN_METEO_KYIV));
+
+        assert_eq!(
+            names(&geocoder.forward("Kyiv").unwrap()),
+            ["Kyiv, Kyiv City, Ukraine"]
+        );
+        assert_eq!(nominatim.request_count(), 1);
+        assert_eq!(open_meteo.request_count(), 1);
+    }
+
+    #[test]
+    fn primary_answer_when_secondary_has_nothing_better() {
+        // The error of the primary one, not the empty results of the secondary one
+        let (geocoder, _, open_meteo) = fallback((SERVER_ERROR, ""), (OK, "{}"));
+
+        let err = geocoder.forward("Kyiv").unwrap_err();
+        assert!(err.to_string().contains("500"), "{err}");
+        assert_eq!(open_meteo.request_count(), 1);
+
+        // Nothing from either of them
+        let (geocoder, ..) = fallback((OK, "[]"), (SERVER_ERROR, ""));
+        assert!(geocoder.forward("Nowhere").unwrap().is_empty());
+
+        // Open-Meteo can't look the coordinates up, so Nominatim's error stays
+        let (geocoder, _, open_meteo) = fallback((SERVER_ERROR, ""), (OK, OPEN_METEO_KYIV));
+        let err = geocoder.reverse(50.45, 30.52).unwrap_err();
+        assert!(err.to_string().contains("500"), "{err}");
+        assert_eq!(open_meteo.request_count(), 0);
+    }
+
+    /// Nominatim's reverse geocoding response for Khreshchatyk street in Kyiv (trimmed)
+    const KYIV_REVERSE: &str = r#"{"place_id": 1290563,
+        "licence": "Data © OpenStreetMap contributors, ODbL 1.0. http://osm.org/copyright",
+        "osm_type": "way", "osm_id": 4274085,
+        "lat": "50.4475447", "lon": "30.5229576", "category": "highway", "type": "primary",
+        "place_rank": 26, "importance": 0.3, "addresstype": "road", "name": "вулиця Хрещатик",
+        "display_name": "вулиця Хрещатик, Липки, Печерський район, Київ, 01001, Україна",
+        "address": {"road": "вулиця Хрещатик", "neighbourhood": "Липки",
+        "borough": "Печерський район", "city": "Київ", "ISO3166-2-lvl4": "UA-30",
+        "postcode": "01001", "country": "Україна", "country_code": "ua"},
+        "boundingbox": ["50.4437", "50.4507", "30.5186", "30.5272"]}"#;
+
+    /// A field in the countryside, outside of the villages
+    const RURAL_REVERSE: &str = r#"{"place_id": 9310071, "osm_type": "way", "osm_id": 53017431,
+        "lat": "50.9143", "lon": "34.6012", "category": "landuse", "type": "farmland",
+        "display_name": "Сумська міська громада, Сумський район, Сумська область, Україна",
+        "address": {"municipality": "Сумська міська громада", "district": "Сумський район",
+        "state": "Сумська область", "ISO3166-2-lvl4": "UA-59", "country": "Україна",
+        "country_code": "ua"}}"#;
+
+    /// The middle of the Atlantic
+    const OCEAN_REVERSE: &str = r#"{"error": "Unable to geocode"}"#;
+
+    #[test]
+    fn reverse_responses() {
+        let cases = [
+            (
+                KYIV_REVERSE,
+                Some(Address {
+                    full: "вулиця Хрещатик, Липки, Печерський район, Київ, 01001, Україна"
+                        .to_string(),
+                    short: Some("Київ, Україна".to_string()),
+                    country_code: Some("UA".to_string()),
+                }),
+            ),
+            (
+                RURAL_REVERSE,
+                Some(Address {
+                    full: "Сумська міська громада, Сумський район, Сумська область, Україна"
+                        .to_string(),
+                    short: Some("Сумська міська громада, Україна".to_string()),
+                    country_code: Some("UA".to_string()),
+                }),
+            ),
+            (OCEAN_REVERSE, None),
+        ];
+
+        for (body, expected) in cases {
+            assert_eq!(parse_reverse(body).unwrap(), expected, "{body}");
+        }
+
+        assert!(matches!(
+            parse_reverse("<html>"),
+            Err(WeatherError::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn short_names() {
+        let address = |parts: &[(&str, &str)]| {
+            let json = parts
+                .iter()
+                .map(|(key, value)| format!("\"{key}\": \"{value}\""))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            serde_json::from_str::<NominatimAddress>(&format!("{{{json}}}"))
+                .unwrap()
+                .short_name()
+        };
+
+        // The parts of the address, and the short name made of them
+        type Case = (
+            &'static [(&'static str, &'static str)],
+            Option<&'static str>,
+        );
+
+        let cases: [Case; 6] = [
+            (
+                &[("town", "Bucha"), ("city", "Kyiv"), ("country", "Ukraine")],
+                Some("Kyiv, Ukraine"),
+            ),
+            (
+                &[
+                    ("village", "Hoverla"),
+                    ("county", "Rakhiv"),
+                    ("country", "Ukraine"),
+                ],
+                Some("Hoverla, Ukraine"),
+            ),
+            (
+                &[("hamlet", "Skagen"), ("state", "Nordjylland")],
+                Some("Skagen"),
+            ),
+            (
+                &[
+                    ("county", "Rakhiv Raion"),
+                    ("state", "Zakarpattia"),
+                    ("country", "Ukraine"),
+                ],
+                Some("Rakhiv Raion, Ukraine"),
+            ),
+            (&[("country", "Antarctica")], Some("Antarctica")),
+            (&[("road", "A1")], None),
+        ];
+
+        for (parts, expected) in cases {
+            assert_eq!(address(parts).as_deref(), expected, "{parts:?}");
+        }
+    }
+
+    #[test]
+    fn reverse_request() {
+        let server = TestServer::start(vec![(OK, KYIV_REVERSE)]);
+        let nominatim = Nominatim::new(
+            GeocodeRequest {
+                accept_language: Some("uk-UA, uk".to_string()),
+            },
+            reqwest::blocking::Client::new(),
+        )
+        .with_base_url(server.url.as_str());
+
+        let address = nominatim.reverse(50.4475, 30.523).unwrap().unwrap();
+        assert_eq!(address.short.as_deref(), Some("Київ, Україна"));
+
+        // A single request, with the User-Agent Nominatim requires
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(
+            requests[0]
+                .starts_with("GET /reverse?lat=50.4475&lon=30.523&format=jsonv2&addressdetails=1 "),
+            "{}",
+            requests[0]
+        );
+        let headers = requests[0].to_lowercase();
+        assert!(
+            headers.contains(&format!("user-agent: {USER_AGENT}\r\n")),
+            "{headers}"
+        );
+        assert!(
+            headers.contains("accept-language: uk-ua, uk\r\n"),
+            "{headers}"
+        );
+    }
+
+    #[test]
+    fn searched_places() {
+        let (nominatim, open_meteo) = (
+            TestServer::start(vec![(OK, NOMINATIM_KYIV)]),
+            TestServer::start(vec![(OK, OPEN_METEO_KYIV)]),
+        );
+        let client = reqwest::blocking::Client::new();
+
+        // The parts of the address are asked for, for the code of the country
+        let places = Nominatim::new(GeocodeRequest::default(), client.clone())
+            .with_base_url(nominatim.url.as_str())
+            .forward("Kyiv")
+            .unwrap();
+        assert_eq!(places[0].display_name, "Київ, Україна");
+        assert_eq!(places[0].country_code.as_deref(), Some("UA"));
+        assert_eq!(places[0].timezone, None);
+        assert!(
+            nominatim.requests()[0]
+                .starts_with("GET /search?q=Kyiv&format=jsonv2&addressdetails=1 "),
+            "{}",
+            nominatim.requests()[0]
+        );
+
+        let places = OpenMeteoGeocoder::new(GeocodeRequest::default(), client)
+            .with_url(open_meteo.url.as_str())
+            .forward("Kyiv")
+            .unwrap();
+        assert_eq!(places[0].display_name, "Kyiv, Kyiv City, Ukraine");
+        assert_eq!(places[0].country_code.as_deref(), Some("UA"));
+        assert_eq!(places[0].timezone.as_deref(), Some("Europe/Kyiv"));
+    }
+
+    #[test]
+    fn search_language() {
+        let cases = [
+            (Some("uk-UA, uk"), Some("uk")),
+            (Some("en"), Some("en")),
+            // Neither the chosen language nor the locale, so the geocoders pick the names
+            (None, None),
+        ];
+
+        for (accept_language, language) in cases {
+            let request = GeocodeRequest {
+                accept_language: accept_language.map(str::to_string),
+            };
+            let (nominatim, open_meteo) = (
+                TestServer::start(vec![(OK, NOMINATIM_KYIV)]),
+                TestServer::start(vec![(OK, OPEN_METEO_KYIV)]),
+            );
+            let client = reqwest::blocking::Client::new();
+
+            Nominatim::new(request.clone(), client.clone())
+                .with_base_url(nominatim.url.as_str())
+                .forward("Kyiv")
+                .unwrap();
+            let headers = nominatim.requests()[0].to_lowercase();
+            match accept_language {
+                Some(accept_language) => assert!(
+                    headers.contains(&format!(
+                        "accept-language: {}\r\n",
+                        accept_language.to_lowercase()
+                    )),
+                    "{headers}"
+                ),
+                None => assert!(!headers.contains("accept-language"), "{headers}"),
+            }
+
+            // Open-Meteo takes the language in the query, and only the code of it
+            OpenMeteoGeocoder::new(request, client)
+                .with_url(open_meteo.url.as_str())
+                .forward("Kyiv")
+                .unwrap();
+            let request_line = open_meteo.requests()[0]
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            match language {
+                Some(language) => assert!(
+                    request_line.contains(&format!("&language={language} ")),
+                    "{request_line}"
+                ),
+                None => assert!(!request_line.contains("language="), "{request_line}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! The HTTP client shared by the provider and the geocoding requests, with the proxy and the TLS
+//! settings of the networks that don't let the requests out directly
+
+use std::time::Duration;
+
+use reqwest::{
+    blocking::{Client, ClientBuilder},
+    Certificate, NoProxy, Proxy,
+};
+
+use crate::{
+    config::NetworkSettings,
+    error::{Result, WeatherError},
+};
+
+/// Proxy variables for the https requests, the first one set wins
+const HTTPS_PROXY_ENV: [&str; 2] = ["HTTPS_PROXY", "https_proxy"];
+
+/// Proxy variables for the plain http requests, the first one set wins
+const HTTP_PROXY_ENV: [&str; 2] = ["HTTP_PROXY", "http_proxy"];
+
+/// Create a client with the configured timeouts, proxy and certificates.
+///
+/// The proxy from the config is used for every request, the `HTTPS_PROXY`/`HTTP_PROXY` ones
+/// otherwise, and the hosts in `NO_PROXY` are reached directly in both cases
+pub(crate) fn client(settings: &NetworkSettings) -> Result<Client> {
+    let timeout = Duration::from_secs(settings.timeout_secs);
+
+    // The proxies are all set up here, so the ones reqwest would pick up on its own are dropped
+    let builder = Client::builder()
+        .connect_timeout(timeout)
+        .timeout(timeout)
+        .no_proxy();
+    let builder = match &settings.proxy {
+        Some(url) => builder.proxy(proxy(Proxy::all(url), "network.proxy")?),
+        None => env_proxies(builder)?,
+    };
+
+    let builder = match &settings.ca_bundle {
+        Some(path) => {
+            let pem = std::fs::read(path).map_err(|e| {
+                WeatherError::Config(format!(
+                    "Couldn't read the CA bundle {}: {e}",
+                    path.display()
+                ))
+            })?;
+
+            // A bundle can have several certificates, all of them are trusted
+            let certificates = Certificate::from_pem_bundle(&pem)
+                .ok()
+                .filter(|certificates| !certificates.is_empty())
+                .ok_or_else(|| {
+                    WeatherError::Config(format!(
+                        "{} is not a PEM CA bundle (no certificates in it)",
+                        path.display()
+                    ))
+                })?;
+
+            certificates
+                .into_iter()
+                .fold(builder, ClientBuilder::add_root_certificate)
+        }
+        None => builder,
+    };
+
+    Ok(builder
+        .danger_accept_invalid_certs(settings.insecure)
+        .build()?)
+}
+
+/// Add the proxies from the environment variables, if there are any
+fn env_proxies(builder: ClientBuilder) -> Result<ClientBuilder> {
+    let builder = match env_var(&HTTPS_PROXY_ENV) {
+        Some((name, url)) => builder.proxy(proxy(Proxy::https(url), name)?),
+        None => builder,
+    };
+
+    Ok(match env_var(&HTTP_PROXY_ENV) {
+        Some((name, url)) => builder.proxy(proxy(Proxy::http(url), name)?),
+        None => builder,
+    })
+}
+
+/// The proxy that skips the `NO_PROXY` hosts, with the error saying where its URL came from (but
+/// not the URL itself, it can have the proxy password in it)
+fn proxy(proxy: reqwest::Result<Proxy>, source: &str) -> Result<Proxy> {
+    let proxy = proxy
+        .map_err(|e| WeatherError::Config(format!("The proxy URL of {source} is invalid: {e}")))?;
+    tracing::debug!(source, "Using a proxy");
+
+    Ok(proxy.no_proxy(NoProxy::from_env()))
+}
+
+/// The first of the variables that is set and not empty, with its name
+fn env_var(names: &[&'static str]) -> Option<(&'static str, String)> {
+    names.iter().find_map(|name| {
+        std::env::var(name)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| (*name, value))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::test_server::TestServer;
+
+    /// The certificate the TLS test server is using
+    fn certificate() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/localhost.pem")
+    }
+
+    /// Body of a request to the server with a client from the settings
+    fn get(server: &TestServer, settings: &NetworkSettings) -> reqwest::Result<String> {
+        client(settings)
+            .unwrap()
+            .get(server.url.clone())
+            .send()?
+            .text()
+    }
+
+    #[test]
+    fn self_signed_certificate() {
+        let server = TestServer::start_tls(vec![("HTTP/1.1 200 OK", "hello")]);
+
+        // Not trusted on its own
+        let error = get(&server, &NetworkSettings::default()).unwrap_err();
+        assert!(error.is_connect() || error.is_request(), "{error:?}");
+
+        // Trusted from the CA bundle
+        let bundle = NetworkSettings {
+            ca_bundle: Some(certificate()),
+            ..Default::default()
+        };
+        assert_eq!(get(&server, &bundle).unwrap(), "hello");
+
+        // Or not checked at all with --insecure
+        let insecure = NetworkSettings {
+            insecure: true,
+            ..Default::default()
+        };
+        assert_eq!(get(&server, &insecure).unwrap(), "hello");
+    }
+
+    #[test]
+    fn invalid_ca_bundle() {
+        let missing = NetworkSettings {
+            ca_bundle: Some(PathBuf::from("/nonexistent/ca.pem")),
+            ..Default::default()
+        };
+        assert!(matches!(
+            client(&missing),
+            Err(WeatherError::Config(message)) if message.contains("Couldn't read the CA bundle")
+        ));
+
+        // A file without any certificates in it
+        let not_pem = NetworkSettings {
+            ca_bundle: Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml")),
+            ..Default::default()
+        };
+        assert!(matches!(
+            client(&not_pem),
+            Err(WeatherError::Config(message)) if message.contains("is not a PEM CA bundle")
+        ));
+    }
+}
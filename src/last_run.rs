@@ -0,0 +1,407 @@
+//! What the last run showed for every location and date (last.json in the state directory), so
+//! that a run can tell whether the forecast changed since then (`get --changed-only`)
+
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+    path::PathBuf,
+};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    data::{SeriesSummary, WeatherData},
+    error::Result,
+    paths::Paths,
+};
+
+/// Step the temperatures are rounded to, so that the smaller wiggles don't count as a change
+pub const DEFAULT_PRECISION: f64 = 0.5;
+
+/// What a change is judged by: the hours with their temperatures rounded to the precision and
+/// whether it rains in them, the current conditions and the alerts
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Snapshot {
+    pub unit: String,
+    /// Labels of the hours, with their temperatures (`None` for the missing ones) and whether it
+    /// rains in them lined up with them
+    pub hours: Vec<String>,
+    pub temperatures: Vec<Option<f64>>,
+    pub rain: Vec<bool>,
+    /// Temperature and conditions now
+    pub current: Option<(f64, String)>,
+    /// Headlines of the alerts
+    pub alerts: Vec<String>,
+}
+
+impl Snapshot {
+    pub fn new(data: &WeatherData, precision: f64) -> Self {
+        let mut rain = vec![false; data.timestamps.len()];
+        for window in data.rain_windows() {
+            rain[window].iter_mut().for_each(|hour| *hour = true);
+        }
+
+        Self {
+            unit: data.unit.clone(),
+            hours: data
+                .timestamps
+                .iter()
+                .map(|timestamp| timestamp.label.clone())
+                .collect(),
+            temperatures: data
+                .temperatures
+                .iter()
+                .map(|temperature| (!temperature.is_nan()).then(|| round(*temperature, precision)))
+                .collect(),
+            rain,
+            current: data.current.as_ref().map(|current| {
+                (
+                    round(current.temperature, precision),
+                    current.weather_code.to_string(),
+                )
+            }),
+            alerts: data
+                .alerts
+                .iter()
+                .map(|alert| alert.headline.clone())
+                .collect(),
+        }
+    }
+
+    /// Hash of everything in the snapshot, the same for the same snapshot on every run
+    pub fn fingerprint(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+
+        format!("{:x}", Sha256::digest(json))
+    }
+
+    /// What changed since the earlier snapshot ("max temp 19→22", "rain window added 15:00–16:00"),
+    /// compared in the hours both of them have
+    pub fn changes(&self, earlier: &Snapshot) -> Vec<String> {
+        if self.unit != earlier.unit {
+            return vec![format!("unit {}→{}", earlier.unit, self.unit)];
+        }
+
+        // Lined up the same way as the data of the providers, on the labels both have
+        let common = self
+            .hours
+            .iter()
+            .collect::<HashSet<_>>()
+            .intersection(&earlier.hours.iter().collect())
+            .copied()
+            .collect::<HashSet<_>>();
+        let (before, after) = (earlier.in_hours(&common), self.in_hours(&common));
+
+        let mut changes = vec![];
+
+        let (before_summary, after_summary) = (before.summary(), after.summary());
+        for (name, before, after) in [
+            ("max temp", before_summary.max, after_summary.max),
+            ("min temp", before_summary.min, after_summary.min),
+        ] {
+            if let (Some((before, _)), Some((after, _))) = (before, after) {
+                if before != after {
+                    changes.push(format!("{name} {before}→{after}"));
+                }
+            }
+        }
+
+        let (before_windows, after_windows) = (before.rain_windows(), after.rain_windows());
+        for (change, windows, other) in [
+            ("added", &after_windows, &before_windows),
+            ("removed", &before_windows, &after_windows),
+        ] {
+            changes.extend(
+                windows
+                    .iter()
+                    .filter(|window| !other.contains(window))
+                    .map(|(first, last)| format!("rain window {change} {first}–{last}")),
+            );
+        }
+
+        if let (Some((before, _)), Some((after, _))) = (&earlier.current, &self.current) {
+            if before != after {
+                changes.push(format!("now {before}→{after}"));
+            }
+        }
+
+        for (change, alerts, other) in [
+            ("added", &self.alerts, &earlier.alerts),
+            ("removed", &earlier.alerts, &self.alerts),
+        ] {
+            changes.extend(
+                alerts
+                    .iter()
+                    .filter(|alert| !other.contains(alert))
+                    .map(|alert| format!("alert {change}: {alert}")),
+            );
+        }
+
+        // The conditions, or the hours one of them doesn't have
+        if changes.is_empty() && self != earlier {
+            changes.push("hourly forecast changed".to_string());
+        }
+
+        changes
+    }
+
+    /// Only the hours with the labels
+    fn in_hours(&self, labels: &HashSet<&String>) -> Self {
+        let keep = self
+            .hours
+            .iter()
+            .map(|hour| labels.contains(hour))
+            .collect_vec();
+
+        Self {
+            hours: self
+                .hours
+                .iter()
+                .filter(|hour| labels.contains(hour))
+                .cloned()
+                .collect(),
+            temperatures: kept(&self.temperatures, &keep),
+            rain: kept(&self.rain, &keep),
+            ..self.clone()
+        }
+    }
+
+    fn summary(&self) -> SeriesSummary {
+        SeriesSummary::new(
+            &self
+                .temperatures
+                .iter()
+                .map(|temperature| temperature.unwrap_or(f64::NAN))
+                .collect_vec(),
+        )
+    }
+
+    /// The hours in a row it rains, as the labels of the first and the last one
+    fn rain_windows(&self) -> Vec<(String, String)> {
+        let mut windows: Vec<Range<usize>> = vec![];
+        for i in (0..self.rain.len()).filter(|i| self.rain[*i]) {
+            match windows.last_mut() {
+                Some(window) if window.end == i => window.end = i + 1,
+                _ => windows.push(i..i + 1),
+            }
+        }
+
+        windows
+            .into_iter()
+            .map(|window| {
+                (
+                    self.hours[window.start].clone(),
+                    self.hours[window.end - 1].clone(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The values lined up with the hours to keep
+fn kept<T: Copy>(values: &[T], keep: &[bool]) -> Vec<T> {
+    values
+        .iter()
+        .zip(keep)
+        .filter(|(_, keep)| **keep)
+        .map(|(value, _)| *value)
+        .collect()
+}
+
+/// Round to the step, without the float noise ("19.5", not "19.500000000000004")
+fn round(value: f64, precision: f64) -> f64 {
+    ((value / precision).round() * precision * 100.0).round() / 100.0
+}
+
+/// How the data compares with the last run for the same location, provider and date
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Change {
+    /// There was no run for them yet
+    First,
+    Unchanged,
+    /// What changed
+    Changed(Vec<String>),
+}
+
+/// The last run of every location, provider and date
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LastRun {
+    fingerprint: String,
+    snapshot: Snapshot,
+}
+
+/// The last runs, from last.json
+#[derive(Debug, Default)]
+pub struct LastRuns {
+    runs: HashMap<String, LastRun>,
+    file_path: PathBuf,
+}
+
+impl LastRuns {
+    pub fn load(paths: &Paths) -> Self {
+        let file_path = paths.state_dir.join("last.json");
+
+        // A missing or broken file just means there were no runs we know about
+        let runs = std::fs::read_to_string(&file_path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Self { runs, file_path }
+    }
+
+    /// Compare the data with the last run for its location, provider and date, and remember it as
+    /// the last one (the temperatures are rounded to the precision)
+    pub fn record(&mut self, data: &WeatherData, precision: f64) -> Result<Change> {
+        let key = format!(
+            "{:.2},{:.2} {} {}",
+            data.location.lat, data.location.lon, data.provider, data.requested_date
+        );
+        let snapshot = Snapshot::new(data, precision);
+        let fingerprint = snapshot.fingerprint();
+
+        let change = match self.runs.get(&key) {
+            None => Change::First,
+            Some(last) if last.fingerprint == fingerprint => return Ok(Change::Unchanged),
+            Some(last) => Change::Changed(snapshot.changes(&last.snapshot)),
+        };
+
+        self.runs.insert(
+            key,
+            LastRun {
+                fingerprint,
+                snapshot,
+            },
+        );
+        if let Some(dir) = self.file_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&self.file_path, serde_json::to_string_pretty(&self.runs)?)?;
+
+        Ok(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Timestamp;
+
+    /// Snapshot of the hours from 12:00 with the temperatures and the rain
+    fn snapshot(temperatures: &[f64], rain: &[bool], alerts: &[&str]) -> Snapshot {
+        Snapshot {
+            unit: "°C".to_string(),
+            hours: (0..temperatures.len())
+                .map(|hour| format!("{}:00", 12 + hour))
+                .collect(),
+            temperatures: temperatures.iter().copied().map(Some).collect(),
+            rain: rain.to_vec(),
+            current: Some((temperatures[0], "Clear sky".to_string())),
+            alerts: alerts.iter().map(|alert| alert.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn changes() {
+        let dry = [false; 5];
+        let rainy = [false, false, false, true, true];
+        let earlier = snapshot(&[18.0, 19.0, 19.0, 17.0, 16.0], &dry, &["Gale"]);
+
+        // (snapshot, changes)
+        let cases = [
+            (earlier.clone(), vec![]),
+            (
+                snapshot(&[18.0, 20.0, 22.0, 17.0, 16.0], &rainy, &["Gale"]),
+                vec!["max temp 19→22", "rain window added 15:00–16:00"],
+            ),
+            (
+                snapshot(&[18.0, 19.0, 19.0, 17.0, 14.5], &dry, &["Ice"]),
+                vec![
+                    "min temp 16→14.5",
+                    "alert added: Ice",
+                    "alert removed: Gale",
+                ],
+            ),
+            // Only the hours both have are compared, the new hour isn't a new maximum
+            (
+                Snapshot {
+                    hours: (1..6).map(|hour| format!("{}:00", 12 + hour)).collect(),
+                    ..snapshot(&[19.0, 19.0, 17.0, 16.0, 25.0], &dry, &["Gale"])
+                },
+                vec!["now 18→19"],
+            ),
+        ];
+
+        for (snapshot, expected) in cases {
+            assert_eq!(snapshot.changes(&earlier), expected, "{snapshot:?}");
+            assert_eq!(
+                snapshot.fingerprint() == earlier.fingerprint(),
+                snapshot == earlier
+            );
+        }
+    }
+
+    #[test]
+    fn wiggles_below_the_precision() {
+        let data = |temperature: f64| WeatherData {
+            temperatures: vec![temperature; 3],
+            ..Default::default()
+        };
+
+        let fingerprint =
+            |temperature, precision| Snapshot::new(&data(temperature), precision).fingerprint();
+        assert_eq!(fingerprint(19.1, 0.5), fingerprint(19.2, 0.5));
+        assert_ne!(fingerprint(19.1, 0.1), fingerprint(19.2, 0.1));
+        assert_eq!(Snapshot::new(&data(19.26), 0.5).temperatures[0], Some(19.5));
+    }
+
+    #[test]
+    fn recorded_runs() {
+        let dir = std::env::temp_dir().join(format!("weather-last-run-{}", std::process::id()));
+        let paths = Paths {
+            state_dir: dir.clone(),
+            ..Default::default()
+        };
+        let time = chrono::DateTime::parse_from_rfc3339("2024-06-14T12:00:00Z").unwrap();
+        let data = |temperature| WeatherData {
+            timestamps: (0..3)
+                .map(|hour| Timestamp::new(time + chrono::Duration::hours(hour), "%H:%M"))
+                .collect(),
+            temperatures: vec![temperature; 3],
+            requested_date: "2024-06-14".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            LastRuns::load(&paths).record(&data(18.0), 0.5).unwrap(),
+            Change::First
+        );
+        // From the file, as the next run would
+        assert_eq!(
+            LastRuns::load(&paths).record(&data(18.1), 0.5).unwrap(),
+            Change::Unchanged
+        );
+        assert_eq!(
+            LastRuns::load(&paths).record(&data(20.0), 0.5).unwrap(),
+            Change::Changed(vec![
+                "max temp 18→20".to_string(),
+                "min temp 18→20".to_string()
+            ])
+        );
+        // Another day is another run
+        let tomorrow = WeatherData {
+            requested_date: "2024-06-15".to_string(),
+            ..data(20.0)
+        };
+        assert_eq!(
+            LastRuns::load(&paths).record(&tomorrow, 0.5).unwrap(),
+            Change::First
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
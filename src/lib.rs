@@ -0,0 +1,56 @@
+//! Weather forecasts, historical data and current conditions from the free weather APIs
+//! (open_meteo, met_no and open_weather_map).
+//!
+//! ```no_run
+//! use weather::{config::TemperatureUnit, providers::Provider, WeatherClient};
+//!
+//! let client = WeatherClient::builder()
+//!     .provider(Provider::MetNo)
+//!     .unit(TemperatureUnit::Fahrenheit)
+//!     .build()?;
+//! let data = client.get("Kyiv, Ukraine", "now")?;
+//!
+//! println!("{:?}", data.temperatures);
+//! # Ok::<(), weather::WeatherError>(())
+//! ```
+
+pub mod archive;
+mod astro;
+mod cassette;
+pub mod client;
+pub mod config;
+mod cooldown;
+mod coordinates;
+pub mod data;
+pub mod date;
+pub mod error;
+pub mod geocode;
+mod http;
+pub mod last_run;
+pub mod locale;
+pub mod outdoor;
+pub mod paths;
+pub mod providers;
+pub mod publish;
+pub mod queries;
+pub mod response_cache;
+pub mod response_dump;
+mod retry;
+#[cfg(test)]
+mod test_server;
+pub mod timezone;
+pub mod update;
+pub mod watch;
+
+pub use client::{WeatherClient, WeatherClientBuilder};
+pub use data::{
+    aqi_category, AirQualityData, CurrentSnapshot, CurrentWeatherData, DataSource, Degradation,
+    NowcastData, RadarCoverage, SeriesSummary, Severity, Trend, UnitSeries, WeatherAlert,
+    WeatherCode, WeatherData, WindDirection,
+};
+pub use error::{Result, WeatherError};
+
+pub(crate) mod built_info {
+    // The file has been placed there by the build script.
+    include!(concat!(env!("OUT_DIR"), "/built.rs"));
+}
@@ -0,0 +1,951 @@
+//! Language of the text drawn around the data: the titles, the labels and the names of the
+//! conditions. Every language has a table of its strings, and the ones a table doesn't have are
+//! taken from the English one
+
+use std::fmt::Display;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::{
+    data::{MoonPhase, Trend, WeatherCode},
+    error::{Result, WeatherError},
+};
+
+/// Languages the strings are translated to
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Language {
+    #[default]
+    En,
+    Uk,
+}
+
+impl Language {
+    pub const AVAILABLE_LANGUAGES: [&'static str; 2] = ["en", "uk"];
+
+    /// Variables with the locale, the first one set wins (like the C library picks them)
+    const LOCALE_ENV: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: impl AsRef<str>) -> Result<Self> {
+        match s.as_ref() {
+            "en" => Ok(Self::En),
+            "uk" => Ok(Self::Uk),
+            s => Err(WeatherError::InvalidInput(format!(
+                "Invalid language {s}, available languages: [{}]",
+                Self::AVAILABLE_LANGUAGES.join(", ")
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Uk => "uk",
+        }
+    }
+
+    /// Language of the locale ("uk_UA.UTF-8" is Ukrainian), English for the rest and if no locale
+    /// is set
+    pub fn from_locale() -> Self {
+        let locale = Self::LOCALE_ENV
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|locale| !locale.is_empty()));
+
+        match locale {
+            Some(locale) if locale.to_lowercase().starts_with("uk") => Self::Uk,
+            _ => Self::En,
+        }
+    }
+
+    /// The string in this language, or in English if it isn't translated
+    pub fn text(&self, text: Text) -> &'static str {
+        match self {
+            Self::En => english(text),
+            Self::Uk => ukrainian(text).unwrap_or_else(|| english(text)),
+        }
+    }
+
+    /// The string with the `{}` in it replaced by the arguments, in order (the extra `{}` are left
+    /// empty)
+    pub fn format(&self, text: Text, args: &[&dyn Display]) -> String {
+        let mut args = args.iter();
+
+        self.text(text)
+            .split("{}")
+            .enumerate()
+            .map(|(i, part)| match i {
+                0 => part.to_string(),
+                _ => format!(
+                    "{}{part}",
+                    args.next().map(|arg| arg.to_string()).unwrap_or_default()
+                ),
+            })
+            .collect()
+    }
+
+    /// How long ago something happened, in the largest unit that fits ("just now", "5m ago", "2h
+    /// ago", "3d ago")
+    pub fn ago(&self, age: chrono::Duration) -> String {
+        match (age.num_days(), age.num_hours(), age.num_minutes()) {
+            (days, ..) if days > 0 => self.format(Text::DaysAgo, &[&days]),
+            (_, hours, _) if hours > 0 => self.format(Text::HoursAgo, &[&hours]),
+            (_, _, minutes) if minutes > 0 => self.format(Text::MinutesAgo, &[&minutes]),
+            _ => self.text(Text::JustNow).to_string(),
+        }
+    }
+
+    /// How the numbers and the dates are written in the language
+    pub fn number_format(&self) -> NumberFormat {
+        match self {
+            Self::En => NumberFormat {
+                decimal_separator: '.',
+                minus: '-',
+                degree_space: false,
+                day_first: false,
+            },
+            Self::Uk => NumberFormat {
+                decimal_separator: ',',
+                minus: '−',
+                degree_space: true,
+                day_first: true,
+            },
+        }
+    }
+
+    /// Date with the names of the day of the week and of the month ("Sat, 17 Oct 2026",
+    /// "сб, 17 жовтня 2026")
+    pub fn date(&self, date: NaiveDate) -> String {
+        match self {
+            Self::En => date.format("%a, %-d %b %Y").to_string(),
+            Self::Uk => format!(
+                "{}, {} {} {}",
+                UK_WEEKDAYS[date.weekday().num_days_from_monday() as usize],
+                date.day(),
+                UK_MONTHS[date.month0() as usize],
+                date.year()
+            ),
+        }
+    }
+}
+
+/// How the numbers, the units and the dates are written for the people reading the language. The
+/// CSV is for the scripts, its numbers are written with [`machine_number`] whatever the language is
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Between the whole and the fractional part
+    pub decimal_separator: char,
+    /// Sign of the negative numbers
+    pub minus: char,
+    /// Whether there is a space between the number and the degrees ("−3,5 °C")
+    pub degree_space: bool,
+    /// Whether the day goes before the month ("14.06." instead of "Jun 14")
+    pub day_first: bool,
+}
+
+impl NumberFormat {
+    /// The number with the decimal places, or rounded to one decimal (dropping ".0") if there is
+    /// no precision, so that the floating point noise of the conversions doesn't show
+    pub fn number(&self, value: f64, precision: Option<usize>) -> String {
+        let number = match precision {
+            Some(precision) => format!("{value:.precision$}"),
+            None => ((value * 10.0).round() / 10.0).to_string(),
+        };
+
+        number
+            .chars()
+            .map(|c| match c {
+                '.' => self.decimal_separator,
+                '-' => self.minus,
+                c => c,
+            })
+            .collect()
+    }
+
+    /// The number with its unit ("21.5°C", "−3,5 °C", "10 km/h", "55%")
+    pub fn quantity(&self, value: f64, precision: Option<usize>, unit: &str) -> String {
+        let number = self.number(value, precision);
+
+        match (unit.starts_with('°'), unit) {
+            (true, _) if !self.degree_space => format!("{number}{unit}"),
+            (_, "%" | "") => format!("{number}{unit}"),
+            _ => format!("{number} {unit}"),
+        }
+    }
+
+    /// Day and month, without the year ("Jun 14", "14.06.")
+    pub fn day_month(&self, date: NaiveDate) -> String {
+        match self.day_first {
+            true => date.format("%d.%m.").to_string(),
+            false => date.format("%b %-d").to_string(),
+        }
+    }
+}
+
+/// The number the way the scripts read it, whatever the language: "." before the decimals, "-"
+/// before the negative ones, and as precise as it is
+pub fn machine_number(value: f64) -> String {
+    value.to_string()
+}
+
+/// Short names of the days of the week, from Monday
+const UK_WEEKDAYS: [&str; 7] = ["пн", "вт", "ср", "чт", "пт", "сб", "нд"];
+
+/// Names of the months as they go after the day ("17 жовтня")
+const UK_MONTHS: [&str; 12] = [
+    "січня",
+    "лютого",
+    "березня",
+    "квітня",
+    "травня",
+    "червня",
+    "липня",
+    "серпня",
+    "вересня",
+    "жовтня",
+    "листопада",
+    "грудня",
+];
+
+/// Every string that is translated. The ones with `{}` in them are filled in with
+/// [`Language::format`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Text {
+    /// Name of the conditions
+    Conditions(WeatherCode),
+    /// Where the series is heading
+    Trend(Trend),
+    /// Name of the phase of the moon
+    MoonPhase(MoonPhase),
+
+    // Current weather
+    CurrentWeather,
+    Temperature,
+    FeelsLike,
+    WindSpeed,
+    WindDirection,
+    Precipitation,
+    Humidity,
+    WindChill,
+    UvIndex,
+
+    // Titles
+    WeatherIn,
+    WeatherInWithProvider,
+    ProvidersCompared,
+    StaleData,
+    CachedHours,
+    FellBackTo,
+    MissingExtra,
+    ExtraNotFetched,
+    ExtraAlerts,
+    ExtraAirQuality,
+    ExtraNowcast,
+    ExtraYesterday,
+    UnknownAddress,
+    SparseHours,
+    DuplicateHours,
+    ConvertedUnits,
+    NextMatch,
+    NoMatch,
+    OneWarning,
+    Warnings,
+    WarningsTitle,
+    MoreWarnings,
+    Now,
+    Forecast,
+    HistoricalData,
+    InUnit,
+    OnDate,
+    FromToDate,
+    LocalTime,
+    PartialDay,
+    OfTotal,
+    /// Note of the charts with the hours in the 24h clock to fit the narrow bars
+    Hours24,
+
+    // Chart series
+    /// Series with what kind of data it is ("Weather Forecast")
+    SeriesKind,
+    SeriesTemperature,
+    SeriesFeelsLike,
+    Pressure,
+    CloudCover,
+
+    // Daily charts
+    DailyMaximum,
+    DailyMinimum,
+    DailyRange,
+    DailySummary,
+
+    // Summary of a series
+    Min,
+    Max,
+    Avg,
+
+    // Age of the data
+    DataAge,
+    Updated,
+    Fetched,
+    DaysAgo,
+    HoursAgo,
+    MinutesAgo,
+    JustNow,
+
+    // Air quality
+    AirQuality,
+    AqiDay,
+
+    // Nowcast
+    Nowcast,
+    NowcastNoCoverage,
+    NowcastRadarDown,
+
+    // Moon and daylight
+    Astro,
+    MoonIllumination,
+    DayLength,
+    HoursMinutes,
+
+    // Alerts
+    Alerts,
+    AlertFrom,
+    AlertUntil,
+
+    // Chart marks
+    /// Written instead of the missing values
+    MissingValue,
+    /// Indicator of the bars scrolled away before the first visible one
+    MoreBefore,
+    /// Indicator of the bars after the last visible one
+    MoreAfter,
+
+    // Plain text
+    /// Age of the data on a line of its own ("Data updated 5m ago")
+    DataAgeLine,
+    ConditionsLabel,
+    Daylight,
+    AirQualityOn,
+    DayRange,
+    Particulates,
+    Moon,
+    DayLengthLabel,
+    SummaryLine,
+
+    // Plain text table headers
+    Time,
+    Yesterday,
+    Clouds,
+    Wind,
+    Day,
+    MinColumn,
+    MaxColumn,
+    Mean,
+
+    // Warnings
+    ExtremeHeat,
+    ExtremeCold,
+    /// The temperature with the feels-like one in the banners
+    TemperatureFeelsLike,
+    StrongWind,
+    /// The banners in the one line output
+    ExtremeHeatShort,
+    ExtremeColdShort,
+    StrongWindShort,
+    /// The banners that didn't fit
+    MoreBanners,
+
+    // Messages and hints
+    None,
+    NoHourlyData,
+    NoWeatherData,
+    CouldntGetWeather,
+    ProviderFailed,
+    TodayYesterday,
+    /// What the styles of the wind arrows under the bars mean
+    WindLegend,
+    /// The best window of the hours to be outside (`--best-window`) with what it's like and its
+    /// penalties
+    BestWindow,
+    RainChance,
+    UvValue,
+    Penalties,
+    KeysHint,
+    TabsHint,
+
+    // Speech (`--output speech`), the hours and the units as they are said
+    SpeechTitle,
+    SpeechRiseFrom,
+    SpeechFallFrom,
+    SpeechTo,
+    SpeechRiseTo,
+    SpeechFallTo,
+    SpeechThenRise,
+    SpeechThenFall,
+    SpeechHigh,
+    SpeechLow,
+    SpeechSteady,
+    SpeechAt,
+    SpeechNoData,
+    SpeechNoDataAt,
+    SpeechDay,
+    SpeechNow,
+    SpeechFeelsLike,
+    SpeechRainChance,
+    SpeechWind,
+    SpeechWindFrom,
+    SpeechAlert,
+    /// Time on another day than the first one ("3 in the afternoon on Jun 15")
+    SpeechOnDay,
+    Midnight,
+    Noon,
+    InTheMorning,
+    InTheAfternoon,
+    InTheEvening,
+    AtNight,
+    Minus,
+    DegreesCelsius,
+    DegreesFahrenheit,
+    KilometresPerHour,
+    MetresPerSecond,
+    MilesPerHour,
+    Knots,
+    Millimetres,
+    Inches,
+    Percent,
+    /// Points of the compass, and the north and the south before the east and the west
+    /// ("northeast")
+    North,
+    East,
+    South,
+    West,
+    Northern,
+    Southern,
+    CompassPair,
+
+    // Calendar (`--export-ics`)
+    RainWindow,
+    SunriseAt,
+    SunsetAt,
+}
+
+/// The English strings, every one of them is here
+fn english(text: Text) -> &'static str {
+    match text {
+        Text::Conditions(code) => match code {
+            WeatherCode::Unknown => "Unknown",
+            WeatherCode::ClearSky => "Clear sky",
+            WeatherCode::MainlyClear => "Mainly Clear Sky",
+            WeatherCode::PartlyCloudy => "Partly Cloudy",
+            WeatherCode::Overcast => "Overcast",
+            WeatherCode::Fog => "Fog",
+            WeatherCode::Drizzle => "Drizzle",
+            WeatherCode::FreezingDrizzle => "Freezing Drizzle",
+            WeatherCode::Rain => "Rain",
+            WeatherCode::FreezingRain => "Freezing Rain",
+            WeatherCode::SnowFall => "Snow Fall",
+            WeatherCode::SnowGrains => "Snow Grains",
+            WeatherCode::RainShowers => "Rain Showers",
+            WeatherCode::SnowShowers => "Snow Showers",
+            WeatherCode::Thunderstorm => "Thunderstorm",
+        },
+        Text::Trend(trend) => match trend {
+            Trend::Rising => "rising",
+            Trend::Falling => "falling",
+            Trend::Steady => "steady",
+        },
+        Text::MoonPhase(phase) => match phase {
+            MoonPhase::New => "New moon",
+            MoonPhase::WaxingCrescent => "Waxing crescent",
+            MoonPhase::FirstQuarter => "First quarter",
+            MoonPhase::WaxingGibbous => "Waxing gibbous",
+            MoonPhase::Full => "Full moon",
+            MoonPhase::WaningGibbous => "Waning gibbous",
+            MoonPhase::LastQuarter => "Last quarter",
+            MoonPhase::WaningCrescent => "Waning crescent",
+        },
+
+        Text::CurrentWeather => "Current Weather",
+        Text::Temperature => "Temperature",
+        Text::FeelsLike => "Feels like",
+        Text::WindSpeed => "Wind Speed",
+        Text::WindDirection => "Wind Direction",
+        Text::Precipitation => "Precipitation",
+        Text::Humidity => "Humidity",
+        Text::WindChill => "Wind Chill",
+        Text::UvIndex => "UV Index",
+
+        Text::WeatherIn => "Weather in {}",
+        Text::WeatherInWithProvider => "Weather in {} (Provider: {})",
+        Text::ProvidersCompared => "Providers compared",
+        Text::StaleData => "stale data from {}",
+        Text::CachedHours => "hours {}–{} from the cache, {}",
+        Text::FellBackTo => "{}, fell back to {}",
+        Text::MissingExtra => "couldn't get the {} ({})",
+        Text::ExtraNotFetched => "the {} is only shown for the freshly fetched weather",
+        Text::ExtraAlerts => "weather alerts",
+        Text::ExtraAirQuality => "air quality",
+        Text::ExtraNowcast => "nowcast",
+        Text::ExtraYesterday => "yesterday's weather",
+        Text::UnknownAddress => "no address found at the coordinates",
+        Text::SparseHours => "the hours are further apart from {} on",
+        Text::DuplicateHours => "{} repeated hour(s) left out",
+        Text::ConvertedUnits => "{} converted from {} to {}",
+        Text::NextMatch => "{} at {} ({} h)",
+        Text::NoMatch => "no {} in the {} hours ahead",
+        Text::OneWarning => "⚠ 1 warning (press w)",
+        Text::Warnings => "⚠ {} warnings (press w)",
+        Text::WarningsTitle => "Warnings",
+        Text::MoreWarnings => "… and {} more",
+        Text::Now => "now",
+        Text::Forecast => "Forecast",
+        Text::HistoricalData => "Historical Data",
+        Text::InUnit => "(in {})",
+        Text::OnDate => "on {}",
+        Text::FromToDate => "from {} to {}",
+        Text::LocalTime => "({} time)",
+        Text::PartialDay => "(* - partial day)",
+        Text::OfTotal => "{}-{} of {}",
+        Text::Hours24 => "(24h)",
+
+        Text::SeriesKind => "{} {}",
+        Text::SeriesTemperature => "Weather",
+        Text::SeriesFeelsLike => "Feels Like",
+        Text::Pressure => "Pressure",
+        Text::CloudCover => "Cloud Cover",
+
+        Text::DailyMaximum => "Daily Maximum Temperatures",
+        Text::DailyMinimum => "Daily Minimum Temperatures",
+        Text::DailyRange => "Daily Minimum to Maximum Temperatures",
+        Text::DailySummary => "Daily Minimum/Maximum/Mean Temperatures",
+
+        Text::Min => "min",
+        Text::Max => "max",
+        Text::Avg => "avg",
+
+        Text::DataAge => "data {}",
+        Text::Updated => "updated {}",
+        Text::Fetched => "fetched {}",
+        Text::DaysAgo => "{}d ago",
+        Text::HoursAgo => "{}h ago",
+        Text::MinutesAgo => "{}m ago",
+        Text::JustNow => "just now",
+
+        Text::AirQuality => "Air Quality (EAQI)",
+        Text::AqiDay => "Day: {}",
+
+        Text::Nowcast => "Precipitation next {} min",
+        Text::NowcastNoCoverage => "Nowcast not available for this location",
+        Text::NowcastRadarDown => {
+            "Nowcast not available for this location at the moment (the radars are down)"
+        }
+
+        Text::Astro => "Moon and Daylight",
+        Text::MoonIllumination => "{}% lit",
+        Text::DayLength => "Day length: {}",
+        Text::HoursMinutes => "{}h {}m",
+
+        Text::Alerts => "Alerts",
+        Text::AlertFrom => "from {}",
+        Text::AlertUntil => "until {}",
+
+        Text::MissingValue => "n/a",
+        Text::MoreBefore => "◀ more",
+        Text::MoreAfter => "more ▶",
+
+        Text::DataAgeLine => "Data {}",
+        Text::ConditionsLabel => "Conditions",
+        Text::Daylight => "Daylight",
+        Text::AirQualityOn => "Air Quality ({})",
+        Text::DayRange => "Day Range",
+        Text::Particulates => "Particulates",
+        Text::Moon => "Moon",
+        Text::DayLengthLabel => "Day Length",
+        Text::SummaryLine => "Summary: {}",
+
+        Text::Time => "Time",
+        Text::Yesterday => "Yesterday",
+        Text::Clouds => "Clouds",
+        Text::Wind => "Wind",
+        Text::Day => "Day",
+        Text::MinColumn => "Min",
+        Text::MaxColumn => "Max",
+        Text::Mean => "Mean",
+
+        Text::ExtremeHeat => "Extreme heat: {} - avoid the midday sun",
+        Text::ExtremeCold => "Extreme cold: {} - cover exposed skin",
+        Text::TemperatureFeelsLike => "{}, feels like {}",
+        Text::StrongWind => "Strong wind: {} - secure loose objects",
+        Text::ExtremeHeatShort => "extreme heat",
+        Text::ExtremeColdShort => "extreme cold",
+        Text::StrongWindShort => "strong wind",
+        Text::MoreBanners => "+{} more",
+
+        Text::None => "none",
+        Text::NoHourlyData => "No hourly data to chart from {}",
+        Text::NoWeatherData => "No weather data available from {}",
+        Text::CouldntGetWeather => "Couldn't get the weather: {}",
+        Text::ProviderFailed => "{} failed: {}",
+        Text::TodayYesterday => "today {} / yesterday {}",
+        Text::WindLegend => "wind: dim <{}, bold {}+, warning >{} km/h",
+        Text::BestWindow => "Best {}h window: {}–{} — {}",
+        Text::RainChance => "{}% rain",
+        Text::UvValue => "UV {}",
+        Text::Penalties => "penalty {}: rain {}, temperature {}, UV {}, gusts {}",
+        Text::KeysHint => "q/Esc: quit  ←/→/Home/End: scroll  u: °C/°F",
+        Text::TabsHint => "Tab/1-9: switch location",
+
+        Text::SpeechTitle => "Weather in {}, from {}",
+        Text::SpeechRiseFrom => "Temperatures rise from {} at {}",
+        Text::SpeechFallFrom => "Temperatures fall from {} at {}",
+        Text::SpeechTo => "to {} at {}",
+        Text::SpeechRiseTo => "rise to {} at {}",
+        Text::SpeechFallTo => "fall to {} at {}",
+        Text::SpeechThenRise => "then rise to {} by {}",
+        Text::SpeechThenFall => "then fall to {} by {}",
+        Text::SpeechHigh => "a high of {}",
+        Text::SpeechLow => "a low of {}",
+        Text::SpeechSteady => "Temperatures stay around {} from {} to {}",
+        Text::SpeechAt => "Temperatures are {} at {}",
+        Text::SpeechNoData => "No data from {} to {}",
+        Text::SpeechNoDataAt => "No data at {}",
+        Text::SpeechDay => "{}, from {} to {}",
+        Text::SpeechNow => "Now it's {} and {}",
+        Text::SpeechFeelsLike => "feels like {}",
+        Text::SpeechRainChance => "{} chance of precipitation",
+        Text::SpeechWind => "Wind {}",
+        Text::SpeechWindFrom => "Wind {} from the {}",
+        Text::SpeechAlert => "{} alert: {}",
+        Text::SpeechOnDay => "{} on {}",
+        Text::Midnight => "midnight",
+        Text::Noon => "noon",
+        Text::InTheMorning => "{} in the morning",
+        Text::InTheAfternoon => "{} in the afternoon",
+        Text::InTheEvening => "{} in the evening",
+        Text::AtNight => "{} at night",
+        Text::Minus => "minus",
+        Text::DegreesCelsius => "degrees Celsius",
+        Text::DegreesFahrenheit => "degrees Fahrenheit",
+        Text::KilometresPerHour => "kilometres per hour",
+        Text::MetresPerSecond => "metres per second",
+        Text::MilesPerHour => "miles per hour",
+        Text::Knots => "knots",
+        Text::Millimetres => "millimetres",
+        Text::Inches => "inches",
+        Text::Percent => "percent",
+        Text::North => "north",
+        Text::East => "east",
+        Text::South => "south",
+        Text::West => "west",
+        Text::Northern => "north",
+        Text::Southern => "south",
+        Text::CompassPair => "{}{}",
+
+        Text::RainWindow => "Rain {}–{}",
+        Text::SunriseAt => "Sunrise {}",
+        Text::SunsetAt => "Sunset {}",
+    }
+}
+
+/// The Ukrainian strings, the ones that aren't here are shown in English
+const UKRAINIAN: &[(Text, &str)] = &[
+    (Text::Conditions(WeatherCode::Unknown), "Невідомо"),
+    (Text::Conditions(WeatherCode::ClearSky), "Ясно"),
+    (Text::Conditions(WeatherCode::MainlyClear), "Переважно ясно"),
+    (
+        Text::Conditions(WeatherCode::PartlyCloudy),
+        "Мінлива хмарність",
+    ),
+    (Text::Conditions(WeatherCode::Overcast), "Хмарно"),
+    (Text::Conditions(WeatherCode::Fog), "Туман"),
+    (Text::Conditions(WeatherCode::Drizzle), "Мряка"),
+    (
+        Text::Conditions(WeatherCode::FreezingDrizzle),
+        "Крижана мряка",
+    ),
+    (Text::Conditions(WeatherCode::Rain), "Дощ"),
+    (Text::Conditions(WeatherCode::FreezingRain), "Крижаний дощ"),
+    (Text::Conditions(WeatherCode::SnowFall), "Снігопад"),
+    (Text::Conditions(WeatherCode::SnowGrains), "Снігова крупа"),
+    (Text::Conditions(WeatherCode::RainShowers), "Зливи"),
+    (Text::Conditions(WeatherCode::SnowShowers), "Сніжні зливи"),
+    (Text::Conditions(WeatherCode::Thunderstorm), "Гроза"),
+    (Text::Trend(Trend::Rising), "зростає"),
+    (Text::Trend(Trend::Falling), "спадає"),
+    (Text::Trend(Trend::Steady), "стабільно"),
+    (Text::MoonPhase(MoonPhase::New), "Новий місяць"),
+    (Text::MoonPhase(MoonPhase::WaxingCrescent), "Молодий місяць"),
+    (Text::MoonPhase(MoonPhase::FirstQuarter), "Перша чверть"),
+    (
+        Text::MoonPhase(MoonPhase::WaxingGibbous),
+        "Місяць, що росте",
+    ),
+    (Text::MoonPhase(MoonPhase::Full), "Повня"),
+    (
+        Text::MoonPhase(MoonPhase::WaningGibbous),
+        "Місяць, що спадає",
+    ),
+    (Text::MoonPhase(MoonPhase::LastQuarter), "Остання чверть"),
+    (Text::MoonPhase(MoonPhase::WaningCrescent), "Старий місяць"),
+    (Text::CurrentWeather, "Поточна погода"),
+    (Text::Temperature, "Температура"),
+    (Text::FeelsLike, "Відчувається як"),
+    (Text::WindSpeed, "Швидкість вітру"),
+    (Text::WindDirection, "Напрямок вітру"),
+    (Text::Precipitation, "Опади"),
+    (Text::Humidity, "Вологість"),
+    (Text::WindChill, "З урахуванням вітру"),
+    (Text::UvIndex, "УФ-індекс"),
+    (Text::WeatherIn, "Погода: {}"),
+    (Text::WeatherInWithProvider, "Погода: {} (Провайдер: {})"),
+    (Text::ProvidersCompared, "Порівняння провайдерів"),
+    (Text::StaleData, "застарілі дані від {}"),
+    (Text::CachedHours, "години {}–{} з кешу, {}"),
+    (Text::FellBackTo, "{}, дані від {}"),
+    (Text::MissingExtra, "не вдалося отримати: {} ({})"),
+    (Text::ExtraNotFetched, "лише для щойно отриманих даних: {}"),
+    (Text::ExtraAlerts, "попередження про погоду"),
+    (Text::ExtraAirQuality, "якість повітря"),
+    (Text::ExtraNowcast, "найближчі опади"),
+    (Text::ExtraYesterday, "вчорашня погода"),
+    (Text::UnknownAddress, "адресу за координатами не знайдено"),
+    (Text::SparseHours, "з {} години рідші"),
+    (Text::DuplicateHours, "пропущено повторених годин: {}"),
+    (Text::ConvertedUnits, "{} переведено з {} у {}"),
+    (Text::NextMatch, "{} о {} ({} год)"),
+    (Text::NoMatch, "{}: нічого протягом {} год"),
+    (Text::OneWarning, "⚠ 1 попередження (натисніть w)"),
+    (Text::Warnings, "⚠ попереджень: {} (натисніть w)"),
+    (Text::WarningsTitle, "Попередження"),
+    (Text::MoreWarnings, "… і ще {}"),
+    (Text::Now, "зараз"),
+    (Text::Forecast, "прогноз"),
+    (Text::HistoricalData, "архівні дані"),
+    (Text::InUnit, "(у {})"),
+    (Text::OnDate, "на {}"),
+    (Text::FromToDate, "з {} по {}"),
+    (Text::LocalTime, "(час {})"),
+    (Text::PartialDay, "(* - неповний день)"),
+    (Text::OfTotal, "{}-{} з {}"),
+    (Text::Hours24, "(24 год)"),
+    (Text::SeriesKind, "{}: {}"),
+    (Text::SeriesTemperature, "Погода"),
+    (Text::SeriesFeelsLike, "Відчувається як"),
+    (Text::Pressure, "Тиск"),
+    (Text::CloudCover, "Хмарність"),
+    (Text::DailyMaximum, "Денні максимуми температури"),
+    (Text::DailyMinimum, "Денні мінімуми температури"),
+    (
+        Text::DailyRange,
+        "Денна температура від мінімуму до максимуму",
+    ),
+    (
+        Text::DailySummary,
+        "Денні мінімуми, максимуми та середні значення температури",
+    ),
+    (Text::Min, "мін"),
+    (Text::Max, "макс"),
+    (Text::Avg, "сер"),
+    (Text::DataAge, "дані {}"),
+    (Text::Updated, "оновлено {}"),
+    (Text::Fetched, "отримано {}"),
+    (Text::DaysAgo, "{} дн. тому"),
+    (Text::HoursAgo, "{} год тому"),
+    (Text::MinutesAgo, "{} хв тому"),
+    (Text::JustNow, "щойно"),
+    (Text::AirQuality, "Якість повітря (EAQI)"),
+    (Text::AqiDay, "День: {}"),
+    (Text::Nowcast, "Опади за наступні {} хв"),
+    (
+        Text::NowcastNoCoverage,
+        "Прогноз опадів на найближчі хвилини недоступний для цього місця",
+    ),
+    (
+        Text::NowcastRadarDown,
+        "Прогноз опадів на найближчі хвилини зараз недоступний для цього місця (радари не працюють)",
+    ),
+    (Text::Astro, "Місяць і світловий день"),
+    (Text::MoonIllumination, "освітлено {}%"),
+    (Text::DayLength, "Тривалість дня: {}"),
+    (Text::HoursMinutes, "{} год {} хв"),
+    (Text::Alerts, "Попередження"),
+    (Text::AlertFrom, "з {}"),
+    (Text::AlertUntil, "до {}"),
+    (Text::MissingValue, "н/д"),
+    (Text::MoreBefore, "◀ ще"),
+    (Text::MoreAfter, "ще ▶"),
+    (Text::DataAgeLine, "Дані {}"),
+    (Text::ConditionsLabel, "Умови"),
+    (Text::Daylight, "Світловий день"),
+    (Text::AirQualityOn, "Якість повітря ({})"),
+    (Text::DayRange, "За день"),
+    (Text::Particulates, "Тверді частинки"),
+    (Text::Moon, "Місяць"),
+    (Text::DayLengthLabel, "Тривалість дня"),
+    (Text::SummaryLine, "Підсумок: {}"),
+    (Text::Time, "Час"),
+    (Text::Yesterday, "Вчора"),
+    (Text::Clouds, "Хмари"),
+    (Text::Wind, "Вітер"),
+    (Text::Day, "День"),
+    (Text::MinColumn, "Мін"),
+    (Text::MaxColumn, "Макс"),
+    (Text::Mean, "Середня"),
+    (
+        Text::ExtremeHeat,
+        "Сильна спека: {} - уникайте полуденного сонця",
+    ),
+    (
+        Text::ExtremeCold,
+        "Сильний мороз: {} - прикрийте відкриту шкіру",
+    ),
+    (Text::TemperatureFeelsLike, "{}, відчувається як {}"),
+    (
+        Text::StrongWind,
+        "Сильний вітер: {} - закріпіть незакріплені предмети",
+    ),
+    (Text::ExtremeHeatShort, "сильна спека"),
+    (Text::ExtremeColdShort, "сильний мороз"),
+    (Text::StrongWindShort, "сильний вітер"),
+    (Text::MoreBanners, "ще {}"),
+    (Text::None, "немає"),
+    (Text::NoHourlyData, "Немає погодинних даних від {}"),
+    (Text::NoWeatherData, "Немає даних про погоду від {}"),
+    (Text::CouldntGetWeather, "Не вдалося отримати погоду: {}"),
+    (Text::ProviderFailed, "{}: помилка: {}"),
+    (Text::TodayYesterday, "сьогодні {} / вчора {}"),
+    (Text::BestWindow, "Найкраще вікно на {} год: {}–{} — {}"),
+    (Text::RainChance, "{}% дощу"),
+    (Text::UvValue, "УФ {}"),
+    (
+        Text::Penalties,
+        "штраф {}: дощ {}, температура {}, УФ {}, пориви {}",
+    ),
+    (
+        Text::WindLegend,
+        "вітер: тьмяний <{}, жирний {}+, попередження >{} км/год",
+    ),
+    (
+        Text::KeysHint,
+        "q/Esc: вихід  ←/→/Home/End: прокрутка  u: °C/°F",
+    ),
+    (Text::TabsHint, "Tab/1-9: інша локація"),
+    (Text::SpeechTitle, "Погода: {}, від {}"),
+    (Text::SpeechRiseFrom, "Температура зростає з {} о {}"),
+    (Text::SpeechFallFrom, "Температура знижується з {} о {}"),
+    (Text::SpeechTo, "до {} о {}"),
+    (Text::SpeechRiseTo, "зростає до {} о {}"),
+    (Text::SpeechFallTo, "знижується до {} о {}"),
+    (Text::SpeechThenRise, "потім зростає до {} близько {}"),
+    (Text::SpeechThenFall, "потім знижується до {} близько {}"),
+    (Text::SpeechHigh, "максимуму {}"),
+    (Text::SpeechLow, "мінімуму {}"),
+    (
+        Text::SpeechSteady,
+        "Температура тримається близько {} з {} до {}",
+    ),
+    (Text::SpeechAt, "Температура {} о {}"),
+    (Text::SpeechNoData, "Немає даних з {} до {}"),
+    (Text::SpeechNoDataAt, "Немає даних о {}"),
+    (Text::SpeechDay, "{}, від {} до {}"),
+    (Text::SpeechNow, "Зараз {}, {}"),
+    (Text::SpeechFeelsLike, "відчувається як {}"),
+    (Text::SpeechRainChance, "ймовірність опадів {}"),
+    (Text::SpeechWind, "Вітер {}"),
+    (Text::SpeechWindFrom, "Вітер {}, напрямок {}"),
+    (Text::SpeechAlert, "Попередження, рівень {}: {}"),
+    (Text::SpeechOnDay, "{}, {}"),
+    (Text::Midnight, "півночі"),
+    (Text::Noon, "12 дня"),
+    (Text::InTheMorning, "{} ранку"),
+    (Text::InTheAfternoon, "{} дня"),
+    (Text::InTheEvening, "{} вечора"),
+    (Text::AtNight, "{} ночі"),
+    (Text::Minus, "мінус"),
+    (Text::DegreesCelsius, "градусів Цельсія"),
+    (Text::DegreesFahrenheit, "градусів Фаренгейта"),
+    (Text::KilometresPerHour, "кілометрів на годину"),
+    (Text::MetresPerSecond, "метрів за секунду"),
+    (Text::MilesPerHour, "миль на годину"),
+    (Text::Knots, "вузлів"),
+    (Text::Millimetres, "міліметрів"),
+    (Text::Inches, "дюймів"),
+    (Text::Percent, "відсотків"),
+    (Text::North, "північ"),
+    (Text::East, "схід"),
+    (Text::South, "південь"),
+    (Text::West, "захід"),
+    (Text::Northern, "північний"),
+    (Text::Southern, "південний"),
+    (Text::CompassPair, "{} {}"),
+    (Text::RainWindow, "Дощ {}–{}"),
+    (Text::SunriseAt, "Схід сонця {}"),
+    (Text::SunsetAt, "Захід сонця {}"),
+];
+
+/// The Ukrainian string, `None` if it isn't translated
+fn ukrainian(text: Text) -> Option<&'static str> {
+    UKRAINIAN
+        .iter()
+        .find(|(translated, _)| *translated == text)
+        .map(|(_, string)| *string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_in_the_language() {
+        type Case = (Language, f64, Option<usize>, &'static str, &'static str);
+        let cases: [Case; 8] = [
+            (Language::En, -3.5, None, "°C", "-3.5°C"),
+            (Language::Uk, -3.5, None, "°C", "−3,5 °C"),
+            (Language::En, 21.0, None, "°F", "21°F"),
+            (Language::Uk, 21.0, Some(1), "°F", "21,0 °F"),
+            (Language::En, 10.25, Some(1), "km/h", "10.2 km/h"),
+            (Language::Uk, 1.5, None, "mm", "1,5 mm"),
+            (Language::En, 55.0, None, "%", "55%"),
+            (Language::Uk, 55.0, None, "%", "55%"),
+        ];
+
+        for (language, value, precision, unit, expected) in cases {
+            assert_eq!(
+                language.number_format().quantity(value, precision, unit),
+                expected,
+                "{language:?} {value} {precision:?} {unit}"
+            );
+        }
+
+        // The scripts get the same numbers whatever the language is
+        assert_eq!(machine_number(-3.5), "-3.5");
+    }
+
+    #[test]
+    fn ago_in_the_language() {
+        let cases = [
+            (chrono::Duration::seconds(59), "just now", "щойно"),
+            (chrono::Duration::minutes(1), "1m ago", "1 хв тому"),
+            (chrono::Duration::minutes(59), "59m ago", "59 хв тому"),
+            (chrono::Duration::minutes(150), "2h ago", "2 год тому"),
+            (chrono::Duration::hours(23), "23h ago", "23 год тому"),
+            (chrono::Duration::hours(49), "2d ago", "2 дн. тому"),
+            // A clock of the provider ahead of this one
+            (chrono::Duration::minutes(-5), "just now", "щойно"),
+        ];
+
+        for (age, english, ukrainian) in cases {
+            assert_eq!(Language::En.ago(age), english, "{age}");
+            assert_eq!(Language::Uk.ago(age), ukrainian, "{age}");
+        }
+    }
+
+    #[test]
+    fn day_month_in_the_language() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 4).unwrap();
+
+        assert_eq!(Language::En.number_format().day_month(date), "Jun 4");
+        assert_eq!(Language::Uk.number_format().day_month(date), "04.06.");
+    }
+}
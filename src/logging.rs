@@ -0,0 +1,76 @@
+//! Diagnostic logs of the requests (`-v`, `-vv` or the `WEATHER_LOG` filter), written to stderr so
+//! that they never end up in the output meant for the scripts
+
+use std::{
+    io::{self, IsTerminal, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use tracing_subscriber::EnvFilter;
+
+/// Environment variable with the log filter, in the `tracing` `EnvFilter` syntax ("weather=debug")
+/// (takes precedence over -v)
+pub(crate) const LOG_ENV: &str = "WEATHER_LOG";
+
+/// Whether the TUI has the terminal, the logs are held back until it's restored then
+static HELD: AtomicBool = AtomicBool::new(false);
+/// Logs written while the TUI had the terminal
+static HELD_LOGS: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+/// Start logging at the verbosity (the number of -v flags): the resolved locations, the request
+/// URLs, the statuses and the timings at 1, the response bodies as well at 2 and up
+pub(crate) fn init(verbosity: u8) {
+    let filter = EnvFilter::try_from_env(LOG_ENV).unwrap_or_else(|_| {
+        EnvFilter::new(match verbosity {
+            0 => "off",
+            1 => "weather=debug",
+            _ => "weather=trace",
+        })
+    });
+
+    // Nothing is logged by default, so there is no point in failing the run over the logs
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(|| LogWriter)
+        .with_ansi(io::stderr().is_terminal())
+        .with_target(false)
+        .try_init();
+}
+
+/// Hold the logs back while the TUI has the terminal, they would garble the screen in raw mode
+pub(crate) fn hold() {
+    HELD.store(true, Ordering::SeqCst);
+}
+
+/// Write out the logs held back while the TUI had the terminal, and stop holding them
+pub(crate) fn release() {
+    HELD.store(false, Ordering::SeqCst);
+
+    if let Ok(mut logs) = HELD_LOGS.lock() {
+        let _ = io::stderr().write_all(&logs);
+        logs.clear();
+    }
+}
+
+/// Stderr, unless the logs are held back
+struct LogWriter;
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match HELD.load(Ordering::SeqCst) {
+            true => match HELD_LOGS.lock() {
+                Ok(mut logs) => logs.write(buf),
+                // The logs are not worth panicking over
+                Err(_) => Ok(buf.len()),
+            },
+            false => io::stderr().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
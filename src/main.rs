@@ -1,85 +1,199 @@
-mod config;
-mod data;
-mod providers;
+mod cli;
+mod commands;
+mod logging;
+mod man;
 mod ui;
 
-use clap::builder::NonEmptyStringValueParser;
-use clap::{arg, command};
+use std::{
+    io::{self, IsTerminal},
+    path::PathBuf,
+    process::ExitCode,
+    thread::{self, JoinHandle},
+};
+
 use color_eyre::eyre;
+use itertools::Itertools;
 
-use crate::{providers::Provider, ui::draw_data};
+// The library modules are used by the cli as if they were its own
+use weather::{
+    archive, config, data, date, error, geocode, last_run, locale, outdoor, paths, providers,
+    publish, queries, response_cache, response_dump, timezone, update, watch, WeatherClient,
+    WeatherClientBuilder,
+};
 
-pub(crate) mod built_info {
-    // The file has been placed there by the build script.
-    include!(concat!(env!("OUT_DIR"), "/built.rs"));
-}
+use crate::{cli::exit_code, error::WeatherError, paths::Paths};
+
+fn main() -> ExitCode {
+    // Parse command line arguments
+    let matches = cli::build_cli().get_matches();
+
+    logging::init(matches.get_count("verbose"));
 
-fn main() -> eyre::Result<()> {
     // Set up colorized error messages
-    color_eyre::install()?;
+    let mut update_check = None;
+    let res = color_eyre::install().and_then(|_| run(&matches, &mut update_check));
 
-    // Parse command line arguments
-    let matches = command!()
-        .subcommand(
-            clap::Command::new("configure")
-                .before_help("Configure the weather cli (only setting a provider is supported for now)")
-                .arg(
-                    arg!(<provider>)
-                        .required(true)
-                        .help("Weather API Provider")
-                        .value_parser(Provider::AVAILABLE_PROVIDERS)
-                )
-        )
-        .subcommand(
-            clap::Command::new("get")
-                .arg(
-                    arg!(<address>)
-                        .required(true)
-                        .allow_hyphen_values(true)
-                        .value_parser(NonEmptyStringValueParser::new())
-                        .help("Address you want to get weather information from (\"lat, lon\" format is supported)")
-                )
-                .arg(
-                    arg!([date])
-                        .help("Date for which you want to get weather information (Check README for more info)")
-                        .value_parser(NonEmptyStringValueParser::new())
-                        .default_value("now")
-                )
-        ).get_matches();
+    // The newer version is mentioned after the output, whatever happened to the command
+    if let Some(message) = update_check.and_then(|check| check.join().ok().flatten()) {
+        eprintln!("{message}");
+    }
+
+    match res {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(report) => report_error(report, matches.get_flag("quiet")),
+    }
+}
+
+/// Print the error and get the exit code for its category.
+///
+/// With --quiet, the error is printed as a single `error: <category>: <message>` line without the
+/// backtrace, so that the scripts can parse it
+fn report_error(report: eyre::Report, quiet: bool) -> ExitCode {
+    // Not an error to print, the output has said what it had to already
+    if let Some(NoMatch(code)) = report.downcast_ref::<NoMatch>() {
+        return ExitCode::from(*code);
+    }
+
+    let category = report
+        .downcast_ref::<WeatherError>()
+        .map(WeatherError::category);
+
+    match quiet {
+        true => eprintln!(
+            "error: {}{}",
+            category
+                .map(|category| format!("{category}: "))
+                .unwrap_or_default(),
+            report.to_string().split_whitespace().join(" ")
+        ),
+        false => eprintln!("Error: {report:?}"),
+    }
+
+    ExitCode::from(exit_code(category))
+}
+
+/// Run the command. The daily check for a newer version goes on in the background meanwhile, it's
+/// left in `update_check` to be waited for at the end
+fn run(
+    matches: &clap::ArgMatches,
+    update_check: &mut Option<JoinHandle<Option<String>>>,
+) -> eyre::Result<()> {
+    // Every decision of the run is made for the same moment
+    let now = chrono::Utc::now();
+
+    // Resolve where all the files live, and move the ones still in the old places (if we can, the
+    // files are just ignored otherwise)
+    let paths = Paths::resolve(
+        matches.get_one::<PathBuf>("config-dir").cloned(),
+        matches.get_one::<PathBuf>("cache-dir").cloned(),
+    )?;
+    let _ = paths.migrate_flat_layout();
+
+    // Bare `weather` is `weather get` for the default location
+    let default_get;
+    let subcommand = match matches.subcommand() {
+        None => {
+            default_get = cli::build_cli().get_matches_from(["weather", "get"]);
+            default_get.subcommand()
+        }
+        subcommand => subcommand,
+    };
+
+    // The commands that change the config hold the lock until it's saved
+    let _config_lock = match subcommand {
+        Some(("configure" | "locations", _)) => {
+            Some(config::ConfigLock::acquire(&paths.config_dir)?)
+        }
+        _ => None,
+    };
 
     // Get config
-    let mut config = config::Config::new()?;
+    let mut config = config::Config::new(&paths)?;
+    for warning in config.warnings() {
+        eprintln!("Warning: {warning}");
+    }
 
-    match matches.subcommand() {
-        Some(("configure", matches)) => {
-            let provider = matches
-                .get_one::<String>("provider")
-                .ok_or(eyre::eyre!("No provider specified"))?;
+    // The first run asks for the settings instead of leaving the defaults, if there is someone to
+    // answer (`configure` sets them itself)
+    if config.created()
+        && !matches!(subcommand, Some(("configure" | "man", _)))
+        && io::stdin().is_terminal()
+        && io::stderr().is_terminal()
+    {
+        // Another invocation could have changed the file since it was created, so it's read again
+        // under the lock and the setup starts at its values
+        let _wizard_lock = config::ConfigLock::acquire(&paths.config_dir)?;
+        config = config::Config::new(&paths)?;
+        run_wizard(&mut config, &paths)?;
+    }
 
-            // Check if the input provider is valid
-            let provider = Provider::from_str(provider)?;
+    // Only for this run, so that it's never left on by accident
+    if matches.get_flag("insecure") {
+        eprintln!(
+            "Warning: TLS certificate verification is disabled (--insecure), the responses can be \
+            read and forged by anyone between you and the servers"
+        );
+        config.network.insecure = true;
+    }
 
-            // If yes, set the provider in the config
-            config.provider = provider;
+    // Only when there is someone to tell, and not when it's checked anyway
+    if config.check_updates
+        && io::stderr().is_terminal()
+        && !matches!(subcommand, Some(("update" | "man", _)))
+    {
+        let (paths, network) = (paths.clone(), config.network.clone());
 
-            // And save the config
-            config.save()
+        *update_check = Some(thread::spawn(move || {
+            update::check_daily(&paths, &network, true, now)
+        }));
+    }
+
+    match subcommand {
+        Some(("configure", matches)) if matches.get_flag("interactive") => {
+            Ok(run_wizard(&mut config, &paths)?)
+        }
+        Some(("configure", matches)) => commands::configure(matches, &mut config, &paths),
+        Some(("get", matches)) => commands::get::run(matches, &config, paths, now),
+        Some(("export", matches)) => commands::export(matches, &config, paths, now),
+        Some(("locations", matches)) => commands::locations(matches, &mut config, &paths),
+        Some(("config", matches)) => commands::show_config(matches, &config),
+        Some(("providers", matches)) => commands::providers(matches),
+        Some(("cache", matches)) => commands::clear_cache(matches, &paths),
+        Some(("man", _)) => {
+            print!("{}", man::render(cli::build_cli())?);
+
+            Ok(())
         }
-        Some(("get", matches)) => {
-            let address = matches
-                .get_one::<String>("address")
-                .ok_or(eyre::eyre!("No address specified"))?;
-            let date = matches
-                .get_one::<String>("date")
-                .cloned()
-                .unwrap_or("now".to_string());
-
-            // Get the weather data
-            let data = config.provider.get(address, date)?;
-
-            // Draw the weather data
-            draw_data(data)
+        Some(("doctor", _)) => {
+            println!("{}", paths.report());
+
+            Ok(())
         }
+        Some(("update", matches)) => commands::update(matches, &config),
         _ => Ok(()),
     }
 }
+
+/// Nothing matched `--next`, the run ends with the exit code asked for (after the output it asked
+/// for, so there is nothing to print)
+#[derive(Debug, thiserror::Error)]
+#[error("Nothing matched --next")]
+struct NoMatch(u8);
+
+/// Go through the setup on the terminal, geocoding the default location like
+/// `configure --default-location` does
+fn run_wizard(config: &mut config::Config, paths: &Paths) -> eyre::Result<()> {
+    let client = WeatherClient::builder()
+        .paths(paths.clone())
+        .network(config.network.clone())
+        .geocoder_backend(config.geocoder)
+        .choose_place(true)
+        .build()?;
+
+    Ok(config::wizard::run(
+        config,
+        &mut config::wizard::TerminalInput,
+        &mut io::stderr(),
+        |address| Ok(client.resolve(address)?.into()),
+    )?)
+}
@@ -0,0 +1,237 @@
+//! Man page generation from the clap command definition and the config/environment registries
+
+use clap_mangen::Man;
+use color_eyre::eyre;
+
+use crate::{
+    cli::{ENVIRONMENT_VARIABLES, EXIT_CODES, GET_EXAMPLES},
+    config::CONFIG_KEYS,
+};
+
+/// Render the whole man page in roff format
+pub(crate) fn render(cmd: clap::Command) -> eyre::Result<String> {
+    let man = Man::new(cmd.clone());
+    let mut page = Vec::new();
+
+    man.render_title(&mut page)?;
+    man.render_name_section(&mut page)?;
+    man.render_synopsis_section(&mut page)?;
+    man.render_description_section(&mut page)?;
+    man.render_options_section(&mut page)?;
+    man.render_subcommands_section(&mut page)?;
+
+    let mut page = String::from_utf8(page)?;
+
+    // clap_mangen only lists the subcommands, so describe their arguments ourselves
+    for subcommand in cmd.get_subcommands() {
+        page.push_str(&subcommand_section(subcommand, ""));
+    }
+
+    page.push_str(&definition_section("CONFIGURATION", &CONFIG_KEYS));
+    page.push_str(&definition_section("ENVIRONMENT", &ENVIRONMENT_VARIABLES));
+    page.push_str(&definition_section(
+        "EXIT STATUS",
+        &EXIT_CODES.map(|(code, description)| (code.to_string(), description)),
+    ));
+    page.push_str(&definition_section(
+        "EXAMPLES",
+        &GET_EXAMPLES.map(|(command, description)| (command, description)),
+    ));
+
+    let mut footer = Vec::new();
+    man.render_authors_section(&mut footer)?;
+    page.push_str(&String::from_utf8(footer)?);
+
+    Ok(page)
+}
+
+/// Section describing a subcommand and all of its arguments, followed by the ones of its own
+/// subcommands (`parent` is the path of the commands above it)
+fn subcommand_section(subcommand: &clap::Command, parent: &str) -> String {
+    let name = format!("{parent}{}", subcommand.get_name());
+    let about = subcommand
+        .get_long_about()
+        .or(subcommand.get_about())
+        .map(|about| about.to_string())
+        .unwrap_or_default();
+
+    let args = subcommand
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set())
+        .map(|arg| {
+            let name = match (arg.get_long(), arg.get_short()) {
+                (Some(long), Some(short)) => {
+                    format!("\\fB\\-{short}\\fR, \\fB\\-\\-{}\\fR", escape(long))
+                }
+                (Some(long), None) => format!("\\fB\\-\\-{}\\fR", escape(long)),
+                (None, Some(short)) => format!("\\fB\\-{short}\\fR"),
+                (None, None) => format!("\\fI<{}>\\fR", arg.get_id()),
+            };
+            let help = arg
+                .get_long_help()
+                .or(arg.get_help())
+                .map(|help| help.to_string())
+                .unwrap_or_default();
+
+            format!(".TP\n{name}\n{}\n", escape(&help))
+        })
+        .collect::<String>();
+
+    let nested = subcommand
+        .get_subcommands()
+        .map(|nested| subcommand_section(nested, &format!("{name} ")))
+        .collect::<String>();
+
+    format!(
+        ".SH \"COMMAND: {}\"\n{}\n{args}{nested}",
+        name.to_uppercase(),
+        escape(&about)
+    )
+}
+
+/// Section made of a list of terms and their descriptions
+fn definition_section(title: &str, definitions: &[(impl AsRef<str>, &str)]) -> String {
+    let definitions = definitions
+        .iter()
+        .map(|(term, description)| {
+            format!(
+                ".TP\n\\fB{}\\fR\n{}\n",
+                escape(term.as_ref()),
+                escape(description)
+            )
+        })
+        .collect::<String>();
+
+    format!(".SH \"{title}\"\n{definitions}")
+}
+
+/// Escape the text so that roff doesn't interpret any of it
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e")
+        .replace('-', "\\-")
+        .lines()
+        // Lines starting with a dot or an apostrophe would be taken as requests
+        .map(|line| match line.starts_with(['.', '\'']) {
+            true => format!("\\&{line}"),
+            // Empty lines separate the paragraphs
+            false if line.is_empty() => ".PP".to_string(),
+            false => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        cli::build_cli,
+        config::{Config, SavedLocation, TimeFormat},
+        providers::Provider,
+    };
+
+    /// Every command of the tree, the top one included
+    fn commands(cmd: &clap::Command) -> Vec<&clap::Command> {
+        std::iter::once(cmd)
+            .chain(cmd.get_subcommands().flat_map(commands))
+            .collect()
+    }
+
+    /// Dotted paths of the values the config is saved with
+    fn saved_keys(value: &Value, prefix: &str) -> Vec<String> {
+        match value {
+            Value::Object(map) if !map.is_empty() => map
+                .iter()
+                .flat_map(|(key, value)| match prefix.is_empty() {
+                    true => saved_keys(value, key),
+                    false => saved_keys(value, &format!("{prefix}.{key}")),
+                })
+                .collect(),
+            _ => vec![prefix.to_string()],
+        }
+    }
+
+    /// Whether the documented key ("network.retries", "locations.<name>", "default_location")
+    /// covers the saved one
+    fn documents(documented: &str, saved: &str) -> bool {
+        let (documented, any) = match documented.split_once(".<") {
+            Some((documented, _)) => (documented, true),
+            None => (documented, false),
+        };
+
+        (!any && saved == documented)
+            || saved
+                .strip_prefix(documented)
+                .is_some_and(|rest| rest.starts_with('.'))
+    }
+
+    #[test]
+    fn every_flag_and_subcommand() {
+        let cmd = build_cli();
+        let page = render(cmd.clone()).unwrap();
+
+        for command in commands(&cmd) {
+            // The sections are titled in the upper case
+            let name = escape(command.get_name()).to_uppercase();
+            assert!(page.contains(&name), "{name}");
+
+            for arg in command.get_arguments().filter(|arg| !arg.is_hide_set()) {
+                let name = match (arg.get_long(), arg.get_short()) {
+                    (Some(long), _) => format!("\\-\\-{}", escape(long)),
+                    (None, Some(short)) => format!("\\-{short}"),
+                    (None, None) => format!("<{}>", arg.get_id()),
+                };
+                assert!(page.contains(&name), "{} {name}", command.get_name());
+            }
+        }
+    }
+
+    #[test]
+    fn config_keys_match_the_config() {
+        let page = render(build_cli()).unwrap();
+
+        // Everything the config can be saved with, the optional parts included
+        let location = SavedLocation {
+            address: "Kyiv".to_string(),
+            lat: 50.45,
+            lon: 30.52,
+            elevation: None,
+            timezone: None,
+            country_code: None,
+        };
+        let mut config = Config::default();
+        config.fallback_provider = Some(Provider::MetNo);
+        config.api_keys = HashMap::from([(Provider::OpenWeatherMap, "key".to_string())]);
+        config.time_format = Some(TimeFormat::Hours24);
+        config.charset = serde_json::from_value(json!("ascii")).unwrap();
+        config.language = serde_json::from_value(json!("uk")).unwrap();
+        config.theme = serde_json::from_value(json!({"preset": "light", "bar": "red"})).unwrap();
+        config.default_location = Some(location.clone());
+        config.locations = HashMap::from([("home".to_string(), location)]);
+        config.network.proxy = Some("http://proxy:3128".to_string());
+        config.network.ca_bundle = Some("ca.pem".into());
+        config.publish.authorization = Some("Bearer token".to_string());
+        let saved = saved_keys(&serde_json::to_value(&config).unwrap(), "");
+
+        // Both ways, so that the table is kept up with the config
+        for key in &saved {
+            assert!(
+                CONFIG_KEYS
+                    .iter()
+                    .any(|(documented, _)| documents(documented, key)),
+                "{key} is not in CONFIG_KEYS"
+            );
+        }
+        for (documented, _) in CONFIG_KEYS {
+            assert!(
+                saved.iter().any(|key| documents(documented, key)),
+                "{documented} is not a config key"
+            );
+            assert!(page.contains(&escape(documented)), "{documented}");
+        }
+    }
+}
@@ -0,0 +1,391 @@
+//! The best hours to be outside: the window of the hours with the least rain, the mildest
+//! temperatures, the lowest UV index and the weakest gusts. And the hours in a row it rains, for
+//! --next and the calendars
+
+use std::ops::Range;
+
+use crate::{
+    config::TemperatureUnit,
+    data::{wind_speed_in_kmh, WeatherData},
+    error::{Result, WeatherError},
+};
+
+/// Temperatures (in °C) that are comfortable to be outside in, the ones below and above are
+/// penalized by how far they are from them
+pub const COMFORTABLE_TEMPERATURES: (f64, f64) = (15.0, 25.0);
+
+/// UV index above which the skin burns quickly
+pub const HIGH_UV_INDEX: f64 = 7.0;
+
+/// Gusts (in km/h) above which they get in the way
+pub const STRONG_GUSTS: f64 = 40.0;
+
+/// Precipitation (in mm) from which the hour counts as a rainy one, for the providers without the
+/// chance of precipitation
+const WET_HOUR_MM: f64 = 0.1;
+
+/// Chance of precipitation (in %) from which the hour counts as a rainy one, for the providers
+/// without the precipitation
+const RAINY_CHANCE: f64 = 50.0;
+
+/// How much every hour is penalized for its conditions (the `outdoor` section of the config)
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct OutdoorWeights {
+    /// For the chance of rain, a certain rain gets the whole of it
+    pub rain: f64,
+    /// For every °C outside of the comfortable temperatures
+    pub temperature: f64,
+    /// For every point of the UV index above the high one
+    pub uv: f64,
+    /// For every km/h of the gusts above the strong ones
+    pub gusts: f64,
+}
+
+impl Default for OutdoorWeights {
+    fn default() -> Self {
+        Self {
+            rain: 1.0,
+            temperature: 0.1,
+            uv: 0.25,
+            gusts: 0.05,
+        }
+    }
+}
+
+/// Conditions of an hour the penalties are for, in °C and km/h (`None` for the ones the provider
+/// doesn't have)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HourConditions {
+    pub temperature: f64,
+    /// From 0 to 1
+    pub rain_chance: Option<f64>,
+    pub uv_index: Option<f64>,
+    pub gusts: Option<f64>,
+}
+
+/// Penalties of an hour, or the mean ones of the hours of a window
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Penalties {
+    pub rain: f64,
+    pub temperature: f64,
+    pub uv: f64,
+    pub gusts: f64,
+}
+
+impl Penalties {
+    /// The score of the hours, the lower the better
+    pub fn total(&self) -> f64 {
+        self.rain + self.temperature + self.uv + self.gusts
+    }
+}
+
+/// Penalize the conditions of the hour with the weights
+pub fn hour_penalties(hour: &HourConditions, weights: &OutdoorWeights) -> Penalties {
+    let (comfortable_min, comfortable_max) = COMFORTABLE_TEMPERATURES;
+    let outside_comfort = (comfortable_min - hour.temperature)
+        .max(hour.temperature - comfortable_max)
+        .max(0.0);
+
+    Penalties {
+        rain: hour.rain_chance.unwrap_or_default() * weights.rain,
+        temperature: outside_comfort * weights.temperature,
+        uv: (hour.uv_index.unwrap_or_default() - HIGH_UV_INDEX).max(0.0) * weights.uv,
+        gusts: (hour.gusts.unwrap_or_default() - STRONG_GUSTS).max(0.0) * weights.gusts,
+    }
+}
+
+/// Find the window of `length` hours with the lowest mean penalties (the earliest one of the equally
+/// good ones), and its mean penalties. The windows with a missing hour (`None`) are left out
+pub fn best_window(
+    hours: &[Option<HourConditions>],
+    length: usize,
+    weights: &OutdoorWeights,
+) -> Result<(usize, Penalties)> {
+    if length == 0 || length > hours.len() {
+        return Err(WeatherError::InvalidInput(format!(
+            "There are {} hours ahead, not enough for a {length}h window",
+            hours.len()
+        )));
+    }
+
+    let penalties = hours
+        .iter()
+        .map(|hour| hour.map(|hour| hour_penalties(&hour, weights)))
+        .collect::<Vec<_>>();
+
+    penalties
+        .windows(length)
+        .enumerate()
+        .filter_map(|(start, window)| {
+            let window = window.iter().copied().collect::<Option<Vec<_>>>()?;
+            let mean = |penalty: fn(&Penalties) -> f64| {
+                window.iter().map(penalty).sum::<f64>() / length as f64
+            };
+
+            Some((
+                start,
+                Penalties {
+                    rain: mean(|p| p.rain),
+                    temperature: mean(|p| p.temperature),
+                    uv: mean(|p| p.uv),
+                    gusts: mean(|p| p.gusts),
+                },
+            ))
+        })
+        // The earlier one wins a tie
+        .reduce(|best, window| match window.1.total() < best.1.total() {
+            true => window,
+            false => best,
+        })
+        .ok_or(WeatherError::InvalidInput(format!(
+            "Every {length}h window ahead has hours without the weather, there is no best one"
+        )))
+}
+
+/// The best window of the hours to be outside, with what it's like
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct OutdoorWindow {
+    /// Index of its first hour in the hourly series of the data, and the number of its hours
+    pub start: usize,
+    pub hours: usize,
+    /// Mean penalties of its hours
+    pub penalties: Penalties,
+    /// Mean temperature in the unit of the data
+    pub temperature: f64,
+    /// The highest chance of rain in % and UV index of its hours (if the provider has them)
+    pub rain_chance: Option<f64>,
+    pub uv_index: Option<f64>,
+}
+
+impl WeatherData {
+    /// Find the best window of `length` hours to be outside, from the current hour on (from the
+    /// first one if the data doesn't have it)
+    pub fn best_outdoor_window(
+        &self,
+        length: usize,
+        weights: &OutdoorWeights,
+    ) -> Result<OutdoorWindow> {
+        let first = self.current_hour.unwrap_or_default();
+        let unit = TemperatureUnit::from_provider_unit(&self.unit);
+        // The series the provider didn't return are left out, the missing hours of the rest make
+        // the hour missing
+        let series = |values: &[f64], i: usize| match values.is_empty() {
+            true => Some(None),
+            false => values
+                .get(i)
+                .filter(|value| !value.is_nan())
+                .map(|value| Some(*value)),
+        };
+        let gusts = match wind_speed_in_kmh(0.0, &self.wind_speed_unit) {
+            Some(_) => &self.wind_gusts[..],
+            None => &[],
+        };
+
+        let hours = (first..self.temperatures.len())
+            .map(|i| {
+                let temperature = series(&self.temperatures, i)??;
+                let rain_chance = match self.precipitation_probabilities.is_empty() {
+                    true => series(&self.precipitation, i)?
+                        .map(|mm| f64::from(u8::from(mm >= WET_HOUR_MM))),
+                    false => series(&self.precipitation_probabilities, i)?.map(|p| p / 100.0),
+                };
+
+                Some(HourConditions {
+                    temperature: TemperatureUnit::Celsius.convert(temperature, unit),
+                    rain_chance,
+                    uv_index: series(&self.uv_indices, i)?,
+                    gusts: series(gusts, i)?
+                        .and_then(|gusts| wind_speed_in_kmh(gusts, &self.wind_speed_unit)),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let (start, penalties) = best_window(&hours, length, weights)?;
+        let window = first + start..first + start + length;
+        let max = |values: &[f64]| {
+            values
+                .get(window.clone())
+                .and_then(|values| values.iter().copied().reduce(f64::max))
+        };
+
+        Ok(OutdoorWindow {
+            start: window.start,
+            hours: length,
+            penalties,
+            temperature: self.temperatures[window.clone()].iter().sum::<f64>() / length as f64,
+            rain_chance: max(&self.precipitation_probabilities),
+            uv_index: max(&self.uv_indices),
+        })
+    }
+}
+
+impl WeatherData {
+    /// The hours in a row it rains (the precipitation, or the chance of it where the provider has
+    /// no precipitation), as the ranges of the indices of the hourly series
+    pub fn rain_windows(&self) -> Vec<Range<usize>> {
+        let rainy = |i: usize| match self.precipitation.get(i).filter(|mm| !mm.is_nan()) {
+            Some(mm) => *mm >= WET_HOUR_MM,
+            None => self
+                .precipitation_probabilities
+                .get(i)
+                .is_some_and(|chance| *chance >= RAINY_CHANCE),
+        };
+
+        let mut windows: Vec<Range<usize>> = vec![];
+        for i in (0..self.timestamps.len()).filter(|i| rainy(*i)) {
+            match windows.last_mut() {
+                Some(window) if window.end == i => window.end = i + 1,
+                _ => windows.push(i..i + 1),
+            }
+        }
+
+        windows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Timestamp;
+
+    fn hour(temperature: f64, rain_chance: f64, uv_index: f64, gusts: f64) -> HourConditions {
+        HourConditions {
+            temperature,
+            rain_chance: Some(rain_chance),
+            uv_index: Some(uv_index),
+            gusts: Some(gusts),
+        }
+    }
+
+    #[test]
+    fn penalties_of_the_hour() {
+        let weights = OutdoorWeights::default();
+        // (conditions) and the penalties for rain, temperature, UV and gusts
+        let cases = [
+            (hour(20.0, 0.0, 3.0, 20.0), [0.0, 0.0, 0.0, 0.0]),
+            // The edges of the comfortable ones are still fine
+            (hour(15.0, 0.0, 7.0, 40.0), [0.0, 0.0, 0.0, 0.0]),
+            (hour(25.0, 0.5, 0.0, 0.0), [0.5, 0.0, 0.0, 0.0]),
+            (hour(10.0, 0.0, 0.0, 0.0), [0.0, 0.5, 0.0, 0.0]),
+            (hour(30.0, 0.0, 9.0, 60.0), [0.0, 0.5, 0.5, 1.0]),
+        ];
+
+        for (conditions, [rain, temperature, uv, gusts]) in cases {
+            let penalties = hour_penalties(&conditions, &weights);
+            let expected = Penalties {
+                rain,
+                temperature,
+                uv,
+                gusts,
+            };
+
+            for (penalty, expected) in [
+                (penalties.rain, expected.rain),
+                (penalties.temperature, expected.temperature),
+                (penalties.uv, expected.uv),
+                (penalties.gusts, expected.gusts),
+            ] {
+                assert!(
+                    (penalty - expected).abs() < 1e-9,
+                    "{conditions:?}: {penalties:?}"
+                );
+            }
+        }
+
+        // Nothing to penalize without them
+        let dry = HourConditions {
+            temperature: 20.0,
+            rain_chance: None,
+            uv_index: None,
+            gusts: None,
+        };
+        assert_eq!(hour_penalties(&dry, &weights).total(), 0.0);
+    }
+
+    #[test]
+    fn best_windows() {
+        let weights = OutdoorWeights::default();
+        let fine = Some(hour(20.0, 0.0, 3.0, 10.0));
+        let rainy = Some(hour(20.0, 0.8, 3.0, 10.0));
+        let hot = Some(hour(32.0, 0.0, 9.0, 10.0));
+        // (hours, window length) and the start of the best window
+        type Case = (Vec<Option<HourConditions>>, usize, usize);
+        let cases: [Case; 5] = [
+            (vec![rainy, fine, fine, rainy], 2, 1),
+            // The earlier of the equally good ones
+            (vec![fine, fine, rainy, fine, fine], 2, 0),
+            (vec![hot, hot, fine, fine, fine], 3, 2),
+            // The windows with the missing hours are left out, even if the rest of them is better
+            (vec![fine, None, fine, rainy, rainy], 2, 2),
+            (vec![rainy, hot, fine], 3, 0),
+        ];
+
+        for (hours, length, start) in cases {
+            let (best, _) = best_window(&hours, length, &weights).unwrap();
+            assert_eq!(best, start, "{hours:?} {length}h");
+        }
+
+        // The penalties are the mean ones of the hours
+        let (_, penalties) = best_window(&[rainy, fine], 2, &weights).unwrap();
+        assert!((penalties.rain - 0.4).abs() < 1e-9, "{penalties:?}");
+    }
+
+    #[test]
+    fn best_window_errors() {
+        let weights = OutdoorWeights::default();
+        let fine = Some(hour(20.0, 0.0, 3.0, 10.0));
+
+        // Fewer hours ahead than the window has
+        match best_window(&[fine, fine], 3, &weights) {
+            Err(WeatherError::InvalidInput(message)) => {
+                assert!(message.contains("2 hours ahead"), "{message}");
+                assert!(message.contains("3h window"), "{message}");
+            }
+            result => panic!("{result:?}"),
+        }
+
+        // Every window has a missing hour
+        assert!(matches!(
+            best_window(&[fine, None, fine], 2, &weights),
+            Err(WeatherError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn rain_windows() {
+        let nan = f64::NAN;
+        // (precipitation, chance of it) and the windows
+        type Case = (Vec<f64>, Vec<f64>, Vec<Range<usize>>);
+        let cases: [Case; 4] = [
+            (vec![0.0, 0.5, 1.2, 0.0, 0.0, 0.3], vec![], vec![1..3, 5..6]),
+            // A trace of it isn't rain
+            (vec![0.05, 0.0, 0.0], vec![], vec![]),
+            // The chance of it where the precipitation is missing
+            (
+                vec![0.0, nan, nan, 0.2],
+                vec![10.0, 60.0, 20.0, 10.0],
+                vec![1..2, 3..4],
+            ),
+            (vec![], vec![80.0, 90.0, 40.0, 55.0], vec![0..2, 3..4]),
+        ];
+
+        let time = chrono::DateTime::parse_from_rfc3339("2024-06-14T00:00:00Z").unwrap();
+        for (precipitation, chances, expected) in cases {
+            let hours = precipitation.len().max(chances.len());
+            let data = WeatherData {
+                timestamps: vec![Timestamp::new(time, "%H"); hours],
+                precipitation: precipitation.clone(),
+                precipitation_probabilities: chances.clone(),
+                ..Default::default()
+            };
+
+            assert_eq!(
+                data.rain_windows(),
+                expected,
+                "{precipitation:?} {chances:?}"
+            );
+        }
+    }
+}
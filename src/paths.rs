@@ -0,0 +1,246 @@
+//! Locations of all the files the weather cli reads and writes
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    built_info,
+    error::{Result, WeatherError},
+};
+
+/// Environment variable overriding the config directory
+pub const CONFIG_DIR_ENV: &str = "WEATHER_CLI_CONFIG_DIR";
+/// Environment variable overriding the cache directory (state files go there as well)
+pub const CACHE_DIR_ENV: &str = "WEATHER_CLI_CACHE_DIR";
+
+/// Files that used to live next to config.json, but are state and not configuration
+const STATE_FILES: [&str; 1] = ["cooldowns.json"];
+
+/// Name of the note left in the config directory after moving files out of it
+const MIGRATION_NOTE: &str = "MOVED.txt";
+
+/// Directories used by the app, resolved once at startup and passed to everything that needs them
+#[derive(Debug, Default, Clone)]
+pub struct Paths {
+    /// User configuration (config.json)
+    pub config_dir: PathBuf,
+    /// Data that can be thrown away at any moment without losing anything important
+    pub cache_dir: PathBuf,
+    /// Data that should survive between runs, but is not configuration (cooldowns)
+    pub state_dir: PathBuf,
+}
+
+impl Paths {
+    /// Resolve the directories, the precedence being: CLI flag > environment variable > system
+    /// default location
+    pub fn resolve(
+        config_dir_override: Option<PathBuf>,
+        cache_dir_override: Option<PathBuf>,
+    ) -> Result<Self> {
+        let from_env = |var| std::env::var_os(var).map(PathBuf::from);
+
+        let config_dir = match config_dir_override.or_else(|| from_env(CONFIG_DIR_ENV)) {
+            Some(dir) => dir,
+            None => dirs::config_dir()
+                .ok_or(WeatherError::Config(
+                    "Could not find config directory".to_string(),
+                ))?
+                .join(built_info::PKG_NAME),
+        };
+
+        let (cache_dir, state_dir) = match cache_dir_override.or_else(|| from_env(CACHE_DIR_ENV)) {
+            // If the cache location is overridden, keep the state there as well, so that
+            // everything mutable ends up in the place the user asked for
+            Some(dir) => (dir.clone(), dir),
+            None => {
+                let cache_dir = dirs::cache_dir()
+                    .ok_or(WeatherError::Config(
+                        "Could not find cache directory".to_string(),
+                    ))?
+                    .join(built_info::PKG_NAME);
+                // Not every platform has a dedicated state directory
+                let state_dir = dirs::state_dir()
+                    .map(|dir| dir.join(built_info::PKG_NAME))
+                    .unwrap_or(cache_dir.clone());
+
+                (cache_dir, state_dir)
+            }
+        };
+
+        Ok(Self {
+            config_dir,
+            cache_dir,
+            state_dir,
+        })
+    }
+
+    /// Move the state files that older versions kept next to config.json into the state directory,
+    /// leaving a note about where they went
+    pub fn migrate_flat_layout(&self) -> Result<()> {
+        if self.state_dir == self.config_dir {
+            return Ok(());
+        }
+
+        let mut moved = Vec::new();
+
+        for file_name in STATE_FILES {
+            let old_path = self.config_dir.join(file_name);
+            let new_path = self.state_dir.join(file_name);
+
+            // Never overwrite the newer files
+            if !old_path.exists() || new_path.exists() {
+                continue;
+            }
+
+            std::fs::create_dir_all(&self.state_dir)?;
+
+            // Renaming doesn't work across file systems, so copy and delete in that case
+            if std::fs::rename(&old_path, &new_path).is_err() {
+                std::fs::copy(&old_path, &new_path)?;
+                std::fs::remove_file(&old_path)?;
+            }
+
+            moved.push(format!("{} -> {}", old_path.display(), new_path.display()));
+        }
+
+        if !moved.is_empty() {
+            std::fs::write(
+                self.config_dir.join(MIGRATION_NOTE),
+                format!(
+                    "These files were moved out of the config directory:\n{}\n",
+                    moved.join("\n")
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Human readable report of all the directories and files in them, with their sizes
+    pub fn report(&self) -> String {
+        [
+            ("Config", &self.config_dir),
+            ("Cache", &self.cache_dir),
+            ("State", &self.state_dir),
+        ]
+        .into_iter()
+        .map(|(name, dir)| format!("{name} directory: {}\n{}", dir.display(), dir_listing(dir)))
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+}
+
+/// List the files in the directory with their sizes
+fn dir_listing(dir: &Path) -> String {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return "  (doesn't exist)\n".to_string();
+    };
+
+    let mut files = entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+
+            metadata
+                .is_file()
+                .then(|| (entry.file_name(), metadata.len()))
+        })
+        .collect::<Vec<_>>();
+
+    files.sort();
+
+    match files.is_empty() {
+        true => "  (empty)\n".to_string(),
+        false => files
+            .into_iter()
+            .map(|(name, size)| format!("  {} ({size} B)\n", name.to_string_lossy()))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Config, cache and state directories of their own under an empty directory of the test
+    fn test_paths(name: &str) -> Paths {
+        let dir = std::env::temp_dir().join(format!("weather-paths-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("config")).unwrap();
+
+        Paths {
+            config_dir: dir.join("config"),
+            cache_dir: dir.join("cache"),
+            state_dir: dir.join("state"),
+        }
+    }
+
+    #[test]
+    fn flat_layout_migration() {
+        let paths = test_paths("migration");
+        let (old, new) = (
+            paths.config_dir.join("cooldowns.json"),
+            paths.state_dir.join("cooldowns.json"),
+        );
+        std::fs::write(&old, "{\"old\":1}").unwrap();
+        std::fs::write(paths.config_dir.join("config.json"), "{}").unwrap();
+
+        paths.migrate_flat_layout().unwrap();
+
+        // The state file is moved, the config stays, and the note says where the file went
+        assert!(!old.exists());
+        assert_eq!(std::fs::read_to_string(&new).unwrap(), "{\"old\":1}");
+        assert!(paths.config_dir.join("config.json").exists());
+        let note = std::fs::read_to_string(paths.config_dir.join(MIGRATION_NOTE)).unwrap();
+        assert!(note.contains(&new.display().to_string()), "{note}");
+
+        // The file written since then is never overwritten by an older one
+        std::fs::write(&old, "{\"older\":1}").unwrap();
+        paths.migrate_flat_layout().unwrap();
+        assert_eq!(std::fs::read_to_string(&new).unwrap(), "{\"old\":1}");
+        assert!(old.exists());
+
+        let _ = std::fs::remove_dir_all(paths.config_dir.parent().unwrap());
+    }
+
+    #[test]
+    fn fresh_start() {
+        let paths = test_paths("fresh");
+
+        // Nothing to move, so nothing is created
+        paths.migrate_flat_layout().unwrap();
+        assert!(!paths.state_dir.exists());
+        assert!(!paths.config_dir.join(MIGRATION_NOTE).exists());
+
+        let _ = std::fs::remove_dir_all(paths.config_dir.parent().unwrap());
+    }
+
+    #[test]
+    fn override_precedence() {
+        let dir = std::env::temp_dir();
+        let (flag, env) = (dir.join("from-flag"), dir.join("from-env"));
+        std::env::set_var(CONFIG_DIR_ENV, &env);
+        std::env::set_var(CACHE_DIR_ENV, &env);
+
+        // The flags win over the environment, and the state follows the cache directory
+        let paths = Paths::resolve(Some(flag.clone()), Some(flag.clone())).unwrap();
+        assert_eq!(
+            (paths.config_dir, paths.cache_dir, paths.state_dir),
+            (flag.clone(), flag.clone(), flag)
+        );
+
+        // The environment wins over the system directories
+        let paths = Paths::resolve(None, None).unwrap();
+        assert_eq!(
+            (paths.config_dir, paths.cache_dir, paths.state_dir),
+            (env.clone(), env.clone(), env)
+        );
+
+        std::env::remove_var(CONFIG_DIR_ENV);
+        std::env::remove_var(CACHE_DIR_ENV);
+
+        // Both of them can be left to the system
+        let paths = Paths::resolve(None, None).unwrap();
+        assert!(paths.config_dir.ends_with(built_info::PKG_NAME));
+        assert!(paths.cache_dir.ends_with(built_info::PKG_NAME));
+    }
+}
@@ -1,31 +1,61 @@
-use std::fmt::{Display, Formatter};
+pub(crate) mod met_no;
+pub(crate) mod open_meteo;
+pub(crate) mod open_weather_map;
 
-use color_eyre::eyre;
-use geocoding::{Forward, Openstreetmap, Point, Reverse};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    ops::RangeInclusive,
+    path::PathBuf,
+    sync::Arc,
+    time::Instant,
+};
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Utc};
 use itertools::Itertools;
+use reqwest::{
+    header::{EXPIRES, IF_MODIFIED_SINCE, LAST_MODIFIED},
+    Url,
+};
 use serde_json::{Map, Value};
 
-use crate::data::WeatherData;
+use crate::{
+    cassette,
+    config::{GeocoderBackend, NetworkSettings, TemperatureUnit, TimeFormat},
+    cooldown::{cooldown_error, throttled_until, Cooldowns, DEFAULT_COOLDOWN_HOURS},
+    coordinates::parse_coordinates,
+    data::{
+        AirQualityData, AstroData, CurrentSnapshot, DataSource, Degradation, Extra, NowcastData,
+        WeatherAlert, WeatherData,
+    },
+    date::{
+        input_has_time, input_has_year, locale_is_month_first, nearest_occurrence,
+        parse_partial_date, parse_relative_date, parse_relative_range, split_range, DateRange,
+    },
+    error::{ErrorCategory, Result, WeatherError},
+    geocode::{
+        choose_place, Address, FallbackGeocoder, GeocodeCache, GeocodeRequest, Geocoder, Nominatim,
+        OpenMeteoGeocoder, ResolvedLocation,
+    },
+    locale::Language,
+    paths::Paths,
+    response_cache::{response_key, CachedResponse, ResponseCache},
+    response_dump::ResponseDump,
+};
 
 /// These providers are free and don't require an API key.
 /// I chose them deliberately because of security concerns of having API keys that are
 /// tied to my account and my wallet available in a public repo
-
 macro_rules! decl_provider_enum {
     ($len:literal: [$(
-        $variant:ident => (
-            str: $str:literal,
-            base_url: $base_url:literal,
-            lat_param: $lat_param:literal,
-            lon_param: $lon_param:literal
-        )
+        $variant:ident => (str: $str:literal, implementation: $implementation:path)
     ),*]) => {
         #[derive(
-            Default, Debug, Copy, Clone,
+            Default, Debug, Copy, Clone, PartialEq, Eq, Hash,
             serde::Serialize, serde::Deserialize,
         )]
         #[serde(rename_all = "snake_case")]
-        pub(crate) enum Provider {
+        pub enum Provider {
             #[default]
             $($variant),*
         }
@@ -39,332 +69,2805 @@ macro_rules! decl_provider_enum {
         }
 
         impl Provider {
-            pub(crate) const AVAILABLE_PROVIDERS: [&str; $len] = [$($str),*];
+            pub const AVAILABLE_PROVIDERS: [&str; $len] = [$($str),*];
+            pub const ALL: [Self; $len] = [$(Self::$variant),*];
 
             /// Parse a string into a provider
-            pub(crate) fn from_str(s: impl AsRef<str>) -> eyre::Result<Self> {
+            #[allow(clippy::should_implement_trait)]
+            pub fn from_str(s: impl AsRef<str>) -> Result<Self> {
                 match s.as_ref() {
                     $($str => Ok(Self::$variant),)*
-                    _ => Err(eyre::eyre!(
-                        r"
+                    _ => Err(WeatherError::InvalidInput(format!(r"
                             Invalid provider!
                             Available providers: [{}]
                             ",
-                        Self::AVAILABLE_PROVIDERS.iter().join(", ")
-                    ))
-                }
-            }
-
-            /// API base URL
-            fn base_url(&self) -> &'static str {
-                match self {
-                    $(Self::$variant => $base_url),*
+                        Self::AVAILABLE_PROVIDERS.iter().join(", "))))
                 }
             }
 
-            /// API parameter format for latitude value
-            fn lat_param(&self) -> &'static str {
+            /// Requests and responses of the provider
+            pub(crate) fn implementation(&self) -> &'static dyn WeatherProvider {
                 match self {
-                    $(Self::$variant => $lat_param),*
-                }
-            }
-
-            /// API parameter format for longitude value
-            fn lon_param(&self) -> &'static str {
-                match self {
-                    $(Self::$variant => $lon_param),*
+                    $(Self::$variant => &$implementation),*
                 }
             }
         }
     };
 }
 
-decl_provider_enum!(2: [
-    OpenMeteo => (
-        str: "open_meteo",
-        base_url: "https://api.open-meteo.com/v1",
-        lat_param: "latitude",
-        lon_param: "longitude"
-    ),
-    MetNo => (
-        str: "met_no",
-        base_url: "https://api.met.no/weatherapi/locationforecast/2.0",
-        lat_param: "lat",
-        lon_param: "lon"
-    )
+decl_provider_enum!(3: [
+    OpenMeteo => (str: "open_meteo", implementation: open_meteo::OpenMeteo),
+    MetNo => (str: "met_no", implementation: met_no::MetNo),
+    OpenWeatherMap => (str: "open_weather_map", implementation: open_weather_map::OpenWeatherMap)
 ]);
 
-impl Provider {
-    /// Get the weather data for a given address and a date
-    pub(crate) fn get(&self, address: impl AsRef<str>, date: String) -> eyre::Result<WeatherData> {
-        // Create the request builder and set the address
-        let mut request_builder = ProviderRequestBuilder::new(*self).address(address)?;
-
-        // Check which provider we are using
-        request_builder = match self {
-            // If we're using open_meteo, just set the date, as it supports custom dates
-            Provider::OpenMeteo => request_builder.date(date)?,
-            // If we're using met_no, check if the date is "now"
-            Provider::MetNo => match date.as_str() == "now" {
-                // If it is, just set the date
-                true => request_builder.date(date)?,
-                // But if it isn't, return an error
-                false => {
-                    return Err(eyre::eyre!("met_no doesn't support custom dates"));
-                }
-            },
-        };
+/// Everything that differs from one provider to another: the requests, the responses and what the
+/// provider can do. Adding a provider takes a module implementing it and its line in the
+/// `decl_provider_enum!` above
+pub(crate) trait WeatherProvider: Sync {
+    /// What the provider can do
+    fn capabilities(&self) -> ProviderCapabilities;
 
-        // Build and execute the request
-        let (request_str, request_type, requested_date, address) = request_builder.build()?;
+    /// API parameters for the latitude and the longitude
+    fn coordinate_params(&self) -> (&'static str, &'static str) {
+        ("lat", "lon")
+    }
 
-        // Check which provider is being used, execute the request based on the provider and get the
-        // json data from the response
-        let json = self.request(request_str)?;
+    /// API parameter for the elevation of the location, if the provider takes it
+    fn elevation_param(&self) -> Option<&'static str> {
+        None
+    }
 
-        // Parse the json data to WeatherData struct
-        let data = WeatherData::from_json(&json, *self, request_type, requested_date, address)?;
+    /// Headers every request to the provider has to have
+    fn headers(&self) -> &'static [(&'static str, &'static str)] {
+        &[]
+    }
 
-        Ok(data)
+    /// URL of the request, with the parameters of the location and the dates collected by the
+    /// request builder
+    fn build_request(
+        &self,
+        request_type: &ProviderRequestType,
+        params: QueryParams,
+        options: &RequestOptions,
+    ) -> Result<Url>;
+
+    /// Parse the response into the data, which already has the details of the request filled in
+    fn parse(&self, json: &Map<String, Value>, data: WeatherData) -> Result<WeatherData>;
+
+    /// URL of the current conditions and the temperatures of the next `hours` hours only (`params`
+    /// are the ones of the location collected by the request builder), if the provider has a
+    /// lighter request for them than the full one
+    fn current_url(
+        &self,
+        _params: QueryParams,
+        _options: &RequestOptions,
+        _hours: usize,
+    ) -> Option<Url> {
+        None
     }
 
-    fn request(&self, request_str: impl reqwest::IntoUrl) -> eyre::Result<Map<String, Value>> {
-        match self {
-            // If it's open_meteo, just use normal get request
-            Provider::OpenMeteo => Ok(reqwest::blocking::get(request_str)?.json()?),
-            // For met_no, we need to specify some headers, so here I'm using Client to build the
-            // appropriate request
-            Provider::MetNo => {
-                let client = reqwest::blocking::Client::new();
-                let response = client
-                    .get(request_str)
-                    .header("Accept", "application/json")
-                    .header("User-Agent", "tukweathercli/0.1.0")
-                    .send()?;
+    /// Parse the body of the response to the [`current_url`](Self::current_url) request
+    fn parse_current(
+        &self,
+        _body: &str,
+        _location: ResolvedLocation,
+        _hours: usize,
+    ) -> Result<CurrentSnapshot> {
+        Err(WeatherError::Unsupported(
+            "The current conditions alone are not supported by the provider".to_string(),
+        ))
+    }
 
-                Ok(response.json()?)
-            }
-        }
+    /// URL of the weather alerts for the location, if the provider has them
+    fn alerts_url(&self, _location: &ResolvedLocation) -> Option<Url> {
+        None
     }
 
-    /// API parameter format for date value
-    fn date_format(&self) -> eyre::Result<&'static str> {
+    /// Parse the response to the [`alerts_url`](Self::alerts_url) request
+    fn parse_alerts(&self, _json: &Map<String, Value>) -> Result<Vec<WeatherAlert>> {
+        Ok(Vec::new())
+    }
+
+    /// URL of the air quality for the same location and days as the weather (`params` are the ones
+    /// collected by the request builder), if the provider has it
+    fn air_quality_url(&self, _params: &QueryParams) -> Option<Url> {
+        None
+    }
+
+    /// Parse the response to the [`air_quality_url`](Self::air_quality_url) request, for the day
+    /// the data is for
+    fn parse_air_quality(
+        &self,
+        _json: &Map<String, Value>,
+        _data: &WeatherData,
+    ) -> Result<Option<AirQualityData>> {
+        Ok(None)
+    }
+
+    /// URL of the precipitation of the next minutes at the location (`params` are the ones
+    /// collected by the request builder), if the provider has it
+    fn nowcast_url(&self, _params: &QueryParams) -> Option<Url> {
+        None
+    }
+
+    /// Parse the response to the [`nowcast_url`](Self::nowcast_url) request, in the clock of the
+    /// data
+    fn parse_nowcast(
+        &self,
+        _json: &Map<String, Value>,
+        _data: &WeatherData,
+    ) -> Result<Option<NowcastData>> {
+        Ok(None)
+    }
+}
+
+/// Elevations (in m) the requests accept, from below the Dead Sea shore to above Everest
+pub const ELEVATION_RANGE: RangeInclusive<f64> = -500.0..=9000.0;
+
+/// Names and values of the request parameters, they are percent-encoded when the URL is built
+pub(crate) type QueryParams = Vec<(&'static str, String)>;
+
+/// Decimals of the coordinates in the requests (~10 m), more than any of the grids resolve, and
+/// met_no asks for no more than 4
+const COORDINATE_DECIMALS: i32 = 4;
+
+/// Coordinate as it goes into the requests, rounded and without the trailing zeros, so that the
+/// float noise of the geocoded ones ("50.450001234") doesn't end up in the URL
+pub(crate) fn request_coordinate(value: f64) -> String {
+    let scale = 10f64.powi(COORDINATE_DECIMALS);
+
+    // Adding 0 turns the -0 of the tiny negative values into 0
+    ((value * scale).round() / scale + 0.0).to_string()
+}
+
+/// URL of the endpoint with the parameters
+pub(crate) fn request_url(endpoint: &str, params: &[(&'static str, String)]) -> Result<Url> {
+    Url::parse_with_params(endpoint, params).map_err(|e| {
+        WeatherError::InvalidInput(format!("Couldn't build the request URL {endpoint}: {e}"))
+    })
+}
+
+/// Body of a successful response as it was received, with the caching headers (met_no sends them)
+#[derive(Debug)]
+struct ProviderResponse {
+    status: reqwest::StatusCode,
+    body: String,
+    last_modified: Option<String>,
+    expires: Option<DateTime<Utc>>,
+    /// The provider marked the version of its API as deprecated (met_no answers with a 203)
+    deprecated: bool,
+}
+
+impl ProviderResponse {
+    /// Parse the body, which is not always JSON even if the request succeeded
+    fn json(&self, provider: Provider) -> Result<Map<String, Value>> {
+        serde_json::from_str(&self.body).map_err(|_| {
+            WeatherError::InvalidResponse(format!(
+                "{provider} sent a response that is not JSON ({}): {}",
+                self.status,
+                body_excerpt(&self.body)
+            ))
+        })
+    }
+}
+
+/// Where the weather is requested for
+#[derive(Debug, Clone)]
+pub enum Location {
+    /// Address or "lat, lon" pair typed by the user
+    Address(String),
+    /// Location resolved before (saved in the config), used without any geocoding
+    Resolved(ResolvedLocation),
+}
+
+/// Provider to turn to when the chosen one can't serve the request
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Fallback {
+    Provider(Provider),
+    /// The first of the providers that can serve it
+    Auto,
+}
+
+impl Display for Fallback {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Provider::OpenMeteo => Ok("%Y-%m-%d"),
-            Provider::MetNo => Err(eyre::eyre!("met_no doesn't support custom dates")),
+            Self::Provider(provider) => write!(f, "{provider}"),
+            Self::Auto => write!(f, "auto"),
         }
     }
 }
 
-#[derive(Default, Debug)]
-pub(crate) enum ProviderRequestType {
-    #[default]
-    Forecast,
-    History,
-}
+impl Fallback {
+    /// Parse a string into a fallback, "auto" or one of the providers
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: impl AsRef<str>) -> Result<Self> {
+        match s.as_ref() {
+            "auto" => Ok(Self::Auto),
+            s => Provider::from_str(s).map(Self::Provider),
+        }
+    }
 
-impl ProviderRequestType {
-    fn to_string(&self, provider: &Provider) -> eyre::Result<&'static str> {
+    /// Providers to try in place of `provider`, in order
+    fn candidates(self, provider: Provider) -> Vec<Provider> {
         match self {
-            ProviderRequestType::Forecast => Ok(match provider {
-                Provider::OpenMeteo => "forecast",
-                Provider::MetNo => "complete",
-            }),
-            ProviderRequestType::History => match provider {
-                Provider::OpenMeteo => Ok("archive"),
-                Provider::MetNo => Err(eyre::eyre!("History is not supported by met_no provider")),
-            },
+            Self::Provider(fallback) => vec![fallback],
+            Self::Auto => Provider::ALL.to_vec(),
         }
+        .into_iter()
+        .filter(|candidate| *candidate != provider)
+        .collect()
     }
 }
 
-struct ProviderRequestBuilder {
-    provider: Provider,
-    requested_date: String,
-    address: String,
-    /// Parameters that are added to the request URL
-    params: Vec<String>,
-    request_type: ProviderRequestType,
+/// Why the provider can't serve the request at all, `None` for the rest of the errors (another
+/// provider can't do anything about the network, the address or the provider being down)
+fn capability_rejection(error: &WeatherError) -> Option<String> {
+    match error {
+        // In the middle of the warning
+        WeatherError::Unsupported(reason) => Some(
+            reason
+                .chars()
+                .take(1)
+                .flat_map(char::to_lowercase)
+                .chain(reason.chars().skip(1))
+                .collect(),
+        ),
+        WeatherError::MissingApiKey { provider } => Some(format!("{provider} has no API key")),
+        _ => None,
+    }
 }
 
-impl ProviderRequestBuilder {
-    fn new(provider: Provider) -> Self {
-        Self {
-            provider,
-            requested_date: String::new(),
-            address: "Unknown".to_string(),
-            params: Vec::new(),
-            request_type: ProviderRequestType::Forecast,
-        }
+impl Provider {
+    /// Check that the date (or the range of days) parses, without requesting anything
+    pub(crate) fn check_date(&self, date: &str, options: &RequestOptions) -> Result<()> {
+        ProviderRequestBuilder::new(*self)
+            .options(options.clone())
+            .check_date(date)
     }
 
-    /// Set the address
-    fn address(mut self, address: impl AsRef<str>) -> eyre::Result<Self> {
-        // Check if the address contains a comma
-        let maybe_lat_lon = match address.as_ref().contains(',') {
-            true => {
-                // If it does, split it into a vector of separated strings
-                let parts = address
-                    .as_ref()
-                    .split(',')
-                    .map(|s| s.trim())
-                    .collect::<Vec<_>>();
-
-                // Check if the vector has two elements and if they are both valid floats
-                let lat_lon_f64 = match parts.len() == 2 {
-                    true => {
-                        let lat = parts[0].parse::<f64>().ok();
-                        let lon = parts[1].parse::<f64>().ok();
-
-                        lat.and_then(|lat| lon.map(|lon| (lat, lon)))
-                    }
-                    false => None,
-                };
+    /// Get the weather data for a given location and a date, from the fallback provider if this
+    /// one can't serve the request (and there is a fallback)
+    pub(crate) fn get(
+        &self,
+        location: Location,
+        date: String,
+        options: &RequestOptions,
+    ) -> Result<WeatherData> {
+        // A date that can't be parsed fails before the address is geocoded, so that it's not the
+        // address getting the blame
+        self.check_date(&date, options)?;
 
-                // If yes, we got the latitude and longitude
-                match lat_lon_f64 {
-                    Some((lat, lon)) => {
-                        if !(-90.0..=90.0).contains(&lat) {
-                            return Err(eyre::eyre!("Latitude must be between -90 and 90"));
-                        }
+        // A replayed response is the one of its provider
+        let fallback = match (options.fallback, &options.replay) {
+            (Some(fallback), None) => fallback,
+            _ => return self.fetch(location, date, options),
+        };
 
-                        if !(-180.0..=180.0).contains(&lon) {
-                            return Err(eyre::eyre!("Longitude must be between -180 and 180"));
-                        }
+        // Geocode the address once, instead of once per provider (and ask which place was meant
+        // only once)
+        let location = match location {
+            Location::Address(address) => Location::Resolved(resolve_location(&address, options)?),
+            location => location,
+        };
 
-                        Some((lat.to_string(), lon.to_string()))
-                    }
-                    None => None,
+        let (error, reason) = match self.fetch(location.clone(), date.clone(), options) {
+            Err(error) => match capability_rejection(&error) {
+                Some(reason) => (error, reason),
+                None => return Err(error),
+            },
+            data => return data,
+        };
+
+        for provider in fallback.candidates(*self) {
+            let options = RequestOptions {
+                api_key: options.fallback_api_keys.get(&provider).cloned(),
+                fallback: None,
+                ..options.clone()
+            };
+            if !provider.can_serve(&options) {
+                continue;
+            }
+
+            // The dates are only checked by the request itself, so the next one is tried if this
+            // one can't serve them either
+            match provider.fetch(location.clone(), date.clone(), &options) {
+                Ok(mut data) => {
+                    data.degradations.push(Degradation::Failover {
+                        provider,
+                        reason: reason.clone(),
+                    });
+
+                    return Ok(data);
                 }
+                Err(e) if capability_rejection(&e).is_some() => continue,
+                Err(e) => return Err(e),
             }
-            false => None,
+        }
+
+        Err(error)
+    }
+
+    /// Get the current conditions and the temperatures of the next `hours` hours for the one line
+    /// outputs. The coordinates aren't looked up by the geocoder, and the providers with a lighter
+    /// request for them don't send and parse the full one (the rest of them still do)
+    pub(crate) fn get_current(
+        &self,
+        location: Location,
+        hours: usize,
+        options: &RequestOptions,
+    ) -> Result<CurrentSnapshot> {
+        // The coordinates name the location themselves
+        let location = match location {
+            Location::Address(address) if parse_coordinates(&address).is_some() => {
+                Location::Resolved(resolve_location(
+                    &address,
+                    &RequestOptions {
+                        no_geocode: true,
+                        ..options.clone()
+                    },
+                )?)
+            }
+            location => location,
         };
 
-        let osm = Openstreetmap::new();
+        // The dumps and the replays are of the full responses, and so is everything the other
+        // providers send
+        let lean = match (
+            &location,
+            options.replay.is_none() && options.dump_response.is_none(),
+        ) {
+            (Location::Resolved(resolved), true) => {
+                let params = ProviderRequestBuilder::new(*self)
+                    .options(options.clone())
+                    .location(Location::Resolved(resolved.clone()))?
+                    .params;
 
-        let lat_lon = match maybe_lat_lon {
-            // If lat, lon were not provided as the address
-            None => {
-                self.address = address.as_ref().to_string();
+                self.implementation()
+                    .current_url(params, options, hours)
+                    .map(|url| (resolved.clone(), url))
+            }
+            _ => None,
+        };
 
-                // Try to get the coordinates from the address
-                let points = osm.forward(address.as_ref())?;
-                let lon_lat_point: &Point<f64> = points
-                    .first()
-                    .ok_or(eyre::eyre!("Could not find location"))?;
+        match lean {
+            Some((resolved, url)) => self.fetch_current(resolved, (url, hours), options),
+            None => self
+                .get(location, "now".to_string(), options)
+                .map(|data| CurrentSnapshot::from_data(&data, hours)),
+        }
+    }
+
+    /// Get the current conditions from the lighter request, or from the cache the same way as the
+    /// full responses
+    fn fetch_current(
+        &self,
+        location: ResolvedLocation,
+        (url, hours): (Url, usize),
+        options: &RequestOptions,
+    ) -> Result<CurrentSnapshot> {
+        let cache = ResponseCache::new(&options.paths);
+        let key = response_key(
+            *self,
+            &location,
+            CURRENT_REQUEST,
+            None,
+            false,
+            options.elevation,
+        );
+        let cached = cache.get(&key);
+        let cached_body = |cached: CachedResponse| serde_json::to_string(&cached.json);
 
-                (lon_lat_point.y().to_string(), lon_lat_point.x().to_string())
+        let body = match (options.cached, cached) {
+            (true, cached) => cached_body(cached.ok_or(WeatherError::NotCached(format!(
+                "No cached current {self} conditions for {}, drop --cached to fetch them",
+                location.display_name
+            )))?)?,
+            (false, Some(cached))
+                if cached.expires.is_some_and(|expires| expires > options.now) =>
+            {
+                cached_body(cached)?
             }
-            Some(lat_lon) => {
-                // If lat, lon were provided as the address, parse them to doubles
-                let lat = lat_lon.0.parse::<f64>()?;
-                let lon = lat_lon.1.parse::<f64>()?;
+            (false, cached) => match self.request(&url, options, cached.as_ref()) {
+                Ok(response) => {
+                    cache.insert(
+                        &key,
+                        &CachedResponse {
+                            provider: *self,
+                            address: location.display_name.clone(),
+                            lat: location.lat,
+                            lon: location.lon,
+                            fetched_at: options.now,
+                            last_modified: response.last_modified.clone(),
+                            expires: response.expires,
+                            json: response.json(*self)?,
+                        },
+                    );
+
+                    response.body
+                }
+                // Old data is better than no data, as long as it's not too old
+                Err(e) if e.category() == ErrorCategory::Network => {
+                    let max_age =
+                        chrono::Duration::hours(options.network.cache_max_age_hours as i64);
+
+                    match cache.get(&key) {
+                        Some(cached) if options.now - cached.fetched_at <= max_age => {
+                            cached_body(cached)?
+                        }
+                        _ => return Err(e),
+                    }
+                }
+                Err(e) => return Err(e),
+            },
+        };
 
-                // General writing convention for coordinates seems to be lat long from just
-                // browsing the net, but the api here requires lon lat, so thats why im swapping
-                // them like this
-                let lon_lat_point = Point::<f64>::new(lon, lat);
+        // The cached response might be in the other unit
+        Ok(self
+            .implementation()
+            .parse_current(&body, location, hours)?
+            .convert_temperatures(options.unit))
+    }
+
+    /// Get the current conditions alone from the lighter request, for when the full one fails. The
+    /// hours of the lighter response are dropped, they are only the temperatures
+    fn current_only(
+        &self,
+        location: &ResolvedLocation,
+        options: &RequestOptions,
+    ) -> Option<Map<String, Value>> {
+        let params = ProviderRequestBuilder::new(*self)
+            .options(options.clone())
+            .location(Location::Resolved(location.clone()))
+            .ok()?
+            .params;
+        let url = self.implementation().current_url(params, options, 1)?;
 
-                // Search for an save the address that we get from coordinates provided
-                self.address = osm
-                    .reverse(&lon_lat_point)
-                    .map_err(|e| eyre::eyre!("Couldn't reverse the (lon, lat) to an address: {e}"))?
-                    .ok_or(eyre::eyre!("Could not find location"))?;
+        let mut json = match self
+            .request(&url, options, None)
+            .and_then(|r| r.json(*self))
+        {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::debug!(error = %e, "The current conditions couldn't be fetched either");
 
-                lat_lon
+                return None;
             }
         };
+        json.remove("hourly");
+        json.remove("hourly_units");
 
-        // Add the latitude and longitude to the parameters list
-        self.params
-            .push(format!("{}={}", self.provider.lat_param(), lat_lon.0));
-        self.params
-            .push(format!("{}={}", self.provider.lon_param(), lat_lon.1));
+        Some(json)
+    }
 
-        Ok(self)
+    /// Check the capabilities the request needs, apart from the dates (the request builder checks
+    /// them itself)
+    fn can_serve(&self, options: &RequestOptions) -> bool {
+        let capabilities = self.capabilities();
+
+        (!capabilities.requires_api_key || options.api_key.is_some())
+            && (!options.air_quality || capabilities.air_quality)
+            && (!options.nowcast || capabilities.nowcast)
+            && (!options.compare_yesterday || capabilities.supports_custom_dates)
     }
 
-    /// Set the date
-    fn date(mut self, date: String) -> eyre::Result<Self> {
-        // Parse the date string to local NaiveDateTime and check if it refers to "now" or not
-        let (date_time, now) = match date.as_str() {
-            "now" => (chrono::Utc::now().naive_local(), true),
-            _ => {
-                let parsed_date = dateparser::parse(&date)
-                    .map_err(|e| eyre::eyre!("Couldn't parse the date: {e}"))?;
+    /// Get the weather data for a given location and a date from this provider only
+    fn fetch(
+        &self,
+        location: Location,
+        date: String,
+        options: &RequestOptions,
+    ) -> Result<WeatherData> {
+        // Don't bother with geocoding if the request can't be made anyway
+        if self.requires_api_key() && options.api_key.is_none() {
+            return Err(WeatherError::MissingApiKey { provider: *self });
+        }
+
+        if options.air_quality && !self.capabilities().air_quality {
+            return Err(WeatherError::Unsupported(format!(
+                "Air quality is not supported by {self} provider"
+            )));
+        }
+
+        if options.nowcast && !self.capabilities().nowcast {
+            return Err(WeatherError::Unsupported(format!(
+                "Nowcast is not supported by {self} provider"
+            )));
+        }
+
+        // It's only ever for the next minutes
+        if options.nowcast && date != "now" {
+            return Err(WeatherError::InvalidInput(
+                "The nowcast is only for the next minutes, drop the date to get it".to_string(),
+            ));
+        }
+
+        // Yesterday is a custom date even if today is "now"
+        if options.compare_yesterday && !self.capabilities().supports_custom_dates {
+            return Err(WeatherError::Unsupported(format!(
+                "Comparing with yesterday is not supported by {self} provider"
+            )));
+        }
+
+        // Create the request builder and set the location
+        let mut request_builder = ProviderRequestBuilder::new(*self)
+            .options(options.clone())
+            .location(location)?;
 
-                (parsed_date.naive_local(), false)
+        // Set the date (it checks itself which dates the provider supports)
+        request_builder = request_builder.date(date.clone())?;
+
+        // Build and execute the request
+        let ProviderRequest {
+            url,
+            request_type,
+            requested_date,
+            requested_time,
+            requested_range,
+            location,
+            caveat,
+            air_quality_url,
+            nowcast_url,
+        } = request_builder.build()?;
+
+        // Get the json data from the provider, or from the cache if it was asked for or if the
+        // provider can't be reached
+        let cache = ResponseCache::new(&options.paths);
+        let key = response_key(
+            *self,
+            &location,
+            &requested_date,
+            requested_range,
+            options.daily,
+            options.elevation,
+        );
+        let cached = cache.get(&key);
+
+        // The body is written out before it's parsed, so that the responses that can't be parsed
+        // can be looked into too
+        let dump = |body: String| match &options.dump_response {
+            Some(path) => ResponseDump {
+                provider: *self,
+                url: redacted_url(url.as_str()),
+                time: options.now,
+                location: location.clone(),
+                elevation: options.elevation,
+                date: date.clone(),
+                daily: options.daily,
+                body,
             }
+            .write(path),
+            None => Ok(()),
         };
 
-        // Save the date as a string with the specific format used in UI
-        self.requested_date = date_time.format("%Y-%m-%d").to_string();
+        // Only the fresh responses say it
+        let mut deprecated = false;
+        // Why only the current conditions are there, if the full request failed
+        let mut hourly_error = None;
 
-        // Set the request type based on the date
-        self.request_type = match now {
-            // If it's "now", it's a forecast
-            true => ProviderRequestType::Forecast,
-            false => match date_time < chrono::Utc::now().naive_local() {
-                // If it's before "now", it's a history
-                true => ProviderRequestType::History,
-                // If it's after "now", it's a forecast
-                false => ProviderRequestType::Forecast,
+        // The earlier response to the same request is kept for the hours the new one doesn't have
+        let (json, cached_at, fetched_at, earlier) = match (&options.replay, options.cached, cached)
+        {
+            // Nothing is fetched for a replayed response, not even the alerts
+            (Some(replay), ..) => {
+                let json = serde_json::from_str(&replay.body).map_err(|_| {
+                    WeatherError::InvalidResponse(format!(
+                        "The {self} response in the dump is not JSON: {}",
+                        body_excerpt(&replay.body)
+                    ))
+                })?;
+
+                (json, None, replay.time, None)
+            }
+            (None, true, cached) => {
+                let cached = cached.ok_or(WeatherError::NotCached(format!(
+                    "No cached {self} data for {} on {requested_date}, drop --cached to fetch it",
+                    location.display_name
+                )))?;
+
+                dump(serde_json::to_string(&cached.json)?)?;
+
+                (
+                    cached.json,
+                    Some(cached.fetched_at),
+                    cached.fetched_at,
+                    None,
+                )
+            }
+            // met_no asks not to repeat the requests until the data they got expires, there is
+            // nothing new before that anyway
+            (None, false, Some(cached))
+                if cached.expires.is_some_and(|expires| expires > options.now) =>
+            {
+                dump(serde_json::to_string(&cached.json)?)?;
+
+                (cached.json, None, cached.fetched_at, None)
+            }
+            (None, false, cached) => match self.request(&url, options, cached.as_ref()) {
+                Ok(response) => {
+                    deprecated = response.deprecated;
+                    let json = response.json(*self);
+                    dump(response.body)?;
+                    let json = json?;
+
+                    cache.insert(
+                        &key,
+                        &CachedResponse {
+                            provider: *self,
+                            address: location.display_name.clone(),
+                            lat: location.lat,
+                            lon: location.lon,
+                            fetched_at: options.now,
+                            last_modified: response.last_modified,
+                            expires: response.expires,
+                            json: json.clone(),
+                        },
+                    );
+
+                    (json, None, options.now, cached)
+                }
+                // Old data is better than no data, as long as it's not too old
+                Err(e) if e.category() == ErrorCategory::Network => {
+                    let max_age =
+                        chrono::Duration::hours(options.network.cache_max_age_hours as i64);
+
+                    match cache.get(&key) {
+                        Some(cached) if options.now - cached.fetched_at <= max_age => {
+                            dump(serde_json::to_string(&cached.json)?)?;
+
+                            (
+                                cached.json,
+                                Some(cached.fetched_at),
+                                cached.fetched_at,
+                                None,
+                            )
+                        }
+                        _ => return Err(e),
+                    }
+                }
+                // The current conditions alone are better than nothing, if the lighter request for
+                // them still works
+                Err(e) if e.category() == ErrorCategory::ProviderResponse && date == "now" => {
+                    match self.current_only(&location, options) {
+                        Some(json) => {
+                            hourly_error = Some(e);
+
+                            (json, None, options.now, None)
+                        }
+                        None => return Err(e),
+                    }
+                }
+                Err(e) => return Err(e),
             },
         };
 
-        // Check which provider is being used
-        match self.provider {
-            Provider::OpenMeteo => {
-                // Construct the date string
-                let date_str = date_time.format(self.provider.date_format()?).to_string();
+        // Parse the json data to WeatherData struct
+        let mut data = WeatherData::from_json(
+            &json,
+            *self,
+            request_type,
+            requested_date,
+            requested_range,
+            location,
+            (options.daily, options.time_format),
+        )?;
+        if let Some(e) = hourly_error {
+            data.caveat = Some(format!(
+                "Hourly data is not available ({e}), showing the current conditions only"
+            ));
+        }
+        // A degradation of its own, so that the one of the response (if any) doesn't replace it
+        if let Some(caveat) = caveat {
+            data.degradations.push(Degradation::Caveat(caveat));
+        }
+        if data.location.display_name == UNKNOWN_ADDRESS {
+            data.degradations.push(Degradation::UnknownAddress);
+        }
+        if deprecated {
+            data.degradations.push(Degradation::Caveat(format!(
+                "{self} reports that this version of its API is deprecated, the requests may stop \
+                working soon"
+            )));
+        }
+        data.cached_at = cached_at;
+        data.fetched_at = fetched_at;
+        // All of the stale data is from the cache, the fresh one only has the earlier hours from it
+        if let Some(cached_at) = cached_at {
+            data.sources = vec![
+                DataSource::Cached {
+                    age: options.now - cached_at
+                };
+                data.timestamps.len()
+            ];
+        }
+        if let Some(earlier) = earlier.filter(|earlier| earlier.fetched_at < fetched_at) {
+            let parsed = WeatherData::from_json(
+                &earlier.json,
+                *self,
+                data.request_type.clone(),
+                data.requested_date.clone(),
+                requested_range,
+                data.location.clone(),
+                (options.daily, options.time_format),
+            );
 
-                // Add the appropriate parameters to the request
-                self.params.push(format!("start_date={}", date_str));
-                self.params.push(format!("end_date={}", date_str));
-            }
-            Provider::MetNo => {
-                // If it's met_no provider and the date is still somehow custom, throw an error
-                if !now {
-                    return Err(eyre::eyre!(
-                        "Custom dates (including history) are not supported by met_no provider"
-                    ));
-                }
+            // The earlier response is only an extra, it's just left out if it can't be parsed (but
+            // not if its units can't be converted, the series would be wrong otherwise)
+            if let Ok(parsed) = parsed {
+                data.merge_cached(parsed, fetched_at - earlier.fetched_at)?;
             }
         }
+        // The hour asked for stands out on the chart, or the current one if there wasn't any
+        data.highlight_hour = match requested_time {
+            Some(time) => data.hour_index(time),
+            None => data.current_hour,
+        };
+        // Nothing to fetch for these, so they are there for the cached data too
+        if options.astro {
+            data.astro = AstroData::new(&data);
+        }
 
-        Ok(self)
-    }
+        // The extras that couldn't be fetched are told about along with the weather
+        let missing = |extra, e: WeatherError| Degradation::MissingExtra {
+            extra,
+            reason: Some(e.to_string()),
+        };
 
-    /// Build the request string and return the relevant data collected during configuration phase
-    fn build(mut self) -> eyre::Result<(String, ProviderRequestType, String, String)> {
-        // Check which provider is being used to add additional parameters in case they are needed
-        match self.provider {
-            Provider::OpenMeteo => {
-                // If it's open_meteo and the request type is forecast, it means that we can also
-                // ask for current weather conditions from the endpoint
-                if matches!(self.request_type, ProviderRequestType::Forecast) {
-                    self.params.push("current_weather=true".to_string());
+        // The alerts are only an extra, so the weather is shown without them if they can't be
+        // fetched (and the stale data has none, they might not be in effect anymore)
+        if cached_at.is_none() && options.replay.is_none() {
+            data.alerts = match self.alerts(&data.location, options) {
+                Ok(alerts) => alerts,
+                Err(e) => {
+                    data.degradations.push(missing(Extra::Alerts, e));
+
+                    Vec::new()
                 }
+            };
+
+            // Same as the alerts, the weather is still worth showing without it
+            if let Some(url) = air_quality_url {
+                data.air_quality = match self.air_quality(url, &data, options) {
+                    Ok(air_quality) => air_quality,
+                    Err(e) => {
+                        data.degradations.push(missing(Extra::AirQuality, e));
 
-                // Add the parameter to the get hourly forecast
-                self.params.push("hourly=temperature_2m".to_string());
+                        None
+                    }
+                };
+            }
+
+            if let Some(url) = nowcast_url {
+                data.nowcast = match self.nowcast(url, &data, options) {
+                    Ok(nowcast) => nowcast,
+                    Err(e) => {
+                        data.degradations.push(missing(Extra::Nowcast, e));
+
+                        None
+                    }
+                };
+            }
+        } else {
+            for (asked, extra) in [
+                (options.air_quality, Extra::AirQuality),
+                (options.nowcast, Extra::Nowcast),
+            ] {
+                if asked {
+                    data.degradations.push(Degradation::MissingExtra {
+                        extra,
+                        reason: None,
+                    });
+                }
             }
-            Provider::MetNo => {}
         }
 
-        // Construct the request string
-        let request_str = format!(
-            "{}/{}?{}",
-            self.provider.base_url(),
-            self.request_type.to_string(&self.provider)?,
-            self.params.join("&")
+        // Only open_meteo can be asked for a specific unit, so convert the data from the rest
+        let mut data = data.convert_temperatures(options.unit);
+
+        // The comparison is only an extra too, and a replayed response has nothing to compare with
+        if options.compare_yesterday && options.replay.is_none() {
+            data.comparison = match self.yesterday(&data, options) {
+                // It's compared with the day series by series, so they have to be in the same units
+                Ok(mut yesterday) => {
+                    let converted = yesterday.convert_units(&data.units())?;
+                    data.degradations.extend(converted);
+
+                    Some(Box::new(yesterday))
+                }
+                Err(e) => {
+                    data.degradations.push(missing(Extra::Yesterday, e));
+
+                    None
+                }
+            };
+        }
+
+        Ok(data)
+    }
+
+    /// Get the weather for the same location on the day before the data (the endpoint is picked
+    /// the same way as for any other date, the forecast one still has the last few days)
+    fn yesterday(&self, data: &WeatherData, options: &RequestOptions) -> Result<WeatherData> {
+        if data.requested_range.is_some() {
+            return Err(WeatherError::InvalidInput(
+                "only a single day can be compared with the day before".to_string(),
+            ));
+        }
+
+        let day = NaiveDate::parse_from_str(&data.requested_date, "%Y-%m-%d")
+            .map_err(|_| {
+                WeatherError::InvalidDate(format!(
+                    "Couldn't parse the requested date {}",
+                    data.requested_date
+                ))
+            })?
+            .pred_opt()
+            .ok_or(WeatherError::InvalidDate(
+                "There is no day before the requested one".to_string(),
+            ))?;
+
+        // Only the weather itself, the extras and the dump are for the requested day
+        let options = RequestOptions {
+            compare_yesterday: false,
+            air_quality: false,
+            astro: false,
+            nowcast: false,
+            dump_response: None,
+            ..options.clone()
+        };
+
+        self.fetch(
+            Location::Resolved(data.location.clone()),
+            day.format("%Y-%m-%d").to_string(),
+            &options,
+        )
+    }
+
+    /// Get the weather alerts for the location that haven't expired yet (only met_no has them, the
+    /// rest of the providers have none)
+    fn alerts(
+        &self,
+        location: &ResolvedLocation,
+        options: &RequestOptions,
+    ) -> Result<Vec<WeatherAlert>> {
+        let Some(url) = self.implementation().alerts_url(location) else {
+            return Ok(Vec::new());
+        };
+        let response = self.request(&url, options, None)?;
+
+        Ok(self
+            .implementation()
+            .parse_alerts(&response.json(*self)?)?
+            .into_iter()
+            .filter(|alert| alert.expires.is_none_or(|expires| expires > options.now))
+            .collect())
+    }
+
+    /// Get the air quality of the day the data is for
+    fn air_quality(
+        &self,
+        url: Url,
+        data: &WeatherData,
+        options: &RequestOptions,
+    ) -> Result<Option<AirQualityData>> {
+        let response = self.request(&url, options, None)?;
+
+        self.implementation()
+            .parse_air_quality(&response.json(*self)?, data)
+    }
+
+    /// Get the precipitation of the next minutes
+    fn nowcast(
+        &self,
+        url: Url,
+        data: &WeatherData,
+        options: &RequestOptions,
+    ) -> Result<Option<NowcastData>> {
+        let response = self.request(&url, options, None)?;
+
+        self.implementation()
+            .parse_nowcast(&response.json(*self)?, data)
+    }
+
+    /// Send the request and get the body of the successful response, with targeted errors for the
+    /// rest of them.
+    ///
+    /// If the response to the same request is cached with its `Last-Modified`, the request is a
+    /// conditional one, and the cached body is used if the provider says it's still the same (304)
+    #[tracing::instrument(skip_all, fields(provider = %self))]
+    fn request(
+        &self,
+        url: &Url,
+        options: &RequestOptions,
+        cached: Option<&CachedResponse>,
+    ) -> Result<ProviderResponse> {
+        let host = url.host_str().unwrap_or_default().to_string();
+        let now = options.now;
+
+        // Don't even try if the API told us to back off during one of the previous runs
+        let mut cooldowns = Cooldowns::load(&options.paths);
+        if let Some(until) = cooldowns.active(&host, now) {
+            return Err(cooldown_error(self, until));
+        }
+
+        let last_modified = cached.and_then(|cached| cached.last_modified.as_deref());
+
+        let client = &options.http;
+        let started = Instant::now();
+        // Straight from the recorded responses in development
+        let response = cassette::send(&options.network, now, || {
+            let request = self
+                .implementation()
+                .headers()
+                .iter()
+                .fold(client.get(url.clone()), |request, (name, value)| {
+                    request.header(*name, *value)
+                });
+
+            match last_modified {
+                Some(last_modified) => request.header(IF_MODIFIED_SINCE, last_modified),
+                None => request,
+            }
+        })?;
+        let status = response.status();
+
+        tracing::debug!(
+            url = %redacted_url(url.as_str()),
+            %status,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "Got the response"
         );
 
-        Ok((
-            request_str,
-            self.request_type,
-            self.requested_date,
-            self.address,
-        ))
+        // met.no sometimes answers with a 403 and a throttling message instead of a 429
+        if status == reqwest::StatusCode::FORBIDDEN {
+            let body = response.text()?;
+
+            return match body.to_lowercase().contains("throttl") {
+                true => {
+                    let until = now + chrono::Duration::hours(DEFAULT_COOLDOWN_HOURS);
+                    cooldowns.start(&host, until);
+
+                    Err(cooldown_error(self, until))
+                }
+                false => Err(WeatherError::Provider(format!(
+                    "{self} refused the request ({status}): {}",
+                    error_message(&body)
+                ))),
+            };
+        }
+
+        if let Some(until) = throttled_until(&response, now) {
+            cooldowns.start(&host, until);
+
+            return Err(cooldown_error(self, until));
+        }
+
+        cooldowns.clear(&host);
+
+        // When the data changes next and when it changed last, for the next requests
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let last_modified = header(LAST_MODIFIED);
+        let expires = header(EXPIRES)
+            .and_then(|expires| DateTime::parse_from_rfc2822(&expires).ok())
+            .map(|expires| expires.with_timezone(&Utc));
+
+        if let (reqwest::StatusCode::NOT_MODIFIED, Some(cached)) = (status, cached) {
+            return Ok(ProviderResponse {
+                status,
+                body: serde_json::to_string(&cached.json)?,
+                last_modified: last_modified.or(cached.last_modified.clone()),
+                expires,
+                deprecated: false,
+            });
+        }
+
+        // The errors (and the throttled requests) often come as plain text or HTML instead of JSON
+        let body = response.text()?;
+        tracing::trace!(body = %logged_body(&body), "Response body");
+
+        match (status.is_success(), status.is_server_error()) {
+            (true, _) => Ok(ProviderResponse {
+                status,
+                body,
+                last_modified,
+                expires,
+                // met_no marks the deprecated versions of its products with a 203, the data is
+                // still fine until they are gone
+                deprecated: status == reqwest::StatusCode::NON_AUTHORITATIVE_INFORMATION,
+            }),
+            (false, true) => Err(WeatherError::Provider(format!(
+                "{self} is not available at the moment ({status}), please try again later: {}",
+                body_excerpt(&body)
+            ))),
+            (false, false) => Err(WeatherError::Provider(format!(
+                "{self} refused the request ({status}): {}",
+                error_message(&body)
+            ))),
+        }
+    }
+
+    /// What the provider can do
+    pub fn capabilities(&self) -> ProviderCapabilities {
+        self.implementation().capabilities()
+    }
+
+    /// API parameter format for date value
+    fn date_format(&self) -> Result<&'static str> {
+        match self.capabilities().supports_custom_dates {
+            true => Ok("%Y-%m-%d"),
+            false => Err(WeatherError::Unsupported(format!(
+                "{self} doesn't support custom dates"
+            ))),
+        }
+    }
+
+    /// Check if the provider can't be used without an API key
+    fn requires_api_key(&self) -> bool {
+        self.capabilities().requires_api_key
+    }
+
+    /// How many days after today the forecast reaches
+    pub fn max_forecast_days(&self) -> i64 {
+        self.capabilities().forecast_days
+    }
+
+    /// How many days before today the history ends (the archive is updated with a delay, and the
+    /// forecast endpoint still has the days in between), `None` if there is no history
+    pub fn history_cutoff(&self) -> Option<i64> {
+        self.capabilities().history_cutoff_days
+    }
+
+    /// Check which endpoint has the data for the days up to the date
+    fn request_type_for(&self, date: NaiveDate, today: NaiveDate) -> ProviderRequestType {
+        let cutoff = self.history_cutoff().unwrap_or_default();
+
+        match date < today - chrono::Duration::days(cutoff) {
+            true => ProviderRequestType::History,
+            false => ProviderRequestType::Forecast,
+        }
+    }
+
+    /// Make sure the forecast reaches the date, the provider would just return nothing otherwise
+    fn check_forecast_horizon(&self, date: NaiveDate, today: NaiveDate) -> Result<()> {
+        let days = (date - today).num_days();
+        let max_days = self.max_forecast_days();
+
+        match days > max_days {
+            true => Err(WeatherError::Unsupported(format!(
+                "{self} can only forecast up to {max_days} days ahead; you asked for {date} ({days} days)"
+            ))),
+            false => Ok(()),
+        }
+    }
+}
+
+/// What a provider can do (`weather providers` lists them)
+#[derive(Debug, Clone, serde::Serialize)]
+#[non_exhaustive]
+pub struct ProviderCapabilities {
+    pub provider: Provider,
+    /// Dates in the past
+    pub supports_history: bool,
+    /// Specific dates instead of "now" and the ranges of the next few days
+    pub supports_custom_dates: bool,
+    /// How many days after today the forecast reaches
+    pub forecast_days: i64,
+    /// How many days before today the history ends, if there is history
+    pub history_cutoff_days: Option<i64>,
+    pub requires_api_key: bool,
+    /// Whether the current conditions are returned along with the forecast
+    pub current_conditions: bool,
+    /// Whether the air quality can be requested along with the weather
+    pub air_quality: bool,
+    /// Whether the precipitation of the next minutes can be requested along with the weather
+    pub nowcast: bool,
+    /// Query parameter with the language of the texts in the response, `None` if they are only in
+    /// English
+    pub language_param: Option<&'static str>,
+    /// Terms of use, including how to attribute the data
+    pub terms_url: &'static str,
+}
+
+impl ProviderCapabilities {
+    /// Table of what the providers can do, a row each (`weather providers`)
+    pub fn table(capabilities: &[Self]) -> String {
+        let yes_no = |value: bool| match value {
+            true => "yes",
+            false => "no",
+        };
+
+        let header = format!(
+            "{:<18} {:<8} {:<13} {:<14} {:<8} {:<8} {:<12} {:<8} {:<9} Terms\n",
+            "Provider",
+            "History",
+            "Custom dates",
+            "Forecast days",
+            "API key",
+            "Current",
+            "Air quality",
+            "Nowcast",
+            "Language"
+        );
+        let rows = capabilities.iter().map(|c| {
+            format!(
+                "{:<18} {:<8} {:<13} {:<14} {:<8} {:<8} {:<12} {:<8} {:<9} {}\n",
+                c.provider.to_string(),
+                yes_no(c.supports_history),
+                yes_no(c.supports_custom_dates),
+                c.forecast_days,
+                match c.requires_api_key {
+                    true => "required",
+                    false => "no",
+                },
+                yes_no(c.current_conditions),
+                yes_no(c.air_quality),
+                yes_no(c.nowcast),
+                yes_no(c.language_param.is_some()),
+                c.terms_url
+            )
+        });
+
+        std::iter::once(header).chain(rows).collect()
+    }
+}
+
+/// Per-invocation options that affect how the request is built
+#[derive(Default, Debug, Clone)]
+pub(crate) struct RequestOptions {
+    /// Wrap longitudes outside of [-180, 180] around the globe instead of rejecting them
+    pub(crate) normalize_lon: bool,
+    /// Resolve dates without a year to their last occurrence instead of the next one
+    pub(crate) past: bool,
+    /// Where to keep the state between runs
+    pub(crate) paths: Paths,
+    /// Current time, captured once at startup so that every decision during the run agrees on it
+    pub(crate) now: DateTime<Utc>,
+    /// API key for the providers that need one
+    pub(crate) api_key: Option<String>,
+    /// Unit to show the temperatures in
+    pub(crate) unit: TemperatureUnit,
+    /// Clock to label the hours in
+    pub(crate) time_format: TimeFormat,
+    /// Summarize the temperatures per day
+    pub(crate) daily: bool,
+    /// Timeouts and retries of the requests
+    pub(crate) network: NetworkSettings,
+    /// Client for all the requests, created from the network settings
+    pub(crate) http: reqwest::blocking::Client,
+    /// Only use the cached responses, without any requests to the provider
+    pub(crate) cached: bool,
+    /// Ask which place was meant if the address matches several of them
+    pub(crate) choose_place: bool,
+    /// Backend for the geocoding (the one of `geocoder_backend` if it's not set)
+    pub(crate) geocoder: Option<Arc<dyn Geocoder>>,
+    /// Which of the built-in geocoders to use
+    pub(crate) geocoder_backend: GeocoderBackend,
+    /// Only accept "lat, lon" pairs and don't look up their addresses
+    pub(crate) no_geocode: bool,
+    /// Language of the addresses and of the texts of the responses, `None` for the one of the locale
+    pub(crate) language: Option<Language>,
+    /// Elevation of the location in m, instead of the one the provider guesses from its grid
+    pub(crate) elevation: Option<f64>,
+    /// File to write the provider responses to, with what they were requested for
+    pub(crate) dump_response: Option<PathBuf>,
+    /// Response written by `dump_response` before, parsed instead of fetching anything
+    pub(crate) replay: Option<Arc<ResponseDump>>,
+    /// Fetch the air quality along with the weather
+    pub(crate) air_quality: bool,
+    /// Calculate the moon phase and the length of the day
+    pub(crate) astro: bool,
+    /// Fetch the precipitation of the next minutes along with the weather
+    pub(crate) nowcast: bool,
+    /// Fetch the day before the requested one too, to compare them
+    pub(crate) compare_yesterday: bool,
+    /// Provider to turn to when this one can't serve the request
+    pub(crate) fallback: Option<Fallback>,
+    /// API keys of the providers to fall back to
+    pub(crate) fallback_api_keys: HashMap<Provider, String>,
+}
+
+#[derive(Default, Debug, Clone)]
+pub enum ProviderRequestType {
+    #[default]
+    Forecast,
+    History,
+}
+
+/// Everything collected by the request builder
+struct ProviderRequest {
+    url: Url,
+    request_type: ProviderRequestType,
+    requested_date: String,
+    requested_time: Option<NaiveDateTime>,
+    requested_range: Option<DateRange>,
+    location: ResolvedLocation,
+    caveat: Option<String>,
+    /// Only if the air quality was asked for
+    air_quality_url: Option<Url>,
+    /// Only if the nowcast was asked for
+    nowcast_url: Option<Url>,
+}
+
+struct ProviderRequestBuilder {
+    provider: Provider,
+    requested_date: String,
+    /// Date and time requested (if the date had the time of the day in it)
+    requested_time: Option<NaiveDateTime>,
+    /// Whole days requested (if the date was a range)
+    requested_range: Option<DateRange>,
+    /// Where the weather is requested for (resolved once from the address)
+    location: ResolvedLocation,
+    /// Parameters that are added to the request URL
+    params: QueryParams,
+    request_type: ProviderRequestType,
+    options: RequestOptions,
+    /// Warning about the quality of the requested data
+    caveat: Option<String>,
+}
+
+impl ProviderRequestBuilder {
+    fn new(provider: Provider) -> Self {
+        Self {
+            provider,
+            requested_date: String::new(),
+            requested_time: None,
+            requested_range: None,
+            location: ResolvedLocation::default(),
+            params: Vec::new(),
+            request_type: ProviderRequestType::Forecast,
+            options: RequestOptions::default(),
+            caveat: None,
+        }
+    }
+
+    /// Set the per-invocation options
+    fn options(mut self, options: RequestOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Set the location (resolving the address if needed)
+    #[tracing::instrument(skip_all, fields(provider = %self.provider))]
+    fn location(mut self, location: Location) -> Result<Self> {
+        self.location = match location {
+            Location::Address(address) => resolve_location(&address, &self.options)?,
+            Location::Resolved(location) => location,
+        };
+        tracing::debug!(
+            lat = self.location.lat,
+            lon = self.location.lon,
+            name = %self.location.display_name,
+            "Resolved the location"
+        );
+
+        // Add the latitude and longitude to the parameters list
+        let (lat_param, lon_param) = self.provider.implementation().coordinate_params();
+        self.params
+            .push((lat_param, request_coordinate(self.location.lat)));
+        self.params
+            .push((lon_param, request_coordinate(self.location.lon)));
+
+        // The elevation of the grid cell can be hundreds of meters off in the mountains, and the
+        // temperatures with it
+        if let Some(elevation) = self.options.elevation {
+            if !ELEVATION_RANGE.contains(&elevation) {
+                return Err(WeatherError::InvalidInput(format!(
+                    "Invalid elevation {elevation} m, it should be between {} and {} m",
+                    ELEVATION_RANGE.start(),
+                    ELEVATION_RANGE.end()
+                )));
+            }
+
+            // met_no only takes whole meters
+            if let Some(param) = self.provider.implementation().elevation_param() {
+                self.params.push((param, elevation.round().to_string()));
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Set the date
+    fn date(mut self, date: String) -> Result<Self> {
+        let now = self.options.now.naive_local();
+
+        // Ranges cover whole days, so they are handled separately
+        if let Some(range) = self.parse_date_range(&date, now)? {
+            return self.date_range(range, now);
+        }
+
+        // Parse the date string to local NaiveDateTime and check if it refers to "now" or not
+        let (date_time, is_now) = match date.as_str() {
+            "now" => (now, true),
+            _ => (self.parse_date(&date, now)?, false),
+        };
+
+        // Save the date as a string with the specific format used in UI
+        self.requested_date = date_time.format("%Y-%m-%d").to_string();
+        // The days alone get the current time from dateparser, which isn't what was asked for
+        self.requested_time = (!is_now && input_has_time(&date)).then_some(date_time);
+
+        // Set the request type based on the date
+        self.request_type = match is_now {
+            // If it's "now", it's a forecast
+            true => ProviderRequestType::Forecast,
+            // Otherwise it depends on which endpoint has the date (the forecast one still has the
+            // last few days the archive doesn't have yet)
+            false => self.provider.request_type_for(date_time.date(), now.date()),
+        };
+
+        // Check if the provider supports anything but "now"
+        match self.provider.capabilities().supports_custom_dates {
+            true => {
+                // Construct the date string
+                let date_str = date_time.format(self.provider.date_format()?).to_string();
+
+                // Add the appropriate parameters to the request
+                self.params.push(("start_date", date_str.clone()));
+                self.params.push(("end_date", date_str));
+
+                match self.request_type {
+                    ProviderRequestType::Forecast => self
+                        .provider
+                        .check_forecast_horizon(date_time.date(), now.date())?,
+                    // Old archive data is a lot less reliable, so let the user know
+                    ProviderRequestType::History => {
+                        self.caveat =
+                            archive_caveat(date_time.date(), self.location.lat, self.location.lon)
+                    }
+                }
+            }
+            false => {
+                // If the date is still somehow custom, throw an error
+                if !is_now {
+                    return Err(WeatherError::Unsupported(format!(
+                        "Custom dates (including history) are not supported by {} provider",
+                        self.provider
+                    )));
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Check that the date (or the range) parses, without setting anything
+    fn check_date(&self, date: &str) -> Result<()> {
+        let now = self.options.now.naive_local();
+
+        match date == "now" || self.parse_date_range(date, now)?.is_some() {
+            true => Ok(()),
+            false => self.parse_date(date, now).map(|_| ()),
+        }
+    }
+
+    /// Parse a single date (anything but "now")
+    fn parse_date(&self, date: &str, now: NaiveDateTime) -> Result<NaiveDateTime> {
+        let past = self.options.past;
+
+        // The days relative to today ("tomorrow", "friday") are not understood by dateparser either
+        if let Some(date) = parse_relative_date(date, now.date()) {
+            return Ok(date.and_time(now.time()));
+        }
+
+        let date_time = match parse_partial_date(date, locale_is_month_first()) {
+            // Dates without a year ("June 5", "05.06") are not understood by dateparser (or worse,
+            // end up in year 5), so pick the nearest occurrence ourselves
+            Some((month, day)) => nearest_occurrence(month, day, now.date(), past)
+                .ok_or(WeatherError::InvalidDate(format!(
+                    "Couldn't parse the date: {date} doesn't exist"
+                )))?
+                .and_time(now.time()),
+            None => {
+                let parsed_date = dateparser::parse(date)
+                    .map_err(|e| {
+                        WeatherError::InvalidDate(format!("Couldn't parse the date: {e}"))
+                    })?
+                    .naive_local();
+
+                // dateparser fills in the year on its own if it's missing, which might not be the
+                // one the user had in mind
+                match input_has_year(date) {
+                    true => parsed_date,
+                    false => {
+                        nearest_occurrence(parsed_date.month(), parsed_date.day(), now.date(), past)
+                            .map(|date| date.and_time(parsed_date.time()))
+                            .unwrap_or(parsed_date)
+                    }
+                }
+            }
+        };
+
+        Ok(date_time)
+    }
+
+    /// Parse the date as a range ("2024-01-01..2024-01-07", "next 3 days"), if it is one
+    fn parse_date_range(&self, date: &str, now: NaiveDateTime) -> Result<Option<DateRange>> {
+        if let Some(range) = parse_relative_range(date, now.date()) {
+            return range.map(Some);
+        }
+
+        let Some((start, end)) = split_range(date) else {
+            return Ok(None);
+        };
+
+        let range = DateRange {
+            start: self.parse_date(start, now)?.date(),
+            end: self.parse_date(end, now)?.date(),
+        };
+
+        match range.start <= range.end {
+            true => Ok(Some(range)),
+            false => Err(WeatherError::InvalidDate(format!(
+                "Invalid date range: {start} is after {end}"
+            ))),
+        }
+    }
+
+    /// Set the range of whole days
+    fn date_range(mut self, range: DateRange, now: NaiveDateTime) -> Result<Self> {
+        self.requested_date = range.start.format("%Y-%m-%d").to_string();
+        self.requested_range = Some(range);
+
+        // Only the ranges completely in the archive are taken from it, the forecast endpoints
+        // still have the data for the past few days
+        self.request_type = self.provider.request_type_for(range.end, now.date());
+
+        if matches!(self.request_type, ProviderRequestType::Forecast) {
+            self.provider
+                .check_forecast_horizon(range.end, now.date())?;
+        }
+
+        let capabilities = self.provider.capabilities();
+
+        if matches!(self.request_type, ProviderRequestType::History)
+            && !capabilities.supports_history
+        {
+            return Err(WeatherError::Unsupported(format!(
+                "History is not supported by {} provider",
+                self.provider
+            )));
+        }
+
+        // The rest always return the next few days, the requested ones are picked out of the
+        // response while parsing
+        if capabilities.supports_custom_dates {
+            let date_format = self.provider.date_format()?;
+
+            self.params
+                .push(("start_date", range.start.format(date_format).to_string()));
+            self.params
+                .push(("end_date", range.end.format(date_format).to_string()));
+
+            // Old archive data is a lot less reliable, so let the user know
+            if matches!(self.request_type, ProviderRequestType::History) {
+                self.caveat = archive_caveat(range.start, self.location.lat, self.location.lon);
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Build the request URL and return the relevant data collected during configuration phase
+    fn build(self) -> Result<ProviderRequest> {
+        // The air quality is for the same location and days, so it takes the same parameters
+        let air_quality_url = match self.options.air_quality {
+            true => self.provider.implementation().air_quality_url(&self.params),
+            false => None,
+        };
+        // So is the nowcast (of the next minutes only)
+        let nowcast_url = match self.options.nowcast {
+            true => self.provider.implementation().nowcast_url(&self.params),
+            false => None,
+        };
+
+        // The texts of the response in the language, if the provider has them in more than one
+        let mut params = self.params;
+        if let Some(param) = self.provider.capabilities().language_param {
+            let language = self.options.language.unwrap_or_else(Language::from_locale);
+            params.push((param, language.name().to_string()));
+        }
+
+        // The rest of the parameters and the endpoint depend on the provider
+        let url = self.provider.implementation().build_request(
+            &self.request_type,
+            params,
+            &self.options,
+        )?;
+        tracing::debug!(provider = %self.provider, url = %redacted_url(url.as_str()), "Built the request");
+
+        Ok(ProviderRequest {
+            url,
+            request_type: self.request_type,
+            requested_date: self.requested_date,
+            requested_time: self.requested_time,
+            requested_range: self.requested_range,
+            location: self.location,
+            caveat: self.caveat,
+            air_quality_url,
+            nowcast_url,
+        })
+    }
+}
+
+/// Requested date of the cached responses to the lighter requests of the current conditions
+const CURRENT_REQUEST: &str = "current";
+
+/// Name of the location given as coordinates that don't resolve to an address
+const UNKNOWN_ADDRESS: &str = "Unknown address";
+
+/// Resolve the address or the "lat, lon" pair typed by the user into the coordinates and the name to
+/// show
+pub(crate) fn resolve_location(
+    address: &str,
+    options: &RequestOptions,
+) -> Result<ResolvedLocation> {
+    // Coordinates are used as they are, anything else is an address to geocode
+    let maybe_lat_lon = match parse_coordinates(address) {
+        Some((lat, lon)) => {
+            if !(-90.0..=90.0).contains(&lat) {
+                return Err(WeatherError::InvalidLocation(format!(
+                    "Latitude must be between -90 and 90, got {lat} (from \"{address}\")"
+                )));
+            }
+
+            let lon = match options.normalize_lon {
+                true => normalize_longitude(lon),
+                false => lon,
+            };
+
+            if !(-180.0..=180.0).contains(&lon) {
+                return Err(WeatherError::InvalidLocation(format!(
+                    "Longitude must be between -180 and 180, got {lon} (from \"{address}\", it's \
+                    the same as {}, pass --normalize-lon to use that instead)",
+                    normalize_longitude(lon)
+                )));
+            }
+
+            // -180 and 180 are the same meridian, but providers snap them to different grid
+            // cells, so always use the latter
+            let lon = match lon == -180.0 {
+                true => 180.0,
+                false => lon,
+            };
+
+            Some((lat, lon))
+        }
+        None => None,
+    };
+
+    // Without geocoding the coordinates are all there is, and they name the location themselves
+    if options.no_geocode {
+        return match maybe_lat_lon {
+            Some((lat, lon)) => Ok(ResolvedLocation {
+                lat,
+                lon,
+                display_name: format!("{lat}, {lon}"),
+                short_name: None,
+                timezone: None,
+                country_code: None,
+            }),
+            None => Err(WeatherError::InvalidLocation(format!(
+                "Only \"lat, lon\" pairs can be used without geocoding, got \"{address}\""
+            ))),
+        };
+    }
+
+    let geocode_request = GeocodeRequest::new(options.language);
+    let mut geocode_cache = GeocodeCache::load(&options.paths, &geocode_request, options.now);
+    let geocoder = match &options.geocoder {
+        Some(geocoder) => geocoder.clone(),
+        None => {
+            let nominatim = Nominatim::new(geocode_request.clone(), options.http.clone());
+            let open_meteo = OpenMeteoGeocoder::new(geocode_request, options.http.clone());
+            let geocoder: Arc<dyn Geocoder> = match options.geocoder_backend {
+                GeocoderBackend::Osm => Arc::new(nominatim),
+                GeocoderBackend::OpenMeteo => Arc::new(open_meteo),
+                GeocoderBackend::Auto => Arc::new(FallbackGeocoder::new(nominatim, open_meteo)),
+            };
+
+            geocoder
+        }
+    };
+
+    let location = match maybe_lat_lon {
+        // If lat, lon were not provided as the address
+        None => {
+            // Try to get the place from the address (asking the geocoder only if we haven't
+            // already, so the place chosen once is remembered)
+            match geocode_cache.forward(address) {
+                Some(place) => place,
+                None => {
+                    let places = geocoder.forward(address)?;
+                    let count = places.len();
+
+                    // Let the user pick one if the address is ambiguous, the best match otherwise
+                    let place = match options.choose_place {
+                        true => choose_place(address, places)?,
+                        false => places.into_iter().next(),
+                    }
+                    .ok_or(WeatherError::Geocoding(format!(
+                        "Could not find location '{address}' ({count} geocoding results)"
+                    )))?;
+
+                    geocode_cache.insert_forward(address, &place);
+
+                    place
+                }
+            }
+        }
+        Some((lat, lon)) => {
+            // Search for and save the address that we get from coordinates provided (asking
+            // the geocoder only if we haven't already)
+            let address = match geocode_cache.reverse(lat, lon) {
+                Some(address) => Ok(Some(address)),
+                None => geocoder.reverse(lat, lon).inspect(|address| {
+                    if let Some(address) = address {
+                        geocode_cache.insert_reverse(lat, lon, address);
+                    }
+                }),
+            };
+
+            // Named after the coordinates or nothing at all, without the rest of an address
+            let unnamed = |full: String| Address {
+                full,
+                short: None,
+                country_code: None,
+            };
+            let address = match (address, is_polar_latitude(lat)) {
+                (Ok(Some(address)), _) => address,
+                // There is nothing to reverse geocode near the poles, so just show the
+                // coordinates themselves
+                (_, true) => unnamed(format!("{lat}, {lon}")),
+                // The coordinates are enough to get the weather, the address is only for show
+                // (the data says it has no address, the failure itself is only logged)
+                (Ok(None), false) => unnamed(UNKNOWN_ADDRESS.to_string()),
+                (Err(e), false) => {
+                    tracing::debug!(lat, lon, error = %e, "Reverse geocoding failed");
+
+                    unnamed(UNKNOWN_ADDRESS.to_string())
+                }
+            };
+
+            ResolvedLocation {
+                lat,
+                lon,
+                display_name: address.full,
+                short_name: address.short,
+                timezone: None,
+                country_code: address.country_code,
+            }
+        }
+    };
+
+    Ok(location)
+}
+
+/// Get the message out of the error response body (the providers put it in a JSON field)
+fn error_message(body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|json| {
+            ["reason", "message"]
+                .into_iter()
+                .find_map(|field| json.get(field)?.as_str().map(str::to_string))
+        })
+        .unwrap_or(body_excerpt(body))
+}
+
+/// Longest part of a response body shown in the errors
+const MAX_BODY_EXCERPT_CHARS: usize = 200;
+
+/// Beginning of the response body for the errors, without the HTML tags (the error pages of the
+/// proxies in front of the APIs are full of them)
+fn body_excerpt(body: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+
+    for c in body.chars() {
+        match (c, in_tag) {
+            ('<', _) => in_tag = true,
+            ('>', true) => {
+                in_tag = false;
+                text.push(' ');
+            }
+            (c, false) => text.push(c),
+            (_, true) => {}
+        }
+    }
+
+    let text = text.split_whitespace().join(" ");
+
+    match text.chars().count() > MAX_BODY_EXCERPT_CHARS {
+        true => format!(
+            "{}...",
+            text.chars()
+                .take(MAX_BODY_EXCERPT_CHARS)
+                .collect::<String>()
+        ),
+        false => text,
+    }
+}
+
+/// Longest response body written to the logs (with -vv), the hourly forecasts are huge
+const MAX_LOGGED_BODY_CHARS: usize = 2000;
+
+/// Beginning of the response body for the logs, as is
+pub(crate) fn logged_body(body: &str) -> String {
+    match body.chars().count() > MAX_LOGGED_BODY_CHARS {
+        true => format!(
+            "{}... ({} bytes)",
+            body.chars().take(MAX_LOGGED_BODY_CHARS).collect::<String>(),
+            body.len()
+        ),
+        false => body.to_string(),
+    }
+}
+
+/// The URL for the logs, without the open_weather_map API key
+fn redacted_url(url: &str) -> String {
+    url.split('&')
+        .map(|param| match param.split_once("appid=") {
+            Some((start, _)) => format!("{start}appid=<redacted>"),
+            None => param.to_string(),
+        })
+        .join("&")
+}
+
+/// Wrap the longitude around the globe into the (-180, 180] range
+fn normalize_longitude(lon: f64) -> f64 {
+    let lon = lon.rem_euclid(360.0);
+
+    match lon > 180.0 {
+        true => lon - 360.0,
+        false => lon,
+    }
+}
+
+/// Check if the latitude is too close to one of the poles for reverse geocoding to find anything
+/// (OSM data basically ends at the northern tip of Greenland and the Antarctic coast)
+fn is_polar_latitude(lat: f64) -> bool {
+    !(-80.0..=84.0).contains(&lat)
+}
+
+/// One of the reanalysis data sets the open_meteo archive is built from
+struct ArchiveSource {
+    name: &'static str,
+    /// Approximate grid resolution
+    resolution: &'static str,
+    /// First year with data
+    from_year: i32,
+    /// Last year with data (if the data set is not updated anymore)
+    to_year: Option<i32>,
+    /// Area covered by the data set as (min lat, max lat, min lon, max lon), whole globe if None
+    region: Option<(f64, f64, f64, f64)>,
+}
+
+impl ArchiveSource {
+    fn covers(&self, year: i32, lat: f64, lon: f64) -> bool {
+        let in_era = year >= self.from_year && self.to_year.is_none_or(|to_year| year <= to_year);
+        let in_region = self
+            .region
+            .is_none_or(|(min_lat, max_lat, min_lon, max_lon)| {
+                (min_lat..=max_lat).contains(&lat) && (min_lon..=max_lon).contains(&lon)
+            });
+
+        in_era && in_region
+    }
+
+    fn covers_location(&self, lat: f64, lon: f64) -> bool {
+        self.covers(self.to_year.unwrap_or(i32::MAX), lat, lon)
+    }
+}
+
+/// Data sources of the open_meteo archive, from the most to the least detailed one
+const ARCHIVE_SOURCES: [ArchiveSource; 3] = [
+    ArchiveSource {
+        name: "CERRA",
+        resolution: "5 km",
+        from_year: 1985,
+        to_year: Some(2021),
+        // Europe
+        region: Some((20.0, 75.0, -60.0, 75.0)),
+    },
+    ArchiveSource {
+        name: "ERA5-Land",
+        resolution: "11 km",
+        from_year: 1950,
+        to_year: None,
+        region: None,
+    },
+    ArchiveSource {
+        name: "ERA5",
+        resolution: "25 km",
+        from_year: 1940,
+        to_year: None,
+        region: None,
+    },
+];
+
+/// Reanalysis of the years before this one is based on a lot fewer observations
+const SPARSE_OBSERVATIONS_UNTIL_YEAR: i32 = 1959;
+
+/// The most detailed archive data source for the date and the location
+fn archive_source(date: NaiveDate, lat: f64, lon: f64) -> Option<&'static ArchiveSource> {
+    ARCHIVE_SOURCES
+        .iter()
+        .find(|source| source.covers(date.year(), lat, lon))
+}
+
+/// Warning about the archive data for the date and the location being less reliable than usual
+fn archive_caveat(date: NaiveDate, lat: f64, lon: f64) -> Option<String> {
+    let year = date.year();
+
+    let Some(source) = archive_source(date, lat, lon) else {
+        let first_year = ARCHIVE_SOURCES
+            .iter()
+            .map(|source| source.from_year)
+            .min()?;
+
+        return Some(format!(
+            "The archive starts in {first_year}, there is no data for {year}"
+        ));
+    };
+
+    // A regional data set covers the location, but not the date, so it's a lot coarser than what
+    // the same place gets in other years
+    let regional_source = ARCHIVE_SOURCES
+        .iter()
+        .find(|source| source.region.is_some() && source.covers_location(lat, lon));
+
+    match (year < SPARSE_OBSERVATIONS_UNTIL_YEAR, regional_source) {
+        (true, _) => Some(format!(
+            "Data for {year} comes from {} ({} grid) and is based on sparse observations, \
+            treat it as an estimate",
+            source.name, source.resolution
+        )),
+        (false, Some(regional)) if regional.name != source.name => Some(format!(
+            "Data for {year} comes from {} ({} grid), {} ({} grid) only covers {}-{}",
+            source.name,
+            source.resolution,
+            regional.name,
+            regional.resolution,
+            regional.from_year,
+            regional
+                .to_year
+                .map(|year| year.to_string())
+                .unwrap_or_default()
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data::WeatherCode, providers::open_meteo::OpenMeteo, test_server::TestServer};
+
+    /// Options for the requests with the state in a directory of their own, without retries
+    fn options(name: &str) -> RequestOptions {
+        let dir = std::env::temp_dir().join(format!("weather-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        RequestOptions {
+            paths: Paths {
+                config_dir: dir.clone(),
+                cache_dir: dir.clone(),
+                state_dir: dir,
+            },
+            now: Utc::now(),
+            network: NetworkSettings {
+                retries: 0,
+                ..NetworkSettings::default()
+            },
+            ..RequestOptions::default()
+        }
+    }
+
+    /// Request built for the location and the date, on a fixed day (June 14, 2024)
+    fn built_request(
+        provider: Provider,
+        (lat, lon): (f64, f64),
+        date: &str,
+    ) -> Result<ProviderRequest> {
+        let options = RequestOptions {
+            now: DateTime::parse_from_rfc3339("2024-06-14T12:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            api_key: Some("secret key&=?".to_string()),
+            language: Some(Language::Uk),
+            ..RequestOptions::default()
+        };
+        let location = ResolvedLocation {
+            lat,
+            lon,
+            display_name: "Somewhere, Earth & Co".to_string(),
+            short_name: None,
+            timezone: None,
+            country_code: None,
+        };
+
+        ProviderRequestBuilder::new(provider)
+            .options(options)
+            .location(Location::Resolved(location))?
+            .date(date.to_string())?
+            .build()
+    }
+
+    #[test]
+    fn request_urls() {
+        const OPEN_METEO_HOURLY: &str = "hourly=temperature_2m%2Cwindspeed_10m%2C\
+            winddirection_10m%2Cwindgusts_10m%2Cprecipitation%2Crelativehumidity_2m%2C\
+            apparent_temperature%2Cweathercode%2Csurface_pressure%2Ccloudcover";
+        let open_meteo_forecast = |coordinates: &str, days: &str| {
+            format!(
+                "https://api.open-meteo.com/v1/forecast?{coordinates}&{days}&\
+                current_weather=true&{OPEN_METEO_HOURLY}%2Cprecipitation_probability%2Cuv_index&\
+                daily=sunrise%2Csunset&timezone=auto"
+            )
+        };
+
+        for (provider, coordinates, date, expected_url, expected_date) in [
+            // Forecast for now
+            (
+                Provider::OpenMeteo,
+                (50.45, 30.5234),
+                "now",
+                open_meteo_forecast(
+                    "latitude=50.45&longitude=30.5234",
+                    "start_date=2024-06-14&end_date=2024-06-14",
+                ),
+                "2024-06-14",
+            ),
+            (
+                Provider::MetNo,
+                (50.45, 30.5234),
+                "now",
+                "https://api.met.no/weatherapi/locationforecast/2.0/complete?lat=50.45&\
+                lon=30.5234"
+                    .to_string(),
+                "2024-06-14",
+            ),
+            // The only one of them with the language of the texts
+            (
+                Provider::OpenWeatherMap,
+                (50.45, 30.5234),
+                "now",
+                "https://api.openweathermap.org/data/3.0/onecall?lat=50.45&lon=30.5234&\
+                lang=uk&units=metric&exclude=minutely%2Cdaily%2Calerts&appid=secret+key%26%3D%3F"
+                    .to_string(),
+                "2024-06-14",
+            ),
+            // Forecast for a day ahead
+            (
+                Provider::OpenMeteo,
+                (-33.8679, 151.2073),
+                "2024-06-20",
+                open_meteo_forecast(
+                    "latitude=-33.8679&longitude=151.2073",
+                    "start_date=2024-06-20&end_date=2024-06-20",
+                ),
+                "2024-06-20",
+            ),
+            // A few days at once, picked out of the response by the ones without the dates
+            (
+                Provider::OpenMeteo,
+                (1.0, 2.0),
+                "next 3 days",
+                open_meteo_forecast(
+                    "latitude=1&longitude=2",
+                    "start_date=2024-06-14&end_date=2024-06-16",
+                ),
+                "2024-06-14",
+            ),
+            (
+                Provider::MetNo,
+                (1.0, 2.0),
+                "next 3 days",
+                "https://api.met.no/weatherapi/locationforecast/2.0/complete?lat=1&lon=2"
+                    .to_string(),
+                "2024-06-14",
+            ),
+            // History from the archive
+            (
+                Provider::OpenMeteo,
+                (50.45, 30.52),
+                "2024-01-01",
+                format!(
+                    "https://archive-api.open-meteo.com/v1/archive?latitude=50.45&\
+                    longitude=30.52&start_date=2024-01-01&end_date=2024-01-01&\
+                    {OPEN_METEO_HOURLY}&timezone=auto"
+                ),
+                "2024-01-01",
+            ),
+            // The geocoded coordinates are rounded, and the tiny negative ones are not -0
+            (
+                Provider::OpenMeteo,
+                (50.450001234, -0.00001),
+                "now",
+                open_meteo_forecast(
+                    "latitude=50.45&longitude=0",
+                    "start_date=2024-06-14&end_date=2024-06-14",
+                ),
+                "2024-06-14",
+            ),
+            (
+                Provider::MetNo,
+                (59.91273456, 10.74609876),
+                "now",
+                "https://api.met.no/weatherapi/locationforecast/2.0/complete?lat=59.9127&\
+                lon=10.7461"
+                    .to_string(),
+                "2024-06-14",
+            ),
+        ] {
+            let request = built_request(provider, coordinates, date).unwrap();
+
+            assert_eq!(request.url.as_str(), expected_url, "{provider} {date}");
+            assert_eq!(request.requested_date, expected_date, "{provider} {date}");
+            assert_eq!(
+                matches!(request.request_type, ProviderRequestType::History),
+                date == "2024-01-01",
+                "{provider} {date}"
+            );
+            // Only the coordinates go to the provider, never the name of the place
+            assert!(!request.url.as_str().contains("Somewhere"));
+        }
+    }
+
+    #[test]
+    fn relative_dates_in_requests() {
+        for (date, requested_date, history) in [
+            // The forecast endpoint still has the last few days
+            ("yesterday", "2024-06-13", false),
+            ("tomorrow", "2024-06-15", false),
+            ("monday", "2024-06-17", false),
+            ("last friday", "2024-06-07", true),
+            ("5 days ago", "2024-06-09", false),
+            ("6 days ago", "2024-06-08", true),
+        ] {
+            let request = built_request(Provider::OpenMeteo, (50.45, 30.52), date).unwrap();
+
+            assert_eq!(request.requested_date, requested_date, "{date}");
+            assert_eq!(
+                matches!(request.request_type, ProviderRequestType::History),
+                history,
+                "{date}"
+            );
+            // Exactly like the absolute date
+            let absolute = built_request(Provider::OpenMeteo, (50.45, 30.52), requested_date);
+            assert_eq!(request.url, absolute.unwrap().url, "{date}");
+        }
+
+        // The rest is still dateparser's, with its errors
+        assert!(matches!(
+            built_request(Provider::OpenMeteo, (50.45, 30.52), "in three days"),
+            Err(WeatherError::InvalidDate(message)) if message.starts_with("Couldn't parse the date")
+        ));
+        // The providers without the custom dates can't take them either
+        assert!(matches!(
+            built_request(Provider::MetNo, (50.45, 30.52), "yesterday"),
+            Err(WeatherError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn history_range_url() {
+        let request = built_request(
+            Provider::OpenMeteo,
+            (50.45, 30.52),
+            "2024-01-01..2024-01-07",
+        );
+        let request = request.unwrap();
+        let url = &request.url;
+
+        // The archive has a host of its own
+        assert!(matches!(request.request_type, ProviderRequestType::History));
+        assert_eq!(
+            (url.scheme(), url.host_str(), url.path()),
+            ("https", Some("archive-api.open-meteo.com"), "/v1/archive")
+        );
+        let params = url.query_pairs().into_owned().collect::<HashMap<_, _>>();
+        for (name, value) in [
+            ("latitude", "50.45"),
+            ("longitude", "30.52"),
+            ("start_date", "2024-01-01"),
+            ("end_date", "2024-01-07"),
+            ("timezone", "auto"),
+        ] {
+            assert_eq!(params.get(name).map(String::as_str), Some(value), "{url}");
+        }
+        // Nothing the archive doesn't have
+        for name in ["current_weather", "daily"] {
+            assert!(!params.contains_key(name), "{url}");
+        }
+        assert!(!params["hourly"].contains("uv_index"), "{url}");
+        assert!(
+            !params["hourly"].contains("precipitation_probability"),
+            "{url}"
+        );
+    }
+
+    #[test]
+    fn requested_time_equal_to_now() {
+        // The requests are made at 2024-06-14 12:00, the very moment asked for is still forecast
+        let request = built_request(Provider::OpenMeteo, (50.45, 30.52), "2024-06-14 12:00");
+        let request = request.unwrap();
+
+        assert!(matches!(
+            request.request_type,
+            ProviderRequestType::Forecast
+        ));
+        assert_eq!(
+            request.requested_time,
+            NaiveDate::from_ymd_opt(2024, 6, 14)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+        );
+        assert_eq!(
+            request.url,
+            built_request(Provider::OpenMeteo, (50.45, 30.52), "now")
+                .unwrap()
+                .url
+        );
+    }
+
+    #[test]
+    fn request_urls_unsupported() {
+        for (provider, date) in [
+            (Provider::MetNo, "2024-06-20"),
+            (Provider::MetNo, "2024-01-01"),
+            (Provider::OpenWeatherMap, "2024-01-01"),
+            (Provider::OpenMeteo, "2024-08-01"),
+        ] {
+            assert!(
+                matches!(
+                    built_request(provider, (50.45, 30.52), date),
+                    Err(WeatherError::Unsupported(_))
+                ),
+                "{provider} {date}"
+            );
+        }
+    }
+
+    #[test]
+    fn forecast_horizon() {
+        // The last day of the open_meteo forecast is today (June 14) + 15, alone or at the end of
+        // a range
+        for date in ["2024-06-29", "2024-06-25..2024-06-29", "next 16 days"] {
+            assert!(
+                built_request(Provider::OpenMeteo, (50.45, 30.52), date).is_ok(),
+                "{date}"
+            );
+        }
+
+        for date in ["2024-06-30", "2024-06-25..2024-06-30", "next 17 days"] {
+            match built_request(Provider::OpenMeteo, (50.45, 30.52), date) {
+                Err(WeatherError::Unsupported(message)) => assert!(
+                    message.contains("up to 15 days ahead") && message.contains("2024-06-30"),
+                    "{date}: {message}"
+                ),
+                Err(e) => panic!("{date}: {e}"),
+                Ok(_) => panic!("{date} was accepted"),
+            }
+        }
+    }
+
+    #[test]
+    fn request_url_encoding() {
+        let url = request_url(
+            "https://example.com/search",
+            &[
+                ("q", "Rue de l'Église 5, Saint-Denis & co".to_string()),
+                ("lang", "uk".to_string()),
+                ("empty", String::new()),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            url.as_str(),
+            "https://example.com/search?q=Rue+de+l%27%C3%89glise+5%2C+Saint-Denis+%26+co&lang=uk&\
+            empty="
+        );
+        assert!(matches!(
+            request_url("not a url", &[]),
+            Err(WeatherError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn request_coordinates() {
+        for (value, expected) in [
+            (50.45, "50.45"),
+            (50.450001234, "50.45"),
+            (30.52345, "30.5235"),
+            (-33.86785, "-33.8679"),
+            (-0.00001, "0"),
+            (180.0, "180"),
+            (0.1 + 0.2, "0.3"),
+        ] {
+            assert_eq!(request_coordinate(value), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn coordinates_out_of_range() {
+        let options = RequestOptions {
+            no_geocode: true,
+            ..options("out-of-range")
+        };
+
+        for (address, echoed) in [
+            ("95, 30", "got 95 (from \"95, 30\")"),
+            ("91S 30E", "got -91 (from \"91S 30E\")"),
+            ("50, 200", "got 200 (from \"50, 200\""),
+            ("200W 50.45N", "got -200 (from \"200W 50.45N\""),
+        ] {
+            match resolve_location(address, &options) {
+                Err(WeatherError::InvalidLocation(message)) => {
+                    assert!(message.contains(echoed), "{address}: {message}")
+                }
+                result => panic!("{address}: {result:?}"),
+            }
+        }
+
+        // In range once it's wrapped around
+        let options = RequestOptions {
+            normalize_lon: true,
+            ..options
+        };
+        let location = resolve_location("50, 200", &options).unwrap();
+        assert_eq!((location.lat, location.lon), (50.0, -160.0));
+    }
+
+    fn rate_limited_until(result: Result<ProviderResponse>) -> DateTime<Utc> {
+        match result {
+            Err(WeatherError::RateLimited { until, .. }) => until,
+            Err(e) => panic!("not rate limited: {e}"),
+            Ok(_) => panic!("not rate limited"),
+        }
+    }
+
+    #[test]
+    fn rate_limited_with_retry_after() {
+        let server = TestServer::start(vec![(
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 120",
+            "{}",
+        )]);
+        let url = server.url.clone();
+        let options = options("retry-after");
+
+        let until = rate_limited_until(Provider::MetNo.request(&url, &options, None));
+        assert_eq!(until, options.now + chrono::Duration::seconds(120));
+
+        // The next run doesn't send the request at all until the cooldown is over
+        let next_run = RequestOptions {
+            now: options.now + chrono::Duration::seconds(60),
+            ..options.clone()
+        };
+        assert_eq!(
+            rate_limited_until(Provider::MetNo.request(&url, &next_run, None)),
+            until
+        );
+        assert_eq!(server.request_count(), 1);
+
+        let _ = std::fs::remove_dir_all(&options.paths.state_dir);
+    }
+
+    #[test]
+    fn rate_limited_without_retry_after() {
+        let server = TestServer::start(vec![
+            ("HTTP/1.1 429 Too Many Requests", "{}"),
+            ("HTTP/1.1 200 OK\r\nContent-Type: application/json", "{}"),
+        ]);
+        let url = server.url.clone();
+        let options = options("no-retry-after");
+
+        let until = rate_limited_until(Provider::MetNo.request(&url, &options, None));
+        assert_eq!(
+            until,
+            options.now + chrono::Duration::hours(DEFAULT_COOLDOWN_HOURS)
+        );
+
+        // Still cooling down a minute before the end, and asked again after it
+        let next_run = |minutes| RequestOptions {
+            now: until + chrono::Duration::minutes(minutes),
+            ..options.clone()
+        };
+        assert!(Provider::MetNo.request(&url, &next_run(-1), None).is_err());
+        assert_eq!(server.request_count(), 1);
+
+        assert!(Provider::MetNo.request(&url, &next_run(1), None).is_ok());
+        assert_eq!(server.request_count(), 2);
+
+        // The successful request cleared the cooldown
+        assert!(Provider::MetNo.request(&url, &options, None).is_ok());
+        assert_eq!(server.request_count(), 3);
+
+        let _ = std::fs::remove_dir_all(&options.paths.state_dir);
+    }
+
+    #[test]
+    fn not_modified_uses_the_cached_body() {
+        let server = TestServer::start(vec![
+            ("HTTP/1.1 304 Not Modified", ""),
+            (
+                "HTTP/1.1 200 OK\r\nLast-Modified: Fri, 14 Jun 2024 12:00:00 GMT",
+                "{\"new\":1}",
+            ),
+        ]);
+        let url = server.url.clone();
+        let options = options("not-modified");
+        let cached = CachedResponse {
+            provider: Provider::MetNo,
+            address: "Kyiv".to_string(),
+            lat: 50.45,
+            lon: 30.52,
+            fetched_at: options.now,
+            last_modified: Some("Fri, 14 Jun 2024 10:00:00 GMT".to_string()),
+            expires: None,
+            json: serde_json::from_str("{\"old\":1}").unwrap(),
+        };
+
+        // Asked if it changed since the cached one, and it didn't
+        let response = Provider::MetNo
+            .request(&url, &options, Some(&cached))
+            .unwrap();
+        assert_eq!(response.status, reqwest::StatusCode::NOT_MODIFIED);
+        assert_eq!(response.body, "{\"old\":1}");
+        assert_eq!(
+            response.last_modified.as_deref(),
+            Some("Fri, 14 Jun 2024 10:00:00 GMT")
+        );
+        assert!(server.requests()[0]
+            .to_lowercase()
+            .contains("if-modified-since: fri, 14 jun 2024 10:00:00 gmt"));
+
+        // Nothing to ask about without the cached response
+        let response = Provider::MetNo.request(&url, &options, None).unwrap();
+        assert_eq!(response.body, "{\"new\":1}");
+        assert_eq!(
+            response.last_modified.as_deref(),
+            Some("Fri, 14 Jun 2024 12:00:00 GMT")
+        );
+        assert!(!server.requests()[1]
+            .to_lowercase()
+            .contains("if-modified-since"));
+
+        let _ = std::fs::remove_dir_all(&options.paths.state_dir);
+    }
+
+    #[test]
+    fn caching_headers_captured() {
+        let server = TestServer::start(vec![(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nExpires: Fri, 14 Jun 2024 12:30:00 GMT\r\nLast-Modified: Fri, 14 Jun 2024 11:45:00 GMT",
+            "{}",
+        )]);
+        let options = options("caching-headers");
+
+        let response = Provider::MetNo
+            .request(&server.url, &options, None)
+            .unwrap();
+        assert_eq!(
+            response.expires.map(|expires| expires.to_rfc3339()),
+            Some("2024-06-14T12:30:00+00:00".to_string())
+        );
+        assert_eq!(
+            response.last_modified.as_deref(),
+            Some("Fri, 14 Jun 2024 11:45:00 GMT")
+        );
+
+        // Without them, nothing is assumed
+        let server = TestServer::start(vec![("HTTP/1.1 200 OK", "{}")]);
+        let response = Provider::MetNo
+            .request(&server.url, &options, None)
+            .unwrap();
+        assert_eq!((response.expires, response.last_modified), (None, None));
+
+        let _ = std::fs::remove_dir_all(&options.paths.state_dir);
+    }
+
+    #[test]
+    fn error_bodies_that_are_not_json() {
+        const HTML: &str = "<html><head><title>502 Bad Gateway</title></head><body><h1>Bad \
+            Gateway</h1><p>The proxy got an invalid response</p></body></html>";
+        let server = TestServer::start(vec![
+            ("HTTP/1.1 502 Bad Gateway\r\nContent-Type: text/html", HTML),
+            ("HTTP/1.1 404 Not Found", "{\"reason\":\"No such place\"}"),
+            ("HTTP/1.1 200 OK\r\nContent-Type: text/html", HTML),
+            ("HTTP/1.1 203 Non-Authoritative Information", "{}"),
+        ]);
+        let url = server.url.clone();
+        let options = options("not-json");
+        let message = |result: Result<ProviderResponse>| match result {
+            Err(WeatherError::Provider(message)) => message,
+            Err(e) => panic!("not a provider error: {e}"),
+            Ok(response) => panic!("succeeded with {}", response.status),
+        };
+
+        // The status with the text of the page, without the tags
+        let error = message(Provider::MetNo.request(&url, &options, None));
+        assert!(error.contains("(502 Bad Gateway)"), "{error}");
+        assert!(
+            error.contains("502 Bad Gateway Bad Gateway The proxy got an invalid response"),
+            "{error}"
+        );
+        assert!(!error.contains('<'), "{error}");
+
+        // The reason out of the JSON ones
+        let error = message(Provider::MetNo.request(&url, &options, None));
+        assert!(
+            error.ends_with("refused the request (404 Not Found): No such place"),
+            "{error}"
+        );
+
+        // The successful response has to be JSON to be parsed
+        let response = Provider::MetNo.request(&url, &options, None).unwrap();
+        match response.json(Provider::MetNo) {
+            Err(WeatherError::InvalidResponse(error)) => {
+                assert!(
+                    error.contains("not JSON (200 OK): 502 Bad Gateway"),
+                    "{error}"
+                )
+            }
+            result => panic!("{result:?}"),
+        }
+
+        // The deprecated API still answers
+        let response = Provider::MetNo.request(&url, &options, None).unwrap();
+        assert!(response.deprecated);
+        assert_eq!(server.request_count(), 4);
+
+        let _ = std::fs::remove_dir_all(&options.paths.state_dir);
+    }
+
+    /// Kyiv, where the fallback tests get the weather for
+    fn kyiv() -> ResolvedLocation {
+        ResolvedLocation {
+            lat: 50.45,
+            lon: 30.52,
+            display_name: "Kyiv, Ukraine".to_string(),
+            short_name: None,
+            timezone: None,
+            country_code: None,
+        }
+    }
+
+    /// Options for the fallback tests on June 14, 2024 (the responses are cached, nothing is
+    /// fetched)
+    fn fallback_options(name: &str, fallback: Option<Fallback>) -> RequestOptions {
+        RequestOptions {
+            now: DateTime::parse_from_rfc3339("2024-06-14T12:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            fallback,
+            ..options(name)
+        }
+    }
+
+    /// Cache the open_meteo response for the date, as if it was just fetched and doesn't expire
+    /// until tomorrow (so it's used instead of sending the request)
+    fn cache_open_meteo(options: &RequestOptions, date: &str) {
+        let request = ProviderRequestBuilder::new(Provider::OpenMeteo)
+            .options(options.clone())
+            .location(Location::Resolved(kyiv()))
+            .and_then(|builder| builder.date(date.to_string()))
+            .and_then(ProviderRequestBuilder::build)
+            .unwrap();
+        let json = serde_json::json!({
+            "utc_offset_seconds": 10800,
+            "timezone": "Europe/Kyiv",
+            "hourly_units": { "temperature_2m": "°C" },
+            "hourly": {
+                "time": (0..24)
+                    .map(|hour| format!("{}T{hour:02}:00", request.requested_date))
+                    .collect::<Vec<_>>(),
+                "temperature_2m": (0..24).map(f64::from).collect::<Vec<_>>(),
+            },
+        });
+
+        cache_response(
+            options,
+            response_key(
+                Provider::OpenMeteo,
+                &request.location,
+                &request.requested_date,
+                request.requested_range,
+                options.daily,
+                options.elevation,
+            ),
+            json.as_object().unwrap().clone(),
+        );
+    }
+
+    /// Cache the open_meteo response for Kyiv under the key, until tomorrow
+    fn cache_response(options: &RequestOptions, key: String, json: Map<String, Value>) {
+        ResponseCache::new(&options.paths).insert(
+            &key,
+            &CachedResponse {
+                provider: Provider::OpenMeteo,
+                address: kyiv().display_name,
+                lat: kyiv().lat,
+                lon: kyiv().lon,
+                fetched_at: options.now,
+                last_modified: None,
+                expires: Some(options.now + chrono::Duration::days(1)),
+                json,
+            },
+        );
+    }
+
+    #[test]
+    fn archive_eras() {
+        const KYIV: (f64, f64) = (50.45, 30.52);
+        const TOKYO: (f64, f64) = (35.68, 139.69);
+        let cerra = "CERRA (5 km grid) only covers 1985-2021";
+        // (year, location, source, caveat)
+        let cases = [
+            (1939, KYIV, None, Some("The archive starts in 1940, there is no data for 1939")),
+            (
+                1940,
+                KYIV,
+                Some("ERA5"),
+                Some("Data for 1940 comes from ERA5 (25 km grid) and is based on sparse observations"),
+            ),
+            (
+                1949,
+                TOKYO,
+                Some("ERA5"),
+                Some("Data for 1949 comes from ERA5 (25 km grid) and is based on sparse observations"),
+            ),
+            (
+                1958,
+                TOKYO,
+                Some("ERA5-Land"),
+                Some("Data for 1958 comes from ERA5-Land (11 km grid) and is based on sparse observations"),
+            ),
+            (1959, TOKYO, Some("ERA5-Land"), None),
+            // Europe has a regional data set for some of the years, and a coarser one for the rest
+            (1984, KYIV, Some("ERA5-Land"), Some(cerra)),
+            (1984, TOKYO, Some("ERA5-Land"), None),
+            (1985, KYIV, Some("CERRA"), None),
+            (2021, KYIV, Some("CERRA"), None),
+            (2022, KYIV, Some("ERA5-Land"), Some(cerra)),
+            (2022, TOKYO, Some("ERA5-Land"), None),
+        ];
+
+        for (year, (lat, lon), source, caveat) in cases {
+            let date = NaiveDate::from_ymd_opt(year, 6, 14).unwrap();
+
+            assert_eq!(
+                archive_source(date, lat, lon).map(|source| source.name),
+                source,
+                "{year} {lat}, {lon}"
+            );
+            let found = archive_caveat(date, lat, lon);
+            match caveat {
+                Some(caveat) => assert!(
+                    found.as_deref().is_some_and(|found| found.contains(caveat)),
+                    "{year} {lat}, {lon}: {found:?}"
+                ),
+                None => assert_eq!(found, None, "{year} {lat}, {lon}"),
+            }
+        }
+
+        // The data of the date comes with it as a degradation, which the outputs list
+        let options = fallback_options("archive-era", None);
+        cache_open_meteo(&options, "1984-06-14");
+        let data = Provider::OpenMeteo
+            .get(
+                Location::Resolved(kyiv()),
+                "1984-06-14".to_string(),
+                &options,
+            )
+            .unwrap();
+        assert!(
+            data.degradations().iter().any(|degradation| matches!(
+                degradation,
+                Degradation::Caveat(caveat) if caveat.contains(cerra)
+            )),
+            "{:?}",
+            data.degradations()
+        );
+
+        let _ = std::fs::remove_dir_all(&options.paths.cache_dir);
+    }
+
+    #[test]
+    fn fallback_on_capability_rejection() {
+        let cases = [
+            // met_no has no history
+            (
+                Provider::MetNo,
+                None,
+                Some(Fallback::Provider(Provider::OpenMeteo)),
+            ),
+            (Provider::MetNo, None, Some(Fallback::Auto)),
+            // open_weather_map can't do anything without the API key
+            (Provider::OpenWeatherMap, None, Some(Fallback::Auto)),
+            // The fallback gets a key of its own
+            (
+                Provider::MetNo,
+                Some("key"),
+                Some(Fallback::Provider(Provider::OpenMeteo)),
+            ),
+        ];
+
+        for (provider, api_key, fallback) in cases {
+            let options = RequestOptions {
+                api_key: api_key.map(str::to_string),
+                ..fallback_options("capability-fallback", fallback)
+            };
+            cache_open_meteo(&options, "2024-06-10");
+
+            let data = provider
+                .get(
+                    Location::Resolved(kyiv()),
+                    "2024-06-10".to_string(),
+                    &options,
+                )
+                .unwrap();
+            assert_eq!(
+                data.provider,
+                Provider::OpenMeteo,
+                "{provider} {fallback:?}"
+            );
+            assert_eq!(data.temperatures.len(), 24, "{provider} {fallback:?}");
+
+            // Nothing to fall back to
+            let options = RequestOptions {
+                fallback: None,
+                ..options
+            };
+            assert!(matches!(
+                provider.get(
+                    Location::Resolved(kyiv()),
+                    "2024-06-10".to_string(),
+                    &options
+                ),
+                Err(WeatherError::Unsupported(_) | WeatherError::MissingApiKey { .. })
+            ));
+
+            let _ = std::fs::remove_dir_all(&options.paths.cache_dir);
+        }
+    }
+
+    #[test]
+    fn no_fallback_on_network_error() {
+        // Every request goes through a proxy that isn't there
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let options = RequestOptions {
+            http: reqwest::blocking::Client::builder()
+                .proxy(reqwest::Proxy::all(format!("http://127.0.0.1:{port}")).unwrap())
+                .build()
+                .unwrap(),
+            ..fallback_options(
+                "network-fallback",
+                Some(Fallback::Provider(Provider::OpenMeteo)),
+            )
+        };
+        // The fallback would have the data without fetching anything
+        cache_open_meteo(&options, "now");
+
+        let err = Provider::MetNo
+            .get(Location::Resolved(kyiv()), "now".to_string(), &options)
+            .unwrap_err();
+        assert_eq!(err.category(), ErrorCategory::Network, "{err}");
+
+        // While the fallback itself does have it
+        let data = Provider::OpenMeteo
+            .get(Location::Resolved(kyiv()), "now".to_string(), &options)
+            .unwrap();
+        assert_eq!(data.provider, Provider::OpenMeteo);
+
+        let _ = std::fs::remove_dir_all(&options.paths.cache_dir);
+    }
+
+    /// open_meteo forecast with the current conditions at 15:15 and the hours of the day, one of
+    /// them without the temperature
+    const OPEN_METEO_NOW: &str = r#"{"utc_offset_seconds":10800,"timezone":"Europe/Kyiv","current_weather":{"time":"2024-06-14T15:15","temperature":21.5,"weathercode":2,"windspeed":10.0,"winddirection":180.0},"hourly_units":{"temperature_2m":"°C","windspeed_10m":"km/h"},"hourly":{"time":["2024-06-14T00:00","2024-06-14T01:00","2024-06-14T02:00","2024-06-14T03:00","2024-06-14T04:00","2024-06-14T05:00","2024-06-14T06:00","2024-06-14T07:00","2024-06-14T08:00","2024-06-14T09:00","2024-06-14T10:00","2024-06-14T11:00","2024-06-14T12:00","2024-06-14T13:00","2024-06-14T14:00","2024-06-14T15:00","2024-06-14T16:00","2024-06-14T17:00","2024-06-14T18:00","2024-06-14T19:00","2024-06-14T20:00","2024-06-14T21:00","2024-06-14T22:00","2024-06-14T23:00"],"temperature_2m":[0.5,1.5,2.5,3.5,4.5,5.5,6.5,7.5,8.5,9.5,10.5,11.5,12.5,13.5,14.5,15.5,16.5,null,18.5,19.5,20.5,21.5,22.5,23.5],"windspeed_10m":[5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0,5.0]}}"#;
+
+    #[test]
+    fn current_snapshot_same_as_full() {
+        let server = TestServer::start(vec![(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json",
+            OPEN_METEO_NOW,
+        )]);
+        let options = fallback_options("current-snapshot", None);
+        // The full response is the cached one, the lighter one comes from the server
+        let request = ProviderRequestBuilder::new(Provider::OpenMeteo)
+            .options(options.clone())
+            .location(Location::Resolved(kyiv()))
+            .and_then(|builder| builder.date("now".to_string()))
+            .and_then(ProviderRequestBuilder::build)
+            .unwrap();
+        cache_response(
+            &options,
+            response_key(
+                Provider::OpenMeteo,
+                &kyiv(),
+                &request.requested_date,
+                None,
+                false,
+                None,
+            ),
+            serde_json::from_str(OPEN_METEO_NOW).unwrap(),
+        );
+        let full = Provider::OpenMeteo
+            .get(Location::Resolved(kyiv()), "now".to_string(), &options)
+            .unwrap();
+        assert_eq!(server.request_count(), 0);
+
+        for hours in [1, 6, 12, 24] {
+            let lean = Provider::OpenMeteo
+                .fetch_current(kyiv(), (server.url.clone(), hours), &options)
+                .unwrap();
+
+            // Whatever is shown is the same both ways
+            assert_eq!(
+                format!("{lean:?}"),
+                format!("{:?}", CurrentSnapshot::from_data(&full, hours)),
+                "{hours} hours"
+            );
+            assert_eq!(lean.temperature, Some(21.5), "{hours} hours");
+            assert_eq!(
+                lean.weather_code,
+                Some(WeatherCode::PartlyCloudy),
+                "{hours} hours"
+            );
+            // From 15:00 to the end of the day at most
+            assert_eq!(lean.temperatures.len(), hours.min(9), "{hours} hours");
+            assert_eq!(lean.temperatures.first(), Some(&15.5), "{hours} hours");
+
+            // Only the current conditions and the temperatures of the hours are asked for
+            let url = OpenMeteo
+                .current_url(request_params(&options), &options, hours)
+                .unwrap();
+            for param in [
+                "current_weather=true",
+                "hourly=temperature_2m&",
+                &format!("forecast_hours={hours}&"),
+            ] {
+                assert!(url.as_str().contains(param), "{hours} hours: {url}");
+            }
+        }
+        assert_eq!(server.request_count(), 4);
+
+        // The coordinates aren't looked up, and the cached response is used without the request
+        let lean = Provider::OpenMeteo
+            .get_current(
+                Location::Address("50.45, 30.52".to_string()),
+                24,
+                &RequestOptions {
+                    cached: true,
+                    ..options.clone()
+                },
+            )
+            .unwrap();
+        assert_eq!(lean.location.display_name, "50.45, 30.52");
+        assert_eq!(lean.temperatures.len(), 9);
+        assert_eq!(server.request_count(), 4);
+
+        let _ = std::fs::remove_dir_all(&options.paths.cache_dir);
+    }
+
+    /// Parameters of the location of the Kyiv requests
+    fn request_params(options: &RequestOptions) -> QueryParams {
+        ProviderRequestBuilder::new(Provider::OpenMeteo)
+            .options(options.clone())
+            .location(Location::Resolved(kyiv()))
+            .unwrap()
+            .params
     }
 }
@@ -0,0 +1,771 @@
+//! Requests to met_no and the parsing of its responses
+
+pub(crate) mod alerts;
+pub(crate) mod nowcast;
+pub(crate) mod response;
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Offset, Utc};
+use chrono_tz::Tz;
+use itertools::{izip, Itertools};
+use reqwest::Url;
+use serde_json::{Map, Value};
+
+use crate::{
+    astro,
+    data::{
+        apparent_temperature, parse_response, utc_offset_name, CurrentWeatherData,
+        DailyWeatherData, NowcastData, RadarCoverage, Timestamp, WeatherAlert, WeatherCode,
+        WeatherData, WindDirection, DAYLIGHT_FORMAT,
+    },
+    error::{Result, WeatherError},
+    geocode::ResolvedLocation,
+    providers::{
+        request_coordinate, request_url, Provider, ProviderCapabilities, ProviderRequestType,
+        QueryParams, RequestOptions, WeatherProvider,
+    },
+};
+
+use self::{
+    alerts::MetAlertsResponse,
+    nowcast::NowcastResponse,
+    response::{MetNoResponse, Properties},
+};
+
+/// Timezone the times of met_no (all of them in UTC) are shown in
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum LocationZone {
+    /// The one the geocoder found the location in
+    Named(Tz),
+    /// The offset of the longitude (15° an hour), for the locations without a known timezone
+    Offset(FixedOffset),
+}
+
+impl LocationZone {
+    fn of(location: &ResolvedLocation) -> Self {
+        match location
+            .timezone
+            .as_deref()
+            .and_then(|name| name.parse::<Tz>().ok())
+        {
+            Some(tz) => Self::Named(tz),
+            None => {
+                let hours = (location.lon / 15.0).round().clamp(-12.0, 14.0) as i32;
+
+                Self::Offset(FixedOffset::east_opt(hours * 3600).unwrap_or(Utc.fix()))
+            }
+        }
+    }
+
+    /// "Asia/Tokyo", or "UTC+09:00" for the offset
+    fn name(&self) -> String {
+        match self {
+            Self::Named(tz) => tz.name().to_string(),
+            Self::Offset(offset) => utc_offset_name(offset.local_minus_utc() as i64),
+        }
+    }
+
+    /// The time in the timezone, with the offset it has then
+    fn local(&self, time: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Self::Named(tz) => time.with_timezone(tz).fixed_offset(),
+            Self::Offset(offset) => time.with_timezone(offset),
+        }
+    }
+
+    /// The time of the day in the timezone
+    fn naive_local(&self, time: DateTime<Utc>) -> NaiveDateTime {
+        self.local(time).naive_local()
+    }
+}
+
+/// API base URL of the forecasts
+const BASE_URL: &str = "https://api.met.no/weatherapi/locationforecast/2.0";
+
+/// The alerts are a separate API (the locationforecast one doesn't have them)
+const ALERTS_URL: &str = "https://api.met.no/weatherapi/metalerts/2.0/current.json";
+
+/// So is the nowcast, which only has the data where the Nordic radars reach
+const NOWCAST_URL: &str = "https://api.met.no/weatherapi/nowcast/2.0/complete";
+
+/// The Norwegian Meteorological Institute, free forecasts for the next few days and the alerts
+#[derive(Debug)]
+pub(crate) struct MetNo;
+
+impl WeatherProvider for MetNo {
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            provider: Provider::MetNo,
+            supports_history: false,
+            supports_custom_dates: false,
+            forecast_days: 9,
+            history_cutoff_days: None,
+            requires_api_key: false,
+            current_conditions: true,
+            air_quality: false,
+            nowcast: true,
+            language_param: None,
+            terms_url: "https://api.met.no/doc/TermsOfService",
+        }
+    }
+
+    /// Only whole meters are taken
+    fn elevation_param(&self) -> Option<&'static str> {
+        Some("altitude")
+    }
+
+    /// The requests without them are refused
+    fn headers(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            ("Accept", "application/json"),
+            ("User-Agent", "tukweathercli/0.1.0"),
+        ]
+    }
+
+    fn build_request(
+        &self,
+        request_type: &ProviderRequestType,
+        params: QueryParams,
+        _options: &RequestOptions,
+    ) -> Result<Url> {
+        match request_type {
+            ProviderRequestType::Forecast => request_url(&format!("{BASE_URL}/complete"), &params),
+            ProviderRequestType::History => Err(WeatherError::Unsupported(format!(
+                "History is not supported by {} provider",
+                Provider::MetNo
+            ))),
+        }
+    }
+
+    fn parse(&self, json: &Map<String, Value>, data: WeatherData) -> Result<WeatherData> {
+        data.parse_met_no(parse_response(json, Provider::MetNo)?)
+    }
+
+    fn alerts_url(&self, location: &ResolvedLocation) -> Option<Url> {
+        request_url(
+            ALERTS_URL,
+            &[
+                ("lat", request_coordinate(location.lat)),
+                ("lon", request_coordinate(location.lon)),
+                ("lang", "en".to_string()),
+            ],
+        )
+        .ok()
+    }
+
+    fn parse_alerts(&self, json: &Map<String, Value>) -> Result<Vec<WeatherAlert>> {
+        let response: MetAlertsResponse = parse_response(json, Provider::MetNo)?;
+
+        Ok(response
+            .features
+            .into_iter()
+            .map(WeatherAlert::from)
+            .collect())
+    }
+
+    fn nowcast_url(&self, params: &QueryParams) -> Option<Url> {
+        // Only the coordinates, the radars don't care about the elevation
+        let params = params
+            .iter()
+            .filter(|(name, _)| ["lat", "lon"].contains(name))
+            .cloned()
+            .collect::<QueryParams>();
+
+        request_url(NOWCAST_URL, &params).ok()
+    }
+
+    fn parse_nowcast(
+        &self,
+        json: &Map<String, Value>,
+        data: &WeatherData,
+    ) -> Result<Option<NowcastData>> {
+        let response: NowcastResponse = parse_response(json, Provider::MetNo)?;
+        let radar_coverage = response
+            .properties
+            .meta
+            .radar_coverage
+            .as_deref()
+            .map_or(RadarCoverage::Ok, RadarCoverage::from_met_no);
+
+        // Outside of the coverage the entries have no precipitation, which isn't the same as dry
+        let timeseries = match radar_coverage {
+            RadarCoverage::Ok => response.properties.timeseries,
+            _ => Vec::new(),
+        };
+
+        // In the timezone of the location, like the timestamps
+        let zone = LocationZone::of(&data.location);
+        Ok(Some(NowcastData::new(
+            timeseries.into_iter().map(|entry| {
+                (
+                    zone.naive_local(entry.time),
+                    entry.data.instant.details.precipitation_rate,
+                )
+            }),
+            data.time_format,
+            radar_coverage,
+        )))
+    }
+}
+
+/// The alerts without a severity or a title still have the event, so it's used in their place
+impl From<alerts::Feature> for WeatherAlert {
+    fn from(feature: alerts::Feature) -> Self {
+        let alerts::FeatureProperties {
+            event,
+            severity,
+            title,
+        } = feature.properties;
+        let interval = feature.when.map(|when| when.interval).unwrap_or_default();
+
+        Self {
+            headline: title.unwrap_or(event.clone()),
+            event,
+            severity: severity.unwrap_or("Unknown".to_string()),
+            onset: interval.first().copied(),
+            expires: interval.get(1).copied(),
+        }
+    }
+}
+
+/// Conditions of the entry: the symbol of the next hour, or of the next 6 hours further ahead
+pub(crate) fn weather_code(entry: &response::TimeseriesEntry) -> WeatherCode {
+    [&entry.data.next_1_hours, &entry.data.next_6_hours]
+        .into_iter()
+        .find_map(|period| period.as_ref()?.summary.as_ref())
+        .map(|summary| WeatherCode::from_met_no(&summary.symbol_code))
+        .unwrap_or_default()
+}
+
+/// There is no separate block with the current conditions, so they are taken from the entry of
+/// the current hour (`None` if it doesn't have the temperature or the wind)
+fn current_conditions(
+    entry: &response::TimeseriesEntry,
+    zone: LocationZone,
+) -> Option<CurrentWeatherData> {
+    let details = &entry.data.instant.details;
+    let temperature = details.air_temperature?;
+    let wind_speed = details.wind_speed?;
+
+    Some(CurrentWeatherData {
+        time: zone.local(entry.time).format("%Y-%m-%d %H:%M").to_string(),
+        temperature,
+        weather_code: weather_code(entry),
+        wind_speed,
+        wind_speed_unit: "m/s".to_string(),
+        wind_direction: WindDirection::from_degrees(details.wind_from_direction?),
+        precipitation_probability: entry
+            .data
+            .next_1_hours
+            .as_ref()
+            .and_then(|period| period.details.as_ref())
+            .and_then(|details| details.probability_of_precipitation),
+        // met_no doesn't have it, so it's calculated
+        apparent_temperature: details
+            .relative_humidity
+            .map(|humidity| apparent_temperature(temperature, humidity, wind_speed)),
+        humidity: details.relative_humidity,
+        // Not every entry has it (the ones further ahead don't)
+        uv_index: details.ultraviolet_index_clear_sky,
+        // Calculated once the response is parsed
+        wind_chill: None,
+    })
+}
+
+impl WeatherData {
+    fn parse_met_no(mut self, response: MetNoResponse) -> Result<Self> {
+        let Properties { meta, timeseries } = response.properties;
+
+        self.data_updated_at = meta.updated_at;
+        self.unit = meta.units.air_temperature;
+        // The coordinates are [lon, lat, altitude]
+        self.elevation = response
+            .geometry
+            .and_then(|geometry| geometry.coordinates.get(2).copied());
+
+        // The times are in UTC, and are shown in the timezone of the location
+        let zone = LocationZone::of(&self.location);
+        self.timezone = zone.name();
+
+        // There is no separate block with the current conditions, the first entry is the current
+        // hour
+        self.current = timeseries
+            .first()
+            .and_then(|entry| current_conditions(entry, zone));
+        let current_time = timeseries.first().map(|entry| entry.time);
+
+        let timeseries = match self.requested_days() {
+            // Pick the requested days out of everything the API returned
+            Some(range) => {
+                let timeseries = timeseries
+                    .iter()
+                    .filter(|entry| range.contains(zone.naive_local(entry.time).date()))
+                    .collect_vec();
+
+                if timeseries.is_empty() {
+                    return Err(WeatherError::Unsupported(
+                        "The requested days are too far ahead for the met_no forecast".to_string(),
+                    ));
+                }
+
+                timeseries
+            }
+            None => timeseries.iter().take(24).collect_vec(),
+        };
+
+        // Unless the requested days start later, the first entry is still the current hour
+        self.current_hour = (current_time.is_some()
+            && timeseries.first().map(|entry| entry.time) == current_time)
+            .then_some(0);
+
+        let local_times = timeseries
+            .iter()
+            .map(|entry| zone.local(entry.time))
+            .collect_vec();
+        let times = local_times
+            .iter()
+            .map(|time| time.naive_local())
+            .collect_vec();
+        let timestamp_format = self.timestamp_format();
+        self.timestamps = local_times
+            .into_iter()
+            .map(|time| Timestamp::new(time, timestamp_format))
+            .collect();
+
+        // met_no doesn't have the sunrise and the sunset, so they are calculated (in UTC, and then
+        // moved to the timezone of the timestamps)
+        if let Some((sunrise, sunset)) = times.first().and_then(|first| {
+            astro::sunrise_sunset(self.location.lat, self.location.lon, first.date())
+        }) {
+            for (time, field) in [(sunrise, &mut self.sunrise), (sunset, &mut self.sunset)] {
+                *field = Some(
+                    zone.naive_local(time.and_utc())
+                        .format(DAYLIGHT_FORMAT)
+                        .to_string(),
+                );
+            }
+        }
+
+        let details = timeseries
+            .iter()
+            .map(|entry| &entry.data.instant.details)
+            .collect_vec();
+
+        // The entries without the temperature or the wind get NaN
+        self.temperatures = details
+            .iter()
+            .map(|d| d.air_temperature.unwrap_or(f64::NAN))
+            .collect();
+        self.wind_speeds = details
+            .iter()
+            .map(|d| d.wind_speed.unwrap_or(f64::NAN))
+            .collect();
+        self.wind_directions = details
+            .iter()
+            .map(|d| WindDirection::from_degrees(d.wind_from_direction.unwrap_or(f64::NAN)))
+            .collect();
+        self.wind_speed_unit = meta.units.wind_speed.unwrap_or("m/s".to_string());
+        self.weather_codes = timeseries.iter().map(|entry| weather_code(entry)).collect();
+
+        // The days are the ones of the location, like the timestamps
+        if self.daily.is_some() {
+            self.daily = Some(
+                DailyWeatherData::from_hourly(
+                    times.iter().copied().zip(self.temperatures.iter().copied()),
+                )
+                .with_hourly_weather_codes(
+                    times
+                        .iter()
+                        .copied()
+                        .zip(self.weather_codes.iter().copied()),
+                ),
+            );
+        }
+
+        // The entries without the humidity get NaN, and the apparent temperatures (met_no doesn't
+        // have them) are calculated from it
+        if details.iter().any(|d| d.relative_humidity.is_some()) {
+            self.humidity = details
+                .iter()
+                .map(|d| d.relative_humidity.unwrap_or(f64::NAN))
+                .collect();
+            self.apparent_temperatures =
+                izip!(&self.temperatures, &self.humidity, &self.wind_speeds)
+                    .map(|(temperature, humidity, wind_speed)| {
+                        apparent_temperature(*temperature, *humidity, *wind_speed)
+                    })
+                    .collect();
+        }
+
+        // Only extras as well, the entries without them get NaN
+        if details
+            .iter()
+            .any(|d| d.air_pressure_at_sea_level.is_some())
+        {
+            self.pressures = details
+                .iter()
+                .map(|d| d.air_pressure_at_sea_level.unwrap_or(f64::NAN))
+                .collect();
+            self.pressure_unit = meta
+                .units
+                .air_pressure_at_sea_level
+                .clone()
+                .unwrap_or("hPa".to_string());
+        }
+        if details.iter().any(|d| d.cloud_area_fraction.is_some()) {
+            self.cloud_cover = details
+                .iter()
+                .map(|d| d.cloud_area_fraction.unwrap_or(f64::NAN))
+                .collect();
+        }
+        if details.iter().any(|d| d.wind_speed_of_gust.is_some()) {
+            self.wind_gusts = details
+                .iter()
+                .map(|d| d.wind_speed_of_gust.unwrap_or(f64::NAN))
+                .collect();
+        }
+        // Not every entry has it either (the ones further ahead don't)
+        if details
+            .iter()
+            .any(|d| d.ultraviolet_index_clear_sky.is_some())
+        {
+            self.uv_indices = details
+                .iter()
+                .map(|d| d.ultraviolet_index_clear_sky.unwrap_or(f64::NAN))
+                .collect();
+        }
+
+        // The precipitation is for the hour after every entry, which the entries further ahead
+        // (6 hour steps) don't have, so they are counted as dry
+        let next_hour_details = timeseries
+            .iter()
+            .map(|entry| {
+                entry
+                    .data
+                    .next_1_hours
+                    .as_ref()
+                    .and_then(|period| period.details.as_ref())
+            })
+            .collect_vec();
+
+        self.precipitation = next_hour_details
+            .iter()
+            .map(|d| d.and_then(|d| d.precipitation_amount).unwrap_or_default())
+            .collect();
+        self.precipitation_unit = meta.units.precipitation_amount.unwrap_or("mm".to_string());
+
+        if next_hour_details
+            .iter()
+            .any(|d| d.is_some_and(|d| d.probability_of_precipitation.is_some()))
+        {
+            self.precipitation_probabilities = next_hour_details
+                .iter()
+                .map(|d| {
+                    d.and_then(|d| d.probability_of_precipitation)
+                        .unwrap_or_default()
+                })
+                .collect();
+        }
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Timelike};
+
+    use super::*;
+    use crate::{config::TimeFormat, date::DateRange};
+
+    /// Two days of hours in UTC (from June 14, 2024 00:00), the temperature of every hour is its
+    /// number
+    fn tokyo_response() -> MetNoResponse {
+        let timeseries = (0..48)
+            .map(|hour| {
+                serde_json::json!({
+                    "time": (DateTime::parse_from_rfc3339("2024-06-14T00:00:00Z").unwrap()
+                        + chrono::Duration::hours(hour))
+                    .with_timezone(&Utc),
+                    "data": {
+                        "instant": {
+                            "details": {
+                                "air_temperature": hour as f64,
+                                "wind_speed": 2.0,
+                                "wind_from_direction": 90.0,
+                            },
+                        },
+                    },
+                })
+            })
+            .collect_vec();
+
+        serde_json::from_value(serde_json::json!({
+            "properties": {
+                "meta": { "units": { "air_temperature": "celsius" } },
+                "timeseries": timeseries,
+            },
+        }))
+        .unwrap()
+    }
+
+    fn tokyo(timezone: Option<&str>) -> ResolvedLocation {
+        ResolvedLocation {
+            lat: 35.68,
+            lon: 139.69,
+            display_name: "Tokyo, Japan".to_string(),
+            short_name: None,
+            timezone: timezone.map(String::from),
+            country_code: None,
+        }
+    }
+
+    #[test]
+    fn times_in_the_timezone_of_the_location() {
+        // The geocoder knew the timezone, or it's the one of the longitude
+        for (timezone, name) in [(Some("Asia/Tokyo"), "Asia/Tokyo"), (None, "UTC+09:00")] {
+            let data = WeatherData {
+                location: tokyo(timezone),
+                requested_date: "now".to_string(),
+                ..WeatherData::default()
+            }
+            .parse_met_no(tokyo_response())
+            .unwrap();
+
+            assert_eq!(data.timezone, name);
+            // 00:00 UTC is 09:00 in Tokyo, and it's still the current hour
+            let first = data.timestamps[0].time;
+            assert_eq!(
+                (first.hour(), first.offset().local_minus_utc()),
+                (9, 9 * 3600)
+            );
+            assert_eq!(data.current_hour, Some(0));
+            assert_eq!(data.current.unwrap().time, "2024-06-14 09:00");
+            // The sunrise is in the morning there, not in the evening of UTC
+            assert!(data.sunrise.unwrap().starts_with("04:"), "{name}");
+        }
+    }
+
+    #[test]
+    fn days_of_the_timezone_of_the_location() {
+        let data = WeatherData {
+            location: tokyo(Some("Asia/Tokyo")),
+            requested_date: "2024-06-15".to_string(),
+            requested_range: Some(DateRange {
+                start: NaiveDate::from_ymd_opt(2024, 6, 15).unwrap(),
+                end: NaiveDate::from_ymd_opt(2024, 6, 15).unwrap(),
+            }),
+            daily: Some(DailyWeatherData::default()),
+            ..WeatherData::default()
+        }
+        .parse_met_no(tokyo_response())
+        .unwrap();
+
+        // June 15 in Tokyo is from 15:00 to 14:00 UTC, midnight to 23:00 there
+        assert_eq!(data.timestamps.len(), 24);
+        let (first, last) = (data.timestamps[0].time, data.timestamps[23].time);
+        assert_eq!(
+            first.to_rfc3339(),
+            "2024-06-15T00:00:00+09:00",
+            "{first} - {last}"
+        );
+        assert_eq!(last.to_rfc3339(), "2024-06-15T23:00:00+09:00");
+        assert_eq!(data.temperatures.first(), Some(&15.0));
+        // The first hour isn't the current one anymore
+        assert_eq!(data.current_hour, None);
+
+        let daily = data.daily.unwrap();
+        assert_eq!(daily.dates, [NaiveDate::from_ymd_opt(2024, 6, 15).unwrap()]);
+        assert_eq!((daily.min_temps[0], daily.max_temps[0]), (15.0, 38.0));
+        assert_eq!(daily.partial_days, [false]);
+    }
+
+    #[test]
+    fn days_across_midnight() {
+        // Hours from June 14 21:00 in Tokyo (12:00 UTC) to June 15 23:00, then 6 hour steps, with
+        // the conditions of the next hour or of the next 6 hours
+        let entry = |hours: i64, temperature: f64, period: &str, symbol: &str| {
+            serde_json::json!({
+                "time": (DateTime::parse_from_rfc3339("2024-06-14T12:00:00Z").unwrap()
+                    + chrono::Duration::hours(hours))
+                .with_timezone(&Utc),
+                "data": {
+                    "instant": {
+                        "details": {
+                            "air_temperature": temperature,
+                            "wind_speed": 2.0,
+                            "wind_from_direction": 90.0,
+                        },
+                    },
+                    period: { "summary": { "symbol_code": symbol } },
+                },
+            })
+        };
+        let evening = (0..3).map(|hour| entry(hour, 30.0 + hour as f64, "next_1_hours", "rain"));
+        let day = (3..27).map(|hour| {
+            let symbol = match hour % 4 {
+                0 => "cloudy",
+                _ => "clearsky_day",
+            };
+
+            entry(hour, (hour - 3) as f64, "next_1_hours", symbol)
+        });
+        // June 16 at 03:00, 09:00, 15:00 and 21:00
+        let steps = [
+            (-4.0, "snow"),
+            (-1.0, "snow"),
+            (2.0, "cloudy"),
+            (-6.0, "snow"),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (temperature, symbol))| {
+            entry(30 + 6 * i as i64, temperature, "next_6_hours", symbol)
+        });
+        let response = serde_json::from_value(serde_json::json!({
+            "properties": {
+                "meta": { "units": { "air_temperature": "celsius" } },
+                "timeseries": evening.chain(day).chain(steps).collect_vec(),
+            },
+        }))
+        .unwrap();
+
+        let date = |day| NaiveDate::from_ymd_opt(2024, 6, day).unwrap();
+        let data = WeatherData {
+            location: tokyo(Some("Asia/Tokyo")),
+            requested_date: "2024-06-14".to_string(),
+            requested_range: Some(DateRange {
+                start: date(14),
+                end: date(16),
+            }),
+            daily: Some(DailyWeatherData::default()),
+            ..WeatherData::default()
+        }
+        .parse_met_no(response)
+        .unwrap();
+
+        // The days of Tokyo, not the ones of UTC
+        let daily = data.daily.unwrap();
+        assert_eq!(daily.dates, [date(14), date(15), date(16)]);
+        assert_eq!(daily.min_temps, [30.0, 0.0, -6.0]);
+        assert_eq!(daily.max_temps, [32.0, 23.0, 2.0]);
+        assert_eq!(daily.mean_temps, [31.0, 11.5, -2.3]);
+        // The evening only is a part of the day, the 6 hour steps from 03:00 to 21:00 are all of it
+        assert_eq!(daily.partial_days, [true, false, false]);
+        assert_eq!(
+            daily.weather_codes,
+            [
+                WeatherCode::Rain,
+                WeatherCode::ClearSky,
+                WeatherCode::SnowFall
+            ]
+        );
+        assert_eq!(daily.label(0), "Fri 14*");
+        assert_eq!(daily.label(1), "Sat 15");
+    }
+
+    #[test]
+    fn compact_fixture() {
+        let response = crate::data::parse_response_body(
+            include_str!("../../tests/fixtures/met_no_compact.json"),
+            Provider::MetNo,
+        )
+        .unwrap();
+        let data = WeatherData {
+            location: ResolvedLocation {
+                lat: 50.45,
+                lon: 30.52,
+                display_name: "Kyiv, Ukraine".to_string(),
+                timezone: Some("Europe/Kyiv".to_string()),
+                ..ResolvedLocation::default()
+            },
+            requested_date: "now".to_string(),
+            ..WeatherData::default()
+        }
+        .parse_met_no(response)
+        .unwrap();
+
+        assert_eq!(data.timezone, "Europe/Kyiv");
+        assert_eq!(data.unit, "celsius");
+        assert_eq!(data.elevation, Some(179.0));
+        assert_eq!(
+            data.data_updated_at
+                .map(|updated_at| updated_at.to_rfc3339()),
+            Some("2024-06-14T09:41:23+00:00".to_string())
+        );
+
+        // The hourly entries and the 6 hour ones after them, 10:00 UTC is 13:00 in Kyiv
+        assert_eq!(data.temperatures[..3], [17.0, 17.7, 18.4]);
+        assert_eq!(
+            data.timestamps[0].time.to_rfc3339(),
+            "2024-06-14T13:00:00+03:00"
+        );
+
+        let current = data.current.unwrap();
+        assert_eq!(
+            (current.time.as_str(), current.temperature),
+            ("2024-06-14 13:00", 17.0)
+        );
+        assert_eq!(
+            (
+                current.wind_direction.to_string().as_str(),
+                current.wind_speed_unit.as_str()
+            ),
+            ("W", "m/s")
+        );
+    }
+
+    /// Nowcast of the fixture for Oslo
+    fn nowcast(json: &str) -> NowcastData {
+        let json = serde_json::from_str(json).unwrap();
+        let data = WeatherData {
+            location: ResolvedLocation {
+                lat: 59.91,
+                lon: 10.75,
+                display_name: "Oslo, Norway".to_string(),
+                timezone: Some("Europe/Oslo".to_string()),
+                ..ResolvedLocation::default()
+            },
+            time_format: TimeFormat::Hours24,
+            ..WeatherData::default()
+        };
+
+        MetNo.parse_nowcast(&json, &data).unwrap().unwrap()
+    }
+
+    #[test]
+    fn nowcast_covered() {
+        let nowcast = nowcast(include_str!(
+            "../../tests/fixtures/met_no_nowcast_covered.json"
+        ));
+
+        assert_eq!(nowcast.radar_coverage, RadarCoverage::Ok);
+        // The 90 minutes from the first entry, in the time of the location
+        assert_eq!(nowcast.labels.len(), 18);
+        assert_eq!(
+            (nowcast.labels[0].as_str(), nowcast.labels[17].as_str()),
+            ("16:05", "17:30")
+        );
+        assert_eq!(
+            nowcast.precipitation_rates[..8],
+            [0.0, 0.0, 0.0, 0.2, 0.6, 1.4, 2.8, 3.1]
+        );
+    }
+
+    #[test]
+    fn nowcast_uncovered() {
+        let uncovered = include_str!("../../tests/fixtures/met_no_nowcast_uncovered.json");
+        let nowcast = nowcast(uncovered);
+
+        // No intervals to draw, rather than the dry ones
+        assert_eq!(nowcast.radar_coverage, RadarCoverage::NoCoverage);
+        assert!(nowcast.labels.is_empty() && nowcast.precipitation_rates.is_empty());
+
+        let down = uncovered.replace("\"no coverage\"", "\"temporarily unavailable\"");
+        let nowcast = self::nowcast(&down);
+        assert_eq!(
+            nowcast.radar_coverage,
+            RadarCoverage::TemporarilyUnavailable
+        );
+        assert!(nowcast.labels.is_empty());
+    }
+}
@@ -0,0 +1,33 @@
+//! Shape of the met_no MetAlerts responses (GeoJSON with a feature per alert, only the parts we use)
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MetAlertsResponse {
+    #[serde(default)]
+    pub(crate) features: Vec<Feature>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Feature {
+    pub(crate) properties: FeatureProperties,
+    pub(crate) when: Option<When>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct FeatureProperties {
+    /// "gale", "ice", "forestFire", ...
+    pub(crate) event: String,
+    /// "Moderate", "Severe" or "Extreme"
+    pub(crate) severity: Option<String>,
+    /// "Gale warning, yellow level, Vestland"
+    pub(crate) title: Option<String>,
+}
+
+/// Period the alert is in effect for
+#[derive(Debug, Deserialize)]
+pub(crate) struct When {
+    /// Onset and expiry
+    pub(crate) interval: Vec<DateTime<Utc>>,
+}
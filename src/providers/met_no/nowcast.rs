@@ -0,0 +1,46 @@
+//! Shape of the met_no nowcast responses (the precipitation of the next minutes from the radars, only
+//! the parts we use)
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NowcastResponse {
+    pub(crate) properties: NowcastProperties,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NowcastProperties {
+    pub(crate) meta: NowcastMeta,
+    /// Entries every 5 minutes for the next couple of hours (none outside the radar coverage)
+    #[serde(default)]
+    pub(crate) timeseries: Vec<NowcastEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NowcastMeta {
+    /// "ok", "temporarily unavailable" or "no coverage"
+    pub(crate) radar_coverage: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NowcastEntry {
+    pub(crate) time: DateTime<Utc>,
+    pub(crate) data: NowcastEntryData,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NowcastEntryData {
+    pub(crate) instant: NowcastInstant,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NowcastInstant {
+    pub(crate) details: NowcastDetails,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NowcastDetails {
+    /// In mm/h, missing from the entries further ahead than the radars can tell
+    pub(crate) precipitation_rate: Option<f64>,
+}
@@ -0,0 +1,95 @@
+//! Shape of the met_no locationforecast responses (only the parts we use, everything else is
+//! ignored)
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MetNoResponse {
+    pub(crate) geometry: Option<Geometry>,
+    pub(crate) properties: Properties,
+}
+
+/// GeoJSON point of the forecast
+#[derive(Debug, Deserialize)]
+pub(crate) struct Geometry {
+    /// Longitude, latitude and the altitude in m (the requested one, or the one met_no guessed)
+    pub(crate) coordinates: Vec<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Properties {
+    pub(crate) meta: Meta,
+    /// Hourly entries for the next couple of days, 6 hour steps further ahead
+    pub(crate) timeseries: Vec<TimeseriesEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Meta {
+    /// When the forecast was computed
+    pub(crate) updated_at: Option<DateTime<Utc>>,
+    pub(crate) units: Units,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Units {
+    pub(crate) air_temperature: String,
+    pub(crate) wind_speed: Option<String>,
+    pub(crate) precipitation_amount: Option<String>,
+    pub(crate) air_pressure_at_sea_level: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct TimeseriesEntry {
+    pub(crate) time: DateTime<Utc>,
+    pub(crate) data: EntryData,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct EntryData {
+    pub(crate) instant: Instant,
+    /// The entries further ahead only have the next 6 hours
+    pub(crate) next_1_hours: Option<Period>,
+    pub(crate) next_6_hours: Option<Period>,
+}
+
+/// Conditions at the time of the entry
+#[derive(Debug, Deserialize)]
+pub(crate) struct Instant {
+    pub(crate) details: InstantDetails,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct InstantDetails {
+    /// Missing from the entries the model didn't compute them for
+    pub(crate) air_temperature: Option<f64>,
+    pub(crate) wind_speed: Option<f64>,
+    pub(crate) wind_from_direction: Option<f64>,
+    /// In the unit of the wind speed
+    pub(crate) wind_speed_of_gust: Option<f64>,
+    pub(crate) relative_humidity: Option<f64>,
+    pub(crate) air_pressure_at_sea_level: Option<f64>,
+    /// Cloud cover in %
+    pub(crate) cloud_area_fraction: Option<f64>,
+    /// UV index for the clear sky (the only one met_no has)
+    pub(crate) ultraviolet_index_clear_sky: Option<f64>,
+}
+
+/// Conditions over the period after the time of the entry
+#[derive(Debug, Deserialize)]
+pub(crate) struct Period {
+    pub(crate) summary: Option<Summary>,
+    pub(crate) details: Option<PeriodDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Summary {
+    /// "lightrainshowers_day"
+    pub(crate) symbol_code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PeriodDetails {
+    pub(crate) precipitation_amount: Option<f64>,
+    pub(crate) probability_of_precipitation: Option<f64>,
+}
@@ -0,0 +1,834 @@
+//! Requests to open_meteo and the parsing of its responses
+
+pub(crate) mod response;
+
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime};
+use itertools::Itertools;
+use reqwest::Url;
+use serde_json::{Map, Value};
+
+use crate::{
+    config::TemperatureUnit,
+    data::{
+        format_timestamps, missing_field, parse_response, parse_response_body, parse_timestamps,
+        utc_offset_name, valid_value, AirQualityData, CurrentSnapshot, CurrentWeatherData,
+        DailyWeatherData, NowcastData, RadarCoverage, Timestamp, WeatherCode, WeatherData,
+        WindDirection, DAYLIGHT_FORMAT, NOWCAST_MINUTES,
+    },
+    error::{Result, WeatherError},
+    geocode::ResolvedLocation,
+    providers::{
+        request_url, Provider, ProviderCapabilities, ProviderRequestType, QueryParams,
+        RequestOptions, WeatherProvider,
+    },
+};
+
+use self::response::{
+    AirQualityResponse, CurrentResponse, Daily, Hourly, HourlyUnits, NowcastResponse,
+    OpenMeteoResponse,
+};
+
+/// API base URL of the forecasts
+const BASE_URL: &str = "https://api.open-meteo.com/v1";
+
+/// The archive is not on the same host as the forecasts
+const ARCHIVE_URL: &str = "https://archive-api.open-meteo.com/v1";
+
+/// Neither is the air quality, the forecasts and the history are on the same endpoint there
+const AIR_QUALITY_URL: &str = "https://air-quality-api.open-meteo.com/v1/air-quality";
+
+/// Minutes in every step of the nowcast (the `minutely_15` variables)
+const NOWCAST_STEP_MINUTES: i64 = 15;
+
+/// Free forecasts and history from the national weather services, no API key needed
+#[derive(Debug)]
+pub(crate) struct OpenMeteo;
+
+impl WeatherProvider for OpenMeteo {
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            provider: Provider::OpenMeteo,
+            supports_history: true,
+            supports_custom_dates: true,
+            // 16 days of the forecast, today is the first of them
+            forecast_days: 15,
+            history_cutoff_days: Some(5),
+            requires_api_key: false,
+            current_conditions: true,
+            air_quality: true,
+            nowcast: true,
+            language_param: None,
+            terms_url: "https://open-meteo.com/en/terms",
+        }
+    }
+
+    fn coordinate_params(&self) -> (&'static str, &'static str) {
+        ("latitude", "longitude")
+    }
+
+    fn elevation_param(&self) -> Option<&'static str> {
+        Some("elevation")
+    }
+
+    fn build_request(
+        &self,
+        request_type: &ProviderRequestType,
+        mut params: QueryParams,
+        options: &RequestOptions,
+    ) -> Result<Url> {
+        // If the request type is forecast, it means that we can also ask for current weather
+        // conditions from the endpoint
+        if matches!(request_type, ProviderRequestType::Forecast) {
+            params.push(("current_weather", "true".to_string()));
+        }
+
+        // Add the parameter to the get hourly forecast (the archive has no precipitation
+        // probability or UV index)
+        params.push((
+            "hourly",
+            format!(
+                "temperature_2m,windspeed_10m,winddirection_10m,windgusts_10m,precipitation,\
+                relativehumidity_2m,apparent_temperature,weathercode,surface_pressure,cloudcover{}",
+                match request_type {
+                    ProviderRequestType::Forecast => ",precipitation_probability,uv_index",
+                    ProviderRequestType::History => "",
+                }
+            ),
+        ));
+
+        // The daily summaries if they were asked for, and the sunrise/sunset for the current
+        // conditions
+        let daily = [
+            options
+                .daily
+                .then_some("temperature_2m_max,temperature_2m_min,weathercode"),
+            matches!(request_type, ProviderRequestType::Forecast).then_some("sunrise,sunset"),
+        ]
+        .into_iter()
+        .flatten()
+        .join(",");
+
+        if !daily.is_empty() {
+            params.push(("daily", daily));
+        }
+
+        // Everything in the local time of the location (GMT otherwise), the days only make sense
+        // in it
+        params.push(("timezone", "auto".to_string()));
+
+        // Celsius is the default
+        if options.unit == TemperatureUnit::Fahrenheit {
+            params.push(("temperature_unit", "fahrenheit".to_string()));
+        }
+
+        match request_type {
+            ProviderRequestType::Forecast => request_url(&format!("{BASE_URL}/forecast"), &params),
+            ProviderRequestType::History => request_url(&format!("{ARCHIVE_URL}/archive"), &params),
+        }
+    }
+
+    fn parse(&self, json: &Map<String, Value>, data: WeatherData) -> Result<WeatherData> {
+        data.parse_open_meteo(parse_response(json, Provider::OpenMeteo)?)
+    }
+
+    fn current_url(
+        &self,
+        mut params: QueryParams,
+        options: &RequestOptions,
+        hours: usize,
+    ) -> Option<Url> {
+        // From the current hour on, the temperatures and nothing else
+        params.extend([
+            ("current_weather", "true".to_string()),
+            ("hourly", "temperature_2m".to_string()),
+            ("forecast_hours", hours.max(1).to_string()),
+            ("timezone", "auto".to_string()),
+        ]);
+
+        if options.unit == TemperatureUnit::Fahrenheit {
+            params.push(("temperature_unit", "fahrenheit".to_string()));
+        }
+
+        request_url(&format!("{BASE_URL}/forecast"), &params).ok()
+    }
+
+    fn parse_current(
+        &self,
+        body: &str,
+        location: ResolvedLocation,
+        hours: usize,
+    ) -> Result<CurrentSnapshot> {
+        let response: CurrentResponse = parse_response_body(body, Provider::OpenMeteo)?;
+
+        if response.error {
+            return Err(WeatherError::Provider(format!(
+                "Error response from open_meteo: {}",
+                response.reason.unwrap_or_default()
+            )));
+        }
+
+        // The same as the full response: the hours are only used with the temperatures lined up
+        // with them and with their unit, the current conditions are shown alone otherwise
+        let hourly = response
+            .hourly
+            .zip(response.hourly_units.temperature_2m)
+            .and_then(|(hourly, unit)| {
+                let temperatures = with_gaps(hourly.temperature_2m.as_deref()?);
+
+                (hourly.time.len() == temperatures.len()).then_some((
+                    hourly.time,
+                    temperatures,
+                    unit,
+                ))
+            });
+        let (times, temperatures, unit) = match (hourly, &response.current_weather) {
+            (Some(hourly), _) => hourly,
+            (None, Some(_)) => (
+                Vec::new(),
+                Vec::new(),
+                response
+                    .current_weather_units
+                    .and_then(|units| units.temperature)
+                    .unwrap_or("°C".to_string()),
+            ),
+            (None, None) => return Err(missing_field(Provider::OpenMeteo, "hourly")),
+        };
+
+        // "2024-01-01T13:15" is in the "2024-01-01T13:00" hour
+        let current_hour = response.current_weather.as_ref().and_then(|current| {
+            let hour = current.time.get(..13)?;
+
+            times.iter().position(|time| time.get(..13) == Some(hour))
+        });
+
+        Ok(CurrentSnapshot::new(
+            (location, unit),
+            response.current_weather.map(|current| {
+                (
+                    current.temperature,
+                    WeatherCode::from_open_meteo(current.weathercode),
+                )
+            }),
+            &temperatures[current_hour.unwrap_or_default()..],
+            hours,
+        ))
+    }
+
+    fn air_quality_url(&self, params: &QueryParams) -> Option<Url> {
+        // The elevation is not taken there
+        let params = params
+            .iter()
+            .filter(|(name, _)| ["latitude", "longitude", "start_date", "end_date"].contains(name))
+            .cloned()
+            .chain([
+                ("hourly", "european_aqi,pm2_5,pm10".to_string()),
+                ("timezone", "auto".to_string()),
+            ])
+            .collect::<QueryParams>();
+
+        request_url(AIR_QUALITY_URL, &params).ok()
+    }
+
+    fn parse_air_quality(
+        &self,
+        json: &Map<String, Value>,
+        data: &WeatherData,
+    ) -> Result<Option<AirQualityData>> {
+        let response: AirQualityResponse = parse_response(json, Provider::OpenMeteo)?;
+
+        if response.error {
+            return Err(WeatherError::Provider(format!(
+                "Error response from open_meteo: {}",
+                response.reason.unwrap_or_default()
+            )));
+        }
+
+        let Some(hourly) = response.hourly else {
+            return Ok(None);
+        };
+
+        // The day of the current conditions, or the requested one if there are none (both are in
+        // the local time of the location, like the hours of the response)
+        let current_hour = data
+            .current
+            .as_ref()
+            .and_then(|current| current.time.get(..13))
+            .and_then(|hour| {
+                NaiveDateTime::parse_from_str(&format!("{hour}:00"), "%Y-%m-%d %H:%M").ok()
+            });
+        let Some(date) = current_hour
+            .map(|hour| hour.date())
+            .or_else(|| NaiveDate::parse_from_str(&data.requested_date, "%Y-%m-%d").ok())
+        else {
+            return Ok(None);
+        };
+
+        let times = parse_timestamps(hourly.time.iter().map(|t| Some(t.replace('T', " "))))?;
+        let value = |values: &Option<Vec<Option<f64>>>, i: usize| {
+            values
+                .as_ref()
+                .and_then(|values| values.get(i).copied().flatten())
+        };
+
+        Ok(Some(AirQualityData::from_hourly(
+            date,
+            current_hour,
+            times.into_iter().enumerate().map(|(i, time)| {
+                (
+                    time,
+                    value(&hourly.european_aqi, i),
+                    value(&hourly.pm2_5, i),
+                    value(&hourly.pm10, i),
+                )
+            }),
+        )))
+    }
+
+    fn nowcast_url(&self, params: &QueryParams) -> Option<Url> {
+        // The steps are counted from the current one, so there are no dates
+        let params = params
+            .iter()
+            .filter(|(name, _)| ["latitude", "longitude", "elevation"].contains(name))
+            .cloned()
+            .chain([
+                ("minutely_15", "precipitation".to_string()),
+                (
+                    "forecast_minutely_15",
+                    (NOWCAST_MINUTES / NOWCAST_STEP_MINUTES).to_string(),
+                ),
+                ("timezone", "auto".to_string()),
+            ])
+            .collect::<QueryParams>();
+
+        request_url(&format!("{BASE_URL}/forecast"), &params).ok()
+    }
+
+    fn parse_nowcast(
+        &self,
+        json: &Map<String, Value>,
+        data: &WeatherData,
+    ) -> Result<Option<NowcastData>> {
+        let response: NowcastResponse = parse_response(json, Provider::OpenMeteo)?;
+
+        if response.error {
+            return Err(WeatherError::Provider(format!(
+                "Error response from open_meteo: {}",
+                response.reason.unwrap_or_default()
+            )));
+        }
+
+        let Some(minutely) = response.minutely_15 else {
+            return Ok(None);
+        };
+
+        // The 15 minute steps are there everywhere (interpolated from the hours where the models
+        // don't have them), so there is nothing like the radar coverage to check
+        let times = parse_timestamps(minutely.time.iter().map(|t| Some(t.replace('T', " "))))?;
+        let rates = minutely.precipitation.unwrap_or_default();
+
+        Ok(Some(NowcastData::new(
+            times.into_iter().enumerate().map(|(i, time)| {
+                (
+                    time,
+                    // From the mm of the step to mm/h
+                    rates
+                        .get(i)
+                        .copied()
+                        .flatten()
+                        .map(|mm| mm * (60 / NOWCAST_STEP_MINUTES) as f64),
+                )
+            }),
+            data.time_format,
+            RadarCoverage::Ok,
+        )))
+    }
+}
+
+impl From<response::CurrentWeather> for CurrentWeatherData {
+    fn from(current: response::CurrentWeather) -> Self {
+        Self {
+            time: current.time.replace('T', " "),
+            temperature: current.temperature,
+            weather_code: WeatherCode::from_open_meteo(current.weathercode),
+            wind_speed: current.windspeed,
+            wind_speed_unit: "km/h".to_string(),
+            wind_direction: WindDirection::from_degrees(current.winddirection),
+            // Only the hourly data has them
+            precipitation_probability: None,
+            apparent_temperature: None,
+            humidity: None,
+            uv_index: None,
+            // Calculated once the response is parsed
+            wind_chill: None,
+        }
+    }
+}
+
+impl WeatherData {
+    fn parse_open_meteo(mut self, response: OpenMeteoResponse) -> Result<Self> {
+        if response.error {
+            return Err(WeatherError::Provider(format!(
+                "Error response from open_meteo: {}",
+                response.reason.unwrap_or_default()
+            )));
+        }
+
+        // The times are in the local time of the location, as long as it was asked for
+        self.timezone = match (response.timezone, response.utc_offset_seconds) {
+            (Some(timezone), _) => timezone,
+            (None, Some(offset)) => utc_offset_name(offset),
+            (None, None) => "GMT".to_string(),
+        };
+        // GMT if the offset is missing too
+        let utc_offset = response
+            .utc_offset_seconds
+            .and_then(|offset| FixedOffset::east_opt(i32::try_from(offset).ok()?))
+            .unwrap_or(FixedOffset::east_opt(0).expect("UTC is a valid offset"));
+        self.elevation = response.elevation;
+
+        let OpenMeteoResponse {
+            current_weather,
+            current_weather_units,
+            hourly,
+            hourly_units,
+            daily,
+            ..
+        } = response;
+
+        // Current weather is only there for the forecasts
+        self.current = current_weather.map(CurrentWeatherData::from);
+
+        // Hourly data missing is not the end of the world if we can show the current weather
+        // conditions instead
+        (self.timestamps, self.temperatures, self.unit) = match (
+            Self::parse_open_meteo_hourly(
+                hourly.as_ref(),
+                &hourly_units,
+                utc_offset,
+                self.timestamp_format(),
+            ),
+            &self.current,
+        ) {
+            (Ok(hourly), _) => hourly,
+            (Err(err), Some(_)) => {
+                self.caveat = Some(format!(
+                    "Hourly data is not available ({err}), showing the current conditions only"
+                ));
+
+                let unit = current_weather_units
+                    .and_then(|units| units.temperature)
+                    .unwrap_or("°C".to_string());
+
+                (Vec::new(), Vec::new(), unit)
+            }
+            (Err(err), None) => return Err(err),
+        };
+
+        // Nothing to line the rest of the hourly series up with if the temperatures are missing
+        if let (Some(hourly), false) = (&hourly, self.timestamps.is_empty()) {
+            self.parse_open_meteo_hourly_series(hourly, &hourly_units)?;
+        }
+
+        if self.daily.is_some() {
+            self.daily = Some(Self::parse_open_meteo_daily(
+                daily.as_ref(),
+                hourly.as_ref(),
+            )?);
+        }
+
+        (self.sunrise, self.sunset) = Self::parse_open_meteo_daylight(daily.as_ref());
+
+        Ok(self)
+    }
+
+    /// Parse the hourly timestamps (which are `utc_offset` ahead of UTC) and temperatures, and the
+    /// unit of the temperatures
+    fn parse_open_meteo_hourly(
+        hourly: Option<&Hourly>,
+        units: &HourlyUnits,
+        utc_offset: FixedOffset,
+        timestamp_format: &str,
+    ) -> Result<(Vec<Timestamp>, Vec<f64>, String)> {
+        let hourly = hourly.ok_or(missing_field(Provider::OpenMeteo, "hourly"))?;
+        let temperatures = hourly
+            .temperature_2m
+            .as_deref()
+            .map(with_gaps)
+            .ok_or(missing_field(Provider::OpenMeteo, "hourly.temperature_2m"))?;
+
+        if hourly.time.len() != temperatures.len() {
+            return Err(WeatherError::InvalidResponse(format!(
+                "Mismatch in timestamps ({}) and temperatures ({}), please try a different \
+                provider/location/date",
+                hourly.time.len(),
+                temperatures.len()
+            )));
+        }
+
+        let timestamps = format_timestamps(
+            hourly.time.iter().map(|t| Some(t.replace('T', " "))),
+            utc_offset,
+            timestamp_format,
+        )?;
+        let unit = units.temperature_2m.clone().ok_or(missing_field(
+            Provider::OpenMeteo,
+            "hourly_units.temperature_2m",
+        ))?;
+
+        Ok((timestamps, temperatures, unit))
+    }
+
+    /// Parse the rest of the hourly series that were returned: the wind, the precipitation and its
+    /// probability (only the forecasts have it), the apparent temperatures, the humidity, the
+    /// pressure and the cloud cover
+    fn parse_open_meteo_hourly_series(
+        &mut self,
+        hourly: &Hourly,
+        units: &HourlyUnits,
+    ) -> Result<()> {
+        if let (Some(speeds), Some(directions)) = (&hourly.windspeed_10m, &hourly.winddirection_10m)
+        {
+            self.wind_speeds = with_gaps(speeds);
+            self.wind_directions = directions
+                .iter()
+                .map(|direction| WindDirection::from_degrees(direction.unwrap_or(f64::NAN)))
+                .collect();
+            self.wind_speed_unit = units.windspeed_10m.clone().unwrap_or("km/h".to_string());
+        }
+
+        if let Some(precipitation) = &hourly.precipitation {
+            self.precipitation = with_gaps(precipitation);
+            self.precipitation_unit = units.precipitation.clone().unwrap_or("mm".to_string());
+        }
+
+        if let Some(probabilities) = &hourly.precipitation_probability {
+            self.precipitation_probabilities = probabilities
+                .iter()
+                .map(|p| p.unwrap_or_default())
+                .collect();
+        }
+
+        if let Some(codes) = &hourly.weathercode {
+            self.weather_codes = codes
+                .iter()
+                .map(|code| code.map(WeatherCode::from_open_meteo).unwrap_or_default())
+                .collect();
+        }
+
+        self.check_hourly_lengths()?;
+
+        // These are only extras, so the hours without a value are NaN, and the series that don't
+        // line up with the timestamps are left out instead of failing the whole response
+        let extra_series = |values: &Option<Vec<Option<f64>>>| {
+            values
+                .as_ref()
+                .filter(|values| values.len() == self.timestamps.len())
+                .map(|values| with_gaps(values))
+                .unwrap_or_default()
+        };
+        self.apparent_temperatures = extra_series(&hourly.apparent_temperature);
+        self.humidity = extra_series(&hourly.relativehumidity_2m);
+        self.pressures = extra_series(&hourly.surface_pressure);
+        self.pressure_unit = units.surface_pressure.clone().unwrap_or("hPa".to_string());
+        self.cloud_cover = extra_series(&hourly.cloudcover);
+        self.wind_gusts = match self.wind_speeds.is_empty() {
+            true => Vec::new(),
+            false => extra_series(&hourly.windgusts_10m),
+        };
+        // The archive doesn't have the UV index
+        self.uv_indices = extra_series(&hourly.uv_index);
+
+        // The current conditions don't have these, so they are taken from the current hour
+        if let Some(current) = &mut self.current {
+            // "2024-01-01 13:15" is in the "2024-01-01T13:00" hour
+            let current_hour = current.time.get(..13).map(|hour| hour.replace(' ', "T"));
+
+            if let Some(i) = hourly
+                .time
+                .iter()
+                .position(|time| time.get(..13) == current_hour.as_deref())
+            {
+                self.current_hour = Some(i);
+                current.precipitation_probability =
+                    self.precipitation_probabilities.get(i).copied();
+                current.apparent_temperature = valid_value(&self.apparent_temperatures, i);
+                current.humidity = valid_value(&self.humidity, i);
+                current.uv_index = valid_value(&self.uv_indices, i);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parse the daily minimums, maximums and conditions, the means are calculated from the hourly
+    /// data
+    fn parse_open_meteo_daily(
+        daily: Option<&Daily>,
+        hourly: Option<&Hourly>,
+    ) -> Result<DailyWeatherData> {
+        let daily = daily.ok_or(missing_field(Provider::OpenMeteo, "daily"))?;
+        let dates = daily.time.clone();
+        let min_temps = daily
+            .temperature_2m_min
+            .as_deref()
+            .map(with_gaps)
+            .ok_or(missing_field(
+                Provider::OpenMeteo,
+                "daily.temperature_2m_min",
+            ))?;
+        let max_temps = daily
+            .temperature_2m_max
+            .as_deref()
+            .map(with_gaps)
+            .ok_or(missing_field(
+                Provider::OpenMeteo,
+                "daily.temperature_2m_max",
+            ))?;
+
+        if min_temps.len() != dates.len() || max_temps.len() != dates.len() {
+            return Err(WeatherError::InvalidResponse(format!(
+                "Mismatch in dates ({}) and daily temperatures ({} minimums, {} maximums), please \
+                try a different provider/location/date",
+                dates.len(),
+                min_temps.len(),
+                max_temps.len()
+            )));
+        }
+
+        // The means are not in the daily data, so they come from the hourly one
+        let hourly = hourly.ok_or(missing_field(Provider::OpenMeteo, "hourly"))?;
+        let times = parse_timestamps(hourly.time.iter().map(|t| Some(t.replace('T', " "))))?;
+        let temperatures = hourly
+            .temperature_2m
+            .as_deref()
+            .map(with_gaps)
+            .ok_or(missing_field(Provider::OpenMeteo, "hourly.temperature_2m"))?;
+        let hourly_summary = DailyWeatherData::from_hourly(times.into_iter().zip(temperatures));
+
+        let mean_temps = dates
+            .iter()
+            .map(|date| {
+                hourly_summary
+                    .dates
+                    .iter()
+                    .position(|day| day == date)
+                    .map(|i| hourly_summary.mean_temps[i])
+                    .ok_or(WeatherError::InvalidResponse(format!(
+                        "No hourly data for {date}"
+                    )))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Only extras, so they are left out if they don't line up with the days
+        let weather_codes = daily
+            .weathercode
+            .as_ref()
+            .filter(|codes| codes.len() == dates.len())
+            .map(|codes| {
+                codes
+                    .iter()
+                    .map(|code| code.map(WeatherCode::from_open_meteo).unwrap_or_default())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(DailyWeatherData {
+            // The daily data always covers whole days
+            partial_days: vec![false; dates.len()],
+            weather_codes,
+            dates,
+            min_temps,
+            max_temps,
+            mean_temps,
+        })
+    }
+
+    /// Parse the sunrise and the sunset of the first day, if they were returned (only the forecasts
+    /// have them)
+    fn parse_open_meteo_daylight(daily: Option<&Daily>) -> (Option<String>, Option<String>) {
+        let time = |times: &Option<Vec<Option<String>>>| {
+            times
+                .as_ref()?
+                .first()?
+                .as_deref()
+                .and_then(|t| NaiveDateTime::parse_from_str(t, "%Y-%m-%dT%H:%M").ok())
+                .map(|t| t.format(DAYLIGHT_FORMAT).to_string())
+        };
+
+        match daily {
+            Some(daily) => (time(&daily.sunrise), time(&daily.sunset)),
+            None => (None, None),
+        }
+    }
+}
+
+/// Values of a series with the nulls as NaN, so that they stay lined up with the timestamps
+fn with_gaps(values: &[Option<f64>]) -> Vec<f64> {
+    values
+        .iter()
+        .map(|value| value.unwrap_or(f64::NAN))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Timelike;
+
+    use super::*;
+    use crate::{config::TimeFormat, date::DateRange};
+
+    /// Response captured from the API, as a JSON map the way the provider gets it
+    fn fixture(json: &str) -> Map<String, Value> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    /// Data for the day of the forecast fixture, with the daily summary
+    fn kyiv_day() -> WeatherData {
+        WeatherData {
+            location: ResolvedLocation {
+                lat: 50.45,
+                lon: 30.52,
+                display_name: "Kyiv, Ukraine".to_string(),
+                ..ResolvedLocation::default()
+            },
+            requested_date: "2024-06-14".to_string(),
+            daily: Some(DailyWeatherData::default()),
+            ..WeatherData::default()
+        }
+    }
+
+    #[test]
+    fn forecast_fixture() {
+        let json = fixture(include_str!(
+            "../../tests/fixtures/open_meteo_forecast.json"
+        ));
+        let data = OpenMeteo.parse(&json, kyiv_day()).unwrap();
+
+        assert_eq!(data.timezone, "Europe/Kiev");
+        assert_eq!(data.elevation, Some(169.0));
+        assert_eq!(data.unit, "°C");
+
+        // Every hour of the day, in the local time of the location
+        assert_eq!((data.timestamps.len(), data.temperatures.len()), (24, 24));
+        let first = data.timestamps[0].time;
+        assert_eq!(
+            (first.hour(), first.offset().local_minus_utc()),
+            (0, 3 * 3600)
+        );
+        assert_eq!(data.temperatures[9], 14.2);
+
+        let current = data.current.unwrap();
+        assert_eq!(
+            (current.time.as_str(), current.temperature),
+            ("2024-06-14 12:00", 19.6)
+        );
+
+        let daily = data.daily.unwrap();
+        assert_eq!(daily.dates, [NaiveDate::from_ymd_opt(2024, 6, 14).unwrap()]);
+        assert_eq!((daily.min_temps[0], daily.max_temps[0]), (6.2, 22.2));
+        assert!(data.sunrise.is_some() && data.sunset.is_some());
+    }
+
+    #[test]
+    fn archive_fixture() {
+        // Two days of the archive: no current conditions and no sunrise/sunset, only the hours
+        let json = fixture(include_str!("../../tests/fixtures/open_meteo_archive.json"));
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let data = WeatherData {
+            request_type: ProviderRequestType::History,
+            requested_date: "2024-01-01".to_string(),
+            requested_range: Some(DateRange {
+                start,
+                end: start.succ_opt().unwrap(),
+            }),
+            daily: None,
+            ..kyiv_day()
+        };
+        let data = OpenMeteo.parse(&json, data).unwrap();
+
+        assert!(data.current.is_none());
+        assert_eq!(data.caveat, None);
+        assert_eq!((data.sunrise, data.sunset), (None, None));
+        assert_eq!(data.timezone, "Europe/Kiev");
+        assert_eq!(data.unit, "°C");
+
+        // Both days, in the winter time of the location
+        assert_eq!((data.timestamps.len(), data.temperatures.len()), (48, 48));
+        let (first, last) = (data.timestamps[0].time, data.timestamps[47].time);
+        assert_eq!(
+            (
+                first.date_naive(),
+                first.hour(),
+                first.offset().local_minus_utc()
+            ),
+            (start, 0, 2 * 3600)
+        );
+        assert_eq!(
+            (last.date_naive(), last.hour()),
+            (start.succ_opt().unwrap(), 23)
+        );
+        assert_eq!(
+            (
+                data.temperatures[0],
+                data.temperatures[9],
+                data.temperatures[47]
+            ),
+            (-8.8, -6.0, -6.5)
+        );
+        assert_eq!(data.precipitation[11], 0.5);
+        // The snow of the morning
+        assert_eq!(data.weather_codes[11], WeatherCode::SnowFall);
+    }
+
+    #[test]
+    fn error_fixture() {
+        let json = fixture(include_str!("../../tests/fixtures/open_meteo_error.json"));
+
+        assert!(matches!(
+            OpenMeteo.parse(&json, kyiv_day()),
+            Err(WeatherError::Provider(message))
+                if message == "Error response from open_meteo: Cannot initialize WeatherVariable \
+                    from invalid String value temperature_3m for key hourly"
+        ));
+    }
+
+    #[test]
+    fn error_names_the_path() {
+        let mut json = fixture(include_str!(
+            "../../tests/fixtures/open_meteo_forecast.json"
+        ));
+        json["hourly"]["temperature_2m"][3] = "warm".into();
+
+        let Err(WeatherError::InvalidResponse(message)) = OpenMeteo.parse(&json, kyiv_day()) else {
+            panic!("the invalid temperature was parsed");
+        };
+        assert!(
+            message
+                .starts_with("Couldn't parse the open_meteo response at hourly.temperature_2m[3]"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn nowcast_fixture() {
+        let json = fixture(include_str!("../../tests/fixtures/open_meteo_nowcast.json"));
+        let data = WeatherData {
+            time_format: TimeFormat::Hours24,
+            ..kyiv_day()
+        };
+        let nowcast = OpenMeteo.parse_nowcast(&json, &data).unwrap().unwrap();
+
+        // Everywhere is covered, the 15 minutes of the 90 are in mm/h
+        assert_eq!(nowcast.radar_coverage, RadarCoverage::Ok);
+        assert_eq!(
+            nowcast.labels,
+            ["16:00", "16:15", "16:30", "16:45", "17:00", "17:15"]
+        );
+        assert_eq!(nowcast.precipitation_rates[..5], [0.0, 0.4, 1.6, 3.2, 1.2]);
+        assert!(nowcast.precipitation_rates[5].is_nan());
+
+        // Without the 15 minute steps there is no nowcast
+        let json = fixture(r#"{"utc_offset_seconds": 7200}"#);
+        assert!(OpenMeteo.parse_nowcast(&json, &data).unwrap().is_none());
+    }
+}
@@ -0,0 +1,157 @@
+//! Shape of the open_meteo responses (only the parts we use, everything else is ignored)
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+/// Response of the forecast and the archive endpoints, or the error response
+/// (`{"error": true, "reason": "..."}`)
+#[derive(Debug, Deserialize)]
+pub(crate) struct OpenMeteoResponse {
+    #[serde(default)]
+    pub(crate) error: bool,
+    pub(crate) reason: Option<String>,
+
+    /// Timezone of the location the times are in ("Asia/Tokyo"), with its offset from UTC
+    pub(crate) timezone: Option<String>,
+    pub(crate) utc_offset_seconds: Option<i64>,
+    /// Elevation in m the data is for (the requested one, or the one of the grid cell)
+    pub(crate) elevation: Option<f64>,
+
+    /// Only the forecasts have it
+    pub(crate) current_weather: Option<CurrentWeather>,
+    pub(crate) current_weather_units: Option<CurrentWeatherUnits>,
+
+    pub(crate) hourly: Option<Hourly>,
+    #[serde(default)]
+    pub(crate) hourly_units: HourlyUnits,
+
+    /// Only there if any of the daily variables were requested
+    pub(crate) daily: Option<Daily>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CurrentWeather {
+    /// "2024-01-01T13:15"
+    pub(crate) time: String,
+    pub(crate) temperature: f64,
+    /// WMO weather interpretation code
+    pub(crate) weathercode: u64,
+    pub(crate) windspeed: f64,
+    pub(crate) winddirection: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CurrentWeatherUnits {
+    pub(crate) temperature: Option<String>,
+}
+
+/// Hourly series, lined up with the times (every variable is only there if it was requested and
+/// the endpoint has it)
+#[derive(Debug, Deserialize)]
+pub(crate) struct Hourly {
+    /// "2024-01-01T13:00"
+    pub(crate) time: Vec<String>,
+    /// The hours without a value are null (the most recent hours of the archive, some stations)
+    pub(crate) temperature_2m: Option<Vec<Option<f64>>>,
+    pub(crate) windspeed_10m: Option<Vec<Option<f64>>>,
+    pub(crate) winddirection_10m: Option<Vec<Option<f64>>>,
+    /// In the unit of the wind speeds
+    pub(crate) windgusts_10m: Option<Vec<Option<f64>>>,
+    pub(crate) precipitation: Option<Vec<Option<f64>>>,
+    pub(crate) precipitation_probability: Option<Vec<Option<f64>>>,
+    pub(crate) relativehumidity_2m: Option<Vec<Option<f64>>>,
+    pub(crate) apparent_temperature: Option<Vec<Option<f64>>>,
+    pub(crate) uv_index: Option<Vec<Option<f64>>>,
+    pub(crate) surface_pressure: Option<Vec<Option<f64>>>,
+    /// Cloud cover in %
+    pub(crate) cloudcover: Option<Vec<Option<f64>>>,
+    /// WMO weather interpretation codes
+    pub(crate) weathercode: Option<Vec<Option<u64>>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct HourlyUnits {
+    pub(crate) temperature_2m: Option<String>,
+    pub(crate) windspeed_10m: Option<String>,
+    pub(crate) precipitation: Option<String>,
+    pub(crate) surface_pressure: Option<String>,
+}
+
+/// Daily series, lined up with the dates
+#[derive(Debug, Deserialize)]
+pub(crate) struct Daily {
+    pub(crate) time: Vec<NaiveDate>,
+    /// The days without a value are null
+    pub(crate) temperature_2m_max: Option<Vec<Option<f64>>>,
+    pub(crate) temperature_2m_min: Option<Vec<Option<f64>>>,
+    /// WMO weather interpretation code of the most severe conditions of the day
+    pub(crate) weathercode: Option<Vec<Option<u64>>>,
+    /// "2024-01-01T07:45", null during the polar days and nights
+    pub(crate) sunrise: Option<Vec<Option<String>>>,
+    pub(crate) sunset: Option<Vec<Option<String>>>,
+}
+
+/// Response of the forecast endpoint with only the current conditions and the temperatures, or the
+/// error response
+#[derive(Debug, Deserialize)]
+pub(crate) struct CurrentResponse {
+    #[serde(default)]
+    pub(crate) error: bool,
+    pub(crate) reason: Option<String>,
+
+    pub(crate) current_weather: Option<CurrentWeather>,
+    pub(crate) current_weather_units: Option<CurrentWeatherUnits>,
+
+    pub(crate) hourly: Option<CurrentHourly>,
+    #[serde(default)]
+    pub(crate) hourly_units: HourlyUnits,
+}
+
+/// Hourly temperatures, lined up with the times
+#[derive(Debug, Deserialize)]
+pub(crate) struct CurrentHourly {
+    /// "2024-01-01T13:00"
+    pub(crate) time: Vec<String>,
+    pub(crate) temperature_2m: Option<Vec<Option<f64>>>,
+}
+
+/// Response of the air quality endpoint, or the error response
+#[derive(Debug, Deserialize)]
+pub(crate) struct AirQualityResponse {
+    #[serde(default)]
+    pub(crate) error: bool,
+    pub(crate) reason: Option<String>,
+
+    pub(crate) hourly: Option<AirQualityHourly>,
+}
+
+/// Hourly air quality, lined up with the times (the hours without a value are null)
+#[derive(Debug, Deserialize)]
+pub(crate) struct AirQualityHourly {
+    /// "2024-01-01T13:00"
+    pub(crate) time: Vec<String>,
+    /// European Air Quality Index
+    pub(crate) european_aqi: Option<Vec<Option<f64>>>,
+    /// Particulate matter in μg/m³
+    pub(crate) pm2_5: Option<Vec<Option<f64>>>,
+    pub(crate) pm10: Option<Vec<Option<f64>>>,
+}
+
+/// Response of the forecast endpoint with only the 15 minute precipitation, or the error response
+#[derive(Debug, Deserialize)]
+pub(crate) struct NowcastResponse {
+    #[serde(default)]
+    pub(crate) error: bool,
+    pub(crate) reason: Option<String>,
+
+    pub(crate) minutely_15: Option<Minutely15>,
+}
+
+/// Precipitation of every 15 minutes, lined up with the times (the ones without a value are null)
+#[derive(Debug, Deserialize)]
+pub(crate) struct Minutely15 {
+    /// "2024-01-01T13:15"
+    pub(crate) time: Vec<String>,
+    /// In mm over the 15 minutes
+    pub(crate) precipitation: Option<Vec<Option<f64>>>,
+}
@@ -0,0 +1,344 @@
+//! Requests to open_weather_map and the parsing of its responses (they are read as plain JSON,
+//! there are no response types for them)
+
+use chrono::{FixedOffset, NaiveDateTime};
+use itertools::Itertools;
+use reqwest::Url;
+use serde_json::{Map, Value};
+
+use crate::{
+    data::{
+        utc_offset_name, with_utc_offset, CurrentWeatherData, DailyWeatherData, Timestamp,
+        WeatherCode, WeatherData, WindDirection, DAYLIGHT_FORMAT,
+    },
+    error::{Result, WeatherError},
+    providers::{
+        request_url, Provider, ProviderCapabilities, ProviderRequestType, QueryParams,
+        RequestOptions, WeatherProvider,
+    },
+};
+
+/// API base URL of the One Call API
+const BASE_URL: &str = "https://api.openweathermap.org/data/3.0";
+
+/// OpenWeatherMap One Call API, which needs an API key
+#[derive(Debug)]
+pub(crate) struct OpenWeatherMap;
+
+impl WeatherProvider for OpenWeatherMap {
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            provider: Provider::OpenWeatherMap,
+            supports_history: false,
+            supports_custom_dates: false,
+            // Only the hourly data is requested, and there are 48 hours of it
+            forecast_days: 2,
+            history_cutoff_days: None,
+            requires_api_key: true,
+            current_conditions: true,
+            air_quality: false,
+            nowcast: false,
+            language_param: Some("lang"),
+            terms_url: "https://openweathermap.org/terms",
+        }
+    }
+
+    fn build_request(
+        &self,
+        request_type: &ProviderRequestType,
+        mut params: QueryParams,
+        options: &RequestOptions,
+    ) -> Result<Url> {
+        if matches!(request_type, ProviderRequestType::History) {
+            return Err(WeatherError::Unsupported(format!(
+                "History is not supported by {} provider",
+                Provider::OpenWeatherMap
+            )));
+        }
+
+        // Ask for °C like the other providers return, and only for the current and the hourly
+        // data
+        params.push(("units", "metric".to_string()));
+        params.push(("exclude", "minutely,daily,alerts".to_string()));
+
+        let api_key = options
+            .api_key
+            .as_ref()
+            .ok_or(WeatherError::MissingApiKey {
+                provider: Provider::OpenWeatherMap,
+            })?;
+        params.push(("appid", api_key.clone()));
+
+        request_url(&format!("{BASE_URL}/onecall"), &params)
+    }
+
+    fn parse(&self, json: &Map<String, Value>, data: WeatherData) -> Result<WeatherData> {
+        data.parse_open_weather_map_json(json)
+    }
+}
+
+impl WeatherData {
+    fn parse_open_weather_map_json(mut self, json: &Map<String, Value>) -> Result<Self> {
+        // Errors come as {"cod": 401, "message": "..."}
+        if let Some(Value::String(message)) = json.get("message") {
+            return Err(WeatherError::Provider(format!(
+                "Error response from open_weather_map: {}",
+                message
+            )));
+        }
+
+        // Timestamps are in UTC, so shift them to the local time of the location
+        let timezone_offset = json
+            .get("timezone_offset")
+            .and_then(|o| o.as_i64())
+            .unwrap_or_default();
+        self.timezone = json
+            .get("timezone")
+            .and_then(|t| t.as_str())
+            .map(str::to_string)
+            .unwrap_or(utc_offset_name(timezone_offset));
+        let local_time = |dt: &Value| {
+            dt.as_i64()
+                .and_then(|dt| chrono::DateTime::from_timestamp(dt + timezone_offset, 0))
+                .map(|dt| dt.naive_utc())
+        };
+
+        // We request the metric units
+        self.unit = "°C".to_string();
+
+        let Value::Array(hourly) = json.get("hourly").ok_or(WeatherError::InvalidResponse(
+            "Hourly data not found".to_string(),
+        ))?
+        else {
+            return Err(WeatherError::InvalidResponse(
+                "Couldn't parse hourly data".to_string(),
+            ));
+        };
+
+        let requested_range = self.requested_days();
+        let timestamp_format = self.timestamp_format();
+
+        // Pick the requested days out of everything the API returned
+        let hours = hourly
+            .iter()
+            .filter(|hour| match requested_range {
+                Some(range) => hour
+                    .get("dt")
+                    .and_then(local_time)
+                    .is_some_and(|dt| range.contains(dt.date())),
+                None => true,
+            })
+            .take(match requested_range {
+                Some(_) => usize::MAX,
+                None => 24,
+            })
+            .collect_vec();
+
+        let times: Vec<NaiveDateTime>;
+        (
+            times,
+            self.temperatures,
+            self.wind_speeds,
+            self.wind_directions,
+            self.precipitation,
+            self.precipitation_probabilities,
+            self.apparent_temperatures,
+            self.humidity,
+            self.pressures,
+            self.cloud_cover,
+            self.weather_codes,
+        ) =
+            hours
+                .iter()
+                .map(|hour| {
+                    let timestamp = hour.get("dt").and_then(local_time).ok_or(
+                        WeatherError::InvalidResponse("Couldn't parse timestamps".to_string()),
+                    )?;
+                    // The hours without the temperature or the wind (null or missing) are gaps,
+                    // not a broken response
+                    let temperature = hour
+                        .get("temp")
+                        .and_then(|t| t.as_f64())
+                        .unwrap_or(f64::NAN);
+                    let wind_speed = hour
+                        .get("wind_speed")
+                        .and_then(|t| t.as_f64())
+                        .unwrap_or(f64::NAN);
+                    let wind_direction = WindDirection::from_degrees(
+                        hour.get("wind_deg")
+                            .and_then(|t| t.as_f64())
+                            .unwrap_or(f64::NAN),
+                    );
+
+                    // Rain and snow are only there for the hours they are expected in
+                    let precipitation = ["rain", "snow"]
+                        .iter()
+                        .filter_map(|kind| hour.get(kind)?.get("1h")?.as_f64())
+                        .sum::<f64>();
+                    let precipitation_probability = hour
+                        .get("pop")
+                        .and_then(|p| p.as_f64())
+                        .map(|p| (p * 100.0).round())
+                        .unwrap_or_default();
+
+                    // Only extras, so the hours without them just don't have them
+                    let apparent_temperature = hour
+                        .get("feels_like")
+                        .and_then(|t| t.as_f64())
+                        .unwrap_or(f64::NAN);
+                    let humidity = hour
+                        .get("humidity")
+                        .and_then(|h| h.as_f64())
+                        .unwrap_or(f64::NAN);
+                    let pressure = hour
+                        .get("pressure")
+                        .and_then(|p| p.as_f64())
+                        .unwrap_or(f64::NAN);
+                    let cloud_cover = hour
+                        .get("clouds")
+                        .and_then(|c| c.as_f64())
+                        .unwrap_or(f64::NAN);
+                    // There might be multiple conditions, the first one is the primary
+                    let weather_code = hour
+                        .get("weather")
+                        .and_then(|w| w.get(0))
+                        .and_then(|w| w.get("id"))
+                        .and_then(|id| id.as_u64().map(WeatherCode::from_open_weather_map))
+                        .unwrap_or_default();
+
+                    Ok((
+                        timestamp,
+                        temperature,
+                        wind_speed,
+                        wind_direction,
+                        precipitation,
+                        precipitation_probability,
+                        apparent_temperature,
+                        humidity,
+                        pressure,
+                        cloud_cover,
+                        weather_code,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .multiunzip();
+        let utc_offset = i32::try_from(timezone_offset)
+            .ok()
+            .and_then(FixedOffset::east_opt)
+            .unwrap_or(FixedOffset::east_opt(0).expect("UTC is a valid offset"));
+        self.timestamps = times
+            .iter()
+            .map(|time| Timestamp::new(with_utc_offset(*time, utc_offset), timestamp_format))
+            .collect();
+        // Only the extras as well, the series no hour has are left out
+        let extra_series = |name: &str| {
+            let values = hours
+                .iter()
+                .map(|hour| hour.get(name).and_then(|v| v.as_f64()).unwrap_or(f64::NAN))
+                .collect_vec();
+
+            match values.iter().all(|value| value.is_nan()) {
+                true => Vec::new(),
+                false => values,
+            }
+        };
+        self.wind_gusts = extra_series("wind_gust");
+        self.uv_indices = extra_series("uvi");
+        self.wind_speed_unit = "m/s".to_string();
+        self.precipitation_unit = "mm".to_string();
+        self.pressure_unit = "hPa".to_string();
+
+        // The hourly data starts with the current hour, unless the requested days start later
+        let current_time = hourly.first().and_then(|hour| local_time(hour.get("dt")?));
+        self.current_hour =
+            (current_time.is_some() && times.first() == current_time.as_ref()).then_some(0);
+
+        if self.daily.is_some() {
+            self.daily = Some(
+                DailyWeatherData::from_hourly(
+                    times.iter().copied().zip(self.temperatures.iter().copied()),
+                )
+                .with_hourly_weather_codes(
+                    times
+                        .iter()
+                        .copied()
+                        .zip(self.weather_codes.iter().copied()),
+                ),
+            );
+        }
+
+        self.current = match json.get("current") {
+            Some(Value::Object(current)) => {
+                // There is no sunrise or sunset during the polar days and nights
+                let daylight_time = |field: &str| {
+                    current
+                        .get(field)
+                        .and_then(local_time)
+                        .map(|time| time.format(DAYLIGHT_FORMAT).to_string())
+                };
+                (self.sunrise, self.sunset) = (daylight_time("sunrise"), daylight_time("sunset"));
+
+                let time = current
+                    .get("dt")
+                    .and_then(local_time)
+                    .ok_or(WeatherError::InvalidResponse("Time not found".to_string()))?
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string();
+
+                let temperature = current.get("temp").and_then(|t| t.as_f64()).ok_or(
+                    WeatherError::InvalidResponse("Temperature not found".to_string()),
+                )?;
+
+                // There might be multiple conditions, the first one is the primary
+                let weather_code = current
+                    .get("weather")
+                    .and_then(|w| w.get(0))
+                    .and_then(|w| w.get("id"))
+                    .and_then(|id| id.as_u64().map(WeatherCode::from_open_weather_map))
+                    .ok_or(WeatherError::InvalidResponse(
+                        "Weather code not found".to_string(),
+                    ))?;
+
+                let wind_speed = current.get("wind_speed").and_then(|t| t.as_f64()).ok_or(
+                    WeatherError::InvalidResponse("Wind speed not found".to_string()),
+                )?;
+
+                let wind_direction = current
+                    .get("wind_deg")
+                    .and_then(|t| t.as_f64().map(WindDirection::from_degrees))
+                    .ok_or(WeatherError::InvalidResponse(
+                        "Wind direction not found".to_string(),
+                    ))?;
+
+                Some(CurrentWeatherData {
+                    time,
+                    temperature,
+                    weather_code,
+                    wind_speed,
+                    wind_speed_unit: "m/s".to_string(),
+                    wind_direction,
+                    // The current conditions don't have it, but the current hour does
+                    precipitation_probability: hourly
+                        .first()
+                        .and_then(|hour| hour.get("pop"))
+                        .and_then(|p| p.as_f64())
+                        .map(|p| (p * 100.0).round()),
+                    apparent_temperature: current.get("feels_like").and_then(|t| t.as_f64()),
+                    humidity: current.get("humidity").and_then(|h| h.as_f64()),
+                    uv_index: current.get("uvi").and_then(|uv| uv.as_f64()),
+                    // Calculated for all the providers once the response is parsed
+                    wind_chill: None,
+                })
+            }
+            Some(_) => {
+                return Err(WeatherError::InvalidResponse(
+                    "Couldn't parse current weather data".to_string(),
+                ))
+            }
+            None => None,
+        };
+
+        Ok(self)
+    }
+}
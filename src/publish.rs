@@ -0,0 +1,335 @@
+//! Sending the JSON of the data somewhere after it's shown (`weather get --publish <dest>`): into a
+//! directory other programs pick the files up from (`file:/path/dir`), or to a webhook
+//! (`https://...`)
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+    blocking::Client,
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    Url,
+};
+
+use crate::{
+    config::{NetworkSettings, PublishSettings},
+    error::{Result, WeatherError},
+    http, retry,
+};
+
+/// Start of the names of the published files, the other files in the directory are left alone
+const FILE_PREFIX: &str = "weather-";
+
+/// Where the JSON of the data goes
+pub trait Publisher {
+    /// The JSON of the data (the line of `--output ndjson`), published at `now`
+    fn publish(&self, json: &str, now: DateTime<Utc>) -> Result<()>;
+
+    /// Where it goes, for the messages (never with the secrets)
+    fn destination(&self) -> String;
+}
+
+/// The publisher of the destination: `file:<dir>` or an http(s) URL
+pub fn publisher(
+    destination: &str,
+    settings: &PublishSettings,
+    network: &NetworkSettings,
+) -> Result<Box<dyn Publisher>> {
+    if let Some(dir) = destination.strip_prefix("file:") {
+        return Ok(Box::new(FileDrop {
+            dir: dir.into(),
+            keep: settings.keep,
+        }));
+    }
+
+    match Url::parse(destination) {
+        Ok(url) if ["http", "https"].contains(&url.scheme()) => Ok(Box::new(Webhook {
+            url,
+            authorization: settings.authorization.clone(),
+            client: http::client(&NetworkSettings {
+                timeout_secs: settings.timeout_secs,
+                ..network.clone()
+            })?,
+            network: network.clone(),
+        })),
+        _ => Err(WeatherError::InvalidInput(format!(
+            "Invalid destination {destination}, expected file:<dir> or an http(s):// URL"
+        ))),
+    }
+}
+
+/// Files in a directory, the last `keep` of them
+#[derive(Debug)]
+pub struct FileDrop {
+    dir: PathBuf,
+    keep: usize,
+}
+
+impl Publisher for FileDrop {
+    /// Written next to where it goes and moved there, so that the programs watching the directory
+    /// never see half of it
+    fn publish(&self, json: &str, now: DateTime<Utc>) -> Result<()> {
+        let failed = |e: std::io::Error| {
+            WeatherError::Publish(format!("Couldn't publish to {}: {e}", self.destination()))
+        };
+
+        std::fs::create_dir_all(&self.dir).map_err(failed)?;
+
+        let name = format!("{FILE_PREFIX}{}.json", now.format("%Y%m%dT%H%M%S%.3fZ"));
+        let partial = self.dir.join(format!(".{name}.partial"));
+        std::fs::write(&partial, json).map_err(failed)?;
+        std::fs::rename(&partial, self.dir.join(&name)).map_err(failed)?;
+
+        self.prune().map_err(failed)
+    }
+
+    fn destination(&self) -> String {
+        format!("file:{}", self.dir.display())
+    }
+}
+
+impl FileDrop {
+    /// Remove the published files before the last `keep` (the names sort by the time)
+    fn prune(&self) -> std::io::Result<()> {
+        let mut published = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with(FILE_PREFIX) && name.ends_with(".json"))
+            .collect::<Vec<_>>();
+        published.sort();
+
+        let old = published.len().saturating_sub(self.keep);
+        for name in &published[..old] {
+            std::fs::remove_file(self.dir.join(name))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// POST requests to an http(s) endpoint, retried like the provider ones
+pub struct Webhook {
+    url: Url,
+    authorization: Option<String>,
+    client: Client,
+    network: NetworkSettings,
+}
+
+impl Publisher for Webhook {
+    fn publish(&self, json: &str, now: DateTime<Utc>) -> Result<()> {
+        let response = retry::send(&self.network, now, || {
+            let request = self
+                .client
+                .post(self.url.clone())
+                .header(CONTENT_TYPE, "application/json")
+                .body(json.to_string());
+
+            match &self.authorization {
+                Some(authorization) => request.header(AUTHORIZATION, authorization),
+                None => request,
+            }
+        })
+        .map_err(|e| {
+            WeatherError::Publish(format!(
+                "Couldn't publish to {}: {}",
+                self.destination(),
+                e.without_url()
+            ))
+        })?;
+        let status = response.status();
+
+        // The header is there, but never its value
+        tracing::debug!(
+            url = %self.destination(),
+            authorization = self.authorization.as_ref().map(|_| "<redacted>"),
+            %status,
+            "Published the data"
+        );
+
+        match status.is_success() {
+            true => Ok(()),
+            false => Err(WeatherError::Publish(format!(
+                "Couldn't publish to {}, it answered with {status}",
+                self.destination()
+            ))),
+        }
+    }
+
+    /// The URL without the credentials and the query, they can have the secrets too
+    fn destination(&self) -> String {
+        let mut url = self.url.clone();
+        let _ = url.set_username("");
+        let _ = url.set_password(None);
+        url.set_query(None);
+
+        url.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+
+    use chrono::Duration;
+
+    use super::*;
+    use crate::test_server::TestServer;
+
+    const JSON: &str = r#"{"meta":{"location":"Garden"},"fingerprint":"abc"}"#;
+
+    fn dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("weather-publish-{name}-{}", std::process::id()))
+    }
+
+    fn names(dir: &PathBuf) -> Vec<String> {
+        let mut names = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+
+        names
+    }
+
+    #[test]
+    fn files_kept() {
+        let dir = dir("files");
+        let _ = std::fs::remove_dir_all(&dir);
+        let settings = PublishSettings {
+            keep: 3,
+            ..Default::default()
+        };
+        let publisher = publisher(
+            &format!("file:{}", dir.display()),
+            &settings,
+            &NetworkSettings::default(),
+        )
+        .unwrap();
+
+        // Somebody else's file stays
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.txt"), "mine").unwrap();
+
+        let time = DateTime::parse_from_rfc3339("2024-06-14T06:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        for minutes in 0..5 {
+            publisher
+                .publish(JSON, time + Duration::minutes(minutes))
+                .unwrap();
+        }
+
+        // The last 3, whole, and nothing half-written left behind
+        assert_eq!(
+            names(&dir),
+            [
+                "notes.txt",
+                "weather-20240614T063200.000Z.json",
+                "weather-20240614T063300.000Z.json",
+                "weather-20240614T063400.000Z.json",
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("weather-20240614T063400.000Z.json")).unwrap(),
+            JSON
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn invalid_destinations() {
+        for destination in ["ftp://example.com", "/tmp/weather", "mqtt:topic"] {
+            let e = publisher(
+                destination,
+                &PublishSettings::default(),
+                &NetworkSettings::default(),
+            )
+            .err()
+            .unwrap();
+
+            assert!(matches!(e, WeatherError::InvalidInput(_)), "{destination}");
+        }
+    }
+
+    /// Log lines written by the subscriber, like the ones of `-v` on stderr
+    #[derive(Clone, Default)]
+    struct Logs(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Logs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn posted_with_the_authorization() {
+        let server = TestServer::start(vec![("HTTP/1.1 204 No Content", "")]);
+        let settings = PublishSettings {
+            authorization: Some("Bearer secret-token".to_string()),
+            ..Default::default()
+        };
+        let publisher = publisher(
+            server.url.join("ingest").unwrap().as_str(),
+            &settings,
+            &NetworkSettings::default(),
+        )
+        .unwrap();
+
+        let logs = Logs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer({
+                let logs = logs.clone();
+                move || logs.clone()
+            })
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            publisher.publish(JSON, Utc::now()).unwrap();
+        });
+
+        let request = &server.requests()[0];
+        assert!(request.starts_with("POST /ingest "), "{request}");
+        assert!(
+            request
+                .to_lowercase()
+                .contains("authorization: bearer secret-token"),
+            "{request}"
+        );
+        assert_eq!(server.bodies()[0], JSON);
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("Published the data"), "{logs}");
+        assert!(logs.contains("authorization=\"<redacted>\""), "{logs}");
+        assert!(!logs.contains("secret-token"), "{logs}");
+    }
+
+    #[test]
+    fn refused() {
+        let server = TestServer::start(vec![("HTTP/1.1 401 Unauthorized", "")]);
+        let publisher = publisher(
+            server.url.join("ingest?key=abc").unwrap().as_str(),
+            &PublishSettings::default(),
+            &NetworkSettings::default(),
+        )
+        .unwrap();
+
+        let e = publisher.publish(JSON, Utc::now()).unwrap_err();
+        assert!(matches!(e, WeatherError::Publish(_)));
+        assert_eq!(
+            e.to_string(),
+            format!(
+                "Couldn't publish to {}ingest, it answered with 401 Unauthorized",
+                server.url
+            )
+        );
+    }
+}
@@ -0,0 +1,339 @@
+//! The next hours of a condition (`weather get --next rain`), for the scripts that only need to
+//! know whether and when it comes, and what they get when it doesn't
+
+use std::fmt::{Display, Formatter};
+
+use chrono::Duration;
+use serde_json::json;
+
+use crate::{
+    config::TemperatureUnit,
+    data::{WeatherCode, WeatherData},
+    error::{Result, WeatherError},
+    locale::{Language, Text},
+};
+
+/// Exit code of the runs nothing matched in, unless another one is asked for (it's none of the
+/// error ones, so that the scripts can tell it apart)
+pub const NO_MATCH_EXIT_CODE: u8 = 9;
+
+/// What the next hours are looked for with
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Condition {
+    /// The precipitation, or the chance of it where the provider has no precipitation
+    Rain,
+    Snow,
+    Thunderstorm,
+    /// The temperature at or below 0 °C
+    Frost,
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rain => write!(f, "rain"),
+            Self::Snow => write!(f, "snow"),
+            Self::Thunderstorm => write!(f, "thunderstorm"),
+            Self::Frost => write!(f, "frost"),
+        }
+    }
+}
+
+impl Condition {
+    pub const AVAILABLE_CONDITIONS: [&'static str; 4] = ["rain", "snow", "thunderstorm", "frost"];
+
+    /// Parse a string into a condition
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: impl AsRef<str>) -> Result<Self> {
+        match s.as_ref() {
+            "rain" => Ok(Self::Rain),
+            "snow" => Ok(Self::Snow),
+            "thunderstorm" => Ok(Self::Thunderstorm),
+            "frost" => Ok(Self::Frost),
+            s => Err(WeatherError::InvalidInput(format!(
+                "Invalid condition {s}, available conditions: [{}]",
+                Self::AVAILABLE_CONDITIONS.join(", ")
+            ))),
+        }
+    }
+
+    /// Whether each of the hours has the condition (an error if the data can't tell)
+    fn hours(&self, data: &WeatherData) -> Result<Vec<bool>> {
+        let unsupported = |series: &str| {
+            WeatherError::Unsupported(format!(
+                "{} has no {series} to find the {self} in",
+                data.provider
+            ))
+        };
+        let codes = |codes: &[WeatherCode]| match data.weather_codes.is_empty() {
+            true => Err(unsupported("conditions")),
+            false => Ok(data
+                .weather_codes
+                .iter()
+                .map(|code| codes.contains(code))
+                .collect()),
+        };
+
+        match self {
+            Self::Rain => {
+                if data.precipitation.is_empty() && data.precipitation_probabilities.is_empty() {
+                    return Err(unsupported("precipitation"));
+                }
+
+                let mut hours = vec![false; data.timestamps.len()];
+                for window in data.rain_windows() {
+                    hours[window].iter_mut().for_each(|hour| *hour = true);
+                }
+
+                Ok(hours)
+            }
+            Self::Snow => codes(&[
+                WeatherCode::SnowFall,
+                WeatherCode::SnowGrains,
+                WeatherCode::SnowShowers,
+            ]),
+            Self::Thunderstorm => codes(&[WeatherCode::Thunderstorm]),
+            Self::Frost => {
+                let unit = TemperatureUnit::from_provider_unit(&data.unit);
+
+                Ok(data
+                    .temperatures
+                    .iter()
+                    .map(|temperature| TemperatureUnit::Celsius.convert(*temperature, unit) <= 0.0)
+                    .collect())
+            }
+        }
+    }
+}
+
+/// What the search found, the data that couldn't be searched is an error instead
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Query {
+    Found(Match),
+    /// None of the hours ahead has the condition
+    NotFound {
+        condition: Condition,
+        hours: usize,
+    },
+}
+
+/// The first hours in a row with the condition
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Match {
+    pub condition: Condition,
+    /// Index of the first of the hours in the hourly series
+    pub start: usize,
+    pub hours: usize,
+}
+
+impl WeatherData {
+    /// Find the first hours with the condition, from the current hour on (from the first one if the
+    /// data doesn't have it)
+    pub fn next(&self, condition: Condition) -> Result<Query> {
+        let first = self.current_hour.unwrap_or_default();
+        let hours = condition.hours(self)?;
+        let ahead = hours.get(first..).unwrap_or_default();
+
+        Ok(match ahead.iter().position(|hour| *hour) {
+            Some(start) => Match {
+                condition,
+                start: first + start,
+                hours: ahead[start..].iter().take_while(|hour| **hour).count(),
+            }
+            .into(),
+            None => Query::NotFound {
+                condition,
+                hours: ahead.len(),
+            },
+        })
+    }
+}
+
+impl From<Match> for Query {
+    fn from(found: Match) -> Self {
+        Self::Found(found)
+    }
+}
+
+/// What is printed when nothing matched
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NoMatchOutput {
+    /// Nothing at all
+    Empty,
+    /// The sentence that says so
+    #[default]
+    Message,
+    /// `{"match": null}`, with the matches as JSON too
+    JsonNull,
+}
+
+impl NoMatchOutput {
+    pub const AVAILABLE_OUTPUTS: [&'static str; 3] = ["empty", "message", "json-null"];
+
+    /// Parse a string into an output
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: impl AsRef<str>) -> Result<Self> {
+        match s.as_ref() {
+            "empty" => Ok(Self::Empty),
+            "message" => Ok(Self::Message),
+            "json-null" => Ok(Self::JsonNull),
+            s => Err(WeatherError::InvalidInput(format!(
+                "Invalid output {s}, available outputs: [{}]",
+                Self::AVAILABLE_OUTPUTS.join(", ")
+            ))),
+        }
+    }
+}
+
+impl Query {
+    /// The line to print for the result (`None` for nothing at all)
+    pub fn format(
+        &self,
+        data: &WeatherData,
+        output: NoMatchOutput,
+        language: Language,
+    ) -> Option<String> {
+        match (self, output) {
+            (Self::Found(found), NoMatchOutput::JsonNull) => {
+                let time = |i: usize| data.timestamps[i].time;
+                let last = time(found.start + found.hours - 1);
+
+                Some(
+                    json!({
+                        "match": {
+                            "condition": found.condition.to_string(),
+                            "start": time(found.start).to_rfc3339(),
+                            "end": (last + Duration::hours(1)).to_rfc3339(),
+                            "hours": found.hours,
+                        }
+                    })
+                    .to_string(),
+                )
+            }
+            (Self::Found(found), _) => Some(language.format(
+                Text::NextMatch,
+                &[
+                    &found.condition,
+                    &data.timestamps[found.start].label,
+                    &found.hours,
+                ],
+            )),
+            (Self::NotFound { .. }, NoMatchOutput::Empty) => None,
+            (Self::NotFound { condition, hours }, NoMatchOutput::Message) => {
+                Some(language.format(Text::NoMatch, &[condition, hours]))
+            }
+            (Self::NotFound { .. }, NoMatchOutput::JsonNull) => {
+                Some(json!({ "match": null }).to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Timestamp;
+
+    /// Six hours from 00:00 with the precipitation, the conditions and the temperatures, the
+    /// current one is 01:00
+    fn six_hours(precipitation: Vec<f64>, weather_codes: Vec<WeatherCode>) -> WeatherData {
+        let time = chrono::DateTime::parse_from_rfc3339("2024-06-14T00:00:00Z").unwrap();
+
+        WeatherData {
+            timestamps: (0..6)
+                .map(|hour| Timestamp::new(time + Duration::hours(hour), "%H:%M"))
+                .collect(),
+            temperatures: vec![3.0, 1.0, 0.0, -1.0, 2.0, 4.0],
+            unit: "°C".to_string(),
+            precipitation,
+            weather_codes,
+            current_hour: Some(1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn next_hours_of_the_condition() {
+        let data = six_hours(
+            vec![0.5, 0.0, 0.0, 0.3, 0.4, 0.0],
+            vec![WeatherCode::Rain; 6],
+        );
+
+        // The rain before the current hour doesn't count
+        assert_eq!(
+            data.next(Condition::Rain).unwrap(),
+            Query::Found(Match {
+                condition: Condition::Rain,
+                start: 3,
+                hours: 2,
+            })
+        );
+        assert_eq!(
+            data.next(Condition::Frost).unwrap(),
+            Query::Found(Match {
+                condition: Condition::Frost,
+                start: 2,
+                hours: 2,
+            })
+        );
+        assert_eq!(
+            data.next(Condition::Snow).unwrap(),
+            Query::NotFound {
+                condition: Condition::Snow,
+                hours: 5,
+            }
+        );
+
+        // The data that can't tell is an error, not a miss
+        let data = six_hours(vec![], vec![]);
+        for condition in [Condition::Rain, Condition::Snow, Condition::Thunderstorm] {
+            let e = data.next(condition).unwrap_err();
+            assert!(
+                matches!(e, WeatherError::Unsupported(_)),
+                "{condition}: {e}"
+            );
+        }
+    }
+
+    #[test]
+    fn outputs() {
+        let data = six_hours(
+            vec![0.0, 0.0, 0.0, 0.3, 0.4, 0.0],
+            vec![WeatherCode::ClearSky; 6],
+        );
+        let found = data.next(Condition::Rain).unwrap();
+        let missed = data.next(Condition::Snow).unwrap();
+
+        // (result, output, line)
+        let cases = [
+            (&found, NoMatchOutput::Message, Some("rain at 03:00 (2 h)")),
+            (&found, NoMatchOutput::Empty, Some("rain at 03:00 (2 h)")),
+            (
+                &found,
+                NoMatchOutput::JsonNull,
+                Some(
+                    r#"{"match":{"condition":"rain","end":"2024-06-14T05:00:00+00:00","hours":2,"start":"2024-06-14T03:00:00+00:00"}}"#,
+                ),
+            ),
+            (
+                &missed,
+                NoMatchOutput::Message,
+                Some("no snow in the 5 hours ahead"),
+            ),
+            (&missed, NoMatchOutput::Empty, None),
+            (&missed, NoMatchOutput::JsonNull, Some(r#"{"match":null}"#)),
+        ];
+
+        for (query, output, line) in cases {
+            assert_eq!(
+                query.format(&data, output, Language::En).as_deref(),
+                line,
+                "{query:?} {output:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Raw provider responses of the successful requests, so that the last fetched data can still be
+//! shown offline (`get --cached`, or automatically if the provider can't be reached)
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use serde_json::{Map, Value};
+
+use crate::{
+    date::DateRange,
+    error::{Result, WeatherError},
+    geocode::ResolvedLocation,
+    paths::Paths,
+    providers::Provider,
+};
+
+const RESPONSES_DIR: &str = "responses";
+
+/// How many responses are kept (the ones fetched the longest ago are removed first)
+const MAX_CACHED_RESPONSES: usize = 50;
+
+/// A provider response with what it was requested for
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub(crate) struct CachedResponse {
+    pub(crate) provider: Provider,
+    pub(crate) address: String,
+    pub(crate) lat: f64,
+    pub(crate) lon: f64,
+    pub(crate) fetched_at: DateTime<Utc>,
+    /// `Last-Modified` of the response, sent back as `If-Modified-Since` on the same request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) last_modified: Option<String>,
+    /// `Expires` of the response, the same request is not sent again until then
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) expires: Option<DateTime<Utc>>,
+    pub(crate) json: Map<String, Value>,
+}
+
+/// Directory with a file per cached response
+#[derive(Debug)]
+pub(crate) struct ResponseCache {
+    dir: PathBuf,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(paths: &Paths) -> Self {
+        Self {
+            dir: paths.cache_dir.join(RESPONSES_DIR),
+        }
+    }
+
+    /// Get the cached response (a missing or broken file just means there is nothing cached)
+    pub(crate) fn get(&self, key: &str) -> Option<CachedResponse> {
+        std::fs::read_to_string(self.dir.join(key))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+    }
+
+    /// Remember the response and remove the oldest ones over the limit, if the cache location is
+    /// writable (the worst that can happen otherwise is no data to fall back to)
+    pub(crate) fn insert(&self, key: &str, response: &CachedResponse) {
+        let _ = self.try_insert(key, response);
+    }
+
+    fn try_insert(&self, key: &str, response: &CachedResponse) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.dir.join(key), serde_json::to_string(response)?)?;
+
+        self.prune()
+    }
+
+    /// Keep only the most recently written responses
+    fn prune(&self) -> Result<()> {
+        let files = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let modified = entry.metadata().ok()?.modified().ok()?;
+
+                Some((modified, entry.path()))
+            })
+            .sorted_by(|(a, _), (b, _)| b.cmp(a))
+            .skip(MAX_CACHED_RESPONSES);
+
+        for (_, path) in files {
+            std::fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Remove all the cached responses (`cache clear --all`), nothing to do if there are none
+pub fn clear(paths: &Paths) -> Result<()> {
+    let dir = ResponseCache::new(paths).dir;
+
+    match std::fs::remove_dir_all(&dir) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(WeatherError::Io(std::io::Error::new(
+                e.kind(),
+                format!(
+                    "Couldn't remove the cached responses at {} ({e})",
+                    dir.display()
+                ),
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// File name of the response for the request: the provider, the coordinates rounded to ~1 km, the
+/// requested days and whether the daily summary was requested, as well as the elevation if it was
+/// given (they change the response)
+pub(crate) fn response_key(
+    provider: Provider,
+    location: &ResolvedLocation,
+    requested_date: &str,
+    requested_range: Option<DateRange>,
+    daily: bool,
+    elevation: Option<f64>,
+) -> String {
+    format!(
+        "{provider}_{:.2}_{:.2}_{}{}{}.json",
+        location.lat,
+        location.lon,
+        match requested_range {
+            Some(range) => format!("{}..{}", range.start, range.end),
+            None => requested_date.to_string(),
+        },
+        match daily {
+            true => "_daily",
+            false => "",
+        },
+        elevation
+            .map(|elevation| format!("_{elevation:.0}m"))
+            .unwrap_or_default()
+    )
+}
@@ -0,0 +1,151 @@
+//! Raw provider responses written to a file with what they were requested for (`get
+//! --dump-response`), so that the parsing of someone else's request can be reproduced without the
+//! network (`get --from-dump`)
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    error::{Result, WeatherError},
+    geocode::ResolvedLocation,
+    providers::Provider,
+};
+
+/// Start of the header lines, the body follows them as it was received
+const HEADER_PREFIX: &str = "# ";
+
+/// A provider response with the request it answered, as in:
+///
+/// ```text
+/// # provider: open_meteo
+/// # url: https://api.open-meteo.com/v1/forecast?latitude=50.45&longitude=30.52&...
+/// # time: 2024-01-01T12:00:00Z
+/// # location: Kyiv, Ukraine
+/// # lat: 50.45
+/// # lon: 30.52
+/// # date: now
+/// # daily: false
+/// {"latitude":50.45,"longitude":30.52,...}
+/// ```
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ResponseDump {
+    pub provider: Provider,
+    /// Request URL with all the params (without the API key)
+    pub url: String,
+    /// When the request was made, the replayed data is parsed as if it was still then
+    pub time: DateTime<Utc>,
+    pub location: ResolvedLocation,
+    /// Elevation the data was requested for, if it was given
+    pub elevation: Option<f64>,
+    /// Date as it was asked for ("now", "tomorrow", "next 3 days", ...)
+    pub date: String,
+    /// Whether the daily summary was requested
+    pub daily: bool,
+    /// Body of the response, not necessarily valid JSON
+    pub body: String,
+}
+
+impl ResponseDump {
+    /// Read a dump written by [`ResponseDump::write`]
+    pub fn read(path: &Path) -> Result<Self> {
+        let dump = std::fs::read_to_string(path).map_err(|e| {
+            WeatherError::InvalidInput(format!("Couldn't read {}: {e}", path.display()))
+        })?;
+        let invalid = |reason: &str| {
+            WeatherError::InvalidInput(format!(
+                "{} is not a response dump ({reason})",
+                path.display()
+            ))
+        };
+
+        // The header ends at the first line that isn't a part of it
+        let mut header = Vec::new();
+        let mut rest = dump.as_str();
+        while let Some(line) = rest.strip_prefix(HEADER_PREFIX) {
+            let (line, next) = line.split_once('\n').unwrap_or((line, ""));
+            let (key, value) = line
+                .split_once(": ")
+                .ok_or_else(|| invalid(&format!("\"{line}\" is not a header line")))?;
+
+            header.push((key, value.trim_end_matches('\r')));
+            rest = next;
+        }
+
+        let value = |key: &str| {
+            header
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| *value)
+                .ok_or_else(|| invalid(&format!("no {key} in its header")))
+        };
+        let number = |key: &str| {
+            value(key)?
+                .parse::<f64>()
+                .map_err(|_| invalid(&format!("{key} is not a number")))
+        };
+
+        Ok(Self {
+            provider: Provider::from_str(value("provider")?)?,
+            url: value("url")?.to_string(),
+            time: DateTime::parse_from_rfc3339(value("time")?)
+                .map_err(|_| invalid("time is not an RFC 3339 timestamp"))?
+                .with_timezone(&Utc),
+            location: ResolvedLocation {
+                lat: number("lat")?,
+                lon: number("lon")?,
+                display_name: value("location")?.to_string(),
+                short_name: value("short_location").ok().map(String::from),
+                timezone: value("timezone").ok().map(String::from),
+                country_code: value("country_code").ok().map(String::from),
+            },
+            elevation: value("elevation")
+                .ok()
+                .map(|_| number("elevation"))
+                .transpose()?,
+            date: value("date")?.to_string(),
+            daily: value("daily")? == "true",
+            body: rest.to_string(),
+        })
+    }
+
+    /// Write the header and the body to the file, replacing it if it exists
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let mut header = vec![
+            ("provider", self.provider.to_string()),
+            ("url", self.url.clone()),
+            ("time", self.time.to_rfc3339()),
+            ("location", self.location.display_name.clone()),
+            ("lat", self.location.lat.to_string()),
+            ("lon", self.location.lon.to_string()),
+        ];
+        if let Some(short_name) = &self.location.short_name {
+            header.push(("short_location", short_name.clone()));
+        }
+        if let Some(timezone) = &self.location.timezone {
+            header.push(("timezone", timezone.clone()));
+        }
+        if let Some(country_code) = &self.location.country_code {
+            header.push(("country_code", country_code.clone()));
+        }
+        if let Some(elevation) = self.elevation {
+            header.push(("elevation", elevation.to_string()));
+        }
+        header.push(("date", self.date.clone()));
+        header.push(("daily", self.daily.to_string()));
+
+        let dump = header
+            .into_iter()
+            .map(|(key, value)| format!("{HEADER_PREFIX}{key}: {value}\n"))
+            .chain([self.body.clone()])
+            .collect::<String>();
+
+        std::fs::write(path, dump).map_err(|e| {
+            WeatherError::Io(std::io::Error::new(
+                e.kind(),
+                format!("Couldn't write the response to {}: {e}", path.display()),
+            ))
+        })
+    }
+}
@@ -0,0 +1,167 @@
+//! Provider requests with timeouts, retrying the failures that are likely to go away on their own
+//! (connection problems, server errors and short rate limits)
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+    blocking::{RequestBuilder, Response},
+    header, StatusCode,
+};
+
+use crate::{config::NetworkSettings, cooldown::throttled_until};
+
+/// Delay before the first retry, doubled for every next one
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Longest wait between two attempts (longer `Retry-After`s are left to the cooldowns)
+const MAX_WAIT: Duration = Duration::from_secs(30);
+
+/// Send the request built by `request`, retrying up to the configured number of times. The
+/// `Retry-After` dates are compared with `now` of the run (moved on by the time the retries took).
+///
+/// The last response is returned as is, even if it's an error one, so that the caller can report it
+pub(crate) fn send(
+    settings: &NetworkSettings,
+    now: DateTime<Utc>,
+    request: impl Fn() -> RequestBuilder,
+) -> reqwest::Result<Response> {
+    let started = Instant::now();
+    let mut attempt = 0;
+
+    loop {
+        let result = request().send();
+        let now = now + started.elapsed();
+
+        let wait = match &result {
+            Err(e) if e.is_connect() || e.is_timeout() => Some(backoff(attempt)),
+            Err(_) => None,
+            Ok(response) if response.status().is_server_error() => Some(backoff(attempt)),
+            // Without the `Retry-After` there is nothing better than the backoff to go on
+            Ok(response)
+                if response.status() == StatusCode::TOO_MANY_REQUESTS
+                    && !response.headers().contains_key(header::RETRY_AFTER) =>
+            {
+                Some(backoff(attempt))
+            }
+            Ok(response) => throttled_until(response, now).and_then(|until| {
+                // The server knows best how long to wait, but only the short waits are worth it
+                let retry_after = (until - now).to_std().unwrap_or_default();
+
+                (retry_after <= MAX_WAIT).then(|| retry_after.max(backoff(attempt)))
+            }),
+        };
+
+        match wait {
+            Some(wait) if attempt < settings.retries => {
+                std::thread::sleep(wait);
+                attempt += 1;
+            }
+            _ => return result,
+        }
+    }
+}
+
+/// Exponential backoff with up to 50% of jitter, so that the clients failed at the same time don't
+/// come back at the same time as well
+fn backoff(attempt: u32) -> Duration {
+    let delay = (BASE_BACKOFF * 2u32.saturating_pow(attempt)).min(MAX_WAIT);
+    let jitter = fastrand::u64(..=delay.as_millis() as u64 / 2);
+
+    delay + Duration::from_millis(jitter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::TestServer;
+
+    /// Send a GET to the server with the number of retries
+    fn get(server: &TestServer, retries: u32) -> StatusCode {
+        let client = reqwest::blocking::Client::new();
+        let settings = NetworkSettings {
+            retries,
+            ..NetworkSettings::default()
+        };
+
+        send(&settings, Utc::now(), || client.get(server.url.clone()))
+            .unwrap()
+            .status()
+    }
+
+    #[test]
+    fn server_errors_retried() {
+        let server = TestServer::start(vec![
+            ("HTTP/1.1 503 Service Unavailable", "down"),
+            ("HTTP/1.1 500 Internal Server Error", "down"),
+            ("HTTP/1.1 200 OK", "{}"),
+        ]);
+
+        assert_eq!(get(&server, 3), StatusCode::OK);
+        assert_eq!(server.request_count(), 3);
+    }
+
+    #[test]
+    fn retries_run_out() {
+        let server = TestServer::start(vec![("HTTP/1.1 503 Service Unavailable", "down")]);
+
+        // The last response is returned for the caller to report
+        assert_eq!(get(&server, 1), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[test]
+    fn client_errors_not_retried() {
+        for (status, expected) in [
+            ("HTTP/1.1 404 Not Found", StatusCode::NOT_FOUND),
+            ("HTTP/1.1 400 Bad Request", StatusCode::BAD_REQUEST),
+            ("HTTP/1.1 401 Unauthorized", StatusCode::UNAUTHORIZED),
+        ] {
+            let server = TestServer::start(vec![(status, "{}"), ("HTTP/1.1 200 OK", "{}")]);
+
+            assert_eq!(get(&server, 3), expected);
+            assert_eq!(server.request_count(), 1, "{status}");
+        }
+    }
+
+    #[test]
+    fn rate_limits_retried() {
+        // Without the Retry-After the backoff is used, with a short one it's waited for
+        let server = TestServer::start(vec![
+            ("HTTP/1.1 429 Too Many Requests", "slow down"),
+            (
+                "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 1",
+                "slow down",
+            ),
+            ("HTTP/1.1 200 OK", "{}"),
+        ]);
+
+        assert_eq!(get(&server, 3), StatusCode::OK);
+        assert_eq!(server.request_count(), 3);
+
+        // The long ones are left to the cooldowns
+        let server = TestServer::start(vec![
+            (
+                "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 3600",
+                "slow down",
+            ),
+            ("HTTP/1.1 200 OK", "{}"),
+        ]);
+
+        assert_eq!(get(&server, 3), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(server.request_count(), 1);
+    }
+
+    #[test]
+    fn backoff_grows() {
+        for attempt in 0..8 {
+            let delay = (BASE_BACKOFF * 2u32.pow(attempt)).min(MAX_WAIT);
+            let wait = backoff(attempt);
+
+            assert!(
+                delay <= wait && wait <= delay + delay / 2,
+                "{attempt}: {wait:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Local HTTP server the tests send their requests to instead of the real APIs
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+};
+
+use reqwest::Url;
+
+/// Server answering every request with the next of the responses, the last one once they run out
+pub(crate) struct TestServer {
+    pub(crate) url: Url,
+    /// Request lines and headers of the requests it got
+    requests: Arc<Mutex<Vec<String>>>,
+    /// Bodies of the requests, lined up with them
+    bodies: Arc<Mutex<Vec<String>>>,
+}
+
+impl TestServer {
+    /// Start the server with the responses: the status line with the headers, and the body
+    pub(crate) fn start(responses: Vec<(&'static str, &'static str)>) -> Self {
+        Self::listen("http", responses, Some)
+    }
+
+    /// Start the server behind TLS, with the self-signed certificate of `localhost` and
+    /// `127.0.0.1` from the fixtures
+    pub(crate) fn start_tls(responses: Vec<(&'static str, &'static str)>) -> Self {
+        let identity = native_tls::Identity::from_pkcs8(
+            include_bytes!("../tests/fixtures/localhost.pem"),
+            include_bytes!("../tests/fixtures/localhost-key.pem"),
+        )
+        .unwrap();
+        let acceptor = native_tls::TlsAcceptor::new(identity).unwrap();
+
+        // The handshakes the client gives up on (untrusted certificate) are just dropped
+        Self::listen("https", responses, move |stream| {
+            acceptor.accept(stream).ok()
+        })
+    }
+
+    /// Accept the connections, wrap them with `accept` and answer the requests on them
+    fn listen<S: Read + Write>(
+        scheme: &str,
+        responses: Vec<(&'static str, &'static str)>,
+        accept: impl Fn(TcpStream) -> Option<S> + Send + 'static,
+    ) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("{scheme}://{}/", listener.local_addr().unwrap())).unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+
+        let (received, received_bodies) = (requests.clone(), bodies.clone());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let Some(mut stream) = accept(stream) else {
+                    continue;
+                };
+
+                let mut reader = BufReader::new(&mut stream);
+                let mut head = String::new();
+                while reader
+                    .read_line(&mut head)
+                    .is_ok_and(|read| read > 0 && !head.ends_with("\r\n\r\n"))
+                {}
+
+                // The body of the POST requests, as long as the head says
+                let length = head
+                    .lines()
+                    .filter_map(|line| line.split_once(':'))
+                    .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                    .and_then(|(_, length)| length.trim().parse().ok())
+                    .unwrap_or(0);
+                let mut body = vec![0; length];
+                let _ = reader.read_exact(&mut body);
+                received_bodies
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&body).to_string());
+
+                let i = {
+                    let mut received = received.lock().unwrap();
+                    received.push(head);
+                    received.len() - 1
+                };
+                let (response, body) = responses[i.min(responses.len() - 1)];
+                let _ = write!(
+                    stream,
+                    "{response}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.flush();
+            }
+        });
+
+        Self {
+            url,
+            requests,
+            bodies,
+        }
+    }
+
+    /// Number of the requests so far
+    pub(crate) fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    /// Heads of the requests so far, in the order they came in
+    pub(crate) fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Bodies of the requests so far, empty for the ones without any
+    pub(crate) fn bodies(&self) -> Vec<String> {
+        self.bodies.lock().unwrap().clone()
+    }
+}
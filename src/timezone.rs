@@ -0,0 +1,299 @@
+//! The times shown in another timezone than the one of the location (`get --display-tz`), for the
+//! weather of the places the user is going to
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use itertools::Itertools;
+
+use crate::{
+    config::TimeFormat,
+    data::{utc_offset_name, with_utc_offset, WeatherData, DAYLIGHT_FORMAT},
+    error::{Result, WeatherError},
+};
+
+/// How many of the closest timezone names an unknown one is answered with
+const SUGGESTIONS: usize = 3;
+
+/// Timezone the times are shown in
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum DisplayZone {
+    /// The one of the location, as the provider returned them
+    #[default]
+    Location,
+    /// The one of this machine
+    Local,
+    /// An IANA timezone ("Europe/Kyiv")
+    Named(Tz),
+}
+
+impl DisplayZone {
+    /// "location", "local" or an IANA timezone name, the unknown names are answered with the
+    /// closest ones
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: impl AsRef<str>) -> Result<Self> {
+        match s.as_ref() {
+            "location" => Ok(Self::Location),
+            "local" => Ok(Self::Local),
+            name => name.parse::<Tz>().map(Self::Named).map_err(|_| {
+                WeatherError::InvalidInput(format!(
+                    "Unknown timezone {name}, closest ones: [{}] (or local, location)",
+                    closest_names(name).join(", ")
+                ))
+            }),
+        }
+    }
+
+    /// The time in the timezone, `None` for the one of the location (it's shown as it is)
+    fn convert(&self, time: DateTime<FixedOffset>) -> Option<DateTime<FixedOffset>> {
+        match self {
+            Self::Location => None,
+            Self::Local => Some(time.with_timezone(&Local).fixed_offset()),
+            Self::Named(tz) => Some(time.with_timezone(tz).fixed_offset()),
+        }
+    }
+}
+
+/// Timezone names the most similar to the unknown one, the most similar first
+fn closest_names(name: &str) -> Vec<&'static str> {
+    let name = name.to_lowercase();
+
+    TZ_VARIANTS
+        .iter()
+        .map(|tz| {
+            let similarity = strsim::jaro_winkler(&name, &tz.name().to_lowercase());
+
+            (tz.name(), similarity)
+        })
+        .sorted_by(|(_, a), (_, b)| b.total_cmp(a))
+        .take(SUGGESTIONS)
+        .map(|(name, _)| name)
+        .collect()
+}
+
+impl WeatherData {
+    /// Shift all the times that are shown (the hours, the time of the current conditions, the
+    /// sunrise and the sunset) to the timezone, and name it as the timezone of the data. It's done
+    /// right before the data is shown, the machine outputs (CSV) keep the time of the location
+    pub fn in_display_zone(mut self, zone: DisplayZone) -> Self {
+        // The offset of the location is the one of its hours, there is nothing to shift without them
+        let Some(first) = self.timestamps.first().map(|timestamp| timestamp.time) else {
+            return self;
+        };
+        let Some(shifted) = zone.convert(first) else {
+            return self;
+        };
+
+        let weekday = self.requested_range.is_some();
+        for timestamp in &mut self.timestamps {
+            let Some(time) = zone.convert(timestamp.time) else {
+                continue;
+            };
+
+            timestamp.label = time
+                .format(&label_format(self.time_format, weekday, time))
+                .to_string();
+            timestamp.time = time;
+        }
+
+        let offset = *first.offset();
+        let shift = |time: NaiveDateTime| zone.convert(with_utc_offset(time, offset));
+
+        if let Some(current) = &mut self.current {
+            if let Some(time) = NaiveDateTime::parse_from_str(&current.time, "%Y-%m-%d %H:%M")
+                .ok()
+                .and_then(shift)
+            {
+                current.time = time.format("%Y-%m-%d %H:%M").to_string();
+            }
+        }
+
+        // The sunrise and the sunset are on the requested day
+        let day = NaiveDate::parse_from_str(&self.requested_date, "%Y-%m-%d")
+            .unwrap_or(first.date_naive());
+        let shift_daylight = |time: &mut Option<String>| {
+            if let Some(shifted) = time
+                .as_deref()
+                .and_then(|time| NaiveTime::parse_from_str(time, DAYLIGHT_FORMAT).ok())
+                .and_then(|time| shift(day.and_time(time)))
+            {
+                *time = Some(shifted.format(DAYLIGHT_FORMAT).to_string());
+            }
+        };
+        shift_daylight(&mut self.sunrise);
+        shift_daylight(&mut self.sunset);
+        if let Some(astro) = &mut self.astro {
+            shift_daylight(&mut astro.sunrise);
+            shift_daylight(&mut astro.sunset);
+        }
+
+        // The next minutes are around the first hour
+        if let Some(nowcast) = &mut self.nowcast {
+            let minute_format = self.time_format.minute_format();
+
+            for label in &mut nowcast.labels {
+                if let Some(time) = NaiveTime::parse_from_str(label, minute_format)
+                    .ok()
+                    .and_then(|time| shift(first.date_naive().and_time(time)))
+                {
+                    *label = time.format(minute_format).to_string();
+                }
+            }
+        }
+
+        self.timezone = match zone {
+            DisplayZone::Named(tz) => tz.name().to_string(),
+            _ => utc_offset_name(shifted.offset().local_minus_utc() as i64),
+        };
+        self.comparison = self
+            .comparison
+            .map(|comparison| Box::new(comparison.in_display_zone(zone)));
+
+        self
+    }
+}
+
+/// Format of the hour labels, with the minutes if the timezone isn't a whole number of hours away
+/// from the one of the location ("14:45" in Kathmandu)
+fn label_format(time_format: TimeFormat, weekday: bool, time: DateTime<FixedOffset>) -> String {
+    match (time.format("%M").to_string() == "00", weekday) {
+        (true, _) => time_format.hour_format(weekday).to_string(),
+        (false, true) => format!("%a {}", time_format.minute_format()),
+        (false, false) => time_format.minute_format().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geocode::ResolvedLocation,
+        providers::{Provider, ProviderRequestType},
+    };
+
+    /// Three hours in Kyiv (UTC+03:00 in the summer) from the noon of June 14, 2024
+    fn kyiv(time_format: TimeFormat) -> WeatherData {
+        let json = serde_json::json!({
+            "utc_offset_seconds": 10800,
+            "timezone": "Europe/Kyiv",
+            "current_weather": {
+                "time": "2024-06-14T12:00",
+                "temperature": 21.0,
+                "weathercode": 0,
+                "windspeed": 5.0,
+                "winddirection": 180.0,
+            },
+            "current_weather_units": { "windspeed": "km/h" },
+            "hourly_units": { "temperature_2m": "°C" },
+            "hourly": {
+                "time": ["2024-06-14T12:00", "2024-06-14T13:00", "2024-06-14T14:00"],
+                "temperature_2m": [21.0, 22.0, 23.0],
+            },
+            "daily": {
+                "time": ["2024-06-14"],
+                "sunrise": ["2024-06-14T04:46"],
+                "sunset": ["2024-06-14T21:08"],
+            },
+        });
+
+        WeatherData::from_json(
+            json.as_object().unwrap(),
+            Provider::OpenMeteo,
+            ProviderRequestType::Forecast,
+            "2024-06-14".to_string(),
+            None,
+            ResolvedLocation::default(),
+            (false, time_format),
+        )
+        .unwrap()
+    }
+
+    fn labels(data: &WeatherData) -> Vec<&str> {
+        data.timestamps
+            .iter()
+            .map(|timestamp| timestamp.label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn times_in_other_zones() {
+        // (zone, clock) and the labels, the time of the current conditions, the sunrise and the
+        // sunset, and the name of the zone
+        type Case = (
+            &'static str,
+            TimeFormat,
+            [&'static str; 3],
+            [&'static str; 4],
+        );
+        let cases: [Case; 5] = [
+            (
+                "location",
+                TimeFormat::Hours24,
+                ["12:00", "13:00", "14:00"],
+                ["2024-06-14 12:00", "04:46", "21:08", "Europe/Kyiv"],
+            ),
+            (
+                "UTC",
+                TimeFormat::Hours24,
+                ["09:00", "10:00", "11:00"],
+                ["2024-06-14 09:00", "01:46", "18:08", "UTC"],
+            ),
+            (
+                "America/New_York",
+                TimeFormat::Hours12,
+                ["05 AM", "06 AM", "07 AM"],
+                ["2024-06-14 05:00", "21:46", "14:08", "America/New_York"],
+            ),
+            // A quarter of an hour off, the labels get the minutes
+            (
+                "Asia/Kathmandu",
+                TimeFormat::Hours24,
+                ["14:45", "15:45", "16:45"],
+                ["2024-06-14 14:45", "07:31", "23:53", "Asia/Kathmandu"],
+            ),
+            (
+                "Asia/Kolkata",
+                TimeFormat::Hours12,
+                ["02:30 PM", "03:30 PM", "04:30 PM"],
+                ["2024-06-14 14:30", "07:16", "23:38", "Asia/Kolkata"],
+            ),
+        ];
+
+        for (zone, time_format, expected_labels, [current, sunrise, sunset, name]) in cases {
+            let data = kyiv(time_format).in_display_zone(DisplayZone::from_str(zone).unwrap());
+
+            assert_eq!(labels(&data), expected_labels, "{zone}");
+            assert_eq!(
+                data.current.as_ref().map(|current| current.time.as_str()),
+                Some(current),
+                "{zone}"
+            );
+            assert_eq!(data.sunrise.as_deref(), Some(sunrise), "{zone}");
+            assert_eq!(data.sunset.as_deref(), Some(sunset), "{zone}");
+            assert_eq!(data.timezone, name, "{zone}");
+        }
+
+        // The same moments, only shown differently
+        let kathmandu = kyiv(TimeFormat::Hours24)
+            .in_display_zone(DisplayZone::from_str("Asia/Kathmandu").unwrap());
+        for (shifted, original) in kathmandu
+            .timestamps
+            .iter()
+            .zip(&kyiv(TimeFormat::Hours24).timestamps)
+        {
+            assert_eq!(shifted.time, original.time);
+        }
+    }
+
+    #[test]
+    fn unknown_zones_suggested() {
+        match DisplayZone::from_str("Europe/Kyjv") {
+            Err(WeatherError::InvalidInput(message)) => {
+                assert!(message.contains("Europe/Kyjv"), "{message}");
+                assert!(message.contains("Europe/Kyiv"), "{message}");
+            }
+            result => panic!("{result:?}"),
+        }
+
+        assert_eq!(DisplayZone::from_str("local").unwrap(), DisplayZone::Local);
+    }
+}
@@ -1,213 +1,2857 @@
+mod ascii;
+mod banners;
 mod bar_chart;
+mod compact;
+mod csv;
+mod export;
+mod plain;
+mod speech;
+mod theme;
+mod warnings;
 
-use std::io::{self, Stdout};
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, IsTerminal, Stdout},
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use color_eyre::eyre;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType, ScrollUp},
+};
+use itertools::{Either, Itertools};
+use serde_json::{json, Map, Value};
+use tui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Span, Spans},
+    widgets::{Block, BorderType, Borders, Paragraph, Tabs, Wrap},
+    Frame, Terminal,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    config::{DataAge, ExtremeTemperatures, TemperatureUnit},
+    data::{
+        align_timestamps, wind_speed_in_kmh, AirQualityData, AstroData, CurrentSnapshot,
+        CurrentWeatherData, DailyWeatherData, DataSource, NowcastData, RadarCoverage,
+        SeriesSummary, WeatherAlert, WeatherCode, WeatherData, WindDirection, NOWCAST_MINUTES,
+    },
+    error::{ErrorCategory, WeatherError},
+    locale::{Language, Text},
+    logging,
+    outdoor::OutdoorWeights,
+    providers::{Provider, ProviderRequestType},
+    timezone::DisplayZone,
+    ui::{
+        ascii::{ascii_text, AsciiBackend},
+        banners::{banners, BannerKind},
+        bar_chart::{axis_width, symbol_rows, BarChart, OVERLAY_TICK},
+        warnings::WarningsView,
+    },
+    watch::{self, NdjsonStream},
+};
+
+pub(crate) use export::{parse_export_path, parse_export_size};
+pub(crate) use theme::Theme;
+
+/// Hourly data shown on the chart
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum ChartSeries {
+    Temperature,
+    /// Apparent ("feels like") temperature
+    FeelsLike,
+    Wind,
+    Pressure,
+    /// Cloud cover in %
+    Clouds,
+    Precipitation,
+}
+
+impl ChartSeries {
+    pub(crate) const AVAILABLE_SERIES: [&str; 6] = [
+        "temperature",
+        "feels-like",
+        "wind",
+        "pressure",
+        "clouds",
+        "precipitation",
+    ];
+    /// Lined up with `AVAILABLE_SERIES`
+    const ALL: [Self; 6] = [
+        Self::Temperature,
+        Self::FeelsLike,
+        Self::Wind,
+        Self::Pressure,
+        Self::Clouds,
+        Self::Precipitation,
+    ];
+
+    /// Parse a string into a chart series
+    pub(crate) fn from_str(s: impl AsRef<str>) -> eyre::Result<Self> {
+        match s.as_ref() {
+            "temperature" => Ok(Self::Temperature),
+            "feels-like" => Ok(Self::FeelsLike),
+            "wind" => Ok(Self::Wind),
+            "pressure" => Ok(Self::Pressure),
+            "clouds" => Ok(Self::Clouds),
+            "precipitation" => Ok(Self::Precipitation),
+            s => Err(eyre::eyre!(
+                "Invalid series {s}, available series: [{}]",
+                Self::AVAILABLE_SERIES.join(", ")
+            )),
+        }
+    }
+
+    /// Name of the series in the messages
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Temperature => "temperature",
+            Self::FeelsLike => "apparent temperature",
+            Self::Wind => "wind",
+            Self::Pressure => "pressure",
+            Self::Clouds => "cloud cover",
+            Self::Precipitation => "precipitation",
+        }
+    }
+
+    /// Header of the series in the tables
+    fn label(&self) -> Text {
+        match self {
+            Self::Temperature => Text::Temperature,
+            Self::FeelsLike => Text::FeelsLike,
+            Self::Wind => Text::WindSpeed,
+            Self::Pressure => Text::Pressure,
+            Self::Clouds => Text::CloudCover,
+            Self::Precipitation => Text::Precipitation,
+        }
+    }
+
+    /// Hourly values of the series
+    fn values<'a>(&self, data: &'a WeatherData) -> &'a [f64] {
+        match self {
+            Self::Temperature => &data.temperatures,
+            Self::FeelsLike => &data.apparent_temperatures,
+            Self::Wind => &data.wind_speeds,
+            Self::Pressure => &data.pressures,
+            Self::Clouds => &data.cloud_cover,
+            Self::Precipitation => &data.precipitation,
+        }
+    }
+
+    /// Unit of the values of the series
+    fn unit<'a>(&self, data: &'a WeatherData) -> &'a str {
+        match self {
+            Self::Temperature | Self::FeelsLike => &data.unit,
+            Self::Wind => &data.wind_speed_unit,
+            Self::Pressure => &data.pressure_unit,
+            Self::Clouds => "%",
+            Self::Precipitation => &data.precipitation_unit,
+        }
+    }
+
+    /// Whether the series is one of the temperatures (colored by how warm they are)
+    fn is_temperature(&self) -> bool {
+        matches!(self, Self::Temperature | Self::FeelsLike)
+    }
+
+    /// Color of the bars in the theme (the temperatures only have it below the first threshold)
+    fn color(&self, theme: &Theme) -> Color {
+        match self {
+            Self::Temperature | Self::FeelsLike | Self::Precipitation => theme.bar,
+            Self::Wind => theme.cool,
+            Self::Pressure => theme.good,
+            Self::Clouds => theme.neutral,
+        }
+    }
+
+    /// Fixed range of the bars, for the percentages to be comparable between the charts (the
+    /// range of the values otherwise)
+    fn range(&self) -> Option<(f64, f64)> {
+        match self {
+            Self::Clouds => Some((0.0, 100.0)),
+            _ => None,
+        }
+    }
+}
+
+/// How the data is shown
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum OutputMode {
+    /// The TUI, or the plain text where there is no terminal (pipes, CI)
+    #[default]
+    Tui,
+    Plain,
+    /// Sentences for the screen readers (see [`speech::format_speech`])
+    Speech,
+    /// A line of compact JSON for the collectors (see [`NdjsonStream`]), one per refresh with
+    /// `--watch`
+    Ndjson,
+    /// The same JSON of the data indented (see [`json_object`]), an array of them for several
+    /// addresses
+    Json,
+}
+
+impl OutputMode {
+    pub(crate) const AVAILABLE_MODES: [&str; 5] = ["tui", "plain", "speech", "ndjson", "json"];
+    /// Variables a screen reader setup is guessed from, the speech is the default output if any of
+    /// them is set (to anything but "0" or "false")
+    const SCREEN_READER_ENV: [&str; 2] = ["SCREEN_READER", "ACCESSIBILITY_ENABLED"];
+
+    /// Parse a string into an output mode
+    pub(crate) fn from_str(s: impl AsRef<str>) -> eyre::Result<Self> {
+        match s.as_ref() {
+            "tui" => Ok(Self::Tui),
+            "plain" => Ok(Self::Plain),
+            "speech" => Ok(Self::Speech),
+            "ndjson" => Ok(Self::Ndjson),
+            "json" => Ok(Self::Json),
+            s => Err(eyre::eyre!(
+                "Invalid output {s}, available outputs: [{}]",
+                Self::AVAILABLE_MODES.join(", ")
+            )),
+        }
+    }
+
+    /// The asked for output (`--output`, or `--plain`), the speech if a screen reader seems to be
+    /// running, the TUI otherwise
+    pub(crate) fn resolve(output: Option<&str>, plain: bool) -> eyre::Result<Self> {
+        let screen_reader = Self::SCREEN_READER_ENV.iter().any(|name| {
+            std::env::var(name).is_ok_and(|value| !["", "0", "false"].contains(&value.as_str()))
+        });
+
+        match (output, plain, screen_reader) {
+            (Some(output), _, _) => Self::from_str(output),
+            (None, true, _) => Ok(Self::Plain),
+            (None, false, true) => Ok(Self::Speech),
+            (None, false, false) => Ok(Self::Tui),
+        }
+    }
+
+    /// Whether the data is printed as text instead of drawn, the TUI is useless in pipes and on CI
+    fn prints(&self) -> bool {
+        *self != Self::Tui || !io::stdout().is_terminal()
+    }
+}
+
+/// How the data is drawn, the same for all the outputs
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct RenderOptions {
+    pub(crate) series: ChartSeries,
+    /// Draw the conditions with ASCII characters instead of the emoji
+    pub(crate) ascii_glyphs: bool,
+    /// Draw only the ASCII characters: the bars, the borders, the units and the conditions (the
+    /// glyphs are ASCII too then)
+    pub(crate) ascii: bool,
+    /// Decimal places of the values on the bars (`None` for the values as they are)
+    pub(crate) precision: Option<usize>,
+    /// Ages from which the data is highlighted as old
+    pub(crate) data_age: DataAge,
+    /// Draw the days as bars from their minimum to their maximum temperature, with their conditions
+    pub(crate) week: bool,
+    /// Language of the titles, the labels and the conditions
+    pub(crate) language: Language,
+    /// Colors and borders
+    pub(crate) theme: Theme,
+    /// Length of the best window of the hours to be outside to show, and how its hours are
+    /// penalized
+    pub(crate) best_window: Option<(usize, OutdoorWeights)>,
+    /// Timezone the times are shown in
+    pub(crate) display_zone: DisplayZone,
+    /// Mark the bars of the chart with their index, and list them with all their values under it
+    /// (only where there is room for the list: the plain text and the text snapshots)
+    pub(crate) with_table: bool,
+    /// Time of the run, the age of the data is counted up to it
+    pub(crate) now: DateTime<Utc>,
+}
+
+impl RenderOptions {
+    /// Bar levels of the chart
+    fn bar_set(&self) -> symbols::bar::Set {
+        match self.ascii {
+            true => ascii::BAR_SET,
+            false => symbols::bar::NINE_LEVELS,
+        }
+    }
+
+    /// The options of the TUI, which has no room for the index table (so the bars have no markers
+    /// either)
+    fn interactive(self) -> Self {
+        Self {
+            with_table: false,
+            ..self
+        }
+    }
+
+    /// The text as it's printed, without the non-ASCII characters if they can't be shown
+    fn text(&self, text: String) -> String {
+        match self.ascii {
+            true => ascii_text(&text),
+            false => text,
+        }
+    }
+}
+
+pub(crate) fn draw_data(
+    data: WeatherData,
+    extreme_temperatures: &ExtremeTemperatures,
+    output: OutputMode,
+    options: RenderOptions,
+) -> eyre::Result<()> {
+    check_series(&data, options)?;
+    let data = data.in_display_zone(options.display_zone);
+
+    if output.prints() {
+        print!(
+            "{}",
+            format_text(&data, extreme_temperatures, output, options)
+        );
+
+        return Ok(());
+    }
+
+    show_app(
+        Pages::single(App::interactive(vec![data], vec![], options)),
+        extreme_temperatures,
+        options.ascii,
+    )
+}
+
+/// Write a snapshot of the data to the file instead of (or before) showing it: the TUI layout in
+/// the `size` (width x height) for .txt and .ansi, or a bar chart for .svg
+pub(crate) fn export_data(
+    data: &WeatherData,
+    path: &Path,
+    size: (u16, u16),
+    extreme_temperatures: &ExtremeTemperatures,
+    options: RenderOptions,
+) -> eyre::Result<()> {
+    check_series(data, options)?;
+    let data = data.clone().in_display_zone(options.display_zone);
+
+    export::write_snapshot(&data, path, size, extreme_temperatures, options)
+}
+
+/// Write the hourly series as CSV to the file, or print them if there is no path
+pub(crate) fn export_csv(data: &WeatherData, path: Option<&Path>) -> eyre::Result<()> {
+    match path {
+        Some(path) => csv::write_csv(data, File::create(path)?),
+        None => csv::write_csv(data, io::stdout().lock()),
+    }
+}
+
+/// Write the rain, the alerts, the sunrise and the sunset (and the temperatures of every day if
+/// `days` is set) as an iCalendar file (see [`export::ics`])
+pub(crate) fn export_ics(
+    data: &WeatherData,
+    path: &Path,
+    days: bool,
+    language: Language,
+) -> eyre::Result<()> {
+    std::fs::write(path, export::ics::format_calendar(data, days, language))?;
+
+    Ok(())
+}
+
+/// Format the data as the text of the output: the sentences of the speech, the line of JSON or the
+/// plain text
+fn format_text(
+    data: &WeatherData,
+    extreme_temperatures: &ExtremeTemperatures,
+    output: OutputMode,
+    options: RenderOptions,
+) -> String {
+    match output {
+        OutputMode::Speech => options.text(speech::format_speech(data, options.language)),
+        OutputMode::Ndjson => NdjsonStream::default().line(Ok(data)) + "\n",
+        OutputMode::Json => json_text(&Value::Object(json_object(data, options))),
+        OutputMode::Tui | OutputMode::Plain => format_plain(data, extreme_temperatures, options),
+    }
+}
+
+/// The JSON of the data (see [`watch::full_object`]), with the best window of the hours to be
+/// outside if it's asked for (`null` if there is no such window)
+fn json_object(data: &WeatherData, options: RenderOptions) -> Map<String, Value> {
+    let mut object = watch::full_object(data);
+
+    if let Some((hours, weights)) = options.best_window {
+        let window = data.best_outdoor_window(hours, &weights).ok();
+        let window = window.and_then(|window| {
+            let start = data.timestamps.get(window.start)?.time;
+            let penalties = window.penalties;
+
+            Some(json!({
+                "hours": window.hours,
+                "start": start.to_rfc3339(),
+                "end": (start + chrono::Duration::hours(hours as i64)).to_rfc3339(),
+                "temperature": window.temperature,
+                "rain_chance": window.rain_chance,
+                "uv_index": window.uv_index,
+                "penalties": {
+                    "total": penalties.total(),
+                    "rain": penalties.rain,
+                    "temperature": penalties.temperature,
+                    "uv": penalties.uv,
+                    "gusts": penalties.gusts,
+                },
+            }))
+        });
+
+        object.insert("best_window".to_string(), window.into());
+    }
+
+    object
+}
+
+/// The JSON indented for the people reading it, with the line ending
+fn json_text(json: &Value) -> String {
+    format!("{json:#}\n")
+}
+
+/// Format the data as text (see [`plain::format_data`]). With the ASCII characters only, the units
+/// are replaced before the columns are lined up, so that they stay lined up
+fn format_plain(
+    data: &WeatherData,
+    extreme_temperatures: &ExtremeTemperatures,
+    options: RenderOptions,
+) -> String {
+    // The best window goes last, under the summary, and the index table under everything
+    let best_window = best_window_line(data, options)
+        .map(|(line, penalties)| format!("  {line} ({penalties})\n"))
+        .unwrap_or_default()
+        + &index_table(data, options);
+
+    if !options.ascii {
+        return plain::format_data(data, (extreme_temperatures, options.language, options.now))
+            + &best_window;
+    }
+
+    let mut data = data.clone();
+    for unit in [
+        &mut data.unit,
+        &mut data.wind_speed_unit,
+        &mut data.pressure_unit,
+        &mut data.precipitation_unit,
+    ] {
+        *unit = ascii_text(unit);
+    }
+
+    ascii_text(
+        &(plain::format_data(&data, (extreme_temperatures, options.language, options.now))
+            + &best_window),
+    )
+}
+
+/// The bars of the chart with their index markers, full times and values (see
+/// [`plain::format_index_table`]), empty unless it's asked for
+fn index_table(data: &WeatherData, options: RenderOptions) -> String {
+    match options.with_table && data.daily.is_none() {
+        true => format!("\n{}", plain::format_index_table(data, options.language)),
+        false => String::new(),
+    }
+}
+
+/// Check that the data has the series to chart, and the hours for the best window if it's asked for
+fn check_series(data: &WeatherData, options: RenderOptions) -> eyre::Result<()> {
+    let series = options.series;
+
+    // Too short a horizon is an error of its own, rather than a missing line
+    if let Some((hours, weights)) = options.best_window {
+        data.best_outdoor_window(hours, &weights)?;
+    }
+
+    // Only the temperatures are summarized per day
+    if series != ChartSeries::Temperature && data.daily.is_some() {
+        return Err(WeatherError::InvalidInput(format!(
+            "The {} is only available hourly, drop --daily to see it",
+            series.name()
+        ))
+        .into());
+    }
+
+    // Not every provider/request has all the series, so the ones this data has are suggested
+    if series != ChartSeries::Temperature && series.values(data).is_empty() {
+        return Err(WeatherError::Unsupported(format!(
+            "No hourly {} data available from {} for this request, available series: [{}]",
+            series.name(),
+            data.provider,
+            ChartSeries::ALL
+                .into_iter()
+                .zip(ChartSeries::AVAILABLE_SERIES)
+                .filter(|(series, _)| !series.values(data).is_empty())
+                .map(|(_, name)| name)
+                .join(", ")
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Print the data as a single line (no TUI or terminal control sequences), with the temperatures of
+/// the next `hours` hours as a sparkline
+pub(crate) fn draw_compact(
+    snapshot: CurrentSnapshot,
+    extreme_temperatures: &ExtremeTemperatures,
+    options: RenderOptions,
+) -> eyre::Result<()> {
+    println!(
+        "{}",
+        compact::format_compact(&snapshot, extreme_temperatures, options)
+    );
+
+    Ok(())
+}
+
+/// Draw the data of all the providers one under another, with the failed ones noted above them
+pub(crate) fn draw_comparison(
+    results: Vec<(Provider, Result<WeatherData, WeatherError>)>,
+    extreme_temperatures: &ExtremeTemperatures,
+    output: OutputMode,
+    options: RenderOptions,
+) -> eyre::Result<()> {
+    let (mut data, failures): (Vec<_>, Vec<_>) =
+        results
+            .into_iter()
+            .partition_map(|(provider, result)| match result {
+                Ok(data) => Either::Left(data),
+                Err(e) => Either::Right((provider, e.to_string())),
+            });
+
+    // Nothing to compare if all of them failed
+    if data.is_empty() {
+        return Err(eyre::eyre!(
+            "None of the providers could get the weather:\n{}",
+            failures
+                .iter()
+                .map(|(provider, e)| format!("  {provider}: {e}"))
+                .join("\n")
+        ));
+    }
+
+    // Only the hours all the providers have are compared, so that the bars line up
+    align_timestamps(&mut data);
+    let data = data
+        .into_iter()
+        .map(|data| data.in_display_zone(options.display_zone))
+        .collect_vec();
+
+    if output.prints() {
+        for data in &data {
+            println!(
+                "{}",
+                format_text(data, extreme_temperatures, output, options)
+            );
+        }
+        for (provider, e) in &failures {
+            println!("{}", options.text(format!("{provider} failed: {e}")));
+        }
+
+        return Ok(());
+    }
+
+    show_app(
+        Pages::single(App::interactive(data, failures, options)),
+        extreme_temperatures,
+        options.ascii,
+    )
+}
+
+/// Draw the data of several locations: a page per location in the TUI (the failed ones say why),
+/// or one after another as plain text
+pub(crate) fn draw_batch(
+    results: Vec<(String, Result<WeatherData, WeatherError>)>,
+    extreme_temperatures: &ExtremeTemperatures,
+    output: OutputMode,
+    options: RenderOptions,
+) -> eyre::Result<()> {
+    // A location without the series fails on its own, like the ones without the data
+    let results = results
+        .into_iter()
+        .map(|(address, result)| {
+            let result = result
+                .map_err(eyre::Report::from)
+                .and_then(|data| check_series(&data, options).map(|_| data))
+                .map(|data| data.in_display_zone(options.display_zone));
+
+            (address, result)
+        })
+        .collect_vec();
+
+    check_batch(&results)?;
+
+    // A single array, the failed locations say why in it
+    if output == OutputMode::Json {
+        let array = results
+            .iter()
+            .map(|(address, result)| {
+                let mut object = match result {
+                    Ok(data) => json_object(data, options),
+                    Err(e) => {
+                        let category = e
+                            .downcast_ref::<WeatherError>()
+                            .map_or(ErrorCategory::Other, WeatherError::category);
+                        let mut object = Map::new();
+                        object.insert(
+                            "error".to_string(),
+                            json!({ "category": category.to_string(), "message": e.to_string() }),
+                        );
+
+                        object
+                    }
+                };
+                object.insert("address".to_string(), address.clone().into());
+
+                Value::Object(object)
+            })
+            .collect();
+        print!("{}", json_text(&Value::Array(array)));
+
+        return Ok(());
+    }
+
+    if output.prints() {
+        for (address, result) in &results {
+            match result {
+                Ok(data) => println!(
+                    "{}",
+                    format_text(data, extreme_temperatures, output, options)
+                ),
+                Err(e) => println!(
+                    "{}",
+                    options.text(format!("{address} failed: {}\n", e.to_string().trim()))
+                ),
+            }
+        }
+
+        return Ok(());
+    }
+
+    let pages = results
+        .into_iter()
+        .map(|(address, result)| {
+            let app = result
+                .map(|data| App::interactive(vec![data], vec![], options))
+                .map_err(|e| e.to_string());
+
+            (address, app)
+        })
+        .collect();
+
+    show_app(
+        Pages {
+            pages,
+            selected: 0,
+            language: options.language,
+            theme: options.theme,
+        },
+        extreme_temperatures,
+        options.ascii,
+    )
+}
+
+/// Print a line per location (see [`draw_compact`]), the failed ones with the reason
+pub(crate) fn draw_batch_compact(
+    results: Vec<(String, Result<CurrentSnapshot, WeatherError>)>,
+    extreme_temperatures: &ExtremeTemperatures,
+    options: RenderOptions,
+) -> eyre::Result<()> {
+    let results = results
+        .into_iter()
+        .map(|(address, result)| (address, result.map_err(|e| e.to_string())))
+        .collect_vec();
+
+    check_batch(&results)?;
+
+    for (address, result) in &results {
+        match result {
+            Ok(snapshot) => println!(
+                "{}",
+                compact::format_compact(snapshot, extreme_temperatures, options)
+            ),
+            Err(e) => println!(
+                "{}",
+                options.text(format!("{address}: {}", e.split_whitespace().join(" ")))
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that at least one of the locations got the weather, there is nothing to show otherwise
+fn check_batch<T, E: Display>(results: &[(String, Result<T, E>)]) -> eyre::Result<()> {
+    match results.iter().any(|(_, result)| result.is_ok()) {
+        true => Ok(()),
+        false => Err(eyre::eyre!(
+            "Couldn't get the weather for any of the addresses:\n{}",
+            results
+                .iter()
+                .filter_map(|(address, result)| result
+                    .as_ref()
+                    .err()
+                    .map(|e| format!("  {address}: {}", e.to_string().trim())))
+                .join("\n")
+        )),
+    }
+}
+
+/// Draw the app until the user quits, restoring the terminal afterwards (only with the ASCII
+/// characters if `ascii`)
+fn show_app(
+    app: Pages,
+    extreme_temperatures: &ExtremeTemperatures,
+    ascii: bool,
+) -> eyre::Result<()> {
+    // A panic or a Ctrl-C in raw mode would leave the terminal unusable
+    install_panic_hook();
+    install_interrupt_handler();
+
+    // Setup terminal
+    let mut guard = TerminalGuard::new(ascii)?;
+
+    // Keep the data on the screen until the user is done with it (the guard restores the terminal
+    // afterwards, even if the event loop failed)
+    run_app(&mut guard.terminal, app, extreme_temperatures)
+}
+
+/// State of the interactive view
+struct App {
+    /// Data as it was received (one per provider when comparing), every unit toggle converts from
+    /// it so that the rounding errors don't pile up
+    original: Vec<WeatherData>,
+    /// Data in the unit that is currently shown
+    data: Vec<WeatherData>,
+    /// Providers that couldn't get the data when comparing, with the reason
+    failures: Vec<(Provider, String)>,
+    unit: TemperatureUnit,
+    options: RenderOptions,
+    /// Index of the first bar on the chart (clamped when drawing, as it depends on the frame size),
+    /// `None` until the first draw centers the chart on the highlighted hour
+    scroll: Option<usize>,
+    /// How the degradations of the data are shown under it
+    warnings: WarningsView,
+}
+
+impl App {
+    fn new(
+        data: Vec<WeatherData>,
+        failures: Vec<(Provider, String)>,
+        options: RenderOptions,
+    ) -> Self {
+        Self {
+            unit: data
+                .first()
+                .map(|data| TemperatureUnit::from_provider_unit(&data.unit))
+                .unwrap_or_default(),
+            original: data.clone(),
+            data,
+            failures,
+            options,
+            scroll: None,
+            warnings: WarningsView::default(),
+        }
+    }
+
+    /// The app of the TUI, where the warnings are only counted until they are expanded with `w`
+    fn interactive(
+        data: Vec<WeatherData>,
+        failures: Vec<(Provider, String)>,
+        options: RenderOptions,
+    ) -> Self {
+        Self {
+            warnings: WarningsView::Collapsed,
+            ..Self::new(data, failures, options.interactive())
+        }
+    }
+
+    /// Switch between °C and °F
+    fn toggle_unit(&mut self) {
+        self.unit = match self.unit {
+            TemperatureUnit::Celsius => TemperatureUnit::Fahrenheit,
+            TemperatureUnit::Fahrenheit => TemperatureUnit::Celsius,
+        };
+        self.data = self
+            .original
+            .iter()
+            .map(|data| data.clone().convert_temperatures(self.unit))
+            .collect();
+    }
+
+    fn scroll_left(&mut self) {
+        self.scroll = Some(self.scroll.unwrap_or_default().saturating_sub(1));
+    }
+
+    fn scroll_right(&mut self) {
+        self.scroll = Some(self.scroll.unwrap_or_default() + 1);
+    }
+
+    fn scroll_home(&mut self) {
+        self.scroll = Some(0);
+    }
+
+    /// Scroll to the last bars (the draw clamps the scroll to them)
+    fn scroll_end(&mut self) {
+        self.scroll = Some(usize::MAX);
+    }
+
+    fn toggle_warnings(&mut self) {
+        self.warnings = self.warnings.toggle();
+    }
+}
+
+/// Apps of the locations, switched between with the tab bar (there is only one of them and no tab
+/// bar unless several locations were asked for at once)
+struct Pages {
+    /// Address as it was typed, with its app or the reason it couldn't get the weather
+    pages: Vec<(String, Result<App, String>)>,
+    selected: usize,
+    /// Language of the tab bar and of the failed pages, which have no app to take it from
+    language: Language,
+    /// Colors and borders of the tab bar and of the failed pages
+    theme: Theme,
+}
+
+impl Pages {
+    fn single(app: App) -> Self {
+        Self {
+            language: app.options.language,
+            theme: app.options.theme,
+            pages: vec![(String::new(), Ok(app))],
+            selected: 0,
+        }
+    }
+
+    /// App of the page that is shown (`None` if the location failed)
+    fn app(&mut self) -> Option<&mut App> {
+        self.pages
+            .get_mut(self.selected)
+            .and_then(|(_, app)| app.as_mut().ok())
+    }
+
+    /// Select the page, if there is one with the index
+    fn select(&mut self, index: usize) {
+        if index < self.pages.len() {
+            self.selected = index;
+        }
+    }
+
+    fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.pages.len();
+    }
+
+    fn previous(&mut self) {
+        self.selected = (self.selected + self.pages.len() - 1) % self.pages.len();
+    }
+
+    /// Switch between °C and °F on all the pages, so that they stay comparable
+    fn toggle_unit(&mut self) {
+        for (_, app) in &mut self.pages {
+            if let Ok(app) = app {
+                app.toggle_unit();
+            }
+        }
+    }
+}
+
+/// How long to wait for an event before redrawing (picks up the terminal resizes)
+const EVENT_POLL_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Redraw the data on every event until the user quits
+fn run_app(
+    terminal: &mut Terminal<impl Backend>,
+    mut pages: Pages,
+    extreme_temperatures: &ExtremeTemperatures,
+) -> eyre::Result<()> {
+    loop {
+        terminal.draw(|f| draw_pages_ui(f, &mut pages, extreme_temperatures))?;
+
+        if !event::poll(EVENT_POLL_TIMEOUT)? {
+            continue;
+        }
+
+        match event::read()? {
+            // The next draw picks up the new size, and the bars are fitted to it again (how many of
+            // them are visible, and how wide they are)
+            Event::Resize(..) => continue,
+            // Only the presses, as some terminals report the releases too
+            Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind: KeyEventKind::Press,
+                ..
+            }) => match code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                // The raw mode turns Ctrl-C into a regular key press instead of a signal
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => interrupt(),
+                KeyCode::Left => pages.app().into_iter().for_each(App::scroll_left),
+                KeyCode::Right => pages.app().into_iter().for_each(App::scroll_right),
+                KeyCode::Home => pages.app().into_iter().for_each(App::scroll_home),
+                KeyCode::End => pages.app().into_iter().for_each(App::scroll_end),
+                KeyCode::Char('u') => pages.toggle_unit(),
+                KeyCode::Char('w') => pages.app().into_iter().for_each(App::toggle_warnings),
+                KeyCode::Tab => pages.next(),
+                KeyCode::BackTab => pages.previous(),
+                KeyCode::Char(c @ '1'..='9') => pages.select(c as usize - '1' as usize),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Whether the TUI has the terminal (raw mode, the screen cleared), so that it's given back exactly
+/// once whichever way the TUI is left: normally, with an error, a panic or Ctrl-C
+static TERMINAL_TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Exit code of the runs interrupted with Ctrl-C (128 + SIGINT, like the shells use)
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Terminal set up for drawing, given back to the shell when the guard is dropped
+struct TerminalGuard {
+    terminal: Terminal<AsciiBackend<CrosstermBackend<Stdout>>>,
+}
+
+impl TerminalGuard {
+    fn new(ascii: bool) -> eyre::Result<Self> {
+        logging::hold();
+        TERMINAL_TAKEN.store(true, Ordering::SeqCst);
+
+        // Whatever was set up before a failure is undone
+        setup_terminal_for_drawing(ascii)
+            .map(|terminal| Self { terminal })
+            .inspect_err(|_| restore_terminal())
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// Restore the terminal before the panic message is printed (the default hook is still called
+/// afterwards)
+fn install_panic_hook() {
+    chain_panic_hook(restore_terminal);
+}
+
+/// Run `restore` on a panic, before the hook that was set already
+fn chain_panic_hook(restore: fn()) {
+    let hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        restore();
+
+        hook(info);
+    }));
+}
+
+/// Restore the terminal and exit on Ctrl-C. In raw mode it's a key press handled by the event loop,
+/// this is for the signals that come while the terminal is set up, or from `kill -INT`
+fn install_interrupt_handler() {
+    // There is only one TUI per run, so the handler is never set twice (which would fail)
+    let _ = ctrlc::set_handler(|| interrupt());
+}
+
+/// Give the terminal back and exit the way an interrupted program does
+fn interrupt() -> ! {
+    restore_terminal();
+
+    std::process::exit(INTERRUPTED_EXIT_CODE)
+}
+
+fn setup_terminal_for_drawing(
+    ascii: bool,
+) -> eyre::Result<Terminal<AsciiBackend<CrosstermBackend<Stdout>>>> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    // Clear stdout so nothing drawn overlaps with previous text on screen
+    execute!(stdout, Clear(ClearType::All))?;
+    let backend = AsciiBackend::new(CrosstermBackend::new(stdout), ascii);
+
+    Ok(Terminal::new(backend)?)
+}
+
+/// Give the terminal back to the shell, if the TUI has it (only the first call does anything, the
+/// rest of the exit paths find it restored)
+fn restore_terminal() {
+    if !TERMINAL_TAKEN.swap(false, Ordering::SeqCst) {
+        return;
+    }
+
+    // Nothing to do about the failures, the terminal is restored as far as it can be
+    let _ = disable_raw_mode();
+    // We're scrolling up in case shell prompt decides to overwrite the last line (which happens to me)
+    let _ = execute!(io::stdout(), ScrollUp(1), cursor::Show);
+    logging::release();
+}
+
+/// Draw the selected page, under the tab bar if there are several of them
+fn draw_pages_ui(
+    f: &mut Frame<impl Backend>,
+    pages: &mut Pages,
+    extreme_temperatures: &ExtremeTemperatures,
+) {
+    let size = f.size();
+    let size = match pages.pages.len() > 1 {
+        true => {
+            draw_tab_bar(f, pages, size);
+
+            Rect {
+                y: size.y + 1,
+                height: size.height.saturating_sub(1),
+                ..size
+            }
+        }
+        false => size,
+    };
+
+    match &mut pages.pages[pages.selected] {
+        (_, Ok(app)) => draw_weather_data_ui(f, app, extreme_temperatures, size),
+        (address, Err(e)) => draw_failed_page(f, address, e, (pages.language, &pages.theme), size),
+    }
+}
+
+/// Draw the addresses in a row, the failed ones in red, with the keys switching between them on
+/// the right (if they fit)
+fn draw_tab_bar(f: &mut Frame<impl Backend>, pages: &Pages, size: Rect) {
+    let titles = pages
+        .pages
+        .iter()
+        .enumerate()
+        .map(|(i, (address, app))| {
+            let title = format!("{} {address}", i + 1);
+
+            Spans::from(match app {
+                Ok(_) => Span::raw(title),
+                Err(_) => Span::styled(title, Style::default().fg(pages.theme.danger)),
+            })
+        })
+        .collect_vec();
+    // Every title is padded with a space on both sides, and divided from the next one
+    let tabs_width = titles
+        .iter()
+        .map(|title| title.width() as u16 + 3)
+        .sum::<u16>();
+
+    let hint = pages.language.text(Text::TabsHint);
+    if tabs_width + hint.chars().count() as u16 <= size.width {
+        f.render_widget(
+            Paragraph::new(Span::styled(hint, Style::default().fg(pages.theme.muted)))
+                .alignment(Alignment::Right),
+            Rect { height: 1, ..size },
+        );
+    }
+
+    f.render_widget(
+        Tabs::new(titles)
+            .select(pages.selected)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)),
+        Rect {
+            width: std::cmp::min(tabs_width, size.width),
+            height: 1,
+            ..size
+        },
+    );
+}
+
+/// Draw the reason the location couldn't get the weather in place of its data
+fn draw_failed_page(
+    f: &mut Frame<impl Backend>,
+    address: &str,
+    e: &str,
+    (language, theme): (Language, &Theme),
+    size: Rect,
+) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(language.format(Text::WeatherIn, &[&address]))
+        .title_alignment(Alignment::Center)
+        .border_type(theme.border(BorderType::Rounded));
+    let inner = block.inner(size);
+    f.render_widget(block, size);
+
+    let layout = Layout::default()
+        .margin(1)
+        .constraints([Constraint::Percentage(100)])
+        .split(inner);
+
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            language.format(Text::CouldntGetWeather, &[&e.trim()]),
+            extreme_temperature_style(theme),
+        ))
+        .wrap(Wrap { trim: true })
+        .alignment(Alignment::Center),
+        layout[0],
+    );
+}
+
+fn draw_weather_data_ui(
+    f: &mut Frame<impl Backend>,
+    app: &mut App,
+    extreme_temperatures: &ExtremeTemperatures,
+    size: Rect,
+) {
+    // A single provider gets the detailed view, the comparison gets a chart per provider
+    match (app.data.as_slice(), app.failures.is_empty()) {
+        ([data], true) => draw_provider_data_ui(
+            f,
+            data,
+            app.options,
+            (&mut app.scroll, app.warnings),
+            (extreme_temperatures, size),
+        ),
+        _ => draw_comparison_ui(f, app, size),
+    }
+}
+
+/// Draw the current conditions and the chart of one provider
+fn draw_provider_data_ui(
+    f: &mut Frame<impl Backend>,
+    data: &WeatherData,
+    options: RenderOptions,
+    (scroll, warnings): (&mut Option<usize>, WarningsView),
+    (extreme_temperatures, size): (&ExtremeTemperatures, Rect),
+) {
+    let title = options.text(title(data, options.language));
+    let has_chart = has_chart(data, options.series);
+    let WeatherData {
+        provider, current, ..
+    } = data;
+
+    // Outer block
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .border_type(options.theme.border(BorderType::Rounded));
+    f.render_widget(block, size);
+
+    // The keys go in the free row at the bottom, same as the banner at the top
+    draw_key_help(f, size, options);
+    draw_data_age(f, data, options, size);
+
+    // Warn about the alerts, the dangerous temperatures and the strong wind in the free row between
+    // the outer block border and the content (what's wrong with the data goes into the strip at the
+    // bottom)
+    let banners = banners(data, extreme_temperatures, options.language);
+    for (row, banner) in banners.iter().enumerate() {
+        draw_banner(
+            f,
+            Rect {
+                y: size.y + row as u16,
+                ..size
+            },
+            &options.text(banner.text.clone()),
+            banner.style(&options.theme),
+        );
+    }
+    let extreme_temperature = banners
+        .iter()
+        .any(|banner| banner.kind == BannerKind::Temperature);
+
+    // The degradations go right above the keys, and the content is moved up to make room for them
+    let degradations = data.degradations();
+    let strip_height =
+        warnings::strip_height(degradations.len(), warnings).min(size.height.saturating_sub(4));
+    let frame = size;
+    let size = Rect {
+        height: size.height - strip_height,
+        ..size
+    };
+    let strip = Rect {
+        x: size.x + 2,
+        y: (size.y + size.height).saturating_sub(2),
+        width: size.width.saturating_sub(4),
+        height: strip_height,
+    };
+
+    // The banners past the first one take the rows of the content, which is moved down for them
+    let banner_rows = banners.len().saturating_sub(1) as u16;
+    let size = Rect {
+        y: size.y + banner_rows,
+        height: size.height.saturating_sub(banner_rows),
+        ..size
+    };
+
+    // The alerts go on top of everything else, which is moved down to make room for them
+    let size = match data.alerts.is_empty() {
+        true => size,
+        false => draw_alerts(f, &data.alerts, (options.language, &options.theme), size),
+    };
+
+    // Some requests only get the current conditions or only the hourly data, so each of them is
+    // drawn only if we have it
+    match (current, has_chart) {
+        (Some(current), true) => {
+            // We set up a horizontal layout, divided into 20%/80% parts to display current weather
+            // data and forecast/history data on each side respectively
+            let horizontal_layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .margin(2)
+                .constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
+                .split(size);
+
+            draw_current_column(
+                f,
+                data,
+                current,
+                options,
+                extreme_temperature,
+                horizontal_layout[0],
+            );
+
+            // Render the forecast/history block with the chart
+            draw_charts(f, data, options, scroll, horizontal_layout[1]);
+        }
+        (Some(current), false) => {
+            // Without the chart, the current weather gets the whole frame as a centered card
+            let vertical_layout = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(2)
+                .constraints([
+                    Constraint::Percentage(15),
+                    Constraint::Percentage(70),
+                    Constraint::Percentage(15),
+                ])
+                .split(size);
+            let horizontal_layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(25),
+                    Constraint::Percentage(50),
+                    Constraint::Percentage(25),
+                ])
+                .split(vertical_layout[1]);
+
+            draw_current_column(
+                f,
+                data,
+                current,
+                options,
+                extreme_temperature,
+                horizontal_layout[1],
+            );
+        }
+        (None, true) => {
+            // If we don't have any current weather data, we just render the forecast/history block
+            // with a small margin around (and the moon and the daylight under it, if they were
+            // asked for)
+            let layout = Layout::default()
+                .margin(2)
+                .constraints(match data.astro.is_some() {
+                    true => vec![Constraint::Min(0), Constraint::Length(5)],
+                    false => vec![Constraint::Percentage(100)],
+                })
+                .split(size);
+
+            // Render the forecast/history block with the chart
+            draw_charts(f, data, options, scroll, layout[0]);
+
+            if let (Some(astro), Some(size)) = (&data.astro, layout.get(1)) {
+                draw_astro(f, astro, options, *size);
+            }
+        }
+        (None, false) => {
+            // Nothing to show (the data parsing should've failed already, but just in case)
+            let layout = Layout::default()
+                .margin(2)
+                .constraints([Constraint::Percentage(100)])
+                .split(size);
+
+            f.render_widget(
+                Paragraph::new(Span::styled(
+                    options.language.format(Text::NoWeatherData, &[provider]),
+                    extreme_temperature_style(&options.theme),
+                ))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(options.theme.border(BorderType::Plain)),
+                )
+                .alignment(Alignment::Center),
+                layout[0],
+            )
+        }
+    }
+
+    // Last, so that the expanded list goes over everything else
+    warnings::draw_warnings(f, &degradations, (warnings, options), (strip, frame));
+}
+
+/// Draw the chart of every provider one under another, with the failed providers noted above them
+fn draw_comparison_ui(f: &mut Frame<impl Backend>, app: &mut App, size: Rect) {
+    // All the providers got the same location, so any of them has it
+    let language = app.options.language;
+    let title = app
+        .data
+        .first()
+        .map(|data| {
+            format!(
+                "{} ({})",
+                language.format(
+                    Text::WeatherIn,
+                    &[&format!(
+                        "{} ({}, {})",
+                        data.location.name(),
+                        data.location.lat,
+                        data.location.lon
+                    )]
+                ),
+                language.text(Text::ProvidersCompared)
+            )
+        })
+        .unwrap_or_default();
+
+    // Outer block
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .border_type(app.options.theme.border(BorderType::Rounded));
+    f.render_widget(block, size);
+
+    draw_key_help(f, size, app.options);
+
+    // The failures don't sink the comparison, but they shouldn't go unnoticed either
+    if !app.failures.is_empty() {
+        let failures = app
+            .failures
+            .iter()
+            .map(|(provider, e)| language.format(Text::ProviderFailed, &[provider, &e.trim()]))
+            .join(" | ");
+
+        draw_banner(f, size, &failures, caveat_style(&app.options.theme));
+    }
+
+    // Every provider gets the same share of the height
+    let count = app.data.len() as u32;
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints(
+            (0..count)
+                .map(|_| Constraint::Ratio(1, count))
+                .collect_vec(),
+        )
+        .split(size);
+
+    for (data, area) in app.data.iter().zip(layout) {
+        // The current temperature goes next to the provider, there is no room for the whole card
+        // (the chart has its own borders already, so the title is enough)
+        let block = Block::default()
+            .title(app.options.text(match &data.current {
+                Some(current) => format!(
+                    " {} ({}: {} {}){} ",
+                    data.provider,
+                    language.text(Text::Now),
+                    current.temperature,
+                    data.unit,
+                    stale_note(data, language)
+                ),
+                None => format!(" {}{} ", data.provider, stale_note(data, language)),
+            }))
+            .title_alignment(Alignment::Left);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        // The scroll is shared, so the same hours are shown for all the providers
+        match has_chart(data, app.options.series) {
+            true => draw_charts(f, data, app.options, &mut app.scroll, inner),
+            false => f.render_widget(
+                Paragraph::new(language.format(Text::NoHourlyData, &[&data.provider]))
+                    .alignment(Alignment::Center),
+                inner,
+            ),
+        }
+    }
+}
+
+/// Title with the location and the provider (and the sunrise/sunset if there is no current weather
+/// block to show them in)
+fn title(data: &WeatherData, language: Language) -> String {
+    let location = format!(
+        "{} ({}, {}{})",
+        data.location.name(),
+        data.location.lat,
+        data.location.lon,
+        data.elevation
+            .map(|elevation| format!(", {elevation:.0} m"))
+            .unwrap_or_default()
+    );
+
+    format!(
+        "{}{}",
+        language.format(Text::WeatherInWithProvider, &[&location, &data.provider]),
+        match (&data.current, &data.sunrise, &data.sunset) {
+            (None, Some(_), _) | (None, _, Some(_)) => format!(" {}", daylight(data, language)),
+            _ => String::new(),
+        }
+    )
+}
+
+/// Note that the data is from the cache, with when it was fetched
+fn stale_note(data: &WeatherData, language: Language) -> String {
+    data.cached_at
+        .map(|cached_at| {
+            let time = cached_at.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+
+            format!(" ({})", language.format(Text::StaleData, &[&time]))
+        })
+        .unwrap_or_default()
+}
+
+/// When the data was computed by the provider, or when it was fetched if the provider doesn't say
+/// ("updated 2h ago", "fetched just now")
+fn data_age(
+    data: &WeatherData,
+    language: Language,
+    now: DateTime<Utc>,
+) -> (String, chrono::Duration) {
+    let (action, time) = match data.data_updated_at {
+        Some(updated_at) => (Text::Updated, updated_at),
+        None => (Text::Fetched, data.fetched_at),
+    };
+    let age = now - time;
+
+    (language.format(action, &[&language.ago(age)]), age)
+}
+
+/// Current index with its category ("35 (Fair)"), "none" if there is no current hour in the data
+fn current_aqi(air_quality: &AirQualityData, language: Language) -> String {
+    match (air_quality.current_aqi, air_quality.category()) {
+        (Some(aqi), Some(category)) => format!("{aqi:.0} ({category})"),
+        _ => language.text(Text::None).to_string(),
+    }
+}
+
+/// Lowest and highest index of the day ("18 - 42"), "none" if the day has no values
+fn aqi_range(air_quality: &AirQualityData, language: Language) -> String {
+    match (air_quality.min_aqi, air_quality.max_aqi) {
+        (Some(min), Some(max)) => format!("{min:.0} - {max:.0}"),
+        _ => language.text(Text::None).to_string(),
+    }
+}
+
+/// Particulate matter of the current hour in μg/m³ ("PM2.5 8.1, PM10 12.3", or "PM2.5 8,1;
+/// PM10 12,3" where the comma separates the decimals)
+fn particulate_matter(air_quality: &AirQualityData, language: Language) -> Option<String> {
+    let numbers = language.number_format();
+    let separator = match numbers.decimal_separator {
+        ',' => ';',
+        _ => ',',
+    };
+    let value = |pm: Option<f64>| pm.map_or("-".to_string(), |pm| numbers.number(pm, Some(1)));
+
+    match (air_quality.pm2_5, air_quality.pm10) {
+        (None, None) => None,
+        (pm2_5, pm10) => Some(format!(
+            "PM2.5 {}{separator} PM10 {}",
+            value(pm2_5),
+            value(pm10)
+        )),
+    }
+}
 
-use color_eyre::eyre;
-use crossterm::{
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType, ScrollUp},
-};
-use itertools::Itertools;
-use tui::{
-    backend::{Backend, CrosstermBackend},
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
-    text::{Span, Spans},
-    widgets::{Block, BorderType, Borders, Paragraph},
-    Frame, Terminal,
-};
+/// Sunrise and sunset, "none" during the polar days and nights
+fn daylight(data: &WeatherData, language: Language) -> String {
+    sun_times(data.sunrise.as_deref(), data.sunset.as_deref(), language)
+}
 
-use crate::{
-    data::{CurrentWeatherData, WeatherData},
-    providers::ProviderRequestType,
-    ui::bar_chart::BarChart,
-};
+/// Sunrise and sunset ("☀ 06:12 → 17:54"), "none" for the ones there aren't
+fn sun_times(sunrise: Option<&str>, sunset: Option<&str>, language: Language) -> String {
+    let none = language.text(Text::None);
 
-pub(crate) fn draw_data(data: WeatherData) -> eyre::Result<()> {
-    // Setup terminal
-    let mut terminal = setup_terminal_for_drawing()?;
+    format!("☀ {} → {}", sunrise.unwrap_or(none), sunset.unwrap_or(none))
+}
 
-    // Draw the frame
-    terminal.draw(|f| draw_weather_data_ui(f, data))?;
+/// Phase of the moon with how much of it is lit ("🌔 Waxing gibbous, 78% lit"), without the symbol
+/// if the emoji aren't shown
+fn moon_phase(astro: &AstroData, (glyphs, language): (bool, Language)) -> String {
+    let phase = format!(
+        "{}, {}",
+        astro.moon_phase.localized_name(language),
+        language.format(
+            Text::MoonIllumination,
+            &[&format!("{:.0}", astro.illumination)]
+        )
+    );
 
-    // Restore terminal
-    restore_terminal(terminal)
+    match glyphs {
+        true => format!("{} {phase}", astro.moon_phase.glyph()),
+        false => phase,
+    }
 }
 
-fn setup_terminal_for_drawing() -> eyre::Result<Terminal<CrosstermBackend<Stdout>>> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    // Clear stdout so nothing drawn overlaps with previous text on screen
-    execute!(stdout, Clear(ClearType::All))?;
-    let backend = CrosstermBackend::new(stdout);
+/// Time from the sunrise to the sunset ("11h 42m", "0h 0m" during the polar night and "24h 0m"
+/// during the polar day)
+fn day_length(astro: &AstroData, language: Language) -> String {
+    let minutes = astro.day_length.num_minutes();
 
-    Ok(Terminal::new(backend)?)
+    language.format(Text::HoursMinutes, &[&(minutes / 60), &(minutes % 60)])
 }
 
-fn restore_terminal(mut terminal: Terminal<CrosstermBackend<Stdout>>) -> eyre::Result<()> {
-    // restore terminal
-    disable_raw_mode()?;
-    // We're scrolling up in case shell prompt decides to overwrite the last line (which happens to me)
-    execute!(terminal.backend_mut(), ScrollUp(1))?;
-    terminal.show_cursor()?;
+/// Title of the forecast/history data
+fn chart_title(data: &WeatherData, series: ChartSeries, language: Language) -> String {
+    let name = match series {
+        ChartSeries::Temperature => Text::SeriesTemperature,
+        ChartSeries::FeelsLike => Text::SeriesFeelsLike,
+        ChartSeries::Wind => Text::WindSpeed,
+        ChartSeries::Pressure => Text::Pressure,
+        ChartSeries::Clouds => Text::CloudCover,
+        ChartSeries::Precipitation => Text::Precipitation,
+    };
 
-    Ok(())
+    let kind = match data.request_type {
+        ProviderRequestType::Forecast => Text::Forecast,
+        ProviderRequestType::History => Text::HistoricalData,
+    };
+
+    format!(
+        "{} {} {}{}",
+        language.format(
+            Text::SeriesKind,
+            &[&language.text(name), &language.text(kind)]
+        ),
+        language.format(Text::InUnit, &[&series.unit(data)]),
+        requested_period(data, language),
+        timezone_note(data, language)
+    )
 }
 
-fn draw_weather_data_ui(f: &mut Frame<impl Backend>, data: WeatherData) {
-    let size = f.size();
-    let WeatherData {
-        provider,
-        request_type,
-        requested_date,
-        address,
-        latitude,
-        longitude,
-        timestamps,
-        temperatures,
-        unit,
-        current,
-    } = data;
+/// The lowest, the highest and the average values of the hourly series with where it's heading
+/// ("min 8.1°C @ 05 AM · max 19.4°C @ 03 PM · avg 13.2°C · ↗ rising"), `None` if it has no values
+fn summary_line(
+    data: &WeatherData,
+    series: ChartSeries,
+    (precision, language): (Option<usize>, Language),
+) -> Option<String> {
+    let summary = SeriesSummary::new(series.values(data));
+    let unit = series.unit(data);
+    let value = |value: f64| language.number_format().quantity(value, precision, unit);
+    let hour = |i: usize| {
+        data.timestamps
+            .get(i)
+            .map(|timestamp| format!(" @ {timestamp}"))
+            .unwrap_or_default()
+    };
 
-    // Cache the length of the timestamps and temperatures lists (has to be the same one,
-    // and we do the check before this code executes)
-    let temp_ts_len = temperatures.len();
+    let parts = [
+        summary
+            .min
+            .map(|(min, i)| format!("{} {}{}", language.text(Text::Min), value(min), hour(i))),
+        summary
+            .max
+            .map(|(max, i)| format!("{} {}{}", language.text(Text::Max), value(max), hour(i))),
+        summary
+            .mean
+            .map(|mean| format!("{} {}", language.text(Text::Avg), value(mean))),
+        summary
+            .trend
+            .map(|trend| format!("{} {}", trend.arrow(), language.text(Text::Trend(trend)))),
+    ]
+    .into_iter()
+    .flatten()
+    .collect_vec();
 
-    // Outer block
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .title(format!(
-            "Weather in {address} ({latitude}, {longitude}) (Provider: {provider})"
-        ))
-        .title_alignment(Alignment::Center)
-        .border_type(BorderType::Rounded);
-    f.render_widget(block, size);
+    (!parts.is_empty()).then(|| parts.join(SUMMARY_SEPARATOR))
+}
+
+/// The best window of the hours to be outside with what it's like ("Best 3h window: 17:00–20:00 —
+/// 21°C, 10% rain, UV 3") and its penalties ("penalty 0.05: rain 0.05, ..."), `None` if it isn't
+/// asked for or there is no such window
+fn best_window_line(data: &WeatherData, options: RenderOptions) -> Option<(String, String)> {
+    let (hours, weights) = options.best_window?;
+    let window = data.best_outdoor_window(hours, &weights).ok()?;
+    let language = options.language;
+    let numbers = language.number_format();
+    let start = data.timestamps.get(window.start)?.time;
+    let end = start + chrono::Duration::hours(hours as i64);
+
+    let conditions = [
+        Some(numbers.quantity(window.temperature, Some(0), &data.unit)),
+        window
+            .rain_chance
+            .map(|chance| language.format(Text::RainChance, &[&numbers.number(chance, Some(0))])),
+        window
+            .uv_index
+            .map(|uv| language.format(Text::UvValue, &[&numbers.number(uv, Some(0))])),
+    ]
+    .into_iter()
+    .flatten()
+    .join(", ");
+    let penalties = window.penalties;
+    let penalty = |penalty: f64| numbers.number(penalty, Some(2));
+
+    Some((
+        language.format(
+            Text::BestWindow,
+            &[
+                &hours,
+                &start.format("%H:%M"),
+                &end.format("%H:%M"),
+                &conditions,
+            ],
+        ),
+        language.format(
+            Text::Penalties,
+            &[
+                &penalty(penalties.total()),
+                &penalty(penalties.rain),
+                &penalty(penalties.temperature),
+                &penalty(penalties.uv),
+                &penalty(penalties.gusts),
+            ],
+        ),
+    ))
+}
+
+/// Separator of the parts of the [`summary_line`]
+const SUMMARY_SEPARATOR: &str = " · ";
+
+/// Which timezone the times are in, as the location can be far from this machine
+fn timezone_note(data: &WeatherData, language: Language) -> String {
+    match data.timezone.is_empty() {
+        true => String::new(),
+        false => format!(" {}", language.format(Text::LocalTime, &[&data.timezone])),
+    }
+}
+
+/// Title of the daily minimums or maximums (`kind` is one of the `Daily*` texts)
+fn daily_chart_title(
+    data: &WeatherData,
+    daily: &DailyWeatherData,
+    (kind, language): (Text, Language),
+) -> String {
+    format!(
+        "{} {} {}{}",
+        language.text(kind),
+        language.format(Text::InUnit, &[&data.unit]),
+        requested_period(data, language),
+        match daily.partial_days.contains(&true) {
+            true => format!(" {}", language.text(Text::PartialDay)),
+            false => String::new(),
+        }
+    )
+}
+
+/// The requested day or days with their names, for the chart titles ("on Sat, 17 Oct 2026")
+fn requested_period(data: &WeatherData, language: Language) -> String {
+    match data.requested_range {
+        Some(range) => language.format(
+            Text::FromToDate,
+            &[&language.date(range.start), &language.date(range.end)],
+        ),
+        None => match NaiveDate::parse_from_str(&data.requested_date, "%Y-%m-%d") {
+            Ok(date) => language.format(Text::OnDate, &[&language.date(date)]),
+            Err(_) => language.format(Text::OnDate, &[&data.requested_date]),
+        },
+    }
+}
+
+/// Check if there is anything to chart
+fn has_chart(data: &WeatherData, series: ChartSeries) -> bool {
+    match (&data.daily, series) {
+        (Some(daily), _) => !daily.dates.is_empty(),
+        (None, series) => !series.values(data).is_empty(),
+    }
+}
+
+/// Draw the hourly chart of the series with the conditions under it, or the daily maximums above
+/// the daily minimums
+fn draw_charts(
+    f: &mut Frame<impl Backend>,
+    data: &WeatherData,
+    options: RenderOptions,
+    scroll: &mut Option<usize>,
+    size: Rect,
+) {
+    let RenderOptions {
+        series,
+        ascii_glyphs,
+        precision,
+        language,
+        ..
+    } = options;
+    // The temperatures are colored by how warm they are, the rest have a color of their own
+    let temperature_styles = temperature_bar_styles(&data.unit, &options.theme);
+
+    // The next minutes go above the rest, they are what the nowcast was asked for
+    let size = match &data.nowcast {
+        Some(nowcast) => {
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(match nowcast_unavailable(nowcast, language) {
+                    // Only the reason there is no chart
+                    Some(_) => [Constraint::Length(3), Constraint::Min(0)],
+                    None => [Constraint::Percentage(40), Constraint::Percentage(60)],
+                })
+                .split(size);
+
+            draw_nowcast(f, nowcast, options, layout[0]);
+
+            layout[1]
+        }
+        None => size,
+    };
+
+    let Some(daily) = &data.daily else {
+        let labels = data
+            .timestamps
+            .iter()
+            .map(|timestamp| timestamp.label.clone())
+            .collect_vec();
+        let narrow_labels = data.narrow_labels();
+        let values = series.values(data);
+        let glyphs = data
+            .weather_codes
+            .iter()
+            .map(|code| chart_glyph(code, ascii_glyphs))
+            .collect_vec();
+        let arrows = wind_arrows(data, options);
+        let title = options.text(format!(" {} ", chart_title(data, series, language)));
+        let overlay = comparison_values(data, series);
+        // The hours from the cache are dimmed
+        let cached = data
+            .sources
+            .iter()
+            .map(|source| matches!(source, DataSource::Cached { .. }))
+            .collect_vec();
+        // The legend and the best window go first, the summary parts that don't fit are left out
+        // before them
+        let summary = [
+            (!overlay.is_empty()).then(|| comparison_legend(language)),
+            // Without the penalties, they don't fit
+            best_window_line(data, options).map(|(line, _)| line),
+            summary_line(data, series, (precision, language)),
+        ]
+        .into_iter()
+        .flatten()
+        .reduce(|summary, part| format!("{summary}{SUMMARY_SEPARATOR}{part}"))
+        .map(|summary| options.text(summary));
+        let bar_styles = match series.is_temperature() {
+            true => &temperature_styles[..],
+            false => &[][..],
+        };
+        let color = series.color(&options.theme);
+
+        // The precipitation goes under the temperatures, so that it's clear which hours are wet
+        if series.is_temperature() && !data.precipitation.is_empty() {
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+                .split(size);
+
+            draw_chart(
+                f,
+                (
+                    (&labels, &narrow_labels),
+                    (&glyphs, &arrows),
+                    &overlay,
+                    (data.highlight_hour, &cached),
+                ),
+                (values, precision, None),
+                (title, summary, options),
+                (color, bar_styles),
+                scroll,
+                layout[0],
+            );
+
+            return draw_chart(
+                f,
+                (
+                    (&labels, &narrow_labels),
+                    (&[], &[]),
+                    &[],
+                    (data.highlight_hour, &cached),
+                ),
+                (&data.precipitation, precision, None),
+                (
+                    options.text(format!(
+                        " {} {} ",
+                        language.text(Text::Precipitation),
+                        language.format(Text::InUnit, &[&data.precipitation_unit])
+                    )),
+                    None,
+                    // The markers are for the main chart, the table lists the precipitation too
+                    RenderOptions {
+                        with_table: false,
+                        ..options
+                    },
+                ),
+                (ChartSeries::Precipitation.color(&options.theme), &[]),
+                scroll,
+                layout[1],
+            );
+        }
+
+        return draw_chart(
+            f,
+            (
+                (&labels, &narrow_labels),
+                (&glyphs, &arrows),
+                &overlay,
+                (data.highlight_hour, &cached),
+            ),
+            (values, precision, series.range()),
+            (title, summary, options),
+            (color, bar_styles),
+            scroll,
+            size,
+        );
+    };
+
+    if options.week {
+        return draw_week_chart(f, data, daily, options, scroll, size);
+    }
+
+    // Both charts have the same days, so the bars line up
+    let labels = (0..daily.dates.len()).map(|i| daily.label(i)).collect_vec();
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(size);
+
+    draw_chart(
+        f,
+        ((&labels, &[]), (&[], &[]), &[], (None, &[])),
+        (&daily.max_temps, precision, None),
+        (
+            options.text(format!(
+                " {} ",
+                daily_chart_title(data, daily, (Text::DailyMaximum, language))
+            )),
+            None,
+            options,
+        ),
+        (options.theme.bar, &temperature_styles),
+        scroll,
+        layout[0],
+    );
+    draw_chart(
+        f,
+        ((&labels, &[]), (&[], &[]), &[], (None, &[])),
+        (&daily.min_temps, precision, None),
+        (
+            options.text(format!(
+                " {} ",
+                daily_chart_title(data, daily, (Text::DailyMinimum, language))
+            )),
+            None,
+            options,
+        ),
+        (options.theme.bar, &temperature_styles),
+        scroll,
+        layout[1],
+    );
+}
+
+/// Draw the days as bars from their minimum to their maximum temperature, with the conditions of
+/// the day under the labels
+fn draw_week_chart(
+    f: &mut Frame<impl Backend>,
+    data: &WeatherData,
+    daily: &DailyWeatherData,
+    options: RenderOptions,
+    scroll: &mut Option<usize>,
+    size: Rect,
+) {
+    let labels = (0..daily.dates.len()).map(|i| daily.label(i)).collect_vec();
+    let ranges = labels
+        .iter()
+        .zip(daily.min_temps.iter().zip(&daily.max_temps))
+        .map(|(label, (min, max))| (label.as_str(), *min, *max))
+        .collect_vec();
+    // The providers without the daily conditions leave the row empty
+    let glyphs = match daily.weather_codes.len() == ranges.len() {
+        true => daily
+            .weather_codes
+            .iter()
+            .map(|code| chart_glyph(code, options.ascii_glyphs))
+            .collect_vec(),
+        false => Vec::new(),
+    };
+    let temperature_styles = temperature_bar_styles(&data.unit, &options.theme);
+
+    // Without the block borders and the scale
+    let (min, max) = ranges
+        .iter()
+        .flat_map(|(_, min, max)| [*min, *max])
+        .filter(|value| !value.is_nan())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+    let chart_width = size
+        .width
+        .saturating_sub(2)
+        .saturating_sub(axis_width(min, max));
+    let visible = visible_bars(ranges.len(), chart_width);
+
+    // The scroll doesn't go past the last day
+    let first = scroll.unwrap_or_default().min(ranges.len() - visible);
+    *scroll = Some(first);
+
+    let bar_width = (chart_width / visible.max(1) as u16)
+        .saturating_sub(CHART_BAR_GAP)
+        .max(1);
+
+    let title = options.text(format!(
+        " {} ",
+        daily_chart_title(data, daily, (Text::DailyRange, options.language))
+    ));
+    // Show where we are if there is something to scroll to
+    let title = match visible < ranges.len() {
+        true => format!(
+            "{title}[{}] ",
+            options.language.format(
+                Text::OfTotal,
+                &[&(first + 1), &(first + visible), &ranges.len()]
+            )
+        ),
+        false => title,
+    };
+
+    let week_block = BarChart::default()
+        .precision(options.precision)
+        .range_data(&ranges)
+        .glyphs(&glyphs)
+        .offset(first)
+        .bar_width(bar_width)
+        .bar_gap(CHART_BAR_GAP)
+        .bar_set(options.bar_set())
+        .bar_style(Style::default().fg(options.theme.bar))
+        .bar_styles(&temperature_styles)
+        .show_axis(true)
+        .axis_style(Style::default().fg(options.theme.muted))
+        .language(options.language)
+        .label_style(Style::default().add_modifier(options.theme.label))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .border_type(options.theme.border(BorderType::Plain)),
+        );
+
+    f.render_widget(week_block, size);
+}
+
+/// Glyph of the conditions under a bar
+fn chart_glyph(code: &WeatherCode, ascii_glyphs: bool) -> &'static str {
+    match (code, ascii_glyphs) {
+        // Nothing is better than a row of question marks for the hours without the data
+        (WeatherCode::Unknown, _) => "",
+        (code, true) => code.ascii_glyph(),
+        (code, false) => code.glyph(),
+    }
+}
+
+/// Arrows of the hourly wind under the bars, styled by its speed (empty if the provider didn't
+/// return the wind)
+fn wind_arrows(data: &WeatherData, options: RenderOptions) -> Vec<(&'static str, Style)> {
+    match data.wind_directions.len() == data.wind_speeds.len() {
+        true => data
+            .wind_speeds
+            .iter()
+            .zip(&data.wind_directions)
+            .map(|(speed, direction)| match speed.is_nan() {
+                true => ("", Style::default()),
+                false => (
+                    wind_arrow(direction, options.ascii_glyphs),
+                    wind_style(
+                        wind_speed_in_kmh(*speed, &data.wind_speed_unit).unwrap_or(*speed),
+                        &options.theme,
+                    ),
+                ),
+            })
+            .collect(),
+        false => Vec::new(),
+    }
+}
+
+/// Arrow pointing where the wind blows (the north wind blows south, ↓), the directions between the
+/// cardinal and the intercardinal ones get the arrow of the cardinal one. With the ASCII characters
+/// only, the diagonals are the letters in the corners of the keyboard (q ↖, p ↗, z ↙, m ↘)
+fn wind_arrow(direction: &WindDirection, ascii: bool) -> &'static str {
+    let arrow = match direction {
+        WindDirection::NNW | WindDirection::N | WindDirection::NNE => ("↓", "v"),
+        WindDirection::NE => ("↙", "z"),
+        WindDirection::ENE | WindDirection::E | WindDirection::ESE => ("←", "<"),
+        WindDirection::SE => ("↖", "q"),
+        WindDirection::SSE | WindDirection::S | WindDirection::SSW => ("↑", "^"),
+        WindDirection::SW => ("↗", "p"),
+        WindDirection::WSW | WindDirection::W | WindDirection::WNW => ("→", ">"),
+        WindDirection::NW => ("↘", "m"),
+        _ => return "",
+    };
+
+    match ascii {
+        true => arrow.1,
+        false => arrow.0,
+    }
+}
+
+/// Wind speeds in km/h from which the arrows are normal, bold, and bold in the warning color
+const WIND_SPEED_BANDS: [f64; 3] = [10.0, 30.0, 50.0];
+
+/// Style of the arrow of the wind with the speed in km/h: dim while it's light, bold when it's
+/// strong, and in the warning color too above the strong one
+fn wind_style(speed: f64, theme: &Theme) -> Style {
+    let [normal, strong, severe] = WIND_SPEED_BANDS;
+
+    match speed {
+        speed if speed > severe => Style::default()
+            .fg(theme.warning)
+            .add_modifier(Modifier::BOLD),
+        speed if speed >= strong => Style::default().add_modifier(Modifier::BOLD),
+        speed if speed >= normal => Style::default(),
+        _ => Style::default().add_modifier(Modifier::DIM),
+    }
+}
+
+/// What the styles of the wind arrows mean
+fn wind_legend(language: Language) -> String {
+    let [normal, strong, severe] = WIND_SPEED_BANDS;
+
+    language.format(Text::WindLegend, &[&normal, &strong, &severe])
+}
+
+/// Values of the series on the day before, lined up with the hours of the data by their labels
+/// (NaN for the hours that day doesn't have, like the hour repeated when the clocks go back), empty
+/// if there is nothing to compare with
+fn comparison_values(data: &WeatherData, series: ChartSeries) -> Vec<f64> {
+    let Some(comparison) = &data.comparison else {
+        return Vec::new();
+    };
+    let values = series.values(comparison);
+
+    data.timestamps
+        .iter()
+        .map(|timestamp| {
+            comparison
+                .timestamps
+                .iter()
+                .position(|other| other.label == timestamp.label)
+                .and_then(|i| values.get(i).copied())
+                .unwrap_or(f64::NAN)
+        })
+        .collect()
+}
+
+/// What the bars and the ticks on them are, when the day is compared with the day before
+fn comparison_legend(language: Language) -> String {
+    language.format(Text::TodayYesterday, &[&symbols::bar::FULL, &OVERLAY_TICK])
+}
+
+/// Colors of the temperature bars from the threshold up: the bar color of the theme below freezing,
+/// cyan while it's cool, yellow when it's warm and red when it's hot (or what the theme has in place
+/// of them, the thresholds are in the unit of the data)
+fn temperature_bar_styles(unit: &str, theme: &Theme) -> [(f64, Style); 3] {
+    let unit = TemperatureUnit::from_provider_unit(unit);
+
+    [
+        (0.0, theme.cool),
+        (15.0, theme.warning),
+        (25.0, theme.danger),
+    ]
+    .map(|(celsius, color)| {
+        (
+            unit.convert(celsius, TemperatureUnit::Celsius),
+            Style::default().fg(color),
+        )
+    })
+}
+
+/// Labels of the bars, and the narrower ones shown instead if the bars are too narrow for them
+/// (empty if there are none)
+type ChartLabels<'a> = (&'a [String], &'a [String]);
+
+/// Glyphs of the conditions and the wind arrows under the bars (empty if there are none)
+type ChartSymbols<'a> = (&'a [&'a str], &'a [(&'a str, Style)]);
+
+/// Index of the bar that stands out, and the flags of the dimmed ones (empty if none is dimmed)
+type ChartEmphasis<'a> = (Option<usize>, &'a [bool]);
+
+/// Draw the forecast/history block with the chart, starting at the scroll position if all the bars
+/// don't fit. The bars have the color, unless their value is over one of the thresholds, the
+/// glyphs and the wind arrows (if they are lined up with the values) go under the labels, the
+/// highlighted hour stands out, and the footer goes on the bottom border
+fn draw_chart(
+    f: &mut Frame<impl Backend>,
+    ((timestamps, narrow_timestamps), (glyphs, arrows), overlay, (highlight, cached)): (
+        ChartLabels,
+        ChartSymbols,
+        &[f64],
+        ChartEmphasis,
+    ),
+    (values, precision, range): (&[f64], Option<usize>, Option<(f64, f64)>),
+    (title, footer, options): (String, Option<String>, RenderOptions),
+    (color, bar_styles): (Color, &[(f64, Style)]),
+    scroll: &mut Option<usize>,
+    size: Rect,
+) {
+    // Without the block borders and the scale (which fits the overlay too)
+    let (min, max) = range.unwrap_or(
+        values
+            .iter()
+            .chain(overlay.iter().filter(|value| !value.is_nan()))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                (min.min(*value), max.max(*value))
+            }),
+    );
+    let chart_width = size
+        .width
+        .saturating_sub(2)
+        .saturating_sub(axis_width(min, max));
+    let visible = visible_bars(values.len(), chart_width);
+
+    // Until it's scrolled, the highlighted hour is in the middle if the bars don't fit, and the
+    // scroll doesn't go past the last bar
+    let first = scroll.unwrap_or_else(|| {
+        highlight
+            .map(|i| i.saturating_sub(visible / 2))
+            .unwrap_or_default()
+    });
+    let first = first.min(values.len() - visible);
+    *scroll = Some(first);
+
+    let glyphs = match glyphs.len() == values.len() {
+        true => glyphs,
+        false => &[],
+    };
+    let arrows = match arrows.len() == values.len() {
+        true => arrows,
+        false => &[],
+    };
+    // What the arrows mean goes first, if they fit under the bars
+    let footer = match symbol_rows(
+        size.height.saturating_sub(2),
+        (!glyphs.is_empty(), !arrows.is_empty()),
+    ) {
+        (_, true) => Some(
+            [Some(options.text(wind_legend(options.language))), footer]
+                .into_iter()
+                .flatten()
+                .join(SUMMARY_SEPARATOR),
+        ),
+        (_, false) => footer,
+    };
+
+    // Set the width of each bar to be evenly distributed across the width of the block
+    let bar_width = (chart_width / visible.max(1) as u16)
+        .saturating_sub(CHART_BAR_GAP)
+        .max(1);
+
+    // The labels wider than the bars are thinned out, the narrow ones (in the 24h clock, whatever
+    // the clock of the data is) leave fewer bars without a label
+    let label_width = |labels: &[String]| {
+        labels
+            .iter()
+            .map(|label| label.width())
+            .max()
+            .unwrap_or_default()
+    };
+    let narrow = !narrow_timestamps.is_empty()
+        && label_width(timestamps) > bar_width as usize
+        && label_width(narrow_timestamps) < label_width(timestamps);
+    let timestamps = match narrow {
+        true => narrow_timestamps,
+        false => timestamps,
+    };
 
-    // The forecast/archive block
     // Setup the data for the bar chart
     let weather_block_data = timestamps
         .iter()
-        .zip(temperatures)
-        .map(|(ts, temp)| (ts.as_str(), temp))
+        .zip(values)
+        .map(|(ts, value)| (ts.as_str(), *value))
         .collect_vec();
+
+    // Show where we are if there is something to scroll to
+    let title = match visible < values.len() {
+        true => format!(
+            "{title}[{}] ",
+            options.language.format(
+                Text::OfTotal,
+                &[&(first + 1), &(first + visible), &values.len()]
+            )
+        ),
+        false => title,
+    };
+    // Before the rest of the title, which is cut first on the narrow terminals
+    let title = match narrow {
+        true => format!(" {}{title}", options.language.text(Text::Hours24)),
+        false => title,
+    };
+
+    // The values are on the bar color, unless the theme has a background for them
+    let theme = options.theme;
+    let value_style = Style::default()
+        .fg(theme.value_fg)
+        .add_modifier(theme.value_modifier);
+    let value_style = match theme.value_bg {
+        Some(color) => value_style.bg(color),
+        None => value_style,
+    };
+
     let weather_block = BarChart::default()
+        .precision(precision)
         .data(weather_block_data.as_slice())
-        .bar_style(Style::default().fg(Color::Cyan))
-        .label_style(Style::default().add_modifier(Modifier::ITALIC))
-        .value_style(
+        .glyphs(glyphs)
+        .arrows(arrows)
+        .index_markers(options.with_table)
+        .overlay(
+            overlay,
             Style::default()
-                .bg(Color::Cyan)
-                .fg(Color::White)
+                .fg(theme.value_fg)
                 .add_modifier(Modifier::BOLD),
         )
+        .dimmed(cached)
+        .offset(first)
+        .bar_width(bar_width)
+        .bar_gap(CHART_BAR_GAP)
+        .bar_set(options.bar_set())
+        .bar_style(Style::default().fg(color))
+        .bar_styles(bar_styles)
+        .show_axis(true)
+        .axis_style(Style::default().fg(theme.muted))
+        .language(options.language)
+        .label_style(Style::default().add_modifier(theme.label))
+        .value_style(value_style)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(format!(
-                    " Weather {} (in {unit}) on {requested_date} ",
-                    match request_type {
-                        ProviderRequestType::Forecast => {
-                            "Forecast"
-                        }
-                        ProviderRequestType::History => {
-                            "Historical Data"
-                        }
-                    }
-                ))
+                .title(title)
                 .title_alignment(Alignment::Center)
-                .border_type(BorderType::Plain),
+                .border_type(theme.border(BorderType::Plain)),
         );
+    let weather_block = match highlight {
+        Some(i) => weather_block.highlight(
+            i,
+            Style::default()
+                .fg(theme.highlight)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+        ),
+        None => weather_block,
+    };
+    let weather_block = match range {
+        Some((min, max)) => weather_block.min(min).max(max),
+        None => weather_block,
+    };
 
-    // Check if we have any current weather data
-    match current {
-        Some(CurrentWeatherData {
-            time,
-            temperature,
-            weather_code,
-            wind_speed,
-            wind_speed_unit,
-            wind_direction,
-        }) => {
-            // If yes, we set up a horizontal layout, divided into 30%/60% parts to display current
-            // weather data and forecast/history data on each side respectively
-            let horizontal_layout = Layout::default()
-                .direction(Direction::Horizontal)
-                .margin(2)
-                .constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
-                .split(size);
+    f.render_widget(weather_block, size);
 
-            // Set up the current weather block
-            let current_weather_block = Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Plain);
+    // The same way the title is on the top border, without the corners
+    if let (Some(footer), true) = (footer, size.height > 2) {
+        let footer_size = Rect {
+            x: size.x + 1,
+            y: size.bottom() - 1,
+            width: size.width.saturating_sub(2),
+            height: 1,
+        };
 
-            let current_weather_size = &horizontal_layout[0];
+        // The parts that don't fit are left out whole, from the end
+        let footer = footer
+            .split(SUMMARY_SEPARATOR)
+            .fold(String::new(), |footer, part| {
+                let longer = match footer.is_empty() {
+                    true => part.to_string(),
+                    false => format!("{footer}{SUMMARY_SEPARATOR}{part}"),
+                };
 
-            // We divide the current weather block into 30%/70% parts vertical layout
-            let current_weather_layout = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
-                .vertical_margin(1)
-                .horizontal_margin(5)
-                .split(*current_weather_size);
-
-            // The top part is the "Heading", I put it inside the block because block titles can't
-            // be multiline and the string is too long to fit in one line
-            let current_weather_heading =
-                Paragraph::new(vec![Spans::from("Current Weather"), Spans::from(time)])
-                    .alignment(Alignment::Center);
-
-            // Render the "Heading"
-            f.render_widget(current_weather_heading, current_weather_layout[0]);
-
-            // The bottom part is the actual data we show
-            let current_weather_data = Paragraph::new(vec![
-                Spans::from(format!("Temperature: {temperature} {unit}")),
-                Spans::from(weather_code.to_string()),
-                Spans::from(""),
-                Spans::from(Span::raw(format!(
-                    "Wind Speed: {wind_speed} {wind_speed_unit}"
-                ))),
-                Spans::from(Span::raw(format!("Wind Direction: {wind_direction}"))),
-            ])
+                match longer.chars().count() + 2 <= footer_size.width as usize {
+                    true => longer,
+                    false => footer,
+                }
+            });
+
+        if !footer.is_empty() {
+            f.render_widget(
+                Paragraph::new(format!(" {footer} ")).alignment(Alignment::Center),
+                footer_size,
+            );
+        }
+    }
+}
+
+/// Gap between the chart bars
+const CHART_BAR_GAP: u16 = 1;
+
+/// Narrowest bar that still fits its value ("-12.3")
+const MIN_BAR_WIDTH: u16 = 5;
+
+/// How many bars fit in the width without getting narrower than `MIN_BAR_WIDTH`
+fn visible_bars(count: usize, width: u16) -> usize {
+    let max_bars = (width / (MIN_BAR_WIDTH + CHART_BAR_GAP)).max(1) as usize;
+
+    count.min(max_bars)
+}
+
+/// Draw the current weather block, highlighting the temperature if it's extreme
+fn draw_current_weather(
+    f: &mut Frame<impl Backend>,
+    current: &CurrentWeatherData,
+    (unit, language): (&str, Language),
+    (time, timezone): (&str, &str),
+    daylight: &str,
+    (extreme_temperature, theme): (bool, &Theme),
+    size: Rect,
+) {
+    let CurrentWeatherData {
+        temperature,
+        weather_code,
+        wind_speed,
+        wind_speed_unit,
+        wind_direction,
+        precipitation_probability,
+        apparent_temperature,
+        humidity,
+        uv_index,
+        wind_chill,
+        ..
+    } = current;
+    let numbers = language.number_format();
+
+    // Set up the current weather block
+    let current_weather_block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(theme.border(BorderType::Plain));
+
+    // We divide the current weather block into 30%/70% parts vertical layout
+    let current_weather_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .vertical_margin(1)
+        .horizontal_margin(5)
+        .split(size);
+
+    // The top part is the "Heading", I put it inside the block because block titles can't be
+    // multiline and the string is too long to fit in one line
+    let current_weather_heading = Paragraph::new(vec![
+        Spans::from(language.text(Text::CurrentWeather)),
+        Spans::from(time),
+        Spans::from(timezone),
+    ])
+    .alignment(Alignment::Center);
+
+    // Render the "Heading"
+    f.render_widget(current_weather_heading, current_weather_layout[0]);
+
+    // The bottom part is the actual data we show
+    let current_weather_data = Paragraph::new(
+        vec![
+            Spans::from(Span::styled(
+                format!(
+                    "{}: {}",
+                    language.text(Text::Temperature),
+                    numbers.quantity(*temperature, None, unit)
+                ),
+                match extreme_temperature {
+                    true => extreme_temperature_style(theme),
+                    false => Style::default(),
+                },
+            )),
+            // Not every provider has it
+            Spans::from(
+                apparent_temperature
+                    .map(|temperature| {
+                        format!(
+                            "{}: {}",
+                            language.text(Text::FeelsLike),
+                            numbers.quantity(temperature, None, unit)
+                        )
+                    })
+                    .unwrap_or_default(),
+            ),
+            Spans::from(weather_code.localized_name(language)),
+            Spans::from(""),
+            Spans::from(Span::raw(format!(
+                "{}: {}",
+                language.text(Text::WindSpeed),
+                numbers.quantity(*wind_speed, None, wind_speed_unit)
+            ))),
+            Spans::from(Span::raw(format!(
+                "{}: {wind_direction}",
+                language.text(Text::WindDirection)
+            ))),
+            Spans::from(daylight),
+            // Not every provider has it
+            Spans::from(
+                precipitation_probability
+                    .map(|probability| {
+                        format!("{}: {probability}%", language.text(Text::Precipitation))
+                    })
+                    .unwrap_or_default(),
+            ),
+            Spans::from(
+                humidity
+                    .map(|humidity| format!("{}: {humidity}%", language.text(Text::Humidity)))
+                    .unwrap_or_default(),
+            ),
+        ]
+        .into_iter()
+        // These only take a line when they are there
+        .chain(wind_chill.map(|temperature| {
+            Spans::from(format!(
+                "{}: {}",
+                language.text(Text::WindChill),
+                numbers.quantity(temperature, None, unit)
+            ))
+        }))
+        .chain(uv_index.map(|uv_index| {
+            Spans::from(format!(
+                "{}: {}",
+                language.text(Text::UvIndex),
+                numbers.number(uv_index, None)
+            ))
+        }))
+        .collect_vec(),
+    )
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(theme.border(BorderType::Rounded))
+            .title_alignment(Alignment::Center),
+    )
+    .alignment(Alignment::Center);
+
+    // Render the data
+    f.render_widget(current_weather_data, current_weather_layout[1]);
+
+    // Render the current weather block
+    f.render_widget(current_weather_block, size);
+}
+
+/// Draw the current weather, with the moon and the daylight, and the air quality under it if they
+/// were asked for
+fn draw_current_column(
+    f: &mut Frame<impl Backend>,
+    data: &WeatherData,
+    current: &CurrentWeatherData,
+    options: RenderOptions,
+    extreme_temperature: bool,
+    size: Rect,
+) {
+    let sections = [data.astro.is_some(), data.air_quality.is_some()];
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [Constraint::Min(0)]
+                .into_iter()
+                .chain(
+                    sections
+                        .iter()
+                        .filter(|shown| **shown)
+                        .map(|_| Constraint::Length(5)),
+                )
+                .collect_vec(),
+        )
+        .split(size);
+
+    draw_current_weather(
+        f,
+        current,
+        (&options.text(data.unit.clone()), options.language),
+        (&data.current_time().unwrap_or_default(), &data.timezone),
+        &options.text(daylight(data, options.language)),
+        (extreme_temperature, &options.theme),
+        layout[0],
+    );
+
+    // The sections go one under another in the order they are listed in
+    let mut sizes = layout.iter().skip(1).copied();
+    if let Some(astro) = &data.astro {
+        draw_astro(f, astro, options, sizes.next().unwrap_or_default());
+    }
+    if let Some(air_quality) = &data.air_quality {
+        draw_air_quality(f, air_quality, options, sizes.next().unwrap_or_default());
+    }
+}
+
+/// Draw the moon and daylight panel: the phase of the moon, the length of the day and the sunrise
+/// and the sunset
+fn draw_astro(f: &mut Frame<impl Backend>, astro: &AstroData, options: RenderOptions, size: Rect) {
+    let language = options.language;
+    let lines = [
+        moon_phase(astro, (!options.ascii_glyphs, language)),
+        language.format(Text::DayLength, &[&day_length(astro, language)]),
+        sun_times(astro.sunrise.as_deref(), astro.sunset.as_deref(), language),
+    ]
+    .into_iter()
+    .map(|line| Spans::from(options.text(line)))
+    .collect_vec();
+
+    f.render_widget(
+        Paragraph::new(lines)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
+                    .border_type(options.theme.border(BorderType::Rounded))
+                    .title(language.text(Text::Astro))
                     .title_alignment(Alignment::Center),
             )
-            .alignment(Alignment::Center);
+            .alignment(Alignment::Center),
+        size,
+    );
+}
 
-            // Render the data
-            f.render_widget(current_weather_data, current_weather_layout[1]);
+/// Title of the nowcast chart ("Precipitation next 90 min (in mm/h)")
+fn nowcast_title(language: Language) -> String {
+    format!(
+        "{} {}",
+        language.format(Text::Nowcast, &[&NOWCAST_MINUTES]),
+        language.format(Text::InUnit, &[&"mm/h"])
+    )
+}
 
-            // Render the current weather block
-            f.render_widget(current_weather_block, *current_weather_size);
+/// Why there is no nowcast to show (the radars don't reach the location or are down, or the
+/// response had nothing), `None` if there is one
+fn nowcast_unavailable(nowcast: &NowcastData, language: Language) -> Option<&'static str> {
+    match (nowcast.radar_coverage, nowcast.labels.is_empty()) {
+        (RadarCoverage::Ok, false) => None,
+        (RadarCoverage::TemporarilyUnavailable, _) => Some(language.text(Text::NowcastRadarDown)),
+        _ => Some(language.text(Text::NowcastNoCoverage)),
+    }
+}
 
-            // Render the forecast/history block with the chart and set the width of each bar to be
-            // evenly distributed across the width of the block
-            let weather_block_size = &horizontal_layout[1];
-            f.render_widget(
-                weather_block.bar_width(weather_block_size.width / temp_ts_len as u16),
-                *weather_block_size,
-            );
-        }
-        None => {
-            // If we don't have any current weather data, we just render the forecast/history block
-            // with a small margin around
-            let layout = Layout::default()
-                .margin(2)
-                .constraints([Constraint::Percentage(100)])
-                .split(size);
+/// Draw the precipitation rates of the next minutes from 0 up, or why there are none
+fn draw_nowcast(
+    f: &mut Frame<impl Backend>,
+    nowcast: &NowcastData,
+    options: RenderOptions,
+    size: Rect,
+) {
+    let title = options.text(format!(" {} ", nowcast_title(options.language)));
+
+    if let Some(reason) = nowcast_unavailable(nowcast, options.language) {
+        f.render_widget(
+            Paragraph::new(Span::styled(reason, caveat_style(&options.theme)))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(options.theme.border(BorderType::Plain))
+                        .title(title)
+                        .title_alignment(Alignment::Center),
+                )
+                .alignment(Alignment::Center),
+            size,
+        );
 
-            let weather_block_size = &layout[0];
+        return;
+    }
 
-            // Render the forecast/history block with the chart and set the width of each bar to be
-            // evenly distributed across the width of the block
-            f.render_widget(
-                weather_block.bar_width(weather_block_size.width / temp_ts_len as u16),
-                *weather_block_size,
+    // A drizzle shouldn't fill the whole height (`f64::max` skips the missing values)
+    let max = nowcast
+        .precipitation_rates
+        .iter()
+        .fold(1.0f64, |max, rate| max.max(*rate));
+
+    draw_chart(
+        f,
+        ((&nowcast.labels, &[]), (&[], &[]), &[], (None, &[])),
+        (
+            &nowcast.precipitation_rates,
+            options.precision,
+            Some((0.0, max)),
+        ),
+        (title, None, options),
+        (ChartSeries::Precipitation.color(&options.theme), &[]),
+        // It all fits usually, and it's not scrolled with the hours
+        &mut None,
+        size,
+    );
+}
+
+/// Draw the air quality panel: the current index colored by its category, the range of the day and
+/// the particulate matter
+fn draw_air_quality(
+    f: &mut Frame<impl Backend>,
+    air_quality: &AirQualityData,
+    options: RenderOptions,
+    size: Rect,
+) {
+    let lines = [
+        Spans::from(Span::styled(
+            current_aqi(air_quality, options.language),
+            air_quality.current_aqi.map_or(Style::default(), |aqi| {
+                air_quality_style(aqi, &options.theme)
+            }),
+        )),
+        Spans::from(
+            options
+                .language
+                .format(Text::AqiDay, &[&aqi_range(air_quality, options.language)]),
+        ),
+    ]
+    .into_iter()
+    .chain(
+        particulate_matter(air_quality, options.language)
+            .map(|matter| Spans::from(options.text(matter))),
+    )
+    .collect_vec();
+
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(options.theme.border(BorderType::Rounded))
+                    .title(options.language.text(Text::AirQuality))
+                    .title_alignment(Alignment::Center),
+            )
+            .alignment(Alignment::Center),
+        size,
+    );
+}
+
+/// Draw a one line message in the free row between the outer block border and the content
+fn draw_banner(f: &mut Frame<impl Backend>, size: Rect, text: &str, style: Style) {
+    let banner_size = Rect {
+        x: size.x + 2,
+        y: size.y + 1,
+        width: size.width.saturating_sub(4),
+        height: 1,
+    };
+
+    f.render_widget(
+        Paragraph::new(Span::styled(text, style)).alignment(Alignment::Center),
+        banner_size,
+    );
+}
+
+/// Draw the alerts in a strip at the top of the content, and return the area left under it
+fn draw_alerts(
+    f: &mut Frame<impl Backend>,
+    alerts: &[WeatherAlert],
+    (language, theme): (Language, &Theme),
+    size: Rect,
+) -> Rect {
+    // Borders and a line per alert, but never more than half of the screen
+    let height = (alerts.len() as u16 + 2).min(size.height / 2);
+    let alerts_size = Rect {
+        x: size.x + 2,
+        y: size.y + 2,
+        width: size.width.saturating_sub(4),
+        height,
+    };
+
+    let lines = alerts
+        .iter()
+        .map(|alert| {
+            Spans::from(vec![
+                Span::styled(
+                    format!("{}: ", alert.severity),
+                    extreme_temperature_style(theme),
+                ),
+                Span::raw(format!(
+                    "{}{}",
+                    alert.headline,
+                    alert_period(alert, language)
+                )),
+            ])
+        })
+        .collect_vec();
+
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(theme.border(BorderType::Plain))
+                .border_style(Style::default().fg(theme.danger))
+                .title(format!(" {} ", language.text(Text::Alerts))),
+        ),
+        alerts_size,
+    );
+
+    Rect {
+        y: size.y + height,
+        height: size.height - height,
+        ..size
+    }
+}
+
+/// When the alert is in effect (" (until 2024-01-02 18:00 UTC)"), if the provider said
+pub(crate) fn alert_period(alert: &WeatherAlert, language: Language) -> String {
+    let format = |time: DateTime<Utc>| format!("{} UTC", time.format("%Y-%m-%d %H:%M"));
+
+    match (alert.onset, alert.expires) {
+        (Some(onset), Some(expires)) => format!(" ({} - {})", format(onset), format(expires)),
+        (None, Some(expires)) => {
+            format!(
+                " ({})",
+                language.format(Text::AlertUntil, &[&format(expires)])
             )
         }
+        (Some(onset), None) => {
+            format!(" ({})", language.format(Text::AlertFrom, &[&format(onset)]))
+        }
+        (None, None) => String::new(),
+    }
+}
+
+/// Draw the available keys in the free row between the content and the outer block border
+fn draw_key_help(f: &mut Frame<impl Backend>, size: Rect, options: RenderOptions) {
+    let help_size = Rect {
+        x: size.x + 2,
+        y: (size.y + size.height).saturating_sub(2),
+        width: size.width.saturating_sub(4),
+        height: 1,
+    };
+
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            options.text(options.language.text(Text::KeysHint).to_string()),
+            Style::default().fg(options.theme.muted),
+        ))
+        .alignment(Alignment::Center),
+        help_size,
+    );
+}
+
+/// Draw how old the data is on the bottom border, on the right, in yellow or red once it's getting
+/// old
+fn draw_data_age(
+    f: &mut Frame<impl Backend>,
+    data: &WeatherData,
+    options: RenderOptions,
+    size: Rect,
+) {
+    let (text, age) = data_age(data, options.language, options.now);
+    let text = format!(" {} ", options.language.format(Text::DataAge, &[&text]));
+    let width = (text.chars().count() as u16).min(size.width.saturating_sub(4));
+    let age_size = Rect {
+        x: (size.x + size.width).saturating_sub(width + 2),
+        y: (size.y + size.height).saturating_sub(1),
+        width,
+        height: 1,
+    };
+
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            text,
+            data_age_style(age, options.data_age, &options.theme),
+        )),
+        age_size,
+    );
+}
+
+fn extreme_temperature_style(theme: &Theme) -> Style {
+    Style::default()
+        .fg(theme.danger)
+        .add_modifier(Modifier::BOLD)
+}
+
+/// Green while the air is good or fair, yellow when it's moderate, red from poor on (the colors of
+/// the theme)
+fn air_quality_style(aqi: f64, theme: &Theme) -> Style {
+    match aqi {
+        aqi if aqi <= 40.0 => Style::default().fg(theme.good),
+        aqi if aqi <= 60.0 => Style::default().fg(theme.warning),
+        _ => Style::default().fg(theme.danger),
+    }
+}
+
+/// Gray while the data is fresh, yellow when it's getting old and red when it's too old to rely on
+/// (the colors of the theme)
+fn data_age_style(age: chrono::Duration, thresholds: DataAge, theme: &Theme) -> Style {
+    match age.num_hours() {
+        hours if hours >= thresholds.critical_hours as i64 => Style::default()
+            .fg(theme.danger)
+            .add_modifier(Modifier::BOLD),
+        hours if hours >= thresholds.warning_hours as i64 => Style::default().fg(theme.warning),
+        _ => Style::default().fg(theme.muted),
+    }
+}
+
+fn caveat_style(theme: &Theme) -> Style {
+    Style::default().fg(theme.warning)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wind_arrows_point_downwind() {
+        // (direction, arrow, ASCII arrow)
+        let cases = [
+            (WindDirection::N, "↓", "v"),
+            (WindDirection::NNE, "↓", "v"),
+            (WindDirection::NE, "↙", "z"),
+            (WindDirection::E, "←", "<"),
+            (WindDirection::ESE, "←", "<"),
+            (WindDirection::SE, "↖", "q"),
+            (WindDirection::S, "↑", "^"),
+            (WindDirection::SW, "↗", "p"),
+            (WindDirection::W, "→", ">"),
+            (WindDirection::WNW, "→", ">"),
+            (WindDirection::NW, "↘", "m"),
+            (WindDirection::NNW, "↓", "v"),
+            (WindDirection::Unknown, "", ""),
+        ];
+
+        for (direction, arrow, ascii) in cases {
+            assert_eq!(wind_arrow(&direction, false), arrow, "{direction}");
+            assert_eq!(wind_arrow(&direction, true), ascii, "{direction} (ASCII)");
+        }
+    }
+
+    #[test]
+    fn panic_hook_restores_the_terminal() {
+        static RESTORED: AtomicBool = AtomicBool::new(false);
+        static REPORTED_AFTER_RESTORE: AtomicBool = AtomicBool::new(false);
+
+        // The hook printing the report, which has to find the terminal restored already
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|_| {
+            REPORTED_AFTER_RESTORE.store(RESTORED.load(Ordering::SeqCst), Ordering::SeqCst);
+        }));
+        chain_panic_hook(|| RESTORED.store(true, Ordering::SeqCst));
+
+        let mut terminal = Terminal::new(tui::backend::TestBackend::new(20, 5)).unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            terminal.draw(|_| panic!("drawing failed")).map(|_| ())
+        }));
+
+        // Back to the hooks of the test harness before asserting anything
+        let _ = std::panic::take_hook();
+        std::panic::set_hook(previous);
+
+        assert!(result.is_err());
+        assert!(RESTORED.load(Ordering::SeqCst));
+        assert!(REPORTED_AFTER_RESTORE.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn wind_styles_by_speed() {
+        let theme = Theme::default();
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let warning = bold.fg(theme.warning);
+        // The bands start at their speed
+        let cases = [
+            (0.0, dim),
+            (9.9, dim),
+            (10.0, Style::default()),
+            (29.9, Style::default()),
+            (30.0, bold),
+            (50.0, bold),
+            (50.1, warning),
+            (120.0, warning),
+        ];
+
+        for (speed, style) in cases {
+            assert_eq!(wind_style(speed, &theme), style, "{speed} km/h");
+        }
     }
 }
@@ -0,0 +1,137 @@
+//! ASCII-only output, for the terminals that can't show the box drawing characters, the blocks and
+//! the emoji (serial consoles, some CI logs)
+
+use std::io;
+
+use tui::{backend::Backend, buffer::Cell, layout::Rect, symbols};
+
+/// Bar levels drawn with the ASCII characters
+pub(crate) const BAR_SET: symbols::bar::Set = symbols::bar::Set {
+    full: "#",
+    seven_eighths: "#",
+    three_quarters: "=",
+    five_eighths: "=",
+    half: ":",
+    three_eighths: ":",
+    one_quarter: ".",
+    one_eighth: ".",
+    empty: " ",
+};
+
+/// ASCII stand-in of the character, `None` if it's ASCII already (the degree sign is dropped, "°C"
+/// is written as "C")
+fn ascii_char(c: char) -> Option<&'static str> {
+    if c.is_ascii() {
+        return None;
+    }
+
+    Some(match c {
+        '°' => "",
+        '·' | '─' | '━' | '═' | '—' | '–' => "-",
+        '│' | '┃' | '║' => "|",
+        '┌' | '┐' | '└' | '┘' | '╭' | '╮' | '╰' | '╯' | '├' | '┤' | '┬' | '┴' | '┼' => {
+            "+"
+        }
+        '█' | '▇' => "#",
+        '▆' | '▅' => "=",
+        '▄' | '▃' => ":",
+        '▂' | '▁' => ".",
+        '◀' | '←' => "<",
+        '▶' | '→' => ">",
+        '↗' => "/",
+        '↘' => "\\",
+        '☀' => "*",
+        'μ' => "u",
+        '×' => "x",
+        '³' => "3",
+        _ => "?",
+    })
+}
+
+/// The text with its non-ASCII characters replaced (the ones without an ASCII look-alike become
+/// "?")
+pub(crate) fn ascii_text(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut ascii, c| {
+            match ascii_char(c) {
+                Some(replacement) => ascii.push_str(replacement),
+                None => ascii.push(c),
+            }
+
+            ascii
+        })
+}
+
+/// Backend that draws only the ASCII characters, if it's `enabled`. The cells can't get narrower,
+/// so whatever was dropped from a cell (like the degree sign) leaves a space in its place
+pub(crate) struct AsciiBackend<B> {
+    backend: B,
+    enabled: bool,
+}
+
+impl<B> AsciiBackend<B> {
+    pub(crate) fn new(backend: B, enabled: bool) -> Self {
+        Self { backend, enabled }
+    }
+
+    pub(crate) fn inner(&self) -> &B {
+        &self.backend
+    }
+}
+
+impl<B: Backend> Backend for AsciiBackend<B> {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        if !self.enabled {
+            return self.backend.draw(content);
+        }
+
+        let cells = content
+            .map(|(x, y, cell)| {
+                let mut cell = cell.clone();
+                if !cell.symbol.is_ascii() {
+                    let symbol = ascii_text(&cell.symbol);
+                    cell.set_symbol(match symbol.is_empty() {
+                        true => " ",
+                        false => &symbol,
+                    });
+                }
+
+                (x, y, cell)
+            })
+            .collect::<Vec<_>>();
+
+        self.backend
+            .draw(cells.iter().map(|(x, y, cell)| (*x, *y, cell)))
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.backend.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.backend.show_cursor()
+    }
+
+    fn get_cursor(&mut self) -> io::Result<(u16, u16)> {
+        self.backend.get_cursor()
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.backend.set_cursor(x, y)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.backend.clear()
+    }
+
+    fn size(&self) -> io::Result<Rect> {
+        self.backend.size()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.backend.flush()
+    }
+}
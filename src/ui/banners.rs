@@ -0,0 +1,259 @@
+//! Banners at the top of the data: the alerts, the extreme temperatures and the strong wind, the
+//! most important of them first and the ones that don't fit counted on the last one
+
+use tui::style::{Modifier, Style};
+
+use crate::{
+    config::{ExtremeTemperatures, TemperatureUnit},
+    data::{wind_speed_in_kmh, CurrentWeatherData, WeatherData},
+    locale::{Language, Text},
+    ui::{extreme_temperature_style, Theme, WIND_SPEED_BANDS},
+};
+
+/// How many banners are shown, the rest of them are counted on the last one
+pub(super) const MAX_BANNERS: usize = 2;
+
+/// How much further than the thresholds the feels-like temperature has to go to be extreme on its
+/// own (it's often a few degrees off the air temperature)
+const FEELS_LIKE_MARGIN: f64 = 5.0;
+
+/// What a banner warns about, from the most important
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum BannerKind {
+    Alert,
+    Temperature,
+    Wind,
+}
+
+/// A warning at the top of the data
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Banner {
+    pub(super) kind: BannerKind,
+    pub(super) text: String,
+}
+
+impl Banner {
+    pub(super) fn style(&self, theme: &Theme) -> Style {
+        match self.kind {
+            BannerKind::Alert | BannerKind::Temperature => extreme_temperature_style(theme),
+            BannerKind::Wind => Style::default()
+                .fg(theme.warning)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+}
+
+/// Which end of the thresholds the temperature is beyond
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) enum Extreme {
+    Heat,
+    Cold,
+}
+
+impl Extreme {
+    /// Short name of the extreme, for the one line output
+    pub(super) fn short_text(&self) -> Text {
+        match self {
+            Self::Heat => Text::ExtremeHeatShort,
+            Self::Cold => Text::ExtremeColdShort,
+        }
+    }
+}
+
+/// Check if the temperature (or the feels-like one, if the provider has it) in the unit the
+/// provider named is extreme. The thresholds are in °C, so the temperatures are converted first
+pub(super) fn extreme_temperature(
+    temperature: f64,
+    feels_like: Option<f64>,
+    unit: &str,
+    thresholds: &ExtremeTemperatures,
+) -> Option<Extreme> {
+    let celsius = |temperature| {
+        TemperatureUnit::Celsius.convert(temperature, TemperatureUnit::from_provider_unit(unit))
+    };
+    let (temperature, feels_like) = (celsius(temperature), feels_like.map(celsius));
+
+    if temperature >= thresholds.heat
+        || feels_like.is_some_and(|t| t >= thresholds.heat + FEELS_LIKE_MARGIN)
+    {
+        Some(Extreme::Heat)
+    } else if temperature <= thresholds.cold
+        || feels_like.is_some_and(|t| t <= thresholds.cold - FEELS_LIKE_MARGIN)
+    {
+        Some(Extreme::Cold)
+    } else {
+        None
+    }
+}
+
+/// The banners of the data, the way [`stack_banners`] leaves them
+pub(super) fn banners(
+    data: &WeatherData,
+    thresholds: &ExtremeTemperatures,
+    language: Language,
+) -> Vec<Banner> {
+    let alerts = data.alerts.iter().map(|alert| Banner {
+        kind: BannerKind::Alert,
+        text: format!("{}: {}", alert.severity, alert.headline),
+    });
+    let current = data
+        .current
+        .iter()
+        .flat_map(|current| current_banners(current, &data.unit, thresholds, language));
+
+    stack_banners(alerts.chain(current).collect(), language)
+}
+
+/// The banners of the current conditions: the extreme temperature (with the feels-like one, the
+/// apparent temperature or the wind chill) and the strong wind
+fn current_banners(
+    current: &CurrentWeatherData,
+    unit: &str,
+    thresholds: &ExtremeTemperatures,
+    language: Language,
+) -> Vec<Banner> {
+    let numbers = language.number_format();
+    let symbol = TemperatureUnit::from_provider_unit(unit).symbol();
+    let feels_like = current.apparent_temperature.or(current.wind_chill);
+
+    let temperature =
+        extreme_temperature(current.temperature, feels_like, unit, thresholds).map(|extreme| {
+            let temperature = numbers.quantity(current.temperature, None, symbol);
+            let temperature = match feels_like {
+                Some(feels_like) => language.format(
+                    Text::TemperatureFeelsLike,
+                    &[&temperature, &numbers.quantity(feels_like, None, symbol)],
+                ),
+                None => temperature,
+            };
+
+            Banner {
+                kind: BannerKind::Temperature,
+                text: language.format(
+                    match extreme {
+                        Extreme::Heat => Text::ExtremeHeat,
+                        Extreme::Cold => Text::ExtremeCold,
+                    },
+                    &[&temperature],
+                ),
+            }
+        });
+
+    let [.., severe] = WIND_SPEED_BANDS;
+    let wind = wind_speed_in_kmh(current.wind_speed, &current.wind_speed_unit)
+        .filter(|speed| *speed > severe)
+        .map(|_| Banner {
+            kind: BannerKind::Wind,
+            text: language.format(
+                Text::StrongWind,
+                &[&numbers.quantity(current.wind_speed, None, &current.wind_speed_unit)],
+            ),
+        });
+
+    temperature.into_iter().chain(wind).collect()
+}
+
+/// The [`MAX_BANNERS`] most important banners (the alerts, then the temperature, then the wind, in
+/// the order they came in otherwise), with the rest of them counted on the last one ("+1 more")
+pub(super) fn stack_banners(mut banners: Vec<Banner>, language: Language) -> Vec<Banner> {
+    banners.sort_by_key(|banner| banner.kind);
+
+    let hidden = banners.len().saturating_sub(MAX_BANNERS);
+    banners.truncate(MAX_BANNERS);
+
+    if let Some(last) = banners.last_mut().filter(|_| hidden > 0) {
+        last.text = format!(
+            "{} ({})",
+            last.text,
+            language.format(Text::MoreBanners, &[&hidden])
+        );
+    }
+
+    banners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn banner(kind: BannerKind, text: &str) -> Banner {
+        Banner {
+            kind,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn most_important_first() {
+        let wind = banner(BannerKind::Wind, "wind");
+        let heat = banner(BannerKind::Temperature, "heat");
+        let (gale, ice) = (
+            banner(BannerKind::Alert, "gale"),
+            banner(BannerKind::Alert, "ice"),
+        );
+
+        // (banners, what is left of them)
+        let cases = [
+            (vec![], vec![]),
+            (vec![wind.clone()], vec!["wind"]),
+            (vec![wind.clone(), heat.clone()], vec!["heat", "wind"]),
+            (
+                vec![wind.clone(), heat.clone(), gale.clone()],
+                vec!["gale", "heat (+1 more)"],
+            ),
+            // The alerts stay in their order
+            (
+                vec![wind.clone(), ice.clone(), heat.clone(), gale.clone()],
+                vec!["ice", "gale (+2 more)"],
+            ),
+            (vec![heat.clone(), gale.clone()], vec!["gale", "heat"]),
+        ];
+
+        for (banners, expected) in cases {
+            let stacked = stack_banners(banners.clone(), Language::En);
+
+            assert_eq!(
+                stacked
+                    .iter()
+                    .map(|banner| &banner.text)
+                    .collect::<Vec<_>>(),
+                expected,
+                "{banners:?}"
+            );
+        }
+
+        assert_eq!(
+            stack_banners(vec![wind, heat, gale], Language::Uk)[1].text,
+            "heat (ще 1)"
+        );
+    }
+
+    #[test]
+    fn extreme_temperatures() {
+        let thresholds = ExtremeTemperatures::default();
+
+        // (temperature, feels like, unit, extreme)
+        let cases = [
+            (34.9, None, "°C", None),
+            (35.0, None, "°C", Some(Extreme::Heat)),
+            // The feels-like one has to go 5 °C further
+            (33.0, Some(39.9), "°C", None),
+            (33.0, Some(40.0), "°C", Some(Extreme::Heat)),
+            (-19.0, Some(-24.0), "°C", None),
+            (-19.0, Some(-25.0), "°C", Some(Extreme::Cold)),
+            (-20.0, None, "°C", Some(Extreme::Cold)),
+            // In °C whatever the unit of the provider is
+            (95.0, None, "°F", Some(Extreme::Heat)),
+            (94.0, None, "fahrenheit", None),
+            (-4.0, None, "°F", Some(Extreme::Cold)),
+        ];
+
+        for (temperature, feels_like, unit, expected) in cases {
+            assert_eq!(
+                extreme_temperature(temperature, feels_like, unit, &thresholds),
+                expected,
+                "{temperature} {unit}, feels like {feels_like:?}"
+            );
+        }
+    }
+}
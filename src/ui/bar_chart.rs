@@ -6,11 +6,13 @@ use unicode_width::UnicodeWidthStr;
 use tui::{
     buffer::Buffer,
     layout::Rect,
-    style::Style,
+    style::{Modifier, Style},
     symbols,
     widgets::{Block, Widget},
 };
 
+use crate::locale::{Language, Text};
+
 /// Display multiple bars in a single widgets
 ///
 /// # Examples
@@ -40,14 +42,21 @@ pub(crate) struct BarChart<'a> {
     bar_set: symbols::bar::Set,
     /// Style of the bars
     bar_style: Style,
+    /// Styles of the bars with the values from the threshold up (in the ascending order of the
+    /// thresholds), the bars below the first threshold have the `bar_style`
+    bar_styles: &'a [(f64, Style)],
     /// Style of the values printed at the bottom of each bar
     value_style: Style,
     /// Style of the labels printed under each bar
     label_style: Style,
     /// Style for the widget
     style: Style,
-    /// Slice of (label, value) pair to plot on the chart
+    /// Slice of (label, value) pair to plot on the chart (NaN for the missing values, which get a
+    /// mark instead of the bar)
     data: &'a [(&'a str, f64)],
+    /// Slice of (label, low, high) to plot as bars from the low to the high value instead of the
+    /// data (NaN for the missing ranges)
+    ranges: &'a [(&'a str, f64, f64)],
     /// Minimum value allowed for the bar chart (since this one can go downwards as well, we might
     /// want to cap off negative values potentially in some cases)
     /// (if the value is not specified, minimum value from the data is taken as reference)
@@ -55,8 +64,36 @@ pub(crate) struct BarChart<'a> {
     /// Value necessary for a bar to reach the maximum height (if no value is specified,
     /// the maximum value in the data is taken as reference)
     max: Option<f64>,
-    /// Values to display on the bar (computed when the data is passed to the widget)
-    values: Vec<String>,
+    /// Decimal places of the values on the bars (`None` for the values as they are, rounded to
+    /// one decimal)
+    precision: Option<usize>,
+    /// Symbols drawn in a row under the labels, lined up with the data (no row if it's empty)
+    glyphs: &'a [&'a str],
+    /// Symbols drawn in a row under the glyphs, each in its own style, lined up with the data (no
+    /// row if it's empty)
+    arrows: &'a [(&'a str, Style)],
+    /// Whether to mark the bars with their index (see [`index_marker`]) in a row above the labels,
+    /// for the table under the chart to refer to them
+    index_markers: bool,
+    /// Second series lined up with the data, drawn as a tick across each bar at the height of its
+    /// value (NaN for the bars without one)
+    overlay: &'a [f64],
+    /// Style of the overlay ticks
+    overlay_style: Style,
+    /// Index of the bar that stands out, and the style patched onto its bar, value and label
+    highlight: Option<(usize, Style)>,
+    /// Flags of the bars drawn dimmed (the ones from the cache), lined up with the data (no bar is
+    /// dimmed if it's empty)
+    dimmed: &'a [bool],
+    /// Number of data points skipped before the first bar (the indices of the highlight and of the
+    /// glyphs are still the ones in the whole data)
+    offset: usize,
+    /// Whether to draw the scale (max/zero/min) on the left of the bars
+    show_axis: bool,
+    /// Style of the scale, of the zero line and of the marks of the missing values
+    axis_style: Style,
+    /// Language of the marks of the missing values and of the indicators of the scrolled away bars
+    language: Language,
 }
 
 impl<'a> Default for BarChart<'a> {
@@ -66,14 +103,27 @@ impl<'a> Default for BarChart<'a> {
             min: None,
             max: None,
             data: &[],
-            values: Vec::new(),
+            ranges: &[],
+            precision: None,
+            glyphs: &[],
+            arrows: &[],
+            index_markers: false,
+            overlay: &[],
+            overlay_style: Style::default(),
+            highlight: None,
+            dimmed: &[],
+            offset: 0,
             bar_style: Style::default(),
+            bar_styles: &[],
             bar_width: 1,
             bar_gap: 1,
             bar_set: symbols::bar::NINE_LEVELS,
             value_style: Default::default(),
             label_style: Default::default(),
             style: Default::default(),
+            show_axis: false,
+            axis_style: Default::default(),
+            language: Language::default(),
         }
     }
 }
@@ -81,8 +131,59 @@ impl<'a> Default for BarChart<'a> {
 impl<'a> BarChart<'a> {
     pub fn data(mut self, data: &'a [(&'a str, f64)]) -> BarChart<'a> {
         self.data = data;
-        self.values = data.iter().map(|(_, v)| v.to_string()).collect();
+        self
+    }
+
+    /// Ranges to plot in place of the data, each bar only fills the rows between its low and its
+    /// high value on the scale of the lowest and the highest ones
+    pub fn range_data(mut self, ranges: &'a [(&'a str, f64, f64)]) -> BarChart<'a> {
+        self.ranges = ranges;
+        self
+    }
+
+    pub fn precision(mut self, precision: Option<usize>) -> BarChart<'a> {
+        self.precision = precision;
+        self
+    }
+
+    pub fn glyphs(mut self, glyphs: &'a [&'a str]) -> BarChart<'a> {
+        self.glyphs = glyphs;
+        self
+    }
+
+    pub fn arrows(mut self, arrows: &'a [(&'a str, Style)]) -> BarChart<'a> {
+        self.arrows = arrows;
+        self
+    }
 
+    pub fn index_markers(mut self, index_markers: bool) -> BarChart<'a> {
+        self.index_markers = index_markers;
+        self
+    }
+
+    pub fn overlay(mut self, overlay: &'a [f64], style: Style) -> BarChart<'a> {
+        self.overlay = overlay;
+        self.overlay_style = style;
+        self
+    }
+
+    /// Draw the bar with the index (and its label, which is always shown) in the style. The
+    /// reversed modifier only applies to the label, the reversed bar would look like a gap in the
+    /// chart
+    pub fn highlight(mut self, index: usize, style: Style) -> BarChart<'a> {
+        self.highlight = Some((index, style));
+        self
+    }
+
+    /// Dim the bars (and their values) whose flags are set
+    pub fn dimmed(mut self, dimmed: &'a [bool]) -> BarChart<'a> {
+        self.dimmed = dimmed;
+        self
+    }
+
+    /// Start the chart at the data point with the index, the ones before it are scrolled away
+    pub fn offset(mut self, offset: usize) -> BarChart<'a> {
+        self.offset = offset;
         self
     }
 
@@ -91,13 +192,11 @@ impl<'a> BarChart<'a> {
         self
     }
 
-    #[allow(dead_code)]
     pub fn min(mut self, min: f64) -> Self {
         self.min = Some(min);
         self
     }
 
-    #[allow(dead_code)]
     pub fn max(mut self, max: f64) -> Self {
         self.max = Some(max);
         self
@@ -108,6 +207,11 @@ impl<'a> BarChart<'a> {
         self
     }
 
+    pub fn bar_styles(mut self, styles: &'a [(f64, Style)]) -> BarChart<'a> {
+        self.bar_styles = styles;
+        self
+    }
+
     pub fn bar_width(mut self, width: u16) -> BarChart<'a> {
         self.bar_width = width;
         self
@@ -119,7 +223,6 @@ impl<'a> BarChart<'a> {
         self
     }
 
-    #[allow(dead_code)]
     pub fn bar_set(mut self, bar_set: symbols::bar::Set) -> BarChart<'a> {
         self.bar_set = bar_set;
         self
@@ -140,48 +243,555 @@ impl<'a> BarChart<'a> {
         self.style = style;
         self
     }
+
+    pub fn show_axis(mut self, show_axis: bool) -> BarChart<'a> {
+        self.show_axis = show_axis;
+        self
+    }
+
+    pub fn axis_style(mut self, style: Style) -> BarChart<'a> {
+        self.axis_style = style;
+        self
+    }
+
+    pub fn language(mut self, language: Language) -> BarChart<'a> {
+        self.language = language;
+        self
+    }
+
+    /// The value written on the bar with the precision, and the same value rounded for when that
+    /// doesn't fit
+    fn value_labels(&self, value: f64) -> [String; 2] {
+        let numbers = self.language.number_format();
+
+        [
+            numbers.number(value, self.precision),
+            numbers.number(value.round(), Some(0)),
+        ]
+    }
+
+    /// Style patched onto the bar with the index, if it's the highlighted one
+    fn highlight_for(&self, index: usize) -> Option<Style> {
+        self.highlight
+            .filter(|(highlighted, _)| *highlighted == index)
+            .map(|(_, style)| style)
+    }
+
+    /// Style of the bar with the index, by its value
+    fn bar_style_for(&self, index: usize) -> Style {
+        self.style_for_value(index, self.data[index].1)
+    }
+
+    /// Style of the value (or of the bar) with the index, by the threshold it's over
+    fn style_for_value(&self, index: usize, value: f64) -> Style {
+        let style = self
+            .bar_styles
+            .iter()
+            .take_while(|(threshold, _)| value >= *threshold)
+            .last()
+            .map(|(_, style)| *style)
+            .unwrap_or(self.bar_style);
+        let style = match self.dimmed.get(index).copied().unwrap_or_default() {
+            true => style.add_modifier(Modifier::DIM),
+            false => style,
+        };
+
+        match self.highlight_for(index) {
+            Some(highlight) => style.patch(highlight.remove_modifier(Modifier::REVERSED)),
+            None => style,
+        }
+    }
+
+    /// Style of the value on the bar, on the background of the bar color so that it stays readable
+    /// (unless the value style has a background of its own)
+    fn value_style_for(&self, index: usize) -> Style {
+        let style = match (self.value_style.bg, self.bar_style_for(index).fg) {
+            (None, Some(color)) => self.value_style.bg(color),
+            _ => self.value_style,
+        };
+
+        match self.dimmed.get(index).copied().unwrap_or_default() {
+            true => style.add_modifier(Modifier::DIM),
+            false => style,
+        }
+    }
+
+    /// Style of the label under the bar with the index
+    fn label_style_for(&self, index: usize) -> Style {
+        match self.highlight_for(index) {
+            Some(highlight) => self.label_style.patch(highlight),
+            None => self.label_style,
+        }
+    }
+
+    /// Labeled bars (by their position among the `count` visible ones after the `offset`), with
+    /// the offsets and the widths of their labels in the chart area. A label is only cut if it would
+    /// still touch the next one, which happens when the first and the last labels don't fit side by
+    /// side
+    fn label_spans(
+        &self,
+        label_width: u16,
+        (offset, count): (usize, usize),
+        area_width: u16,
+    ) -> Vec<(usize, u16, u16)> {
+        let indices = labeled_bar_indices(
+            self.bar_width,
+            self.bar_gap,
+            label_width,
+            count,
+            self.highlight
+                .and_then(|(index, _)| index.checked_sub(offset)),
+        );
+        let label_width = std::cmp::max(label_width, self.bar_width);
+        let offsets = indices
+            .into_iter()
+            .map(|i| {
+                (
+                    i,
+                    label_offset(i, self.bar_width, self.bar_gap, label_width, count),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        offsets
+            .iter()
+            .enumerate()
+            .map(|(n, &(i, x))| {
+                let width = std::cmp::min(label_width, area_width.saturating_sub(x));
+                let width = match offsets.get(n + 1) {
+                    Some(&(_, next)) if next < x + width => (next - x).saturating_sub(1),
+                    _ => width,
+                };
+
+                (i, x, width)
+            })
+            .collect()
+    }
+
+    /// Symbol of a cell filled up to the number of eighths
+    fn symbol(&self, eighths: u64) -> &'static str {
+        match eighths {
+            0 => self.bar_set.empty,
+            1 => self.bar_set.one_eighth,
+            2 => self.bar_set.one_quarter,
+            3 => self.bar_set.three_eighths,
+            4 => self.bar_set.half,
+            5 => self.bar_set.five_eighths,
+            6 => self.bar_set.three_quarters,
+            7 => self.bar_set.seven_eighths,
+            _ => self.bar_set.full,
+        }
+    }
+
+    /// Draw the ranges instead of the data: the bars go from the low to the high value of each
+    /// range, with the high value written above the bar, the low one under it and the labels on
+    /// the bottom row
+    fn render_ranges(
+        &self,
+        chart_area: Rect,
+        (glyph_row, arrow_row, marker_row): (Option<u16>, Option<u16>, Option<u16>),
+        border: Option<Rect>,
+        buf: &mut Buffer,
+    ) {
+        // The high values, at least a row of the bars, the low values and the labels
+        if chart_area.height < 4 {
+            return;
+        }
+
+        let values = || {
+            self.ranges
+                .iter()
+                .flat_map(|(_, low, high)| [*low, *high])
+                .filter(|value| !value.is_nan())
+        };
+        let min = self.min.unwrap_or(values().fold(f64::INFINITY, f64::min));
+        let max = self
+            .max
+            .unwrap_or(values().fold(f64::NEG_INFINITY, f64::max));
+        // Nothing but the missing ranges
+        let (min, max) = match min <= max {
+            true => (min, max),
+            false => (0.0, 0.0),
+        };
+
+        // The scale takes a few columns on the left, the bars get the rest
+        let axis_width = match self.show_axis {
+            true => axis_width(min, max).min(chart_area.width),
+            false => 0,
+        };
+        let axis_area = Rect {
+            width: axis_width,
+            ..chart_area
+        };
+        let chart_area = Rect {
+            x: chart_area.x + axis_width,
+            width: chart_area.width - axis_width,
+            ..chart_area
+        };
+
+        // The offset can't go past the last bar
+        let offset = self.offset.min(self.ranges.len().saturating_sub(1));
+        let count = std::cmp::min(
+            (chart_area.width / (self.bar_width + self.bar_gap)) as usize,
+            self.ranges.len() - offset,
+        );
+
+        if let Some(border) = border {
+            self.draw_more_indicators(
+                (offset > 0, offset + count < self.ranges.len()),
+                border,
+                buf,
+            );
+        }
+
+        // Rows the bars can take, between the high values and the low values
+        let top = chart_area.top() + 1;
+        let bottom = chart_area.bottom() - 3;
+        let available_height = u64::from(bottom + 1 - top);
+        // The labels move down to the row of the markers, which take their place above them
+        let (label_row, marker_row) = match marker_row {
+            Some(row) => (row, Some(chart_area.bottom() - 1)),
+            None => (chart_area.bottom() - 1, None),
+        };
+
+        if self.show_axis && axis_width > 0 {
+            self.draw_axis(axis_area, &[(top, max), (bottom, min)], (top, bottom), buf);
+        }
+
+        // Written centered on the bar, rounded if it doesn't fit
+        let draw_value = |buf: &mut Buffer, i: usize, value: f64, y: u16| {
+            let placed = self.value_labels(value).into_iter().find_map(|label| {
+                value_label_offset(
+                    i as u16,
+                    self.bar_width,
+                    self.bar_gap,
+                    label.width() as u16,
+                    chart_area.width,
+                )
+                .map(|x| (x, label))
+            });
+
+            if let Some((x, label)) = placed {
+                buf.set_string(
+                    chart_area.left() + x,
+                    y,
+                    label,
+                    self.style_for_value(offset + i, value),
+                );
+            }
+        };
+
+        for (i, &(_, low, high)) in self.ranges.iter().skip(offset).take(count).enumerate() {
+            let x = chart_area.left() + i as u16 * (self.bar_width + self.bar_gap);
+
+            // The missing ranges have no bar, only a mark at the bottom and "n/a" instead of the
+            // low value, both as dim as the scale
+            if low.is_nan() || high.is_nan() {
+                buf.get_mut(x + self.bar_width.saturating_sub(1) / 2, bottom)
+                    .set_symbol(MISSING_MARK)
+                    .set_style(self.axis_style);
+
+                if let Some(offset) = value_label_offset(
+                    i as u16,
+                    self.bar_width,
+                    self.bar_gap,
+                    self.language.text(Text::MissingValue).width() as u16,
+                    chart_area.width,
+                ) {
+                    buf.set_string(
+                        chart_area.left() + offset,
+                        bottom + 1,
+                        self.language.text(Text::MissingValue),
+                        self.axis_style,
+                    );
+                }
+
+                continue;
+            }
+
+            let (low, high) = (low.min(high), high.max(low));
+
+            // The bar starts at the bottom of the row of the low value (the partial cells can only
+            // be filled from the bottom) and takes at least an eighth of it
+            let steps = available_height * 8;
+            let first_row =
+                (scaled_value(low - min, max - min, steps) / 8).min(available_height - 1);
+            let end = scaled_value(high - min, max - min, steps).max(first_row * 8 + 1);
+            let last_row = (end - 1) / 8;
+            let bar_style = self.style_for_value(offset + i, high);
+
+            for row in first_row..=last_row {
+                let symbol = self.symbol(end - row * 8);
+
+                for dx in 0..self.bar_width {
+                    buf.get_mut(x + dx, bottom - row as u16)
+                        .set_symbol(symbol)
+                        .set_style(bar_style);
+                }
+            }
+
+            draw_value(buf, i, high, bottom - last_row as u16 - 1);
+            draw_value(buf, i, low, bottom - first_row as u16 + 1);
+        }
+
+        // If the labels don't fit under their bars, only label every few bars, but in full
+        let label_width = self
+            .ranges
+            .iter()
+            .skip(offset)
+            .take(count)
+            .map(|(label, ..)| label.width() as u16)
+            .max()
+            .unwrap_or_default();
+
+        for (i, label_x, width) in self.label_spans(label_width, (offset, count), chart_area.width)
+        {
+            buf.set_stringn(
+                chart_area.left() + label_x,
+                label_row,
+                self.ranges[offset + i].0,
+                width as usize,
+                self.label_style_for(offset + i),
+            );
+        }
+
+        self.draw_markers(chart_area, marker_row, (offset, count), buf);
+        self.draw_symbol_rows(chart_area, (glyph_row, arrow_row), (offset, count), buf);
+    }
+
+    /// Draw the scale with the ticks at their rows, and the line separating it from the bars
+    /// between the top and the bottom rows
+    fn draw_axis(
+        &self,
+        axis_area: Rect,
+        ticks: &[(u16, f64)],
+        (top, bottom): (u16, u16),
+        buf: &mut Buffer,
+    ) {
+        // The labels are right aligned to the line separating the scale from the bars
+        for (y, value) in ticks {
+            let tick = tick_label(*value);
+            let width = std::cmp::min(tick.width() as u16, axis_area.width - 1);
+
+            buf.set_stringn(
+                axis_area.right() - 1 - width,
+                *y,
+                tick,
+                width as usize,
+                self.axis_style,
+            );
+        }
+
+        for y in top..=bottom {
+            buf.get_mut(axis_area.right() - 1, y)
+                .set_symbol(symbols::line::VERTICAL)
+                .set_style(self.axis_style);
+        }
+    }
+
+    /// Draw the glyphs and the arrows of the visible bars in their rows (if they have them)
+    fn draw_symbol_rows(
+        &self,
+        chart_area: Rect,
+        (glyph_row, arrow_row): (Option<u16>, Option<u16>),
+        (offset, count): (usize, usize),
+        buf: &mut Buffer,
+    ) {
+        if let Some(y) = glyph_row {
+            let glyphs = self.glyphs.iter().map(|glyph| (*glyph, Style::default()));
+            self.draw_row(chart_area, y, glyphs.skip(offset).take(count), buf);
+        }
+        if let Some(y) = arrow_row {
+            let arrows = self.arrows.iter().copied();
+            self.draw_row(chart_area, y, arrows.skip(offset).take(count), buf);
+        }
+    }
+
+    /// Draw the index markers of the visible bars in the row (if there is one)
+    fn draw_markers(
+        &self,
+        chart_area: Rect,
+        marker_row: Option<u16>,
+        (offset, count): (usize, usize),
+        buf: &mut Buffer,
+    ) {
+        let Some(y) = marker_row else {
+            return;
+        };
+        let markers = (offset..offset + count)
+            .map(index_marker)
+            .collect::<Vec<_>>();
+
+        self.draw_row(
+            chart_area,
+            y,
+            markers
+                .iter()
+                .map(|marker| (marker.as_str(), self.axis_style)),
+            buf,
+        );
+    }
+
+    /// Draw the symbols of the visible bars in the row, each centered on its bar
+    fn draw_row<'s>(
+        &self,
+        chart_area: Rect,
+        y: u16,
+        symbols: impl Iterator<Item = (&'s str, Style)>,
+        buf: &mut Buffer,
+    ) {
+        for (i, (symbol, style)) in symbols.enumerate() {
+            // The emoji are 2 cells wide
+            let width = symbol.width() as u16;
+            let x = symbol_offset(i, self.bar_width, self.bar_gap, width);
+
+            if x + width <= chart_area.width {
+                buf.set_string(chart_area.left() + x, y, symbol, style);
+            }
+        }
+    }
+
+    /// Mark the sides of the top border that have more bars beyond them, where the border isn't
+    /// taken by the title
+    fn draw_more_indicators(&self, (before, after): (bool, bool), area: Rect, buf: &mut Buffer) {
+        let more_after = self.language.text(Text::MoreAfter);
+        let indicators = [
+            (
+                before,
+                self.language.text(Text::MoreBefore),
+                area.left() + 1,
+            ),
+            (
+                after,
+                more_after,
+                area.right().saturating_sub(1 + more_after.width() as u16),
+            ),
+        ];
+
+        for (_, indicator, x) in indicators.into_iter().filter(|(shown, ..)| *shown) {
+            let width = indicator.width() as u16;
+            let free = x > area.left()
+                && x + width < area.right()
+                && (x..x + width)
+                    .all(|x| buf.get(x, area.top()).symbol == symbols::line::HORIZONTAL);
+
+            if free {
+                buf.set_string(x, area.top(), indicator, self.axis_style);
+            }
+        }
+    }
 }
 
+/// Tick drawn across the bars at the values of the overlay
+pub(crate) const OVERLAY_TICK: &str = symbols::line::THICK_HORIZONTAL;
+
+/// Drawn instead of the bars of the missing values (NaN)
+const MISSING_MARK: &str = "×";
+
 impl<'a> Widget for BarChart<'a> {
     fn render(mut self, area: Rect, buf: &mut Buffer) {
         buf.set_style(area, self.style);
 
-        let chart_area = match self.block.take() {
+        let (chart_area, border) = match self.block.take() {
             Some(b) => {
                 let inner_area = b.inner(area);
                 b.render(area, buf);
-                inner_area
+                (inner_area, Some(area))
             }
-            None => area,
+            None => (area, None),
         };
 
         if chart_area.height < 2 {
             return;
         }
 
-        let min =
-            self.min
-                .unwrap_or(self.data.iter().fold(f64::INFINITY, |min, (_, val)| {
-                    match *val < min {
-                        true => *val,
-                        false => min,
-                    }
-                }));
+        // The glyphs take the bottom row and the arrows the one under it, if there is still room
+        // for the bars above them
+        let (glyphs_fit, arrows_fit) = symbol_rows(
+            chart_area.height,
+            (!self.glyphs.is_empty(), !self.arrows.is_empty()),
+        );
+        let rows = u16::from(glyphs_fit) + u16::from(arrows_fit);
+        let glyph_row = glyphs_fit.then(|| chart_area.bottom() - rows);
+        let arrow_row = arrows_fit.then(|| chart_area.bottom() - 1);
+        let chart_area = Rect {
+            height: chart_area.height - rows,
+            ..chart_area
+        };
 
-        let max =
-            self.max
-                .unwrap_or(self.data.iter().fold(-f64::INFINITY, |max, (_, val)| {
-                    match *val > max {
-                        true => *val,
-                        false => max,
-                    }
-                }));
+        // The markers take the row under the chart, if there is still room for the bars (the
+        // labels move down to it, so that the markers are right above them)
+        let marker_row = (self.index_markers && chart_area.height > MARKERS_MIN_HEIGHT)
+            .then(|| chart_area.bottom() - 1);
+        let chart_area = Rect {
+            height: chart_area.height - u16::from(marker_row.is_some()),
+            ..chart_area
+        };
+
+        if !self.ranges.is_empty() {
+            return self.render_ranges(chart_area, (glyph_row, arrow_row, marker_row), border, buf);
+        }
+
+        // The overlay has to fit on the same scale
+        let values = || {
+            self.data
+                .iter()
+                .map(|(_, val)| *val)
+                .chain(self.overlay.iter().copied().filter(|val| !val.is_nan()))
+        };
+
+        let min = self
+            .min
+            .unwrap_or(values().fold(f64::INFINITY, |min, val| match val < min {
+                true => val,
+                false => min,
+            }));
+
+        let max = self
+            .max
+            .unwrap_or(values().fold(-f64::INFINITY, |max, val| match val > max {
+                true => val,
+                false => max,
+            }));
+
+        // The scale takes a few columns on the left, the bars get the rest
+        let axis_width = match self.show_axis {
+            true => axis_width(min, max).min(chart_area.width),
+            false => 0,
+        };
+        let axis_area = Rect {
+            width: axis_width,
+            ..chart_area
+        };
+        let chart_area = Rect {
+            x: chart_area.x + axis_width,
+            width: chart_area.width - axis_width,
+            ..chart_area
+        };
+
+        // The offset can't go past the last bar
+        let offset = self.offset.min(self.data.len().saturating_sub(1));
         let max_index = std::cmp::min(
             (chart_area.width / (self.bar_width + self.bar_gap)) as usize,
-            self.data.len(),
+            self.data.len() - offset,
         );
 
-        let any_negative_values = self.data.iter().take(max_index).any(|(_, v)| *v < 0.0);
+        if let Some(border) = border {
+            self.draw_more_indicators(
+                (offset > 0, offset + max_index < self.data.len()),
+                border,
+                buf,
+            );
+        }
+
+        let any_negative_values = self
+            .data
+            .iter()
+            .map(|(_, v)| *v)
+            .skip(offset)
+            .take(max_index)
+            .chain(self.overlay.iter().copied().skip(offset).take(max_index))
+            .any(|v| v < 0.0);
 
         let available_height = match any_negative_values {
             true => chart_area.height / 2,
@@ -191,17 +801,18 @@ impl<'a> Widget for BarChart<'a> {
         let mut data = self
             .data
             .iter()
+            .skip(offset)
             .take(max_index)
             .map(|&(l, v)| {
                 let is_negative = v < 0.0;
-                let val = v.abs() as u64 * u64::from(available_height) * 8
-                    / std::cmp::max(
-                        match is_negative {
-                            true => min.abs(),
-                            false => max,
-                        } as u64,
-                        1,
-                    );
+                let val = scaled_value(
+                    v.abs(),
+                    match is_negative {
+                        true => min.abs(),
+                        false => max,
+                    },
+                    u64::from(available_height) * 8,
+                );
 
                 (l, val, is_negative && val != 0)
             })
@@ -212,82 +823,963 @@ impl<'a> Widget for BarChart<'a> {
             false => chart_area.bottom() - 2,
         };
 
-        let symbol = |value| match value {
-            0 => self.bar_set.empty,
-            1 => self.bar_set.one_eighth,
-            2 => self.bar_set.one_quarter,
-            3 => self.bar_set.three_eighths,
-            4 => self.bar_set.half,
-            5 => self.bar_set.five_eighths,
-            6 => self.bar_set.three_quarters,
-            7 => self.bar_set.seven_eighths,
-            _ => self.bar_set.full,
+        // Rows the bars can take (the zero line is the first row of both the positive and the
+        // negative bars)
+        let top = zero_line + 1 - available_height.max(1);
+        let bottom = match any_negative_values {
+            true => zero_line + available_height - 1,
+            false => zero_line,
+        };
+
+        // The bars going both ways have the labels next to them, the markers stay under the chart
+        let (label_shift, marker_row) = match (marker_row, any_negative_values) {
+            (Some(_), false) => (1, Some(zero_line + 1)),
+            (marker_row, _) => (0, marker_row),
         };
 
+        if self.show_axis && axis_width > 0 {
+            let ticks = [
+                (max > 0.0).then_some((top, max)),
+                Some((zero_line, 0.0)),
+                any_negative_values.then_some((bottom, min)),
+            ];
+
+            self.draw_axis(
+                axis_area,
+                &ticks.into_iter().flatten().collect::<Vec<_>>(),
+                (top, bottom),
+                buf,
+            );
+        }
+
+        // With the bars going both ways, the zero line shows where they start (the bars are drawn
+        // over it)
+        if any_negative_values {
+            for x in chart_area.left()..chart_area.right() {
+                buf.get_mut(x, zero_line)
+                    .set_symbol(symbols::line::HORIZONTAL)
+                    .set_style(self.axis_style);
+            }
+        }
+
+        let symbol = |value| self.symbol(value);
+
         data.iter_mut()
             .enumerate()
-            .for_each(|(i, (_, value, is_negative))| match is_negative {
-                true => (0..available_height).for_each(|j| {
-                    let symbol = symbol(*value);
-
-                    (0..self.bar_width).for_each(|x| {
-                        buf.get_mut(
-                            chart_area.left() + i as u16 * (self.bar_width + self.bar_gap) + x,
-                            zero_line + j,
-                        )
-                        .set_symbol(symbol)
-                        .set_style(self.bar_style);
-                    });
+            .for_each(|(i, (_, value, is_negative))| {
+                let bar_style = self.bar_style_for(offset + i);
 
-                    *value = value.saturating_sub(8);
-                }),
-                false => {
-                    (0..available_height).for_each(|j| {
+                match is_negative {
+                    true => (0..available_height).for_each(|j| {
                         let symbol = symbol(*value);
 
+                        // Nothing left of the bar
+                        if *value == 0 {
+                            return;
+                        }
+
                         (0..self.bar_width).for_each(|x| {
                             buf.get_mut(
                                 chart_area.left() + i as u16 * (self.bar_width + self.bar_gap) + x,
-                                zero_line - j,
+                                zero_line + j,
                             )
                             .set_symbol(symbol)
-                            .set_style(self.bar_style);
+                            .set_style(bar_style);
                         });
 
                         *value = value.saturating_sub(8);
-                    });
+                    }),
+                    false => {
+                        (0..available_height).for_each(|j| {
+                            let symbol = symbol(*value);
+
+                            // Nothing left of the bar
+                            if *value == 0 {
+                                return;
+                            }
+
+                            (0..self.bar_width).for_each(|x| {
+                                buf.get_mut(
+                                    chart_area.left()
+                                        + i as u16 * (self.bar_width + self.bar_gap)
+                                        + x,
+                                    zero_line - j,
+                                )
+                                .set_symbol(symbol)
+                                .set_style(bar_style);
+                            });
+
+                            *value = value.saturating_sub(8);
+                        });
+                    }
                 }
             });
 
-        for (i, &(label, value)) in self.data.iter().take(max_index).enumerate() {
+        // The ticks go over the bars, at the row their value would reach as a bar of its own
+        for (i, value) in self.overlay.iter().enumerate().skip(offset).take(max_index) {
+            if value.is_nan() {
+                continue;
+            }
+
+            let is_negative = *value < 0.0;
+            let steps = scaled_value(
+                value.abs(),
+                match is_negative {
+                    true => min.abs(),
+                    false => max,
+                },
+                u64::from(available_height) * 8,
+            );
+            let rows = (steps.saturating_sub(1) / 8) as u16;
+            let y = match is_negative {
+                true => zero_line + rows,
+                false => zero_line - rows,
+            };
+
+            (0..self.bar_width).for_each(|x| {
+                buf.get_mut(
+                    chart_area.left() + (i - offset) as u16 * (self.bar_width + self.bar_gap) + x,
+                    y,
+                )
+                .set_symbol(OVERLAY_TICK)
+                .set_style(self.overlay_style);
+            });
+        }
+
+        // If the labels don't fit under their bars, only label every few bars, but in full
+        let label_width = self
+            .data
+            .iter()
+            .skip(offset)
+            .take(max_index)
+            .map(|(label, _)| label.width() as u16)
+            .max()
+            .unwrap_or_default();
+        let label_spans = self.label_spans(label_width, (offset, max_index), chart_area.width);
+
+        for (i, &(label, value)) in self.data.iter().skip(offset).take(max_index).enumerate() {
+            let index = offset + i;
             let val_u64 = value.abs() as u64;
             let is_negative = value < 0.0 && val_u64 != 0;
+            // Try the full value first, and if it doesn't fit into the space that belongs to this
+            // bar, fall back to the rounded value
+            let placed_label = self
+                .value_labels(value)
+                .into_iter()
+                .find_map(|value_label| {
+                    value_label_offset(
+                        i as u16,
+                        self.bar_width,
+                        self.bar_gap,
+                        value_label.width() as u16,
+                        chart_area.width,
+                    )
+                    .map(|offset| (offset, value_label))
+                });
+
+            match (value.is_nan(), placed_label) {
+                // The missing values have no bar, only a mark where it would start and "n/a"
+                // instead of the value, both as dim as the scale
+                (true, _) => {
+                    let x = chart_area.left()
+                        + i as u16 * (self.bar_width + self.bar_gap)
+                        + self.bar_width.saturating_sub(1) / 2;
+
+                    if zero_line > chart_area.top() {
+                        buf.get_mut(x, zero_line - 1)
+                            .set_symbol(MISSING_MARK)
+                            .set_style(self.axis_style);
+                    }
 
-            if val_u64 != 0 {
-                let value_label = &self.values[i];
-                let width = value_label.width() as u16;
-                if width < self.bar_width {
+                    if let Some(offset) = value_label_offset(
+                        i as u16,
+                        self.bar_width,
+                        self.bar_gap,
+                        self.language.text(Text::MissingValue).width() as u16,
+                        chart_area.width,
+                    ) {
+                        buf.set_string(
+                            chart_area.left() + offset,
+                            zero_line,
+                            self.language.text(Text::MissingValue),
+                            self.axis_style,
+                        );
+                    }
+                }
+                (false, Some((offset, value_label))) => {
                     buf.set_string(
-                        chart_area.left()
-                            + i as u16 * (self.bar_width + self.bar_gap)
-                            + (self.bar_width - width) / 2,
+                        chart_area.left() + offset,
                         zero_line,
                         value_label,
-                        self.value_style,
+                        self.value_style_for(index),
                     );
                 }
+                // Even the rounded value is too wide, so it's written along the bar instead (and
+                // cut if the bar is too short)
+                (false, None) => {
+                    let value_label = value.round().to_string();
+                    let length = std::cmp::min(value_label.len() as u16, available_height);
+                    let x = chart_area.left()
+                        + i as u16 * (self.bar_width + self.bar_gap)
+                        + self.bar_width / 2;
+                    let first_y = match is_negative {
+                        true => zero_line,
+                        false => zero_line + 1 - length,
+                    };
+
+                    for (j, c) in value_label.chars().take(length as usize).enumerate() {
+                        buf.get_mut(x, first_y + j as u16)
+                            .set_char(c)
+                            .set_style(self.value_style_for(index));
+                    }
+                }
             }
 
+            let Some(&(_, label_x, width)) = label_spans.iter().find(|(labeled, ..)| *labeled == i)
+            else {
+                continue;
+            };
+
             buf.set_stringn(
-                chart_area.left() + i as u16 * (self.bar_width + self.bar_gap),
+                chart_area.left() + label_x,
                 match is_negative {
                     true => zero_line - 1,
-                    false => zero_line + 1,
+                    false => zero_line + 1 + label_shift,
                 },
                 label,
-                self.bar_width as usize,
-                self.label_style,
+                width as usize,
+                self.label_style_for(index),
+            );
+        }
+
+        self.draw_markers(chart_area, marker_row, (offset, max_index), buf);
+        self.draw_symbol_rows(chart_area, (glyph_row, arrow_row), (offset, max_index), buf);
+    }
+}
+
+/// Rows the chart needs for the index markers to get one of their own: the values, a row of the
+/// bars, the labels and the markers (and the low values of the ranges)
+const MARKERS_MIN_HEIGHT: u16 = 4;
+
+/// Marker of the bar with the index: a to z, then aa, ab... (like the columns of a spreadsheet)
+pub(crate) fn index_marker(index: usize) -> String {
+    let mut marker = Vec::new();
+    let mut rest = index + 1;
+
+    while rest > 0 {
+        rest -= 1;
+        marker.push(b'a' + (rest % 26) as u8);
+        rest /= 26;
+    }
+
+    marker.iter().rev().map(|c| *c as char).collect()
+}
+
+/// Number of steps (out of `steps`) the value takes of the range starting at 0. The values outside
+/// of it are clamped, and an empty range takes none of them
+pub(crate) fn scaled_value(value: f64, range: f64, steps: u64) -> u64 {
+    match range > 0.0 {
+        true => (value / range * steps as f64).clamp(0.0, steps as f64) as u64,
+        false => 0,
+    }
+}
+
+/// Width of the scale for the values between `min` and `max`, with the line separating it from the
+/// bars
+pub(crate) fn axis_width(min: f64, max: f64) -> u16 {
+    [min.min(0.0), 0.0, max.max(0.0)]
+        .into_iter()
+        .map(|value| tick_label(value).width() as u16)
+        .max()
+        .unwrap_or_default()
+        + 1
+}
+
+/// Value on the scale, rounded to one decimal so that it doesn't take the whole chart
+fn tick_label(value: f64) -> String {
+    ((value * 10.0).round() / 10.0).to_string()
+}
+
+/// Get the indices of the bars that should get a label under them.
+///
+/// If the labels are wider than the bars, labeling every bar would just truncate all of them, so
+/// only every Nth bar is labeled, with N chosen so that a full label (at its `label_offset`) and at
+/// least one empty cell fit before the next labeled bar. The first and the last bars are always
+/// labeled, the bars of the stride that would touch them are left out instead. So is the
+/// highlighted bar, unless it would touch the first or the last label itself.
+fn labeled_bar_indices(
+    bar_width: u16,
+    bar_gap: u16,
+    label_width: u16,
+    count: usize,
+    highlighted: Option<usize>,
+) -> Vec<usize> {
+    if label_width <= bar_width {
+        return (0..count).collect();
+    }
+
+    let Some(last) = count.checked_sub(1) else {
+        return Vec::new();
+    };
+
+    let offset = |i: usize| label_offset(i, bar_width, bar_gap, label_width, count);
+    // Whether the label of the bar `a` ends before the one of the bar `b`, with a cell between them
+    let apart = |a: usize, b: usize| offset(a) + label_width < offset(b);
+
+    let mut fixed = vec![0];
+    if let Some(highlighted) = highlighted.filter(|i| *i < last && apart(0, *i) && apart(*i, last))
+    {
+        fixed.push(highlighted);
+    }
+    if last > 0 {
+        fixed.push(last);
+    }
+
+    let bar_step = (bar_width + bar_gap) as usize;
+    let every = (label_width as usize + 1).div_ceil(bar_step);
+
+    // The stride starts over from the highlighted bar
+    let mut indices: Vec<usize> = Vec::new();
+    for i in 0..count {
+        let stride_start = fixed.iter().rev().find(|start| **start <= i).unwrap_or(&0);
+        let labeled = fixed.contains(&i)
+            || ((i - stride_start) % every == 0
+                && indices.last().is_none_or(|prev| apart(*prev, i))
+                && fixed
+                    .iter()
+                    .find(|next| **next > i)
+                    .is_none_or(|next| apart(i, *next)));
+
+        if labeled {
+            indices.push(i);
+        }
+    }
+
+    indices
+}
+
+/// Get the horizontal offset (relative to the chart area) of the label under the bar with the
+/// given index, out of `count` visible bars.
+///
+/// The labels are left-aligned to the first column of their bar, except the ones that would stick
+/// out past the last bar, which are moved left to end with it.
+fn label_offset(index: usize, bar_width: u16, bar_gap: u16, label_width: u16, count: usize) -> u16 {
+    let bars_end = bar_start(count, bar_width, bar_gap).saturating_sub(bar_gap);
+
+    std::cmp::min(
+        bar_start(index, bar_width, bar_gap),
+        bars_end.saturating_sub(label_width),
+    )
+}
+
+/// Offset (relative to the chart area) of the first column of the bar with the index
+fn bar_start(index: usize, bar_width: u16, bar_gap: u16) -> u16 {
+    index as u16 * (bar_width + bar_gap)
+}
+
+/// Offset (relative to the chart area) of the symbol centered on the bar with the index, in the
+/// rows of the glyphs and of the arrows (the symbols wider than the bar start where it does)
+fn symbol_offset(index: usize, bar_width: u16, bar_gap: u16, symbol_width: u16) -> u16 {
+    bar_start(index, bar_width, bar_gap) + bar_width.saturating_sub(symbol_width) / 2
+}
+
+/// Whether the rows of the glyphs and of the arrows (if there are any) fit into the chart area of
+/// the height, leaving at least two rows for the bars, the values and the labels. The glyphs are
+/// the ones left out last
+pub(crate) fn symbol_rows(height: u16, (glyphs, arrows): (bool, bool)) -> (bool, bool) {
+    let glyphs = glyphs && height > 2;
+    let arrows = arrows && height > 2 + u16::from(glyphs);
+
+    (glyphs, arrows)
+}
+
+/// Get the horizontal offset (relative to the chart area) at which the value label of the bar with
+/// the given index can be drawn without touching the cells of the neighboring bars.
+///
+/// A bar owns its own columns plus half of the gap on each side of it. Labels narrower than the bar
+/// are centered on the bar, wider ones are centered on the whole owned span, and if the label
+/// doesn't fit even there (or would stick out of the chart area), `None` is returned.
+fn value_label_offset(
+    index: u16,
+    bar_width: u16,
+    bar_gap: u16,
+    label_width: u16,
+    area_width: u16,
+) -> Option<u16> {
+    let bar_start = index * (bar_width + bar_gap);
+
+    let offset = match label_width < bar_width {
+        true => bar_start + (bar_width - label_width) / 2,
+        false => {
+            let half_gap = bar_gap / 2;
+            let span_start = bar_start.saturating_sub(half_gap);
+            let span_end = bar_start + bar_width + half_gap;
+            let span_width = span_end - span_start;
+
+            if label_width > span_width {
+                return None;
+            }
+
+            span_start + (span_width - label_width) / 2
+        }
+    };
+
+    match offset + label_width <= area_width {
+        true => Some(offset),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use tui::style::Color;
+
+    use super::*;
+    use crate::data::WeatherCode;
+
+    #[test]
+    fn value_label_offsets() {
+        for bar_width in 1..=5u16 {
+            for bar_gap in 0..=4 {
+                for label_width in 1..=8 {
+                    for area_width in 0..=40 {
+                        for index in 0..8 {
+                            let bar_start = index * (bar_width + bar_gap);
+                            let span = (
+                                bar_start.saturating_sub(bar_gap / 2),
+                                bar_start + bar_width + bar_gap / 2,
+                            );
+                            let case = format!(
+                                "bar {index}, width {bar_width}, gap {bar_gap}, label \
+                                 {label_width}, area {area_width}"
+                            );
+
+                            match value_label_offset(
+                                index,
+                                bar_width,
+                                bar_gap,
+                                label_width,
+                                area_width,
+                            ) {
+                                Some(offset) => {
+                                    assert!(offset >= span.0, "{case}");
+                                    assert!(offset + label_width <= span.1, "{case}");
+                                    assert!(offset + label_width <= area_width, "{case}");
+                                }
+                                // Only if it doesn't fit into the span or into the area
+                                None => assert!(
+                                    label_width > span.1 - span.0 || span.1 > area_width,
+                                    "{case}"
+                                ),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn value_label_offsets_centered() {
+        // Narrower than the bar, centered on it
+        assert_eq!(value_label_offset(0, 5, 1, 3, 80), Some(1));
+        assert_eq!(value_label_offset(2, 5, 1, 2, 80), Some(13));
+        // As wide as the bar or wider, centered on the bar with half of the gap on each side
+        assert_eq!(value_label_offset(1, 3, 2, 3, 80), Some(5));
+        assert_eq!(value_label_offset(1, 3, 2, 5, 80), Some(4));
+        assert_eq!(value_label_offset(0, 3, 2, 4, 80), Some(0));
+        // Wider than that
+        assert_eq!(value_label_offset(1, 3, 2, 6, 80), None);
+        assert_eq!(value_label_offset(3, 1, 1, 3, 80), None);
+        // Sticking out of the chart
+        assert_eq!(value_label_offset(9, 3, 1, 3, 38), None);
+    }
+
+    #[test]
+    fn labeled_bars() {
+        // (bar width, gap, label width, bars, highlighted bar) and the labeled bars
+        type Case = ((u16, u16, u16, usize, Option<usize>), &'static [usize]);
+
+        let cases: &[Case] = &[
+            // The labels fit under every bar
+            ((5, 1, 5, 6, None), &[0, 1, 2, 3, 4, 5]),
+            ((2, 1, 2, 3, Some(1)), &[0, 1, 2]),
+            ((3, 0, 1, 0, None), &[]),
+            // Every third bar, the last label ends with the last bar
+            ((1, 1, 5, 12, None), &[0, 3, 6, 11]),
+            ((2, 1, 5, 8, None), &[0, 2, 4, 7]),
+            ((1, 1, 5, 24, None), &[0, 3, 6, 9, 12, 15, 18, 23]),
+            // Or before the bar the last label is moved over
+            ((1, 1, 5, 10, None), &[0, 3, 9]),
+            ((1, 1, 5, 11, None), &[0, 3, 10]),
+            // The last bar after the first one only
+            ((1, 1, 5, 6, None), &[0, 5]),
+            ((1, 1, 5, 5, None), &[0, 4]),
+            ((1, 1, 5, 1, None), &[0]),
+            // The highlighted bar, with the stride going on after it
+            ((1, 1, 5, 12, Some(4)), &[0, 4, 11]),
+            ((1, 1, 5, 24, Some(13)), &[0, 3, 6, 9, 13, 16, 23]),
+            // Unless it touches the first or the last label
+            ((1, 1, 5, 12, Some(2)), &[0, 3, 6, 11]),
+            ((1, 1, 5, 12, Some(10)), &[0, 3, 6, 11]),
+            ((1, 1, 5, 12, Some(11)), &[0, 3, 6, 11]),
+        ];
+
+        for &((bar_width, bar_gap, label_width, count, highlighted), expected) in cases {
+            assert_eq!(
+                labeled_bar_indices(bar_width, bar_gap, label_width, count, highlighted),
+                expected,
+                "width {bar_width}, gap {bar_gap}, label {label_width}, {count} bars, \
+                 highlighted {highlighted:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn labeled_bars_apart() {
+        for bar_width in 1..=4u16 {
+            for bar_gap in 0..=2 {
+                for label_width in bar_width + 1..=9 {
+                    for count in 1..=30 {
+                        for highlighted in [None, Some(0), Some(count / 2), Some(count - 1)] {
+                            let case = format!(
+                                "width {bar_width}, gap {bar_gap}, label {label_width}, {count} \
+                                 bars, highlighted {highlighted:?}"
+                            );
+                            let indices = labeled_bar_indices(
+                                bar_width,
+                                bar_gap,
+                                label_width,
+                                count,
+                                highlighted,
+                            );
+                            let offsets = indices
+                                .iter()
+                                .map(|i| label_offset(*i, bar_width, bar_gap, label_width, count))
+                                .collect::<Vec<_>>();
+                            let bars_end = count as u16 * (bar_width + bar_gap) - bar_gap;
+
+                            assert_eq!(indices.first(), Some(&0), "{case}");
+                            assert_eq!(indices.last(), Some(&(count - 1)), "{case}");
+                            assert!(
+                                offsets
+                                    .iter()
+                                    .all(|x| x + label_width <= bars_end.max(label_width)),
+                                "{case}"
+                            );
+
+                            // Only the first and the last labels can touch, if they don't fit
+                            // side by side
+                            let fit = 2 * label_width < bars_end;
+                            for (a, b) in offsets.iter().tuple_windows() {
+                                assert!(
+                                    a + label_width < *b || (!fit && indices.len() == 2),
+                                    "{case}"
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Row of the buffer as a string
+    fn row(buf: &Buffer, y: u16) -> String {
+        (0..buf.area.width)
+            .map(|x| buf.get(x, y).symbol.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn hour_labels_in_full() {
+        let hours = (0..24)
+            .map(|hour| format!("{:02} {}", (hour + 11) % 12 + 1, ["AM", "PM"][hour / 12]))
+            .collect::<Vec<_>>();
+        let data = hours
+            .iter()
+            .map(|label| (label.as_str(), 20.0))
+            .collect::<Vec<_>>();
+
+        for width in [60, 80] {
+            // As the charts of the app lay the bars out
+            let bar_width = (width / 24 - 1).max(1);
+            let area = Rect::new(0, 0, width, 10);
+            let mut buf = Buffer::empty(area);
+
+            BarChart::default()
+                .data(&data)
+                .bar_width(bar_width)
+                .render(area, &mut buf);
+
+            let labels = row(&buf, 9);
+            let mut end = 0;
+            for i in labeled_bar_indices(bar_width, 1, 5, 24, None) {
+                let x = label_offset(i, bar_width, 1, 5, 24) as usize;
+
+                assert_eq!(&labels[x..x + 5], hours[i], "width {width}: {labels:?}");
+                assert!(i == 0 || end < x, "width {width}: {labels:?}");
+                end = x + 5;
+            }
+
+            assert!(labels.starts_with("12 AM "), "width {width}: {labels:?}");
+            assert!(
+                labels.trim_end().ends_with(" 11 PM"),
+                "width {width}: {labels:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn values_with_precision() {
+        let data = [
+            ("a", 17.299999999999997),
+            ("b", -2.56),
+            ("c", 0.04),
+            ("d", 21.0),
+        ];
+        let cases: [(_, _, &[&str]); 6] = [
+            (None, Language::En, &["17.3", "-2.6", "0", "21"]),
+            (Some(0), Language::En, &["17", "-3", "0", "21"]),
+            (Some(1), Language::En, &["17.3", "-2.6", "0.0", "21.0"]),
+            (Some(2), Language::En, &["17.30", "-2.56", "0.04", "21.00"]),
+            (None, Language::Uk, &["17,3", "−2,6", "0", "21"]),
+            (Some(2), Language::Uk, &["17,30", "−2,56", "0,04", "21,00"]),
+        ];
+
+        for (precision, language, expected) in cases {
+            let chart = BarChart::default()
+                .precision(precision)
+                .language(language)
+                .data(&data);
+            let values = data
+                .iter()
+                .map(|(_, value)| chart.value_labels(*value)[0].clone())
+                .collect::<Vec<_>>();
+
+            assert_eq!(values, expected, "{precision:?} {language:?}");
+        }
+    }
+
+    #[test]
+    fn arrows_under_their_bars() {
+        let data = [("a", 5.0), ("b", 10.0), ("c", 15.0), ("d", 20.0)];
+        let glyphs = ["☀", "☁", "☀", "☁"];
+        let style = Style::default().fg(Color::Red);
+        let arrows = [("↓", style), ("←", style), ("", style), ("↗", style)];
+
+        for bar_width in [2, 4] {
+            let area = Rect::new(0, 0, 20, 10);
+            let mut buf = Buffer::empty(area);
+
+            BarChart::default()
+                .data(&data)
+                .glyphs(&glyphs)
+                .arrows(&arrows)
+                .bar_width(bar_width)
+                .bar_style(Style::default().fg(Color::Blue))
+                .render(area, &mut buf);
+
+            // The glyphs above the arrows, each in the columns of its bar
+            let (glyph_row, arrow_row) = (row(&buf, 8), row(&buf, 9));
+            for (i, (glyph, (arrow, _))) in glyphs.iter().zip(&arrows).enumerate() {
+                let start = bar_start(i, bar_width, 1);
+                let x = symbol_offset(i, bar_width, 1, 1);
+                let case = format!("width {bar_width}, bar {i}");
+
+                assert!(x >= start && x < start + bar_width, "{case}");
+                assert_eq!(buf.get(x, 8).symbol, *glyph, "{case}: {glyph_row:?}");
+                match arrow.is_empty() {
+                    true => assert_eq!(buf.get(x, 9).symbol, " ", "{case}: {arrow_row:?}"),
+                    false => {
+                        assert_eq!(buf.get(x, 9).symbol, *arrow, "{case}: {arrow_row:?}");
+                        assert_eq!(buf.get(x, 9).fg, Color::Red, "{case}");
+                    }
+                }
+                // The bar is above them, in the same column
+                assert!(
+                    (0..8).any(|y| buf.get(x, y).fg == Color::Blue),
+                    "{case}: {:?}",
+                    (0..8).map(|y| row(&buf, y)).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn symbol_rows_left_out_when_short() {
+        // (height, (glyphs, arrows)) and the rows that fit
+        let cases = [
+            ((2, (true, true)), (false, false)),
+            ((3, (true, true)), (true, false)),
+            ((4, (true, true)), (true, true)),
+            ((3, (false, true)), (false, true)),
+            ((4, (false, false)), (false, false)),
+        ];
+
+        for (case, rows) in cases {
+            assert_eq!(symbol_rows(case.0, case.1), rows, "{case:?}");
+        }
+    }
+
+    #[test]
+    fn index_markers_past_z() {
+        let cases = [
+            (0, "a"),
+            (23, "x"),
+            (25, "z"),
+            (26, "aa"),
+            (27, "ab"),
+            (51, "az"),
+            (52, "ba"),
+            (701, "zz"),
+            (702, "aaa"),
+        ];
+
+        for (index, marker) in cases {
+            assert_eq!(index_marker(index), marker, "{index}");
+        }
+    }
+
+    /// Columns the value labels were written to, by the background only they have
+    fn value_label_columns(buf: &Buffer) -> Vec<u16> {
+        buf.content
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.bg == Color::Magenta)
+            .map(|(i, _)| buf.pos_of(i).0)
+            .collect()
+    }
+
+    #[test]
+    fn value_labels_stay_in_their_span() {
+        let data = (0..80)
+            .map(|i| match i % 3 {
+                0 => ("03 PM", -12.0),
+                1 => ("04 PM", -7.5),
+                _ => ("05 PM", 104.25),
+            })
+            .collect::<Vec<_>>();
+
+        for (bar_width, bar_gap) in [(1, 1), (1, 0), (2, 1), (2, 2), (3, 1), (4, 3)] {
+            let area = Rect::new(0, 0, 60, 12);
+            let mut buf = Buffer::empty(area);
+
+            BarChart::default()
+                .data(&data)
+                .bar_width(bar_width)
+                .bar_gap(bar_gap)
+                .bar_style(Style::default().fg(Color::Blue))
+                .value_style(Style::default().bg(Color::Magenta))
+                .render(area, &mut buf);
+
+            let columns = value_label_columns(&buf);
+            assert!(!columns.is_empty(), "width {bar_width}, gap {bar_gap}");
+
+            let step = bar_width + bar_gap;
+            for x in columns {
+                // In the bar or in the half of the gap next to it
+                let from_bar_start = (x + bar_gap / 2) % step;
+                assert!(
+                    from_bar_start < bar_width + bar_gap / 2 * 2,
+                    "width {bar_width}, gap {bar_gap}, column {x}"
+                );
+            }
+        }
+    }
+
+    /// Every row of the buffer
+    fn rows(buf: &Buffer) -> Vec<String> {
+        (0..buf.area.height).map(|y| row(buf, y)).collect()
+    }
+
+    #[test]
+    fn axis_and_zero_line() {
+        // Mixed temperatures: the bars go both ways from the zero line, the scale has all three
+        let data = [("a", -4.0), ("b", 8.0), ("c", 4.0), ("d", -2.0)];
+        let area = Rect::new(0, 0, 16, 10);
+        let mut buf = Buffer::empty(area);
+
+        BarChart::default()
+            .data(&data)
+            .bar_width(2)
+            .show_axis(true)
+            .render(area, &mut buf);
+
+        assert_eq!(
+            rows(&buf),
+            [
+                "                ",
+                " 8│   ██        ",
+                "  │   ██        ",
+                "  │   ██ ▄▄     ",
+                "  │a  ██ ██ d   ",
+                " 0│-4─8█─4█─-2──",
+                "  │██ b  c  ██  ",
+                "  │██       ▄▄  ",
+                "  │██           ",
+                "-4│██           ",
+            ]
+        );
+
+        // Positive only: no zero line, the bars stand on the labels
+        let data = [("a", 2.0), ("b", 8.0), ("c", 4.0)];
+        let area = Rect::new(0, 0, 16, 6);
+        let mut buf = Buffer::empty(area);
+
+        BarChart::default()
+            .data(&data)
+            .bar_width(2)
+            .show_axis(true)
+            .render(area, &mut buf);
+
+        assert_eq!(
+            rows(&buf),
+            [
+                "                ",
+                "8│   ██         ",
+                " │   ██         ",
+                " │   ██ ██      ",
+                "0│2█ 8█ 4█      ",
+                "  a  b  c       ",
+            ]
+        );
+    }
+
+    #[test]
+    fn offset_and_more_indicators() {
+        let hours = (0..24).map(|hour| format!("{hour:02}")).collect::<Vec<_>>();
+        let data = hours
+            .iter()
+            .map(|label| (label.as_str(), 10.0))
+            .collect::<Vec<_>>();
+
+        // 8 of the 24 bars fit, the indicators are only on the sides with more of them
+        for (offset, top, labels) in [
+            (
+                0,
+                "┌──────────────────more ▶┐",
+                "│00 01 02 03 04 05 06 07 │",
+            ),
+            (
+                6,
+                "┌◀ more────────────more ▶┐",
+                "│06 07 08 09 10 11 12 13 │",
+            ),
+            (
+                18,
+                "┌◀ more──────────────────┐",
+                "│18 19 20 21 22 23       │",
+            ),
+            // Past the end, the last bar is still there
+            (
+                30,
+                "┌◀ more──────────────────┐",
+                "│23                      │",
+            ),
+        ] {
+            let area = Rect::new(0, 0, 26, 8);
+            let mut buf = Buffer::empty(area);
+
+            BarChart::default()
+                .data(&data)
+                .bar_width(2)
+                .offset(offset)
+                .block(Block::default().borders(tui::widgets::Borders::ALL))
+                .render(area, &mut buf);
+
+            assert_eq!(row(&buf, 0), top, "offset {offset}");
+            assert_eq!(row(&buf, 6), labels, "offset {offset}");
+        }
+
+        // All of them fit, no indicators at all
+        let area = Rect::new(0, 0, 26, 8);
+        let mut buf = Buffer::empty(area);
+
+        BarChart::default()
+            .data(&data[..8])
+            .bar_width(2)
+            .block(Block::default().borders(tui::widgets::Borders::ALL))
+            .render(area, &mut buf);
+
+        assert_eq!(row(&buf, 0), "┌────────────────────────┐");
+    }
+
+    #[test]
+    fn glyph_row_under_the_labels() {
+        let data = [("00", 5.0), ("01", 10.0), ("02", 15.0), ("03", 20.0)];
+        let codes = [
+            WeatherCode::ClearSky,
+            WeatherCode::PartlyCloudy,
+            WeatherCode::Rain,
+            WeatherCode::SnowFall,
+        ];
+
+        // The emoji are 2 cells wide, the ASCII ones 1, both start at their bar
+        for (glyphs, expected) in [
+            (codes.map(|code| code.glyph()), "☀  ⛅  🌧  ❄  "),
+            (codes.map(|code| code.ascii_glyph()), "O  c  /  *  "),
+        ] {
+            let area = Rect::new(0, 0, 12, 8);
+            let mut buf = Buffer::empty(area);
+
+            BarChart::default()
+                .data(&data)
+                .bar_width(2)
+                .glyphs(&glyphs)
+                .render(area, &mut buf);
+
+            assert_eq!(
+                rows(&buf),
+                [
+                    "            ",
+                    "         ██ ",
+                    "      ▆▆ ██ ",
+                    "   ▄▄ ██ ██ ",
+                    "▂▂ ██ ██ ██ ",
+                    "5█ 10 15 20 ",
+                    "00 01 02 03 ",
+                    expected,
+                ]
             );
         }
     }
+
+    #[test]
+    fn range_bar_cells() {
+        // From the minimum to the maximum of every day, on the scale of all of them
+        let ranges = [
+            ("Mon 03", -2.0, 6.0),
+            ("Tue 04", 0.0, 10.0),
+            ("Wed 05", 4.0, 8.0),
+        ];
+        let glyphs = ["☀", "☁", "❄"];
+        let area = Rect::new(0, 0, 21, 12);
+        let mut buf = Buffer::empty(area);
+
+        BarChart::default()
+            .range_data(&ranges)
+            .bar_width(6)
+            .glyphs(&glyphs)
+            .render(area, &mut buf);
+
+        assert_eq!(
+            rows(&buf),
+            [
+                "         10          ",
+                "       ██████   8    ",
+                "  6    ██████ ▅▅▅▅▅▅ ",
+                "▂▂▂▂▂▂ ██████ ██████ ",
+                "██████ ██████ ██████ ",
+                "██████ ██████   4    ",
+                "██████ ██████        ",
+                "██████ ██████        ",
+                "██████   0           ",
+                "  -2                 ",
+                "Mon 03 Tue 04 Wed 05 ",
+                "  ☀      ☁      ❄    ",
+            ]
+        );
+    }
 }
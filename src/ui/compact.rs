@@ -0,0 +1,123 @@
+//! One line output for the status bars (tmux, polybar): the location, the current temperature and
+//! conditions, and a sparkline of the next hours
+
+use crate::{
+    config::{ExtremeTemperatures, TemperatureUnit},
+    data::CurrentSnapshot,
+    ui::{ascii::ascii_text, banners::extreme_temperature, bar_chart::scaled_value, RenderOptions},
+};
+
+/// Blocks from the lowest to the highest temperature
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The same levels for the ASCII-only output
+const ASCII_SPARKLINE_LEVELS: [char; 8] = ['_', '.', '-', ':', '=', '+', '*', '#'];
+
+/// Format the snapshot as a single line like "Kyiv 21°C ☀ ▃▄▅▆▇▇▆▅▄▃▂▂", with the temperatures of
+/// its hours, or like "Kyiv 21C O _.-:=+**=:-." with the ASCII characters only. An extreme
+/// temperature is called out at the end ("Kyiv 38°C ☀ ▅▆▇█ !extreme heat")
+pub(crate) fn format_compact(
+    snapshot: &CurrentSnapshot,
+    extreme_temperatures: &ExtremeTemperatures,
+    options: RenderOptions,
+) -> String {
+    // Only the first part of the name ("Kyiv" out of "Kyiv, Kyiv City, Ukraine"), there is not much
+    // space in a status bar (the coordinates are kept whole though)
+    let display_name = snapshot.location.name();
+    let name = match display_name.split(',').next() {
+        Some(first) if first.trim().parse::<f64>().is_err() => first.trim(),
+        _ => display_name,
+    };
+
+    // The providers name the units differently ("°C", "celsius")
+    let unit = TemperatureUnit::from_provider_unit(&snapshot.unit).symbol();
+
+    [
+        Some(name.to_string()),
+        // Adding 0 turns -0 (anything between -0.5 and 0) into 0
+        snapshot.temperature.map(|temperature| {
+            options
+                .language
+                .number_format()
+                .quantity(temperature.round() + 0.0, None, unit)
+        }),
+        snapshot
+            .weather_code
+            .map(|weather_code| match options.ascii_glyphs {
+                true => weather_code.ascii_glyph().to_string(),
+                false => weather_code.glyph().to_string(),
+            }),
+        Some(sparkline(
+            &snapshot.temperatures,
+            match options.ascii {
+                true => &ASCII_SPARKLINE_LEVELS,
+                false => &SPARKLINE_LEVELS,
+            },
+        )),
+        snapshot
+            .temperature
+            .and_then(|temperature| {
+                extreme_temperature(temperature, None, &snapshot.unit, extreme_temperatures)
+            })
+            .map(|extreme| format!("!{}", options.language.text(extreme.short_text()))),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|part| !part.is_empty())
+    .map(|part| match options.ascii {
+        true => ascii_text(&part),
+        false => part,
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Draw the values as the levels scaled between their minimum and maximum (so the negative values
+/// are handled the same way as the positive ones), the missing ones (NaN) are left blank
+fn sparkline(values: &[f64], levels: &[char; 8]) -> String {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(-f64::INFINITY, f64::max);
+    let steps = levels.len() as u64 - 1;
+
+    values
+        .iter()
+        .map(|value| match value.is_nan() {
+            true => ' ',
+            false => levels[scaled_value(value - min, max - min, steps) as usize],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_sparklines() {
+        for (values, expected) in [
+            (vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0], "▁▂▃▄▅▆▇█"),
+            (vec![7.0, 6.0, 5.0, 4.0, 3.0, 2.0, 1.0, 0.0], "█▇▆▅▄▃▂▁"),
+            // Scaled between the minimum and the maximum, whatever their sign
+            (vec![-5.0, -3.0, -1.0, 1.0, 3.0], "▁▂▄▆█"),
+            (vec![-12.0, -8.0, -10.0, -4.0], "▁▄▂█"),
+            // A range under a degree still spans all the levels
+            (vec![20.0, 20.25, 20.5, 20.75], "▁▃▅█"),
+            (vec![0.25, 0.5], "▁█"),
+            // Flat, and the missing hours left blank
+            (vec![3.0, 3.0, 3.0], "▁▁▁"),
+            (vec![1.0, f64::NAN, 8.0], "▁ █"),
+            (vec![], ""),
+        ] {
+            assert_eq!(
+                sparkline(&values, &SPARKLINE_LEVELS),
+                expected,
+                "{values:?}"
+            );
+        }
+
+        assert_eq!(
+            sparkline(&[-5.0, -3.0, -1.0, 1.0, 3.0], &ASCII_SPARKLINE_LEVELS),
+            "_.:+#"
+        );
+    }
+}
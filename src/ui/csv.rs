@@ -0,0 +1,188 @@
+//! The hourly series as CSV, one row per hour, for the spreadsheets
+
+use std::io;
+
+use chrono::SecondsFormat;
+use color_eyre::eyre;
+
+use crate::{
+    data::{DataSource, WeatherData},
+    locale::machine_number,
+};
+
+/// Write the hours of the data with a header row. The wind, the precipitation and the humidity only
+/// get their columns if the provider returned them, and the source of every hour (with the age of
+/// the cached ones in minutes) if some of them are not from this response. The hours without a
+/// value (NaN) are left empty. The numbers are the same in every language, the scripts read them
+pub(crate) fn write_csv(data: &WeatherData, output: impl io::Write) -> eyre::Result<()> {
+    let has_wind = !data.wind_speeds.is_empty();
+    let has_precipitation = !data.precipitation.is_empty();
+    let has_humidity = !data.humidity.is_empty();
+    let has_sources = data
+        .sources
+        .iter()
+        .any(|source| *source != DataSource::Live);
+
+    let mut writer = csv::Writer::from_writer(output);
+
+    let mut header = vec![
+        "timestamp",
+        "temperature",
+        "unit",
+        "latitude",
+        "longitude",
+        "provider",
+        "address",
+    ];
+    if has_wind {
+        header.extend(["wind_speed", "wind_speed_unit", "wind_direction"]);
+    }
+    if has_precipitation {
+        header.extend(["precipitation", "precipitation_unit"]);
+    }
+    if has_humidity {
+        header.push("humidity");
+    }
+    if has_sources {
+        header.extend(["source", "source_age_minutes"]);
+    }
+    writer.write_record(&header)?;
+
+    let value = |values: &[f64], i: usize| {
+        values
+            .get(i)
+            .filter(|value| !value.is_nan())
+            .map(|value| machine_number(*value))
+            .unwrap_or_default()
+    };
+
+    for (i, timestamp) in data.timestamps.iter().enumerate() {
+        let mut record = vec![
+            // Full time with the offset, UTC as "Z"
+            timestamp.time.to_rfc3339_opts(SecondsFormat::Secs, true),
+            value(&data.temperatures, i),
+            data.unit.clone(),
+            machine_number(data.location.lat),
+            machine_number(data.location.lon),
+            data.provider.to_string(),
+            data.location.display_name.clone(),
+        ];
+        if has_wind {
+            record.extend([
+                value(&data.wind_speeds, i),
+                data.wind_speed_unit.clone(),
+                data.wind_directions
+                    .get(i)
+                    .map(|direction| direction.to_string())
+                    .unwrap_or_default(),
+            ]);
+        }
+        if has_precipitation {
+            record.extend([
+                value(&data.precipitation, i),
+                data.precipitation_unit.clone(),
+            ]);
+        }
+        if has_humidity {
+            record.push(value(&data.humidity, i));
+        }
+        if has_sources {
+            let source = data.source(i);
+
+            record.extend([
+                source.to_string(),
+                match source {
+                    DataSource::Cached { age } => age.num_minutes().to_string(),
+                    _ => String::new(),
+                },
+            ]);
+        }
+        writer.write_record(&record)?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        geocode::ResolvedLocation,
+        providers::{Provider, ProviderRequestType},
+    };
+
+    /// Three hours in Kyiv with the series of the response (the second hour without the
+    /// temperature), for the address
+    fn data(mut json: serde_json::Value, address: &str) -> WeatherData {
+        json["utc_offset_seconds"] = json!(10800);
+        json["hourly"]["time"] =
+            json!(["2024-06-14T13:00", "2024-06-14T14:00", "2024-06-14T15:00"]);
+        json["hourly"]["temperature_2m"] = json!([18.5, null, -0.25]);
+        json["hourly_units"]["temperature_2m"] = json!("°C");
+
+        WeatherData::from_json(
+            json.as_object().unwrap(),
+            Provider::OpenMeteo,
+            ProviderRequestType::Forecast,
+            "2024-06-14".to_string(),
+            None,
+            ResolvedLocation {
+                lat: 50.45,
+                lon: 30.5234,
+                display_name: address.to_string(),
+                ..ResolvedLocation::default()
+            },
+            (false, Default::default()),
+        )
+        .unwrap()
+    }
+
+    /// The CSV of the data
+    fn csv(data: &WeatherData) -> String {
+        let mut output = Vec::new();
+        write_csv(data, &mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn temperatures_only() {
+        // The commas and the quotes of the address are quoted
+        let data = data(json!({}), "Kyiv, Kyiv City, \"Ukraine\"");
+
+        assert_eq!(
+            csv(&data),
+            "timestamp,temperature,unit,latitude,longitude,provider,address\n\
+            2024-06-14T13:00:00+03:00,18.5,°C,50.45,30.5234,open_meteo,\"Kyiv, Kyiv City, \"\"Ukraine\"\"\"\n\
+            2024-06-14T14:00:00+03:00,,°C,50.45,30.5234,open_meteo,\"Kyiv, Kyiv City, \"\"Ukraine\"\"\"\n\
+            2024-06-14T15:00:00+03:00,-0.25,°C,50.45,30.5234,open_meteo,\"Kyiv, Kyiv City, \"\"Ukraine\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn every_series() {
+        let json = json!({
+            "hourly_units": { "windspeed_10m": "km/h", "precipitation": "mm" },
+            "hourly": {
+                "windspeed_10m": [3.0, 4.5, 6.0],
+                "winddirection_10m": [0.0, 225.0, null],
+                "precipitation": [0.0, 1.2, null],
+                "relativehumidity_2m": [60, 65, 70],
+            },
+        });
+        let data = data(json, "Kyiv, Ukraine");
+
+        assert_eq!(
+            csv(&data),
+            "timestamp,temperature,unit,latitude,longitude,provider,address,wind_speed,\
+            wind_speed_unit,wind_direction,precipitation,precipitation_unit,humidity\n\
+            2024-06-14T13:00:00+03:00,18.5,°C,50.45,30.5234,open_meteo,\"Kyiv, Ukraine\",3,km/h,N,0,mm,60\n\
+            2024-06-14T14:00:00+03:00,,°C,50.45,30.5234,open_meteo,\"Kyiv, Ukraine\",4.5,km/h,SW,1.2,mm,65\n\
+            2024-06-14T15:00:00+03:00,-0.25,°C,50.45,30.5234,open_meteo,\"Kyiv, Ukraine\",6,km/h,Unknown,,mm,70\n"
+        );
+    }
+}
@@ -0,0 +1,1111 @@
+//! Snapshots of the data for sharing: the TUI rendered into a text file (with or without the
+//! colors), or an SVG bar chart. And the calendar of the notable weather (see [`ics`])
+
+pub(crate) mod ics;
+
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre;
+use itertools::Itertools;
+use tui::{
+    backend::TestBackend,
+    buffer::Buffer,
+    style::{Color, Modifier},
+    Terminal,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    config::ExtremeTemperatures,
+    data::WeatherData,
+    locale::Text,
+    ui::{
+        ascii::AsciiBackend, chart_title, daily_chart_title, draw_weather_data_ui, index_table,
+        temperature_bar_styles, title, App, RenderOptions,
+    },
+};
+
+/// Format of the snapshot, chosen by the file extension
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    /// The TUI as plain text
+    Text,
+    /// The TUI with the colors as ANSI escape sequences (`cat` it in a terminal)
+    Ansi,
+    Svg,
+}
+
+impl ExportFormat {
+    pub(crate) const AVAILABLE_EXTENSIONS: [&str; 3] = ["txt", "ansi", "svg"];
+
+    pub(crate) fn from_path(path: &Path) -> Result<Self, String> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default();
+
+        match extension.to_lowercase().as_str() {
+            "txt" => Ok(Self::Text),
+            "ansi" => Ok(Self::Ansi),
+            "svg" => Ok(Self::Svg),
+            _ => Err(format!(
+                "Unsupported export format '{extension}', available extensions: [{}]",
+                Self::AVAILABLE_EXTENSIONS.join(", ")
+            )),
+        }
+    }
+}
+
+/// Smallest snapshot the layout still fits in
+const MIN_EXPORT_SIZE: (u16, u16) = (40, 12);
+
+/// Check that the export file has one of the supported extensions (before anything is fetched)
+pub(crate) fn parse_export_path(s: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(s);
+
+    ExportFormat::from_path(&path).map(|_| path)
+}
+
+/// Parse the snapshot size in characters ("120x40")
+pub(crate) fn parse_export_size(s: &str) -> Result<(u16, u16), String> {
+    let invalid = || format!("Invalid size '{s}', expected <width>x<height> like 120x40");
+
+    let (width, height) = s.split_once(['x', 'X']).ok_or_else(invalid)?;
+    let (width, height) = (
+        width.trim().parse::<u16>().map_err(|_| invalid())?,
+        height.trim().parse::<u16>().map_err(|_| invalid())?,
+    );
+
+    match width >= MIN_EXPORT_SIZE.0 && height >= MIN_EXPORT_SIZE.1 {
+        true => Ok((width, height)),
+        false => Err(format!(
+            "The size '{s}' is too small, the smallest is {}x{}",
+            MIN_EXPORT_SIZE.0, MIN_EXPORT_SIZE.1
+        )),
+    }
+}
+
+/// Write the snapshot of the data to the file, in the format of its extension
+pub(crate) fn write_snapshot(
+    data: &WeatherData,
+    path: &Path,
+    size: (u16, u16),
+    extreme_temperatures: &ExtremeTemperatures,
+    options: RenderOptions,
+) -> eyre::Result<()> {
+    let app = || App::new(vec![data.clone()], vec![], options);
+
+    let output = match ExportFormat::from_path(path).map_err(|e| eyre::eyre!(e))? {
+        ExportFormat::Text => {
+            format_buffer(&render(app(), size, extreme_temperatures)?, false)
+                + &options.text(index_table(data, options))
+        }
+        ExportFormat::Ansi => {
+            format_buffer(&render(app(), size, extreme_temperatures)?, true)
+                + &options.text(index_table(data, options))
+        }
+        ExportFormat::Svg => format_svg(data, options),
+    };
+
+    std::fs::write(path, output)
+        .map_err(|e| eyre::eyre!("Couldn't write the snapshot to {}: {e}", path.display()))
+}
+
+/// Draw the same layout as the TUI into an in-memory buffer of the size
+fn render(
+    mut app: App,
+    (width, height): (u16, u16),
+    extreme_temperatures: &ExtremeTemperatures,
+) -> eyre::Result<Buffer> {
+    let ascii = app.options.ascii;
+    let mut terminal = Terminal::new(AsciiBackend::new(TestBackend::new(width, height), ascii))?;
+
+    terminal.draw(|f| draw_weather_data_ui(f, &mut app, extreme_temperatures, f.size()))?;
+
+    Ok(terminal.backend().inner().buffer().clone())
+}
+
+/// Turn the buffer into lines of text, with the colors and the modifiers of the cells as SGR
+/// sequences if `ansi`
+fn format_buffer(buffer: &Buffer, ansi: bool) -> String {
+    let mut output = String::new();
+
+    for row in buffer.content.chunks(buffer.area.width as usize) {
+        let mut line = String::new();
+        let mut style = None;
+        // The cells covered by the wide symbols (the weather glyphs) are not printed
+        let mut skip = 0;
+
+        for cell in row {
+            if skip == 0 {
+                let cell_style = (cell.fg, cell.bg, cell.modifier);
+
+                if ansi && style != Some(cell_style) {
+                    line.push_str(&sgr(cell.fg, cell.bg, cell.modifier));
+                    style = Some(cell_style);
+                }
+
+                line.push_str(&cell.symbol);
+            }
+
+            skip = skip.max(cell.symbol.width()).saturating_sub(1);
+        }
+
+        match ansi {
+            true => line.push_str("\x1b[0m"),
+            // The trailing spaces are only there to fill the buffer
+            false => line.truncate(line.trim_end().len()),
+        }
+
+        output.push_str(&line);
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Escape sequence that resets the style and then sets the one of the cell
+fn sgr(fg: Color, bg: Color, modifier: Modifier) -> String {
+    let modifiers = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| modifier.contains(*flag))
+    .map(|(_, code)| code.to_string());
+
+    let codes = std::iter::once("0".to_string())
+        .chain(modifiers)
+        .chain(color_code(fg, false))
+        .chain(color_code(bg, true))
+        .join(";");
+
+    format!("\x1b[{codes}m")
+}
+
+/// SGR code of the foreground (or the background) color, nothing for the default one
+fn color_code(color: Color, background: bool) -> Option<String> {
+    // The background codes are the foreground ones + 10
+    let offset = match background {
+        true => 10,
+        false => 0,
+    };
+    let extended = match background {
+        true => 48,
+        false => 38,
+    };
+
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => 30,
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Yellow => 33,
+        Color::Blue => 34,
+        Color::Magenta => 35,
+        Color::Cyan => 36,
+        Color::Gray => 37,
+        Color::DarkGray => 90,
+        Color::LightRed => 91,
+        Color::LightGreen => 92,
+        Color::LightYellow => 93,
+        Color::LightBlue => 94,
+        Color::LightMagenta => 95,
+        Color::LightCyan => 96,
+        Color::White => 97,
+        Color::Indexed(index) => return Some(format!("{extended};5;{index}")),
+        Color::Rgb(r, g, b) => return Some(format!("{extended};2;{r};{g};{b}")),
+    };
+
+    Some((code + offset).to_string())
+}
+
+/// Width of a bar on the SVG chart, with the gap
+const SVG_BAR_WIDTH: usize = 36;
+/// Height of the bars area of the SVG chart
+const SVG_CHART_HEIGHT: f64 = 300.0;
+/// Space around the chart for the titles, the values and the labels
+const SVG_MARGIN: usize = 60;
+
+/// Draw the hourly series (or the daily maximums) as an SVG bar chart with the titles of the TUI,
+/// colored the same way
+fn format_svg(data: &WeatherData, options: RenderOptions) -> String {
+    let RenderOptions {
+        series,
+        language,
+        theme,
+        ..
+    } = options;
+    let (labels, values, chart_title) = match &data.daily {
+        Some(daily) => (
+            (0..daily.dates.len()).map(|i| daily.label(i)).collect_vec(),
+            daily.max_temps.as_slice(),
+            daily_chart_title(data, daily, (Text::DailyMaximum, language)),
+        ),
+        None => (
+            data.timestamps
+                .iter()
+                .map(|timestamp| timestamp.label.clone())
+                .collect_vec(),
+            series.values(data),
+            chart_title(data, series, language),
+        ),
+    };
+    let bar_styles = match series.is_temperature() {
+        true => temperature_bar_styles(&data.unit, &theme).to_vec(),
+        false => vec![],
+    };
+
+    // The bars start at 0, so the scale includes it even if all the values are on one side of it
+    // (`f64::min` and `f64::max` skip the missing values)
+    let (min, max) = values.iter().fold((0.0f64, 0.0f64), |(min, max), value| {
+        (min.min(*value), max.max(*value))
+    });
+    let range = (max - min).max(1.0);
+    let y = |value: f64| 2.0 * SVG_MARGIN as f64 + (max - value) / range * SVG_CHART_HEIGHT;
+
+    let width = (values.len() * SVG_BAR_WIDTH + 2 * SVG_MARGIN).max(640);
+    let height = SVG_CHART_HEIGHT as usize + 4 * SVG_MARGIN;
+
+    let mut svg = String::new();
+
+    // Writing to a String never fails, so the results are ignored
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="monospace" font-size="11">"#
+    );
+    let _ = writeln!(svg, r#"  <rect width="100%" height="100%" fill="white"/>"#);
+    let _ = writeln!(
+        svg,
+        r#"  <text x="{}" y="24" text-anchor="middle" font-size="14" font-weight="bold">{}</text>"#,
+        width / 2,
+        escape_xml(&title(data, language))
+    );
+    let _ = writeln!(
+        svg,
+        r#"  <text x="{}" y="46" text-anchor="middle" font-size="12">{}</text>"#,
+        width / 2,
+        escape_xml(&chart_title)
+    );
+    let _ = writeln!(
+        svg,
+        r#"  <line x1="{SVG_MARGIN}" y1="{zero:.1}" x2="{}" y2="{zero:.1}" stroke="gray"/>"#,
+        width - SVG_MARGIN,
+        zero = y(0.0)
+    );
+
+    for (i, (label, value)) in labels.iter().zip(values).enumerate() {
+        let x = SVG_MARGIN + i * SVG_BAR_WIDTH;
+        let center = x + SVG_BAR_WIDTH / 2;
+        let _ = writeln!(
+            svg,
+            r#"  <text x="{center}" y="{}" text-anchor="end" transform="rotate(-45 {center} {})">{}</text>"#,
+            height - SVG_MARGIN + 12,
+            height - SVG_MARGIN + 12,
+            escape_xml(label)
+        );
+
+        // The missing values have no bar, only "n/a" on the zero line
+        if value.is_nan() {
+            let _ = writeln!(
+                svg,
+                r#"  <text x="{center}" y="{:.1}" text-anchor="middle" fill="gray">{}</text>"#,
+                y(0.0) - 4.0,
+                escape_xml(language.text(Text::MissingValue))
+            );
+            continue;
+        }
+
+        let (top, bottom) = match *value >= 0.0 {
+            true => (y(*value), y(0.0)),
+            false => (y(0.0), y(*value)),
+        };
+        let color = bar_styles
+            .iter()
+            .rev()
+            .find(|(threshold, _)| *value >= *threshold)
+            .and_then(|(_, style)| style.fg)
+            .unwrap_or(series.color(&theme));
+
+        let _ = writeln!(
+            svg,
+            r#"  <rect x="{}" y="{top:.1}" width="{}" height="{:.1}" fill="{}"/>"#,
+            x + 2,
+            SVG_BAR_WIDTH - 4,
+            bottom - top,
+            svg_color(color)
+        );
+        // The value goes above the positive bars and under the negative ones
+        let _ = writeln!(
+            svg,
+            r#"  <text x="{center}" y="{:.1}" text-anchor="middle">{value}</text>"#,
+            match *value >= 0.0 {
+                true => top - 4.0,
+                false => bottom + 12.0,
+            }
+        );
+    }
+
+    let _ = writeln!(svg, "</svg>");
+
+    svg
+}
+
+/// SVG color closest to the terminal one (the default one of the terminal is black on the white
+/// background)
+fn svg_color(color: Color) -> String {
+    match color {
+        Color::Red | Color::LightRed => "crimson".to_string(),
+        Color::Yellow | Color::LightYellow => "gold".to_string(),
+        Color::Cyan | Color::LightCyan => "darkturquoise".to_string(),
+        Color::Green | Color::LightGreen => "seagreen".to_string(),
+        Color::Magenta | Color::LightMagenta => "orchid".to_string(),
+        Color::Gray | Color::DarkGray => "gray".to_string(),
+        Color::Reset | Color::Black => "black".to_string(),
+        Color::Rgb(r, g, b) => format!("#{r:02x}{g:02x}{b:02x}"),
+        _ => "royalblue".to_string(),
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use serde_json::json;
+    use weather::date::DateRange;
+
+    use super::*;
+    use crate::{
+        config::{DataAge, TimeFormat},
+        data::{CurrentSnapshot, DataSource, Degradation, Extra, WeatherAlert},
+        geocode::ResolvedLocation,
+        locale::Language,
+        outdoor::OutdoorWeights,
+        providers::{Provider, ProviderRequestType},
+        timezone::DisplayZone,
+        ui::{
+            check_series, compact, format_plain, format_text, index_table,
+            warnings::{self, by_severity, WarningsView},
+            ChartSeries, OutputMode, Theme,
+        },
+    };
+
+    /// Two days of the hourly temperatures in the clock
+    fn two_days(time_format: TimeFormat) -> WeatherData {
+        parse(two_days_json(), time_format)
+    }
+
+    /// Response with two days of the hourly temperatures
+    fn two_days_json() -> serde_json::Value {
+        let times = (0..48)
+            .map(|hour| format!("2024-06-{}T{:02}:00", 14 + hour / 24, hour % 24))
+            .collect::<Vec<_>>();
+
+        json!({
+            "utc_offset_seconds": 0,
+            "timezone": "GMT",
+            "hourly_units": { "temperature_2m": "°C" },
+            "hourly": {
+                "time": times,
+                "temperature_2m": (0..48).map(|hour| 15.0 + f64::from(hour % 24) / 2.0).collect::<Vec<_>>(),
+            },
+        })
+    }
+
+    /// The response of the two days from June 14, 2024
+    fn parse(json: serde_json::Value, time_format: TimeFormat) -> WeatherData {
+        let start = NaiveDate::from_ymd_opt(2024, 6, 14).unwrap();
+
+        WeatherData::from_json(
+            json.as_object().unwrap(),
+            Provider::OpenMeteo,
+            ProviderRequestType::Forecast,
+            "2024-06-14".to_string(),
+            Some(DateRange {
+                start,
+                end: start.succ_opt().unwrap(),
+            }),
+            ResolvedLocation::default(),
+            (false, time_format),
+        )
+        .unwrap()
+    }
+
+    fn options(language: Language) -> RenderOptions {
+        RenderOptions {
+            series: ChartSeries::Temperature,
+            ascii_glyphs: false,
+            ascii: false,
+            precision: None,
+            data_age: DataAge::default(),
+            week: false,
+            language,
+            theme: Theme::default(),
+            best_window: None,
+            display_zone: DisplayZone::Location,
+            with_table: false,
+            now: chrono::Utc::now(),
+        }
+    }
+
+    /// The TUI as text, the way it's exported
+    fn snapshot(data: &WeatherData, options: RenderOptions, size: (u16, u16)) -> String {
+        let app = App::new(vec![data.clone()], vec![], options);
+
+        format_buffer(
+            &render(app, size, &ExtremeTemperatures::default()).unwrap(),
+            false,
+        )
+    }
+
+    #[test]
+    fn narrow_bars_have_24h_labels() {
+        let data = two_days(TimeFormat::Hours12);
+
+        // The labels with the day of the week don't fit the bars of a narrow terminal
+        let narrow = snapshot(&data, options(Language::En), (60, 20));
+        assert!(narrow.contains("(24h)"), "{narrow}");
+        let labels = narrow.lines().find(|line| line.contains("Fri 00"));
+        assert!(
+            labels.is_some_and(|labels| !labels.contains("AM")),
+            "{narrow}"
+        );
+
+        // But they do on a wide one
+        let wide = snapshot(&data, options(Language::En), (500, 20));
+        assert!(!wide.contains("(24h)"), "{wide}");
+        assert!(wide.contains("Fri 01 AM"), "{wide}");
+
+        let narrow = snapshot(&data, options(Language::Uk), (60, 20));
+        assert!(narrow.contains("(24 год)"), "{narrow}");
+    }
+
+    #[test]
+    fn current_weather_in_the_language() {
+        let mut json = two_days_json();
+        json["current_weather"] = json!({
+            "time": "2024-06-14T15:15",
+            "temperature": 21.5,
+            "weathercode": 2,
+            "windspeed": 10.0,
+            "winddirection": 180.0,
+        });
+        // The hour without the temperature gets the mark instead of the value
+        json["hourly"]["temperature_2m"][1] = serde_json::Value::Null;
+        let data = parse(json, TimeFormat::Hours24);
+
+        let cases = [
+            (
+                Language::En,
+                [
+                    "Current Weather",
+                    "Temperature",
+                    "Wind Speed",
+                    "Partly Cloudy",
+                    "n/a",
+                    "more ▶",
+                ],
+            ),
+            (
+                Language::Uk,
+                [
+                    "Поточна погода",
+                    "Температура",
+                    "Швидкість вітру",
+                    "Мінлива хмарність",
+                    "н/д",
+                    "ще ▶",
+                ],
+            ),
+        ];
+
+        for (language, texts) in cases {
+            let snapshot = snapshot(&data, options(language), (220, 30));
+            for text in texts {
+                assert!(snapshot.contains(text), "{language:?} {text}:\n{snapshot}");
+            }
+        }
+    }
+
+    #[test]
+    fn wind_arrows_under_the_hours() {
+        let mut json = two_days_json();
+        // From the south, a breeze first and a storm later
+        json["hourly"]["windspeed_10m"] = json!((0..48)
+            .map(|hour| f64::from(hour) * 1.5)
+            .collect::<Vec<_>>());
+        json["hourly"]["winddirection_10m"] = json!(vec![180.0; 48]);
+        json["hourly_units"]["windspeed_10m"] = json!("km/h");
+        let data = parse(json, TimeFormat::Hours24);
+
+        let windy = snapshot(&data, options(Language::En), (100, 24));
+        let arrows = windy.lines().find(|line| line.contains('↑'));
+        assert!(
+            arrows.is_some_and(|arrows| arrows.matches('↑').count() > 10),
+            "{windy}"
+        );
+        assert!(windy.contains("wind: dim <10"), "{windy}");
+
+        // No room for them under the bars of a short terminal
+        let short = snapshot(&data, options(Language::En), (100, 6));
+        assert!(!short.contains('↑'), "{short}");
+        assert!(!short.contains("wind: dim"), "{short}");
+
+        // Nor without the wind
+        let calm = snapshot(
+            &two_days(TimeFormat::Hours24),
+            options(Language::En),
+            (100, 24),
+        );
+        assert!(!calm.contains("wind: dim"), "{calm}");
+    }
+
+    #[test]
+    fn ascii_only() {
+        let mut json = two_days_json();
+        json["current_weather"] = json!({
+            "time": "2024-06-14T15:15",
+            "temperature": 21.5,
+            "weathercode": 95,
+            "windspeed": 10.0,
+            "winddirection": 225.0,
+        });
+        json["hourly"]["windspeed_10m"] = json!(vec![12.0; 48]);
+        json["hourly"]["winddirection_10m"] = json!(vec![45.0; 48]);
+        json["hourly"]["precipitation"] = json!(vec![0.4; 48]);
+        json["hourly"]["weathercode"] =
+            json!((0..48).map(|hour| hour % 4 * 20).collect::<Vec<_>>());
+        json["hourly_units"]["windspeed_10m"] = json!("km/h");
+        json["hourly_units"]["precipitation"] = json!("mm");
+        let mut data = parse(json, TimeFormat::Hours24);
+        data.location.display_name = "Київ, Україна".to_string();
+
+        let ascii = RenderOptions {
+            ascii: true,
+            ascii_glyphs: true,
+            with_table: true,
+            ..options(Language::En)
+        };
+        let app = App::new(vec![data.clone()], vec![], ascii);
+        let buffer = render(app, (120, 30), &ExtremeTemperatures::default()).unwrap();
+        let outputs = [
+            format_buffer(&buffer, false),
+            format_buffer(&buffer, true),
+            format_plain(&data, &ExtremeTemperatures::default(), ascii),
+            compact::format_compact(
+                &CurrentSnapshot::from_data(&data, 3),
+                &ExtremeTemperatures::default(),
+                ascii,
+            ),
+        ];
+
+        assert!(buffer.content.iter().all(|cell| cell.symbol.is_ascii()));
+        for output in &outputs {
+            let non_ascii = output.bytes().filter(|byte| !byte.is_ascii()).count();
+            assert_eq!(non_ascii, 0, "{output}");
+        }
+
+        // While the same data has plenty of them otherwise
+        let unicode = snapshot(&data, options(Language::En), (120, 30));
+        assert!(
+            unicode.contains("°C") && unicode.contains("Київ"),
+            "{unicode}"
+        );
+    }
+
+    #[test]
+    fn monochrome_without_colors() {
+        let mut json = two_days_json();
+        json["current_weather"] = json!({
+            "time": "2024-06-14T15:15",
+            "temperature": 36.5,
+            "weathercode": 95,
+            "windspeed": 60.0,
+            "winddirection": 225.0,
+        });
+        json["hourly"]["windspeed_10m"] = json!((0..48).map(|hour| hour * 2).collect::<Vec<_>>());
+        json["hourly"]["winddirection_10m"] = json!(vec![45.0; 48]);
+        json["hourly"]["weathercode"] =
+            json!((0..48).map(|hour| hour % 4 * 20).collect::<Vec<_>>());
+        json["hourly_units"]["windspeed_10m"] = json!("km/h");
+        let data = parse(json, TimeFormat::Hours24);
+
+        let monochrome = RenderOptions {
+            theme: Theme::preset("monochrome").unwrap(),
+            ..options(Language::En)
+        };
+        let colored = |options: RenderOptions| {
+            let app = App::new(vec![data.clone()], vec![], options);
+            let buffer = render(app, (120, 30), &ExtremeTemperatures::default()).unwrap();
+
+            buffer
+                .content
+                .iter()
+                .filter(|cell| cell.fg != Color::Reset || cell.bg != Color::Reset)
+                .count()
+        };
+
+        assert_eq!(colored(monochrome), 0);
+        // The default theme has them
+        assert!(colored(options(Language::En)) > 0);
+    }
+
+    #[test]
+    fn best_window_under_the_summary() {
+        let mut json = two_days_json();
+        // Rainy until 06:00, and the warmest hours of the afternoon are too hot
+        json["hourly"]["precipitation_probability"] = json!((0..48)
+            .map(|hour| match hour < 6 {
+                true => 80.0,
+                false => 0.0,
+            })
+            .collect::<Vec<_>>());
+        json["hourly"]["uv_index"] = json!(vec![3.0; 48]);
+        json["hourly_units"]["precipitation_probability"] = json!("%");
+        let data = parse(json, TimeFormat::Hours24);
+        let options = RenderOptions {
+            best_window: Some((2, OutdoorWeights::default())),
+            ..options(Language::En)
+        };
+        let line = "Best 2h window: 06:00–08:00 — 18°C, 0% rain, UV 3";
+
+        let text = format_plain(&data, &ExtremeTemperatures::default(), options);
+        assert!(
+            text.contains(&format!(
+                "{line} (penalty 0.00: rain 0.00, temperature 0.00, UV 0.00, gusts 0.00)"
+            )),
+            "{text}"
+        );
+
+        // The TUI has it in the summary, without the penalties
+        let tui = snapshot(&data, options, (100, 24));
+        assert!(tui.contains(line), "{tui}");
+        assert!(!tui.contains("penalty"), "{tui}");
+
+        // In the language
+        let ukrainian = RenderOptions {
+            language: Language::Uk,
+            ..options
+        };
+        let text = format_plain(&data, &ExtremeTemperatures::default(), ukrainian);
+        assert!(
+            text.contains("Найкраще вікно на 2 год: 06:00–08:00 — 18 °C, 0% дощу, УФ 3"),
+            "{text}"
+        );
+
+        // A window longer than the hours there are fails clearly
+        let too_long = RenderOptions {
+            best_window: Some((49, OutdoorWeights::default())),
+            ..options
+        };
+        let e = check_series(&data, too_long).unwrap_err().to_string();
+        assert!(e.contains("48 hours ahead"), "{e}");
+    }
+
+    #[test]
+    fn json_output() {
+        let mut json = two_days_json();
+        json["hourly"]["precipitation_probability"] = json!((0..48)
+            .map(|hour| match hour < 6 {
+                true => 80.0,
+                false => 0.0,
+            })
+            .collect::<Vec<_>>());
+        json["hourly"]["uv_index"] = json!(vec![3.0; 48]);
+        json["hourly_units"]["precipitation_probability"] = json!("%");
+        let mut data = parse(json, TimeFormat::Hours24);
+        let time = |hour| Utc.with_ymd_and_hms(2024, 6, 14, hour, 0, 0).unwrap();
+        data.fetched_at = time(12);
+        data.data_updated_at = Some(time(10));
+        data.degradations.push(Degradation::UnknownAddress);
+        // The first two hours are from an earlier response
+        data.sources = (0..48)
+            .map(|hour| match hour < 2 {
+                true => DataSource::Cached {
+                    age: chrono::Duration::hours(2),
+                },
+                false => DataSource::Live,
+            })
+            .collect();
+        let options = RenderOptions {
+            best_window: Some((2, OutdoorWeights::default())),
+            ..options(Language::En)
+        };
+
+        let text = format_text(
+            &data,
+            &ExtremeTemperatures::default(),
+            OutputMode::Json,
+            options,
+        );
+        let json = serde_json::from_str::<serde_json::Value>(&text).unwrap();
+
+        assert_eq!(
+            json["freshness"],
+            json!({
+                "fetched_at": "2024-06-14T12:00:00+00:00",
+                "data_updated_at": "2024-06-14T10:00:00+00:00",
+                "cached_at": null,
+            })
+        );
+        assert_eq!(json["meta"]["address_full"], json!("Unknown"));
+        assert_eq!(
+            json["summary"],
+            json!({
+                "min": { "value": 15.0, "time": "2024-06-14T00:00:00+00:00" },
+                "max": { "value": 26.5, "time": "2024-06-14T23:00:00+00:00" },
+                "mean": 20.75,
+                "trend": "rising",
+            })
+        );
+        assert_eq!(json["hourly"]["source"][1], json!("cached"));
+        assert_eq!(json["hourly"]["source"][2], json!("live"));
+        assert_eq!(
+            json["degradations"],
+            json!([
+                {
+                    "severity": "notice",
+                    "message": "no address found at the coordinates",
+                },
+                {
+                    "severity": "warning",
+                    "message": "hours Fri 00:00–Fri 01:00 from the cache, 2h ago",
+                },
+            ])
+        );
+        assert_eq!(
+            json["best_window"],
+            json!({
+                "hours": 2,
+                "start": "2024-06-14T06:00:00+00:00",
+                "end": "2024-06-14T08:00:00+00:00",
+                "temperature": 18.25,
+                "rain_chance": 0.0,
+                "uv_index": 3.0,
+                "penalties": { "total": 0.0, "rain": 0.0, "temperature": 0.0, "uv": 0.0, "gusts": 0.0 },
+            })
+        );
+        // Indented, unlike the line of ndjson
+        assert!(text.contains("\n  \"best_window\": {"), "{text}");
+
+        // The same bytes whatever the language is
+        let ukrainian = RenderOptions {
+            language: Language::Uk,
+            ..options
+        };
+        assert_eq!(
+            format_text(
+                &data,
+                &ExtremeTemperatures::default(),
+                OutputMode::Json,
+                ukrainian
+            ),
+            text
+        );
+    }
+
+    /// Compare the text with the golden file in src/ui/golden (`WEATHER_UPDATE_GOLDEN=1` writes
+    /// it instead, to be reviewed in the diff)
+    pub(super) fn assert_golden(name: &str, text: &str) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/ui/golden")
+            .join(name);
+
+        if std::env::var_os("WEATHER_UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, text).unwrap();
+        }
+
+        let golden = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(golden == text, "{name} changed:\n{text}");
+    }
+
+    #[test]
+    fn warnings_strip() {
+        let mut data = two_days(TimeFormat::Hours24);
+        data.fetched_at = chrono::Utc::now();
+        let five = vec![
+            Degradation::UnknownAddress,
+            Degradation::MissingExtra {
+                extra: Extra::Alerts,
+                reason: Some("timed out".to_string()),
+            },
+            Degradation::DuplicateHours(2),
+            Degradation::Failover {
+                provider: Provider::OpenMeteo,
+                reason: "met_no has no history".to_string(),
+            },
+            Degradation::Caveat("Only the daily forecast is this far ahead".to_string()),
+        ];
+        // Rows of the chart, from its top border to its bottom one
+        let chart_height = |text: &str| {
+            let top = text.lines().position(|line| line.contains('┐')).unwrap();
+            let bottom = text.lines().position(|line| line.contains('└')).unwrap();
+
+            bottom - top
+        };
+        let clear = snapshot(&data, options(Language::En), (80, 24));
+
+        // (warnings, golden file)
+        let cases = [
+            (0, "warnings_0.txt"),
+            (1, "warnings_1.txt"),
+            (5, "warnings_5.txt"),
+        ];
+        for (count, golden) in cases {
+            data.degradations = five[..count].to_vec();
+
+            let text = snapshot(&data, options(Language::En), (80, 24));
+            assert_golden(golden, &text);
+
+            let height = warnings::strip_height(count, WarningsView::OneShot) as usize;
+            let shrunk = chart_height(&clear) - chart_height(&text);
+            assert!(shrunk <= height, "{golden}: {shrunk} > {height}");
+        }
+
+        // The TUI only counts them, and lists them all once expanded
+        let app = |view| App {
+            warnings: view,
+            ..App::new(vec![data.clone()], vec![], options(Language::En))
+        };
+        let collapsed = format_buffer(
+            &render(
+                app(WarningsView::Collapsed),
+                (80, 24),
+                &ExtremeTemperatures::default(),
+            )
+            .unwrap(),
+            false,
+        );
+        assert!(collapsed.contains("⚠ 5 warnings (press w)"), "{collapsed}");
+        assert!(!collapsed.contains("timed out"), "{collapsed}");
+        let expanded = format_buffer(
+            &render(
+                app(WarningsView::Expanded),
+                (80, 24),
+                &ExtremeTemperatures::default(),
+            )
+            .unwrap(),
+            false,
+        );
+        assert!(expanded.contains("Warnings"), "{expanded}");
+        for degradation in &five {
+            assert!(
+                expanded.contains(&degradation.message(Language::En)),
+                "{expanded}"
+            );
+        }
+
+        // The plain text lists them all, the most severe first
+        let plain = format_plain(
+            &data,
+            &ExtremeTemperatures::default(),
+            options(Language::En),
+        );
+        let lines = by_severity(&five)
+            .into_iter()
+            .map(|degradation| format!("! {}", degradation.message(Language::En)))
+            .join("\n");
+        assert!(plain.contains(&lines), "{plain}");
+    }
+
+    #[test]
+    fn cached_hours_dimmed() {
+        let mut json = two_days_json();
+        json["hourly"]["time"] = json!(json["hourly"]["time"].as_array().unwrap()[..12]);
+        json["hourly"]["temperature_2m"] =
+            json!(json["hourly"]["temperature_2m"].as_array().unwrap()[..12]);
+        let mut data = parse(json, TimeFormat::Hours24);
+        data.fetched_at = chrono::Utc::now();
+        // The first half from an earlier response
+        let age = chrono::Duration::hours(2);
+        data.sources = [DataSource::Cached { age }; 6]
+            .into_iter()
+            .chain([DataSource::Live; 6])
+            .collect();
+
+        let app = App::new(vec![data.clone()], vec![], options(Language::En));
+        let ansi = format_buffer(
+            &render(app, (120, 20), &ExtremeTemperatures::default()).unwrap(),
+            true,
+        );
+        assert_golden("cached_hours.ansi", &ansi);
+
+        let note = "hours Fri 00:00–Fri 05:00 from the cache, 2h ago";
+        let text = snapshot(&data, options(Language::En), (120, 20));
+        assert!(text.contains(note), "{text}");
+        let plain = format_plain(
+            &data,
+            &ExtremeTemperatures::default(),
+            options(Language::En),
+        );
+        assert!(plain.contains(&format!("! {note}")), "{plain}");
+    }
+
+    #[test]
+    fn index_markers_with_the_table() {
+        // (hours, snapshot width, golden file)
+        let cases = [
+            (24, 160, "index_table_24.txt"),
+            // Past z, the markers get two letters
+            (30, 200, "index_table_30.txt"),
+        ];
+
+        for (hours, width, golden) in cases {
+            let mut json = two_days_json();
+            json["hourly"]["time"] = json!(json["hourly"]["time"].as_array().unwrap()[..hours]);
+            json["hourly"]["temperature_2m"] =
+                json!(json["hourly"]["temperature_2m"].as_array().unwrap()[..hours]);
+            json["hourly"]["precipitation"] = json!((0..hours)
+                .map(|hour| f64::from(hour as u32 % 5) * 0.2)
+                .collect::<Vec<_>>());
+            json["hourly_units"]["precipitation"] = json!("mm");
+            let mut data = parse(json, TimeFormat::Hours24);
+            // The age of the data is in the corner, it has to be the same every day
+            data.fetched_at = chrono::Utc::now();
+            let options = RenderOptions {
+                with_table: true,
+                ..options(Language::En)
+            };
+
+            let text = snapshot(&data, options, (width, 30)) + &index_table(&data, options);
+            assert_golden(golden, &text);
+        }
+    }
+
+    #[test]
+    fn banners_by_priority() {
+        // A heat wave with the gale and the alert on top of it, and a mild day
+        let day = |temperature: f64, feels_like: f64, wind: f64| {
+            let mut json = two_days_json();
+            json["current_weather"] = json!({
+                "time": "2024-06-14T15:15",
+                "temperature": temperature,
+                "weathercode": 0,
+                "windspeed": wind,
+                "winddirection": 180.0,
+            });
+            json["hourly"]["apparent_temperature"] = json!(vec![feels_like; 48]);
+            json["hourly_units"]["apparent_temperature"] = json!("°C");
+            let mut data = parse(json, TimeFormat::Hours24);
+            data.fetched_at = chrono::Utc::now();
+
+            data
+        };
+        let mut heat_wave = day(38.0, 42.5, 65.0);
+        heat_wave.alerts = vec![WeatherAlert::new(
+            (
+                "heat".to_string(),
+                "Severe".to_string(),
+                "Heat wave until Sunday".to_string(),
+            ),
+            None,
+            None,
+        )];
+        let mild = day(18.0, 17.0, 8.0);
+
+        // (data, golden file, the banner lines of the plain text)
+        let cases = [
+            (
+                &heat_wave,
+                "banners_heat_wave.txt",
+                vec![
+                    "! Severe: Heat wave until Sunday",
+                    "! Extreme heat: 38°C, feels like 42.5°C - avoid the midday sun (+1 more)",
+                ],
+            ),
+            (&mild, "banners_mild.txt", vec![]),
+        ];
+        for (data, golden, lines) in cases {
+            let text = snapshot(data, options(Language::En), (120, 24));
+            assert_golden(golden, &text);
+
+            let plain = format_plain(data, &ExtremeTemperatures::default(), options(Language::En));
+            let banners = plain
+                .lines()
+                .filter(|line| line.starts_with("! "))
+                .collect_vec();
+            assert_eq!(banners, lines, "{plain}");
+        }
+
+        // The one line output calls it out at the end
+        let compact = |data: &WeatherData| {
+            compact::format_compact(
+                &CurrentSnapshot::from_data(data, 3),
+                &ExtremeTemperatures::default(),
+                options(Language::En),
+            )
+        };
+        assert!(compact(&heat_wave).ends_with(" !extreme heat"));
+        assert!(!compact(&mild).contains('!'));
+
+        // Without the alert, the wind has the room
+        heat_wave.alerts.clear();
+        let plain = format_plain(
+            &heat_wave,
+            &ExtremeTemperatures::default(),
+            options(Language::En),
+        );
+        assert!(
+            plain.contains(
+                "! Extreme heat: 38°C, feels like 42.5°C - avoid the midday sun\n\
+                ! Strong wind: 65 km/h - secure loose objects\n"
+            ),
+            "{plain}"
+        );
+
+        // In the numbers of the language
+        let plain = format_plain(
+            &heat_wave,
+            &ExtremeTemperatures::default(),
+            options(Language::Uk),
+        );
+        assert!(
+            plain.contains("! Сильна спека: 38 °C, відчувається як 42,5 °C"),
+            "{plain}"
+        );
+    }
+
+    #[test]
+    fn current_only_card() {
+        // The hours failed, the current conditions and the day are still there
+        let json = json!({
+            "utc_offset_seconds": 0,
+            "timezone": "GMT",
+            "current_weather": {
+                "time": "2024-06-14T12:00",
+                "temperature": 21.5,
+                "weathercode": 2,
+                "windspeed": 12.0,
+                "winddirection": 270.0,
+            },
+            "current_weather_units": { "temperature": "°C" },
+            "daily": {
+                "time": ["2024-06-14"],
+                "sunrise": ["2024-06-14T02:46"],
+                "sunset": ["2024-06-14T19:08"],
+            },
+        });
+        let mut data = parse(json, TimeFormat::Hours24);
+        data.fetched_at = chrono::Utc::now();
+        assert!(data.timestamps.is_empty());
+
+        let text = snapshot(&data, options(Language::En), (80, 24));
+        assert_golden("current_only.txt", &text);
+    }
+}
@@ -0,0 +1,396 @@
+//! iCalendar file of the notable weather (`get --export-ics`): the rain, the alerts, the sunrise
+//! and the sunset (and the temperatures of every day, if asked for) as the events of a calendar.
+//! It's lines of "NAME:value", so it's written by hand (RFC 5545)
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveTime, Utc};
+use itertools::Itertools;
+
+use crate::{
+    data::{DailyWeatherData, WeatherData, DAYLIGHT_FORMAT},
+    locale::{Language, Text},
+};
+
+/// Longest line in octets, the rest of it goes on the next lines after a space
+const LINE_OCTETS: usize = 75;
+
+/// Format of the times in UTC
+const UTC_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// When the event is
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum EventTime {
+    Utc(DateTime<Utc>),
+    /// The whole day
+    Day(NaiveDate),
+}
+
+impl EventTime {
+    /// The property with the time ("DTSTART:20240614T110000Z", "DTEND;VALUE=DATE:20240615")
+    fn property(&self, name: &str) -> String {
+        match self {
+            Self::Utc(time) => format!("{name}:{}", time.format(UTC_FORMAT)),
+            Self::Day(date) => format!("{name};VALUE=DATE:{}", date.format("%Y%m%d")),
+        }
+    }
+
+    /// The time in the UID
+    fn id(&self) -> String {
+        match self {
+            Self::Utc(time) => time.format(UTC_FORMAT).to_string(),
+            Self::Day(date) => date.format("%Y%m%d").to_string(),
+        }
+    }
+}
+
+/// Event of the calendar
+#[derive(Debug, Clone, PartialEq)]
+struct Event {
+    /// What it is about ("rain", "alert"...), for the UID
+    kind: &'static str,
+    start: EventTime,
+    /// `None` for the moments (the sunrise and the sunset)
+    end: Option<EventTime>,
+    summary: String,
+    description: Option<String>,
+}
+
+/// Format the events of the data as an iCalendar file, with the ones summarizing the days if
+/// `days` is set
+pub(crate) fn format_calendar(data: &WeatherData, days: bool, language: Language) -> String {
+    let events = rain_events(data, language)
+        .into_iter()
+        .chain(alert_events(data))
+        .chain(sun_events(data, language))
+        .chain(match days {
+            true => day_events(data, language),
+            false => vec![],
+        });
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//weather//weather//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!(
+            "X-WR-CALNAME:{}",
+            escape(&language.format(Text::WeatherIn, &[&data.location.name()]))
+        ),
+    ];
+    // The same place, time and kind get the same UID, so that importing the file again updates
+    // the events instead of adding them again
+    let place = format!("{:.4}_{:.4}", data.location.lat, data.location.lon);
+
+    for event in events {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}-{}-{place}@weather", event.kind, event.start.id()),
+            format!("DTSTAMP:{}", data.fetched_at.format(UTC_FORMAT)),
+            event.start.property("DTSTART"),
+        ]);
+        lines.extend(event.end.map(|end| end.property("DTEND")));
+        lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+        lines.extend(
+            event
+                .description
+                .map(|description| format!("DESCRIPTION:{}", escape(&description))),
+        );
+        lines.extend(["TRANSP:TRANSPARENT".to_string(), "END:VEVENT".to_string()]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| format!("{}\r\n", fold(line)))
+        .collect()
+}
+
+/// The hours in a row it rains ("Rain 14:00–17:00"), with how much and how likely
+fn rain_events(data: &WeatherData, language: Language) -> Vec<Event> {
+    let numbers = language.number_format();
+    let minute_format = data.time_format.minute_format();
+
+    data.rain_windows()
+        .into_iter()
+        .map(|window| {
+            let start = data.timestamps[window.start].time;
+            let last = data.timestamps[window.end - 1].time;
+            // The rain lasts until the next hour
+            let end = data
+                .timestamps
+                .get(window.end)
+                .map(|timestamp| timestamp.time)
+                .unwrap_or(last + Duration::hours(1));
+
+            let total = data
+                .precipitation
+                .get(window.clone())
+                .map(|values| values.iter().filter(|mm| !mm.is_nan()).sum::<f64>())
+                .filter(|total| *total > 0.0)
+                .map(|total| numbers.quantity(total, None, &data.precipitation_unit));
+            let chance = data
+                .precipitation_probabilities
+                .get(window.clone())
+                .and_then(|chances| chances.iter().copied().reduce(f64::max))
+                .map(|chance| language.format(Text::RainChance, &[&chance]));
+            let description = [total, chance].into_iter().flatten().join(", ");
+
+            Event {
+                kind: "rain",
+                start: EventTime::Utc(start.to_utc()),
+                end: Some(EventTime::Utc(end.to_utc())),
+                summary: language.format(
+                    Text::RainWindow,
+                    &[&start.format(minute_format), &end.format(minute_format)],
+                ),
+                description: Some(description).filter(|description| !description.is_empty()),
+            }
+        })
+        .collect()
+}
+
+/// The alerts from their onset until they expire, the ones without either of them are left out
+fn alert_events(data: &WeatherData) -> Vec<Event> {
+    data.alerts
+        .iter()
+        .filter_map(|alert| {
+            Some(Event {
+                kind: "alert",
+                start: EventTime::Utc(alert.onset?),
+                end: Some(EventTime::Utc(alert.expires?)),
+                summary: alert.headline.clone(),
+                description: Some(format!("{} ({})", alert.event, alert.severity)),
+            })
+        })
+        .collect()
+}
+
+/// The sunrise and the sunset of the requested day
+fn sun_events(data: &WeatherData, language: Language) -> Vec<Event> {
+    let Some(offset) = data
+        .timestamps
+        .first()
+        .map(|timestamp| *timestamp.time.offset())
+    else {
+        return vec![];
+    };
+    let Ok(day) = NaiveDate::parse_from_str(&data.requested_date, "%Y-%m-%d") else {
+        return vec![];
+    };
+
+    [
+        ("sunrise", Text::SunriseAt, &data.sunrise),
+        ("sunset", Text::SunsetAt, &data.sunset),
+    ]
+    .into_iter()
+    .filter_map(|(kind, text, time)| {
+        let time = NaiveTime::parse_from_str(time.as_deref()?, DAYLIGHT_FORMAT).ok()?;
+        let time = day
+            .and_time(time)
+            .and_local_timezone(offset)
+            .single()
+            .map(|time: DateTime<FixedOffset>| time.to_utc())?;
+
+        Some(Event {
+            kind,
+            start: EventTime::Utc(time),
+            end: None,
+            summary: language.format(text, &[&time.with_timezone(&offset).format("%H:%M")]),
+            description: None,
+        })
+    })
+    .collect()
+}
+
+/// An all-day event per day with its lowest and highest temperature, the days without any are left
+/// out
+fn day_events(data: &WeatherData, language: Language) -> Vec<Event> {
+    let numbers = language.number_format();
+    let hourly;
+    let daily = match &data.daily {
+        Some(daily) => daily,
+        None => {
+            hourly = DailyWeatherData::from_hourly(
+                data.timestamps
+                    .iter()
+                    .map(|timestamp| timestamp.time.naive_local())
+                    .zip(data.temperatures.iter().copied()),
+            );
+            &hourly
+        }
+    };
+
+    daily
+        .dates
+        .iter()
+        .zip(&daily.min_temps)
+        .zip(&daily.max_temps)
+        .filter(|((_, min), max)| !min.is_nan() && !max.is_nan())
+        .filter_map(|((date, min), max)| {
+            Some(Event {
+                kind: "day",
+                start: EventTime::Day(*date),
+                end: Some(EventTime::Day(date.succ_opt()?)),
+                summary: format!(
+                    "{}: {}–{}",
+                    language.text(Text::DayRange),
+                    numbers.quantity(*min, None, &data.unit),
+                    numbers.quantity(*max, None, &data.unit)
+                ),
+                description: None,
+            })
+        })
+        .collect()
+}
+
+/// The text as a value: the backslashes, the commas and the semicolons escaped, the new lines as
+/// "\n"
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\\' => "\\\\".to_string(),
+            ',' => "\\,".to_string(),
+            ';' => "\\;".to_string(),
+            '\n' => "\\n".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// The line split into the lines of [`LINE_OCTETS`] at most, the next ones start with a space.
+/// It's split between the characters, never inside one
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut octets = 0;
+
+    for c in line.chars() {
+        if octets + c.len_utf8() > LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        config::TimeFormat,
+        data::WeatherAlert,
+        geocode::ResolvedLocation,
+        providers::{Provider, ProviderRequestType},
+        ui::export::tests::assert_golden,
+    };
+
+    /// Eight hours of June 14, 2024 in Kyiv with two rains, the sunrise and the sunset, and a gale
+    /// alert
+    fn data() -> WeatherData {
+        let json = json!({
+            "utc_offset_seconds": 10800,
+            "timezone": "Europe/Kyiv",
+            "hourly_units": { "temperature_2m": "°C", "precipitation": "mm" },
+            "hourly": {
+                "time": (10..18).map(|hour| format!("2024-06-14T{hour}:00")).collect::<Vec<_>>(),
+                "temperature_2m": [18.0, 19.5, 20.0, 21.0, 21.5, 20.0, 19.0, 18.5],
+                "precipitation": [0.0, 0.4, 1.2, 0.0, 0.0, 0.0, 0.3, 0.0],
+                "precipitation_probability": [10, 70, 90, 20, 10, 30, 60, 20],
+            },
+            "daily": {
+                "time": ["2024-06-14"],
+                "sunrise": ["2024-06-14T04:46"],
+                "sunset": ["2024-06-14T21:08"],
+            },
+        });
+
+        let mut data = WeatherData::from_json(
+            json.as_object().unwrap(),
+            Provider::OpenMeteo,
+            ProviderRequestType::Forecast,
+            "2024-06-14".to_string(),
+            None,
+            ResolvedLocation {
+                lat: 50.45,
+                lon: 30.52,
+                display_name: "Kyiv, Ukraine".to_string(),
+                short_name: None,
+                timezone: None,
+                country_code: None,
+            },
+            (false, TimeFormat::Hours24),
+        )
+        .unwrap();
+        let time = |time: &str| Some(time.parse::<DateTime<Utc>>().unwrap());
+        data.alerts = vec![WeatherAlert::new(
+            (
+                "gale".to_string(),
+                "Severe".to_string(),
+                "Strong winds, gusts up to 25 m/s; stay away from the trees".to_string(),
+            ),
+            time("2024-06-14T15:00:00Z"),
+            time("2024-06-14T21:00:00Z"),
+        )];
+        data.fetched_at = time("2024-06-14T09:30:00Z").unwrap();
+
+        data
+    }
+
+    #[test]
+    fn calendar_of_the_rain_and_the_alert() {
+        let calendar = format_calendar(&data(), true, Language::En);
+
+        assert_golden("calendar.ics", &calendar);
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 6, "{calendar}");
+    }
+
+    #[test]
+    fn uids_stay_the_same() {
+        let uids = |calendar: String| {
+            calendar
+                .lines()
+                .filter(|line| line.starts_with("UID:"))
+                .map(str::to_string)
+                .collect_vec()
+        };
+
+        // Fetched again later, the events are the same ones
+        let mut later = data();
+        later.fetched_at += Duration::hours(1);
+
+        assert_eq!(
+            uids(format_calendar(&data(), false, Language::En)),
+            uids(format_calendar(&later, false, Language::En))
+        );
+    }
+
+    #[test]
+    fn long_lines_folded() {
+        let summary = format!("SUMMARY:{}", escape(&"Сильний вітер, пориви; ".repeat(8)));
+        let folded = fold(&summary);
+
+        let lines = folded.split("\r\n").collect_vec();
+        assert!(lines.len() > 1, "{folded}");
+        for (i, line) in lines.iter().enumerate() {
+            assert!(line.len() <= LINE_OCTETS, "{i}: {line}");
+            // Only the first one doesn't continue the previous one
+            assert_eq!(line.starts_with(' '), i > 0, "{i}: {line}");
+        }
+
+        // Unfolded, it's the line again
+        assert_eq!(folded.replace("\r\n ", ""), summary);
+        // Escaped, the commas and the semicolons don't end the value
+        for separator in [",", ";"] {
+            assert_eq!(
+                summary.matches(separator).count(),
+                summary.matches(&format!("\\{separator}")).count(),
+                "{summary}"
+            );
+        }
+    }
+}
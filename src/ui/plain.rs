@@ -0,0 +1,663 @@
+//! Plain text output, for when the TUI can't be used (pipes, CI)
+
+use std::fmt::{Display, Write};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    config::ExtremeTemperatures,
+    data::{WeatherCode, WeatherData},
+    locale::{Language, Text},
+    ui::{
+        alert_period, aqi_range, banners::banners, bar_chart::index_marker, chart_title,
+        comparison_values, current_aqi, daily_chart_title, data_age, day_length, daylight,
+        moon_phase, nowcast_title, nowcast_unavailable, particulate_matter, summary_line,
+        sun_times, title, warnings::by_severity, ChartSeries,
+    },
+};
+
+/// Labels of the current weather, the air quality and the moon sections, lined up with each other
+const FIELD_LABELS: [Text; 14] = [
+    Text::Temperature,
+    Text::FeelsLike,
+    Text::ConditionsLabel,
+    Text::WindSpeed,
+    Text::WindDirection,
+    Text::Daylight,
+    Text::Precipitation,
+    Text::Humidity,
+    Text::WindChill,
+    Text::UvIndex,
+    Text::DayRange,
+    Text::Particulates,
+    Text::Moon,
+    Text::DayLengthLabel,
+];
+
+/// Format the data as a human readable text in the language without any terminal control
+/// sequences: current conditions on top, then the hour/temperature (and wind, if available) table
+/// or the daily summary
+pub(crate) fn format_data(
+    data: &WeatherData,
+    (extreme_temperatures, language, now): (&ExtremeTemperatures, Language, DateTime<Utc>),
+) -> String {
+    let mut output = String::new();
+    let numbers = language.number_format();
+
+    // The values start after the longest label with its colon
+    let label_width = FIELD_LABELS
+        .iter()
+        .map(|label| language.text(*label).chars().count() + 1)
+        .max()
+        .unwrap_or_default();
+    let field = |output: &mut String, label: Text, value: &dyn Display| {
+        let label = format!("{}:", language.text(label));
+        let _ = writeln!(output, "  {label:<label_width$} {value}");
+    };
+
+    // Writing to a String never fails, so the results are ignored
+    let _ = writeln!(output, "{}", title(data, language));
+    let _ = writeln!(
+        output,
+        "{}",
+        language.format(Text::DataAgeLine, &[&data_age(data, language, now).0])
+    );
+
+    // Warnings go right under the title, so they are not missed
+    for banner in banners(data, extreme_temperatures, language) {
+        let _ = writeln!(output, "! {}", banner.text);
+    }
+    for degradation in by_severity(&data.degradations()) {
+        let _ = writeln!(output, "! {}", degradation.message(language));
+    }
+
+    if !data.alerts.is_empty() {
+        let _ = writeln!(output);
+        let _ = writeln!(output, "{}", language.text(Text::Alerts));
+        for alert in &data.alerts {
+            let _ = writeln!(
+                output,
+                "  {}: {}{}",
+                alert.severity,
+                alert.headline,
+                alert_period(alert, language)
+            );
+        }
+    }
+
+    if let Some(current) = &data.current {
+        let _ = writeln!(output);
+        let _ = writeln!(
+            output,
+            "{} ({} {})",
+            language.text(Text::CurrentWeather),
+            data.current_time().unwrap_or_default(),
+            data.timezone
+        );
+        field(
+            &mut output,
+            Text::Temperature,
+            &numbers.quantity(current.temperature, None, &data.unit),
+        );
+        if let Some(temperature) = current.apparent_temperature {
+            field(
+                &mut output,
+                Text::FeelsLike,
+                &numbers.quantity(temperature, None, &data.unit),
+            );
+        }
+        field(
+            &mut output,
+            Text::ConditionsLabel,
+            &language.text(Text::Conditions(current.weather_code)),
+        );
+        field(
+            &mut output,
+            Text::WindSpeed,
+            &numbers.quantity(current.wind_speed, None, &current.wind_speed_unit),
+        );
+        field(&mut output, Text::WindDirection, &current.wind_direction);
+        field(&mut output, Text::Daylight, &daylight(data, language));
+        if let Some(probability) = current.precipitation_probability {
+            field(
+                &mut output,
+                Text::Precipitation,
+                &format_args!("{probability}%"),
+            );
+        }
+        if let Some(humidity) = current.humidity {
+            field(&mut output, Text::Humidity, &format_args!("{humidity}%"));
+        }
+        if let Some(temperature) = current.wind_chill {
+            field(
+                &mut output,
+                Text::WindChill,
+                &numbers.quantity(temperature, None, &data.unit),
+            );
+        }
+        if let Some(uv_index) = current.uv_index {
+            field(&mut output, Text::UvIndex, &numbers.number(uv_index, None));
+        }
+    }
+
+    if let Some(air_quality) = &data.air_quality {
+        let _ = writeln!(output);
+        let _ = writeln!(
+            output,
+            "{}",
+            language.format(Text::AirQualityOn, &[&numbers.day_month(air_quality.date)])
+        );
+        let _ = writeln!(
+            output,
+            "  {:<label_width$} {}",
+            "EAQI:",
+            current_aqi(air_quality, language)
+        );
+        field(
+            &mut output,
+            Text::DayRange,
+            &aqi_range(air_quality, language),
+        );
+        if let Some(matter) = particulate_matter(air_quality, language) {
+            field(
+                &mut output,
+                Text::Particulates,
+                &format_args!("{matter} μg/m³"),
+            );
+        }
+    }
+
+    if let Some(nowcast) = &data.nowcast {
+        let _ = writeln!(output);
+        let _ = writeln!(output, "{}", nowcast_title(language));
+        match nowcast_unavailable(nowcast, language) {
+            Some(reason) => {
+                let _ = writeln!(output, "  {reason}");
+            }
+            None => {
+                // The intervals without a rate are marked with a dash
+                for (label, rate) in nowcast.labels.iter().zip(&nowcast.precipitation_rates) {
+                    match rate.is_nan() {
+                        true => {
+                            let _ = writeln!(output, "  {label:<10}  {:>6}", "-");
+                        }
+                        false => {
+                            let _ = writeln!(
+                                output,
+                                "  {label:<10}  {:>6}",
+                                numbers.number(*rate, Some(1))
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(astro) = &data.astro {
+        let _ = writeln!(output);
+        let _ = writeln!(
+            output,
+            "{} ({})",
+            language.text(Text::Astro),
+            numbers.day_month(astro.date)
+        );
+        field(
+            &mut output,
+            Text::Moon,
+            &moon_phase(astro, (false, language)),
+        );
+        field(
+            &mut output,
+            Text::DayLengthLabel,
+            &day_length(astro, language),
+        );
+        field(
+            &mut output,
+            Text::Daylight,
+            &sun_times(astro.sunrise.as_deref(), astro.sunset.as_deref(), language),
+        );
+    }
+
+    if let Some(daily) = &data.daily {
+        let _ = writeln!(output);
+        let _ = writeln!(
+            output,
+            "{}",
+            daily_chart_title(data, daily, (Text::DailySummary, language))
+        );
+
+        // Only the providers that have the conditions of the days fill the last column
+        let has_conditions = daily.weather_codes.len() == daily.dates.len();
+
+        // The columns are as wide as their headers, if those are wider than the values
+        let day_width = column_width(language, Text::Day, 10);
+        let [min_width, max_width, mean_width] = [Text::MinColumn, Text::MaxColumn, Text::Mean]
+            .map(|header| column_width(language, header, 9));
+
+        let _ = write!(
+            output,
+            "  {:<day_width$}  {:>min_width$}  {:>max_width$}  {:>mean_width$}",
+            language.text(Text::Day),
+            language.text(Text::MinColumn),
+            language.text(Text::MaxColumn),
+            language.text(Text::Mean)
+        );
+        if has_conditions {
+            let _ = write!(output, "  {}", language.text(Text::ConditionsLabel));
+        }
+        let _ = writeln!(output);
+        for i in 0..daily.dates.len() {
+            // The days without any temperatures are marked with a dash
+            let temperature = |temperature: f64| match temperature.is_nan() {
+                true => format!(
+                    "{:>6} {:<width$}",
+                    "-",
+                    "",
+                    width = data.unit.chars().count()
+                ),
+                false => format!("{:>6} {}", numbers.number(temperature, Some(1)), data.unit),
+            };
+
+            let _ = write!(
+                output,
+                "  {:<day_width$}  {:>min_width$}  {:>max_width$}  {:>mean_width$}",
+                daily.label(i),
+                temperature(daily.min_temps[i]),
+                temperature(daily.max_temps[i]),
+                temperature(daily.mean_temps[i])
+            );
+            match (has_conditions, daily.weather_codes.get(i)) {
+                (true, Some(WeatherCode::Unknown)) => {
+                    let _ = write!(output, "  -");
+                }
+                (true, Some(code)) => {
+                    let _ = write!(output, "  {}", language.text(Text::Conditions(*code)));
+                }
+                _ => {}
+            }
+            let _ = writeln!(output);
+        }
+    } else if !data.temperatures.is_empty() {
+        let _ = writeln!(output);
+        let _ = writeln!(
+            output,
+            "{}",
+            chart_title(data, ChartSeries::Temperature, language)
+        );
+
+        // Align the temperatures to the longest time label
+        let time_width = data
+            .timestamps
+            .iter()
+            .map(|timestamp| timestamp.label.chars().count())
+            .max()
+            .unwrap_or_default();
+
+        // Yesterday's temperatures at the same hours, if the day is compared with it
+        let yesterday = comparison_values(data, ChartSeries::Temperature);
+
+        // The other columns are only there if the provider returned them
+        let has_yesterday = !yesterday.is_empty();
+        let has_apparent_temperatures = !data.apparent_temperatures.is_empty();
+        let has_humidity = !data.humidity.is_empty();
+        let has_precipitation = !data.precipitation.is_empty();
+        let has_pressure = !data.pressures.is_empty();
+        let has_cloud_cover = !data.cloud_cover.is_empty();
+        let has_wind = !data.wind_speeds.is_empty();
+
+        // Every column is as wide as its values or as its header, whichever is wider (the wind is
+        // the last one, it isn't padded)
+        let columns = [
+            (true, Text::Time, time_width),
+            (true, Text::Temperature, 11),
+            (has_yesterday, Text::Yesterday, 11),
+            (has_apparent_temperatures, Text::FeelsLike, 11),
+            (has_humidity, Text::Humidity, 8),
+            (has_precipitation, Text::Precipitation, 14),
+            (has_pressure, Text::Pressure, 12),
+            (has_cloud_cover, Text::Clouds, 7),
+            (has_wind, Text::Wind, 0),
+        ]
+        .map(|(shown, header, width)| (shown, header, column_width(language, header, width)));
+        for (i, (shown, header, width)) in columns.into_iter().enumerate() {
+            // The times are on the left of their column, the values are on the right
+            let header = language.text(header);
+            let _ = match (shown, i) {
+                (false, _) => Ok(()),
+                (true, 0) => write!(output, "  {header:<width$}"),
+                (true, _) => write!(output, "  {header:>width$}"),
+            };
+        }
+        let [time_width, temperature_width, yesterday_width, apparent_width, humidity_width, precipitation_width, pressure_width, cloud_cover_width, _] =
+            columns.map(|(_, _, width)| width);
+        let _ = writeln!(output);
+
+        // A value of the temperature column, the hours the provider didn't have it for are marked
+        // with a dash
+        let temperature = |temperature: f64| match temperature.is_nan() {
+            true => format!("{:>6} {:<4}", "-", ""),
+            false => format!(
+                "{:>6} {:<4}",
+                numbers.number(temperature, Some(1)),
+                data.unit
+            ),
+        };
+        for (i, (timestamp, value)) in data.timestamps.iter().zip(&data.temperatures).enumerate() {
+            let _ = write!(
+                output,
+                "  {timestamp:<time_width$}  {:>temperature_width$}",
+                temperature(*value)
+            );
+            if let (true, Some(value)) = (has_yesterday, yesterday.get(i)) {
+                let _ = write!(output, "  {:>yesterday_width$}", temperature(*value));
+            }
+            if let (true, Some(value)) =
+                (has_apparent_temperatures, data.apparent_temperatures.get(i))
+            {
+                let _ = write!(output, "  {:>apparent_width$}", temperature(*value));
+            }
+            if let (true, Some(humidity)) = (has_humidity, data.humidity.get(i)) {
+                let humidity = match humidity.is_nan() {
+                    true => format!("{:>7} ", "-"),
+                    false => format!("{humidity:>7}%"),
+                };
+                let _ = write!(output, "  {humidity:>humidity_width$}");
+            }
+            if let (true, Some(precipitation)) = (has_precipitation, data.precipitation.get(i)) {
+                let amount = match precipitation.is_nan() {
+                    true => format!("{:>5} {:<3}", "-", ""),
+                    false => format!(
+                        "{:>5} {:<3}",
+                        numbers.number(*precipitation, Some(1)),
+                        data.precipitation_unit
+                    ),
+                };
+                let probability = match data.precipitation_probabilities.get(i) {
+                    Some(probability) => format!(" {probability:>3}%"),
+                    None => "     ".to_string(),
+                };
+                let _ = write!(
+                    output,
+                    "  {:>precipitation_width$}",
+                    format!("{amount}{probability}")
+                );
+            }
+            if let (true, Some(pressure)) = (has_pressure, data.pressures.get(i)) {
+                let pressure = match pressure.is_nan() {
+                    true => format!("{:>7} {:<4}", "-", ""),
+                    false => format!(
+                        "{:>7} {:<4}",
+                        numbers.number(*pressure, Some(1)),
+                        data.pressure_unit
+                    ),
+                };
+                let _ = write!(output, "  {pressure:>pressure_width$}");
+            }
+            if let (true, Some(clouds)) = (has_cloud_cover, data.cloud_cover.get(i)) {
+                let clouds = match clouds.is_nan() {
+                    true => format!("{:>6} ", "-"),
+                    false => format!("{:>6}%", numbers.number(*clouds, Some(0))),
+                };
+                let _ = write!(output, "  {clouds:>cloud_cover_width$}");
+            }
+            if let (true, Some(speed), Some(direction)) = (
+                has_wind,
+                data.wind_speeds.get(i),
+                data.wind_directions.get(i),
+            ) {
+                match speed.is_nan() {
+                    true => {
+                        let _ = write!(output, "  {:>5}", "-");
+                    }
+                    false => {
+                        let _ = write!(
+                            output,
+                            "  {:>5} {} {direction}",
+                            numbers.number(*speed, Some(1)),
+                            data.wind_speed_unit
+                        );
+                    }
+                }
+            }
+            let _ = writeln!(output);
+        }
+
+        if let Some(summary) = summary_line(data, ChartSeries::Temperature, (None, language)) {
+            let _ = writeln!(
+                output,
+                "  {}",
+                language.format(Text::SummaryLine, &[&summary])
+            );
+        }
+    }
+
+    output
+}
+
+/// The bars of the chart, a line each: the marker of the bar (the same one as in the row above the
+/// labels), its full time and the values of all the series the data has ("-" for the missing ones)
+pub(crate) fn format_index_table(data: &WeatherData, language: Language) -> String {
+    let numbers = language.number_format();
+    let series = ChartSeries::ALL
+        .into_iter()
+        .filter(|series| !series.values(data).is_empty())
+        .collect::<Vec<_>>();
+
+    let header = ["#".to_string(), language.text(Text::Time).to_string()]
+        .into_iter()
+        .chain(series.iter().map(|series| {
+            format!(
+                "{} {}",
+                language.text(series.label()),
+                language.format(Text::InUnit, &[&series.unit(data)])
+            )
+        }))
+        .collect::<Vec<_>>();
+    let rows = data
+        .timestamps
+        .iter()
+        .enumerate()
+        .map(|(i, timestamp)| {
+            let values = series.iter().map(|series| {
+                match series.values(data).get(i).filter(|value| !value.is_nan()) {
+                    Some(value) => numbers.number(*value, None),
+                    None => "-".to_string(),
+                }
+            });
+
+            [
+                index_marker(i),
+                timestamp
+                    .time
+                    .format(data.time_format.time_format())
+                    .to_string(),
+            ]
+            .into_iter()
+            .chain(values)
+            .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // The marker and the time are left aligned, the values right aligned under their headers
+    let widths = (0..header.len())
+        .map(|column| {
+            std::iter::once(&header)
+                .chain(&rows)
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    let mut output = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, width))| match column < 2 {
+                true => format!("{cell:<width$}"),
+                false => format!("{cell:>width$}"),
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        let _ = writeln!(output, "  {}", line.trim_end());
+    }
+
+    output
+}
+
+/// Width of the column, enough for the values and for the header in the language
+fn column_width(language: Language, header: Text, values_width: usize) -> usize {
+    language.text(header).chars().count().max(values_width)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        geocode::ResolvedLocation,
+        providers::{Provider, ProviderRequestType},
+    };
+
+    /// A few hours of June 14, 2024 with the current weather at 15:15
+    fn data() -> WeatherData {
+        let json = json!({
+            "utc_offset_seconds": 0,
+            "timezone": "GMT",
+            "current_weather": {
+                "time": "2024-06-14T15:15",
+                "temperature": 21.5,
+                "weathercode": 2,
+                "windspeed": 10.0,
+                "winddirection": 180.0,
+            },
+            "hourly_units": { "temperature_2m": "°C", "relativehumidity_2m": "%" },
+            "hourly": {
+                "time": ["2024-06-14T14:00", "2024-06-14T15:00", "2024-06-14T16:00"],
+                "temperature_2m": [20.5, null, 22.0],
+                "relativehumidity_2m": [60, 55, 50],
+            },
+        });
+
+        WeatherData::from_json(
+            json.as_object().unwrap(),
+            Provider::OpenMeteo,
+            ProviderRequestType::Forecast,
+            "2024-06-14".to_string(),
+            None,
+            ResolvedLocation::default(),
+            (false, Default::default()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn text_in_the_language() {
+        let cases = [
+            (
+                Language::En,
+                [
+                    "Current Weather",
+                    "Temperature:",
+                    "Wind Speed:",
+                    "Partly Cloudy",
+                    "Time",
+                    "Summary:",
+                ],
+            ),
+            (
+                Language::Uk,
+                [
+                    "Поточна погода",
+                    "Температура:",
+                    "Швидкість вітру:",
+                    "Мінлива хмарність",
+                    "Час",
+                    "Підсумок:",
+                ],
+            ),
+        ];
+
+        for (language, texts) in cases {
+            let text = format_data(
+                &data(),
+                (&ExtremeTemperatures::default(), language, Utc::now()),
+            );
+            for expected in texts {
+                assert!(text.contains(expected), "{language:?} {expected}:\n{text}");
+            }
+        }
+    }
+
+    #[test]
+    fn values_line_up_in_the_language() {
+        for language in [Language::En, Language::Uk] {
+            let text = format_data(
+                &data(),
+                (&ExtremeTemperatures::default(), language, Utc::now()),
+            );
+            let lines = text.lines().collect::<Vec<_>>();
+
+            // The values of the current weather start in the same column
+            let current = lines
+                .iter()
+                .skip_while(|line| !line.starts_with(language.text(Text::CurrentWeather)))
+                .skip(1)
+                .take_while(|line| !line.is_empty())
+                .filter_map(|line| line.split_once(':'))
+                .map(|(label, value)| {
+                    label.chars().count() + 1 + value.len() - value.trim_start().len()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(current.len(), 6, "{language:?}:\n{text}");
+            assert!(
+                current.iter().all(|start| *start == current[0]),
+                "{language:?}:\n{text}"
+            );
+
+            // And the humidity column ends where its header does
+            let table = lines
+                .iter()
+                .skip_while(|line| !line.trim_start().starts_with(language.text(Text::Time)))
+                .take(4)
+                .map(|line| line.trim_end().chars().count())
+                .collect::<Vec<_>>();
+            assert!(
+                table.iter().all(|end| *end == table[0]),
+                "{language:?}:\n{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn numbers_in_the_language() {
+        let data = data();
+        let cases = [
+            (Language::En, ["21.5°C", "20.5 °C", "22.0 °C", "avg 21.3°C"]),
+            (
+                Language::Uk,
+                ["21,5 °C", "20,5 °C", "22,0 °C", "сер 21,3 °C"],
+            ),
+        ];
+
+        for (language, numbers) in cases {
+            let text = format_data(
+                &data,
+                (&ExtremeTemperatures::default(), language, Utc::now()),
+            );
+            for number in numbers {
+                assert!(text.contains(number), "{language:?} {number}:\n{text}");
+            }
+        }
+
+        // While the CSV is for the scripts, with the same numbers in every language
+        let mut csv = Vec::new();
+        crate::ui::csv::write_csv(&data, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.contains(",20.5,"), "{csv}");
+        assert!(!csv.contains("20,5"), "{csv}");
+    }
+}
@@ -0,0 +1,603 @@
+//! Output for the screen readers (`--output speech`): sentences one after another, without the box
+//! drawing, the glyphs and the tables. The signs and the units are words ("minus 3.5 degrees
+//! Celsius", the digits stay digits), the hours are said the way people say them ("3 in the
+//! afternoon") and the hourly temperatures are told as where they rise and fall
+
+use std::ops::Range;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, Timelike};
+use itertools::Itertools;
+
+use crate::{
+    data::{CurrentWeatherData, DailyWeatherData, WeatherAlert, WeatherData, WindDirection},
+    locale::{Language, Text},
+};
+
+/// Changes of the temperatures (in their unit) smaller than this don't make them rise or fall, so
+/// that the wobbles of a degree aren't read out one by one
+const STEADY_CHANGE: f64 = 1.0;
+
+/// Format the data as sentences, one per line: the title, the temperatures, the current
+/// conditions, the wind and the alerts
+pub(crate) fn format_speech(data: &WeatherData, language: Language) -> String {
+    let mut sentences = vec![language.format(
+        Text::SpeechTitle,
+        &[
+            &data.location.name(),
+            // "open_meteo" is read out with the underscore
+            &data.provider.to_string().replace('_', " "),
+        ],
+    )];
+
+    match &data.daily {
+        Some(daily) => sentences.extend(day_sentences(daily, &data.unit, language)),
+        None => sentences.extend(series_prose(
+            &data.temperatures,
+            &moments(data, language),
+            &data.unit,
+            language,
+        )),
+    }
+
+    if let Some(current) = &data.current {
+        sentences.push(current_sentence(current, &data.unit, language));
+        sentences.push(wind_sentence(current, language));
+    }
+
+    let offset = data
+        .timestamps
+        .first()
+        .map(|timestamp| *timestamp.time.offset());
+    for alert in &data.alerts {
+        sentences.push(alert_sentence(alert, offset, language));
+    }
+
+    sentences
+        .into_iter()
+        .map(|sentence| format!("{sentence}.\n"))
+        .collect()
+}
+
+/// The hours of the data as they are said, with the day for the ones after the first day
+fn moments(data: &WeatherData, language: Language) -> Vec<String> {
+    let first_day = data
+        .timestamps
+        .first()
+        .map(|timestamp| timestamp.time.date_naive());
+
+    data.timestamps
+        .iter()
+        .map(|timestamp| spoken_moment(timestamp.time, first_day, language))
+        .collect()
+}
+
+/// The time as it's said, with the day if it's not on the `day`
+fn spoken_moment(
+    time: DateTime<FixedOffset>,
+    day: Option<NaiveDate>,
+    language: Language,
+) -> String {
+    let spoken = spoken_time(time.time(), language);
+
+    match Some(time.date_naive()) == day {
+        true => spoken,
+        false => language.format(
+            Text::SpeechOnDay,
+            &[
+                &spoken,
+                &language.number_format().day_month(time.date_naive()),
+            ],
+        ),
+    }
+}
+
+/// The time of the day as it's said: "midnight", "noon", "6 in the morning", "2:45 in the
+/// afternoon", "9 in the evening", "2 at night"
+pub(crate) fn spoken_time(time: NaiveTime, language: Language) -> String {
+    let (hour, minute) = (time.hour(), time.minute());
+    let clock = match (hour % 12, minute) {
+        (0, 0) => "12".to_string(),
+        (0, minute) => format!("12:{minute:02}"),
+        (hour, 0) => hour.to_string(),
+        (hour, minute) => format!("{hour}:{minute:02}"),
+    };
+
+    match (hour, minute) {
+        (0, 0) => language.text(Text::Midnight).to_string(),
+        (12, 0) => language.text(Text::Noon).to_string(),
+        (0..=4, _) => language.format(Text::AtNight, &[&clock]),
+        (5..=11, _) => language.format(Text::InTheMorning, &[&clock]),
+        (12..=17, _) => language.format(Text::InTheAfternoon, &[&clock]),
+        _ => language.format(Text::InTheEvening, &[&clock]),
+    }
+}
+
+/// The number with its sign as a word ("minus 3.5"), rounded to one decimal like the rest of the
+/// outputs
+fn spoken_number(value: f64, language: Language) -> String {
+    let rounded = (value * 10.0).round() / 10.0;
+    // Without the sign of the negative zero
+    let number = language.number_format().number(rounded.abs(), None);
+
+    match rounded < 0.0 {
+        true => format!("{} {number}", language.text(Text::Minus)),
+        false => number,
+    }
+}
+
+/// The number with its sign and its unit as words ("minus 3.5 degrees Celsius"), the units
+/// without the words are said as they are
+pub(crate) fn spoken_quantity(value: f64, unit: &str, language: Language) -> String {
+    let number = spoken_number(value, language);
+
+    match (unit_words(unit), unit) {
+        (Some(words), _) => format!("{number} {}", language.text(words)),
+        (None, "") => number,
+        (None, unit) => format!("{number} {unit}"),
+    }
+}
+
+/// Words of the unit, as the providers name it
+fn unit_words(unit: &str) -> Option<Text> {
+    match unit {
+        "°C" => Some(Text::DegreesCelsius),
+        "°F" => Some(Text::DegreesFahrenheit),
+        "km/h" => Some(Text::KilometresPerHour),
+        "m/s" => Some(Text::MetresPerSecond),
+        "mph" | "mp/h" => Some(Text::MilesPerHour),
+        "kn" => Some(Text::Knots),
+        "mm" => Some(Text::Millimetres),
+        "inch" => Some(Text::Inches),
+        "%" => Some(Text::Percent),
+        _ => None,
+    }
+}
+
+/// Name of the direction the wind blows from ("north-northeast"), `None` if it isn't known
+pub(crate) fn spoken_direction(direction: &WindDirection, language: Language) -> Option<String> {
+    let point = |c: char| match c {
+        'N' => Some(Text::North),
+        'E' => Some(Text::East),
+        'S' => Some(Text::South),
+        'W' => Some(Text::West),
+        _ => None,
+    };
+    // The north and the south go first ("northeast", "southwest")
+    let pair = |first: char, second: char| {
+        let first = match first {
+            'N' => Text::Northern,
+            'S' => Text::Southern,
+            _ => return None,
+        };
+
+        Some(language.format(
+            Text::CompassPair,
+            &[&language.text(first), &language.text(point(second)?)],
+        ))
+    };
+
+    match direction.to_string().chars().collect_vec().as_slice() {
+        [c] => point(*c).map(|point| language.text(point).to_string()),
+        [first, second] => pair(*first, *second),
+        [c, first, second] => Some(format!(
+            "{}-{}",
+            language.text(point(*c)?),
+            pair(*first, *second)?
+        )),
+        _ => None,
+    }
+}
+
+/// Sentences about the hourly values: where they rise and fall (with the times they turn at), stay
+/// the same or are missing. The `moments` are the hours as they are said, lined up with the values
+/// (NaN for the missing ones)
+pub(crate) fn series_prose(
+    values: &[f64],
+    moments: &[String],
+    unit: &str,
+    language: Language,
+) -> Vec<String> {
+    // The runs of the values there are and of the missing ones
+    let mut runs: Vec<(bool, Range<usize>)> = vec![];
+    for (i, value) in values.iter().enumerate().take(moments.len()) {
+        match runs.last_mut() {
+            Some((missing, run)) if *missing == value.is_nan() => run.end = i + 1,
+            _ => runs.push((value.is_nan(), i..i + 1)),
+        }
+    }
+
+    runs.into_iter()
+        .map(|(missing, run)| match (missing, run.len()) {
+            (true, 1) => language.format(Text::SpeechNoDataAt, &[&moments[run.start]]),
+            (true, _) => language.format(
+                Text::SpeechNoData,
+                &[&moments[run.start], &moments[run.end - 1]],
+            ),
+            (false, _) => run_sentence(&values[run.clone()], &moments[run], unit, language),
+        })
+        .collect()
+}
+
+/// The sentence about a run of values without the missing ones: "Temperatures rise from 5 degrees
+/// Celsius at 6 in the morning to a high of 14 at 4 in the afternoon, then fall to 8 by midnight"
+fn run_sentence(values: &[f64], moments: &[String], unit: &str, language: Language) -> String {
+    let quantity = |value: f64| spoken_quantity(value, unit, language);
+
+    let points = turning_points(values);
+    match (values.len(), points.as_slice()) {
+        (1, _) => return language.format(Text::SpeechAt, &[&quantity(values[0]), &moments[0]]),
+        (_, [_]) => {
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+
+            return language.format(
+                Text::SpeechSteady,
+                &[&quantity(mean), &moments[0], &moments[values.len() - 1]],
+            );
+        }
+        _ => {}
+    }
+
+    let max = values.iter().copied().fold(f64::MIN, f64::max);
+    let min = values.iter().copied().fold(f64::MAX, f64::min);
+    // The highest and the lowest ones of the run are said to be
+    let value = |i: usize, rising: bool| {
+        let number = spoken_number(values[i], language);
+
+        match (rising, values[i]) {
+            (true, value) if value == max => language.format(Text::SpeechHigh, &[&number]),
+            (false, value) if value == min => language.format(Text::SpeechLow, &[&number]),
+            _ => number,
+        }
+    };
+
+    let last = points.len() - 1;
+    points
+        .windows(2)
+        .enumerate()
+        .map(|(k, pair)| {
+            let (from, to) = (pair[0], pair[1]);
+            let rising = values[to] > values[from];
+            let value = value(to, rising);
+
+            match (k, k + 1 == last, rising) {
+                (0, _, _) => format!(
+                    "{} {}",
+                    language.format(
+                        match rising {
+                            true => Text::SpeechRiseFrom,
+                            false => Text::SpeechFallFrom,
+                        },
+                        &[&quantity(values[from]), &moments[from]]
+                    ),
+                    language.format(Text::SpeechTo, &[&value, &moments[to]])
+                ),
+                (_, true, true) => language.format(Text::SpeechThenRise, &[&value, &moments[to]]),
+                (_, true, false) => language.format(Text::SpeechThenFall, &[&value, &moments[to]]),
+                (_, false, true) => language.format(Text::SpeechRiseTo, &[&value, &moments[to]]),
+                (_, false, false) => language.format(Text::SpeechFallTo, &[&value, &moments[to]]),
+            }
+        })
+        .join(", ")
+}
+
+/// Indices of the first value and of the ones the values turn at (by [`STEADY_CHANGE`] or more),
+/// with the last high or low one. Only the first one if the values stay within the change
+fn turning_points(values: &[f64]) -> Vec<usize> {
+    let mut points = vec![0];
+    // Whether the values are rising, and the furthest one they got to since the last turn (`None`
+    // until they head anywhere)
+    let mut heading: Option<(bool, usize)> = None;
+
+    for (i, value) in values.iter().enumerate().skip(1) {
+        heading = match heading {
+            None => match (value - values[0]).abs() >= STEADY_CHANGE {
+                true => Some((*value > values[0], i)),
+                false => None,
+            },
+            Some((rising, furthest)) => {
+                let further = match rising {
+                    true => *value > values[furthest],
+                    false => *value < values[furthest],
+                };
+
+                match (further, (values[furthest] - value).abs() >= STEADY_CHANGE) {
+                    (true, _) => Some((rising, i)),
+                    (false, true) => {
+                        points.push(furthest);
+
+                        Some((!rising, i))
+                    }
+                    (false, false) => Some((rising, furthest)),
+                }
+            }
+        };
+    }
+
+    if let Some((_, furthest)) = heading {
+        points.push(furthest);
+    }
+
+    points
+}
+
+/// A sentence per day with its lowest and highest temperature, the days without any are left out
+fn day_sentences(daily: &DailyWeatherData, unit: &str, language: Language) -> Vec<String> {
+    daily
+        .dates
+        .iter()
+        .zip(&daily.min_temps)
+        .zip(&daily.max_temps)
+        .filter(|((_, min), max)| !min.is_nan() && !max.is_nan())
+        .map(|((date, min), max)| {
+            language.format(
+                Text::SpeechDay,
+                &[
+                    &language.number_format().day_month(*date),
+                    &spoken_quantity(*min, unit, language),
+                    &spoken_quantity(*max, unit, language),
+                ],
+            )
+        })
+        .collect()
+}
+
+/// "Now it's 21.5 degrees Celsius and partly cloudy, feels like 20 degrees Celsius, 10 percent
+/// chance of precipitation"
+fn current_sentence(current: &CurrentWeatherData, unit: &str, language: Language) -> String {
+    let now = language.format(
+        Text::SpeechNow,
+        &[
+            &spoken_quantity(current.temperature, unit, language),
+            &language
+                .text(Text::Conditions(current.weather_code))
+                .to_lowercase(),
+        ],
+    );
+    let feels_like = current.apparent_temperature.map(|temperature| {
+        language.format(
+            Text::SpeechFeelsLike,
+            &[&spoken_quantity(temperature, unit, language)],
+        )
+    });
+    let rain_chance = current.precipitation_probability.map(|probability| {
+        language.format(
+            Text::SpeechRainChance,
+            &[&spoken_quantity(probability, "%", language)],
+        )
+    });
+
+    [Some(now), feels_like, rain_chance]
+        .into_iter()
+        .flatten()
+        .join(", ")
+}
+
+/// "Wind 10 kilometres per hour from the south"
+fn wind_sentence(current: &CurrentWeatherData, language: Language) -> String {
+    let speed = spoken_quantity(current.wind_speed, &current.wind_speed_unit, language);
+
+    match spoken_direction(&current.wind_direction, language) {
+        Some(direction) => language.format(Text::SpeechWindFrom, &[&speed, &direction]),
+        None => language.format(Text::SpeechWind, &[&speed]),
+    }
+}
+
+/// "Severe alert: strong winds, until 6 in the evening on Jun 14", in the time of the data
+fn alert_sentence(alert: &WeatherAlert, offset: Option<FixedOffset>, language: Language) -> String {
+    let sentence = language.format(
+        Text::SpeechAlert,
+        &[
+            &alert.severity.to_lowercase(),
+            &alert.headline.trim_end_matches('.'),
+        ],
+    );
+
+    match alert.expires {
+        Some(expires) => {
+            let expires =
+                expires.with_timezone(&offset.unwrap_or(FixedOffset::east_opt(0).unwrap()));
+
+            format!(
+                "{sentence}, {}",
+                language.format(Text::AlertUntil, &[&spoken_moment(expires, None, language)])
+            )
+        }
+        None => sentence,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        geocode::ResolvedLocation,
+        providers::{Provider, ProviderRequestType},
+    };
+
+    /// The hours from `from` on as they are said, in English
+    fn hours(from: u32, count: u32) -> Vec<String> {
+        (from..from + count)
+            .map(|hour| {
+                spoken_time(
+                    NaiveTime::from_hms_opt(hour % 24, 0, 0).unwrap(),
+                    Language::En,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn spoken_times() {
+        let cases = [
+            (Language::En, (0, 0), "midnight"),
+            (Language::En, (12, 0), "noon"),
+            (Language::En, (6, 0), "6 in the morning"),
+            (Language::En, (15, 0), "3 in the afternoon"),
+            (Language::En, (21, 0), "9 in the evening"),
+            (Language::En, (2, 0), "2 at night"),
+            (Language::En, (14, 45), "2:45 in the afternoon"),
+            (Language::En, (0, 30), "12:30 at night"),
+            (Language::Uk, (0, 0), "півночі"),
+            (Language::Uk, (15, 0), "3 дня"),
+            (Language::Uk, (21, 30), "9:30 вечора"),
+        ];
+
+        for (language, (hour, minute), expected) in cases {
+            let time = NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+            assert_eq!(
+                spoken_time(time, language),
+                expected,
+                "{language:?} {hour}:{minute}"
+            );
+        }
+    }
+
+    #[test]
+    fn spoken_quantities() {
+        // Only the sign and the unit are words, the digits stay digits
+        let cases = [
+            (Language::En, -3.5, "°C", "minus 3.5 degrees Celsius"),
+            (Language::En, -0.04, "°C", "0 degrees Celsius"),
+            (Language::En, 21.0, "°F", "21 degrees Fahrenheit"),
+            (Language::En, 12.0, "km/h", "12 kilometres per hour"),
+            (Language::En, 55.0, "%", "55 percent"),
+            (Language::En, 1013.0, "hPa", "1013 hPa"),
+            (Language::Uk, -3.5, "°C", "мінус 3,5 градусів Цельсія"),
+        ];
+
+        for (language, value, unit, expected) in cases {
+            assert_eq!(
+                spoken_quantity(value, unit, language),
+                expected,
+                "{language:?} {value} {unit}"
+            );
+        }
+    }
+
+    #[test]
+    fn spoken_directions() {
+        let cases = [
+            (Language::En, WindDirection::N, Some("north")),
+            (Language::En, WindDirection::NNE, Some("north-northeast")),
+            (Language::En, WindDirection::SW, Some("southwest")),
+            (Language::En, WindDirection::ENE, Some("east-northeast")),
+            (Language::En, WindDirection::Unknown, None),
+            (Language::Uk, WindDirection::NE, Some("північний схід")),
+        ];
+
+        for (language, direction, expected) in cases {
+            assert_eq!(
+                spoken_direction(&direction, language).as_deref(),
+                expected,
+                "{language:?} {direction}"
+            );
+        }
+    }
+
+    #[test]
+    fn series_in_prose() {
+        let nan = f64::NAN;
+        // (case, values, the hour of the first one) and the sentences
+        type Case = (&'static str, Vec<f64>, u32, Vec<&'static str>);
+        let cases: [Case; 5] = [
+            (
+                "monotonic day",
+                vec![5.0, 7.0, 9.5, 11.0, 12.0, 14.0],
+                9,
+                vec!["Temperatures rise from 5 degrees Celsius at 9 in the morning to a high of 14 at 2 in the afternoon"],
+            ),
+            (
+                "double-peak day",
+                vec![8.0, 12.0, 16.0, 13.0, 11.0, 14.0, 17.0, 12.0],
+                6,
+                vec!["Temperatures rise from 8 degrees Celsius at 6 in the morning to 16 at 8 in the morning, fall to 11 at 10 in the morning, rise to a high of 17 at noon, then fall to 12 by 1 in the afternoon"],
+            ),
+            // The wobbles under a degree don't count
+            (
+                "flat day",
+                vec![12.0, 12.4, 11.8, 12.3],
+                18,
+                vec!["Temperatures stay around 12.1 degrees Celsius from 6 in the evening to 9 in the evening"],
+            ),
+            (
+                "data gaps",
+                vec![nan, 3.0, 1.0, -1.5, nan, nan, -2.0, -2.4],
+                22,
+                vec![
+                    "No data at 10 in the evening",
+                    "Temperatures fall from 3 degrees Celsius at 11 in the evening to a low of minus 1.5 at 1 at night",
+                    "No data from 2 at night to 3 at night",
+                    "Temperatures stay around minus 2.2 degrees Celsius from 4 at night to 5 in the morning",
+                ],
+            ),
+            (
+                "single hour",
+                vec![7.0, nan],
+                12,
+                vec![
+                    "Temperatures are 7 degrees Celsius at noon",
+                    "No data at 1 in the afternoon",
+                ],
+            ),
+        ];
+
+        for (case, values, from, expected) in cases {
+            let moments = hours(from, values.len() as u32);
+
+            assert_eq!(
+                series_prose(&values, &moments, "°C", Language::En),
+                expected,
+                "{case}"
+            );
+        }
+    }
+
+    #[test]
+    fn speech_without_the_drawing() {
+        let json = json!({
+            "utc_offset_seconds": 0,
+            "timezone": "GMT",
+            "current_weather": {
+                "time": "2024-06-14T15:15",
+                "temperature": -3.5,
+                "weathercode": 2,
+                "windspeed": 10.0,
+                "winddirection": 180.0,
+            },
+            "current_weather_units": { "windspeed": "km/h" },
+            "hourly_units": { "temperature_2m": "°C" },
+            "hourly": {
+                "time": ["2024-06-14T14:00", "2024-06-14T15:00", "2024-06-14T16:00"],
+                "temperature_2m": [-2.0, -4.0, -5.5],
+            },
+        });
+        let data = WeatherData::from_json(
+            json.as_object().unwrap(),
+            Provider::OpenMeteo,
+            ProviderRequestType::Forecast,
+            "2024-06-14".to_string(),
+            None,
+            ResolvedLocation::default(),
+            (false, Default::default()),
+        )
+        .unwrap();
+
+        let text = format_speech(&data, Language::En);
+        for expected in [
+            "from open meteo.",
+            "Temperatures fall from minus 2 degrees Celsius at 2 in the afternoon to a low of minus 5.5 at 4 in the afternoon.",
+            "Now it's minus 3.5 degrees Celsius and partly cloudy.",
+            "Wind 10 kilometres per hour from the south.",
+        ] {
+            assert!(text.contains(expected), "{expected}:\n{text}");
+        }
+
+        // Nothing a screen reader reads out as symbols
+        assert!(
+            !text.contains(['°', '│', '─', '▁', '█', '(', ')']),
+            "{text}"
+        );
+    }
+}
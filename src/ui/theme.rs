@@ -0,0 +1,380 @@
+//! Colors and borders of the TUI: the built-in presets, and the `theme` section of the config that
+//! picks one of them and overrides its elements
+
+use serde_json::{Map, Value};
+use tui::{
+    style::{Color, Modifier},
+    widgets::BorderType,
+};
+
+/// Colors and borders the data is drawn with
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Theme {
+    /// Bars of the temperatures (below the first threshold) and of the precipitation
+    pub(crate) bar: Color,
+    /// Values on the bars
+    pub(crate) value_fg: Color,
+    /// Background of the values on the bars (the color of the bar under them if it's not set)
+    pub(crate) value_bg: Option<Color>,
+    /// Modifiers of the values on the bars
+    pub(crate) value_modifier: Modifier,
+    /// Modifiers of the labels under the bars
+    pub(crate) label: Modifier,
+    /// Borders of all the blocks (the charts have plain ones and the rest rounded if it's not set)
+    pub(crate) border: Option<BorderType>,
+    /// Highlighted (current) hour
+    pub(crate) highlight: Color,
+    /// Scale, hints and the fresh data age
+    pub(crate) muted: Color,
+    /// Cool temperatures and the wind
+    pub(crate) cool: Color,
+    /// Good air and the pressure
+    pub(crate) good: Color,
+    /// Warm temperatures, moderate air and the data getting old
+    pub(crate) warning: Color,
+    /// Hot temperatures, poor air, alerts and the failed locations
+    pub(crate) danger: Color,
+    /// Clouds
+    pub(crate) neutral: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            bar: Color::Blue,
+            value_fg: Color::White,
+            value_bg: None,
+            value_modifier: Modifier::BOLD,
+            label: Modifier::ITALIC,
+            border: None,
+            highlight: Color::Magenta,
+            muted: Color::DarkGray,
+            cool: Color::Cyan,
+            good: Color::Green,
+            warning: Color::Yellow,
+            danger: Color::Red,
+            neutral: Color::Gray,
+        }
+    }
+}
+
+impl Theme {
+    pub(crate) const PRESETS: [&'static str; 4] = ["default", "light", "monochrome", "solarized"];
+
+    /// Elements of the preset that the `theme` section of the config can override
+    const ELEMENTS: [&'static str; 6] = [
+        "bar",
+        "value_fg",
+        "value_bg",
+        "label",
+        "border",
+        "highlight",
+    ];
+
+    /// Built-in theme by its name
+    pub(crate) fn preset(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            // Darker colors that can be read on a white background
+            "light" => Some(Self {
+                bar: Color::LightBlue,
+                value_fg: Color::Black,
+                value_modifier: Modifier::empty(),
+                warning: Color::Indexed(136),
+                neutral: Color::DarkGray,
+                ..Self::default()
+            }),
+            // No colors at all, the values and the highlighted hour are reversed instead
+            "monochrome" => Some(Self {
+                bar: Color::Reset,
+                value_fg: Color::Reset,
+                value_bg: Some(Color::Reset),
+                value_modifier: Modifier::BOLD | Modifier::REVERSED,
+                label: Modifier::ITALIC,
+                border: Some(BorderType::Plain),
+                highlight: Color::Reset,
+                muted: Color::Reset,
+                cool: Color::Reset,
+                good: Color::Reset,
+                warning: Color::Reset,
+                danger: Color::Reset,
+                neutral: Color::Reset,
+            }),
+            // https://ethanschoonover.com/solarized/
+            "solarized" => Some(Self {
+                bar: Color::Rgb(0x26, 0x8b, 0xd2),
+                value_fg: Color::Rgb(0xfd, 0xf6, 0xe3),
+                value_bg: None,
+                value_modifier: Modifier::BOLD,
+                label: Modifier::ITALIC,
+                border: Some(BorderType::Rounded),
+                highlight: Color::Rgb(0xd3, 0x36, 0x82),
+                muted: Color::Rgb(0x58, 0x6e, 0x75),
+                cool: Color::Rgb(0x2a, 0xa1, 0x98),
+                good: Color::Rgb(0x85, 0x99, 0x00),
+                warning: Color::Rgb(0xb5, 0x89, 0x00),
+                danger: Color::Rgb(0xdc, 0x32, 0x2f),
+                neutral: Color::Rgb(0x93, 0xa1, 0xa1),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Theme of the `theme` section of the config: its preset (or `preset` in place of it, from
+    /// `--theme`) with the elements it overrides. The unknown presets, elements and values are
+    /// warned about and left as they are in the preset
+    pub(crate) fn resolve(preset: Option<&str>, section: &Map<String, Value>) -> Self {
+        let configured = match section.get("preset") {
+            Some(Value::String(name)) => Some(name.as_str()),
+            Some(value) => {
+                warn("preset", value, "isn't a name of a preset");
+                None
+            }
+            None => None,
+        };
+        let name = preset.or(configured).unwrap_or("default");
+        let mut theme = Self::preset(name).unwrap_or_else(|| {
+            eprintln!(
+                "Warning: unknown theme {name}, available themes: [{}], the default one is used",
+                Self::PRESETS.join(", ")
+            );
+            Self::default()
+        });
+
+        for (element, value) in section.iter().filter(|(key, _)| *key != "preset") {
+            if !Self::ELEMENTS.contains(&element.as_str()) {
+                eprintln!(
+                    "Warning: unknown theme element {element} in the config, available elements: \
+                    [{}]",
+                    Self::ELEMENTS.join(", ")
+                );
+                continue;
+            }
+
+            let Some(text) = value.as_str() else {
+                warn(element, value, "isn't a string");
+                continue;
+            };
+
+            let applied = match element.as_str() {
+                "bar" => parse_color(text).map(|color| theme.bar = color),
+                "value_fg" => parse_color(text).map(|color| theme.value_fg = color),
+                "value_bg" => parse_color(text).map(|color| theme.value_bg = Some(color)),
+                "label" => parse_modifier(text).map(|modifier| theme.label = modifier),
+                "border" => parse_border(text).map(|border| theme.border = Some(border)),
+                // The highlight, the only one left
+                _ => parse_color(text).map(|color| theme.highlight = color),
+            };
+            if applied.is_none() {
+                let reason = match element.as_str() {
+                    "label" => "isn't a style (bold, italic, underlined, dim or plain)",
+                    "border" => "isn't a border (plain, rounded, double or thick)",
+                    _ => "isn't a color name, an index of the 256 colors or #rrggbb",
+                };
+                warn(element, value, reason);
+            }
+        }
+
+        theme
+    }
+
+    /// Border of a block that has `default` without a theme
+    pub(crate) fn border(&self, default: BorderType) -> BorderType {
+        self.border.unwrap_or(default)
+    }
+}
+
+/// Warn about the element of the config that is left as it is in the preset
+fn warn(element: &str, value: &Value, reason: &str) {
+    eprintln!(
+        "Warning: theme.{element} in the config ({value}) {reason}, the one of the preset is used"
+    );
+}
+
+/// Color by its name ("red", "light_blue", "dark-gray", "Grey", "reset"), its index of the 256
+/// colors ("136") or its hex code ("#268bd2")
+pub(crate) fn parse_color(name: &str) -> Option<Color> {
+    let name = name
+        .trim()
+        .to_lowercase()
+        .replace(['_', '-', ' '], "")
+        .replace("grey", "gray");
+
+    if let Some(hex) = name.strip_prefix('#') {
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+
+        return match hex.len() {
+            6 => Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?)),
+            _ => None,
+        };
+    }
+
+    if let Ok(index) = name.parse::<u8>() {
+        return Some(Color::Indexed(index));
+    }
+
+    match name.as_str() {
+        "reset" | "default" | "none" => Some(Color::Reset),
+        "black" => Some(Color::Black),
+        "red" => Some(Color::Red),
+        "green" => Some(Color::Green),
+        "yellow" => Some(Color::Yellow),
+        "blue" => Some(Color::Blue),
+        "magenta" => Some(Color::Magenta),
+        "cyan" => Some(Color::Cyan),
+        "gray" => Some(Color::Gray),
+        "darkgray" => Some(Color::DarkGray),
+        "lightred" => Some(Color::LightRed),
+        "lightgreen" => Some(Color::LightGreen),
+        "lightyellow" => Some(Color::LightYellow),
+        "lightblue" => Some(Color::LightBlue),
+        "lightmagenta" => Some(Color::LightMagenta),
+        "lightcyan" => Some(Color::LightCyan),
+        "white" => Some(Color::White),
+        _ => None,
+    }
+}
+
+/// Modifiers like "bold", "bold+italic" or "plain" (none of them)
+fn parse_modifier(style: &str) -> Option<Modifier> {
+    style
+        .split(['+', ',', ' '])
+        .filter(|part| !part.is_empty())
+        .try_fold(Modifier::empty(), |modifier, part| {
+            let part = match part.to_lowercase().as_str() {
+                "plain" | "none" | "normal" => Modifier::empty(),
+                "bold" => Modifier::BOLD,
+                "italic" => Modifier::ITALIC,
+                "underlined" | "underline" => Modifier::UNDERLINED,
+                "dim" => Modifier::DIM,
+                "reversed" => Modifier::REVERSED,
+                _ => return None,
+            };
+
+            Some(modifier | part)
+        })
+}
+
+fn parse_border(border: &str) -> Option<BorderType> {
+    match border.trim().to_lowercase().as_str() {
+        "plain" => Some(BorderType::Plain),
+        "rounded" => Some(BorderType::Rounded),
+        "double" => Some(BorderType::Double),
+        "thick" => Some(BorderType::Thick),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn color_names() {
+        let cases = [
+            ("red", Some(Color::Red)),
+            (" Light_Blue ", Some(Color::LightBlue)),
+            ("dark-grey", Some(Color::DarkGray)),
+            ("Dark Gray", Some(Color::DarkGray)),
+            ("default", Some(Color::Reset)),
+            ("136", Some(Color::Indexed(136))),
+            ("#268BD2", Some(Color::Rgb(0x26, 0x8b, 0xd2))),
+            // Not colors
+            ("256", None),
+            ("#268bd", None),
+            ("#268bdz", None),
+            ("purple", None),
+            ("", None),
+        ];
+
+        for (name, color) in cases {
+            assert_eq!(parse_color(name), color, "{name:?}");
+        }
+    }
+
+    #[test]
+    fn styles_and_borders() {
+        assert_eq!(parse_modifier("bold"), Some(Modifier::BOLD));
+        assert_eq!(
+            parse_modifier("Bold+italic"),
+            Some(Modifier::BOLD | Modifier::ITALIC)
+        );
+        assert_eq!(parse_modifier("plain"), Some(Modifier::empty()));
+        assert_eq!(parse_modifier("bold+blinking"), None);
+
+        assert_eq!(parse_border(" Double"), Some(BorderType::Double));
+        assert_eq!(parse_border("dashed"), None);
+    }
+
+    #[test]
+    fn presets_with_overrides() {
+        let section = |json: Value| json.as_object().unwrap().clone();
+
+        // Every preset has a theme, and the default one is used without any
+        for name in Theme::PRESETS {
+            assert!(Theme::preset(name).is_some(), "{name}");
+        }
+        assert_eq!(Theme::resolve(None, &Map::new()), Theme::default());
+        assert_eq!(
+            Theme::resolve(Some("unknown"), &Map::new()),
+            Theme::default()
+        );
+
+        // The preset of the config, with its elements overridden
+        let theme = Theme::resolve(
+            None,
+            &section(json!({
+                "preset": "light",
+                "bar": "green",
+                "value_bg": "#000000",
+                "label": "bold+underlined",
+                "border": "thick",
+            })),
+        );
+        assert_eq!(
+            theme,
+            Theme {
+                bar: Color::Green,
+                value_bg: Some(Color::Rgb(0, 0, 0)),
+                label: Modifier::BOLD | Modifier::UNDERLINED,
+                border: Some(BorderType::Thick),
+                ..Theme::preset("light").unwrap()
+            }
+        );
+
+        // --theme wins over the preset of the config, but not over its elements
+        let theme = Theme::resolve(
+            Some("solarized"),
+            &section(json!({ "preset": "light", "highlight": "yellow" })),
+        );
+        assert_eq!(
+            theme,
+            Theme {
+                highlight: Color::Yellow,
+                ..Theme::preset("solarized").unwrap()
+            }
+        );
+
+        // The elements that can't be read are left as they are in the preset, one by one
+        let theme = Theme::resolve(
+            None,
+            &section(json!({
+                "preset": 3,
+                "bar": "purple",
+                "value_fg": 7,
+                "border": "dashed",
+                "highlight": "cyan",
+                "shadow": "black",
+            })),
+        );
+        assert_eq!(
+            theme,
+            Theme {
+                highlight: Color::Cyan,
+                ..Theme::default()
+            }
+        );
+    }
+}
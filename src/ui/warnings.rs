@@ -0,0 +1,268 @@
+//! Strip of the degradations of the data at the bottom of the frame: how many there are in the TUI
+//! (listed in a popup with `w`), or the most severe of them in full in the snapshots
+
+use std::cmp::Reverse;
+
+use itertools::Itertools;
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    data::{Degradation, Severity},
+    locale::Text,
+    ui::{caveat_style, extreme_temperature_style, RenderOptions, Theme},
+};
+
+/// Lines of the strip in the snapshots, the rest of the degradations are only counted on the last
+/// one
+pub(super) const ONE_SHOT_LINES: usize = 2;
+
+/// How the strip is drawn
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub(super) enum WarningsView {
+    /// The most severe ones in full (the snapshots, nothing can be pressed there)
+    #[default]
+    OneShot,
+    /// Only how many there are
+    Collapsed,
+    /// All of them in a popup over the data
+    Expanded,
+}
+
+impl WarningsView {
+    /// Expand the collapsed strip or collapse the popup (the snapshots stay as they are)
+    pub(super) fn toggle(self) -> Self {
+        match self {
+            Self::OneShot => Self::OneShot,
+            Self::Collapsed => Self::Expanded,
+            Self::Expanded => Self::Collapsed,
+        }
+    }
+}
+
+/// The degradations from the most severe, in the order they came in otherwise
+pub(crate) fn by_severity(degradations: &[Degradation]) -> Vec<&Degradation> {
+    degradations
+        .iter()
+        .sorted_by_key(|degradation| Reverse(degradation.severity()))
+        .collect()
+}
+
+/// The `count` most severe degradations, and how many more there are
+pub(crate) fn top_degradations(
+    degradations: &[Degradation],
+    count: usize,
+) -> (Vec<&Degradation>, usize) {
+    let mut sorted = by_severity(degradations);
+    let more = sorted.len().saturating_sub(count);
+    sorted.truncate(count);
+
+    (sorted, more)
+}
+
+/// Rows the strip takes under the content
+pub(super) fn strip_height(count: usize, view: WarningsView) -> u16 {
+    match (count, view) {
+        (0, _) => 0,
+        (_, WarningsView::Collapsed | WarningsView::Expanded) => 1,
+        (count, WarningsView::OneShot) => count.min(ONE_SHOT_LINES) as u16,
+    }
+}
+
+/// Draw the strip into the area (as high as [`strip_height`]), and the popup over the frame if the
+/// view is expanded
+pub(super) fn draw_warnings(
+    f: &mut Frame<impl Backend>,
+    degradations: &[Degradation],
+    (view, options): (WarningsView, RenderOptions),
+    (area, frame): (Rect, Rect),
+) {
+    if degradations.is_empty() {
+        return;
+    }
+
+    let language = options.language;
+    let lines = match view {
+        WarningsView::OneShot => {
+            let (top, more) = top_degradations(degradations, ONE_SHOT_LINES);
+            let last = top.len() - 1;
+
+            top.into_iter()
+                .enumerate()
+                .map(|(i, degradation)| {
+                    let message = match (i == last, more) {
+                        (true, more) if more > 0 => format!(
+                            "⚠ {} {}",
+                            degradation.message(language),
+                            language.format(Text::MoreWarnings, &[&more])
+                        ),
+                        _ => format!("⚠ {}", degradation.message(language)),
+                    };
+
+                    Spans::from(Span::styled(
+                        options.text(message),
+                        severity_style(degradation.severity(), &options.theme),
+                    ))
+                })
+                .collect_vec()
+        }
+        WarningsView::Collapsed | WarningsView::Expanded => {
+            let summary = match degradations.len() {
+                1 => language.text(Text::OneWarning).to_string(),
+                count => language.format(Text::Warnings, &[&count]),
+            };
+
+            vec![Spans::from(Span::styled(
+                options.text(summary),
+                Style::default()
+                    .fg(options.theme.muted)
+                    .add_modifier(Modifier::DIM),
+            ))]
+        }
+    };
+
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), area);
+
+    if view == WarningsView::Expanded {
+        draw_popup(f, degradations, options, frame);
+    }
+}
+
+/// Draw all the degradations in a bordered list in the middle of the frame
+fn draw_popup(
+    f: &mut Frame<impl Backend>,
+    degradations: &[Degradation],
+    options: RenderOptions,
+    frame: Rect,
+) {
+    let lines = by_severity(degradations)
+        .into_iter()
+        .map(|degradation| {
+            Spans::from(Span::styled(
+                options.text(format!("⚠ {}", degradation.message(options.language))),
+                severity_style(degradation.severity(), &options.theme),
+            ))
+        })
+        .collect_vec();
+
+    // The borders and a line per degradation, in the middle 80% of the width
+    let width = frame.width * 4 / 5;
+    let height = (lines.len() as u16 + 2).min(frame.height);
+    let popup = Rect {
+        x: frame.x + (frame.width - width) / 2,
+        y: frame.y + (frame.height - height) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: true }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", options.language.text(Text::WarningsTitle)))
+                .title_alignment(Alignment::Center)
+                .border_type(options.theme.border(BorderType::Rounded)),
+        ),
+        popup,
+    );
+}
+
+/// The severe degradations in the color of the danger, the warnings in the one of the caveats and
+/// the notices muted
+fn severity_style(severity: Severity, theme: &Theme) -> Style {
+    match severity {
+        Severity::Severe => extreme_temperature_style(theme),
+        Severity::Warning => caveat_style(theme),
+        _ => Style::default().fg(theme.muted),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::*;
+    use crate::{data::Extra, providers::Provider};
+
+    /// Five degradations, from the least severe to the most
+    fn five() -> Vec<Degradation> {
+        vec![
+            Degradation::UnknownAddress,
+            Degradation::MissingExtra {
+                extra: Extra::Alerts,
+                reason: Some("timed out".to_string()),
+            },
+            Degradation::DuplicateHours(2),
+            Degradation::Failover {
+                provider: Provider::OpenMeteo,
+                reason: "met_no has no history".to_string(),
+            },
+            Degradation::Stale {
+                cached_at: Utc::now() - Duration::hours(3),
+            },
+        ]
+    }
+
+    #[test]
+    fn most_severe_first() {
+        let five = five();
+        let severities = by_severity(&five)
+            .into_iter()
+            .map(Degradation::severity)
+            .collect_vec();
+        assert_eq!(
+            severities,
+            [
+                Severity::Severe,
+                Severity::Warning,
+                Severity::Warning,
+                Severity::Notice,
+                Severity::Notice,
+            ]
+        );
+        // The same severity stays in its order
+        assert_eq!(by_severity(&five)[1], &five[2]);
+        assert_eq!(by_severity(&five)[3], &five[0]);
+
+        // (how many to show, the shown ones and how many are left out)
+        let cases: [(usize, &[usize], usize); 4] = [
+            (0, &[], 5),
+            (2, &[4, 2], 3),
+            (5, &[4, 2, 3, 0, 1], 0),
+            (9, &[4, 2, 3, 0, 1], 0),
+        ];
+        for (count, shown, more) in cases {
+            let expected = shown.iter().map(|i| &five[*i]).collect_vec();
+
+            assert_eq!(
+                top_degradations(&five, count),
+                (expected, more),
+                "top {count}"
+            );
+        }
+    }
+
+    #[test]
+    fn strip_heights() {
+        // (degradations, view, height)
+        let cases = [
+            (0, WarningsView::OneShot, 0),
+            (0, WarningsView::Collapsed, 0),
+            (1, WarningsView::OneShot, 1),
+            (5, WarningsView::OneShot, 2),
+            (5, WarningsView::Collapsed, 1),
+            (5, WarningsView::Expanded, 1),
+        ];
+
+        for (count, view, height) in cases {
+            assert_eq!(strip_height(count, view), height, "{count} {view:?}");
+        }
+    }
+}
@@ -0,0 +1,413 @@
+//! Newer releases of the weather cli on GitHub: the check for them (`weather update`, and once a
+//! day if it's turned on) and the replacement of the running executable with the new one
+
+use std::{fs, path::Path};
+
+use chrono::{DateTime, Duration, Utc};
+use reqwest::StatusCode;
+use semver::Version;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    built_info,
+    config::NetworkSettings,
+    error::{Result, WeatherError},
+    http,
+    paths::Paths,
+};
+
+/// Version of the running executable
+pub const CURRENT_VERSION: &str = built_info::PKG_VERSION;
+
+/// Environment variable that turns the daily check off (set to anything), whatever the config says
+pub const NO_UPDATE_CHECK_ENV: &str = "WEATHER_NO_UPDATE_CHECK";
+
+/// GitHub doesn't answer the requests without it
+const USER_AGENT: &str = concat!("weather-cli/", env!("CARGO_PKG_VERSION"));
+
+/// How often the daily check asks GitHub
+const CHECK_INTERVAL_HOURS: i64 = 24;
+
+/// Timeout of the daily check, nobody should wait for it
+const CHECK_TIMEOUT_SECS: u64 = 3;
+
+/// Timeout of the download of the new executable, which is a lot bigger than the API responses
+const DOWNLOAD_TIMEOUT_SECS: u64 = 120;
+
+/// File in the state directory with the time of the last daily check
+const LAST_CHECK_FILE: &str = "update_check.json";
+
+/// Extensions of the release assets that are not the executables themselves
+const NOT_EXECUTABLE: [&str; 8] = [
+    ".sha256", ".sig", ".asc", ".txt", ".tar.gz", ".tar.xz", ".tgz", ".zip",
+];
+
+/// Latest release, as the GitHub API returns it (only the parts that are used)
+#[derive(Debug, Clone, serde::Deserialize)]
+#[non_exhaustive]
+pub struct Release {
+    /// "v0.3.0" (or "0.3.0")
+    pub tag_name: String,
+    /// Page of the release
+    pub html_url: String,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+/// File attached to a release
+#[derive(Debug, Clone, serde::Deserialize)]
+#[non_exhaustive]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    /// Version of the release, `None` if the tag isn't a version
+    pub fn version(&self) -> Option<Version> {
+        Version::parse(self.tag_name.trim().trim_start_matches(['v', 'V'])).ok()
+    }
+
+    /// Version of the release if it's newer than the running one
+    pub fn newer_version(&self) -> Option<Version> {
+        self.newer_than(CURRENT_VERSION)
+    }
+
+    /// Version of the release if it's newer than `current` (the prereleases are older than the
+    /// releases of the same version)
+    pub fn newer_than(&self, current: &str) -> Option<Version> {
+        let current = Version::parse(current).ok()?;
+
+        self.version().filter(|version| *version > current)
+    }
+
+    /// Executable built for the target triple ("weather-x86_64-unknown-linux-gnu",
+    /// "weather-x86_64-pc-windows-msvc.exe"), the archives and the checksums are skipped
+    pub fn executable_for(&self, target: &str) -> Option<&Asset> {
+        self.assets.iter().find(|asset| {
+            let name = asset.name.to_lowercase();
+
+            name.contains(target) && !NOT_EXECUTABLE.iter().any(|ext| name.ends_with(ext))
+        })
+    }
+
+    /// File with the SHA-256 checksums of the assets ("checksums.txt", "SHA256SUMS")
+    pub fn checksums(&self) -> Option<&Asset> {
+        self.assets.iter().find(|asset| {
+            let name = asset.name.to_lowercase();
+
+            name.contains("checksums") || name.contains("sha256sums")
+        })
+    }
+}
+
+/// What is said about the newer version
+pub fn update_message(release: &Release, version: &Version) -> String {
+    format!(
+        "weather-cli {version} is available (you have {CURRENT_VERSION}): {}",
+        release.html_url
+    )
+}
+
+/// Ask GitHub for the latest release
+pub fn latest_release(settings: &NetworkSettings) -> Result<Release> {
+    let repository = built_info::PKG_REPOSITORY
+        .trim_start_matches("https://github.com/")
+        .trim_end_matches('/');
+    let url = format!("https://api.github.com/repos/{repository}/releases/latest");
+
+    let response = http::client(settings)?
+        .get(url)
+        .header("User-Agent", USER_AGENT)
+        .header("Accept", "application/vnd.github+json")
+        .send()?;
+
+    match response.status() {
+        StatusCode::NOT_FOUND => Err(WeatherError::Provider(
+            "There are no releases of the weather cli on GitHub yet".to_string(),
+        )),
+        _ => Ok(response.error_for_status()?.json()?),
+    }
+}
+
+/// Check for a newer version if it's turned on and the last check was more than a day ago, the
+/// message about it if there is one. Nothing that goes wrong here is worth bothering anyone with,
+/// so the failures are the same as no newer version
+pub fn check_daily(
+    paths: &Paths,
+    settings: &NetworkSettings,
+    enabled: bool,
+    now: DateTime<Utc>,
+) -> Option<String> {
+    if !enabled || std::env::var_os(NO_UPDATE_CHECK_ENV).is_some() {
+        return None;
+    }
+
+    let file_path = paths.state_dir.join(LAST_CHECK_FILE);
+    let last_check = fs::read_to_string(&file_path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<DateTime<Utc>>(&json).ok());
+
+    if last_check.is_some_and(|last_check| now - last_check < Duration::hours(CHECK_INTERVAL_HOURS))
+    {
+        return None;
+    }
+
+    // Written before asking, so that a check that fails isn't repeated on every run
+    let _ = fs::create_dir_all(&paths.state_dir)
+        .and_then(|_| fs::write(&file_path, serde_json::to_string(&now).unwrap_or_default()));
+
+    let settings = NetworkSettings {
+        timeout_secs: settings.timeout_secs.min(CHECK_TIMEOUT_SECS),
+        ..settings.clone()
+    };
+    let release = latest_release(&settings).ok()?;
+    let version = release.newer_version()?;
+
+    Some(update_message(&release, &version))
+}
+
+/// Download the executable of the release built for this platform, check it against the checksums
+/// of the release and put it in place of the running one
+pub fn install(release: &Release, settings: &NetworkSettings) -> Result<()> {
+    let asset = release.executable_for(built_info::TARGET).ok_or_else(|| {
+        WeatherError::Unsupported(format!(
+            "The release has no executable for {}, it can be built from the source: {}",
+            built_info::TARGET,
+            release.html_url
+        ))
+    })?;
+    let checksums = release.checksums().ok_or_else(|| {
+        WeatherError::InvalidResponse(format!(
+            "The release has no checksums, so {} can't be verified and wasn't installed",
+            asset.name
+        ))
+    })?;
+
+    let client = http::client(&NetworkSettings {
+        timeout_secs: settings.timeout_secs.max(DOWNLOAD_TIMEOUT_SECS),
+        ..settings.clone()
+    })?;
+    let download = |url: &str| -> Result<Vec<u8>> {
+        Ok(client
+            .get(url)
+            .header("User-Agent", USER_AGENT)
+            .send()?
+            .error_for_status()?
+            .bytes()?
+            .to_vec())
+    };
+
+    let checksums =
+        String::from_utf8_lossy(&download(&checksums.browser_download_url)?).to_string();
+    let expected = expected_checksum(&checksums, &asset.name).ok_or_else(|| {
+        WeatherError::InvalidResponse(format!(
+            "The checksums of the release don't have {}, so it can't be verified and wasn't \
+            installed",
+            asset.name
+        ))
+    })?;
+
+    let executable = download(&asset.browser_download_url)?;
+    let checksum = format!("{:x}", Sha256::digest(&executable));
+
+    if !checksum.eq_ignore_ascii_case(&expected) {
+        return Err(WeatherError::InvalidResponse(format!(
+            "The checksum of the downloaded {} ({checksum}) is not the one of the release \
+            ({expected}), nothing was replaced",
+            asset.name
+        )));
+    }
+
+    replace_executable(&std::env::current_exe()?, &executable)
+}
+
+/// Checksum of the file in the `sha256sum` output ("<checksum>  <name>", with `*` before the
+/// names of the files checked in the binary mode)
+pub fn expected_checksum(checksums: &str, name: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        let (checksum, file) = line.trim().split_once(char::is_whitespace)?;
+        let file = file.trim_start().trim_start_matches('*');
+
+        (file == name && checksum.len() == 64 && checksum.chars().all(|c| c.is_ascii_hexdigit()))
+            .then(|| checksum.to_lowercase())
+    })
+}
+
+/// Put the new executable in place of the running one. It's written next to it first, so that it's
+/// moved in place instead of copied over, and a failed write leaves the old one as it was
+fn replace_executable(current: &Path, executable: &[u8]) -> Result<()> {
+    let file_name = current
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(built_info::PKG_NAME);
+    let temp_path = current.with_file_name(format!(".{file_name}.update"));
+
+    let result = fs::write(&temp_path, executable)
+        // With the permissions of the old one (the executable bit on unix)
+        .and_then(|_| fs::set_permissions(&temp_path, fs::metadata(current)?.permissions()))
+        .and_then(|_| match cfg!(windows) {
+            // The running executable can't be replaced on Windows, but it can be renamed out of
+            // the way. The renamed one is removed by the next update, it's still running now
+            true => {
+                let old_path = current.with_extension("old");
+                let _ = fs::remove_file(&old_path);
+
+                fs::rename(current, &old_path)?;
+                fs::rename(&temp_path, current).inspect_err(|_| {
+                    let _ = fs::rename(&old_path, current);
+                })
+            }
+            false => fs::rename(&temp_path, current),
+        });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    result.map_err(|e| {
+        WeatherError::Io(std::io::Error::new(
+            e.kind(),
+            format!("Couldn't replace {}: {e}", current.display()),
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Latest release the way the GitHub API returns it (with most of the fields left out)
+    const RELEASE: &str = r#"{
+        "url": "https://api.github.com/repos/tukanoidd/elastio_rust_test_weather/releases/1",
+        "html_url": "https://github.com/tukanoidd/elastio_rust_test_weather/releases/tag/v0.3.0",
+        "tag_name": "v0.3.0",
+        "name": "0.3.0",
+        "draft": false,
+        "prerelease": false,
+        "assets": [
+            {"name": "checksums.txt", "size": 402, "browser_download_url": "https://example.com/checksums.txt"},
+            {"name": "weather-x86_64-unknown-linux-gnu.tar.gz", "size": 3100000, "browser_download_url": "https://example.com/linux.tar.gz"},
+            {"name": "weather-x86_64-unknown-linux-gnu", "size": 7400000, "browser_download_url": "https://example.com/linux"},
+            {"name": "weather-x86_64-unknown-linux-gnu.sha256", "size": 98, "browser_download_url": "https://example.com/linux.sha256"},
+            {"name": "weather-aarch64-apple-darwin", "size": 6900000, "browser_download_url": "https://example.com/macos"},
+            {"name": "Weather-x86_64-pc-windows-msvc.exe", "size": 7100000, "browser_download_url": "https://example.com/windows.exe"}
+        ]
+    }"#;
+
+    fn release(tag_name: &str) -> Release {
+        Release {
+            tag_name: tag_name.to_string(),
+            ..serde_json::from_str(RELEASE).unwrap()
+        }
+    }
+
+    #[test]
+    fn version_comparison() {
+        let cases = [
+            ("v0.3.0", "0.1.0", Some("0.3.0")),
+            ("0.3.0", "0.1.0", Some("0.3.0")),
+            (" V0.1.1 ", "0.1.0", Some("0.1.1")),
+            ("v0.10.0", "0.9.0", Some("0.10.0")),
+            // Not newer
+            ("v0.1.0", "0.1.0", None),
+            ("v0.0.9", "0.1.0", None),
+            // The prereleases come before their release, and after the one before it
+            ("v0.2.0-rc.1", "0.2.0", None),
+            ("v0.2.0-rc.1", "0.1.0", Some("0.2.0-rc.1")),
+            ("v0.2.0", "0.2.0-rc.1", Some("0.2.0")),
+            // Not versions
+            ("nightly", "0.1.0", None),
+            ("v0.3.0", "unknown", None),
+        ];
+
+        for (tag, current, newer) in cases {
+            assert_eq!(
+                release(tag)
+                    .newer_than(current)
+                    .map(|version| version.to_string()),
+                newer.map(String::from),
+                "{tag} vs {current}"
+            );
+        }
+    }
+
+    #[test]
+    fn asset_selection() {
+        let release = release("v0.3.0");
+        let executable = |target| {
+            release
+                .executable_for(target)
+                .map(|asset| asset.browser_download_url.as_str())
+        };
+
+        // The executable itself, not its archive nor its checksum
+        assert_eq!(
+            executable("x86_64-unknown-linux-gnu"),
+            Some("https://example.com/linux")
+        );
+        assert_eq!(
+            executable("aarch64-apple-darwin"),
+            Some("https://example.com/macos")
+        );
+        // Whatever the case of the name
+        assert_eq!(
+            executable("x86_64-pc-windows-msvc"),
+            Some("https://example.com/windows.exe")
+        );
+        assert_eq!(executable("aarch64-unknown-linux-musl"), None);
+
+        assert_eq!(
+            release.checksums().map(|asset| asset.name.as_str()),
+            Some("checksums.txt")
+        );
+        let no_checksums = Release {
+            assets: release.assets[1..].to_vec(),
+            ..release.clone()
+        };
+        assert!(no_checksums.checksums().is_none());
+    }
+
+    #[test]
+    fn checksum_of_the_asset() {
+        let linux = "a".repeat(64);
+        let windows = "B".repeat(64);
+        let checksums = format!(
+            "{linux}  weather-x86_64-unknown-linux-gnu\n\
+            {windows} *Weather-x86_64-pc-windows-msvc.exe\n\
+            abc123  weather-aarch64-apple-darwin\n"
+        );
+
+        assert_eq!(
+            expected_checksum(&checksums, "weather-x86_64-unknown-linux-gnu"),
+            Some(linux)
+        );
+        // Checked in the binary mode, and lowercased
+        assert_eq!(
+            expected_checksum(&checksums, "Weather-x86_64-pc-windows-msvc.exe"),
+            Some("b".repeat(64))
+        );
+        // Not a SHA-256
+        assert_eq!(
+            expected_checksum(&checksums, "weather-aarch64-apple-darwin"),
+            None
+        );
+        // Only the whole names
+        assert_eq!(expected_checksum(&checksums, "weather-x86_64"), None);
+    }
+
+    #[test]
+    fn executable_replaced() {
+        let dir = std::env::temp_dir().join(format!("weather-update-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let current = dir.join("weather");
+        fs::write(&current, "old").unwrap();
+
+        replace_executable(&current, b"new").unwrap();
+
+        assert_eq!(fs::read_to_string(&current).unwrap(), "new");
+        // Nothing left next to it
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+    }
+}
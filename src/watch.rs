@@ -0,0 +1,372 @@
+//! Getting the data again every few seconds (`weather get --watch 60`), streamed to the collectors
+//! that tail the output as a line of compact JSON per refresh (`--output ndjson`)
+
+use std::{io::Write, time::Duration};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    data::WeatherData,
+    error::{Result, WeatherError},
+    last_run::{Snapshot, DEFAULT_PRECISION},
+    locale::Language,
+};
+
+/// Fields of the full object that the deltas carry only when they changed
+const FIELDS: [&str; 7] = [
+    "meta",
+    "freshness",
+    "current",
+    "hourly",
+    "summary",
+    "alerts",
+    "degradations",
+];
+
+/// What a refresh is written as
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NdjsonMode {
+    /// Everything every time
+    #[default]
+    Full,
+    /// Only the fields that changed since the last line with the data, with its fingerprint (the
+    /// first line is the full one)
+    Delta,
+}
+
+/// The lines of the refreshes, with the last of the data written for the deltas
+#[derive(Debug, Default)]
+pub struct NdjsonStream {
+    mode: NdjsonMode,
+    last: Option<(Map<String, Value>, Snapshot)>,
+}
+
+impl NdjsonStream {
+    pub fn new(mode: NdjsonMode) -> Self {
+        Self { mode, last: None }
+    }
+
+    /// The line of the refresh, an error is a line of its own and the next refresh is compared with
+    /// the data before it
+    pub fn line(&mut self, refresh: std::result::Result<&WeatherData, &WeatherError>) -> String {
+        let data = match refresh {
+            Ok(data) => data,
+            Err(e) => {
+                return json!({
+                    "error": { "category": e.category().to_string(), "message": e.to_string() }
+                })
+                .to_string()
+            }
+        };
+
+        let object = full_object(data);
+        // The same fingerprint as the one of --changed-only
+        let snapshot = Snapshot::new(data, DEFAULT_PRECISION);
+        let fingerprint = snapshot.fingerprint();
+
+        let mut line = match (self.mode, &self.last) {
+            (NdjsonMode::Delta, Some((last_object, last_snapshot))) => {
+                let mut delta = Map::new();
+                delta.insert("previous".to_string(), last_snapshot.fingerprint().into());
+                delta.insert(
+                    "changes".to_string(),
+                    snapshot.changes(last_snapshot).into(),
+                );
+                for field in FIELDS {
+                    if object.get(field) != last_object.get(field) {
+                        delta.insert(field.to_string(), object[field].clone());
+                    }
+                }
+
+                delta
+            }
+            _ => object.clone(),
+        };
+        line.insert("fingerprint".to_string(), fingerprint.into());
+
+        self.last = Some((object, snapshot));
+
+        Value::Object(line).to_string()
+    }
+}
+
+/// Everything of the data a refresh is written with: where and from whom it is, how old it is, the
+/// current conditions, the hourly series (the missing values are `null`) with their summary, and
+/// what's wrong with it. The numbers and the times are the same whatever the language is, the
+/// degradations are in English
+pub fn full_object(data: &WeatherData) -> Map<String, Value> {
+    let number = |value: f64| match value.is_nan() {
+        true => Value::Null,
+        false => value.into(),
+    };
+    let numbers = |values: &[f64]| values.iter().copied().map(number).collect::<Vec<_>>();
+    let time = |i: usize| data.timestamps.get(i).map(|ts| ts.time.to_rfc3339());
+
+    let mut hourly = Map::new();
+    hourly.insert(
+        "time".to_string(),
+        data.timestamps
+            .iter()
+            .map(|timestamp| timestamp.time.to_rfc3339())
+            .collect::<Vec<_>>()
+            .into(),
+    );
+    for (name, values) in [
+        ("temperature", &data.temperatures),
+        ("precipitation", &data.precipitation),
+        (
+            "precipitation_probability",
+            &data.precipitation_probabilities,
+        ),
+        ("wind_speed", &data.wind_speeds),
+    ] {
+        if !values.is_empty() {
+            hourly.insert(name.to_string(), numbers(values).into());
+        }
+    }
+    if !data.weather_codes.is_empty() {
+        hourly.insert(
+            "conditions".to_string(),
+            data.weather_codes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .into(),
+        );
+    }
+    // Only when some of the hours are not from this response
+    if !data.sources.is_empty() {
+        hourly.insert(
+            "source".to_string(),
+            (0..data.timestamps.len())
+                .map(|i| data.source(i).to_string())
+                .collect::<Vec<_>>()
+                .into(),
+        );
+    }
+
+    let summary = data.summary();
+    let extreme = |extreme: Option<(f64, usize)>| {
+        extreme.map(|(value, i)| json!({ "value": value, "time": time(i) }))
+    };
+
+    let object = json!({
+        "meta": {
+            "provider": data.provider.to_string(),
+            "location": data.location.name(),
+            "address_full": data.location.display_name,
+            "country_code": data.location.country_code,
+            "lat": data.location.lat,
+            "lon": data.location.lon,
+            "date": data.requested_date,
+            "timezone": data.timezone,
+            "units": {
+                "temperature": data.unit,
+                "precipitation": data.precipitation_unit,
+                "wind_speed": data.wind_speed_unit,
+            },
+        },
+        "freshness": {
+            "fetched_at": data.fetched_at.to_rfc3339(),
+            "data_updated_at": data.data_updated_at.map(|updated_at| updated_at.to_rfc3339()),
+            "cached_at": data.cached_at.map(|cached_at| cached_at.to_rfc3339()),
+        },
+        "current": data.current.as_ref().map(|current| json!({
+            "time": current.time,
+            "temperature": number(current.temperature),
+            "conditions": current.weather_code.to_string(),
+            "wind_speed": number(current.wind_speed),
+            "wind_direction": current.wind_direction.to_string(),
+        })),
+        "hourly": hourly,
+        "summary": {
+            "min": extreme(summary.min),
+            "max": extreme(summary.max),
+            "mean": summary.mean,
+            "trend": summary.trend.map(|trend| trend.to_string()),
+        },
+        "alerts": data.alerts.iter().map(|alert| json!({
+            "event": alert.event,
+            "severity": alert.severity,
+            "headline": alert.headline,
+            "onset": alert.onset.map(|onset| onset.to_rfc3339()),
+            "expires": alert.expires.map(|expires| expires.to_rfc3339()),
+        })).collect::<Vec<_>>(),
+        "degradations": data.degradations().iter().map(|degradation| json!({
+            "severity": degradation.severity().to_string(),
+            "message": degradation.message(Language::En),
+        })).collect::<Vec<_>>(),
+    });
+
+    match object {
+        Value::Object(object) => object,
+        _ => unreachable!("the object is built as one"),
+    }
+}
+
+/// Get the data with `fetch` every `interval`, `ticks` times (forever without them), writing a
+/// line of the stream for each refresh and flushing it right away. Only the output failing (the
+/// collector went away) stops it
+pub fn watch(
+    mut fetch: impl FnMut() -> Result<WeatherData>,
+    (interval, ticks): (Duration, Option<usize>),
+    stream: &mut NdjsonStream,
+    out: &mut impl Write,
+) -> Result<()> {
+    let mut tick = 0;
+    while ticks.is_none_or(|ticks| tick < ticks) {
+        if tick > 0 {
+            std::thread::sleep(interval);
+        }
+
+        writeln!(out, "{}", stream.line(fetch().as_ref()))?;
+        out.flush()?;
+        tick += 1;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geocode::ResolvedLocation, providers::Provider, providers::ProviderRequestType,
+        test_server::TestServer,
+    };
+
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: application/json";
+    const FAILED: &str = "HTTP/1.1 500 Internal Server Error";
+
+    /// open_meteo response of 2024-06-14 with the temperatures of its hours
+    fn response(temperatures: [f64; 3]) -> &'static str {
+        let json = json!({
+            "utc_offset_seconds": 0,
+            "timezone": "GMT",
+            "hourly_units": { "temperature_2m": "°C", "precipitation": "mm" },
+            "hourly": {
+                "time": ["2024-06-14T12:00", "2024-06-14T13:00", "2024-06-14T14:00"],
+                "temperature_2m": temperatures,
+                "precipitation": [0.0, 0.0, 0.0],
+            },
+        });
+
+        Box::leak(json.to_string().into_boxed_str())
+    }
+
+    fn fetch(server: &TestServer) -> impl FnMut() -> Result<WeatherData> + '_ {
+        || {
+            let response = reqwest::blocking::get(server.url.join("forecast").unwrap())?;
+            if !response.status().is_success() {
+                return Err(WeatherError::Provider(format!(
+                    "open_meteo answered with {}",
+                    response.status()
+                )));
+            }
+            let json = response.json::<Map<String, Value>>()?;
+
+            WeatherData::from_json(
+                &json,
+                Provider::OpenMeteo,
+                ProviderRequestType::Forecast,
+                "2024-06-14".to_string(),
+                None,
+                ResolvedLocation::default(),
+                (false, Default::default()),
+            )
+        }
+    }
+
+    /// The lines of four refreshes from the server: the same data twice, the warmer data and an
+    /// error
+    fn lines(mode: NdjsonMode) -> Vec<Value> {
+        let server = TestServer::start(vec![
+            (OK, response([18.0, 19.0, 18.0])),
+            (OK, response([18.0, 19.0, 18.0])),
+            (OK, response([18.0, 22.0, 18.0])),
+            (FAILED, "{}"),
+        ]);
+
+        let mut out = vec![];
+        watch(
+            fetch(&server),
+            (Duration::ZERO, Some(4)),
+            &mut NdjsonStream::new(mode),
+            &mut out,
+        )
+        .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        // Compact, a line per refresh and nothing for the terminals
+        assert!(!out.contains('\x1b') && !out.contains("\n "), "{out}");
+        out.lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn full_lines() {
+        let lines = lines(NdjsonMode::Full);
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], lines[1]);
+        assert_eq!(lines[0]["hourly"]["temperature"], json!([18.0, 19.0, 18.0]));
+        assert_eq!(lines[2]["hourly"]["temperature"], json!([18.0, 22.0, 18.0]));
+        assert_eq!(
+            lines[2]["hourly"]["time"][0],
+            json!("2024-06-14T12:00:00+00:00")
+        );
+        assert_ne!(lines[1]["fingerprint"], lines[2]["fingerprint"]);
+        assert_eq!(
+            lines[3],
+            json!({
+                "error": {
+                    "category": "provider",
+                    "message": "open_meteo answered with 500 Internal Server Error",
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn delta_lines() {
+        let (full, delta) = (lines(NdjsonMode::Full), lines(NdjsonMode::Delta));
+
+        // The first line is the full one, the same data again changes nothing
+        assert_eq!(delta[0], full[0]);
+        assert_eq!(
+            delta[1],
+            json!({
+                "previous": full[0]["fingerprint"],
+                "fingerprint": full[1]["fingerprint"],
+                "changes": [],
+            })
+        );
+        assert_eq!(
+            delta[2],
+            json!({
+                "previous": full[1]["fingerprint"],
+                "fingerprint": full[2]["fingerprint"],
+                "changes": ["max temp 19→22"],
+                "hourly": full[2]["hourly"],
+                "summary": full[2]["summary"],
+            })
+        );
+        assert_eq!(delta[3], full[3]);
+
+        // The deltas carry the fields the full lines differ in, and only them
+        for i in 1..3 {
+            let changed = FIELDS
+                .into_iter()
+                .filter(|field| full[i][field] != full[i - 1][field])
+                .collect::<Vec<_>>();
+            let carried = FIELDS
+                .into_iter()
+                .filter(|field| delta[i].get(field).is_some())
+                .collect::<Vec<_>>();
+
+            assert_eq!(carried, changed, "line {i}");
+        }
+    }
+}
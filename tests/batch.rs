@@ -0,0 +1,160 @@
+//! `weather get` with several addresses: the date after them, and the addresses failing on their own
+//! while the rest are shown
+
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+
+/// Archive request for the hours of 2024-06-14 at the coordinates
+fn archive_url(lat: u32, lon: u32) -> String {
+    format!(
+        "https://archive-api.open-meteo.com/v1/archive?end_date=2024-06-14&hourly=temperature_2m%2C\
+        windspeed_10m%2Cwinddirection_10m%2Cwindgusts_10m%2Cprecipitation%2Crelativehumidity_2m%2C\
+        apparent_temperature%2Cweathercode%2Csurface_pressure%2Ccloudcover&latitude={lat}\
+        &longitude={lon}&start_date=2024-06-14&timezone=auto"
+    )
+}
+
+/// Empty directory of the test
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("weather-batch-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+/// Cassette with the archive response for 50,30 only, the rest of the requests aren't in it
+fn cassette(dir: &Path) -> PathBuf {
+    let body = r#"{"utc_offset_seconds":0,"timezone":"GMT","hourly_units":{"temperature_2m":"°C"},"hourly":{"time":["2024-06-14T12:00","2024-06-14T13:00"],"temperature_2m":[18.5,19.0]}}"#;
+    let path = dir.join("cassette.json");
+    std::fs::write(
+        &path,
+        serde_json::json!([{
+            "url": archive_url(50, 30),
+            "status": 200,
+            "headers": { "content-type": "application/json" },
+            "body": body,
+        }])
+        .to_string(),
+    )
+    .unwrap();
+
+    path
+}
+
+/// `weather get <args>` answered from the cassette, in the output (`--plain` by default)
+fn get(dir: &Path, args: &[&str]) -> Command {
+    get_as(dir, args, &["--plain"])
+}
+
+fn get_as(dir: &Path, args: &[&str], output: &[&str]) -> Command {
+    let mut command = Command::cargo_bin("weather").unwrap();
+    command
+        .env("WEATHER_NO_UPDATE_CHECK", "1")
+        .env_remove("WEATHER_PROVIDER")
+        .env(
+            "WEATHER_CASSETTE",
+            format!("replay:{}", cassette(dir).display()),
+        )
+        .arg("--config-dir")
+        .arg(dir.join("config"))
+        .arg("--cache-dir")
+        .arg(dir.join("cache"))
+        .arg("get")
+        .args(args)
+        .args(output)
+        .args(["--lang", "en"]);
+
+    command
+}
+
+#[test]
+fn invalid_date_after_the_addresses() {
+    let dir = test_dir("invalid-date");
+
+    // The date that doesn't exist is still the date, not one more address
+    for args in [
+        ["50,30", "9999-99-99"].as_slice(),
+        &["Kyiv", "2024-13-01"],
+        &["50,30", "51,31", "2024-13-01"],
+    ] {
+        let output = get(&dir, args).assert().failure().code(2);
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr).to_string();
+        assert!(
+            stderr.contains("Couldn't parse the date"),
+            "{args:?}: {stderr}"
+        );
+        assert!(!stderr.contains("addresses"), "{args:?}: {stderr}");
+    }
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn one_address_failing() {
+    let dir = test_dir("partial-failure");
+
+    // The address that failed says why, the other one is still shown
+    let output = get(&dir, &["50,30", "51,31", "2024-06-14"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    assert!(stdout.contains("18.5"), "{stdout}");
+    assert!(stdout.contains("51,31 failed: "), "{stdout}");
+    assert!(stdout.contains("isn't in the cassette"), "{stdout}");
+    assert!(
+        stdout.find("18.5") < stdout.find("51,31 failed"),
+        "{stdout}"
+    );
+
+    // All of them failing is an error
+    let output = get(&dir, &["51,31", "52,32", "2024-06-14"])
+        .assert()
+        .failure();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr).to_string();
+    assert!(
+        stderr.contains("Couldn't get the weather for any of the addresses"),
+        "{stderr}"
+    );
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn json_array() {
+    let dir = test_dir("json");
+
+    // An object per address in their order, the failed one with its error instead of the data
+    let output = get_as(
+        &dir,
+        &["50,30", "51,31", "2024-06-14"],
+        &["--output", "json"],
+    )
+    .assert()
+    .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let entries = json.as_array().unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["address"], "50,30");
+    assert_eq!(entries[0]["meta"]["provider"], "open_meteo");
+    assert_eq!(
+        entries[0]["hourly"]["temperature"],
+        serde_json::json!([18.5, 19.0])
+    );
+    assert!(entries[0].get("error").is_none());
+
+    assert_eq!(entries[1]["address"], "51,31");
+    assert_eq!(entries[1]["error"]["category"], "other");
+    assert!(
+        entries[1]["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("isn't in the cassette"),
+        "{json}"
+    );
+    assert!(entries[1].get("hourly").is_none());
+
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,61 @@
+//! `weather cache clear`: the geocoding cache only, or the cached responses as well with `--all`
+
+use assert_cmd::Command;
+
+#[test]
+fn clear_all() {
+    let dir = std::env::temp_dir().join(format!("weather-cache-clear-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let cache_dir = dir.join("cache");
+    std::fs::create_dir_all(cache_dir.join("responses")).unwrap();
+
+    let (geocache, response, state) = (
+        cache_dir.join("geocache.json"),
+        cache_dir
+            .join("responses")
+            .join("open_meteo_50.45_30.52_now"),
+        cache_dir.join("cooldowns.json"),
+    );
+    let fill = || {
+        for file in [&geocache, &response, &state] {
+            std::fs::write(file, "{}").unwrap();
+        }
+    };
+    let clear = |args: &[&str]| {
+        let output = Command::cargo_bin("weather")
+            .unwrap()
+            .env("WEATHER_NO_UPDATE_CHECK", "1")
+            .arg("--config-dir")
+            .arg(dir.join("config"))
+            .arg("--cache-dir")
+            .arg(&cache_dir)
+            .args(["cache", "clear"])
+            .args(args)
+            .assert()
+            .success();
+
+        String::from_utf8_lossy(&output.get_output().stdout).to_string()
+    };
+
+    // Without --all the responses stay, to be shown offline
+    fill();
+    assert_eq!(clear(&[]), "Geocoding cache cleared\n");
+    assert!(!geocache.exists());
+    assert!(response.exists());
+
+    // The state kept in the same directory isn't a cache
+    fill();
+    assert_eq!(
+        clear(&["--all"]),
+        "Geocoding cache and cached responses cleared\n"
+    );
+    assert!(!geocache.exists());
+    assert!(!cache_dir.join("responses").exists());
+    assert!(state.exists());
+
+    // Nothing cached is nothing to clear
+    std::fs::remove_file(&state).unwrap();
+    clear(&["--all"]);
+
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,80 @@
+//! `weather get --changed-only` run twice against the same replayed response, the second run has
+//! nothing new to print
+
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+
+/// Replayed open_meteo response for the next 2 days, received on 2024-06-14 at 06:30, with the
+/// temperatures all at `temperature`
+fn dump(name: &str, temperature: f64) -> PathBuf {
+    let hours = (0..48)
+        .map(|hour| format!("\"2024-06-{}T{:02}:00\"", 14 + hour / 24, hour % 24))
+        .collect::<Vec<_>>();
+    let body = format!(
+        r#"{{"utc_offset_seconds":0,"timezone":"GMT","current_weather":{{"time":"2024-06-14T06:30","temperature":{temperature:.1},"weathercode":1,"windspeed":5.0,"winddirection":180.0}},"hourly_units":{{"temperature_2m":"°C","precipitation":"mm"}},"hourly":{{"time":[{}],"temperature_2m":[{}],"precipitation":[{}]}}}}"#,
+        hours.join(","),
+        vec![format!("{temperature:.1}"); 48].join(","),
+        vec!["0.0"; 48].join(",")
+    );
+
+    let path = std::env::temp_dir().join(format!(
+        "weather-changed-{name}-{}.dump",
+        std::process::id()
+    ));
+    std::fs::write(
+        &path,
+        format!(
+            "# provider: open_meteo\n\
+            # url: https://api.open-meteo.com/v1/forecast?latitude=52.22&longitude=6.89\n\
+            # time: 2024-06-14T06:30:00Z\n\
+            # location: Garden\n\
+            # lat: 52.22\n\
+            # lon: 6.89\n\
+            # date: next 2 days\n\
+            # daily: false\n\
+            {body}"
+        ),
+    )
+    .unwrap();
+
+    path
+}
+
+/// `weather get --changed-only --plain` for the dump, with the state in `dir`
+fn changed_only(dump: &Path, dir: &Path) -> Command {
+    let mut command = Command::cargo_bin("weather").unwrap();
+    command
+        .env("WEATHER_NO_UPDATE_CHECK", "1")
+        .arg("--config-dir")
+        .arg(dir.join("config"))
+        .arg("--cache-dir")
+        .arg(dir.join("cache"))
+        .args(["get", "--from-dump"])
+        .arg(dump)
+        .args(["--changed-only", "--plain", "--lang", "en"]);
+
+    command
+}
+
+#[test]
+fn second_run_is_silent() {
+    let (first, warmer) = (dump("first", 18.0), dump("warmer", 21.0));
+    let dir = first.with_extension("d");
+
+    // The first run shows the data, the same data again shows nothing
+    let output = changed_only(&first, &dir).assert().success();
+    assert!(!output.get_output().stdout.is_empty());
+    changed_only(&first, &dir).assert().success().stdout("");
+
+    // The changes come before the data
+    let output = changed_only(&warmer, &dir).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    assert!(
+        stdout.starts_with("max temp 18→21, min temp 18→21, now 18→21\n"),
+        "{stdout}"
+    );
+    changed_only(&warmer, &dir).assert().success().stdout("");
+
+    let _ = std::fs::remove_dir_all(dir);
+}
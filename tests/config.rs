@@ -0,0 +1,177 @@
+//! The config location that can't be written to, and the environment overriding the config file
+//! without ending up in it
+
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+
+/// Empty directory of the test
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("weather-config-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+/// Replayed open_meteo response of a single hour
+fn dump(dir: &Path) -> PathBuf {
+    let path = dir.join("response.dump");
+    std::fs::write(
+        &path,
+        "# provider: open_meteo\n\
+        # url: https://api.open-meteo.com/v1/forecast?latitude=52.22&longitude=6.89\n\
+        # time: 2024-06-14T06:30:00Z\n\
+        # location: Garden\n\
+        # lat: 52.22\n\
+        # lon: 6.89\n\
+        # date: 2024-06-14\n\
+        # daily: false\n\
+        {\"utc_offset_seconds\":0,\"timezone\":\"GMT\",\"hourly_units\":{\"temperature_2m\":\"°C\"},\
+        \"hourly\":{\"time\":[\"2024-06-14T12:00\"],\"temperature_2m\":[18.5]}}",
+    )
+    .unwrap();
+
+    path
+}
+
+/// `weather` with the config in `config_dir` and the cache in the test directory
+fn weather(dir: &Path, config_dir: &Path) -> Command {
+    let mut command = Command::cargo_bin("weather").unwrap();
+    command
+        .env("WEATHER_NO_UPDATE_CHECK", "1")
+        .env_remove("WEATHER_PROVIDER")
+        .arg("--config-dir")
+        .arg(config_dir)
+        .arg("--cache-dir")
+        .arg(dir.join("cache"));
+
+    command
+}
+
+#[test]
+fn read_only_config_location() {
+    let dir = test_dir("read-only");
+    // Nothing can be created under a file, not even by root
+    let blocker = dir.join("blocker");
+    std::fs::write(&blocker, "").unwrap();
+    let config_dir = blocker.join("config");
+
+    // The data is shown with the defaults
+    let output = weather(&dir, &config_dir)
+        .args(["get", "--from-dump"])
+        .arg(dump(&dir))
+        .args(["--plain", "--lang", "en"])
+        .assert()
+        .success();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr).to_string();
+    assert!(stderr.contains("using the defaults"), "{stderr}");
+    assert!(!output.get_output().stdout.is_empty());
+
+    // Changing the config says where it can't be written and how to choose another place
+    let output = weather(&dir, &config_dir)
+        .args(["configure", "--unit", "fahrenheit"])
+        .assert()
+        .failure()
+        .code(6);
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr).to_string();
+    assert!(
+        stderr.contains(&format!(
+            "Config location {} is not writable",
+            config_dir.join("config.json").display()
+        )),
+        "{stderr}"
+    );
+    assert!(stderr.contains("--config-dir"), "{stderr}");
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn environment_over_the_file_never_saved() {
+    let dir = test_dir("precedence");
+    let config_dir = dir.join("config");
+    let show = |env: Option<&str>| {
+        let mut command = weather(&dir, &config_dir);
+        if let Some(provider) = env {
+            command.env("WEATHER_PROVIDER", provider);
+        }
+        let output = command.args(["config", "show"]).assert().success();
+
+        String::from_utf8_lossy(&output.get_output().stdout).to_string()
+    };
+    let saved = || std::fs::read_to_string(config_dir.join("config.json")).unwrap();
+
+    weather(&dir, &config_dir)
+        .args(["configure", "open_meteo"])
+        .assert()
+        .success();
+
+    // The environment takes precedence over the file
+    assert!(show(None).contains("Provider: open_meteo\n"));
+    let overridden = show(Some("met_no"));
+    assert!(
+        overridden.contains("Provider: met_no (from WEATHER_PROVIDER)"),
+        "{overridden}"
+    );
+
+    // Saving the config with the override in effect keeps the provider of the file
+    weather(&dir, &config_dir)
+        .env("WEATHER_PROVIDER", "met_no")
+        .args(["configure", "--unit", "fahrenheit"])
+        .assert()
+        .success();
+    let config = saved();
+    assert!(config.contains("\"provider\": \"open_meteo\""), "{config}");
+    assert!(config.contains("\"unit\": \"fahrenheit\""), "{config}");
+    assert!(show(None).contains("Provider: open_meteo\n"));
+
+    // The provider set on the command line is the one saved, the environment still wins the runs
+    weather(&dir, &config_dir)
+        .env("WEATHER_PROVIDER", "met_no")
+        .args(["configure", "open_weather_map"])
+        .assert()
+        .success();
+    assert!(saved().contains("\"provider\": \"open_weather_map\""));
+    assert!(show(Some("met_no")).contains("Provider: met_no (from WEATHER_PROVIDER)"));
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn concurrent_changes_kept() {
+    let dir = test_dir("concurrent");
+    let config_dir = dir.join("config");
+    let executable = assert_cmd::cargo::cargo_bin("weather");
+
+    // All at once, each of them reading the config, adding its location and saving it
+    let children = (0..8)
+        .map(|i| {
+            std::process::Command::new(&executable)
+                .env("WEATHER_NO_UPDATE_CHECK", "1")
+                .env("WEATHER_CASSETTE", "replay:/nonexistent/cassette.json")
+                .arg("--config-dir")
+                .arg(&config_dir)
+                .arg("--cache-dir")
+                .arg(dir.join("cache"))
+                .args(["locations", "add", &format!("place{i}")])
+                .arg(format!("50.{i}, 30.{i}"))
+                .arg("--no-geocode")
+                .stdout(std::process::Stdio::null())
+                .spawn()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    for mut child in children {
+        assert!(child.wait().unwrap().success());
+    }
+
+    // None of them lost the others' changes
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(config_dir.join("config.json")).unwrap())
+            .unwrap();
+    let locations = config["locations"].as_object().unwrap();
+    assert_eq!(locations.len(), 8, "{locations:?}");
+
+    let _ = std::fs::remove_dir_all(dir);
+}
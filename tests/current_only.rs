@@ -0,0 +1,88 @@
+//! The current conditions are still shown when the full forecast request fails
+
+use chrono::{DateTime, Utc};
+use weather::{data::Degradation, paths::Paths, WeatherClient};
+
+#[test]
+fn hourly_500_still_shows_current() {
+    let dir = std::env::temp_dir().join(format!("weather-current-only-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let cassette = dir.join("cassette.json");
+    let interactions = serde_json::json!([
+        {
+            "url": "https://api.open-meteo.com/v1/forecast?current_weather=true&\
+                daily=sunrise%2Csunset&end_date=2024-06-14&hourly=temperature_2m%2C\
+                windspeed_10m%2Cwinddirection_10m%2Cwindgusts_10m%2Cprecipitation%2C\
+                relativehumidity_2m%2Capparent_temperature%2Cweathercode%2Csurface_pressure%2C\
+                cloudcover%2Cprecipitation_probability%2Cuv_index&latitude=50.45&\
+                longitude=30.52&start_date=2024-06-14&timezone=auto",
+            "status": 500,
+            "headers": { "content-type": "text/plain" },
+            "body": "Internal Server Error",
+        },
+        {
+            "url": "https://api.open-meteo.com/v1/forecast?current_weather=true&\
+                forecast_hours=1&hourly=temperature_2m&latitude=50.45&longitude=30.52&\
+                timezone=auto",
+            "status": 200,
+            "headers": { "content-type": "application/json" },
+            "body": serde_json::json!({
+                "utc_offset_seconds": 0,
+                "timezone": "GMT",
+                "current_weather": {
+                    "time": "2024-06-14T12:00",
+                    "temperature": 21.5,
+                    "windspeed": 12.0,
+                    "winddirection": 270.0,
+                    "weathercode": 2,
+                },
+                "current_weather_units": { "temperature": "°C" },
+                "hourly_units": { "temperature_2m": "°C" },
+                "hourly": { "time": ["2024-06-14T12:00"], "temperature_2m": [21.5] },
+            })
+            .to_string(),
+        },
+    ]);
+    std::fs::write(&cassette, interactions.to_string()).unwrap();
+    // Read once, before the first request
+    std::env::set_var("WEATHER_CASSETTE", format!("replay:{}", cassette.display()));
+
+    let client = WeatherClient::builder()
+        .paths(Paths {
+            config_dir: dir.join("config"),
+            cache_dir: dir.join("cache"),
+            state_dir: dir.join("state"),
+        })
+        .now(
+            DateTime::parse_from_rfc3339("2024-06-14T12:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        )
+        .no_geocode(true)
+        .build()
+        .unwrap();
+
+    // The run succeeds with the current conditions alone, and says why the hours are missing
+    let data = client.get("50.45, 30.52", "now").unwrap();
+    assert_eq!(data.current.as_ref().unwrap().temperature, 21.5);
+    assert!(data.timestamps.is_empty());
+    assert!(data.temperatures.is_empty());
+    let caveats: Vec<_> = data
+        .degradations()
+        .into_iter()
+        .filter_map(|degradation| match degradation {
+            Degradation::Caveat(caveat) => Some(caveat),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(caveats.len(), 1, "{caveats:?}");
+    assert!(caveats[0].starts_with("Hourly data is not available ("));
+    assert!(
+        caveats[0].contains("(500 Internal Server Error)"),
+        "{caveats:?}"
+    );
+    assert!(caveats[0].ends_with("showing the current conditions only"));
+
+    let _ = std::fs::remove_dir_all(dir);
+}
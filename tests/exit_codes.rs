@@ -0,0 +1,102 @@
+//! Exit codes and the `--quiet` error lines the scripts tell the failures apart with
+
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+
+/// Empty directory of the test
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("weather-exit-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+/// `weather --quiet` with the config and the cache in the directory, replaying the cassette in it
+/// instead of sending any requests
+fn weather(dir: &Path, interactions: &str) -> Command {
+    let cassette = dir.join("cassette.json");
+    std::fs::write(&cassette, interactions).unwrap();
+
+    let mut command = Command::cargo_bin("weather").unwrap();
+    command
+        .env("WEATHER_NO_UPDATE_CHECK", "1")
+        .env_remove("WEATHER_PROVIDER")
+        .env("WEATHER_CASSETTE", format!("replay:{}", cassette.display()))
+        .arg("--config-dir")
+        .arg(dir.join("config"))
+        .arg("--cache-dir")
+        .arg(dir.join("cache"))
+        .arg("--quiet");
+
+    command
+}
+
+/// The exit code and the stderr of the command
+fn run(command: &mut Command) -> (Option<i32>, String) {
+    let output = command.output().unwrap();
+
+    (
+        output.status.code(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn nonexistent_address() {
+    let dir = test_dir("nonexistent");
+    let nominatim = r#"[{"url": "https://nominatim.openstreetmap.org/search?addressdetails=1&format=jsonv2&q=Nowhereville+Qqq", "status": 200, "headers": {"content-type": "application/json"}, "body": "[]"}]"#;
+
+    let (code, stderr) =
+        run(weather(&dir, nominatim).args(["get", "Nowhereville Qqq", "--lang", "en"]));
+    assert_eq!(code, Some(5));
+    assert_eq!(
+        stderr,
+        "error: geocoding: Could not find location 'Nowhereville Qqq' (0 geocoding results)\n"
+    );
+}
+
+#[test]
+fn unsupported_met_no_history() {
+    let dir = test_dir("history");
+
+    // Coordinates are not looked up, so nothing is requested before the date is refused
+    let (code, stderr) = run(weather(&dir, "[]").env("WEATHER_PROVIDER", "met_no").args([
+        "get",
+        "50.45, 30.52",
+        "2000-01-01",
+        "--lang",
+        "en",
+    ]));
+    assert_eq!(code, Some(2));
+    assert_eq!(
+        stderr,
+        "error: input: Custom dates (including history) are not supported by met_no provider\n"
+    );
+}
+
+#[test]
+fn invalid_config() {
+    let dir = test_dir("config");
+
+    let (code, stderr) = run(weather(&dir, "[]")
+        .env("WEATHER_PROVIDER", "nonexistent")
+        .args(["get", "50.45, 30.52", "--lang", "en"]));
+    assert_eq!(code, Some(6));
+    assert!(
+        stderr.starts_with("error: config: Invalid WEATHER_PROVIDER value"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn missing_response() {
+    let dir = test_dir("missing");
+
+    // Not an error of any of the categories
+    let (code, stderr) = run(weather(&dir, "[]").args(["get", "Nowhereville Qqq", "--lang", "en"]));
+    assert_eq!(code, Some(1));
+    assert!(stderr.starts_with("error: other: "), "{stderr}");
+    assert_eq!(stderr.lines().count(), 1);
+}
@@ -0,0 +1,163 @@
+//! The address is geocoded once per run, however many requests the data takes
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use chrono::{DateTime, Utc};
+use weather::{
+    error::Result,
+    geocode::{Address, Geocoder, ResolvedLocation},
+    paths::Paths,
+    WeatherClient,
+};
+
+/// Finds every address in Kyiv and names every point Kyiv, counting how many times it's asked
+#[derive(Debug, Default, Clone)]
+struct CountingGeocoder {
+    forward: Arc<AtomicUsize>,
+    reverse: Arc<AtomicUsize>,
+}
+
+impl Geocoder for CountingGeocoder {
+    fn forward(&self, address: &str) -> Result<Vec<ResolvedLocation>> {
+        self.forward.fetch_add(1, Ordering::SeqCst);
+
+        Ok(vec![ResolvedLocation {
+            lat: 50.45,
+            lon: 30.52,
+            display_name: address.to_string(),
+            country_code: Some("UA".to_string()),
+            ..ResolvedLocation::default()
+        }])
+    }
+
+    fn reverse(&self, _lat: f64, _lon: f64) -> Result<Option<Address>> {
+        self.reverse.fetch_add(1, Ordering::SeqCst);
+
+        Ok(Some(Address {
+            full: "Kyiv, Ukraine".to_string(),
+            short: None,
+            country_code: Some("UA".to_string()),
+        }))
+    }
+}
+
+/// open_meteo forecast request of the day at the coordinates the geocoder finds
+fn forecast(day: &str) -> String {
+    format!(
+        "https://api.open-meteo.com/v1/forecast?current_weather=true&daily=sunrise%2Csunset&\
+        end_date={day}&hourly=temperature_2m%2Cwindspeed_10m%2Cwinddirection_10m%2C\
+        windgusts_10m%2Cprecipitation%2Crelativehumidity_2m%2Capparent_temperature%2C\
+        weathercode%2Csurface_pressure%2Ccloudcover%2Cprecipitation_probability%2Cuv_index&\
+        latitude=50.45&longitude=30.52&start_date={day}&timezone=auto"
+    )
+}
+
+#[test]
+fn one_geocode_for_every_endpoint() {
+    let dir = std::env::temp_dir().join(format!("weather-geocoding-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let cassette = dir.join("cassette.json");
+    let interaction = |url: &str, body: serde_json::Value| {
+        serde_json::json!({
+            "url": url,
+            "status": 200,
+            "headers": { "content-type": "application/json" },
+            "body": body.to_string(),
+        })
+    };
+    let hourly = |day: &str| {
+        serde_json::json!({
+            "utc_offset_seconds": 0,
+            "timezone": "GMT",
+            "hourly_units": { "temperature_2m": "°C" },
+            "hourly": {
+                "time": [format!("{day}T12:00"), format!("{day}T13:00")],
+                "temperature_2m": [18.5, 19.0],
+            },
+        })
+    };
+    let interactions = [
+        interaction(&forecast("2024-06-14"), hourly("2024-06-14")),
+        interaction(&forecast("2024-06-13"), hourly("2024-06-13")),
+        interaction(
+            "https://air-quality-api.open-meteo.com/v1/air-quality?end_date=2024-06-14&\
+            hourly=european_aqi%2Cpm2_5%2Cpm10&latitude=50.45&longitude=30.52&\
+            start_date=2024-06-14&timezone=auto",
+            serde_json::json!({
+                "utc_offset_seconds": 0,
+                "hourly": {
+                    "time": ["2024-06-14T12:00", "2024-06-14T13:00"],
+                    "european_aqi": [20.0, 25.0],
+                    "pm2_5": [5.0, 6.0],
+                    "pm10": [9.0, 10.0],
+                },
+            }),
+        ),
+        interaction(
+            "https://api.open-meteo.com/v1/forecast?forecast_minutely_15=6&latitude=50.45&\
+            longitude=30.52&minutely_15=precipitation&timezone=auto",
+            serde_json::json!({
+                "utc_offset_seconds": 0,
+                "timezone": "GMT",
+                "minutely_15_units": { "precipitation": "mm" },
+                "minutely_15": {
+                    "time": ["2024-06-14T12:00", "2024-06-14T12:15"],
+                    "precipitation": [0.0, 0.2],
+                },
+            }),
+        ),
+    ];
+    std::fs::write(&cassette, serde_json::json!(interactions).to_string()).unwrap();
+    // Read once, before the first request
+    std::env::set_var("WEATHER_CASSETTE", format!("replay:{}", cassette.display()));
+
+    let geocoder = CountingGeocoder::default();
+    let client = WeatherClient::builder()
+        .paths(Paths {
+            config_dir: dir.join("config"),
+            cache_dir: dir.join("cache"),
+            state_dir: dir.join("state"),
+        })
+        .now(
+            DateTime::parse_from_rfc3339("2024-06-14T12:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        )
+        .geocoder(geocoder.clone())
+        // The three requests after the forecast
+        .air_quality(true)
+        .nowcast(true)
+        .compare_yesterday(true)
+        .build()
+        .unwrap();
+
+    // All of them got their data, for the place found once
+    let data = client.get("Kyiv", "now").unwrap();
+    assert!(data.degradations().is_empty(), "{:?}", data.degradations());
+    assert!(data.air_quality.is_some());
+    assert!(data.nowcast.is_some());
+    let yesterday = data.comparison.as_ref().unwrap();
+    assert_eq!(yesterday.requested_date, "2024-06-13");
+    for data in [&data, yesterday] {
+        assert_eq!((data.location.lat, data.location.lon), (50.45, 30.52));
+        assert_eq!(data.location.country_code.as_deref(), Some("UA"));
+    }
+    assert_eq!(geocoder.forward.load(Ordering::SeqCst), 1);
+    assert_eq!(geocoder.reverse.load(Ordering::SeqCst), 0);
+
+    // The coordinates are named once too
+    let data = client.get("50.45, 30.52", "now").unwrap();
+    assert_eq!(data.location.display_name, "Kyiv, Ukraine");
+    assert_eq!(
+        data.comparison.unwrap().location.display_name,
+        "Kyiv, Ukraine"
+    );
+    assert_eq!(geocoder.forward.load(Ordering::SeqCst), 1);
+    assert_eq!(geocoder.reverse.load(Ordering::SeqCst), 1);
+
+    let _ = std::fs::remove_dir_all(dir);
+}
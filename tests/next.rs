@@ -0,0 +1,111 @@
+//! `weather get --next` against replayed responses, for what the scripts see: the output and the
+//! exit code
+
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+
+/// Replayed open_meteo response for the next 2 days, received on 2024-06-14 at 06:30 (so 42 of the
+/// hours are ahead), with the rain at 15:00-16:00 on the first day if `rain`
+fn dump(name: &str, rain: bool) -> PathBuf {
+    let hours = (0..48)
+        .map(|hour| format!("\"2024-06-{}T{:02}:00\"", 14 + hour / 24, hour % 24))
+        .collect::<Vec<_>>();
+    let precipitation = (0..48)
+        .map(|hour| match rain && (15..17).contains(&hour) {
+            true => "0.6",
+            false => "0.0",
+        })
+        .collect::<Vec<_>>();
+    let body = format!(
+        r#"{{"utc_offset_seconds":0,"timezone":"GMT","current_weather":{{"time":"2024-06-14T06:30","temperature":18.0,"weathercode":1,"windspeed":5.0,"winddirection":180.0}},"hourly_units":{{"temperature_2m":"°C","precipitation":"mm"}},"hourly":{{"time":[{}],"temperature_2m":[{}],"precipitation":[{}]}}}}"#,
+        hours.join(","),
+        vec!["18.0"; 48].join(","),
+        precipitation.join(",")
+    );
+
+    let path =
+        std::env::temp_dir().join(format!("weather-next-{name}-{}.dump", std::process::id()));
+    std::fs::write(
+        &path,
+        format!(
+            "# provider: open_meteo\n\
+            # url: https://api.open-meteo.com/v1/forecast?latitude=52.22&longitude=6.89\n\
+            # time: 2024-06-14T06:30:00Z\n\
+            # location: Garden\n\
+            # lat: 52.22\n\
+            # lon: 6.89\n\
+            # date: next 2 days\n\
+            # daily: false\n\
+            {body}"
+        ),
+    )
+    .unwrap();
+
+    path
+}
+
+/// `weather get --next rain` for the dump, with the config and the cache of its own
+fn next_rain(dump: &PathBuf, args: &[&str]) -> Command {
+    let dir = dump.with_extension("d");
+    let mut command = Command::cargo_bin("weather").unwrap();
+    command
+        .env("WEATHER_NO_UPDATE_CHECK", "1")
+        .arg("--config-dir")
+        .arg(dir.join("config"))
+        .arg("--cache-dir")
+        .arg(dir.join("cache"))
+        .args(["get", "--from-dump"])
+        .arg(dump)
+        .args(["--next", "rain", "--lang", "en"])
+        .args(args);
+
+    command
+}
+
+#[test]
+fn rain_found() {
+    let dump = dump("found", true);
+
+    next_rain(&dump, &["--24h"])
+        .assert()
+        .success()
+        .stdout("rain at Fri 15:00 (2 h)\n");
+    next_rain(&dump, &["--no-match-output", "json-null"])
+        .assert()
+        .success()
+        .stdout(
+            "{\"match\":{\"condition\":\"rain\",\"end\":\"2024-06-14T17:00:00+00:00\",\"hours\":2,\
+            \"start\":\"2024-06-14T15:00:00+00:00\"}}\n",
+        );
+}
+
+#[test]
+fn no_rain() {
+    let dump = dump("dry", false);
+
+    // (args, exit code, output)
+    let cases: [(&[&str], i32, &str); 5] = [
+        (&[], 9, "no rain in the 42 hours ahead\n"),
+        (
+            &["--no-match-output", "message"],
+            9,
+            "no rain in the 42 hours ahead\n",
+        ),
+        (&["--no-match-output", "empty"], 9, ""),
+        (&["--no-match-output", "json-null"], 9, "{\"match\":null}\n"),
+        (
+            &["--no-match-exit", "0", "--no-match-output", "empty"],
+            0,
+            "",
+        ),
+    ];
+
+    for (args, code, output) in cases {
+        next_rain(&dump, args)
+            .assert()
+            .code(code)
+            .stdout(output)
+            .stderr("");
+    }
+}